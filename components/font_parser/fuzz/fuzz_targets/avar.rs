@@ -0,0 +1,18 @@
+//! The first two bytes select the fvar axis count the table is checked against
+
+#![no_main]
+
+use font_parser::AvarTable;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((axis_count, table)) = data.split_first_chunk::<2>() else {
+        return;
+    };
+    let axis_count = u16::from_be_bytes(*axis_count) as usize;
+    if let Ok(avar) = AvarTable::parse(table, axis_count) {
+        for axis in 0..axis_count.min(4) {
+            let _ = avar.map_value(axis, 0.5);
+        }
+    }
+});
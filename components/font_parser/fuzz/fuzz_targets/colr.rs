@@ -0,0 +1,8 @@
+#![no_main]
+
+use font_parser::ColrTable;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ColrTable::parse(data);
+});
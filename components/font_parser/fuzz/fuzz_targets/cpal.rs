@@ -0,0 +1,8 @@
+#![no_main]
+
+use font_parser::CpalTable;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = CpalTable::parse(data);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use font_parser::FvarTable;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(fvar) = FvarTable::parse(data) {
+        let _ = fvar.get_axis(font_parser::Tag::WEIGHT);
+    }
+});
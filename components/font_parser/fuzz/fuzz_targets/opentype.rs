@@ -0,0 +1,46 @@
+//! Parse an sfnt and exercise every table accessor
+
+#![no_main]
+
+use font_parser::{OpenTypeFont, VariationCoordinates};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = font_parser::validate(data);
+
+    let Ok(font) = OpenTypeFont::parse(data.to_vec()) else {
+        return;
+    };
+    let _ = font.get_metrics();
+    let _ = font.get_cmap().map(|cmap| cmap.get_glyph('A'));
+    let _ = font.get_fvar();
+    let _ = font.get_avar();
+    let _ = font.get_named_instances();
+    let _ = font.named_instances_resolved();
+    let _ = font.get_stat();
+    let _ = font.get_gvar();
+
+    let mut coords = VariationCoordinates::new();
+    for axis in font.get_variation_axes() {
+        coords.set_axis(axis.tag, axis.max_value);
+    }
+    let _ = font.get_metrics_at(&coords);
+    for glyph_id in 0..4 {
+        let _ = font.get_glyph_outline(glyph_id);
+        let _ = font.get_glyph_outline_at(glyph_id, &coords);
+        let _ = font.get_advance_at(glyph_id, &coords);
+    }
+    let _ = font.get_post();
+    let _ = font.glyph_name(3);
+    let _ = font.glyph_by_name("A");
+    let _ = font.get_gsub();
+    let _ = font.get_gpos();
+    let _ = font.get_base();
+    let _ = font.get_color_formats();
+    let _ = font.get_cpal();
+    let _ = font.get_colr();
+    let _ = font.get_color_layers(1);
+    let _ = font.get_cbdt();
+    let _ = font.get_svg();
+    let _ = font.verify_checksums();
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use font_parser::WoffFont;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(woff) = WoffFont::parse(data) {
+        let _ = font_parser::validate(&woff.ttf_data);
+    }
+});
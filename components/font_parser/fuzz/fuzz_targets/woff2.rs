@@ -0,0 +1,10 @@
+#![no_main]
+
+use font_parser::Woff2Font;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(woff2) = Woff2Font::parse(data) {
+        let _ = font_parser::validate(&woff2.ttf_data);
+    }
+});
@@ -0,0 +1,235 @@
+//! Baseline table (BASE)
+//!
+//! BASE gives, per script, the position of each baseline (alphabetic
+//! `romn`, hanging `hang`, ideographic `ideo`/`idtp`, ...) in font units
+//! above the glyph origin. Layout uses it to line up runs of different
+//! scripts and fonts, e.g. Latin set in a CJK font next to Han text. The
+//! min/max extents of the table are not parsed.
+
+use crate::limits;
+use crate::types::Tag;
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Cursor;
+
+/// Script tag of the record used when a script has none of its own
+const DEFAULT_SCRIPT: &str = "DFLT";
+
+/// Baseline positions of one script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseScript {
+    /// Script tag (e.g. `latn`, `hani`)
+    pub tag: Tag,
+    /// Baseline the script's glyphs are aligned by, if given
+    pub default_baseline: Option<Tag>,
+    /// Position of each baseline in font units, by baseline tag
+    pub coordinates: Vec<(Tag, i16)>,
+}
+
+impl BaseScript {
+    /// Position of `baseline` in font units, if the script defines it
+    pub fn coordinate(&self, baseline: Tag) -> Option<i16> {
+        self.coordinates
+            .iter()
+            .find(|(tag, _)| *tag == baseline)
+            .map(|&(_, coordinate)| coordinate)
+    }
+}
+
+/// Baselines of one layout direction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseAxis {
+    /// Baselines with a position in every script record
+    pub baseline_tags: Vec<Tag>,
+    /// Per-script baseline positions
+    pub scripts: Vec<BaseScript>,
+}
+
+impl BaseAxis {
+    /// Baseline positions for `script`, falling back to the `DFLT` record
+    pub fn script(&self, script: Tag) -> Option<&BaseScript> {
+        let default_script = Tag::new(DEFAULT_SCRIPT).expect("valid tag");
+        let find = |tag| self.scripts.iter().find(|record| record.tag == tag);
+        find(script).or_else(|| find(default_script))
+    }
+
+    /// Position of `baseline` for `script` in font units
+    pub fn coordinate(&self, script: Tag, baseline: Tag) -> Option<i16> {
+        self.script(script)?.coordinate(baseline)
+    }
+}
+
+/// Baseline table (BASE)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseTable {
+    /// Baselines for horizontal text
+    pub horizontal: Option<BaseAxis>,
+    /// Baselines for vertical text
+    pub vertical: Option<BaseAxis>,
+}
+
+impl BaseTable {
+    /// Parse a BASE table from raw data
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the table is truncated or has an unsupported
+    /// major version.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let major_version = cursor.read_u16::<BigEndian>()?;
+        let _minor_version = cursor.read_u16::<BigEndian>()?;
+        if major_version != 1 {
+            return Err(ParseError::UnsupportedVersion);
+        }
+        let horizontal_offset = cursor.read_u16::<BigEndian>()? as u64;
+        let vertical_offset = cursor.read_u16::<BigEndian>()? as u64;
+
+        // As in GSUB/GPOS, script records may share their baseline values;
+        // each decoded coordinate is charged against the table's length so
+        // aliasing can't multiply a small table into a huge one
+        let mut budget = data.len();
+        Ok(BaseTable {
+            horizontal: Self::parse_axis(data, horizontal_offset, &mut budget)?,
+            vertical: Self::parse_axis(data, vertical_offset, &mut budget)?,
+        })
+    }
+
+    fn parse_axis(
+        data: &[u8],
+        offset: u64,
+        budget: &mut usize,
+    ) -> Result<Option<BaseAxis>, ParseError> {
+        if offset == 0 {
+            return Ok(None);
+        }
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(offset);
+        let tag_list_offset = cursor.read_u16::<BigEndian>()? as u64;
+        let script_list_offset = cursor.read_u16::<BigEndian>()? as u64;
+
+        let baseline_tags = if tag_list_offset == 0 {
+            Vec::new()
+        } else {
+            cursor.set_position(offset + tag_list_offset);
+            let count = cursor.read_u16::<BigEndian>()?;
+            limits::check_declared(
+                "base tag list",
+                count as usize,
+                4,
+                limits::remaining(data, cursor.position()),
+            )?;
+            (0..count)
+                .map(|_| cursor.read_u32::<BigEndian>().map(Tag::from_bytes))
+                .collect::<Result<_, _>>()?
+        };
+        let scripts = if script_list_offset == 0 {
+            Vec::new()
+        } else {
+            Self::parse_script_list(data, offset + script_list_offset, &baseline_tags, budget)?
+        };
+
+        Ok(Some(BaseAxis {
+            baseline_tags,
+            scripts,
+        }))
+    }
+
+    fn parse_script_list(
+        data: &[u8],
+        offset: u64,
+        baseline_tags: &[Tag],
+        budget: &mut usize,
+    ) -> Result<Vec<BaseScript>, ParseError> {
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(offset);
+        let count = cursor.read_u16::<BigEndian>()?;
+        limits::check_declared(
+            "base script list",
+            count as usize,
+            6,
+            limits::remaining(data, cursor.position()),
+        )?;
+        let mut records = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let tag = Tag::from_bytes(cursor.read_u32::<BigEndian>()?);
+            let script_offset = cursor.read_u16::<BigEndian>()? as u64;
+            records.push((tag, offset + script_offset));
+        }
+
+        records
+            .into_iter()
+            .map(|(tag, script_offset)| {
+                cursor.set_position(script_offset);
+                let values_offset = cursor.read_u16::<BigEndian>()? as u64;
+                if values_offset == 0 {
+                    return Ok(BaseScript {
+                        tag,
+                        default_baseline: None,
+                        coordinates: Vec::new(),
+                    });
+                }
+
+                let values_offset = script_offset + values_offset;
+                cursor.set_position(values_offset);
+                let default_index = cursor.read_u16::<BigEndian>()?;
+                let coordinate_count = cursor.read_u16::<BigEndian>()?;
+                *budget = budget
+                    .checked_sub(1 + coordinate_count as usize)
+                    .ok_or_else(|| {
+                        ParseError::CorruptedData(
+                            "base script offsets alias excessively".to_string(),
+                        )
+                    })?;
+                limits::check_declared(
+                    "base coordinates",
+                    coordinate_count as usize,
+                    2,
+                    limits::remaining(data, cursor.position()),
+                )?;
+                let coordinate_offsets = (0..coordinate_count)
+                    .map(|_| cursor.read_u16::<BigEndian>().map(u64::from))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // Every BaseCoord format starts with the format and the
+                // coordinate; glyph points and device tables only refine it
+                let coordinates = baseline_tags
+                    .iter()
+                    .zip(coordinate_offsets)
+                    .map(|(&baseline, coordinate_offset)| {
+                        cursor.set_position(values_offset + coordinate_offset);
+                        let _format = cursor.read_u16::<BigEndian>()?;
+                        Ok((baseline, cursor.read_i16::<BigEndian>()?))
+                    })
+                    .collect::<Result<_, ParseError>>()?;
+
+                Ok(BaseScript {
+                    tag,
+                    default_baseline: baseline_tags.get(default_index as usize).copied(),
+                    coordinates,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_unknown_major_version() {
+        let data = [0, 2, 0, 0, 0, 0, 0, 0];
+        assert!(matches!(
+            BaseTable::parse(&data),
+            Err(ParseError::UnsupportedVersion)
+        ));
+    }
+
+    #[test]
+    fn test_axes_are_optional() {
+        let table = BaseTable::parse(&[0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert!(table.horizontal.is_none());
+        assert!(table.vertical.is_none());
+    }
+}
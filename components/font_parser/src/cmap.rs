@@ -0,0 +1,168 @@
+//! Character to glyph mapping table (cmap) parsing
+//!
+//! Only Unicode subtables are read: format 12 (full Unicode range) when the
+//! font has one, otherwise format 4 (Basic Multilingual Plane). Symbol and
+//! legacy Macintosh encodings map no characters.
+
+use crate::limits;
+use crate::types::GlyphId;
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// Character mapping table
+#[derive(Debug, Clone, Default)]
+pub struct CMapTable {
+    /// Format 4 mappings of BMP code points
+    mappings: HashMap<u32, GlyphId>,
+    /// Format 12 groups as (first code point, last code point, first glyph),
+    /// sorted by code point
+    groups: Vec<(u32, u32, u32)>,
+}
+
+impl CMapTable {
+    /// Parse a cmap table from raw data
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the selected subtable is truncated, or
+    /// `InvalidFormat` if the table has no Unicode subtable in format 4 or 12.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let _version = cursor.read_u16::<BigEndian>()?;
+        let count = cursor.read_u16::<BigEndian>()?;
+        limits::check_declared(
+            "cmap encoding records",
+            count as usize,
+            8,
+            limits::remaining(data, cursor.position()),
+        )?;
+
+        // Prefer full-range subtables to BMP-only ones
+        let mut best: Option<(u8, u64)> = None;
+        for _ in 0..count {
+            let platform = cursor.read_u16::<BigEndian>()?;
+            let encoding = cursor.read_u16::<BigEndian>()?;
+            let offset = cursor.read_u32::<BigEndian>()? as u64;
+            let format = data
+                .get(offset as usize..offset as usize + 2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]));
+            let unicode = platform == 0 || (platform == 3 && matches!(encoding, 1 | 10));
+            let rank = match format {
+                Some(12) if unicode => 0,
+                Some(4) if unicode => 1,
+                _ => continue,
+            };
+            if best.is_none_or(|(best_rank, _)| rank < best_rank) {
+                best = Some((rank, offset));
+            }
+        }
+
+        match best {
+            Some((0, offset)) => Ok(CMapTable {
+                mappings: HashMap::new(),
+                groups: Self::parse_format12(data, offset)?,
+            }),
+            Some((_, offset)) => Ok(CMapTable {
+                mappings: Self::parse_format4(data, offset)?,
+                groups: Vec::new(),
+            }),
+            None => Err(ParseError::InvalidFormat),
+        }
+    }
+
+    fn parse_format4(data: &[u8], offset: u64) -> Result<HashMap<u32, GlyphId>, ParseError> {
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(offset + 6);
+        let seg_count = cursor.read_u16::<BigEndian>()? as u64 / 2;
+        limits::check_declared(
+            "cmap format 4 segments",
+            seg_count as usize,
+            8,
+            limits::remaining(data, offset + 14),
+        )?;
+
+        let end_codes = offset + 14;
+        let start_codes = end_codes + seg_count * 2 + 2; // after reservedPad
+        let id_deltas = start_codes + seg_count * 2;
+        let id_range_offsets = id_deltas + seg_count * 2;
+        let read_u16 = |position: u64| -> Result<u16, ParseError> {
+            let mut cursor = Cursor::new(data);
+            cursor.set_position(position);
+            Ok(cursor.read_u16::<BigEndian>()?)
+        };
+
+        let mut mappings = HashMap::new();
+        for segment in 0..seg_count {
+            let end = read_u16(end_codes + segment * 2)?;
+            let start = read_u16(start_codes + segment * 2)?;
+            let delta = read_u16(id_deltas + segment * 2)?;
+            let range_offset_position = id_range_offsets + segment * 2;
+            let range_offset = read_u16(range_offset_position)?;
+            if start > end {
+                continue;
+            }
+
+            for code in start..=end {
+                // The final segment only maps the 0xFFFF sentinel
+                if code == 0xFFFF {
+                    break;
+                }
+                let glyph = if range_offset == 0 {
+                    code.wrapping_add(delta)
+                } else {
+                    let position =
+                        range_offset_position + range_offset as u64 + (code - start) as u64 * 2;
+                    match read_u16(position) {
+                        Ok(0) | Err(_) => 0,
+                        Ok(glyph) => glyph.wrapping_add(delta),
+                    }
+                };
+                if glyph != 0 {
+                    mappings.insert(code as u32, glyph);
+                }
+            }
+        }
+        Ok(mappings)
+    }
+
+    fn parse_format12(data: &[u8], offset: u64) -> Result<Vec<(u32, u32, u32)>, ParseError> {
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(offset + 12);
+        let count = cursor.read_u32::<BigEndian>()?;
+        limits::check_declared(
+            "cmap format 12 groups",
+            count as usize,
+            12,
+            limits::remaining(data, cursor.position()),
+        )?;
+
+        let mut groups = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let start = cursor.read_u32::<BigEndian>()?;
+            let end = cursor.read_u32::<BigEndian>()?;
+            let glyph = cursor.read_u32::<BigEndian>()?;
+            if start <= end {
+                groups.push((start, end, glyph));
+            }
+        }
+        groups.sort_unstable_by_key(|&(start, _, _)| start);
+        Ok(groups)
+    }
+
+    /// Get glyph ID for a character
+    pub fn get_glyph(&self, codepoint: char) -> Option<GlyphId> {
+        let code = codepoint as u32;
+        if let Some(&glyph) = self.mappings.get(&code) {
+            return Some(glyph);
+        }
+        let index = self.groups.partition_point(|&(start, _, _)| start <= code);
+        let &(start, end, first_glyph) = self.groups.get(index.checked_sub(1)?)?;
+        if code > end {
+            return None;
+        }
+        let glyph = first_glyph.checked_add(code - start)?;
+        GlyphId::try_from(glyph).ok().filter(|&glyph| glyph != 0)
+    }
+}
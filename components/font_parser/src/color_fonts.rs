@@ -3,10 +3,15 @@
 //! This module provides parsing for color font tables in OpenType fonts,
 //! including emoji and multi-color glyph support.
 
+use crate::colr_v1::{Paint, PaintGraph};
+use crate::limits::{self, MAX_COLOR_LAYERS, MAX_PALETTE_COLORS, MAX_SVG_DOCUMENT_SIZE};
 use crate::types::GlyphId;
 use crate::ParseError;
 use byteorder::{BigEndian, ReadBytesExt};
-use std::io::Cursor;
+use flate2::read::GzDecoder;
+use font_types::{BasePalette, FontPalette};
+use std::borrow::Cow;
+use std::io::{Cursor, Read};
 
 /// RGBA color definition
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -41,6 +46,12 @@ impl Color {
     }
 }
 
+/// CPAL palette type flag: the palette suits a light background
+pub const PALETTE_USABLE_WITH_LIGHT_BACKGROUND: u32 = 0x0001;
+
+/// CPAL palette type flag: the palette suits a dark background
+pub const PALETTE_USABLE_WITH_DARK_BACKGROUND: u32 = 0x0002;
+
 /// CPAL - Color Palette Table
 ///
 /// Defines color palettes used by COLR table for layered color glyphs.
@@ -48,9 +59,10 @@ impl Color {
 pub struct CpalTable {
     /// Color palettes (each palette is a list of colors)
     pub palettes: Vec<Vec<Color>>,
-    /// Optional palette labels (name table IDs)
+    /// Palette labels (name table IDs), from version 1 tables
     pub palette_labels: Vec<Option<u16>>,
-    /// Optional palette type flags
+    /// Palette type flags (`PALETTE_USABLE_WITH_*`), zero unless given by a
+    /// version 1 table
     pub palette_types: Vec<u32>,
 }
 
@@ -66,15 +78,35 @@ impl CpalTable {
         let num_color_records = cursor.read_u16::<BigEndian>()?;
         let color_records_array_offset = cursor.read_u32::<BigEndian>()?;
 
+        // Palettes may share color records, so the decoded size is bounded
+        // separately from the table size
+        limits::check_cap(
+            "CPAL palette color",
+            num_palettes as usize * num_palette_entries as usize,
+            MAX_PALETTE_COLORS,
+        )?;
+        limits::check_declared(
+            "CPAL palette indices",
+            num_palettes as usize,
+            2,
+            limits::remaining(data, cursor.position()),
+        )?;
+        limits::check_declared(
+            "CPAL color records",
+            num_color_records as usize,
+            4,
+            limits::remaining(data, color_records_array_offset as u64),
+        )?;
+
         // Read color record indices for each palette
-        let mut color_record_indices = Vec::new();
+        let mut color_record_indices = Vec::with_capacity(num_palettes as usize);
         for _ in 0..num_palettes {
             color_record_indices.push(cursor.read_u16::<BigEndian>()?);
         }
 
         // Read color records
         cursor.set_position(color_records_array_offset as u64);
-        let mut color_records = Vec::new();
+        let mut color_records = Vec::with_capacity(num_color_records as usize);
         for _ in 0..num_color_records {
             let blue = cursor.read_u8()?;
             let green = cursor.read_u8()?;
@@ -89,7 +121,7 @@ impl CpalTable {
         }
 
         // Build palettes from color records
-        let mut palettes = Vec::new();
+        let mut palettes = Vec::with_capacity(num_palettes as usize);
         for &start_color_index in &color_record_indices {
             let start_index = start_color_index as usize;
             let end_index = start_index + num_palette_entries as usize;
@@ -102,21 +134,27 @@ impl CpalTable {
             palettes.push(palette);
         }
 
-        // Version 1 has additional data (labels, types)
-        let (palette_labels, palette_types) = if version >= 1 {
-            // For version 1, there are optional palette labels and types
-            // For simplicity, we'll initialize with defaults
-            // Full implementation would parse these from the extended data
-            (
-                vec![None; num_palettes as usize],
-                vec![0; num_palettes as usize],
-            )
-        } else {
-            (
-                vec![None; num_palettes as usize],
-                vec![0; num_palettes as usize],
-            )
-        };
+        // Version 1 adds optional per-palette type flags and name IDs after
+        // the color record indices; a zero offset means the array is absent
+        let mut palette_types = vec![0; num_palettes as usize];
+        let mut palette_labels = vec![None; num_palettes as usize];
+        if version >= 1 {
+            cursor.set_position(12 + 2 * num_palettes as u64);
+            let palette_types_offset = cursor.read_u32::<BigEndian>()?;
+            let palette_labels_offset = cursor.read_u32::<BigEndian>()?;
+            if palette_types_offset != 0 {
+                cursor.set_position(palette_types_offset as u64);
+                for palette_type in &mut palette_types {
+                    *palette_type = cursor.read_u32::<BigEndian>()?;
+                }
+            }
+            if palette_labels_offset != 0 {
+                cursor.set_position(palette_labels_offset as u64);
+                for label in &mut palette_labels {
+                    *label = Some(cursor.read_u16::<BigEndian>()?).filter(|&id| id != 0xFFFF);
+                }
+            }
+        }
 
         Ok(CpalTable {
             palettes,
@@ -139,15 +177,56 @@ impl CpalTable {
     pub fn palette_count(&self) -> usize {
         self.palettes.len()
     }
+
+    /// Index of the palette `base` selects
+    ///
+    /// `Light` and `Dark` pick the first palette with the matching
+    /// [`PALETTE_USABLE_WITH_LIGHT_BACKGROUND`] or
+    /// [`PALETTE_USABLE_WITH_DARK_BACKGROUND`] flag. Like an out-of-range
+    /// index, a missing flag falls back to the default palette.
+    pub fn select_palette(&self, base: BasePalette) -> usize {
+        let flagged = |flag: u32| {
+            self.palette_types
+                .iter()
+                .position(|&palette_type| palette_type & flag != 0)
+        };
+        let index = match base {
+            BasePalette::Normal => None,
+            BasePalette::Light => flagged(PALETTE_USABLE_WITH_LIGHT_BACKGROUND),
+            BasePalette::Dark => flagged(PALETTE_USABLE_WITH_DARK_BACKGROUND),
+            BasePalette::Index(index) => Some(index as usize),
+        };
+        index
+            .filter(|&index| index < self.palettes.len())
+            .unwrap_or(0)
+    }
+
+    /// Colors of the selected palette with the overrides applied
+    ///
+    /// Overrides of entries beyond the palette are ignored. Returns `None`
+    /// if the table has no palettes.
+    pub fn resolve_palette(&self, palette: &FontPalette) -> Option<Vec<Color>> {
+        let mut colors = self.get_palette(self.select_palette(palette.base))?.clone();
+        for entry in &palette.overrides {
+            if let Some(color) = colors.get_mut(entry.index as usize) {
+                let [red, green, blue, alpha] = entry.color;
+                *color = Color::from_rgba(red, green, blue, alpha);
+            }
+        }
+        Some(colors)
+    }
 }
 
 /// COLR - Color Layer Table
 ///
-/// Defines layered color glyphs using palette colors from CPAL.
+/// Defines layered color glyphs using palette colors from CPAL. Version 1
+/// tables add paint graphs, read through [`ColrTable::paint`].
 #[derive(Debug, Clone)]
 pub struct ColrTable {
     /// Base glyphs with their color layers
     pub base_glyphs: Vec<BaseGlyph>,
+    /// Version 1 paint graphs
+    paints: Option<PaintGraph>,
 }
 
 /// A base glyph with color layers
@@ -174,15 +253,34 @@ impl ColrTable {
         let mut cursor = Cursor::new(data);
 
         // Read COLR header
-        let _version = cursor.read_u16::<BigEndian>()?;
+        let version = cursor.read_u16::<BigEndian>()?;
         let num_base_glyph_records = cursor.read_u16::<BigEndian>()?;
         let base_glyph_records_offset = cursor.read_u32::<BigEndian>()?;
         let layer_records_offset = cursor.read_u32::<BigEndian>()?;
         let num_layer_records = cursor.read_u16::<BigEndian>()?;
 
+        let paints = if version >= 1 {
+            Some(PaintGraph::parse(data, &mut cursor)?)
+        } else {
+            None
+        };
+
+        limits::check_declared(
+            "COLR base glyph records",
+            num_base_glyph_records as usize,
+            6,
+            limits::remaining(data, base_glyph_records_offset as u64),
+        )?;
+        limits::check_declared(
+            "COLR layer records",
+            num_layer_records as usize,
+            4,
+            limits::remaining(data, layer_records_offset as u64),
+        )?;
+
         // Read base glyph records
         cursor.set_position(base_glyph_records_offset as u64);
-        let mut base_glyph_records = Vec::new();
+        let mut base_glyph_records = Vec::with_capacity(num_base_glyph_records as usize);
         for _ in 0..num_base_glyph_records {
             let glyph_id = cursor.read_u16::<BigEndian>()?;
             let first_layer_index = cursor.read_u16::<BigEndian>()?;
@@ -192,7 +290,7 @@ impl ColrTable {
 
         // Read layer records
         cursor.set_position(layer_records_offset as u64);
-        let mut layer_records = Vec::new();
+        let mut layer_records = Vec::with_capacity(num_layer_records as usize);
         for _ in 0..num_layer_records {
             let glyph_id = cursor.read_u16::<BigEndian>()?;
             let palette_index = cursor.read_u16::<BigEndian>()?;
@@ -202,8 +300,15 @@ impl ColrTable {
             });
         }
 
+        // Base glyphs may share layer ranges, so bound the decoded total
+        let total_layers: usize = base_glyph_records
+            .iter()
+            .map(|&(_, _, num_layers)| num_layers as usize)
+            .sum();
+        limits::check_cap("COLR layer", total_layers, MAX_COLOR_LAYERS)?;
+
         // Build base glyphs with their layers
-        let mut base_glyphs = Vec::new();
+        let mut base_glyphs = Vec::with_capacity(base_glyph_records.len());
         for (glyph_id, first_layer_index, num_layers) in base_glyph_records {
             let start = first_layer_index as usize;
             let end = start + num_layers as usize;
@@ -216,7 +321,10 @@ impl ColrTable {
             base_glyphs.push(BaseGlyph { glyph_id, layers });
         }
 
-        Ok(ColrTable { base_glyphs })
+        Ok(ColrTable {
+            base_glyphs,
+            paints,
+        })
     }
 
     /// Get layers for a specific glyph
@@ -227,14 +335,34 @@ impl ColrTable {
             .map(|bg| &bg.layers)
     }
 
-    /// Check if a glyph has color layers
+    /// Check if a glyph has color layers or a paint graph
     pub fn is_color_glyph(&self, glyph_id: GlyphId) -> bool {
-        self.base_glyphs.iter().any(|bg| bg.glyph_id == glyph_id)
+        self.base_glyphs.iter().any(|bg| bg.glyph_id == glyph_id) || self.has_paint(glyph_id)
     }
 
-    /// Get number of base color glyphs
+    /// Get number of base color glyphs, with layers or a paint graph
     pub fn color_glyph_count(&self) -> usize {
-        self.base_glyphs.len()
+        let painted = self.paints.as_ref().map_or(0, |paints| paints.len());
+        self.base_glyphs.len() + painted
+    }
+
+    /// Check if a glyph has a version 1 paint graph
+    pub fn has_paint(&self, glyph_id: GlyphId) -> bool {
+        self.paints
+            .as_ref()
+            .is_some_and(|paints| paints.contains(glyph_id))
+    }
+
+    /// Resolve the version 1 paint graph of a glyph
+    ///
+    /// Returns `Ok(None)` if the glyph has no paint graph; glyphs may still
+    /// have version 0 layers. Paints are read on demand, so errors in one
+    /// glyph's graph do not affect others.
+    pub fn paint(&self, glyph_id: GlyphId) -> Result<Option<Paint>, ParseError> {
+        match &self.paints {
+            Some(paints) => paints.paint(glyph_id),
+            None => Ok(None),
+        }
     }
 }
 
@@ -275,31 +403,124 @@ impl CbdtTable {
 
 /// SVG - SVG Table
 ///
-/// Provides SVG glyph definitions.
-/// This is a simplified implementation - full SVG parsing is complex.
+/// Maps ranges of glyph IDs to SVG documents. Each glyph is drawn by the
+/// element with ID `glyph<id>` in its document, in font units with y down.
 #[derive(Debug, Clone)]
 pub struct SvgTable {
     /// Table version
     pub version: u16,
-    // Note: Full implementation would include SVG document storage
+    /// Document records, sorted by glyph range
+    pub documents: Vec<SvgDocumentRecord>,
+    /// SVG document list, which document offsets count from
+    document_list: Vec<u8>,
+}
+
+/// Glyph range of an SVG document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvgDocumentRecord {
+    /// First glyph drawn by the document
+    pub start_glyph_id: GlyphId,
+    /// Last glyph drawn by the document
+    pub end_glyph_id: GlyphId,
+    /// Offset of the document from the start of the document list
+    pub offset: u32,
+    /// Length of the document in bytes, possibly gzip-compressed
+    pub length: u32,
 }
 
+/// Magic bytes of a gzip member
+const GZIP_MAGIC: [u8; 3] = [0x1F, 0x8B, 0x08];
+
 impl SvgTable {
-    /// Parse SVG table from raw bytes (simplified)
+    /// Parse SVG table from raw bytes
     pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
         let mut cursor = Cursor::new(data);
 
         let version = cursor.read_u16::<BigEndian>()?;
-        let _svg_document_list_offset = cursor.read_u32::<BigEndian>()?;
+        let document_list_offset = cursor.read_u32::<BigEndian>()?;
         let _reserved = cursor.read_u32::<BigEndian>()?;
 
-        // TODO: Full SVG parsing would include:
-        // - SVG document list
-        // - SVG document records
-        // - Actual SVG data extraction
-        // This is left as a future enhancement
+        let document_list = data.get(document_list_offset as usize..).ok_or_else(|| {
+            ParseError::CorruptedData("SVG document list out of range".to_string())
+        })?;
+        let mut cursor = Cursor::new(document_list);
+        let num_entries = cursor.read_u16::<BigEndian>()? as usize;
+        limits::check_declared(
+            "SVG document records",
+            num_entries,
+            12,
+            limits::remaining(document_list, cursor.position()),
+        )?;
+
+        let mut documents = Vec::with_capacity(num_entries);
+        for _ in 0..num_entries {
+            let record = SvgDocumentRecord {
+                start_glyph_id: cursor.read_u16::<BigEndian>()?,
+                end_glyph_id: cursor.read_u16::<BigEndian>()?,
+                offset: cursor.read_u32::<BigEndian>()?,
+                length: cursor.read_u32::<BigEndian>()?,
+            };
+            let end = record.offset as u64 + record.length as u64;
+            if record.start_glyph_id > record.end_glyph_id || end > document_list.len() as u64 {
+                return Err(ParseError::CorruptedData(
+                    "SVG document record out of range".to_string(),
+                ));
+            }
+            documents.push(record);
+        }
+        documents.sort_by_key(|record| record.start_glyph_id);
+
+        Ok(SvgTable {
+            version,
+            documents,
+            document_list: document_list.to_vec(),
+        })
+    }
+
+    /// Get the record of the document that draws a glyph
+    pub fn document_record(&self, glyph_id: GlyphId) -> Option<&SvgDocumentRecord> {
+        let index = self
+            .documents
+            .partition_point(|record| record.end_glyph_id < glyph_id);
+        self.documents
+            .get(index)
+            .filter(|record| record.start_glyph_id <= glyph_id)
+    }
+
+    /// Check if a glyph has an SVG document
+    pub fn is_svg_glyph(&self, glyph_id: GlyphId) -> bool {
+        self.document_record(glyph_id).is_some()
+    }
+
+    /// Get the SVG document that draws a glyph, decompressed if gzipped
+    ///
+    /// Returns `Ok(None)` if no document covers the glyph. The document may
+    /// draw other glyphs too; the glyph's element has ID `glyph<id>`.
+    /// Decompressed documents are capped at [`MAX_SVG_DOCUMENT_SIZE`].
+    ///
+    /// [`MAX_SVG_DOCUMENT_SIZE`]: crate::limits::MAX_SVG_DOCUMENT_SIZE
+    pub fn get_svg_document(&self, glyph_id: GlyphId) -> Result<Option<Cow<'_, [u8]>>, ParseError> {
+        let Some(record) = self.document_record(glyph_id) else {
+            return Ok(None);
+        };
+        let start = record.offset as usize;
+        let document = &self.document_list[start..start + record.length as usize];
+        if !document.starts_with(&GZIP_MAGIC) {
+            return Ok(Some(Cow::Borrowed(document)));
+        }
 
-        Ok(SvgTable { version })
+        // Read at most one byte past the cap so a gzip bomb is caught below
+        let mut decompressed = Vec::new();
+        GzDecoder::new(document)
+            .take(MAX_SVG_DOCUMENT_SIZE as u64 + 1)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
+        limits::check_cap(
+            "SVG document byte",
+            decompressed.len(),
+            MAX_SVG_DOCUMENT_SIZE,
+        )?;
+        Ok(Some(Cow::Owned(decompressed)))
     }
 }
 
@@ -0,0 +1,636 @@
+//! COLR version 1 paint graphs
+//!
+//! Version 1 color glyphs are graphs of paints: fills (solid colors and
+//! linear, radial and sweep gradients) clipped by glyph outlines, nested
+//! through transforms and blended by composite modes. [`ColrTable::paint`]
+//! resolves a glyph's graph into a [`Paint`] tree that renderers evaluate
+//! or hand to a vector backend.
+//!
+//! Variable paints (`PaintVar*`) are read at the default instance; their
+//! deltas are not applied. The clip list is not read, so renderers size
+//! glyphs from the outlines the paints are clipped to.
+//!
+//! [`ColrTable::paint`]: crate::ColrTable::paint
+
+use crate::limits::{self, MAX_PAINT_DEPTH, MAX_PAINT_NODES};
+use crate::types::GlyphId;
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Cursor;
+
+/// How a color line continues outside its first and last stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extend {
+    /// Keep the color of the nearest stop
+    Pad,
+    /// Repeat the stops
+    Repeat,
+    /// Repeat the stops, mirrored every other time
+    Reflect,
+}
+
+/// A color at a position along a gradient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorStop {
+    /// Position along the gradient, 0 at the start and 1 at the end
+    pub offset: f32,
+    /// CPAL palette entry, or `0xFFFF` for the text color
+    pub palette_index: u16,
+    /// Alpha multiplied with the palette color's alpha
+    pub alpha: f32,
+}
+
+/// Gradient colors
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorLine {
+    /// Behavior outside the stops
+    pub extend: Extend,
+    /// Stops in table order; they may be unsorted
+    pub stops: Vec<ColorStop>,
+}
+
+/// 2x3 affine transform in font units
+///
+/// Maps `(x, y)` to `(xx * x + xy * y + dx, yx * x + yy * y + dy)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+    /// x scale
+    pub xx: f32,
+    /// y shear from x
+    pub yx: f32,
+    /// x shear from y
+    pub xy: f32,
+    /// y scale
+    pub yy: f32,
+    /// x translation
+    pub dx: f32,
+    /// y translation
+    pub dy: f32,
+}
+
+impl Affine {
+    /// Transform that leaves points unchanged
+    pub const IDENTITY: Affine = Affine {
+        xx: 1.0,
+        yx: 0.0,
+        xy: 0.0,
+        yy: 1.0,
+        dx: 0.0,
+        dy: 0.0,
+    };
+
+    /// Translation by `(dx, dy)`
+    pub fn translate(dx: f32, dy: f32) -> Affine {
+        Affine {
+            dx,
+            dy,
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// Scale by `sx` horizontally and `sy` vertically
+    pub fn scale(sx: f32, sy: f32) -> Affine {
+        Affine {
+            xx: sx,
+            yy: sy,
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// Counter-clockwise rotation by `degrees`
+    pub fn rotate(degrees: f32) -> Affine {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Affine {
+            xx: cos,
+            yx: sin,
+            xy: -sin,
+            yy: cos,
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// Skew by counter-clockwise angles of the x and y axes, in degrees
+    pub fn skew(x_degrees: f32, y_degrees: f32) -> Affine {
+        Affine {
+            xy: -x_degrees.to_radians().tan(),
+            yx: y_degrees.to_radians().tan(),
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// This transform applied after `inner`
+    pub fn multiply(&self, inner: &Affine) -> Affine {
+        Affine {
+            xx: self.xx * inner.xx + self.xy * inner.yx,
+            yx: self.yx * inner.xx + self.yy * inner.yx,
+            xy: self.xx * inner.xy + self.xy * inner.yy,
+            yy: self.yx * inner.xy + self.yy * inner.yy,
+            dx: self.xx * inner.dx + self.xy * inner.dy + self.dx,
+            dy: self.yx * inner.dx + self.yy * inner.dy + self.dy,
+        }
+    }
+
+    /// This transform about `(x, y)` instead of the origin
+    pub fn around(&self, x: f32, y: f32) -> Affine {
+        Affine::translate(x, y)
+            .multiply(self)
+            .multiply(&Affine::translate(-x, -y))
+    }
+
+    /// Transform a point
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.xx * x + self.xy * y + self.dx,
+            self.yx * x + self.yy * y + self.dy,
+        )
+    }
+
+    /// Inverse transform, or `None` if the transform is degenerate
+    pub fn invert(&self) -> Option<Affine> {
+        let det = self.xx * self.yy - self.xy * self.yx;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let xx = self.yy / det;
+        let yx = -self.yx / det;
+        let xy = -self.xy / det;
+        let yy = self.xx / det;
+        Some(Affine {
+            xx,
+            yx,
+            xy,
+            yy,
+            dx: -(xx * self.dx + xy * self.dy),
+            dy: -(yx * self.dx + yy * self.dy),
+        })
+    }
+}
+
+/// How [`Paint::Composite`] combines its source with its backdrop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompositeMode {
+    /// Porter-Duff clear
+    Clear,
+    /// Porter-Duff source
+    Src,
+    /// Porter-Duff destination
+    Dest,
+    /// Porter-Duff source over
+    SrcOver,
+    /// Porter-Duff destination over
+    DestOver,
+    /// Porter-Duff source in
+    SrcIn,
+    /// Porter-Duff destination in
+    DestIn,
+    /// Porter-Duff source out
+    SrcOut,
+    /// Porter-Duff destination out
+    DestOut,
+    /// Porter-Duff source atop
+    SrcAtop,
+    /// Porter-Duff destination atop
+    DestAtop,
+    /// Porter-Duff xor
+    Xor,
+    /// Porter-Duff plus (additive)
+    Plus,
+    /// Screen blend
+    Screen,
+    /// Overlay blend
+    Overlay,
+    /// Darken blend
+    Darken,
+    /// Lighten blend
+    Lighten,
+    /// Color dodge blend
+    ColorDodge,
+    /// Color burn blend
+    ColorBurn,
+    /// Hard light blend
+    HardLight,
+    /// Soft light blend
+    SoftLight,
+    /// Difference blend
+    Difference,
+    /// Exclusion blend
+    Exclusion,
+    /// Multiply blend
+    Multiply,
+    /// Hue blend
+    Hue,
+    /// Saturation blend
+    Saturation,
+    /// Color blend
+    Color,
+    /// Luminosity blend
+    Luminosity,
+}
+
+impl CompositeMode {
+    fn from_u8(mode: u8) -> Option<Self> {
+        use CompositeMode::*;
+        const MODES: [CompositeMode; 28] = [
+            Clear, Src, Dest, SrcOver, DestOver, SrcIn, DestIn, SrcOut, DestOut, SrcAtop, DestAtop,
+            Xor, Plus, Screen, Overlay, Darken, Lighten, ColorDodge, ColorBurn, HardLight,
+            SoftLight, Difference, Exclusion, Multiply, Hue, Saturation, Color, Luminosity,
+        ];
+        MODES.get(mode as usize).copied()
+    }
+}
+
+/// A node of a COLR version 1 paint graph
+///
+/// Coordinates are in font units with y up. Translations, scales, rotations
+/// and skews, with or without a center, are all read as
+/// [`Transform`](Paint::Transform).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    /// Paints drawn bottom to top
+    Layers(Vec<Paint>),
+    /// A palette color everywhere
+    Solid {
+        /// CPAL palette entry, or `0xFFFF` for the text color
+        palette_index: u16,
+        /// Alpha multiplied with the palette color's alpha
+        alpha: f32,
+    },
+    /// Gradient along the line from `p0` towards `p1`, rotated so that its
+    /// color bands are parallel to the line from `p0` to `p2`
+    LinearGradient {
+        /// Colors
+        color_line: ColorLine,
+        /// Start point
+        p0: (f32, f32),
+        /// End point
+        p1: (f32, f32),
+        /// Rotation point
+        p2: (f32, f32),
+    },
+    /// Gradient between two circles
+    RadialGradient {
+        /// Colors
+        color_line: ColorLine,
+        /// Center of the start circle
+        c0: (f32, f32),
+        /// Radius of the start circle
+        r0: f32,
+        /// Center of the end circle
+        c1: (f32, f32),
+        /// Radius of the end circle
+        r1: f32,
+    },
+    /// Gradient around a center, counter-clockwise from `start_angle` to
+    /// `end_angle` degrees
+    SweepGradient {
+        /// Colors
+        color_line: ColorLine,
+        /// Center
+        center: (f32, f32),
+        /// Angle of the first stop in degrees
+        start_angle: f32,
+        /// Angle of the last stop in degrees
+        end_angle: f32,
+    },
+    /// `paint` clipped to the outline of `glyph_id`
+    Glyph {
+        /// Glyph whose outline clips the paint
+        glyph_id: GlyphId,
+        /// Fill
+        paint: Box<Paint>,
+    },
+    /// The paint graph of another color glyph, resolved by the renderer
+    ColrGlyph(GlyphId),
+    /// `paint` drawn with `transform` applied
+    Transform {
+        /// Transform from the paint's coordinates to its parent's
+        transform: Affine,
+        /// Transformed paint
+        paint: Box<Paint>,
+    },
+    /// `source` combined with `backdrop`
+    Composite {
+        /// Paint drawn onto the backdrop
+        source: Box<Paint>,
+        /// Blending of source and backdrop
+        mode: CompositeMode,
+        /// Paint drawn first
+        backdrop: Box<Paint>,
+    },
+}
+
+/// Version 1 part of a COLR table: base glyph paints and the layer list
+#[derive(Debug, Clone)]
+pub(crate) struct PaintGraph {
+    data: Vec<u8>,
+    /// Base glyphs sorted by glyph ID, with absolute paint offsets
+    base_glyphs: Vec<(GlyphId, u32)>,
+    /// Absolute offsets of the layer list paints
+    layers: Vec<u32>,
+}
+
+impl PaintGraph {
+    /// Read the version 1 lists of a COLR table, given its header cursor
+    /// positioned after the version 0 fields
+    pub(crate) fn parse(data: &[u8], cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+        let base_glyph_list_offset = cursor.read_u32::<BigEndian>()?;
+        let layer_list_offset = cursor.read_u32::<BigEndian>()?;
+
+        let mut base_glyphs = Vec::new();
+        if base_glyph_list_offset != 0 {
+            cursor.set_position(base_glyph_list_offset as u64);
+            let count = cursor.read_u32::<BigEndian>()? as usize;
+            limits::check_declared(
+                "COLR base glyph paint records",
+                count,
+                6,
+                limits::remaining(data, cursor.position()),
+            )?;
+            base_glyphs.reserve(count);
+            for _ in 0..count {
+                let glyph_id = cursor.read_u16::<BigEndian>()?;
+                let offset = cursor.read_u32::<BigEndian>()?;
+                base_glyphs.push((glyph_id, base_glyph_list_offset.saturating_add(offset)));
+            }
+            base_glyphs.sort_by_key(|&(glyph_id, _)| glyph_id);
+        }
+
+        let mut layers = Vec::new();
+        if layer_list_offset != 0 {
+            cursor.set_position(layer_list_offset as u64);
+            let count = cursor.read_u32::<BigEndian>()? as usize;
+            limits::check_declared(
+                "COLR layer paints",
+                count,
+                4,
+                limits::remaining(data, cursor.position()),
+            )?;
+            layers.reserve(count);
+            for _ in 0..count {
+                let offset = cursor.read_u32::<BigEndian>()?;
+                layers.push(layer_list_offset.saturating_add(offset));
+            }
+        }
+
+        Ok(PaintGraph {
+            data: data.to_vec(),
+            base_glyphs,
+            layers,
+        })
+    }
+
+    /// Whether `glyph_id` has a paint graph
+    pub(crate) fn contains(&self, glyph_id: GlyphId) -> bool {
+        self.paint_offset(glyph_id).is_some()
+    }
+
+    /// Number of glyphs with a paint graph
+    pub(crate) fn len(&self) -> usize {
+        self.base_glyphs.len()
+    }
+
+    /// Resolve the paint graph of `glyph_id` into a tree
+    pub(crate) fn paint(&self, glyph_id: GlyphId) -> Result<Option<Paint>, ParseError> {
+        let Some(offset) = self.paint_offset(glyph_id) else {
+            return Ok(None);
+        };
+        let mut reader = PaintReader {
+            data: &self.data,
+            layers: &self.layers,
+            nodes: 0,
+        };
+        reader.read(offset as u64, 0).map(Some)
+    }
+
+    fn paint_offset(&self, glyph_id: GlyphId) -> Option<u32> {
+        self.base_glyphs
+            .binary_search_by_key(&glyph_id, |&(id, _)| id)
+            .ok()
+            .map(|index| self.base_glyphs[index].1)
+    }
+}
+
+/// Reads paints, bounding the depth and the number of nodes a graph with
+/// shared subgraphs expands to
+struct PaintReader<'a> {
+    data: &'a [u8],
+    layers: &'a [u32],
+    nodes: usize,
+}
+
+impl PaintReader<'_> {
+    fn read(&mut self, offset: u64, depth: usize) -> Result<Paint, ParseError> {
+        limits::check_cap("COLR paint nesting", depth, MAX_PAINT_DEPTH)?;
+        self.nodes += 1;
+        limits::check_cap("COLR paint", self.nodes, MAX_PAINT_NODES)?;
+
+        let mut cursor = Cursor::new(self.data);
+        cursor.set_position(offset);
+        let format = cursor.read_u8()?;
+        let child = |cursor: &mut Cursor<&[u8]>| -> Result<u64, ParseError> {
+            Ok(offset + cursor.read_u24::<BigEndian>()? as u64)
+        };
+
+        let paint = match format {
+            1 => {
+                let count = cursor.read_u8()? as usize;
+                let first = cursor.read_u32::<BigEndian>()? as usize;
+                let offsets = first
+                    .checked_add(count)
+                    .and_then(|end| self.layers.get(first..end))
+                    .ok_or_else(|| {
+                        ParseError::CorruptedData("COLR layer index out of range".to_string())
+                    })?;
+                let mut layers = Vec::with_capacity(count);
+                for &layer in offsets {
+                    layers.push(self.read(layer as u64, depth + 1)?);
+                }
+                Paint::Layers(layers)
+            }
+            2 | 3 => Paint::Solid {
+                palette_index: cursor.read_u16::<BigEndian>()?,
+                alpha: read_f2dot14(&mut cursor)?,
+            },
+            4 | 5 => {
+                let color_line = self.color_line(child(&mut cursor)?, format == 5)?;
+                let p0 = read_point(&mut cursor)?;
+                let p1 = read_point(&mut cursor)?;
+                let p2 = read_point(&mut cursor)?;
+                Paint::LinearGradient {
+                    color_line,
+                    p0,
+                    p1,
+                    p2,
+                }
+            }
+            6 | 7 => {
+                let color_line = self.color_line(child(&mut cursor)?, format == 7)?;
+                let c0 = read_point(&mut cursor)?;
+                let r0 = cursor.read_u16::<BigEndian>()? as f32;
+                let c1 = read_point(&mut cursor)?;
+                let r1 = cursor.read_u16::<BigEndian>()? as f32;
+                Paint::RadialGradient {
+                    color_line,
+                    c0,
+                    r0,
+                    c1,
+                    r1,
+                }
+            }
+            8 | 9 => {
+                let color_line = self.color_line(child(&mut cursor)?, format == 9)?;
+                let center = read_point(&mut cursor)?;
+                // Angles are stored in half turns
+                let start_angle = read_f2dot14(&mut cursor)? * 180.0;
+                let end_angle = read_f2dot14(&mut cursor)? * 180.0;
+                Paint::SweepGradient {
+                    color_line,
+                    center,
+                    start_angle,
+                    end_angle,
+                }
+            }
+            10 => {
+                let paint = child(&mut cursor)?;
+                let glyph_id = cursor.read_u16::<BigEndian>()?;
+                Paint::Glyph {
+                    glyph_id,
+                    paint: Box::new(self.read(paint, depth + 1)?),
+                }
+            }
+            11 => Paint::ColrGlyph(cursor.read_u16::<BigEndian>()?),
+            12..=31 => {
+                let paint = child(&mut cursor)?;
+                let transform = read_transform(format, offset, &mut cursor)?;
+                Paint::Transform {
+                    transform,
+                    paint: Box::new(self.read(paint, depth + 1)?),
+                }
+            }
+            32 => {
+                let source = child(&mut cursor)?;
+                let mode = cursor.read_u8()?;
+                let backdrop = child(&mut cursor)?;
+                let mode = CompositeMode::from_u8(mode).ok_or_else(|| {
+                    ParseError::CorruptedData(format!("Unknown COLR composite mode {mode}"))
+                })?;
+                Paint::Composite {
+                    source: Box::new(self.read(source, depth + 1)?),
+                    mode,
+                    backdrop: Box::new(self.read(backdrop, depth + 1)?),
+                }
+            }
+            _ => {
+                return Err(ParseError::CorruptedData(format!(
+                    "Unknown COLR paint format {format}"
+                )))
+            }
+        };
+        Ok(paint)
+    }
+
+    /// Read a color line; variable stops carry a trailing variation index
+    fn color_line(&self, offset: u64, variable: bool) -> Result<ColorLine, ParseError> {
+        let mut cursor = Cursor::new(self.data);
+        cursor.set_position(offset);
+        let extend = match cursor.read_u8()? {
+            1 => Extend::Repeat,
+            2 => Extend::Reflect,
+            // Unknown modes fall back to pad, as the specification requires
+            _ => Extend::Pad,
+        };
+        let count = cursor.read_u16::<BigEndian>()? as usize;
+        let stop_size = if variable { 10 } else { 6 };
+        limits::check_declared(
+            "COLR color stops",
+            count,
+            stop_size,
+            limits::remaining(self.data, cursor.position()),
+        )?;
+        let mut stops = Vec::with_capacity(count);
+        for _ in 0..count {
+            stops.push(ColorStop {
+                offset: read_f2dot14(&mut cursor)?,
+                palette_index: cursor.read_u16::<BigEndian>()?,
+                alpha: read_f2dot14(&mut cursor)?,
+            });
+            if variable {
+                cursor.read_u32::<BigEndian>()?;
+            }
+        }
+        Ok(ColorLine { extend, stops })
+    }
+}
+
+/// Read the transform of paint formats 12 to 31, after the child offset
+fn read_transform(
+    format: u8,
+    paint_offset: u64,
+    cursor: &mut Cursor<&[u8]>,
+) -> Result<Affine, ParseError> {
+    // Variable formats are odd and share the layout of the one before
+    let format = format & !1;
+    let transform = match format {
+        12 => {
+            let offset = paint_offset + cursor.read_u24::<BigEndian>()? as u64;
+            cursor.set_position(offset);
+            let mut values = [0.0; 6];
+            for value in &mut values {
+                *value = cursor.read_i32::<BigEndian>()? as f32 / 65536.0;
+            }
+            let [xx, yx, xy, yy, dx, dy] = values;
+            Affine {
+                xx,
+                yx,
+                xy,
+                yy,
+                dx,
+                dy,
+            }
+        }
+        14 => {
+            let (dx, dy) = read_point(cursor)?;
+            Affine::translate(dx, dy)
+        }
+        16 | 18 => {
+            let scale = Affine::scale(read_f2dot14(cursor)?, read_f2dot14(cursor)?);
+            centered(format == 18, scale, cursor)?
+        }
+        20 | 22 => {
+            let scale = read_f2dot14(cursor)?;
+            centered(format == 22, Affine::scale(scale, scale), cursor)?
+        }
+        24 | 26 => {
+            let rotate = Affine::rotate(read_f2dot14(cursor)? * 180.0);
+            centered(format == 26, rotate, cursor)?
+        }
+        _ => {
+            let skew = Affine::skew(read_f2dot14(cursor)? * 180.0, read_f2dot14(cursor)? * 180.0);
+            centered(format == 30, skew, cursor)?
+        }
+    };
+    Ok(transform)
+}
+
+/// `transform` about the center that follows it, if `has_center`
+fn centered(
+    has_center: bool,
+    transform: Affine,
+    cursor: &mut Cursor<&[u8]>,
+) -> Result<Affine, ParseError> {
+    if !has_center {
+        return Ok(transform);
+    }
+    let (x, y) = read_point(cursor)?;
+    Ok(transform.around(x, y))
+}
+
+fn read_point(cursor: &mut Cursor<&[u8]>) -> Result<(f32, f32), ParseError> {
+    let x = cursor.read_i16::<BigEndian>()? as f32;
+    let y = cursor.read_i16::<BigEndian>()? as f32;
+    Ok((x, y))
+}
+
+fn read_f2dot14(cursor: &mut Cursor<&[u8]>) -> Result<f32, ParseError> {
+    Ok(cursor.read_i16::<BigEndian>()? as f32 / 16384.0)
+}
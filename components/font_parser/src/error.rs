@@ -1,34 +1,25 @@
 //! Error types for font parsing
 
-use std::fmt;
 use std::io;
+use thiserror::Error;
 
 /// Font parsing errors
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Error, Clone, PartialEq)]
 pub enum ParseError {
     /// Invalid font format
+    #[error("Invalid font format")]
     InvalidFormat,
     /// Required table is missing
+    #[error("Missing required table: {0}")]
     MissingTable(String),
     /// Font data is corrupted
+    #[error("Corrupted font data: {0}")]
     CorruptedData(String),
     /// Unsupported font version
+    #[error("Unsupported font version")]
     UnsupportedVersion,
 }
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ParseError::InvalidFormat => write!(f, "Invalid font format"),
-            ParseError::MissingTable(table) => write!(f, "Missing required table: {}", table),
-            ParseError::CorruptedData(msg) => write!(f, "Corrupted font data: {}", msg),
-            ParseError::UnsupportedVersion => write!(f, "Unsupported font version"),
-        }
-    }
-}
-
-impl std::error::Error for ParseError {}
-
 impl From<io::Error> for ParseError {
     fn from(err: io::Error) -> Self {
         ParseError::CorruptedData(err.to_string())
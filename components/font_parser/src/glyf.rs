@@ -0,0 +1,451 @@
+//! Glyph data (glyf/loca) parsing
+//!
+//! TrueType outlines are quadratic contours stored in glyf and located via
+//! the loca offsets. Composite glyphs place other glyphs with an offset and
+//! an optional 2x2 transform; they are flattened into a single outline here.
+//! When gvar deltas are requested they are applied at every level, to the
+//! points of simple glyphs and to the component offsets of composites.
+
+use crate::gvar::GvarTable;
+use crate::limits::{self, MAX_COMPONENT_DEPTH, MAX_GLYPH_COMPONENTS};
+use crate::types::{BoundingBox, Contour, GlyphId, GlyphOutline};
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Cursor;
+
+/// Simple glyph flag: the point is on the curve
+const ON_CURVE_POINT: u8 = 0x01;
+
+/// Simple glyph flag: the x coordinate is a single byte
+const X_SHORT_VECTOR: u8 = 0x02;
+
+/// Simple glyph flag: the y coordinate is a single byte
+const Y_SHORT_VECTOR: u8 = 0x04;
+
+/// Simple glyph flag: the next byte repeats this flag that many times
+const REPEAT_FLAG: u8 = 0x08;
+
+/// Simple glyph flag: positive short x, or x unchanged for long vectors
+const X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+
+/// Simple glyph flag: positive short y, or y unchanged for long vectors
+const Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+
+/// Component flag: arguments are 16-bit
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+
+/// Component flag: arguments are an x/y offset rather than point indices
+const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+
+/// Component flag: a uniform scale follows
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+
+/// Component flag: another component follows
+const MORE_COMPONENTS: u16 = 0x0020;
+
+/// Component flag: separate x and y scales follow
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+
+/// Component flag: a 2x2 transform follows
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// glyf and loca tables of a font
+pub(crate) struct GlyfTable<'a> {
+    glyf: &'a [u8],
+    loca: &'a [u8],
+    long_offsets: bool,
+    num_glyphs: u16,
+}
+
+/// Points of a glyph, flattened across components
+#[derive(Debug, Clone, Default)]
+struct GlyphPoints {
+    points: Vec<(f32, f32)>,
+    on_curve: Vec<bool>,
+    /// Index of the last point of each contour
+    end_points: Vec<usize>,
+}
+
+/// Placement of one component of a composite glyph
+struct Component {
+    glyph_id: GlyphId,
+    /// Offset, or `None` when the component is positioned by point matching
+    offset: Option<(f32, f32)>,
+    /// Parent and child point indices used for point matching
+    anchor: (usize, usize),
+    /// 2x2 transform `[xx, yx, xy, yy]`
+    transform: [f32; 4],
+}
+
+enum Glyph {
+    Simple(GlyphPoints),
+    Composite(Vec<Component>),
+}
+
+impl<'a> GlyfTable<'a> {
+    pub(crate) fn new(glyf: &'a [u8], loca: &'a [u8], long_offsets: bool, num_glyphs: u16) -> Self {
+        GlyfTable {
+            glyf,
+            loca,
+            long_offsets,
+            num_glyphs,
+        }
+    }
+
+    /// Decode the outline of a glyph, applying gvar deltas at the given
+    /// normalized coordinates when `variations` is set
+    pub(crate) fn outline(
+        &self,
+        glyph_id: GlyphId,
+        variations: Option<(&GvarTable<'_>, &[f32])>,
+    ) -> Result<GlyphOutline, ParseError> {
+        let mut budget = MAX_GLYPH_COMPONENTS;
+        let glyph = self.resolve(glyph_id, variations, 0, &mut budget)?;
+        Ok(glyph.into_outline())
+    }
+
+    /// Horizontal advance delta from the gvar phantom points of a glyph
+    ///
+    /// Used for fonts without HVAR, where advances vary only through the
+    /// second phantom point moving relative to the first.
+    pub(crate) fn advance_delta(
+        &self,
+        glyph_id: GlyphId,
+        gvar: &GvarTable<'_>,
+        coords: &[f32],
+    ) -> Result<f32, ParseError> {
+        let (points, end_points) = match self.parse_glyph(glyph_id)? {
+            Glyph::Simple(glyph) => (glyph.points, glyph.end_points),
+            Glyph::Composite(components) => (
+                components
+                    .iter()
+                    .map(|component| component.offset.unwrap_or((0.0, 0.0)))
+                    .collect(),
+                Vec::new(),
+            ),
+        };
+        let count = points.len();
+        Ok(gvar
+            .glyph_deltas(glyph_id, coords, &points, &end_points)?
+            .map_or(0.0, |deltas| deltas[count + 1].0 - deltas[count].0))
+    }
+
+    /// Raw glyph data located via loca; empty for glyphs without outline
+    fn glyph_data(&self, glyph_id: GlyphId) -> Result<&'a [u8], ParseError> {
+        if glyph_id >= self.num_glyphs {
+            return Err(ParseError::CorruptedData(format!(
+                "glyph {} out of range",
+                glyph_id
+            )));
+        }
+        let index = glyph_id as usize;
+        let (start, end) = if self.long_offsets {
+            let mut cursor = Cursor::new(self.loca.get(index * 4..index * 4 + 8).unwrap_or(&[]));
+            (
+                cursor.read_u32::<BigEndian>()? as usize,
+                cursor.read_u32::<BigEndian>()? as usize,
+            )
+        } else {
+            let mut cursor = Cursor::new(self.loca.get(index * 2..index * 2 + 4).unwrap_or(&[]));
+            (
+                cursor.read_u16::<BigEndian>()? as usize * 2,
+                cursor.read_u16::<BigEndian>()? as usize * 2,
+            )
+        };
+        if start > end || end > self.glyf.len() {
+            return Err(ParseError::CorruptedData(format!(
+                "loca entry of glyph {} out of bounds",
+                glyph_id
+            )));
+        }
+        Ok(&self.glyf[start..end])
+    }
+
+    fn parse_glyph(&self, glyph_id: GlyphId) -> Result<Glyph, ParseError> {
+        let data = self.glyph_data(glyph_id)?;
+        if data.is_empty() {
+            return Ok(Glyph::Simple(GlyphPoints::default()));
+        }
+        let mut cursor = Cursor::new(data);
+        let contour_count = cursor.read_i16::<BigEndian>()?;
+        // Skip the bounding box; it is recomputed from the points
+        cursor.set_position(10);
+        if contour_count >= 0 {
+            parse_simple(&mut cursor, contour_count as usize).map(Glyph::Simple)
+        } else {
+            parse_composite(&mut cursor).map(Glyph::Composite)
+        }
+    }
+
+    fn resolve(
+        &self,
+        glyph_id: GlyphId,
+        variations: Option<(&GvarTable<'_>, &[f32])>,
+        depth: usize,
+        budget: &mut usize,
+    ) -> Result<GlyphPoints, ParseError> {
+        if depth > MAX_COMPONENT_DEPTH {
+            return Err(ParseError::CorruptedData(format!(
+                "composite glyph {} nests deeper than {} levels",
+                glyph_id, MAX_COMPONENT_DEPTH
+            )));
+        }
+
+        match self.parse_glyph(glyph_id)? {
+            Glyph::Simple(mut glyph) => {
+                if let Some((gvar, coords)) = variations {
+                    let deltas =
+                        gvar.glyph_deltas(glyph_id, coords, &glyph.points, &glyph.end_points)?;
+                    for (point, (dx, dy)) in glyph.points.iter_mut().zip(deltas.unwrap_or_default())
+                    {
+                        point.0 += dx;
+                        point.1 += dy;
+                    }
+                }
+                Ok(glyph)
+            }
+            Glyph::Composite(mut components) => {
+                *budget = budget.checked_sub(components.len()).ok_or_else(|| {
+                    ParseError::CorruptedData(format!(
+                        "composite glyph {} references more than {} components",
+                        glyph_id, MAX_GLYPH_COMPONENTS
+                    ))
+                })?;
+
+                // Each component offset varies like a point
+                if let Some((gvar, coords)) = variations {
+                    let offsets: Vec<(f32, f32)> = components
+                        .iter()
+                        .map(|component| component.offset.unwrap_or((0.0, 0.0)))
+                        .collect();
+                    let deltas = gvar.glyph_deltas(glyph_id, coords, &offsets, &[])?;
+                    for (component, (dx, dy)) in
+                        components.iter_mut().zip(deltas.unwrap_or_default())
+                    {
+                        if let Some(offset) = &mut component.offset {
+                            offset.0 += dx;
+                            offset.1 += dy;
+                        }
+                    }
+                }
+
+                let mut glyph = GlyphPoints::default();
+                for component in components {
+                    let mut child =
+                        self.resolve(component.glyph_id, variations, depth + 1, budget)?;
+                    let [xx, yx, xy, yy] = component.transform;
+                    for point in &mut child.points {
+                        let (x, y) = *point;
+                        *point = (xx * x + xy * y, yx * x + yy * y);
+                    }
+
+                    let (dx, dy) = match component.offset {
+                        Some(offset) => offset,
+                        None => {
+                            // Align the child's anchor point with the parent's
+                            let (parent, child_point) = component.anchor;
+                            match (glyph.points.get(parent), child.points.get(child_point)) {
+                                (Some(p), Some(c)) => (p.0 - c.0, p.1 - c.1),
+                                _ => {
+                                    return Err(ParseError::CorruptedData(format!(
+                                        "component anchor of glyph {} out of range",
+                                        glyph_id
+                                    )))
+                                }
+                            }
+                        }
+                    };
+
+                    let base = glyph.points.len();
+                    glyph
+                        .points
+                        .extend(child.points.iter().map(|&(x, y)| (x + dx, y + dy)));
+                    glyph.on_curve.extend(child.on_curve);
+                    glyph
+                        .end_points
+                        .extend(child.end_points.iter().map(|end| end + base));
+                }
+                Ok(glyph)
+            }
+        }
+    }
+}
+
+impl GlyphPoints {
+    /// Split into contours with coordinates rounded to font units
+    fn into_outline(self) -> GlyphOutline {
+        let rounded: Vec<(i16, i16)> = self
+            .points
+            .iter()
+            .map(|&(x, y)| (x.round() as i16, y.round() as i16))
+            .collect();
+
+        let bounds = if rounded.is_empty() {
+            BoundingBox {
+                x_min: 0,
+                y_min: 0,
+                x_max: 0,
+                y_max: 0,
+            }
+        } else {
+            BoundingBox {
+                x_min: rounded.iter().map(|p| p.0).min().unwrap_or(0),
+                y_min: rounded.iter().map(|p| p.1).min().unwrap_or(0),
+                x_max: rounded.iter().map(|p| p.0).max().unwrap_or(0),
+                y_max: rounded.iter().map(|p| p.1).max().unwrap_or(0),
+            }
+        };
+
+        let mut contours = Vec::with_capacity(self.end_points.len());
+        let mut start = 0;
+        for end in self.end_points {
+            contours.push(Contour {
+                points: rounded[start..=end].to_vec(),
+                on_curve: self.on_curve[start..=end].to_vec(),
+            });
+            start = end + 1;
+        }
+
+        GlyphOutline { contours, bounds }
+    }
+}
+
+fn parse_simple(
+    cursor: &mut Cursor<&[u8]>,
+    contour_count: usize,
+) -> Result<GlyphPoints, ParseError> {
+    limits::check_declared(
+        "glyf contours",
+        contour_count,
+        2,
+        limits::remaining(cursor.get_ref(), cursor.position()),
+    )?;
+    let mut end_points = Vec::with_capacity(contour_count);
+    for _ in 0..contour_count {
+        let end = cursor.read_u16::<BigEndian>()? as usize;
+        if end_points.last().is_some_and(|&last| end <= last) {
+            return Err(ParseError::CorruptedData(
+                "glyf contour end points not increasing".to_string(),
+            ));
+        }
+        end_points.push(end);
+    }
+    let point_count = end_points.last().map_or(0, |&last| last + 1);
+
+    let instruction_length = cursor.read_u16::<BigEndian>()?;
+    cursor.set_position(cursor.position() + instruction_length as u64);
+
+    let mut flags = Vec::with_capacity(point_count);
+    while flags.len() < point_count {
+        let flag = cursor.read_u8()?;
+        let repeat = if flag & REPEAT_FLAG != 0 {
+            cursor.read_u8()? as usize
+        } else {
+            0
+        };
+        if flags.len() + repeat + 1 > point_count {
+            return Err(ParseError::CorruptedData(
+                "glyf flag repeat exceeds point count".to_string(),
+            ));
+        }
+        flags.extend(std::iter::repeat_n(flag, repeat + 1));
+    }
+
+    let xs = read_coordinates(
+        cursor,
+        &flags,
+        X_SHORT_VECTOR,
+        X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+    )?;
+    let ys = read_coordinates(
+        cursor,
+        &flags,
+        Y_SHORT_VECTOR,
+        Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+    )?;
+
+    Ok(GlyphPoints {
+        points: xs.into_iter().zip(ys).collect(),
+        on_curve: flags
+            .iter()
+            .map(|flag| flag & ON_CURVE_POINT != 0)
+            .collect(),
+        end_points,
+    })
+}
+
+/// Read delta-encoded coordinates along one axis
+fn read_coordinates(
+    cursor: &mut Cursor<&[u8]>,
+    flags: &[u8],
+    short_flag: u8,
+    same_or_positive_flag: u8,
+) -> Result<Vec<f32>, ParseError> {
+    let mut value = 0i32;
+    let mut coordinates = Vec::with_capacity(flags.len());
+    for &flag in flags {
+        if flag & short_flag != 0 {
+            let delta = cursor.read_u8()? as i32;
+            value += if flag & same_or_positive_flag != 0 {
+                delta
+            } else {
+                -delta
+            };
+        } else if flag & same_or_positive_flag == 0 {
+            value += cursor.read_i16::<BigEndian>()? as i32;
+        }
+        coordinates.push(value as f32);
+    }
+    Ok(coordinates)
+}
+
+fn parse_composite(cursor: &mut Cursor<&[u8]>) -> Result<Vec<Component>, ParseError> {
+    let mut components = Vec::new();
+    loop {
+        let flags = cursor.read_u16::<BigEndian>()?;
+        let glyph_id = cursor.read_u16::<BigEndian>()?;
+        let xy_values = flags & ARGS_ARE_XY_VALUES != 0;
+        let (arg1, arg2) = match (flags & ARG_1_AND_2_ARE_WORDS != 0, xy_values) {
+            (true, true) => (
+                cursor.read_i16::<BigEndian>()? as i32,
+                cursor.read_i16::<BigEndian>()? as i32,
+            ),
+            (true, false) => (
+                cursor.read_u16::<BigEndian>()? as i32,
+                cursor.read_u16::<BigEndian>()? as i32,
+            ),
+            (false, true) => (cursor.read_i8()? as i32, cursor.read_i8()? as i32),
+            (false, false) => (cursor.read_u8()? as i32, cursor.read_u8()? as i32),
+        };
+
+        let mut read_f2dot14 =
+            || -> Result<f32, ParseError> { Ok(cursor.read_i16::<BigEndian>()? as f32 / 16384.0) };
+        let transform = if flags & WE_HAVE_A_SCALE != 0 {
+            let scale = read_f2dot14()?;
+            [scale, 0.0, 0.0, scale]
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            let x_scale = read_f2dot14()?;
+            let y_scale = read_f2dot14()?;
+            [x_scale, 0.0, 0.0, y_scale]
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            [
+                read_f2dot14()?,
+                read_f2dot14()?,
+                read_f2dot14()?,
+                read_f2dot14()?,
+            ]
+        } else {
+            [1.0, 0.0, 0.0, 1.0]
+        };
+
+        components.push(Component {
+            glyph_id,
+            offset: xy_values.then_some((arg1 as f32, arg2 as f32)),
+            anchor: (arg1 as usize, arg2 as usize),
+            transform,
+        });
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(components);
+        }
+    }
+}
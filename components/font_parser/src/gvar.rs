@@ -0,0 +1,517 @@
+//! Glyph variations table (gvar) parsing
+//!
+//! gvar stores, per glyph, a list of tuple variations: point deltas that
+//! apply with a weight derived from the normalized variation coordinates.
+//! A tuple may only list some of a glyph's points; deltas for the others are
+//! inferred from the neighbouring listed points of the same contour
+//! (interpolation of untouched points, IUP).
+
+use crate::limits::{self, MAX_VARIATION_AXES};
+use crate::types::GlyphId;
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Cursor;
+
+/// Tuple count flag: point numbers shared by all tuples precede the deltas
+const SHARED_POINT_NUMBERS: u16 = 0x8000;
+
+/// Mask of the tuple count in the glyph variation data header
+const TUPLE_COUNT_MASK: u16 = 0x0FFF;
+
+/// Tuple index flag: the header embeds its own peak tuple
+const EMBEDDED_PEAK_TUPLE: u16 = 0x8000;
+
+/// Tuple index flag: the header embeds start and end tuples
+const INTERMEDIATE_REGION: u16 = 0x4000;
+
+/// Tuple index flag: the serialized data starts with its own point numbers
+const PRIVATE_POINT_NUMBERS: u16 = 0x2000;
+
+/// Mask of the shared tuple index
+const TUPLE_INDEX_MASK: u16 = 0x0FFF;
+
+/// Packed point numbers: the count or run uses 16-bit values
+const POINTS_ARE_WORDS: u8 = 0x80;
+
+/// Packed point numbers: mask of the run length minus one
+const POINT_RUN_COUNT_MASK: u8 = 0x7F;
+
+/// Packed deltas: the run is all zeros and stores no values
+const DELTAS_ARE_ZERO: u8 = 0x80;
+
+/// Packed deltas: the run stores 16-bit values
+const DELTAS_ARE_WORDS: u8 = 0x40;
+
+/// Packed deltas: mask of the run length minus one
+const DELTA_RUN_COUNT_MASK: u8 = 0x3F;
+
+/// Phantom points (side bearings and advances) that follow a glyph's points
+const PHANTOM_POINT_COUNT: usize = 4;
+
+/// Glyph Variations Table (gvar)
+///
+/// Holds the header and shared tuples; per-glyph variation data is decoded
+/// on demand from the borrowed table data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GvarTable<'a> {
+    /// Number of variation axes, matching fvar
+    pub axis_count: u16,
+    /// Peak tuples referenced by index from tuple variation headers
+    pub shared_tuples: Vec<Vec<f32>>,
+    glyph_count: u16,
+    long_offsets: bool,
+    offsets: &'a [u8],
+    variation_data: &'a [u8],
+}
+
+impl<'a> GvarTable<'a> {
+    /// Parse gvar table from raw data
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the header, shared tuples or glyph offset
+    /// array are truncated, or the version is unsupported.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let major_version = cursor.read_u16::<BigEndian>()?;
+        let minor_version = cursor.read_u16::<BigEndian>()?;
+        if major_version != 1 {
+            return Err(ParseError::CorruptedData(format!(
+                "Unsupported gvar version {}.{}",
+                major_version, minor_version
+            )));
+        }
+
+        let axis_count = cursor.read_u16::<BigEndian>()?;
+        let shared_tuple_count = cursor.read_u16::<BigEndian>()?;
+        let shared_tuples_offset = cursor.read_u32::<BigEndian>()? as u64;
+        let glyph_count = cursor.read_u16::<BigEndian>()?;
+        let flags = cursor.read_u16::<BigEndian>()?;
+        let variation_data_offset = cursor.read_u32::<BigEndian>()? as usize;
+        limits::check_cap("gvar axis", axis_count as usize, MAX_VARIATION_AXES)?;
+
+        let long_offsets = flags & 1 != 0;
+        let offset_size = if long_offsets { 4 } else { 2 };
+        let offsets_start = cursor.position() as usize;
+        let offsets_len = (glyph_count as usize + 1) * offset_size;
+        limits::check_declared(
+            "gvar glyph offsets",
+            glyph_count as usize + 1,
+            offset_size,
+            limits::remaining(data, offsets_start as u64),
+        )?;
+        let offsets = &data[offsets_start..offsets_start + offsets_len];
+
+        limits::check_declared(
+            "gvar shared tuples",
+            shared_tuple_count as usize,
+            axis_count as usize * 2,
+            limits::remaining(data, shared_tuples_offset),
+        )?;
+        cursor.set_position(shared_tuples_offset);
+        let mut shared_tuples = Vec::with_capacity(shared_tuple_count as usize);
+        for _ in 0..shared_tuple_count {
+            shared_tuples.push(read_tuple(&mut cursor, axis_count)?);
+        }
+
+        let variation_data = data.get(variation_data_offset..).ok_or_else(|| {
+            ParseError::CorruptedData("gvar variation data offset out of bounds".to_string())
+        })?;
+
+        Ok(GvarTable {
+            axis_count,
+            shared_tuples,
+            glyph_count,
+            long_offsets,
+            offsets,
+            variation_data,
+        })
+    }
+
+    /// Number of glyphs covered by the table
+    pub fn glyph_count(&self) -> u16 {
+        self.glyph_count
+    }
+
+    /// Check whether a glyph has any variation data
+    pub fn has_variations(&self, glyph_id: GlyphId) -> bool {
+        self.glyph_variation_data(glyph_id)
+            .is_ok_and(|data| !data.is_empty())
+    }
+
+    /// Variation data of one glyph; empty when the glyph does not vary
+    fn glyph_variation_data(&self, glyph_id: GlyphId) -> Result<&'a [u8], ParseError> {
+        if glyph_id >= self.glyph_count {
+            return Ok(&[]);
+        }
+        let index = glyph_id as usize;
+        let offset_at = |i: usize| {
+            if self.long_offsets {
+                let b = &self.offsets[i * 4..i * 4 + 4];
+                u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize
+            } else {
+                let b = &self.offsets[i * 2..i * 2 + 2];
+                u16::from_be_bytes([b[0], b[1]]) as usize * 2
+            }
+        };
+        let (start, end) = (offset_at(index), offset_at(index + 1));
+        if start > end || end > self.variation_data.len() {
+            return Err(ParseError::CorruptedData(format!(
+                "gvar data of glyph {} out of bounds",
+                glyph_id
+            )));
+        }
+        Ok(&self.variation_data[start..end])
+    }
+
+    /// Compute the accumulated deltas of a glyph's points
+    ///
+    /// `points` are the glyph's original points without phantom points (for
+    /// composites, one point per component offset) and `end_points` the last
+    /// point index of each contour, used to infer deltas of untouched points.
+    /// The result holds one delta per point followed by the four phantom
+    /// points (left side bearing, advance, top and bottom origin), or `None`
+    /// when no tuple applies at `coords`.
+    pub(crate) fn glyph_deltas(
+        &self,
+        glyph_id: GlyphId,
+        coords: &[f32],
+        points: &[(f32, f32)],
+        end_points: &[usize],
+    ) -> Result<Option<Vec<(f32, f32)>>, ParseError> {
+        let data = self.glyph_variation_data(glyph_id)?;
+        if data.is_empty() || coords.iter().all(|&coord| coord == 0.0) {
+            return Ok(None);
+        }
+        let point_count = points.len() + PHANTOM_POINT_COUNT;
+
+        let mut cursor = Cursor::new(data);
+        let tuple_header = cursor.read_u16::<BigEndian>()?;
+        let mut serialized_offset = cursor.read_u16::<BigEndian>()? as usize;
+        let tuple_count = tuple_header & TUPLE_COUNT_MASK;
+
+        let shared_points = if tuple_header & SHARED_POINT_NUMBERS != 0 {
+            let mut shared = Cursor::new(data);
+            shared.set_position(serialized_offset as u64);
+            let numbers = read_packed_points(&mut shared)?;
+            serialized_offset = shared.position() as usize;
+            numbers
+        } else {
+            None
+        };
+
+        let mut deltas = vec![(0.0f32, 0.0f32); point_count];
+        let mut applied = false;
+        for _ in 0..tuple_count {
+            let data_size = cursor.read_u16::<BigEndian>()? as usize;
+            let tuple_index = cursor.read_u16::<BigEndian>()?;
+            let peak = if tuple_index & EMBEDDED_PEAK_TUPLE != 0 {
+                read_tuple(&mut cursor, self.axis_count)?
+            } else {
+                let index = (tuple_index & TUPLE_INDEX_MASK) as usize;
+                self.shared_tuples.get(index).cloned().ok_or_else(|| {
+                    ParseError::CorruptedData(format!("gvar shared tuple {} out of range", index))
+                })?
+            };
+            let region = if tuple_index & INTERMEDIATE_REGION != 0 {
+                let start = read_tuple(&mut cursor, self.axis_count)?;
+                let end = read_tuple(&mut cursor, self.axis_count)?;
+                Some((start, end))
+            } else {
+                None
+            };
+
+            let tuple_data = data
+                .get(serialized_offset..serialized_offset + data_size)
+                .ok_or_else(|| {
+                    ParseError::CorruptedData(format!(
+                        "gvar tuple data of glyph {} out of bounds",
+                        glyph_id
+                    ))
+                })?;
+            serialized_offset += data_size;
+
+            let scalar = tuple_scalar(coords, &peak, region.as_ref());
+            if scalar == 0.0 {
+                continue;
+            }
+
+            let mut tuple_cursor = Cursor::new(tuple_data);
+            let private_points;
+            let numbers = if tuple_index & PRIVATE_POINT_NUMBERS != 0 {
+                private_points = read_packed_points(&mut tuple_cursor)?;
+                private_points.as_deref()
+            } else {
+                shared_points.as_deref()
+            };
+            let count = numbers.map_or(point_count, <[usize]>::len);
+            let x_deltas = read_packed_deltas(&mut tuple_cursor, count)?;
+            let y_deltas = read_packed_deltas(&mut tuple_cursor, count)?;
+
+            match numbers {
+                None => {
+                    for (i, delta) in deltas.iter_mut().enumerate() {
+                        delta.0 += x_deltas[i] * scalar;
+                        delta.1 += y_deltas[i] * scalar;
+                    }
+                }
+                Some(numbers) => {
+                    let mut explicit = vec![None; point_count];
+                    for (k, &number) in numbers.iter().enumerate() {
+                        if let Some(slot) = explicit.get_mut(number) {
+                            *slot = Some((x_deltas[k], y_deltas[k]));
+                        }
+                    }
+                    let inferred = infer_untouched(points, end_points, &explicit);
+                    for (delta, (dx, dy)) in deltas.iter_mut().zip(inferred) {
+                        delta.0 += dx * scalar;
+                        delta.1 += dy * scalar;
+                    }
+                }
+            }
+            applied = true;
+        }
+
+        Ok(applied.then_some(deltas))
+    }
+}
+
+/// Read one F2DOT14 coordinate per axis
+fn read_tuple(cursor: &mut Cursor<&[u8]>, axis_count: u16) -> Result<Vec<f32>, ParseError> {
+    (0..axis_count)
+        .map(|_| Ok(cursor.read_i16::<BigEndian>()? as f32 / 16384.0))
+        .collect()
+}
+
+/// Read packed point numbers; `None` means every point of the glyph
+fn read_packed_points(cursor: &mut Cursor<&[u8]>) -> Result<Option<Vec<usize>>, ParseError> {
+    let first = cursor.read_u8()?;
+    if first == 0 {
+        return Ok(None);
+    }
+    let count = if first & POINTS_ARE_WORDS != 0 {
+        ((first & POINT_RUN_COUNT_MASK) as usize) << 8 | cursor.read_u8()? as usize
+    } else {
+        first as usize
+    };
+    // Every point number takes at least one byte
+    limits::check_declared(
+        "gvar point numbers",
+        count,
+        1,
+        limits::remaining(cursor.get_ref(), cursor.position()),
+    )?;
+
+    let mut numbers = Vec::with_capacity(count);
+    let mut current = 0usize;
+    while numbers.len() < count {
+        let control = cursor.read_u8()?;
+        let run = (control & POINT_RUN_COUNT_MASK) as usize + 1;
+        if numbers.len() + run > count {
+            return Err(ParseError::CorruptedData(
+                "gvar point run exceeds point count".to_string(),
+            ));
+        }
+        for _ in 0..run {
+            current += if control & POINTS_ARE_WORDS != 0 {
+                cursor.read_u16::<BigEndian>()? as usize
+            } else {
+                cursor.read_u8()? as usize
+            };
+            numbers.push(current);
+        }
+    }
+    Ok(Some(numbers))
+}
+
+/// Read `count` packed deltas
+fn read_packed_deltas(cursor: &mut Cursor<&[u8]>, count: usize) -> Result<Vec<f32>, ParseError> {
+    let mut deltas = Vec::with_capacity(count);
+    while deltas.len() < count {
+        let control = cursor.read_u8()?;
+        let run = (control & DELTA_RUN_COUNT_MASK) as usize + 1;
+        if deltas.len() + run > count {
+            return Err(ParseError::CorruptedData(
+                "gvar delta run exceeds point count".to_string(),
+            ));
+        }
+        for _ in 0..run {
+            let delta = if control & DELTAS_ARE_ZERO != 0 {
+                0
+            } else if control & DELTAS_ARE_WORDS != 0 {
+                cursor.read_i16::<BigEndian>()?
+            } else {
+                cursor.read_i8()? as i16
+            };
+            deltas.push(delta as f32);
+        }
+    }
+    Ok(deltas)
+}
+
+/// Weight of a tuple variation at the given normalized coordinates
+fn tuple_scalar(coords: &[f32], peak: &[f32], region: Option<&(Vec<f32>, Vec<f32>)>) -> f32 {
+    let mut scalar = 1.0;
+    for (axis, &peak) in peak.iter().enumerate() {
+        let coord = coords.get(axis).copied().unwrap_or(0.0);
+        if peak == 0.0 || coord == peak {
+            continue;
+        }
+        match region {
+            Some((starts, ends)) => {
+                let start = starts.get(axis).copied().unwrap_or(peak);
+                let end = ends.get(axis).copied().unwrap_or(peak);
+                // Invalid regions do not constrain the axis
+                if start > peak || peak > end || (start < 0.0 && end > 0.0) {
+                    continue;
+                }
+                if coord < start || coord > end {
+                    return 0.0;
+                }
+                scalar *= if coord < peak {
+                    (coord - start) / (peak - start)
+                } else {
+                    (end - coord) / (end - peak)
+                };
+            }
+            None => {
+                if coord == 0.0 || coord.signum() != peak.signum() || coord.abs() > peak.abs() {
+                    return 0.0;
+                }
+                scalar *= coord / peak;
+            }
+        }
+    }
+    scalar
+}
+
+/// Fill in deltas of points a tuple does not list (IUP)
+///
+/// Within each contour, an untouched point between two touched points takes
+/// the delta interpolated from their original positions, or the nearer
+/// delta when it lies outside them. A contour with a single touched point
+/// moves rigidly; one without touched points stays in place.
+fn infer_untouched(
+    points: &[(f32, f32)],
+    end_points: &[usize],
+    explicit: &[Option<(f32, f32)>],
+) -> Vec<(f32, f32)> {
+    let mut deltas: Vec<(f32, f32)> = explicit
+        .iter()
+        .map(|delta| delta.unwrap_or((0.0, 0.0)))
+        .collect();
+
+    let mut start = 0;
+    for &end in end_points {
+        if end < start || end >= points.len() {
+            break;
+        }
+        let touched: Vec<usize> = (start..=end).filter(|&i| explicit[i].is_some()).collect();
+        match touched.as_slice() {
+            [] => {}
+            [only] => {
+                let delta = deltas[*only];
+                deltas[start..=end].fill(delta);
+            }
+            _ => {
+                for (k, &previous) in touched.iter().enumerate() {
+                    let next = touched[(k + 1) % touched.len()];
+                    let mut i = previous;
+                    loop {
+                        i = if i == end { start } else { i + 1 };
+                        if i == next {
+                            break;
+                        }
+                        deltas[i] = (
+                            interpolate(
+                                points[i].0,
+                                (points[previous].0, deltas[previous].0),
+                                (points[next].0, deltas[next].0),
+                            ),
+                            interpolate(
+                                points[i].1,
+                                (points[previous].1, deltas[previous].1),
+                                (points[next].1, deltas[next].1),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+        start = end + 1;
+    }
+    deltas
+}
+
+/// Interpolate the delta of coordinate `p` from two (coordinate, delta) pairs
+fn interpolate(p: f32, a: (f32, f32), b: (f32, f32)) -> f32 {
+    let ((low, low_delta), (high, high_delta)) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+    if low == high {
+        return if low_delta == high_delta {
+            low_delta
+        } else {
+            0.0
+        };
+    }
+    if p <= low {
+        low_delta
+    } else if p >= high {
+        high_delta
+    } else {
+        low_delta + (p - low) / (high - low) * (high_delta - low_delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuple_scalar() {
+        assert_eq!(tuple_scalar(&[0.5], &[1.0], None), 0.5);
+        assert_eq!(tuple_scalar(&[-0.5], &[1.0], None), 0.0);
+        assert_eq!(tuple_scalar(&[0.0], &[1.0], None), 0.0);
+        assert_eq!(tuple_scalar(&[0.7], &[0.0], None), 1.0);
+
+        let region = (vec![0.0], vec![1.0]);
+        assert_eq!(tuple_scalar(&[0.75], &[0.5], Some(&region)), 0.5);
+        assert_eq!(tuple_scalar(&[0.25], &[0.5], Some(&region)), 0.5);
+    }
+
+    #[test]
+    fn test_packed_points_and_deltas() {
+        // 3 points as one byte run: 1, 1+2, 1+2+4
+        let data = [3u8, 0x02, 1, 2, 4];
+        let mut cursor = Cursor::new(&data[..]);
+        assert_eq!(
+            read_packed_points(&mut cursor).unwrap(),
+            Some(vec![1, 3, 7])
+        );
+
+        // 2 zero deltas, then one word delta of -300
+        let data = [0x81u8, 0x40, 0xFE, 0xD4];
+        let mut cursor = Cursor::new(&data[..]);
+        assert_eq!(
+            read_packed_deltas(&mut cursor, 3).unwrap(),
+            vec![0.0, 0.0, -300.0]
+        );
+
+        let mut cursor = Cursor::new(&[0u8][..]);
+        assert_eq!(read_packed_points(&mut cursor).unwrap(), None);
+    }
+
+    #[test]
+    fn test_infer_untouched_points() {
+        // Square contour; only the bottom-left and top-right corners move
+        let points = [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)];
+        let explicit = [Some((10.0, 0.0)), None, Some((30.0, 20.0)), None];
+        let deltas = infer_untouched(&points, &[3], &explicit);
+        assert_eq!(deltas[1], (30.0, 0.0));
+        assert_eq!(deltas[3], (10.0, 20.0));
+
+        // A single touched point moves the whole contour
+        let explicit = [None, Some((5.0, -5.0)), None, None];
+        let deltas = infer_untouched(&points, &[3], &explicit);
+        assert!(deltas.iter().all(|&delta| delta == (5.0, -5.0)));
+    }
+}
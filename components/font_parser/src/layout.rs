@@ -0,0 +1,459 @@
+//! OpenType layout table (GSUB/GPOS) introspection
+//!
+//! GSUB and GPOS share the script, language system and feature lists parsed
+//! here. Lookups are left to the shaping engine; this module only answers
+//! which features a font offers, e.g. to decide between native small caps
+//! (`smcp`) and synthesized ones.
+
+use crate::limits;
+use crate::types::Tag;
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Cursor;
+
+/// Script tag used for features that apply to any script
+const DEFAULT_SCRIPT: &str = "DFLT";
+
+/// Language system: the features enabled for a script/language pair
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LanguageSystem {
+    /// Index of the feature that must always be applied, if any
+    pub required_feature: Option<u16>,
+    /// Indices into the table's feature list
+    pub feature_indices: Vec<u16>,
+}
+
+/// Script record with its default and language-specific feature sets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutScript {
+    /// Script tag (e.g. `latn`, `DFLT`)
+    pub tag: Tag,
+    /// Language system used when no language matches
+    pub default_language: Option<LanguageSystem>,
+    /// Language-specific systems keyed by OpenType language tag
+    pub languages: Vec<(Tag, LanguageSystem)>,
+}
+
+/// Feature record with the lookups it applies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutFeature {
+    /// Feature tag (e.g. `smcp`, `kern`)
+    pub tag: Tag,
+    /// Indices into the table's lookup list
+    pub lookup_indices: Vec<u16>,
+}
+
+/// Glyph substitution (GSUB) or positioning (GPOS) table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutTable {
+    /// Script list
+    pub scripts: Vec<LayoutScript>,
+    /// Feature list, referenced by index from language systems
+    pub features: Vec<LayoutFeature>,
+}
+
+impl LayoutTable {
+    /// Parse a GSUB or GPOS table from raw data
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the table is truncated or has an unsupported
+    /// major version.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let major_version = cursor.read_u16::<BigEndian>()?;
+        let _minor_version = cursor.read_u16::<BigEndian>()?;
+        if major_version != 1 {
+            return Err(ParseError::UnsupportedVersion);
+        }
+        let script_list_offset = cursor.read_u16::<BigEndian>()? as u64;
+        let feature_list_offset = cursor.read_u16::<BigEndian>()? as u64;
+
+        // Every distinct record or index occupies at least two bytes, so a
+        // well-formed table never decodes more entries than its byte length.
+        // Offsets that alias the same subtable could otherwise multiply a
+        // small table into gigabytes of decoded lists.
+        let mut budget = data.len();
+        Ok(LayoutTable {
+            scripts: Self::parse_script_list(data, script_list_offset, &mut budget)?,
+            features: Self::parse_feature_list(data, feature_list_offset, &mut budget)?,
+        })
+    }
+
+    /// Deduct `entries` from the decoding budget
+    fn charge(budget: &mut usize, entries: usize) -> Result<(), ParseError> {
+        *budget = budget.checked_sub(entries).ok_or_else(|| {
+            ParseError::CorruptedData("layout table offsets alias excessively".to_string())
+        })?;
+        Ok(())
+    }
+
+    fn parse_script_list(
+        data: &[u8],
+        offset: u64,
+        budget: &mut usize,
+    ) -> Result<Vec<LayoutScript>, ParseError> {
+        if offset == 0 {
+            return Ok(Vec::new());
+        }
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(offset);
+        let count = cursor.read_u16::<BigEndian>()?;
+        limits::check_declared(
+            "script list",
+            count as usize,
+            6,
+            limits::remaining(data, cursor.position()),
+        )?;
+        let mut records = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let tag = Tag::from_bytes(cursor.read_u32::<BigEndian>()?);
+            let script_offset = cursor.read_u16::<BigEndian>()? as u64;
+            records.push((tag, offset + script_offset));
+        }
+
+        records
+            .into_iter()
+            .map(|(tag, script_offset)| {
+                cursor.set_position(script_offset);
+                let default_offset = cursor.read_u16::<BigEndian>()? as u64;
+                let language_count = cursor.read_u16::<BigEndian>()?;
+                Self::charge(budget, 1 + language_count as usize)?;
+                limits::check_declared(
+                    "script language records",
+                    language_count as usize,
+                    6,
+                    limits::remaining(data, cursor.position()),
+                )?;
+                let mut language_records = Vec::with_capacity(language_count as usize);
+                for _ in 0..language_count {
+                    let language = Tag::from_bytes(cursor.read_u32::<BigEndian>()?);
+                    let lang_offset = cursor.read_u16::<BigEndian>()? as u64;
+                    language_records.push((language, script_offset + lang_offset));
+                }
+
+                let default_language = if default_offset == 0 {
+                    None
+                } else {
+                    Some(Self::parse_language_system(
+                        data,
+                        script_offset + default_offset,
+                        budget,
+                    )?)
+                };
+                let languages = language_records
+                    .into_iter()
+                    .map(|(language, lang_offset)| {
+                        Ok((
+                            language,
+                            Self::parse_language_system(data, lang_offset, budget)?,
+                        ))
+                    })
+                    .collect::<Result<_, ParseError>>()?;
+
+                Ok(LayoutScript {
+                    tag,
+                    default_language,
+                    languages,
+                })
+            })
+            .collect()
+    }
+
+    fn parse_language_system(
+        data: &[u8],
+        offset: u64,
+        budget: &mut usize,
+    ) -> Result<LanguageSystem, ParseError> {
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(offset);
+        let _lookup_order_offset = cursor.read_u16::<BigEndian>()?;
+        let required_feature = cursor.read_u16::<BigEndian>()?;
+        let count = cursor.read_u16::<BigEndian>()?;
+        Self::charge(budget, count as usize)?;
+        let feature_indices = (0..count)
+            .map(|_| cursor.read_u16::<BigEndian>())
+            .collect::<Result<_, _>>()?;
+
+        Ok(LanguageSystem {
+            required_feature: (required_feature != 0xFFFF).then_some(required_feature),
+            feature_indices,
+        })
+    }
+
+    fn parse_feature_list(
+        data: &[u8],
+        offset: u64,
+        budget: &mut usize,
+    ) -> Result<Vec<LayoutFeature>, ParseError> {
+        if offset == 0 {
+            return Ok(Vec::new());
+        }
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(offset);
+        let count = cursor.read_u16::<BigEndian>()?;
+        limits::check_declared(
+            "feature list",
+            count as usize,
+            6,
+            limits::remaining(data, cursor.position()),
+        )?;
+        let mut records = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let tag = Tag::from_bytes(cursor.read_u32::<BigEndian>()?);
+            let feature_offset = cursor.read_u16::<BigEndian>()? as u64;
+            records.push((tag, offset + feature_offset));
+        }
+
+        records
+            .into_iter()
+            .map(|(tag, feature_offset)| {
+                cursor.set_position(feature_offset);
+                let _feature_params_offset = cursor.read_u16::<BigEndian>()?;
+                let lookup_count = cursor.read_u16::<BigEndian>()?;
+                Self::charge(budget, 1 + lookup_count as usize)?;
+                let lookup_indices = (0..lookup_count)
+                    .map(|_| cursor.read_u16::<BigEndian>())
+                    .collect::<Result<_, _>>()?;
+                Ok(LayoutFeature {
+                    tag,
+                    lookup_indices,
+                })
+            })
+            .collect()
+    }
+
+    /// Check whether any script offers the feature
+    pub fn has_feature(&self, feature: Tag) -> bool {
+        self.features.iter().any(|record| record.tag == feature)
+    }
+
+    /// Distinct feature tags in the table, sorted
+    pub fn feature_tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = self.features.iter().map(|record| record.tag).collect();
+        tags.sort_by_key(Tag::to_bytes);
+        tags.dedup();
+        tags
+    }
+
+    /// Script tags in the table
+    pub fn script_tags(&self) -> Vec<Tag> {
+        self.scripts.iter().map(|script| script.tag).collect()
+    }
+
+    /// Features enabled for `script`'s default language system
+    ///
+    /// Falls back to the `DFLT` script when the font has no entry for
+    /// `script`, as shaping engines do.
+    pub fn script_features(&self, script: Tag) -> Vec<Tag> {
+        let Some(record) = self.script_or_default(script) else {
+            return Vec::new();
+        };
+        record
+            .default_language
+            .as_ref()
+            .map(|language_system| self.language_system_features(language_system))
+            .unwrap_or_default()
+    }
+
+    /// Check whether `feature` is enabled for `script` (see [`Self::script_features`])
+    pub fn has_script_feature(&self, script: Tag, feature: Tag) -> bool {
+        self.script_features(script).contains(&feature)
+    }
+
+    /// Features enabled for an OpenType language system of `script`
+    ///
+    /// Uses the script's default language system when the font has no
+    /// entry for `language`, and the `DFLT` script as in
+    /// [`Self::script_features`].
+    pub fn language_features(&self, script: Tag, language: Tag) -> Vec<Tag> {
+        let Some(record) = self.script_or_default(script) else {
+            return Vec::new();
+        };
+        match record.languages.iter().find(|(tag, _)| *tag == language) {
+            Some((_, language_system)) => self.language_system_features(language_system),
+            None => self.script_features(script),
+        }
+    }
+
+    /// Check whether the font defines a language system for `language` under
+    /// `script` (or `DFLT`) that enables `feature`
+    ///
+    /// Unlike [`Self::language_features`], the default language system does
+    /// not count, so this tells whether e.g. `locl` localizes forms for the
+    /// language.
+    pub fn has_language_feature(&self, script: Tag, language: Tag, feature: Tag) -> bool {
+        self.script_or_default(script).is_some_and(|record| {
+            record
+                .languages
+                .iter()
+                .find(|(tag, _)| *tag == language)
+                .is_some_and(|(_, language_system)| {
+                    self.language_system_features(language_system)
+                        .contains(&feature)
+                })
+        })
+    }
+
+    fn language_system_features(&self, language_system: &LanguageSystem) -> Vec<Tag> {
+        language_system
+            .required_feature
+            .iter()
+            .chain(&language_system.feature_indices)
+            .filter_map(|&index| self.features.get(index as usize))
+            .map(|record| record.tag)
+            .collect()
+    }
+
+    fn script_or_default(&self, script: Tag) -> Option<&LayoutScript> {
+        let default_script = Tag::new(DEFAULT_SCRIPT).expect("valid tag");
+        self.script(script).or_else(|| self.script(default_script))
+    }
+
+    fn script(&self, tag: Tag) -> Option<&LayoutScript> {
+        self.scripts.iter().find(|script| script.tag == tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(s: &str) -> Tag {
+        Tag::new(s).unwrap()
+    }
+
+    /// GSUB with one `latn` script (default language enabling feature 1)
+    /// and features `liga` and `smcp`
+    fn gsub_table() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+        data.extend_from_slice(&0u16.to_be_bytes()); // minorVersion
+        data.extend_from_slice(&10u16.to_be_bytes()); // scriptListOffset
+        data.extend_from_slice(&30u16.to_be_bytes()); // featureListOffset
+        data.extend_from_slice(&0u16.to_be_bytes()); // lookupListOffset
+
+        // ScriptList at 10
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(b"latn");
+        data.extend_from_slice(&8u16.to_be_bytes()); // Script at 18
+                                                     // Script at 18
+        data.extend_from_slice(&4u16.to_be_bytes()); // defaultLangSys at 22
+        data.extend_from_slice(&0u16.to_be_bytes()); // langSysCount
+                                                     // LangSys at 22
+        data.extend_from_slice(&0u16.to_be_bytes()); // lookupOrderOffset
+        data.extend_from_slice(&0xFFFFu16.to_be_bytes()); // no required feature
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes()); // feature index 1
+
+        // FeatureList at 30
+        data.extend_from_slice(&2u16.to_be_bytes());
+        data.extend_from_slice(b"liga");
+        data.extend_from_slice(&14u16.to_be_bytes());
+        data.extend_from_slice(b"smcp");
+        data.extend_from_slice(&20u16.to_be_bytes());
+        // Feature tables
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn test_parse_feature_list() {
+        let gsub = LayoutTable::parse(&gsub_table()).unwrap();
+        assert_eq!(gsub.feature_tags(), vec![tag("liga"), tag("smcp")]);
+        assert_eq!(gsub.features[1].lookup_indices, vec![1]);
+        assert!(gsub.has_feature(tag("smcp")));
+        assert!(!gsub.has_feature(tag("c2sc")));
+    }
+
+    #[test]
+    fn test_script_features_follow_language_system() {
+        let gsub = LayoutTable::parse(&gsub_table()).unwrap();
+        assert_eq!(gsub.script_tags(), vec![tag("latn")]);
+        assert_eq!(gsub.script_features(tag("latn")), vec![tag("smcp")]);
+        assert!(!gsub.has_script_feature(tag("latn"), tag("liga")));
+        // No DFLT script to fall back to
+        assert!(gsub.script_features(tag("cyrl")).is_empty());
+    }
+
+    #[test]
+    fn test_language_features_use_language_system() {
+        let feature = |name: &str| LayoutFeature {
+            tag: tag(name),
+            lookup_indices: Vec::new(),
+        };
+        let language_system = |indices: Vec<u16>| LanguageSystem {
+            required_feature: None,
+            feature_indices: indices,
+        };
+        let gsub = LayoutTable {
+            scripts: vec![LayoutScript {
+                tag: tag("DFLT"),
+                default_language: Some(language_system(vec![0])),
+                languages: vec![(tag("TRK "), language_system(vec![0, 1]))],
+            }],
+            features: vec![feature("liga"), feature("locl")],
+        };
+
+        assert_eq!(
+            gsub.language_features(tag("latn"), tag("TRK ")),
+            vec![tag("liga"), tag("locl")]
+        );
+        assert_eq!(
+            gsub.language_features(tag("latn"), tag("DEU ")),
+            vec![tag("liga")]
+        );
+        assert!(gsub.has_language_feature(tag("latn"), tag("TRK "), tag("locl")));
+        assert!(!gsub.has_language_feature(tag("latn"), tag("DEU "), tag("liga")));
+    }
+
+    #[test]
+    fn test_parse_rejects_truncated_and_unknown_versions() {
+        assert!(LayoutTable::parse(&gsub_table()[..20]).is_err());
+        let mut data = gsub_table();
+        data[1] = 2;
+        assert!(LayoutTable::parse(&data).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_aliased_offsets() {
+        // 1000 scripts sharing one Script, whose 1000 languages share one
+        // LangSys of 1000 indices: 14 KB that would decode to 10^9 entries
+        let count = 1000u16;
+        let script_list = 10u16;
+        let script = script_list + 2 + count * 6;
+        let lang_sys = script + 4 + count * 6;
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+        data.extend_from_slice(&0u16.to_be_bytes()); // minorVersion
+        data.extend_from_slice(&script_list.to_be_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes()); // featureListOffset
+        data.extend_from_slice(&0u16.to_be_bytes()); // lookupListOffset
+        data.extend_from_slice(&count.to_be_bytes());
+        for _ in 0..count {
+            data.extend_from_slice(b"latn");
+            data.extend_from_slice(&(script - script_list).to_be_bytes());
+        }
+        data.extend_from_slice(&0u16.to_be_bytes()); // defaultLangSys
+        data.extend_from_slice(&count.to_be_bytes());
+        for _ in 0..count {
+            data.extend_from_slice(b"ENG ");
+            data.extend_from_slice(&(lang_sys - script).to_be_bytes());
+        }
+        data.extend_from_slice(&0u16.to_be_bytes()); // lookupOrderOffset
+        data.extend_from_slice(&0xFFFFu16.to_be_bytes());
+        data.extend_from_slice(&count.to_be_bytes());
+        data.resize(data.len() + count as usize * 2, 0);
+
+        assert!(matches!(
+            LayoutTable::parse(&data),
+            Err(ParseError::CorruptedData(_))
+        ));
+    }
+}
@@ -3,24 +3,52 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+mod base;
+mod cmap;
 mod color_fonts;
+mod colr_v1;
 mod error;
+mod glyf;
+mod gvar;
+mod layout;
+pub mod limits;
+mod math;
+mod metric_variations;
+mod name;
+mod post;
 pub mod types;
+mod validation;
 mod variable_fonts;
 mod woff;
 mod woff2;
 
 // Public exports
+pub use base::{BaseAxis, BaseScript, BaseTable};
 pub use color_fonts::{
-    BaseGlyph, CbdtTable, Color, ColorFormat, ColrTable, CpalTable, Layer, SvgTable,
+    BaseGlyph, CbdtTable, Color, ColorFormat, ColrTable, CpalTable, Layer, SvgDocumentRecord,
+    SvgTable, PALETTE_USABLE_WITH_DARK_BACKGROUND, PALETTE_USABLE_WITH_LIGHT_BACKGROUND,
 };
+pub use colr_v1::{Affine, ColorLine, ColorStop, CompositeMode, Extend, Paint};
 pub use error::ParseError;
+pub use gvar::GvarTable;
+pub use layout::{LanguageSystem, LayoutFeature, LayoutScript, LayoutTable};
+pub use math::{
+    GlyphAssembly, GlyphConstruction, GlyphPart, GlyphVariant, MathConstants, MathGlyphInfo,
+    MathKern, MathKernInfo, MathTable, MathVariants,
+};
+pub use metric_variations::{HvarTable, MvarTable};
+pub use name::NameTable;
+pub use post::PostTable;
 pub use types::{
-    BoundingBox, CMapTable, Contour, FontMetrics, GlyphId, GlyphOutline, OpenTypeFont, Tag,
-    TagParseError,
+    BoundingBox, CMapTable, ChecksumReport, Contour, FontMetrics, GlyphId, GlyphOutline,
+    OpenTypeFont, ParseOptions, Tag, TagParseError, VerticalGlyphMetrics,
+};
+pub use validation::{
+    validate, validate_with_options, SanitizedFont, ValidationError, ValidationOptions,
 };
 pub use variable_fonts::{
-    AvarTable, AxisSegmentMap, FvarTable, NamedInstance, VariationAxis, VariationCoordinates,
+    AvarTable, AxisSegmentMap, AxisValue, AxisValueKind, FvarTable, NamedInstance, StatAxisRecord,
+    StatTable, VariationAxis, VariationCoordinates,
 };
 pub use woff::WoffFont;
 pub use woff2::Woff2Font;
@@ -0,0 +1,95 @@
+//! Hard limits applied while parsing untrusted font data
+//!
+//! Every count read from a font is checked against the bytes that are
+//! actually available before anything is allocated for it. The caps below
+//! additionally bound structures whose decoded size is not proportional to
+//! the table size, e.g. CPAL palettes that share color records. They sit far
+//! above anything seen in shipping fonts.
+
+use crate::ParseError;
+
+/// Maximum number of variation axes in fvar/avar
+pub const MAX_VARIATION_AXES: usize = 64;
+
+/// Maximum number of named instances in fvar
+pub const MAX_NAMED_INSTANCES: usize = 4096;
+
+/// Maximum number of colors across all CPAL palettes
+pub const MAX_PALETTE_COLORS: usize = 1 << 20;
+
+/// Maximum number of layers across all COLR base glyphs
+pub const MAX_COLOR_LAYERS: usize = 1 << 20;
+
+/// Maximum nesting depth of a COLR paint graph
+pub const MAX_PAINT_DEPTH: usize = 64;
+
+/// Maximum number of paints resolved for one COLR glyph
+pub const MAX_PAINT_NODES: usize = 1 << 16;
+
+/// Maximum nesting depth of composite glyphs
+pub const MAX_COMPONENT_DEPTH: usize = 8;
+
+/// Maximum number of component references resolved for one glyph outline
+pub const MAX_GLYPH_COMPONENTS: usize = 4096;
+
+/// Maximum size of a decompressed SVG glyph document
+pub const MAX_SVG_DOCUMENT_SIZE: usize = 16 * 1024 * 1024;
+
+/// Maximum size of an sfnt decoded from a WOFF or WOFF2 container
+pub const MAX_DECOMPRESSED_SIZE: usize = 256 * 1024 * 1024;
+
+/// Check that `count` records of `record_size` bytes fit in `available` bytes
+pub(crate) fn check_declared(
+    what: &str,
+    count: usize,
+    record_size: usize,
+    available: usize,
+) -> Result<(), ParseError> {
+    match count.checked_mul(record_size) {
+        Some(needed) if needed <= available => Ok(()),
+        _ => Err(ParseError::CorruptedData(format!(
+            "{} declares {} records but only {} bytes are available",
+            what, count, available
+        ))),
+    }
+}
+
+/// Check a decoded count against one of the hard caps above
+pub(crate) fn check_cap(what: &str, count: usize, max: usize) -> Result<(), ParseError> {
+    if count > max {
+        return Err(ParseError::CorruptedData(format!(
+            "{} count {} exceeds limit of {}",
+            what, count, max
+        )));
+    }
+    Ok(())
+}
+
+/// Bytes remaining after `position` in `data`
+pub(crate) fn remaining(data: &[u8], position: u64) -> usize {
+    data.len().saturating_sub(position as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_declared() {
+        assert!(check_declared("test", 4, 4, 16).is_ok());
+        assert!(check_declared("test", 5, 4, 16).is_err());
+        assert!(check_declared("test", usize::MAX, 2, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_check_cap() {
+        assert!(check_cap("test", 64, MAX_VARIATION_AXES).is_ok());
+        assert!(check_cap("test", 65, MAX_VARIATION_AXES).is_err());
+    }
+
+    #[test]
+    fn test_remaining_saturates() {
+        assert_eq!(remaining(&[0; 8], 3), 5);
+        assert_eq!(remaining(&[0; 8], 100), 0);
+    }
+}
@@ -0,0 +1,735 @@
+//! Mathematical typesetting table (MATH)
+//!
+//! MATH gives math layout engines (MathML, TeX-style layout) the font's
+//! constants for placing scripts, fractions, radicals and limits, per-glyph
+//! italic corrections, accent attachment points and cut-in kerns, and the
+//! larger variants and part assemblies used to stretch delimiters, radicals
+//! and arrows. All values are in font units; device tables (per-size pixel
+//! adjustments) are not parsed.
+
+use crate::limits;
+use crate::types::GlyphId;
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+
+/// Glyph IDs are 16-bit, so a coverage table lists at most this many glyphs
+const MAX_COVERAGE_GLYPHS: usize = 1 << 16;
+
+/// Font-wide math layout constants
+///
+/// Percentages are whole numbers (e.g. 70 for 70%); everything else is in
+/// font units. Names follow the OpenType specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MathConstants {
+    /// Scale of the first level of scripts, in percent
+    pub script_percent_scale_down: i16,
+    /// Scale of the second level of scripts, in percent
+    pub script_script_percent_scale_down: i16,
+    /// Minimum height of an expression that needs delimiters
+    pub delimited_sub_formula_min_height: u16,
+    /// Minimum height of n-ary operators (e.g. ∑) in display style
+    pub display_operator_min_height: u16,
+    /// White space between consecutive lines of a formula
+    pub math_leading: i16,
+    /// Height of the math axis above the baseline
+    pub axis_height: i16,
+    /// Maximum height of a base that accents don't need to be raised over
+    pub accent_base_height: i16,
+    /// Maximum height of a base that takes unflattened accents
+    pub flattened_accent_base_height: i16,
+    /// Standard shift down of subscripts
+    pub subscript_shift_down: i16,
+    /// Maximum height of the top of a subscript above the baseline
+    pub subscript_top_max: i16,
+    /// Minimum drop of a subscript's baseline below the base's bottom
+    pub subscript_baseline_drop_min: i16,
+    /// Standard shift up of superscripts
+    pub superscript_shift_up: i16,
+    /// Standard shift up of superscripts in cramped style
+    pub superscript_shift_up_cramped: i16,
+    /// Minimum height of the bottom of a superscript above the baseline
+    pub superscript_bottom_min: i16,
+    /// Maximum drop of a superscript's baseline below the base's top
+    pub superscript_baseline_drop_max: i16,
+    /// Minimum gap between a superscript's bottom and a subscript's top
+    pub sub_superscript_gap_min: i16,
+    /// Maximum height of a superscript's bottom when a subscript is
+    /// present and the gap between them is adjusted
+    pub superscript_bottom_max_with_subscript: i16,
+    /// Extra white space after a subscript or superscript
+    pub space_after_script: i16,
+    /// Minimum gap between an upper limit's bottom and the base's top
+    pub upper_limit_gap_min: i16,
+    /// Minimum rise of an upper limit's baseline above the base's top
+    pub upper_limit_baseline_rise_min: i16,
+    /// Minimum gap between a lower limit's top and the base's bottom
+    pub lower_limit_gap_min: i16,
+    /// Minimum drop of a lower limit's baseline below the base's bottom
+    pub lower_limit_baseline_drop_min: i16,
+    /// Standard shift up of the top element of a stack
+    pub stack_top_shift_up: i16,
+    /// Standard shift up of the top element of a stack in display style
+    pub stack_top_display_style_shift_up: i16,
+    /// Standard shift down of the bottom element of a stack
+    pub stack_bottom_shift_down: i16,
+    /// Standard shift down of the bottom element of a stack in display style
+    pub stack_bottom_display_style_shift_down: i16,
+    /// Minimum gap between the elements of a stack
+    pub stack_gap_min: i16,
+    /// Minimum gap between the elements of a stack in display style
+    pub stack_display_style_gap_min: i16,
+    /// Standard shift up of the element above a stretched glyph
+    pub stretch_stack_top_shift_up: i16,
+    /// Standard shift down of the element below a stretched glyph
+    pub stretch_stack_bottom_shift_down: i16,
+    /// Minimum gap between a stretched glyph and the element above it
+    pub stretch_stack_gap_above_min: i16,
+    /// Minimum gap between a stretched glyph and the element below it
+    pub stretch_stack_gap_below_min: i16,
+    /// Standard shift up of a fraction's numerator
+    pub fraction_numerator_shift_up: i16,
+    /// Standard shift up of a fraction's numerator in display style
+    pub fraction_numerator_display_style_shift_up: i16,
+    /// Standard shift down of a fraction's denominator
+    pub fraction_denominator_shift_down: i16,
+    /// Standard shift down of a fraction's denominator in display style
+    pub fraction_denominator_display_style_shift_down: i16,
+    /// Minimum gap between a numerator's bottom and the fraction bar
+    pub fraction_numerator_gap_min: i16,
+    /// Minimum gap between a numerator's bottom and the fraction bar in
+    /// display style
+    pub fraction_num_display_style_gap_min: i16,
+    /// Thickness of the fraction bar
+    pub fraction_rule_thickness: i16,
+    /// Minimum gap between the fraction bar and a denominator's top
+    pub fraction_denominator_gap_min: i16,
+    /// Minimum gap between the fraction bar and a denominator's top in
+    /// display style
+    pub fraction_denom_display_style_gap_min: i16,
+    /// Horizontal gap around the slash of a skewed fraction
+    pub skewed_fraction_horizontal_gap: i16,
+    /// Vertical gap around the slash of a skewed fraction
+    pub skewed_fraction_vertical_gap: i16,
+    /// Gap between an overbar and the top of the base
+    pub overbar_vertical_gap: i16,
+    /// Thickness of an overbar
+    pub overbar_rule_thickness: i16,
+    /// Extra white space above an overbar
+    pub overbar_extra_ascender: i16,
+    /// Gap between an underbar and the bottom of the base
+    pub underbar_vertical_gap: i16,
+    /// Thickness of an underbar
+    pub underbar_rule_thickness: i16,
+    /// Extra white space below an underbar
+    pub underbar_extra_descender: i16,
+    /// Gap between the radical's overbar and the radicand
+    pub radical_vertical_gap: i16,
+    /// Gap between the radical's overbar and the radicand in display style
+    pub radical_display_style_vertical_gap: i16,
+    /// Thickness of the radical's overbar
+    pub radical_rule_thickness: i16,
+    /// Extra white space above the radical's overbar
+    pub radical_extra_ascender: i16,
+    /// Horizontal kern before a radical's degree
+    pub radical_kern_before_degree: i16,
+    /// Horizontal kern after a radical's degree
+    pub radical_kern_after_degree: i16,
+    /// Rise of a radical degree's bottom, in percent of the radical's height
+    pub radical_degree_bottom_raise_percent: i16,
+}
+
+/// Kern of one corner of a glyph, varying with height
+///
+/// Used to tuck scripts into the shape of a base glyph (cut-ins).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MathKern {
+    /// Heights, ascending, at which the kern changes
+    pub correction_heights: Vec<i16>,
+    /// Kerns below the first height, between consecutive heights and above
+    /// the last one (one more than `correction_heights`)
+    pub kern_values: Vec<i16>,
+}
+
+impl MathKern {
+    /// Kern at `height` above the baseline
+    pub fn kern_at(&self, height: i16) -> i16 {
+        let index = self
+            .correction_heights
+            .iter()
+            .take_while(|&&correction_height| height >= correction_height)
+            .count();
+        self.kern_values
+            .get(index)
+            .or(self.kern_values.last())
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// Kerns of a glyph's four corners
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MathKernInfo {
+    /// Kern for superscripts after the glyph
+    pub top_right: Option<MathKern>,
+    /// Kern for prescripts above before the glyph
+    pub top_left: Option<MathKern>,
+    /// Kern for subscripts after the glyph
+    pub bottom_right: Option<MathKern>,
+    /// Kern for prescripts below before the glyph
+    pub bottom_left: Option<MathKern>,
+}
+
+/// Per-glyph math positioning data
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MathGlyphInfo {
+    /// Italic correction, added after slanted glyphs before upright ones
+    /// and used to shift superscripts
+    pub italics_corrections: HashMap<GlyphId, i16>,
+    /// Horizontal position to center accents over, if not half the advance
+    pub top_accent_attachments: HashMap<GlyphId, i16>,
+    /// Glyphs that are extended shapes (e.g. large operators, stretched
+    /// delimiters), whose scripts are placed relative to their ink
+    pub extended_shapes: HashSet<GlyphId>,
+    /// Cut-in kerns
+    pub kerns: HashMap<GlyphId, MathKernInfo>,
+}
+
+impl MathGlyphInfo {
+    /// Italic correction of `glyph`, if the font gives one
+    pub fn italics_correction(&self, glyph: GlyphId) -> Option<i16> {
+        self.italics_corrections.get(&glyph).copied()
+    }
+
+    /// Accent attachment point of `glyph`, if the font gives one
+    pub fn top_accent_attachment(&self, glyph: GlyphId) -> Option<i16> {
+        self.top_accent_attachments.get(&glyph).copied()
+    }
+
+    /// Whether `glyph` is an extended shape
+    pub fn is_extended_shape(&self, glyph: GlyphId) -> bool {
+        self.extended_shapes.contains(&glyph)
+    }
+}
+
+/// A larger variant of a glyph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphVariant {
+    /// Variant glyph
+    pub glyph: GlyphId,
+    /// Size of the variant along the stretch direction
+    pub advance: u16,
+}
+
+/// A part of a glyph assembly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphPart {
+    /// Part glyph
+    pub glyph: GlyphId,
+    /// Length of the connector at the part's start (bottom or left)
+    pub start_connector_length: u16,
+    /// Length of the connector at the part's end (top or right)
+    pub end_connector_length: u16,
+    /// Size of the part along the stretch direction
+    pub full_advance: u16,
+    /// Whether the part can be repeated to grow the assembly
+    pub is_extender: bool,
+}
+
+/// Parts a stretched glyph is built from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GlyphAssembly {
+    /// Italic correction of the assembled glyph
+    pub italics_correction: i16,
+    /// Parts from bottom to top, or left to right
+    pub parts: Vec<GlyphPart>,
+}
+
+/// How a glyph stretches in one direction
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GlyphConstruction {
+    /// Variants, in increasing size, starting with the glyph itself
+    pub variants: Vec<GlyphVariant>,
+    /// Assembly for sizes beyond the largest variant
+    pub assembly: Option<GlyphAssembly>,
+}
+
+/// Stretchy glyph variants and assemblies
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MathVariants {
+    /// Minimum overlap of connecting parts in an assembly
+    pub min_connector_overlap: u16,
+    /// Constructions of glyphs that stretch vertically, e.g. parentheses
+    pub vertical: HashMap<GlyphId, GlyphConstruction>,
+    /// Constructions of glyphs that stretch horizontally, e.g. arrows
+    pub horizontal: HashMap<GlyphId, GlyphConstruction>,
+}
+
+/// Mathematical typesetting table (MATH)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MathTable {
+    /// Font-wide layout constants
+    pub constants: MathConstants,
+    /// Per-glyph positioning data
+    pub glyph_info: MathGlyphInfo,
+    /// Stretchy glyph constructions
+    pub variants: MathVariants,
+}
+
+impl MathTable {
+    /// Parse a MATH table from raw data
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the table is truncated or has an unsupported
+    /// major version.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let major_version = cursor.read_u16::<BigEndian>()?;
+        let _minor_version = cursor.read_u16::<BigEndian>()?;
+        if major_version != 1 {
+            return Err(ParseError::UnsupportedVersion);
+        }
+        let constants_offset = cursor.read_u16::<BigEndian>()? as u64;
+        let glyph_info_offset = cursor.read_u16::<BigEndian>()? as u64;
+        let variants_offset = cursor.read_u16::<BigEndian>()? as u64;
+
+        // Constructions and kerns may be shared between glyphs; each decoded
+        // record is charged against the table's length so aliasing can't
+        // multiply a small table into a huge one
+        let mut budget = data.len();
+        Ok(MathTable {
+            constants: if constants_offset == 0 {
+                MathConstants::default()
+            } else {
+                parse_constants(data, constants_offset)?
+            },
+            glyph_info: if glyph_info_offset == 0 {
+                MathGlyphInfo::default()
+            } else {
+                parse_glyph_info(data, glyph_info_offset, &mut budget)?
+            },
+            variants: if variants_offset == 0 {
+                MathVariants::default()
+            } else {
+                parse_variants(data, variants_offset, &mut budget)?
+            },
+        })
+    }
+}
+
+fn parse_constants(data: &[u8], offset: u64) -> Result<MathConstants, ParseError> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    let script_percent_scale_down = cursor.read_i16::<BigEndian>()?;
+    let script_script_percent_scale_down = cursor.read_i16::<BigEndian>()?;
+    let delimited_sub_formula_min_height = cursor.read_u16::<BigEndian>()?;
+    let display_operator_min_height = cursor.read_u16::<BigEndian>()?;
+
+    // MathValueRecords: a value and a device table offset; fields are
+    // initialized in declaration order, which is the table's order
+    let mut value = || -> Result<i16, ParseError> {
+        let value = cursor.read_i16::<BigEndian>()?;
+        let _device_offset = cursor.read_u16::<BigEndian>()?;
+        Ok(value)
+    };
+    let mut constants = MathConstants {
+        script_percent_scale_down,
+        script_script_percent_scale_down,
+        delimited_sub_formula_min_height,
+        display_operator_min_height,
+        math_leading: value()?,
+        axis_height: value()?,
+        accent_base_height: value()?,
+        flattened_accent_base_height: value()?,
+        subscript_shift_down: value()?,
+        subscript_top_max: value()?,
+        subscript_baseline_drop_min: value()?,
+        superscript_shift_up: value()?,
+        superscript_shift_up_cramped: value()?,
+        superscript_bottom_min: value()?,
+        superscript_baseline_drop_max: value()?,
+        sub_superscript_gap_min: value()?,
+        superscript_bottom_max_with_subscript: value()?,
+        space_after_script: value()?,
+        upper_limit_gap_min: value()?,
+        upper_limit_baseline_rise_min: value()?,
+        lower_limit_gap_min: value()?,
+        lower_limit_baseline_drop_min: value()?,
+        stack_top_shift_up: value()?,
+        stack_top_display_style_shift_up: value()?,
+        stack_bottom_shift_down: value()?,
+        stack_bottom_display_style_shift_down: value()?,
+        stack_gap_min: value()?,
+        stack_display_style_gap_min: value()?,
+        stretch_stack_top_shift_up: value()?,
+        stretch_stack_bottom_shift_down: value()?,
+        stretch_stack_gap_above_min: value()?,
+        stretch_stack_gap_below_min: value()?,
+        fraction_numerator_shift_up: value()?,
+        fraction_numerator_display_style_shift_up: value()?,
+        fraction_denominator_shift_down: value()?,
+        fraction_denominator_display_style_shift_down: value()?,
+        fraction_numerator_gap_min: value()?,
+        fraction_num_display_style_gap_min: value()?,
+        fraction_rule_thickness: value()?,
+        fraction_denominator_gap_min: value()?,
+        fraction_denom_display_style_gap_min: value()?,
+        skewed_fraction_horizontal_gap: value()?,
+        skewed_fraction_vertical_gap: value()?,
+        overbar_vertical_gap: value()?,
+        overbar_rule_thickness: value()?,
+        overbar_extra_ascender: value()?,
+        underbar_vertical_gap: value()?,
+        underbar_rule_thickness: value()?,
+        underbar_extra_descender: value()?,
+        radical_vertical_gap: value()?,
+        radical_display_style_vertical_gap: value()?,
+        radical_rule_thickness: value()?,
+        radical_extra_ascender: value()?,
+        radical_kern_before_degree: value()?,
+        radical_kern_after_degree: value()?,
+        radical_degree_bottom_raise_percent: 0,
+    };
+    constants.radical_degree_bottom_raise_percent = cursor.read_i16::<BigEndian>()?;
+    Ok(constants)
+}
+
+fn parse_glyph_info(
+    data: &[u8],
+    offset: u64,
+    budget: &mut usize,
+) -> Result<MathGlyphInfo, ParseError> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    let italics_offset = cursor.read_u16::<BigEndian>()? as u64;
+    let accents_offset = cursor.read_u16::<BigEndian>()? as u64;
+    let extended_offset = cursor.read_u16::<BigEndian>()? as u64;
+    let kern_offset = cursor.read_u16::<BigEndian>()? as u64;
+
+    let extended_shapes = parse_coverage(data, offset, extended_offset)?
+        .into_iter()
+        .collect();
+    Ok(MathGlyphInfo {
+        italics_corrections: parse_glyph_values(data, offset, italics_offset)?,
+        top_accent_attachments: parse_glyph_values(data, offset, accents_offset)?,
+        extended_shapes,
+        kerns: if kern_offset == 0 {
+            HashMap::new()
+        } else {
+            parse_kern_info(data, offset + kern_offset, budget)?
+        },
+    })
+}
+
+/// Parse a coverage table and its MathValueRecords, as in
+/// MathItalicsCorrectionInfo and MathTopAccentAttachment
+fn parse_glyph_values(
+    data: &[u8],
+    parent: u64,
+    offset: u64,
+) -> Result<HashMap<GlyphId, i16>, ParseError> {
+    if offset == 0 {
+        return Ok(HashMap::new());
+    }
+    let offset = parent + offset;
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    let coverage_offset = cursor.read_u16::<BigEndian>()? as u64;
+    let count = cursor.read_u16::<BigEndian>()?;
+    limits::check_declared(
+        "math value records",
+        count as usize,
+        4,
+        limits::remaining(data, cursor.position()),
+    )?;
+    let mut values = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        values.push(cursor.read_i16::<BigEndian>()?);
+        let _device_offset = cursor.read_u16::<BigEndian>()?;
+    }
+    let glyphs = parse_coverage(data, offset, coverage_offset)?;
+    Ok(glyphs.into_iter().zip(values).collect())
+}
+
+fn parse_kern_info(
+    data: &[u8],
+    offset: u64,
+    budget: &mut usize,
+) -> Result<HashMap<GlyphId, MathKernInfo>, ParseError> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    let coverage_offset = cursor.read_u16::<BigEndian>()? as u64;
+    let count = cursor.read_u16::<BigEndian>()?;
+    limits::check_declared(
+        "math kern info records",
+        count as usize,
+        8,
+        limits::remaining(data, cursor.position()),
+    )?;
+    let records = (0..count)
+        .map(|_| {
+            let mut corners = [0u64; 4];
+            for corner in &mut corners {
+                *corner = cursor.read_u16::<BigEndian>()? as u64;
+            }
+            Ok(corners)
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    let glyphs = parse_coverage(data, offset, coverage_offset)?;
+
+    let mut kern = |corner: u64| -> Result<Option<MathKern>, ParseError> {
+        if corner == 0 {
+            return Ok(None);
+        }
+        parse_kern(data, offset + corner, budget).map(Some)
+    };
+    glyphs
+        .into_iter()
+        .zip(records)
+        .map(
+            |(glyph, [top_right, top_left, bottom_right, bottom_left])| {
+                Ok((
+                    glyph,
+                    MathKernInfo {
+                        top_right: kern(top_right)?,
+                        top_left: kern(top_left)?,
+                        bottom_right: kern(bottom_right)?,
+                        bottom_left: kern(bottom_left)?,
+                    },
+                ))
+            },
+        )
+        .collect()
+}
+
+fn parse_kern(data: &[u8], offset: u64, budget: &mut usize) -> Result<MathKern, ParseError> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    let height_count = cursor.read_u16::<BigEndian>()? as usize;
+    charge(budget, 2 * height_count + 1)?;
+    limits::check_declared(
+        "math kern values",
+        2 * height_count + 1,
+        4,
+        limits::remaining(data, cursor.position()),
+    )?;
+    let mut value = || -> Result<i16, ParseError> {
+        let value = cursor.read_i16::<BigEndian>()?;
+        let _device_offset = cursor.read_u16::<BigEndian>()?;
+        Ok(value)
+    };
+    let correction_heights = (0..height_count)
+        .map(|_| value())
+        .collect::<Result<_, _>>()?;
+    let kern_values = (0..=height_count)
+        .map(|_| value())
+        .collect::<Result<_, _>>()?;
+    Ok(MathKern {
+        correction_heights,
+        kern_values,
+    })
+}
+
+fn parse_variants(
+    data: &[u8],
+    offset: u64,
+    budget: &mut usize,
+) -> Result<MathVariants, ParseError> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    let min_connector_overlap = cursor.read_u16::<BigEndian>()?;
+    let vertical_coverage = cursor.read_u16::<BigEndian>()? as u64;
+    let horizontal_coverage = cursor.read_u16::<BigEndian>()? as u64;
+    let vertical_count = cursor.read_u16::<BigEndian>()?;
+    let horizontal_count = cursor.read_u16::<BigEndian>()?;
+    limits::check_declared(
+        "math glyph constructions",
+        vertical_count as usize + horizontal_count as usize,
+        2,
+        limits::remaining(data, cursor.position()),
+    )?;
+    let mut construction_offsets = |count: u16| {
+        (0..count)
+            .map(|_| cursor.read_u16::<BigEndian>().map(u64::from))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let vertical_offsets = construction_offsets(vertical_count)?;
+    let horizontal_offsets = construction_offsets(horizontal_count)?;
+
+    let mut constructions = |coverage: u64,
+                             offsets: Vec<u64>|
+     -> Result<HashMap<GlyphId, GlyphConstruction>, ParseError> {
+        parse_coverage(data, offset, coverage)?
+            .into_iter()
+            .zip(offsets)
+            .filter(|&(_, construction)| construction != 0)
+            .map(|(glyph, construction)| {
+                Ok((
+                    glyph,
+                    parse_construction(data, offset + construction, budget)?,
+                ))
+            })
+            .collect()
+    };
+    Ok(MathVariants {
+        min_connector_overlap,
+        vertical: constructions(vertical_coverage, vertical_offsets)?,
+        horizontal: constructions(horizontal_coverage, horizontal_offsets)?,
+    })
+}
+
+fn parse_construction(
+    data: &[u8],
+    offset: u64,
+    budget: &mut usize,
+) -> Result<GlyphConstruction, ParseError> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    let assembly_offset = cursor.read_u16::<BigEndian>()? as u64;
+    let variant_count = cursor.read_u16::<BigEndian>()? as usize;
+    charge(budget, 1 + variant_count)?;
+    limits::check_declared(
+        "math glyph variants",
+        variant_count,
+        4,
+        limits::remaining(data, cursor.position()),
+    )?;
+    let variants = (0..variant_count)
+        .map(|_| {
+            Ok(GlyphVariant {
+                glyph: cursor.read_u16::<BigEndian>()?,
+                advance: cursor.read_u16::<BigEndian>()?,
+            })
+        })
+        .collect::<Result<_, ParseError>>()?;
+
+    let assembly = if assembly_offset == 0 {
+        None
+    } else {
+        cursor.set_position(offset + assembly_offset);
+        let italics_correction = cursor.read_i16::<BigEndian>()?;
+        let _device_offset = cursor.read_u16::<BigEndian>()?;
+        let part_count = cursor.read_u16::<BigEndian>()? as usize;
+        charge(budget, part_count)?;
+        limits::check_declared(
+            "glyph assembly parts",
+            part_count,
+            10,
+            limits::remaining(data, cursor.position()),
+        )?;
+        let parts = (0..part_count)
+            .map(|_| {
+                Ok(GlyphPart {
+                    glyph: cursor.read_u16::<BigEndian>()?,
+                    start_connector_length: cursor.read_u16::<BigEndian>()?,
+                    end_connector_length: cursor.read_u16::<BigEndian>()?,
+                    full_advance: cursor.read_u16::<BigEndian>()?,
+                    is_extender: cursor.read_u16::<BigEndian>()? & 1 != 0,
+                })
+            })
+            .collect::<Result<_, ParseError>>()?;
+        Some(GlyphAssembly {
+            italics_correction,
+            parts,
+        })
+    };
+
+    Ok(GlyphConstruction { variants, assembly })
+}
+
+/// Glyphs of the OpenType coverage table at `offset` from `parent`, in
+/// coverage index order; a null offset is an empty coverage
+fn parse_coverage(data: &[u8], parent: u64, offset: u64) -> Result<Vec<GlyphId>, ParseError> {
+    if offset == 0 {
+        return Ok(Vec::new());
+    }
+    let offset = parent + offset;
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    let format = cursor.read_u16::<BigEndian>()?;
+    let count = cursor.read_u16::<BigEndian>()? as usize;
+    match format {
+        1 => {
+            limits::check_declared(
+                "coverage glyphs",
+                count,
+                2,
+                limits::remaining(data, cursor.position()),
+            )?;
+            (0..count)
+                .map(|_| cursor.read_u16::<BigEndian>().map_err(ParseError::from))
+                .collect()
+        }
+        2 => {
+            limits::check_declared(
+                "coverage ranges",
+                count,
+                6,
+                limits::remaining(data, cursor.position()),
+            )?;
+            let mut indexed = Vec::new();
+            for _ in 0..count {
+                let start = cursor.read_u16::<BigEndian>()?;
+                let end = cursor.read_u16::<BigEndian>()?;
+                let start_index = cursor.read_u16::<BigEndian>()? as usize;
+                if start > end {
+                    continue;
+                }
+                limits::check_cap(
+                    "coverage glyphs",
+                    indexed.len() + (end - start) as usize + 1,
+                    MAX_COVERAGE_GLYPHS,
+                )?;
+                indexed.extend(
+                    (start..=end).map(|glyph| (start_index + (glyph - start) as usize, glyph)),
+                );
+            }
+            indexed.sort_unstable();
+            Ok(indexed.into_iter().map(|(_, glyph)| glyph).collect())
+        }
+        _ => Err(ParseError::InvalidFormat),
+    }
+}
+
+/// Charge `records` decoded records against the table's budget
+fn charge(budget: &mut usize, records: usize) -> Result<(), ParseError> {
+    *budget = budget.checked_sub(records).ok_or_else(|| {
+        ParseError::CorruptedData("math table offsets alias excessively".to_string())
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_unknown_major_version() {
+        let data = [0, 2, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(matches!(
+            MathTable::parse(&data),
+            Err(ParseError::UnsupportedVersion)
+        ));
+    }
+
+    #[test]
+    fn test_subtables_are_optional() {
+        let table = MathTable::parse(&[0, 1, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(table, MathTable::default());
+    }
+
+    #[test]
+    fn test_kern_at_height() {
+        let kern = MathKern {
+            correction_heights: vec![100, 300],
+            kern_values: vec![-50, -20, 0],
+        };
+        assert_eq!(kern.kern_at(0), -50);
+        assert_eq!(kern.kern_at(100), -20);
+        assert_eq!(kern.kern_at(299), -20);
+        assert_eq!(kern.kern_at(1000), 0);
+        assert_eq!(MathKern::default().kern_at(10), 0);
+    }
+}
@@ -0,0 +1,372 @@
+//! Metric variations (MVAR, HVAR) parsing
+//!
+//! Both tables store their deltas in an item variation store: rows of
+//! per-region deltas addressed by an (outer, inner) index pair. A delta is the
+//! sum of each region's delta weighted by how strongly the normalized
+//! coordinates fall inside that region. MVAR maps font-wide metric tags
+//! (ascender, x-height, ...) to such rows; HVAR maps glyph IDs to advance
+//! width rows.
+
+use crate::limits::{self, MAX_VARIATION_AXES};
+use crate::types::{GlyphId, Tag};
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Cursor;
+
+/// Outer/inner index pair meaning "no variation"
+const NO_VARIATION_INDEX: (u16, u16) = (0xFFFF, 0xFFFF);
+
+/// Item variation data: deltas use 32/16-bit instead of 16/8-bit values
+const LONG_WORDS: u16 = 0x8000;
+
+/// Item variation data: mask of the count of wide deltas per row
+const WORD_DELTA_COUNT_MASK: u16 = 0x7FFF;
+
+/// Delta set index map: mask of the inner index bit count minus one
+const INNER_INDEX_BIT_COUNT_MASK: u8 = 0x0F;
+
+/// Delta set index map: mask of the entry size minus one
+const MAP_ENTRY_SIZE_MASK: u8 = 0x30;
+
+/// Region of the design space: (start, peak, end) per axis
+type Region = Vec<(f32, f32, f32)>;
+
+/// Item variation store shared by the metric variation tables
+#[derive(Debug, Clone, PartialEq)]
+struct ItemVariationStore<'a> {
+    data: &'a [u8],
+    regions: Vec<Region>,
+    item_data_offsets: Vec<u32>,
+}
+
+impl<'a> ItemVariationStore<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let format = cursor.read_u16::<BigEndian>()?;
+        if format != 1 {
+            return Err(ParseError::CorruptedData(format!(
+                "Unsupported item variation store format {}",
+                format
+            )));
+        }
+        let region_list_offset = cursor.read_u32::<BigEndian>()? as u64;
+        let item_data_count = cursor.read_u16::<BigEndian>()?;
+        limits::check_declared(
+            "item variation data",
+            item_data_count as usize,
+            4,
+            limits::remaining(data, cursor.position()),
+        )?;
+        let mut item_data_offsets = Vec::with_capacity(item_data_count as usize);
+        for _ in 0..item_data_count {
+            item_data_offsets.push(cursor.read_u32::<BigEndian>()?);
+        }
+
+        cursor.set_position(region_list_offset);
+        let axis_count = cursor.read_u16::<BigEndian>()?;
+        let region_count = cursor.read_u16::<BigEndian>()?;
+        limits::check_cap(
+            "variation region axis",
+            axis_count as usize,
+            MAX_VARIATION_AXES,
+        )?;
+        limits::check_declared(
+            "variation regions",
+            region_count as usize,
+            axis_count as usize * 6,
+            limits::remaining(data, cursor.position()),
+        )?;
+        let mut regions = Vec::with_capacity(region_count as usize);
+        for _ in 0..region_count {
+            let mut region = Vec::with_capacity(axis_count as usize);
+            for _ in 0..axis_count {
+                let start = read_f2dot14(&mut cursor)?;
+                let peak = read_f2dot14(&mut cursor)?;
+                let end = read_f2dot14(&mut cursor)?;
+                region.push((start, peak, end));
+            }
+            regions.push(region);
+        }
+
+        Ok(ItemVariationStore {
+            data,
+            regions,
+            item_data_offsets,
+        })
+    }
+
+    /// Interpolated delta of one row, or `None` for an out-of-range index
+    fn delta(&self, outer: u16, inner: u16, coords: &[f32]) -> Option<f32> {
+        if (outer, inner) == NO_VARIATION_INDEX {
+            return Some(0.0);
+        }
+        let offset = *self.item_data_offsets.get(outer as usize)? as u64;
+        let mut cursor = Cursor::new(self.data);
+        cursor.set_position(offset);
+        let item_count = cursor.read_u16::<BigEndian>().ok()?;
+        let word_delta_count = cursor.read_u16::<BigEndian>().ok()?;
+        let region_index_count = cursor.read_u16::<BigEndian>().ok()?;
+        if inner >= item_count {
+            return None;
+        }
+
+        let long_words = word_delta_count & LONG_WORDS != 0;
+        let word_count = word_delta_count & WORD_DELTA_COUNT_MASK;
+        if word_count > region_index_count {
+            return None;
+        }
+        let (word_size, short_size) = if long_words { (4, 2) } else { (2, 1) };
+        let row_size =
+            word_count as u64 * word_size + (region_index_count - word_count) as u64 * short_size;
+
+        let mut region_indices = Vec::with_capacity(region_index_count as usize);
+        for _ in 0..region_index_count {
+            region_indices.push(cursor.read_u16::<BigEndian>().ok()?);
+        }
+        cursor.set_position(cursor.position() + inner as u64 * row_size);
+
+        let mut delta = 0.0;
+        for (i, &region_index) in region_indices.iter().enumerate() {
+            let value = match (i < word_count as usize, long_words) {
+                (true, true) => cursor.read_i32::<BigEndian>().ok()? as f32,
+                (true, false) | (false, true) => cursor.read_i16::<BigEndian>().ok()? as f32,
+                (false, false) => cursor.read_i8().ok()? as f32,
+            };
+            let region = self.regions.get(region_index as usize)?;
+            delta += value * region_scalar(region, coords);
+        }
+        Some(delta)
+    }
+}
+
+/// Metrics Variations Table (MVAR)
+///
+/// Varies font-wide metrics identified by tags such as `hasc` (ascender),
+/// `xhgt` (x-height) or `undo` (underline offset).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MvarTable<'a> {
+    /// Value records as (tag, outer index, inner index)
+    records: Vec<(Tag, u16, u16)>,
+    store: Option<ItemVariationStore<'a>>,
+}
+
+impl<'a> MvarTable<'a> {
+    /// Parse MVAR table from raw data
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the table is truncated or has an unsupported
+    /// version.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let major_version = cursor.read_u16::<BigEndian>()?;
+        let minor_version = cursor.read_u16::<BigEndian>()?;
+        if major_version != 1 {
+            return Err(ParseError::CorruptedData(format!(
+                "Unsupported MVAR version {}.{}",
+                major_version, minor_version
+            )));
+        }
+        let _reserved = cursor.read_u16::<BigEndian>()?;
+        let record_size = cursor.read_u16::<BigEndian>()? as u64;
+        let record_count = cursor.read_u16::<BigEndian>()?;
+        let store_offset = cursor.read_u16::<BigEndian>()? as usize;
+        if record_size < 8 {
+            return Err(ParseError::CorruptedData(format!(
+                "MVAR value record size {} too small",
+                record_size
+            )));
+        }
+        limits::check_declared(
+            "MVAR value records",
+            record_count as usize,
+            record_size as usize,
+            limits::remaining(data, cursor.position()),
+        )?;
+
+        let records_start = cursor.position();
+        let mut records = Vec::with_capacity(record_count as usize);
+        for i in 0..record_count as u64 {
+            cursor.set_position(records_start + i * record_size);
+            let tag = Tag::from_bytes(cursor.read_u32::<BigEndian>()?);
+            let outer = cursor.read_u16::<BigEndian>()?;
+            let inner = cursor.read_u16::<BigEndian>()?;
+            records.push((tag, outer, inner));
+        }
+
+        let store = if store_offset == 0 {
+            None
+        } else {
+            let store_data = data.get(store_offset..).ok_or_else(|| {
+                ParseError::CorruptedData("MVAR item variation store out of bounds".to_string())
+            })?;
+            Some(ItemVariationStore::parse(store_data)?)
+        };
+
+        Ok(MvarTable { records, store })
+    }
+
+    /// Tags of the metrics this table varies
+    pub fn tags(&self) -> impl Iterator<Item = Tag> + '_ {
+        self.records.iter().map(|(tag, _, _)| *tag)
+    }
+
+    /// Delta in font units for a metric tag at normalized coordinates
+    ///
+    /// Returns 0 for metrics the table does not vary.
+    pub fn delta(&self, tag: Tag, coords: &[f32]) -> f32 {
+        let Some(store) = &self.store else {
+            return 0.0;
+        };
+        self.records
+            .iter()
+            .find(|(record_tag, _, _)| *record_tag == tag)
+            .and_then(|&(_, outer, inner)| store.delta(outer, inner, coords))
+            .unwrap_or(0.0)
+    }
+}
+
+/// Horizontal Metrics Variations Table (HVAR)
+///
+/// Varies glyph advance widths. Side bearing mappings are not used: they are
+/// only needed when instancing a font, not when laying out text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HvarTable<'a> {
+    data: &'a [u8],
+    store: ItemVariationStore<'a>,
+    advance_map_offset: usize,
+}
+
+impl<'a> HvarTable<'a> {
+    /// Parse HVAR table from raw data
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the table or its item variation store is
+    /// truncated, or the version is unsupported.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let major_version = cursor.read_u16::<BigEndian>()?;
+        let minor_version = cursor.read_u16::<BigEndian>()?;
+        if major_version != 1 {
+            return Err(ParseError::CorruptedData(format!(
+                "Unsupported HVAR version {}.{}",
+                major_version, minor_version
+            )));
+        }
+        let store_offset = cursor.read_u32::<BigEndian>()? as usize;
+        let advance_map_offset = cursor.read_u32::<BigEndian>()? as usize;
+
+        let store_data = data.get(store_offset..).ok_or_else(|| {
+            ParseError::CorruptedData("HVAR item variation store out of bounds".to_string())
+        })?;
+
+        Ok(HvarTable {
+            data,
+            store: ItemVariationStore::parse(store_data)?,
+            advance_map_offset,
+        })
+    }
+
+    /// Advance width delta in font units for a glyph at normalized coordinates
+    pub fn advance_delta(&self, glyph_id: GlyphId, coords: &[f32]) -> f32 {
+        // Without a mapping, rows are addressed directly by glyph ID
+        let (outer, inner) = if self.advance_map_offset == 0 {
+            Some((0, glyph_id))
+        } else {
+            map_delta_set_index(self.data, self.advance_map_offset, glyph_id)
+        }
+        .unwrap_or(NO_VARIATION_INDEX);
+        self.store.delta(outer, inner, coords).unwrap_or(0.0)
+    }
+}
+
+/// Look up the (outer, inner) index of a glyph in a delta set index map
+///
+/// Glyphs past the end of the map use its last entry.
+fn map_delta_set_index(data: &[u8], offset: usize, glyph_id: GlyphId) -> Option<(u16, u16)> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset as u64);
+    let format = cursor.read_u8().ok()?;
+    let entry_format = cursor.read_u8().ok()?;
+    let map_count = match format {
+        0 => cursor.read_u16::<BigEndian>().ok()? as u32,
+        1 => cursor.read_u32::<BigEndian>().ok()?,
+        _ => return None,
+    };
+    if map_count == 0 {
+        return None;
+    }
+
+    let entry_size = ((entry_format & MAP_ENTRY_SIZE_MASK) >> 4) as u64 + 1;
+    let inner_bits = (entry_format & INNER_INDEX_BIT_COUNT_MASK) as u32 + 1;
+    let index = (glyph_id as u32).min(map_count - 1) as u64;
+    cursor.set_position(cursor.position() + index * entry_size);
+    let mut entry = 0u32;
+    for _ in 0..entry_size {
+        entry = entry << 8 | cursor.read_u8().ok()? as u32;
+    }
+    Some((
+        (entry >> inner_bits) as u16,
+        (entry & ((1 << inner_bits) - 1)) as u16,
+    ))
+}
+
+/// Weight of a variation region at the given normalized coordinates
+fn region_scalar(region: &[(f32, f32, f32)], coords: &[f32]) -> f32 {
+    let mut scalar = 1.0;
+    for (axis, &(start, peak, end)) in region.iter().enumerate() {
+        // Axes with a zero peak or an invalid range do not constrain the region
+        if peak == 0.0 || start > peak || peak > end || (start < 0.0 && end > 0.0) {
+            continue;
+        }
+        let coord = coords.get(axis).copied().unwrap_or(0.0);
+        if coord == peak {
+            continue;
+        }
+        if coord <= start || coord >= end {
+            return 0.0;
+        }
+        scalar *= if coord < peak {
+            (coord - start) / (peak - start)
+        } else {
+            (end - coord) / (end - peak)
+        };
+    }
+    scalar
+}
+
+fn read_f2dot14(cursor: &mut Cursor<&[u8]>) -> Result<f32, ParseError> {
+    Ok(cursor.read_i16::<BigEndian>()? as f32 / 16384.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_scalar() {
+        let region = [(0.0, 1.0, 1.0)];
+        assert_eq!(region_scalar(&region, &[0.5]), 0.5);
+        assert_eq!(region_scalar(&region, &[1.0]), 1.0);
+        assert_eq!(region_scalar(&region, &[-0.5]), 0.0);
+
+        // Intermediate region peaking at 0.5
+        let region = [(0.0, 0.5, 1.0)];
+        assert_eq!(region_scalar(&region, &[0.75]), 0.5);
+
+        // Axes with a zero peak are ignored
+        let region = [(0.0, 1.0, 1.0), (-1.0, 0.0, 1.0)];
+        assert_eq!(region_scalar(&region, &[1.0, -0.3]), 1.0);
+    }
+
+    #[test]
+    fn test_map_delta_set_index() {
+        // Format 0, 1-byte entries with 4 inner bits: 0x12 is (1, 2)
+        let data = [0u8, 0x03, 0, 2, 0x12, 0x05];
+        assert_eq!(map_delta_set_index(&data, 0, 0), Some((1, 2)));
+        assert_eq!(map_delta_set_index(&data, 0, 1), Some((0, 5)));
+        // Past the end: last entry
+        assert_eq!(map_delta_set_index(&data, 0, 9), Some((0, 5)));
+    }
+}
@@ -0,0 +1,253 @@
+//! Naming table (name) parsing
+//!
+//! The name table stores human-readable strings (family, subfamily,
+//! PostScript name, ...) as records keyed by platform, encoding, language and
+//! name ID. Only Unicode records and Macintosh Roman records are decoded.
+
+use crate::limits;
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Cursor;
+
+/// Unicode platform: UTF-16BE strings
+const PLATFORM_UNICODE: u16 = 0;
+
+/// Macintosh platform: single-byte strings in the encoding's script
+const PLATFORM_MACINTOSH: u16 = 1;
+
+/// Windows platform: UTF-16BE strings for the symbol, BMP and full repertoire encodings
+const PLATFORM_WINDOWS: u16 = 3;
+
+/// Macintosh Roman encoding ID
+const MAC_ROMAN: u16 = 0;
+
+/// Windows language ID for English (United States)
+const WINDOWS_ENGLISH_US: u16 = 0x0409;
+
+/// Macintosh language ID for English
+const MAC_ENGLISH: u16 = 0;
+
+/// Characters of Macintosh Roman bytes 0x80-0xFF
+const MAC_ROMAN_HIGH: [char; 128] = [
+    'Ä', 'Å', 'Ç', 'É', 'Ñ', 'Ö', 'Ü', 'á', 'à', 'â', 'ä', 'ã', 'å', 'ç', 'é', 'è', //
+    'ê', 'ë', 'í', 'ì', 'î', 'ï', 'ñ', 'ó', 'ò', 'ô', 'ö', 'õ', 'ú', 'ù', 'û', 'ü', //
+    '†', '°', '¢', '£', '§', '•', '¶', 'ß', '®', '©', '™', '´', '¨', '≠', 'Æ', 'Ø', //
+    '∞', '±', '≤', '≥', '¥', 'µ', '∂', '∑', '∏', 'π', '∫', 'ª', 'º', 'Ω', 'æ', 'ø', //
+    '¿', '¡', '¬', '√', 'ƒ', '≈', '∆', '«', '»', '…', '\u{A0}', 'À', 'Ã', 'Õ', 'Œ', 'œ', //
+    '–', '—', '“', '”', '‘', '’', '÷', '◊', 'ÿ', 'Ÿ', '⁄', '€', '‹', '›', 'ﬁ', 'ﬂ', //
+    '‡', '·', '‚', '„', '‰', 'Â', 'Ê', 'Á', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', 'Ó', 'Ô', //
+    '\u{F8FF}', 'Ò', 'Ú', 'Û', 'Ù', 'ı', 'ˆ', '˜', '¯', '˘', '˙', '˚', '¸', '˝', '˛', 'ˇ',
+];
+
+/// A single name record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NameRecord {
+    platform_id: u16,
+    encoding_id: u16,
+    language_id: u16,
+    name_id: u16,
+    length: u16,
+    offset: u16,
+}
+
+impl NameRecord {
+    /// Lookup preference: English Windows names first, Macintosh names last
+    fn priority(&self) -> Option<u8> {
+        match (self.platform_id, self.encoding_id, self.language_id) {
+            (PLATFORM_WINDOWS, 0 | 1 | 10, WINDOWS_ENGLISH_US) => Some(0),
+            (PLATFORM_UNICODE, _, _) => Some(1),
+            (PLATFORM_WINDOWS, 0 | 1 | 10, _) => Some(2),
+            (PLATFORM_MACINTOSH, MAC_ROMAN, MAC_ENGLISH) => Some(3),
+            _ => None,
+        }
+    }
+}
+
+/// Naming Table (name)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTable<'a> {
+    records: Vec<NameRecord>,
+    storage: &'a [u8],
+}
+
+impl<'a> NameTable<'a> {
+    /// Font family name
+    pub const FAMILY: u16 = 1;
+    /// Font subfamily name (e.g. "Bold Italic")
+    pub const SUBFAMILY: u16 = 2;
+    /// Full font name
+    pub const FULL_NAME: u16 = 4;
+    /// PostScript name
+    pub const POSTSCRIPT_NAME: u16 = 6;
+    /// Typographic family name, when the family groups more than four styles
+    pub const TYPOGRAPHIC_FAMILY: u16 = 16;
+    /// Typographic subfamily name
+    pub const TYPOGRAPHIC_SUBFAMILY: u16 = 17;
+    /// Prefix for PostScript names of variable font instances
+    pub const VARIATIONS_POSTSCRIPT_NAME_PREFIX: u16 = 25;
+
+    /// Parse name table from raw data
+    ///
+    /// Formats 0 and 1 are supported; the language tag records of format 1
+    /// are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the header or name records are truncated, or
+    /// the string storage lies outside the table.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+        let format = cursor.read_u16::<BigEndian>()?;
+        if format > 1 {
+            return Err(ParseError::CorruptedData(format!(
+                "Unsupported name table format {}",
+                format
+            )));
+        }
+        let count = cursor.read_u16::<BigEndian>()?;
+        let storage_offset = cursor.read_u16::<BigEndian>()? as usize;
+        limits::check_declared(
+            "name records",
+            count as usize,
+            12,
+            limits::remaining(data, cursor.position()),
+        )?;
+
+        let mut records = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            records.push(NameRecord {
+                platform_id: cursor.read_u16::<BigEndian>()?,
+                encoding_id: cursor.read_u16::<BigEndian>()?,
+                language_id: cursor.read_u16::<BigEndian>()?,
+                name_id: cursor.read_u16::<BigEndian>()?,
+                length: cursor.read_u16::<BigEndian>()?,
+                offset: cursor.read_u16::<BigEndian>()?,
+            });
+        }
+
+        let storage = data.get(storage_offset..).ok_or_else(|| {
+            ParseError::CorruptedData("name string storage out of bounds".to_string())
+        })?;
+
+        Ok(NameTable { records, storage })
+    }
+
+    /// Get the string for a name ID
+    ///
+    /// Prefers English Windows records, then Unicode, other Windows and
+    /// English Macintosh records. Records whose strings are out of bounds or
+    /// not valid UTF-16 are skipped.
+    pub fn get(&self, name_id: u16) -> Option<String> {
+        let mut candidates: Vec<(u8, &NameRecord)> = self
+            .records
+            .iter()
+            .filter(|record| record.name_id == name_id)
+            .filter_map(|record| Some((record.priority()?, record)))
+            .collect();
+        candidates.sort_by_key(|(priority, _)| *priority);
+        candidates
+            .into_iter()
+            .find_map(|(_, record)| self.decode(record))
+    }
+
+    /// Decode a record's string from the storage area
+    fn decode(&self, record: &NameRecord) -> Option<String> {
+        let start = record.offset as usize;
+        let bytes = self.storage.get(start..start + record.length as usize)?;
+        if record.platform_id == PLATFORM_MACINTOSH {
+            return Some(
+                bytes
+                    .iter()
+                    .map(|&byte| match byte {
+                        0x00..=0x7F => byte as char,
+                        _ => MAC_ROMAN_HIGH[byte as usize - 0x80],
+                    })
+                    .collect(),
+            );
+        }
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a name table from (platform, encoding, language, name ID, bytes)
+    fn name_table(records: &[(u16, u16, u16, u16, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut storage = Vec::new();
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&(records.len() as u16).to_be_bytes());
+        data.extend_from_slice(&(6 + 12 * records.len() as u16).to_be_bytes());
+        for &(platform, encoding, language, name_id, bytes) in records {
+            for value in [platform, encoding, language, name_id] {
+                data.extend_from_slice(&value.to_be_bytes());
+            }
+            data.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+            data.extend_from_slice(&(storage.len() as u16).to_be_bytes());
+            storage.extend_from_slice(bytes);
+        }
+        data.extend(storage);
+        data
+    }
+
+    fn utf16(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    #[test]
+    fn test_mac_roman_table() {
+        assert_eq!(MAC_ROMAN_HIGH[0], 'Ä');
+        assert_eq!(MAC_ROMAN_HIGH[0xDB - 0x80], '€');
+        assert_eq!(MAC_ROMAN_HIGH[127], 'ˇ');
+    }
+
+    #[test]
+    fn test_get_prefers_english_windows_names() {
+        let german = utf16("Fett");
+        let english = utf16("Bold");
+        let data = name_table(&[
+            (1, 0, 0, 2, b"Mac Bold"),
+            (3, 1, 0x0407, 2, &german),
+            (3, 1, 0x0409, 2, &english),
+        ]);
+        let name = NameTable::parse(&data).unwrap();
+
+        assert_eq!(name.get(NameTable::SUBFAMILY).as_deref(), Some("Bold"));
+        assert_eq!(name.get(NameTable::FAMILY), None);
+    }
+
+    #[test]
+    fn test_get_decodes_mac_roman() {
+        let data = name_table(&[(1, 0, 0, 1, b"Caf\x8E")]);
+        let name = NameTable::parse(&data).unwrap();
+
+        assert_eq!(name.get(NameTable::FAMILY).as_deref(), Some("Café"));
+    }
+
+    #[test]
+    fn test_get_skips_unreadable_records() {
+        let valid = utf16("Regular");
+        let mut data = name_table(&[(3, 1, 0x0409, 2, &[0xD8, 0x00]), (0, 3, 0, 2, &valid)]);
+        assert_eq!(
+            NameTable::parse(&data).unwrap().get(2).as_deref(),
+            Some("Regular")
+        );
+
+        // Strings past the end of the table are ignored
+        data.truncate(data.len() - 2);
+        assert_eq!(NameTable::parse(&data).unwrap().get(2), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_truncated_records() {
+        let data = name_table(&[(3, 1, 0x0409, 1, b"")]);
+        assert!(NameTable::parse(&data[..10]).is_err());
+        assert!(NameTable::parse(&[0, 0, 0, 2, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,514 @@
+//! PostScript table (post) parsing
+//!
+//! The post table carries the italic angle, the fixed-pitch flag and, for
+//! formats 1.0 and 2.0, PostScript glyph names used for PDF generation and
+//! debugging.
+
+use crate::limits;
+use crate::types::GlyphId;
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::sync::OnceLock;
+
+/// Number of glyph names in the standard Macintosh glyph order
+const MAC_GLYPH_COUNT: usize = 258;
+
+/// Standard Macintosh glyph order used by post formats 1.0 and 2.0
+const MAC_GLYPH_NAMES: [&str; MAC_GLYPH_COUNT] = [
+    ".notdef",
+    ".null",
+    "nonmarkingreturn",
+    "space",
+    "exclam",
+    "quotedbl",
+    "numbersign",
+    "dollar",
+    "percent",
+    "ampersand",
+    "quotesingle",
+    "parenleft",
+    "parenright",
+    "asterisk",
+    "plus",
+    "comma",
+    "hyphen",
+    "period",
+    "slash",
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "colon",
+    "semicolon",
+    "less",
+    "equal",
+    "greater",
+    "question",
+    "at",
+    "A",
+    "B",
+    "C",
+    "D",
+    "E",
+    "F",
+    "G",
+    "H",
+    "I",
+    "J",
+    "K",
+    "L",
+    "M",
+    "N",
+    "O",
+    "P",
+    "Q",
+    "R",
+    "S",
+    "T",
+    "U",
+    "V",
+    "W",
+    "X",
+    "Y",
+    "Z",
+    "bracketleft",
+    "backslash",
+    "bracketright",
+    "asciicircum",
+    "underscore",
+    "grave",
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "f",
+    "g",
+    "h",
+    "i",
+    "j",
+    "k",
+    "l",
+    "m",
+    "n",
+    "o",
+    "p",
+    "q",
+    "r",
+    "s",
+    "t",
+    "u",
+    "v",
+    "w",
+    "x",
+    "y",
+    "z",
+    "braceleft",
+    "bar",
+    "braceright",
+    "asciitilde",
+    "Adieresis",
+    "Aring",
+    "Ccedilla",
+    "Eacute",
+    "Ntilde",
+    "Odieresis",
+    "Udieresis",
+    "aacute",
+    "agrave",
+    "acircumflex",
+    "adieresis",
+    "atilde",
+    "aring",
+    "ccedilla",
+    "eacute",
+    "egrave",
+    "ecircumflex",
+    "edieresis",
+    "iacute",
+    "igrave",
+    "icircumflex",
+    "idieresis",
+    "ntilde",
+    "oacute",
+    "ograve",
+    "ocircumflex",
+    "odieresis",
+    "otilde",
+    "uacute",
+    "ugrave",
+    "ucircumflex",
+    "udieresis",
+    "dagger",
+    "degree",
+    "cent",
+    "sterling",
+    "section",
+    "bullet",
+    "paragraph",
+    "germandbls",
+    "registered",
+    "copyright",
+    "trademark",
+    "acute",
+    "dieresis",
+    "notequal",
+    "AE",
+    "Oslash",
+    "infinity",
+    "plusminus",
+    "lessequal",
+    "greaterequal",
+    "yen",
+    "mu",
+    "partialdiff",
+    "summation",
+    "product",
+    "pi",
+    "integral",
+    "ordfeminine",
+    "ordmasculine",
+    "Omega",
+    "ae",
+    "oslash",
+    "questiondown",
+    "exclamdown",
+    "logicalnot",
+    "radical",
+    "florin",
+    "approxequal",
+    "Delta",
+    "guillemotleft",
+    "guillemotright",
+    "ellipsis",
+    "nonbreakingspace",
+    "Agrave",
+    "Atilde",
+    "Otilde",
+    "OE",
+    "oe",
+    "endash",
+    "emdash",
+    "quotedblleft",
+    "quotedblright",
+    "quoteleft",
+    "quoteright",
+    "divide",
+    "lozenge",
+    "ydieresis",
+    "Ydieresis",
+    "fraction",
+    "currency",
+    "guilsinglleft",
+    "guilsinglright",
+    "fi",
+    "fl",
+    "daggerdbl",
+    "periodcentered",
+    "quotesinglbase",
+    "quotedblbase",
+    "perthousand",
+    "Acircumflex",
+    "Ecircumflex",
+    "Aacute",
+    "Edieresis",
+    "Egrave",
+    "Iacute",
+    "Icircumflex",
+    "Idieresis",
+    "Igrave",
+    "Oacute",
+    "Ocircumflex",
+    "apple",
+    "Ograve",
+    "Uacute",
+    "Ucircumflex",
+    "Ugrave",
+    "dotlessi",
+    "circumflex",
+    "tilde",
+    "macron",
+    "breve",
+    "dotaccent",
+    "ring",
+    "cedilla",
+    "hungarumlaut",
+    "ogonek",
+    "caron",
+    "Lslash",
+    "lslash",
+    "Scaron",
+    "scaron",
+    "Zcaron",
+    "zcaron",
+    "brokenbar",
+    "Eth",
+    "eth",
+    "Yacute",
+    "yacute",
+    "Thorn",
+    "thorn",
+    "minus",
+    "multiply",
+    "onesuperior",
+    "twosuperior",
+    "threesuperior",
+    "onehalf",
+    "onequarter",
+    "threequarters",
+    "franc",
+    "Gbreve",
+    "gbreve",
+    "Idotaccent",
+    "Scedilla",
+    "scedilla",
+    "Cacute",
+    "cacute",
+    "Ccaron",
+    "ccaron",
+    "dcroat",
+];
+
+/// PostScript Table (post)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostTable {
+    /// Table version (1.0, 2.0, 2.5 or 3.0)
+    pub version: f32,
+    /// Italic angle in counter-clockwise degrees from vertical (negative leans right)
+    pub italic_angle: f32,
+    /// Suggested underline position in font units
+    pub underline_position: i16,
+    /// Suggested underline thickness in font units
+    pub underline_thickness: i16,
+    /// Whether the font is monospaced
+    pub is_fixed_pitch: bool,
+    /// Glyph names indexed by glyph ID (empty for format 3.0)
+    glyph_names: Vec<String>,
+    /// Lowest glyph ID for each name, built on the first lookup by name
+    glyph_ids: OnceLock<HashMap<String, GlyphId>>,
+}
+
+impl PostTable {
+    /// Parse post table from raw data
+    ///
+    /// Formats 1.0, 2.0 and 3.0 are supported. Glyph names are only
+    /// available for formats 1.0 and 2.0; other formats parse the header only.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the table data is truncated or a glyph name
+    /// index points past the names stored in the table.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+
+        // Read post header (32 bytes)
+        let version = cursor.read_u32::<BigEndian>()?;
+        let italic_angle = cursor.read_i32::<BigEndian>()? as f32 / 65536.0;
+        let underline_position = cursor.read_i16::<BigEndian>()?;
+        let underline_thickness = cursor.read_i16::<BigEndian>()?;
+        let is_fixed_pitch = cursor.read_u32::<BigEndian>()? != 0;
+        let _min_mem_type42 = cursor.read_u32::<BigEndian>()?;
+        let _max_mem_type42 = cursor.read_u32::<BigEndian>()?;
+        let _min_mem_type1 = cursor.read_u32::<BigEndian>()?;
+        let _max_mem_type1 = cursor.read_u32::<BigEndian>()?;
+
+        let glyph_names = match version {
+            0x00010000 => MAC_GLYPH_NAMES
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            0x00020000 => Self::parse_format2_names(&mut cursor)?,
+            _ => Vec::new(),
+        };
+
+        Ok(PostTable {
+            version: version as f32 / 65536.0,
+            italic_angle,
+            underline_position,
+            underline_thickness,
+            is_fixed_pitch,
+            glyph_names,
+            glyph_ids: OnceLock::new(),
+        })
+    }
+
+    fn parse_format2_names(cursor: &mut Cursor<&[u8]>) -> Result<Vec<String>, ParseError> {
+        let num_glyphs = cursor.read_u16::<BigEndian>()?;
+        limits::check_declared(
+            "post glyph name indices",
+            num_glyphs as usize,
+            2,
+            limits::remaining(cursor.get_ref(), cursor.position()),
+        )?;
+        let mut name_indices = Vec::with_capacity(num_glyphs as usize);
+        for _ in 0..num_glyphs {
+            name_indices.push(cursor.read_u16::<BigEndian>()?);
+        }
+
+        // Custom names are stored as Pascal strings after the index array
+        let mut custom_names = Vec::new();
+        while let Ok(length) = cursor.read_u8() {
+            let mut bytes = vec![0u8; length as usize];
+            cursor.read_exact(&mut bytes)?;
+            custom_names.push(String::from_utf8_lossy(&bytes).into_owned());
+        }
+
+        name_indices
+            .iter()
+            .map(|&index| {
+                let index = index as usize;
+                if index < MAC_GLYPH_COUNT {
+                    Ok(MAC_GLYPH_NAMES[index].to_string())
+                } else {
+                    custom_names
+                        .get(index - MAC_GLYPH_COUNT)
+                        .cloned()
+                        .ok_or_else(|| {
+                            ParseError::CorruptedData(
+                                "post glyph name index out of range".to_string(),
+                            )
+                        })
+                }
+            })
+            .collect()
+    }
+
+    /// Get the PostScript name of a glyph
+    ///
+    /// Returns None for glyph IDs without a name or for formats without names.
+    pub fn glyph_name(&self, glyph_id: GlyphId) -> Option<&str> {
+        self.glyph_names.get(glyph_id as usize).map(String::as_str)
+    }
+
+    /// Find the glyph ID for a PostScript glyph name
+    ///
+    /// When several glyphs share a name, the lowest glyph ID is returned.
+    pub fn glyph_by_name(&self, name: &str) -> Option<GlyphId> {
+        self.glyph_ids
+            .get_or_init(|| {
+                let mut glyph_ids = HashMap::with_capacity(self.glyph_names.len());
+                for (index, glyph_name) in self.glyph_names.iter().enumerate() {
+                    glyph_ids
+                        .entry(glyph_name.clone())
+                        .or_insert(index as GlyphId);
+                }
+                glyph_ids
+            })
+            .get(name)
+            .copied()
+    }
+
+    /// Check whether this table provides glyph names
+    pub fn has_glyph_names(&self) -> bool {
+        !self.glyph_names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post_header(version: u32, italic_angle: i32, is_fixed_pitch: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&version.to_be_bytes());
+        data.extend_from_slice(&italic_angle.to_be_bytes());
+        data.extend_from_slice(&(-100i16).to_be_bytes()); // underlinePosition
+        data.extend_from_slice(&50i16.to_be_bytes()); // underlineThickness
+        data.extend_from_slice(&is_fixed_pitch.to_be_bytes());
+        data.extend_from_slice(&[0u8; 16]); // memory usage fields
+        data
+    }
+
+    #[test]
+    fn test_mac_glyph_names_table() {
+        assert_eq!(MAC_GLYPH_NAMES[0], ".notdef");
+        assert_eq!(MAC_GLYPH_NAMES[36], "A");
+        assert_eq!(MAC_GLYPH_NAMES[MAC_GLYPH_COUNT - 1], "dcroat");
+    }
+
+    #[test]
+    fn test_parse_format3_header() {
+        let data = post_header(0x00030000, -12 * 65536, 1);
+        let post = PostTable::parse(&data).unwrap();
+
+        assert_eq!(post.version, 3.0);
+        assert_eq!(post.italic_angle, -12.0);
+        assert_eq!(post.underline_position, -100);
+        assert_eq!(post.underline_thickness, 50);
+        assert!(post.is_fixed_pitch);
+        assert!(!post.has_glyph_names());
+        assert_eq!(post.glyph_name(0), None);
+    }
+
+    #[test]
+    fn test_parse_fractional_italic_angle() {
+        // -11.5 degrees as 16.16 fixed
+        let data = post_header(0x00030000, -(11 * 65536 + 32768), 0);
+        let post = PostTable::parse(&data).unwrap();
+        assert_eq!(post.italic_angle, -11.5);
+        assert!(!post.is_fixed_pitch);
+    }
+
+    #[test]
+    fn test_parse_format1_uses_standard_names() {
+        let data = post_header(0x00010000, 0, 0);
+        let post = PostTable::parse(&data).unwrap();
+        assert_eq!(post.glyph_name(3), Some("space"));
+        assert_eq!(post.glyph_by_name("A"), Some(36));
+    }
+
+    #[test]
+    fn test_parse_format2_names() {
+        let mut data = post_header(0x00020000, 0, 0);
+        data.extend_from_slice(&3u16.to_be_bytes()); // numGlyphs
+        data.extend_from_slice(&0u16.to_be_bytes()); // .notdef
+        data.extend_from_slice(&258u16.to_be_bytes()); // first custom name
+        data.extend_from_slice(&68u16.to_be_bytes()); // "a"
+        data.push(7);
+        data.extend_from_slice(b"uni4E00");
+
+        let post = PostTable::parse(&data).unwrap();
+        assert_eq!(post.glyph_name(0), Some(".notdef"));
+        assert_eq!(post.glyph_name(1), Some("uni4E00"));
+        assert_eq!(post.glyph_name(2), Some("a"));
+        assert_eq!(post.glyph_name(3), None);
+        assert_eq!(post.glyph_by_name("uni4E00"), Some(1));
+        assert_eq!(post.glyph_by_name("missing"), None);
+    }
+
+    #[test]
+    fn test_glyph_by_name_prefers_lowest_glyph_id() {
+        let mut data = post_header(0x00020000, 0, 0);
+        data.extend_from_slice(&3u16.to_be_bytes()); // numGlyphs
+        data.extend_from_slice(&0u16.to_be_bytes()); // .notdef
+        data.extend_from_slice(&68u16.to_be_bytes()); // "a"
+        data.extend_from_slice(&68u16.to_be_bytes()); // "a" again
+
+        let post = PostTable::parse(&data).unwrap();
+        assert_eq!(post.glyph_by_name("a"), Some(1));
+        assert_eq!(post.glyph_by_name(".notdef"), Some(0));
+        assert_eq!(post.glyph_by_name("b"), None);
+    }
+
+    #[test]
+    fn test_parse_format2_name_index_out_of_range() {
+        let mut data = post_header(0x00020000, 0, 0);
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&300u16.to_be_bytes());
+        assert!(PostTable::parse(&data).is_err());
+    }
+
+    #[test]
+    fn test_parse_truncated_header() {
+        let data = post_header(0x00030000, 0, 0);
+        assert!(PostTable::parse(&data[..10]).is_err());
+    }
+}
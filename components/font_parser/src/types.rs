@@ -1,11 +1,13 @@
 //! Common types for font_parser
 
+pub use crate::cmap::CMapTable;
 use crate::ParseError;
 use byteorder::{BigEndian, ReadBytesExt};
 use std::collections::HashMap;
 use std::fmt;
 use std::io::Cursor;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
 /// OpenType table tag (4-byte identifier)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -14,7 +16,6 @@ pub struct Tag([u8; 4]);
 /// Table record in the font directory
 #[derive(Debug, Clone)]
 struct TableRecord {
-    #[allow(dead_code)] // Checksum validation not yet implemented
     checksum: u32,
     offset: u32,
     length: u32,
@@ -25,9 +26,56 @@ struct TableRecord {
 pub struct OpenTypeFont {
     data: Vec<u8>,
     tables: HashMap<Tag, TableRecord>,
+    /// post table, parsed on first use
+    post: OnceLock<Option<crate::post::PostTable>>,
 }
 
-/// Font metrics (from head, hhea tables)
+/// Options controlling [`OpenTypeFont::parse_with_options`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Verify per-table checksums and the head checkSumAdjustment while parsing
+    pub verify_checksums: bool,
+}
+
+/// Result of verifying a font's checksums and head table
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChecksumReport {
+    /// Tables whose computed checksum does not match their directory record
+    pub failed_tables: Vec<Tag>,
+    /// Whether head.checkSumAdjustment matches the whole-font checksum
+    pub checksum_adjustment_valid: bool,
+    /// Whether the head table is present with a valid magic number
+    pub head_valid: bool,
+}
+
+impl ChecksumReport {
+    /// Check if every verification passed
+    pub fn is_valid(&self) -> bool {
+        self.failed_tables.is_empty() && self.checksum_adjustment_valid && self.head_valid
+    }
+}
+
+impl fmt::Display for ChecksumReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "all checksums valid");
+        }
+
+        let mut problems = Vec::new();
+        if !self.failed_tables.is_empty() {
+            let tags: Vec<&str> = self.failed_tables.iter().map(Tag::as_str).collect();
+            problems.push(format!("table checksum mismatch in {}", tags.join(", ")));
+        }
+        if !self.head_valid {
+            problems.push("missing or invalid head table".to_string());
+        } else if !self.checksum_adjustment_valid {
+            problems.push("head checkSumAdjustment mismatch".to_string());
+        }
+        write!(f, "{}", problems.join("; "))
+    }
+}
+
+/// Font metrics (from head, hhea, OS/2 tables)
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct FontMetrics {
     /// Units per em (from head table)
@@ -38,25 +86,101 @@ pub struct FontMetrics {
     pub descender: i16,
     /// Line gap (from hhea table)
     pub line_gap: i16,
+    /// Maximum advance width (hhea advanceWidthMax)
+    pub max_advance: u16,
+    /// Typographic ascender (OS/2 sTypoAscender, 0 without OS/2)
+    pub typo_ascender: i16,
+    /// Typographic descender (OS/2 sTypoDescender, 0 without OS/2)
+    pub typo_descender: i16,
+    /// Typographic line gap (OS/2 sTypoLineGap, 0 without OS/2)
+    pub typo_line_gap: i16,
+    /// Windows clipping ascent (OS/2 usWinAscent, 0 without OS/2)
+    pub win_ascent: u16,
+    /// Windows clipping descent, positive below baseline (OS/2 usWinDescent)
+    pub win_descent: u16,
+    /// Average character width (OS/2 xAvgCharWidth, 0 without OS/2)
+    pub avg_char_width: i16,
+    /// OS/2 fsSelection USE_TYPO_METRICS bit
+    pub use_typo_metrics: bool,
 }
 
-/// Glyph ID
-pub type GlyphId = u16;
+impl FontMetrics {
+    /// Ascender, descender and line gap to use for line layout
+    ///
+    /// Uses the typo metrics when USE_TYPO_METRICS is set, otherwise the
+    /// hhea metrics, falling back to the win metrics when hhea is zeroed.
+    pub fn vertical_metrics(&self) -> (i16, i16, i16) {
+        if self.use_typo_metrics {
+            (self.typo_ascender, self.typo_descender, self.typo_line_gap)
+        } else if self.ascender == 0 && self.descender == 0 && self.win_ascent != 0 {
+            (
+                self.win_ascent.min(i16::MAX as u16) as i16,
+                -(self.win_descent.min(i16::MAX as u16) as i16),
+                0,
+            )
+        } else {
+            (self.ascender, self.descender, self.line_gap)
+        }
+    }
 
-/// Character mapping table
-#[derive(Debug, Clone)]
-pub struct CMapTable {
-    // Simplified cmap - maps codepoints to glyph IDs
-    mappings: HashMap<u32, GlyphId>,
+    /// Default line height in font units (ascender - descender + line gap)
+    pub fn line_height(&self) -> i32 {
+        let (ascender, descender, line_gap) = self.vertical_metrics();
+        ascender as i32 - descender as i32 + line_gap as i32
+    }
 }
 
-impl CMapTable {
-    /// Get glyph ID for a character
-    pub fn get_glyph(&self, codepoint: char) -> Option<GlyphId> {
-        self.mappings.get(&(codepoint as u32)).copied()
+impl From<FontMetrics> for font_types::FontMetrics {
+    /// Convert raw table metrics to the shared metrics type (in font units)
+    ///
+    /// Cap height, x-height and underline metrics are not parsed here and are
+    /// left at zero.
+    fn from(metrics: FontMetrics) -> Self {
+        let (ascent, descent, line_gap) = metrics.vertical_metrics();
+        font_types::FontMetrics {
+            units_per_em: metrics.units_per_em,
+            ascent: ascent as f32,
+            descent: descent as f32,
+            line_gap: line_gap as f32,
+            hhea_ascent: metrics.ascender as f32,
+            hhea_descent: metrics.descender as f32,
+            hhea_line_gap: metrics.line_gap as f32,
+            typo_ascent: metrics.typo_ascender as f32,
+            typo_descent: metrics.typo_descender as f32,
+            typo_line_gap: metrics.typo_line_gap as f32,
+            win_ascent: metrics.win_ascent as f32,
+            win_descent: -(metrics.win_descent as f32),
+            use_typo_metrics: metrics.use_typo_metrics,
+            avg_char_width: metrics.avg_char_width as f32,
+            max_advance: metrics.max_advance as f32,
+            ..Default::default()
+        }
     }
 }
 
+/// Vertical metrics of a glyph in font units, for top-to-bottom text
+///
+/// See [`OpenTypeFont::get_vertical_glyph_metrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerticalGlyphMetrics {
+    /// Advance height
+    pub advance: u16,
+    /// X of the vertical origin from the horizontal origin (half the
+    /// advance width)
+    pub origin_x: i16,
+    /// Y of the vertical origin, up from the baseline
+    pub origin_y: i16,
+    /// Whether the font has no vhea/vmtx, so the metrics were synthesized
+    /// from the ascender and descender
+    pub synthesized: bool,
+}
+
+/// OS/2 fsSelection bit 7: use typo metrics for line layout
+const USE_TYPO_METRICS: u16 = 1 << 7;
+
+/// Glyph ID
+pub type GlyphId = u16;
+
 /// Bounding box
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BoundingBox {
@@ -154,6 +278,10 @@ impl Tag {
     pub const OPTICAL_SIZE: Tag = Tag(*b"opsz");
     /// Italic axis tag ('ital')
     pub const ITALIC: Tag = Tag(*b"ital");
+
+    // Standard table tags
+    /// Font header table tag ('head')
+    pub const HEAD: Tag = Tag(*b"head");
 }
 
 impl fmt::Display for Tag {
@@ -171,6 +299,33 @@ impl OpenTypeFont {
     /// - WOFF (0x774F4646 or 'wOFF')
     /// - WOFF2 (0x774F4632 or 'wOF2')
     pub fn parse(data: Vec<u8>) -> Result<Self, ParseError> {
+        Self::parse_with_options(data, &ParseOptions::default())
+    }
+
+    /// Parse a font from bytes with explicit options
+    ///
+    /// With `verify_checksums` enabled, every table checksum and the head
+    /// checkSumAdjustment are verified after the directory is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is not a valid font, or if checksum
+    /// verification is enabled and any check fails (`CorruptedData` naming
+    /// the failing tables).
+    pub fn parse_with_options(data: Vec<u8>, options: &ParseOptions) -> Result<Self, ParseError> {
+        let font = Self::parse_directory(data)?;
+
+        if options.verify_checksums {
+            let report = font.verify_checksums();
+            if !report.is_valid() {
+                return Err(ParseError::CorruptedData(report.to_string()));
+            }
+        }
+
+        Ok(font)
+    }
+
+    fn parse_directory(data: Vec<u8>) -> Result<Self, ParseError> {
         if data.len() < 12 {
             return Err(ParseError::CorruptedData("Font data too short".to_string()));
         }
@@ -219,8 +374,15 @@ impl OpenTypeFont {
             .read_u16::<BigEndian>()
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
 
+        crate::limits::check_declared(
+            "table directory",
+            num_tables as usize,
+            16,
+            crate::limits::remaining(&data, cursor.position()),
+        )?;
+
         // Parse table directory entries
-        let mut tables = HashMap::new();
+        let mut tables = HashMap::with_capacity(num_tables as usize);
         for _ in 0..num_tables {
             let tag_bytes = cursor
                 .read_u32::<BigEndian>()
@@ -246,7 +408,18 @@ impl OpenTypeFont {
             );
         }
 
-        Ok(OpenTypeFont { data, tables })
+        Ok(OpenTypeFont {
+            data,
+            tables,
+            post: OnceLock::new(),
+        })
+    }
+
+    /// Consume the font and return its underlying sfnt data
+    ///
+    /// WOFF and WOFF2 input is returned in decompressed form.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
     }
 
     /// Get the number of tables in this font
@@ -260,12 +433,77 @@ impl OpenTypeFont {
     }
 
     /// Get raw table data
+    ///
+    /// Returns None if the table is missing or its record points outside the font data.
     pub fn get_table(&self, tag: Tag) -> Option<&[u8]> {
-        self.tables.get(&tag).map(|record| {
-            let start = record.offset as usize;
-            let end = start + record.length as usize;
-            &self.data[start..end]
-        })
+        self.get_table_checked(tag).ok()
+    }
+
+    /// Get raw table data, distinguishing missing tables from corrupted records
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The table is not present in the font (`MissingTable`)
+    /// - The table's offset/length extends beyond the font data (`CorruptedData`)
+    pub fn get_table_checked(&self, tag: Tag) -> Result<&[u8], ParseError> {
+        let record = self
+            .tables
+            .get(&tag)
+            .ok_or_else(|| ParseError::MissingTable(tag.to_string()))?;
+
+        let start = record.offset as usize;
+        start
+            .checked_add(record.length as usize)
+            .and_then(|end| self.data.get(start..end))
+            .ok_or_else(|| {
+                ParseError::CorruptedData(format!(
+                    "Table {} out of range (offset {}, length {}, font size {})",
+                    tag,
+                    record.offset,
+                    record.length,
+                    self.data.len()
+                ))
+            })
+    }
+
+    /// Verify table checksums and the head table
+    ///
+    /// Computes each table's checksum and compares it with the directory
+    /// record, checks the head magic number, and verifies that
+    /// head.checkSumAdjustment matches the whole-font checksum. Tables whose
+    /// records point outside the font data are reported as failed.
+    pub fn verify_checksums(&self) -> ChecksumReport {
+        let mut failed_tables: Vec<Tag> = self
+            .tables
+            .iter()
+            .filter(|(tag, record)| match self.get_table(**tag) {
+                Some(table) => crate::validation::table_checksum(**tag, table) != record.checksum,
+                None => true,
+            })
+            .map(|(tag, _)| *tag)
+            .collect();
+        failed_tables.sort_by_key(Tag::to_bytes);
+
+        let head_valid = self
+            .get_table(Tag::HEAD)
+            .filter(|head| head.len() >= 16)
+            .map(|head| {
+                u32::from_be_bytes([head[12], head[13], head[14], head[15]])
+                    == crate::validation::HEAD_MAGIC_NUMBER
+            })
+            .unwrap_or(false);
+
+        // With checkSumAdjustment in place, the whole font sums to the magic base
+        let checksum_adjustment_valid = head_valid
+            && crate::validation::calculate_checksum(&self.data)
+                == crate::validation::CHECKSUM_ADJUSTMENT_BASE;
+
+        ChecksumReport {
+            failed_tables,
+            checksum_adjustment_valid,
+            head_valid,
+        }
     }
 
     /// Get font metrics
@@ -284,8 +522,8 @@ impl OpenTypeFont {
             })
             .unwrap_or(1000); // Default value if head table missing or invalid
 
-        // Parse hhea table for ascender, descender, line_gap
-        let (ascender, descender, line_gap) = self
+        // Parse hhea table for ascender, descender, line_gap, advanceWidthMax
+        let (ascender, descender, line_gap, max_advance) = self
             .get_table("hhea".parse().unwrap())
             .and_then(|data| {
                 if data.len() >= 12 {
@@ -294,35 +532,285 @@ impl OpenTypeFont {
                     let asc = cursor.read_i16::<BigEndian>().ok()?;
                     let desc = cursor.read_i16::<BigEndian>().ok()?;
                     let gap = cursor.read_i16::<BigEndian>().ok()?;
-                    Some((asc, desc, gap))
+                    let max_advance = cursor.read_u16::<BigEndian>().unwrap_or(units_per_em);
+                    Some((asc, desc, gap, max_advance))
                 } else {
                     None
                 }
             })
-            .unwrap_or((800, -200, 0)); // Default values
+            .unwrap_or((800, -200, 0, units_per_em)); // Default values
 
-        FontMetrics {
+        let mut metrics = FontMetrics {
             units_per_em,
             ascender,
             descender,
             line_gap,
+            max_advance,
+            typo_ascender: 0,
+            typo_descender: 0,
+            typo_line_gap: 0,
+            win_ascent: 0,
+            win_descent: 0,
+            avg_char_width: 0,
+            use_typo_metrics: false,
+        };
+
+        // Parse OS/2 table for typo/win metrics and average width (version 0
+        // tables from old Apple fonts may stop before the typo metrics)
+        if let Some(data) = self.get_table("OS/2".parse().unwrap()) {
+            let read_i16 = |offset: usize| {
+                data.get(offset..offset + 2)
+                    .map(|b| i16::from_be_bytes([b[0], b[1]]))
+            };
+            let read_u16 = |offset: usize| read_i16(offset).map(|v| v as u16);
+
+            metrics.avg_char_width = read_i16(2).unwrap_or(0);
+            if let (
+                Some(selection),
+                Some(asc),
+                Some(desc),
+                Some(gap),
+                Some(win_asc),
+                Some(win_desc),
+            ) = (
+                read_u16(62),
+                read_i16(68),
+                read_i16(70),
+                read_i16(72),
+                read_u16(74),
+                read_u16(76),
+            ) {
+                metrics.use_typo_metrics = selection & USE_TYPO_METRICS != 0;
+                metrics.typo_ascender = asc;
+                metrics.typo_descender = desc;
+                metrics.typo_line_gap = gap;
+                metrics.win_ascent = win_asc;
+                metrics.win_descent = win_desc;
+            }
         }
+
+        metrics
+    }
+
+    /// Get font metrics at variation coordinates
+    ///
+    /// Applies MVAR deltas to [`get_metrics`](Self::get_metrics), rounded to
+    /// font units. `hasc`, `hdsc` and `hlgp` vary both the hhea and the
+    /// OS/2 typo values, as they describe the same ascender, descender and
+    /// line gap; `hcla` and `hcld` vary the win metrics. Fonts without MVAR
+    /// return the default metrics.
+    pub fn get_metrics_at(
+        &self,
+        coords: &crate::variable_fonts::VariationCoordinates,
+    ) -> FontMetrics {
+        let mut metrics = self.get_metrics();
+        let Some(mvar) = self.get_mvar() else {
+            return metrics;
+        };
+        let normalized = self.normalize_coordinates(coords);
+        let delta = |tag: &str| mvar.delta(tag.parse().unwrap(), &normalized);
+        let vary = |value: i16, delta: f32| (value as f32 + delta).round() as i16;
+
+        let ascender = delta("hasc");
+        let descender = delta("hdsc");
+        let line_gap = delta("hlgp");
+        metrics.ascender = vary(metrics.ascender, ascender);
+        metrics.descender = vary(metrics.descender, descender);
+        metrics.line_gap = vary(metrics.line_gap, line_gap);
+        metrics.typo_ascender = vary(metrics.typo_ascender, ascender);
+        metrics.typo_descender = vary(metrics.typo_descender, descender);
+        metrics.typo_line_gap = vary(metrics.typo_line_gap, line_gap);
+        metrics.win_ascent = (metrics.win_ascent as f32 + delta("hcla")).round() as u16;
+        metrics.win_descent = (metrics.win_descent as f32 + delta("hcld")).round() as u16;
+        metrics
+    }
+
+    /// Get the advance width of a glyph in font units (hmtx)
+    ///
+    /// Glyphs past the last long metric share its advance, as in monospaced
+    /// fonts. Returns `None` without hhea/hmtx or for out-of-range glyphs.
+    pub fn get_advance(&self, glyph_id: GlyphId) -> Option<u16> {
+        let hhea = self.get_table("hhea".parse().unwrap())?;
+        let hmtx = self.get_table("hmtx".parse().unwrap())?;
+        let num_glyphs = self
+            .get_table("maxp".parse().unwrap())
+            .and_then(|maxp| maxp.get(4..6))
+            .map_or(u16::MAX, |b| u16::from_be_bytes([b[0], b[1]]));
+        if glyph_id >= num_glyphs {
+            return None;
+        }
+        let num_metrics = hhea.get(34..36).map(|b| u16::from_be_bytes([b[0], b[1]]))?;
+        let index = glyph_id.min(num_metrics.checked_sub(1)?) as usize;
+        hmtx.get(index * 4..index * 4 + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    /// Get the advance width of a glyph at variation coordinates
+    ///
+    /// Applies the HVAR delta, or for fonts without HVAR the delta of the
+    /// gvar advance phantom point. The result is in unrounded font units.
+    pub fn get_advance_at(
+        &self,
+        glyph_id: GlyphId,
+        coords: &crate::variable_fonts::VariationCoordinates,
+    ) -> Option<f32> {
+        let advance = self.get_advance(glyph_id)? as f32;
+        let normalized = self.normalize_coordinates(coords);
+        if normalized.iter().all(|&coord| coord == 0.0) {
+            return Some(advance);
+        }
+
+        let delta = if let Some(hvar) = self.get_hvar() {
+            hvar.advance_delta(glyph_id, &normalized)
+        } else if let (Some(glyf), Some(gvar)) = (self.glyf_table(), self.get_gvar()) {
+            glyf.advance_delta(glyph_id, &gvar, &normalized)
+                .unwrap_or(0.0)
+        } else {
+            0.0
+        };
+        Some(advance + delta)
+    }
+
+    /// Whether the font has vertical metrics (vhea and vmtx)
+    ///
+    /// Fonts without them, like most Latin fonts, get synthesized metrics
+    /// from [`get_vertical_glyph_metrics`](Self::get_vertical_glyph_metrics).
+    pub fn has_vertical_metrics(&self) -> bool {
+        self.vertical_metric_count().is_some()
+    }
+
+    /// Get the vertical metrics of a glyph in font units
+    ///
+    /// The advance height comes from vmtx, and the vertical origin from
+    /// VORG or else the vmtx top side bearing above the top of the glyph's
+    /// outline. Fonts without vhea/vmtx get synthesized metrics: every
+    /// glyph advances by the ascender minus the descender (as chosen by
+    /// [`FontMetrics::vertical_metrics`]) with its origin at the ascender,
+    /// so glyphs stand upright in cells as tall as a horizontal line. The
+    /// origin is always centered on the advance width.
+    ///
+    /// Returns `None` without hmtx, for out-of-range glyphs and for
+    /// truncated vmtx tables.
+    pub fn get_vertical_glyph_metrics(&self, glyph_id: GlyphId) -> Option<VerticalGlyphMetrics> {
+        let origin_x = (self.get_advance(glyph_id)? / 2) as i16;
+        let (ascender, descender, _) = self.get_metrics().vertical_metrics();
+        let Some(num_metrics) = self.vertical_metric_count() else {
+            return Some(VerticalGlyphMetrics {
+                advance: (ascender as i32 - descender as i32).clamp(0, u16::MAX as i32) as u16,
+                origin_x,
+                origin_y: ascender,
+                synthesized: true,
+            });
+        };
+
+        // Glyphs past the last long metric share its advance and only have
+        // a top side bearing
+        let vmtx = self.get_table("vmtx".parse().unwrap())?;
+        let index = glyph_id.min(num_metrics - 1) as usize;
+        let advance = vmtx
+            .get(index * 4..index * 4 + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))?;
+        let bearing = if glyph_id < num_metrics {
+            index * 4 + 2
+        } else {
+            num_metrics as usize * 4 + (glyph_id - num_metrics) as usize * 2
+        };
+        let top_bearing = vmtx
+            .get(bearing..bearing + 2)
+            .map_or(0, |b| i16::from_be_bytes([b[0], b[1]]));
+        let origin_y = self.vertical_origin(glyph_id).unwrap_or_else(|| {
+            self.get_glyph_outline(glyph_id)
+                .map_or(ascender, |outline| {
+                    top_bearing.saturating_add(outline.bounds.y_max)
+                })
+        });
+        Some(VerticalGlyphMetrics {
+            advance,
+            origin_x,
+            origin_y,
+            synthesized: false,
+        })
+    }
+
+    /// Number of long vertical metrics, if the font has vhea and vmtx
+    fn vertical_metric_count(&self) -> Option<u16> {
+        let vhea = self.get_table("vhea".parse().unwrap())?;
+        self.get_table("vmtx".parse().unwrap())?;
+        vhea.get(34..36)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .filter(|&count| count > 0)
+    }
+
+    /// Y of a glyph's vertical origin from the VORG table
+    fn vertical_origin(&self, glyph_id: GlyphId) -> Option<i16> {
+        let vorg = self.get_table("VORG".parse().unwrap())?;
+        let read = |offset: usize| vorg.get(offset..offset + 2).map(|b| [b[0], b[1]]);
+        let default = i16::from_be_bytes(read(4)?);
+        let count = u16::from_be_bytes(read(6)?) as usize;
+
+        // Records are sorted by glyph ID
+        let (mut low, mut high) = (0, count);
+        while low < high {
+            let middle = (low + high) / 2;
+            let record = 8 + middle * 4;
+            match u16::from_be_bytes(read(record)?).cmp(&glyph_id) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return Some(i16::from_be_bytes(read(record + 2)?)),
+            }
+        }
+        Some(default)
     }
 
     /// Get character mapping table
+    ///
+    /// Returns `None` without a cmap table or when it has no Unicode
+    /// subtable in format 4 or 12.
     pub fn get_cmap(&self) -> Option<CMapTable> {
-        // Stub implementation - returns empty cmap
-        // Full implementation would parse the cmap table
-        Some(CMapTable {
-            mappings: HashMap::new(),
-        })
+        let data = self.get_table("cmap".parse().unwrap())?;
+        CMapTable::parse(data).ok()
     }
 
     /// Get glyph outline
-    pub fn get_glyph_outline(&self, _glyph_id: GlyphId) -> Option<GlyphOutline> {
-        // Stub implementation - returns None
-        // Full implementation would parse glyf table
-        None
+    ///
+    /// Decodes the TrueType outline from glyf/loca, flattening composite
+    /// glyphs. Returns `None` for fonts without glyf (e.g. CFF outlines) and
+    /// for malformed glyphs; glyphs without contours yield an empty outline.
+    pub fn get_glyph_outline(&self, glyph_id: GlyphId) -> Option<GlyphOutline> {
+        self.glyf_table()?.outline(glyph_id, None).ok()
+    }
+
+    /// Get glyph outline at variation coordinates
+    ///
+    /// Like [`get_glyph_outline`](Self::get_glyph_outline), with gvar point
+    /// deltas applied for `coords`. Axes missing from `coords` stay at their
+    /// default; fonts without gvar return the default outline.
+    pub fn get_glyph_outline_at(
+        &self,
+        glyph_id: GlyphId,
+        coords: &crate::variable_fonts::VariationCoordinates,
+    ) -> Option<GlyphOutline> {
+        let glyf = self.glyf_table()?;
+        let gvar = self.get_gvar();
+        let normalized = self.normalize_coordinates(coords);
+        let variations = gvar.as_ref().map(|gvar| (gvar, normalized.as_slice()));
+        glyf.outline(glyph_id, variations).ok()
+    }
+
+    /// glyf/loca access, with the loca format from head and glyph count from maxp
+    fn glyf_table(&self) -> Option<crate::glyf::GlyfTable<'_>> {
+        let glyf = self.get_table("glyf".parse().unwrap())?;
+        let loca = self.get_table("loca".parse().unwrap())?;
+        let head = self.get_table("head".parse().unwrap())?;
+        let maxp = self.get_table("maxp".parse().unwrap())?;
+        let index_to_loc_format = head.get(50..52).map(|b| i16::from_be_bytes([b[0], b[1]]))?;
+        let num_glyphs = maxp.get(4..6).map(|b| u16::from_be_bytes([b[0], b[1]]))?;
+        Some(crate::glyf::GlyfTable::new(
+            glyf,
+            loca,
+            index_to_loc_format != 0,
+            num_glyphs,
+        ))
     }
 
     /// Check if this is a variable font
@@ -352,6 +840,73 @@ impl OpenTypeFont {
         crate::variable_fonts::AvarTable::parse(data, fvar.axes.len()).ok()
     }
 
+    /// Get glyph variations table (gvar)
+    ///
+    /// Returns the parsed gvar header and shared tuples; glyph variation data
+    /// is decoded lazily from the font data.
+    pub fn get_gvar(&self) -> Option<crate::gvar::GvarTable<'_>> {
+        let data = self.get_table("gvar".parse().unwrap())?;
+        crate::gvar::GvarTable::parse(data).ok()
+    }
+
+    /// Get metrics variations table (MVAR)
+    pub fn get_mvar(&self) -> Option<crate::metric_variations::MvarTable<'_>> {
+        let data = self.get_table("MVAR".parse().unwrap())?;
+        crate::metric_variations::MvarTable::parse(data).ok()
+    }
+
+    /// Get horizontal metrics variations table (HVAR)
+    pub fn get_hvar(&self) -> Option<crate::metric_variations::HvarTable<'_>> {
+        let data = self.get_table("HVAR".parse().unwrap())?;
+        crate::metric_variations::HvarTable::parse(data).ok()
+    }
+
+    /// Normalize variation coordinates for use with variation tables
+    ///
+    /// Returns one value per fvar axis in the -1..1 range, after avar
+    /// remapping and rounding to F2DOT14 precision. Axes missing from
+    /// `coords` normalize to 0. Empty for non-variable fonts.
+    pub fn normalize_coordinates(
+        &self,
+        coords: &crate::variable_fonts::VariationCoordinates,
+    ) -> Vec<f32> {
+        let Some(fvar) = self.get_fvar() else {
+            return Vec::new();
+        };
+        fvar.normalize_coordinates(coords, self.get_avar().as_ref())
+    }
+
+    /// Get style attributes table (STAT)
+    ///
+    /// Returns the parsed STAT table if present. Unlike fvar, STAT is also
+    /// found in static fonts that belong to a larger family.
+    pub fn get_stat(&self) -> Option<crate::variable_fonts::StatTable> {
+        let data = self.get_table("STAT".parse().unwrap())?;
+        crate::variable_fonts::StatTable::parse(data).ok()
+    }
+
+    /// Compose the STAT name IDs that label a variation instance
+    ///
+    /// Axes missing from `coords` take their fvar default, so e.g. setting
+    /// only `wght` to 900 yields the names for "Black" at the default width.
+    /// Returns an empty list when the font has no STAT table.
+    /// See [`StatTable::name_coordinates`](crate::variable_fonts::StatTable::name_coordinates).
+    pub fn variation_name_ids(
+        &self,
+        coords: &crate::variable_fonts::VariationCoordinates,
+    ) -> Vec<u16> {
+        let Some(stat) = self.get_stat() else {
+            return Vec::new();
+        };
+        let mut full = coords.clone();
+        for axis in self.get_variation_axes() {
+            if full.get_axis(axis.tag).is_none() {
+                full.set_axis(axis.tag, axis.default_value);
+            }
+        }
+        stat.name_coordinates(&full)
+    }
+
     /// Get available variation axes
     ///
     /// Returns a list of all variation axes defined in the font's fvar table.
@@ -370,6 +925,64 @@ impl OpenTypeFont {
             .unwrap_or_default()
     }
 
+    /// Get named instances with their names resolved through the name table
+    ///
+    /// Returns (display name, PostScript name, coordinates) for each named
+    /// instance; see [`named_instance_names`](Self::named_instance_names).
+    /// Instances whose subfamily name can't be resolved are skipped.
+    pub fn named_instances_resolved(
+        &self,
+    ) -> Vec<(String, String, crate::variable_fonts::VariationCoordinates)> {
+        let Some(fvar) = self.get_fvar() else {
+            return Vec::new();
+        };
+        fvar.instances
+            .iter()
+            .filter_map(|instance| {
+                let (display_name, postscript_name) = self.named_instance_names(instance)?;
+                let mut coords = crate::variable_fonts::VariationCoordinates::new();
+                for (axis, &value) in fvar.axes.iter().zip(&instance.coordinates) {
+                    coords.set_axis(axis.tag, value);
+                }
+                Some((display_name, postscript_name, coords))
+            })
+            .collect()
+    }
+
+    /// Resolve the display and PostScript names of a named instance
+    ///
+    /// The display name is the instance's subfamily name (e.g. "Bold
+    /// Condensed"). The PostScript name comes from the instance's
+    /// PostScript name ID when present, otherwise it is derived as in Adobe
+    /// Technical Note #5902: the variations PostScript name prefix (or the
+    /// family name) and the subfamily name, stripped to ASCII letters and
+    /// digits and joined by a hyphen.
+    ///
+    /// Returns `None` without a name table or when the subfamily name is missing.
+    pub fn named_instance_names(
+        &self,
+        instance: &crate::variable_fonts::NamedInstance,
+    ) -> Option<(String, String)> {
+        use crate::name::NameTable;
+
+        let name = self.get_name()?;
+        let display_name = name.get(instance.subfamily_name_id)?;
+        let postscript_name = instance
+            .postscript_name_id
+            .filter(|&id| id != 0xFFFF)
+            .and_then(|id| name.get(id))
+            .or_else(|| {
+                let prefix = name
+                    .get(NameTable::VARIATIONS_POSTSCRIPT_NAME_PREFIX)
+                    .or_else(|| name.get(NameTable::TYPOGRAPHIC_FAMILY))
+                    .or_else(|| name.get(NameTable::FAMILY))?;
+                let strip =
+                    |s: &str| -> String { s.chars().filter(char::is_ascii_alphanumeric).collect() };
+                Some(format!("{}-{}", strip(&prefix), strip(&display_name)))
+            })?;
+        Some((display_name, postscript_name))
+    }
+
     /// Validate variation coordinates
     ///
     /// Checks that all coordinates are within the valid range for their respective axes.
@@ -402,6 +1015,91 @@ impl OpenTypeFont {
         Ok(())
     }
 
+    /// Get PostScript table (post)
+    ///
+    /// Returns the parsed post table if present, which provides glyph names,
+    /// the italic angle and the fixed-pitch flag. The table is parsed once
+    /// and kept for the lifetime of the font.
+    pub fn get_post(&self) -> Option<&crate::post::PostTable> {
+        self.post
+            .get_or_init(|| {
+                let data = self.get_table("post".parse().unwrap())?;
+                crate::post::PostTable::parse(data).ok()
+            })
+            .as_ref()
+    }
+
+    /// Get naming table (name)
+    ///
+    /// Returns the parsed name table if present, which resolves the name IDs
+    /// referenced by fvar and STAT to strings.
+    pub fn get_name(&self) -> Option<crate::name::NameTable<'_>> {
+        let data = self.get_table("name".parse().unwrap())?;
+        crate::name::NameTable::parse(data).ok()
+    }
+
+    /// Get glyph substitution table (GSUB)
+    ///
+    /// Returns the script and feature lists, which tell which substitution
+    /// features (ligatures, small caps, ...) the font provides.
+    pub fn get_gsub(&self) -> Option<crate::layout::LayoutTable> {
+        let data = self.get_table("GSUB".parse().unwrap())?;
+        crate::layout::LayoutTable::parse(data).ok()
+    }
+
+    /// Get glyph positioning table (GPOS)
+    pub fn get_gpos(&self) -> Option<crate::layout::LayoutTable> {
+        let data = self.get_table("GPOS".parse().unwrap())?;
+        crate::layout::LayoutTable::parse(data).ok()
+    }
+
+    /// Get baseline table (BASE)
+    ///
+    /// Returns the per-script baseline positions used to align runs of
+    /// different scripts on one line.
+    pub fn get_base(&self) -> Option<crate::base::BaseTable> {
+        let data = self.get_table("BASE".parse().unwrap())?;
+        crate::base::BaseTable::parse(data).ok()
+    }
+
+    /// Get mathematical typesetting table (MATH)
+    ///
+    /// Returns the constants, italic corrections and stretchy glyph
+    /// constructions used to lay out mathematical formulas.
+    pub fn get_math(&self) -> Option<crate::math::MathTable> {
+        let data = self.get_table("MATH".parse().unwrap())?;
+        crate::math::MathTable::parse(data).ok()
+    }
+
+    /// Check if the font provides a GSUB feature (e.g. `smcp`) for any script
+    pub fn has_gsub_feature(&self, feature: Tag) -> bool {
+        self.get_gsub()
+            .is_some_and(|gsub| gsub.has_feature(feature))
+    }
+
+    /// Get the PostScript name of a glyph
+    ///
+    /// Returns None if the font has no post table or the table format
+    /// (e.g. 3.0) does not store glyph names.
+    pub fn glyph_name(&self, glyph_id: GlyphId) -> Option<String> {
+        self.get_post()?.glyph_name(glyph_id).map(str::to_string)
+    }
+
+    /// Find a glyph by its PostScript name
+    pub fn glyph_by_name(&self, name: &str) -> Option<GlyphId> {
+        self.get_post()?.glyph_by_name(name)
+    }
+
+    /// Get the italic angle in degrees (0.0 for upright fonts or without a post table)
+    pub fn italic_angle(&self) -> f32 {
+        self.get_post().map(|post| post.italic_angle).unwrap_or(0.0)
+    }
+
+    /// Check if the post table marks this font as fixed pitch (monospaced)
+    pub fn is_fixed_pitch(&self) -> bool {
+        self.get_post().is_some_and(|post| post.is_fixed_pitch)
+    }
+
     /// Check if this font has color glyphs
     ///
     /// Returns true if the font contains any color font tables (COLR, CBDT, sbix, or SVG).
@@ -0,0 +1,593 @@
+//! Font validation and sanitization (OTS-style)
+//!
+//! Webfont bytes are untrusted and must not reach FreeType or HarfBuzz
+//! unchecked. [`validate`] bounds-checks the table directory, rejects absurd
+//! header values, optionally verifies table checksums and re-serializes a
+//! clean sfnt whose structure downstream consumers can rely on.
+
+use crate::types::{OpenTypeFont, Tag};
+use crate::ParseError;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::fmt;
+use std::io::Cursor;
+
+/// Size of the sfnt header (version, numTables, searchRange, entrySelector, rangeShift)
+const SFNT_HEADER_SIZE: usize = 12;
+/// Size of a single table record in the sfnt directory
+const TABLE_RECORD_SIZE: usize = 16;
+/// Magic number stored in every valid head table
+pub(crate) const HEAD_MAGIC_NUMBER: u32 = 0x5F0F3CF5;
+/// Offset of checkSumAdjustment within the head table
+pub(crate) const CHECKSUM_ADJUSTMENT_OFFSET: usize = 8;
+/// Constant the whole-font checksum is subtracted from for checkSumAdjustment
+pub(crate) const CHECKSUM_ADJUSTMENT_BASE: u32 = 0xB1B0AFBA;
+
+/// Limits and switches applied by [`validate_with_options`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationOptions {
+    /// Verify each table's checksum against its directory record
+    pub verify_checksums: bool,
+    /// Maximum number of tables accepted in the sfnt directory
+    pub max_tables: u16,
+    /// Maximum glyph count accepted from the maxp table
+    pub max_glyphs: u16,
+    /// Maximum size in bytes of the (decompressed) font data
+    pub max_font_size: usize,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            verify_checksums: false,
+            max_tables: 256,
+            max_glyphs: u16::MAX,
+            max_font_size: 30 * 1024 * 1024,
+        }
+    }
+}
+
+/// Font validation errors
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// Data is too short to contain an sfnt header and directory
+    TooShort,
+    /// Font data exceeds the configured size limit
+    TooLarge {
+        /// Actual size in bytes
+        size: usize,
+        /// Configured maximum size in bytes
+        max: usize,
+    },
+    /// Unknown sfnt version / signature
+    InvalidSignature(u32),
+    /// Table count is zero or exceeds the configured limit
+    InvalidTableCount(u16),
+    /// The same table tag appears more than once
+    DuplicateTable(Tag),
+    /// Table offset/length points outside the font data
+    TableOutOfBounds(Tag),
+    /// Two tables share overlapping byte ranges
+    OverlappingTables(Tag, Tag),
+    /// Table checksum does not match its directory record
+    ChecksumMismatch {
+        /// Table whose checksum failed
+        tag: Tag,
+        /// Checksum stored in the table directory
+        expected: u32,
+        /// Checksum computed from the table data
+        actual: u32,
+    },
+    /// Required table is missing
+    MissingTable(Tag),
+    /// Table contents are structurally invalid
+    InvalidTable {
+        /// Offending table
+        tag: Tag,
+        /// Reason the table was rejected
+        reason: String,
+    },
+    /// WOFF/WOFF2 container could not be decoded
+    Decode(ParseError),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::TooShort => write!(f, "Font data too short"),
+            ValidationError::TooLarge { size, max } => {
+                write!(f, "Font data too large: {} bytes (max {})", size, max)
+            }
+            ValidationError::InvalidSignature(sig) => {
+                write!(f, "Invalid font signature: 0x{:08X}", sig)
+            }
+            ValidationError::InvalidTableCount(count) => {
+                write!(f, "Invalid table count: {}", count)
+            }
+            ValidationError::DuplicateTable(tag) => write!(f, "Duplicate table: {}", tag),
+            ValidationError::TableOutOfBounds(tag) => {
+                write!(f, "Table {} extends beyond font data", tag)
+            }
+            ValidationError::OverlappingTables(a, b) => {
+                write!(f, "Tables {} and {} overlap", a, b)
+            }
+            ValidationError::ChecksumMismatch {
+                tag,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Checksum mismatch in {}: expected 0x{:08X}, got 0x{:08X}",
+                tag, expected, actual
+            ),
+            ValidationError::MissingTable(tag) => write!(f, "Missing required table: {}", tag),
+            ValidationError::InvalidTable { tag, reason } => {
+                write!(f, "Invalid {} table: {}", tag, reason)
+            }
+            ValidationError::Decode(err) => write!(f, "Failed to decode font container: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ValidationError::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for ValidationError {
+    fn from(err: ParseError) -> Self {
+        ValidationError::Decode(err)
+    }
+}
+
+/// A font that passed validation, re-serialized into a clean sfnt
+///
+/// The sanitized data has a sorted table directory, 4-byte aligned tables,
+/// recomputed table checksums and a recomputed head checkSumAdjustment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizedFont {
+    data: Vec<u8>,
+    tables: Vec<Tag>,
+}
+
+impl SanitizedFont {
+    /// Get the sanitized sfnt bytes
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the sanitized font and return its sfnt bytes
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Get the tags of all tables kept in the sanitized font, in directory order
+    pub fn tables(&self) -> &[Tag] {
+        &self.tables
+    }
+
+    /// Parse the sanitized data as an [`OpenTypeFont`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if the sanitized data cannot be parsed, which
+    /// should not happen for data produced by [`validate`].
+    pub fn into_font(self) -> Result<OpenTypeFont, ParseError> {
+        OpenTypeFont::parse(self.data)
+    }
+}
+
+/// Table directory entry as read from untrusted data
+#[derive(Debug, Clone, Copy)]
+struct RawTableRecord {
+    tag: Tag,
+    checksum: u32,
+    offset: usize,
+    length: usize,
+}
+
+/// Validate and sanitize untrusted font data using default options
+///
+/// Accepts TrueType, OpenType/CFF, WOFF and WOFF2 input. WOFF containers are
+/// decoded first; the result is always a plain sfnt.
+///
+/// # Errors
+///
+/// Returns a [`ValidationError`] describing the first structural problem found.
+///
+/// # Example
+///
+/// ```no_run
+/// let bytes = std::fs::read("webfont.woff").unwrap();
+/// let sanitized = font_parser::validate(&bytes).unwrap();
+/// let font = sanitized.into_font().unwrap();
+/// ```
+pub fn validate(data: &[u8]) -> Result<SanitizedFont, ValidationError> {
+    validate_with_options(data, &ValidationOptions::default())
+}
+
+/// Validate and sanitize untrusted font data using explicit limits
+///
+/// # Errors
+///
+/// Returns a [`ValidationError`] describing the first structural problem found.
+pub fn validate_with_options(
+    data: &[u8],
+    options: &ValidationOptions,
+) -> Result<SanitizedFont, ValidationError> {
+    check_size(data.len(), options)?;
+    if data.len() < 4 {
+        return Err(ValidationError::TooShort);
+    }
+
+    let signature = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    match signature {
+        0x774F4646 => {
+            let woff = crate::woff::WoffFont::parse(data)?;
+            validate_sfnt(&woff.ttf_data, options)
+        }
+        0x774F4632 => {
+            let woff2 = crate::woff2::Woff2Font::parse(data)?;
+            validate_sfnt(&woff2.ttf_data, options)
+        }
+        _ => validate_sfnt(data, options),
+    }
+}
+
+/// Calculate an OpenType table checksum (sum of big-endian u32 words, zero padded)
+pub(crate) fn calculate_checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Calculate the checksum of a table as stored in its directory record
+///
+/// The head table's checkSumAdjustment field is treated as zero.
+pub(crate) fn table_checksum(tag: Tag, table: &[u8]) -> u32 {
+    let adjustment_end = CHECKSUM_ADJUSTMENT_OFFSET + 4;
+    if tag == Tag::HEAD && table.len() >= adjustment_end {
+        let adjustment = u32::from_be_bytes([
+            table[CHECKSUM_ADJUSTMENT_OFFSET],
+            table[CHECKSUM_ADJUSTMENT_OFFSET + 1],
+            table[CHECKSUM_ADJUSTMENT_OFFSET + 2],
+            table[CHECKSUM_ADJUSTMENT_OFFSET + 3],
+        ]);
+        calculate_checksum(table).wrapping_sub(adjustment)
+    } else {
+        calculate_checksum(table)
+    }
+}
+
+fn check_size(size: usize, options: &ValidationOptions) -> Result<(), ValidationError> {
+    if size > options.max_font_size {
+        return Err(ValidationError::TooLarge {
+            size,
+            max: options.max_font_size,
+        });
+    }
+    Ok(())
+}
+
+fn validate_sfnt(
+    data: &[u8],
+    options: &ValidationOptions,
+) -> Result<SanitizedFont, ValidationError> {
+    check_size(data.len(), options)?;
+    if data.len() < SFNT_HEADER_SIZE {
+        return Err(ValidationError::TooShort);
+    }
+
+    let mut cursor = Cursor::new(data);
+    let version = cursor
+        .read_u32::<BigEndian>()
+        .map_err(|_| ValidationError::TooShort)?;
+    if version != 0x00010000 && version != 0x4F54544F {
+        return Err(ValidationError::InvalidSignature(version));
+    }
+
+    let num_tables = cursor
+        .read_u16::<BigEndian>()
+        .map_err(|_| ValidationError::TooShort)?;
+    if num_tables == 0 || num_tables > options.max_tables {
+        return Err(ValidationError::InvalidTableCount(num_tables));
+    }
+
+    let directory_end = SFNT_HEADER_SIZE + num_tables as usize * TABLE_RECORD_SIZE;
+    if data.len() < directory_end {
+        return Err(ValidationError::TooShort);
+    }
+
+    let records = read_table_records(data, num_tables, directory_end)?;
+
+    if options.verify_checksums {
+        verify_checksums(data, &records)?;
+    }
+
+    let table = |name: &str| -> Option<&[u8]> {
+        let tag = Tag::new(name)?;
+        records
+            .iter()
+            .find(|record| record.tag == tag)
+            .map(|record| &data[record.offset..record.offset + record.length])
+    };
+    validate_tables(&table, options)?;
+
+    Ok(serialize(data, version, records))
+}
+
+fn read_table_records(
+    data: &[u8],
+    num_tables: u16,
+    directory_end: usize,
+) -> Result<Vec<RawTableRecord>, ValidationError> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(SFNT_HEADER_SIZE as u64);
+
+    let mut records: Vec<RawTableRecord> = Vec::with_capacity(num_tables as usize);
+    for _ in 0..num_tables {
+        let mut fields = [0u32; 4];
+        for field in &mut fields {
+            *field = cursor
+                .read_u32::<BigEndian>()
+                .map_err(|_| ValidationError::TooShort)?;
+        }
+        let [tag_bytes, checksum, offset, length] = fields;
+        let tag = Tag::from_bytes(tag_bytes);
+
+        if records.iter().any(|record| record.tag == tag) {
+            return Err(ValidationError::DuplicateTable(tag));
+        }
+
+        let offset = offset as usize;
+        let length = length as usize;
+        let in_bounds = offset >= directory_end
+            && offset
+                .checked_add(length)
+                .is_some_and(|end| end <= data.len());
+        if !in_bounds {
+            return Err(ValidationError::TableOutOfBounds(tag));
+        }
+
+        records.push(RawTableRecord {
+            tag,
+            checksum,
+            offset,
+            length,
+        });
+    }
+
+    // Reject overlapping table data
+    let mut by_offset: Vec<&RawTableRecord> = records.iter().collect();
+    by_offset.sort_by_key(|record| record.offset);
+    for pair in by_offset.windows(2) {
+        if pair[0].offset + pair[0].length > pair[1].offset {
+            return Err(ValidationError::OverlappingTables(pair[0].tag, pair[1].tag));
+        }
+    }
+
+    Ok(records)
+}
+
+fn verify_checksums(data: &[u8], records: &[RawTableRecord]) -> Result<(), ValidationError> {
+    for record in records {
+        let table = &data[record.offset..record.offset + record.length];
+        let actual = table_checksum(record.tag, table);
+
+        if actual != record.checksum {
+            return Err(ValidationError::ChecksumMismatch {
+                tag: record.tag,
+                expected: record.checksum,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn invalid(name: &str, reason: &str) -> ValidationError {
+    ValidationError::InvalidTable {
+        tag: Tag::new(name).expect("table tags are 4 bytes"),
+        reason: reason.to_string(),
+    }
+}
+
+fn required<'a>(
+    table: &impl Fn(&str) -> Option<&'a [u8]>,
+    name: &str,
+) -> Result<&'a [u8], ValidationError> {
+    table(name).ok_or_else(|| ValidationError::MissingTable(Tag::new(name).expect("4-byte tag")))
+}
+
+fn read_u16_at(data: &[u8], offset: u64, name: &str) -> Result<u16, ValidationError> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(offset);
+    cursor
+        .read_u16::<BigEndian>()
+        .map_err(|_| invalid(name, "table truncated"))
+}
+
+fn read_i16_at(data: &[u8], offset: u64, name: &str) -> Result<i16, ValidationError> {
+    read_u16_at(data, offset, name).map(|value| value as i16)
+}
+
+fn validate_tables<'a>(
+    table: &impl Fn(&str) -> Option<&'a [u8]>,
+    options: &ValidationOptions,
+) -> Result<(), ValidationError> {
+    // head: magic number, unitsPerEm and indexToLocFormat
+    let head = required(table, "head")?;
+    if head.len() < 54 {
+        return Err(invalid("head", "table truncated"));
+    }
+    let magic = u32::from_be_bytes([head[12], head[13], head[14], head[15]]);
+    if magic != HEAD_MAGIC_NUMBER {
+        return Err(invalid("head", "bad magic number"));
+    }
+    let units_per_em = read_u16_at(head, 18, "head")?;
+    if !(16..=16384).contains(&units_per_em) {
+        return Err(invalid("head", "unitsPerEm out of range"));
+    }
+    let index_to_loc_format = read_i16_at(head, 50, "head")?;
+    if index_to_loc_format != 0 && index_to_loc_format != 1 {
+        return Err(invalid("head", "invalid indexToLocFormat"));
+    }
+
+    // maxp: glyph count
+    let maxp = required(table, "maxp")?;
+    let num_glyphs = read_u16_at(maxp, 4, "maxp")?;
+    if num_glyphs == 0 || num_glyphs > options.max_glyphs {
+        return Err(invalid("maxp", "glyph count out of range"));
+    }
+
+    // hhea/hmtx: metric counts must be consistent with the glyph count
+    if let Some(hhea) = table("hhea") {
+        let num_h_metrics = read_u16_at(hhea, 34, "hhea")?;
+        if num_h_metrics == 0 || num_h_metrics > num_glyphs {
+            return Err(invalid("hhea", "numberOfHMetrics out of range"));
+        }
+        let hmtx = required(table, "hmtx")?;
+        let expected = num_h_metrics as usize * 4 + (num_glyphs - num_h_metrics) as usize * 2;
+        if hmtx.len() < expected {
+            return Err(invalid("hmtx", "table shorter than declared metrics"));
+        }
+    }
+
+    // glyf/loca: loca must cover every glyph and stay inside glyf
+    if let Some(glyf) = table("glyf") {
+        let loca = required(table, "loca")?;
+        let entry_size = if index_to_loc_format == 0 { 2 } else { 4 };
+        let entries = num_glyphs as usize + 1;
+        if loca.len() < entries * entry_size {
+            return Err(invalid("loca", "table shorter than glyph count"));
+        }
+
+        let mut previous = 0usize;
+        for i in 0..entries {
+            let offset = if entry_size == 2 {
+                read_u16_at(loca, (i * 2) as u64, "loca")? as usize * 2
+            } else {
+                let hi = read_u16_at(loca, (i * 4) as u64, "loca")? as usize;
+                let lo = read_u16_at(loca, (i * 4 + 2) as u64, "loca")? as usize;
+                (hi << 16) | lo
+            };
+            if offset < previous || offset > glyf.len() {
+                return Err(invalid(
+                    "loca",
+                    "glyph offsets out of order or out of bounds",
+                ));
+            }
+            previous = offset;
+        }
+    }
+
+    Ok(())
+}
+
+/// Re-serialize validated tables into a fresh sfnt with a sorted directory
+fn serialize(data: &[u8], version: u32, mut records: Vec<RawTableRecord>) -> SanitizedFont {
+    records.sort_by_key(|record| record.tag.to_bytes());
+
+    let num_tables = records.len() as u16;
+    let entry_selector = 15 - num_tables.leading_zeros() as u16;
+    let search_range = (1u32 << entry_selector) * 16;
+    let range_shift = (num_tables as u32 * 16).saturating_sub(search_range);
+
+    let mut out = Vec::with_capacity(
+        SFNT_HEADER_SIZE
+            + records.len() * TABLE_RECORD_SIZE
+            + records.iter().map(|r| (r.length + 3) & !3).sum::<usize>(),
+    );
+    out.extend_from_slice(&version.to_be_bytes());
+    out.extend_from_slice(&num_tables.to_be_bytes());
+    out.extend_from_slice(&(search_range as u16).to_be_bytes());
+    out.extend_from_slice(&entry_selector.to_be_bytes());
+    out.extend_from_slice(&(range_shift as u16).to_be_bytes());
+
+    let mut offset = SFNT_HEADER_SIZE + records.len() * TABLE_RECORD_SIZE;
+    let mut table_data = Vec::new();
+    let mut head_offset = None;
+
+    for record in &records {
+        let mut table = data[record.offset..record.offset + record.length].to_vec();
+        if record.tag == Tag::HEAD {
+            // Recomputed below once the whole font is assembled
+            table[CHECKSUM_ADJUSTMENT_OFFSET..CHECKSUM_ADJUSTMENT_OFFSET + 4].fill(0);
+            head_offset = Some(offset);
+        }
+
+        out.extend_from_slice(&record.tag.to_bytes().to_be_bytes());
+        out.extend_from_slice(&calculate_checksum(&table).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(record.length as u32).to_be_bytes());
+
+        table_data.extend_from_slice(&table);
+        // Pad to 4-byte boundary
+        while table_data.len() % 4 != 0 {
+            table_data.push(0);
+        }
+        offset = SFNT_HEADER_SIZE + records.len() * TABLE_RECORD_SIZE + table_data.len();
+    }
+    out.extend_from_slice(&table_data);
+
+    if let Some(head_offset) = head_offset {
+        let adjustment = CHECKSUM_ADJUSTMENT_BASE.wrapping_sub(calculate_checksum(&out));
+        let start = head_offset + CHECKSUM_ADJUSTMENT_OFFSET;
+        out[start..start + 4].copy_from_slice(&adjustment.to_be_bytes());
+    }
+
+    SanitizedFont {
+        data: out,
+        tables: records.iter().map(|record| record.tag).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_checksum_pads_trailing_bytes() {
+        assert_eq!(calculate_checksum(&[]), 0);
+        assert_eq!(calculate_checksum(&[0, 0, 0, 1, 0, 0, 0, 2]), 3);
+        assert_eq!(calculate_checksum(&[0x01]), 0x0100_0000);
+    }
+
+    #[test]
+    fn test_calculate_checksum_wraps() {
+        assert_eq!(calculate_checksum(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 2]), 1);
+    }
+
+    #[test]
+    fn test_validate_rejects_short_data() {
+        assert_eq!(validate(&[0, 1]), Err(ValidationError::TooShort));
+    }
+
+    #[test]
+    fn test_validate_rejects_oversized_data() {
+        let options = ValidationOptions {
+            max_font_size: 8,
+            ..Default::default()
+        };
+        let result = validate_with_options(&[0u8; 16], &options);
+        assert_eq!(result, Err(ValidationError::TooLarge { size: 16, max: 8 }));
+    }
+
+    #[test]
+    fn test_validation_error_display() {
+        let tag = Tag::new("glyf").unwrap();
+        assert_eq!(
+            format!("{}", ValidationError::TableOutOfBounds(tag)),
+            "Table glyf extends beyond font data"
+        );
+        assert_eq!(
+            format!("{}", ValidationError::InvalidTableCount(0)),
+            "Invalid table count: 0"
+        );
+    }
+}
@@ -3,6 +3,7 @@
 //! This module implements parsing for variable fonts, which allow fonts to interpolate
 //! between different design axes (weight, width, slant, optical size, etc.).
 
+use crate::limits::{self, MAX_NAMED_INSTANCES, MAX_VARIATION_AXES};
 use crate::types::Tag;
 use crate::ParseError;
 use byteorder::{BigEndian, ReadBytesExt};
@@ -23,6 +24,24 @@ pub struct VariationAxis {
     pub max_value: f32,
 }
 
+impl VariationAxis {
+    /// Map a user-space value to the normalized -1..1 range
+    ///
+    /// The default value maps to 0 and the min/max values to -1/1; values
+    /// outside the axis range are clamped. This is the space variation data
+    /// (gvar, HVAR, ...) is defined in, before any avar remapping.
+    pub fn normalize(&self, value: f32) -> f32 {
+        let value = value.max(self.min_value).min(self.max_value);
+        if value < self.default_value && self.default_value > self.min_value {
+            (value - self.default_value) / (self.default_value - self.min_value)
+        } else if value > self.default_value && self.max_value > self.default_value {
+            (value - self.default_value) / (self.max_value - self.default_value)
+        } else {
+            0.0
+        }
+    }
+}
+
 /// Named instance in variable font
 #[derive(Debug, Clone, PartialEq)]
 pub struct NamedInstance {
@@ -91,8 +110,33 @@ impl FvarTable {
             .read_u16::<BigEndian>()
             .map_err(|e| ParseError::CorruptedData(format!("instance size: {}", e)))?;
 
+        limits::check_cap("fvar axis", axis_count as usize, MAX_VARIATION_AXES)?;
+        limits::check_cap(
+            "fvar instance",
+            instance_count as usize,
+            MAX_NAMED_INSTANCES,
+        )?;
+
+        // Every axis and instance record is read in full even when the
+        // declared record size is smaller, so size the check accordingly
+        let axis_record_size = (axis_size as usize).max(20);
+        let instance_record_size = (instance_size as usize).max(4 + axis_count as usize * 4);
+        let available = limits::remaining(data, axes_array_offset as u64);
+        limits::check_declared(
+            "fvar axes",
+            axis_count as usize,
+            axis_record_size,
+            available,
+        )?;
+        limits::check_declared(
+            "fvar instances",
+            instance_count as usize,
+            instance_record_size,
+            available - axis_count as usize * axis_record_size,
+        )?;
+
         // Parse axes
-        let mut axes = Vec::new();
+        let mut axes = Vec::with_capacity(axis_count as usize);
         cursor.set_position(axes_array_offset as u64);
 
         for i in 0..axis_count {
@@ -134,7 +178,7 @@ impl FvarTable {
         }
 
         // Parse named instances
-        let mut instances = Vec::new();
+        let mut instances = Vec::with_capacity(instance_count as usize);
         for i in 0..instance_count {
             let subfamily_name_id = cursor.read_u16::<BigEndian>().map_err(|e| {
                 ParseError::CorruptedData(format!("instance {} subfamily_name_id: {}", i, e))
@@ -143,7 +187,7 @@ impl FvarTable {
                 .read_u16::<BigEndian>()
                 .map_err(|e| ParseError::CorruptedData(format!("instance {} flags: {}", i, e)))?;
 
-            let mut coordinates = Vec::new();
+            let mut coordinates = Vec::with_capacity(axis_count as usize);
             for j in 0..axis_count {
                 let coord = Fixed::read(&mut cursor).map_err(|e| {
                     ParseError::CorruptedData(format!("instance {} coord {}: {}", i, j, e))
@@ -191,6 +235,30 @@ impl FvarTable {
     pub fn is_variable(&self) -> bool {
         !self.axes.is_empty()
     }
+
+    /// Normalize user-space coordinates, one value per axis
+    ///
+    /// Axes missing from `coords` take their default (0). The avar mapping
+    /// is applied when given, and values are rounded to F2DOT14 precision
+    /// like the variation tables they are matched against.
+    pub fn normalize_coordinates(
+        &self,
+        coords: &VariationCoordinates,
+        avar: Option<&AvarTable>,
+    ) -> Vec<f32> {
+        self.axes
+            .iter()
+            .enumerate()
+            .map(|(index, axis)| {
+                let value = coords.get_axis(axis.tag).unwrap_or(axis.default_value);
+                let mut normalized = axis.normalize(value);
+                if let Some(avar) = avar {
+                    normalized = avar.map_value(index, normalized);
+                }
+                (normalized * 16384.0).round() / 16384.0
+            })
+            .collect()
+    }
 }
 
 /// Axis Variations Table (avar)
@@ -207,7 +275,7 @@ pub struct AvarTable {
 #[derive(Debug, Clone, PartialEq)]
 pub struct AxisSegmentMap {
     /// Mappings from input coordinates to output coordinates
-    pub mappings: Vec<(f32, f32)>, // (from_coord, to_coord)
+    pub mappings: Vec<(f32, f32)>, // (from_coord, to_coord), normalized
 }
 
 impl AvarTable {
@@ -254,21 +322,36 @@ impl AvarTable {
             )));
         }
 
+        limits::check_cap("avar axis", axis_count, MAX_VARIATION_AXES)?;
+        // Each segment map holds at least its u16 mapping count
+        limits::check_declared(
+            "avar segment maps",
+            axis_count,
+            2,
+            limits::remaining(data, cursor.position()),
+        )?;
+
         // Parse segment maps for each axis
-        let mut axis_segment_maps = Vec::new();
+        let mut axis_segment_maps = Vec::with_capacity(axis_count);
         for i in 0..axis_count {
             let position_map_count = cursor.read_u16::<BigEndian>().map_err(|e| {
                 ParseError::CorruptedData(format!("axis {} position_map_count: {}", i, e))
             })?;
-
-            let mut mappings = Vec::new();
+            limits::check_declared(
+                "avar axis value maps",
+                position_map_count as usize,
+                4,
+                limits::remaining(data, cursor.position()),
+            )?;
+
+            let mut mappings = Vec::with_capacity(position_map_count as usize);
             for j in 0..position_map_count {
-                let from_coord = Fixed::read(&mut cursor)
+                let from_coord = F2Dot14::read(&mut cursor)
                     .map_err(|e| {
                         ParseError::CorruptedData(format!("axis {} mapping {} from: {}", i, j, e))
                     })?
                     .to_f32();
-                let to_coord = Fixed::read(&mut cursor)
+                let to_coord = F2Dot14::read(&mut cursor)
                     .map_err(|e| {
                         ParseError::CorruptedData(format!("axis {} mapping {} to: {}", i, j, e))
                     })?
@@ -312,6 +395,373 @@ impl AvarTable {
     }
 }
 
+/// Axis value flag: the value applies to older sibling fonts only
+const OLDER_SIBLING_FONT_ATTRIBUTE: u16 = 0x0001;
+
+/// Axis value flag: the name may be omitted when composing a style name
+const ELIDABLE_AXIS_VALUE_NAME: u16 = 0x0002;
+
+/// Name ID used as elided fallback when STAT 1.0 does not provide one ("Regular")
+const DEFAULT_ELIDED_FALLBACK_NAME_ID: u16 = 2;
+
+/// Tolerance when matching coordinates against 16.16 axis values
+const VALUE_EPSILON: f32 = 0.001;
+
+/// Design axis record from the STAT table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatAxisRecord {
+    /// Axis tag (may name axes that are not in fvar, e.g. `ital` in a family)
+    pub tag: Tag,
+    /// Name ID of the axis name
+    pub name_id: u16,
+    /// Position of this axis when composing style names
+    pub ordering: u16,
+}
+
+/// Axis value description, by STAT axis value table format
+#[derive(Debug, Clone, PartialEq)]
+pub enum AxisValueKind {
+    /// Format 1: a single value on one axis
+    Single {
+        /// Index into the design axes
+        axis_index: u16,
+        /// Axis value
+        value: f32,
+    },
+    /// Format 2: a nominal value with the range it covers
+    Range {
+        /// Index into the design axes
+        axis_index: u16,
+        /// Nominal axis value
+        nominal_value: f32,
+        /// Smallest value covered
+        min_value: f32,
+        /// Largest value covered
+        max_value: f32,
+    },
+    /// Format 3: a value with a style-linked counterpart (e.g. Regular/Bold)
+    Linked {
+        /// Index into the design axes
+        axis_index: u16,
+        /// Axis value
+        value: f32,
+        /// Value of the linked style
+        linked_value: f32,
+    },
+    /// Format 4: a combination of values on several axes
+    Combination {
+        /// (design axis index, value) pairs
+        values: Vec<(u16, f32)>,
+    },
+}
+
+/// Axis value table: a display name for a position in the design space
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisValue {
+    /// Axis value flags
+    pub flags: u16,
+    /// Name ID of the display name (e.g. "Condensed", "Black")
+    pub value_name_id: u16,
+    /// Format-specific value data
+    pub kind: AxisValueKind,
+}
+
+impl AxisValue {
+    /// Whether the name may be dropped from a composed style name (e.g. "Regular")
+    pub fn is_elidable(&self) -> bool {
+        self.flags & ELIDABLE_AXIS_VALUE_NAME != 0
+    }
+
+    /// Whether the value only describes older sibling fonts
+    pub fn is_older_sibling(&self) -> bool {
+        self.flags & OLDER_SIBLING_FONT_ATTRIBUTE != 0
+    }
+
+    /// Check whether a single-axis value covers `value` on `axis_index`
+    fn matches_axis(&self, axis_index: u16, value: f32) -> bool {
+        match self.kind {
+            AxisValueKind::Single {
+                axis_index: index,
+                value: v,
+            }
+            | AxisValueKind::Linked {
+                axis_index: index,
+                value: v,
+                ..
+            } => index == axis_index && (v - value).abs() < VALUE_EPSILON,
+            AxisValueKind::Range {
+                axis_index: index,
+                min_value,
+                max_value,
+                ..
+            } => index == axis_index && value >= min_value && value <= max_value,
+            AxisValueKind::Combination { .. } => false,
+        }
+    }
+}
+
+/// Style Attributes Table (STAT)
+///
+/// Describes the design axes of a font family and names positions along
+/// them, so UIs can label arbitrary variable font instances (e.g.
+/// "Condensed Black") rather than only the fvar named instances.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatTable {
+    /// Design axes, including axes the font itself does not vary
+    pub design_axes: Vec<StatAxisRecord>,
+    /// Axis value tables
+    pub axis_values: Vec<AxisValue>,
+    /// Name ID used when every name of a style is elided
+    pub elided_fallback_name_id: u16,
+}
+
+impl StatTable {
+    /// Parse STAT table from raw data
+    ///
+    /// Versions 1.0 to 1.2 are supported. Axis value tables with unknown
+    /// formats are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the table data is invalid or corrupted.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(data);
+
+        let major_version = cursor
+            .read_u16::<BigEndian>()
+            .map_err(|e| ParseError::CorruptedData(format!("STAT version: {}", e)))?;
+        let minor_version = cursor
+            .read_u16::<BigEndian>()
+            .map_err(|e| ParseError::CorruptedData(format!("STAT minor version: {}", e)))?;
+        if major_version != 1 {
+            return Err(ParseError::CorruptedData(format!(
+                "Unsupported STAT version {}.{}",
+                major_version, minor_version
+            )));
+        }
+
+        let design_axis_size = cursor
+            .read_u16::<BigEndian>()
+            .map_err(|e| ParseError::CorruptedData(format!("design axis size: {}", e)))?;
+        let design_axis_count = cursor
+            .read_u16::<BigEndian>()
+            .map_err(|e| ParseError::CorruptedData(format!("design axis count: {}", e)))?;
+        let design_axes_offset = cursor
+            .read_u32::<BigEndian>()
+            .map_err(|e| ParseError::CorruptedData(format!("design axes offset: {}", e)))?;
+        let axis_value_count = cursor
+            .read_u16::<BigEndian>()
+            .map_err(|e| ParseError::CorruptedData(format!("axis value count: {}", e)))?;
+        let axis_value_offsets_offset = cursor
+            .read_u32::<BigEndian>()
+            .map_err(|e| ParseError::CorruptedData(format!("axis value offsets: {}", e)))?;
+        let elided_fallback_name_id = if minor_version >= 1 {
+            cursor
+                .read_u16::<BigEndian>()
+                .map_err(|e| ParseError::CorruptedData(format!("elided fallback: {}", e)))?
+        } else {
+            DEFAULT_ELIDED_FALLBACK_NAME_ID
+        };
+
+        if design_axis_count > 0 && design_axis_size < 8 {
+            return Err(ParseError::CorruptedData(format!(
+                "STAT design axis size {} too small",
+                design_axis_size
+            )));
+        }
+        limits::check_cap(
+            "STAT design axis",
+            design_axis_count as usize,
+            MAX_VARIATION_AXES,
+        )?;
+        limits::check_declared(
+            "STAT design axes",
+            design_axis_count as usize,
+            design_axis_size as usize,
+            limits::remaining(data, design_axes_offset as u64),
+        )?;
+        limits::check_declared(
+            "STAT axis value offsets",
+            axis_value_count as usize,
+            2,
+            limits::remaining(data, axis_value_offsets_offset as u64),
+        )?;
+
+        let mut design_axes = Vec::with_capacity(design_axis_count as usize);
+        for i in 0..design_axis_count {
+            cursor.set_position(design_axes_offset as u64 + i as u64 * design_axis_size as u64);
+            let tag = Tag::from_bytes(
+                cursor
+                    .read_u32::<BigEndian>()
+                    .map_err(|e| ParseError::CorruptedData(format!("axis {} tag: {}", i, e)))?,
+            );
+            let name_id = cursor
+                .read_u16::<BigEndian>()
+                .map_err(|e| ParseError::CorruptedData(format!("axis {} name_id: {}", i, e)))?;
+            let ordering = cursor
+                .read_u16::<BigEndian>()
+                .map_err(|e| ParseError::CorruptedData(format!("axis {} ordering: {}", i, e)))?;
+            design_axes.push(StatAxisRecord {
+                tag,
+                name_id,
+                ordering,
+            });
+        }
+
+        let mut axis_values = Vec::with_capacity(axis_value_count as usize);
+        for i in 0..axis_value_count {
+            cursor.set_position(axis_value_offsets_offset as u64 + i as u64 * 2);
+            let offset = cursor.read_u16::<BigEndian>().map_err(|e| {
+                ParseError::CorruptedData(format!("axis value {} offset: {}", i, e))
+            })?;
+            // Offsets are relative to the start of the offsets array
+            cursor.set_position(axis_value_offsets_offset as u64 + offset as u64);
+            if let Some(axis_value) = Self::parse_axis_value(&mut cursor)? {
+                axis_values.push(axis_value);
+            }
+        }
+
+        Ok(StatTable {
+            design_axes,
+            axis_values,
+            elided_fallback_name_id,
+        })
+    }
+
+    fn parse_axis_value(cursor: &mut Cursor<&[u8]>) -> Result<Option<AxisValue>, ParseError> {
+        let format = cursor.read_u16::<BigEndian>()?;
+        let (flags, value_name_id, kind) = match format {
+            1..=3 => {
+                let axis_index = cursor.read_u16::<BigEndian>()?;
+                let flags = cursor.read_u16::<BigEndian>()?;
+                let value_name_id = cursor.read_u16::<BigEndian>()?;
+                let value = Fixed::read(cursor)?.to_f32();
+                let kind = match format {
+                    1 => AxisValueKind::Single { axis_index, value },
+                    2 => AxisValueKind::Range {
+                        axis_index,
+                        nominal_value: value,
+                        min_value: Fixed::read(cursor)?.to_f32(),
+                        max_value: Fixed::read(cursor)?.to_f32(),
+                    },
+                    _ => AxisValueKind::Linked {
+                        axis_index,
+                        value,
+                        linked_value: Fixed::read(cursor)?.to_f32(),
+                    },
+                };
+                (flags, value_name_id, kind)
+            }
+            4 => {
+                let axis_count = cursor.read_u16::<BigEndian>()?;
+                let flags = cursor.read_u16::<BigEndian>()?;
+                let value_name_id = cursor.read_u16::<BigEndian>()?;
+                limits::check_cap("STAT axis value", axis_count as usize, MAX_VARIATION_AXES)?;
+                let mut values = Vec::with_capacity(axis_count as usize);
+                for _ in 0..axis_count {
+                    let axis_index = cursor.read_u16::<BigEndian>()?;
+                    values.push((axis_index, Fixed::read(cursor)?.to_f32()));
+                }
+                (flags, value_name_id, AxisValueKind::Combination { values })
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(AxisValue {
+            flags,
+            value_name_id,
+            kind,
+        }))
+    }
+
+    /// Get the design axis record for a tag
+    pub fn get_axis(&self, tag: Tag) -> Option<&StatAxisRecord> {
+        self.design_axes.iter().find(|axis| axis.tag == tag)
+    }
+
+    /// Compose the name IDs that label an arbitrary coordinate set
+    ///
+    /// Returns name IDs in design axis ordering, e.g. the IDs for
+    /// "Condensed" and "Black". A format 4 value matching several axes at
+    /// once takes precedence over per-axis values; exact values take
+    /// precedence over ranges. Elidable names are dropped, and if nothing
+    /// remains the elided fallback name (usually "Regular") is returned.
+    /// Axes missing from `coords` are not named.
+    pub fn name_coordinates(&self, coords: &VariationCoordinates) -> Vec<u16> {
+        let axis_value = |index: u16| {
+            self.design_axes
+                .get(index as usize)
+                .and_then(|axis| coords.get_axis(axis.tag))
+        };
+        let ordering = |index: u16| {
+            self.design_axes
+                .get(index as usize)
+                .map_or(u16::MAX, |axis| axis.ordering)
+        };
+
+        let mut named = vec![false; self.design_axes.len()];
+        let mut names: Vec<(u16, &AxisValue)> = Vec::new();
+
+        // Combinations covering the most axes first
+        let mut combinations: Vec<(&AxisValue, &[(u16, f32)])> = self
+            .axis_values
+            .iter()
+            .filter_map(|value| match &value.kind {
+                AxisValueKind::Combination { values } => Some((value, values.as_slice())),
+                _ => None,
+            })
+            .collect();
+        combinations.sort_by_key(|(_, values)| std::cmp::Reverse(values.len()));
+        for (value, values) in combinations {
+            let applies = !values.is_empty()
+                && values.iter().all(|&(index, expected)| {
+                    named.get(index as usize) == Some(&false)
+                        && axis_value(index)
+                            .is_some_and(|actual| (actual - expected).abs() < VALUE_EPSILON)
+                });
+            if applies {
+                for &(index, _) in values {
+                    named[index as usize] = true;
+                }
+                let first = values.iter().map(|&(index, _)| ordering(index)).min();
+                names.push((first.unwrap_or(u16::MAX), value));
+            }
+        }
+
+        for index in 0..self.design_axes.len() as u16 {
+            if named[index as usize] {
+                continue;
+            }
+            let Some(actual) = axis_value(index) else {
+                continue;
+            };
+            let candidates = || {
+                self.axis_values
+                    .iter()
+                    .filter(move |value| value.matches_axis(index, actual))
+            };
+            let exact =
+                candidates().find(|value| !matches!(value.kind, AxisValueKind::Range { .. }));
+            if let Some(value) = exact.or_else(|| candidates().next()) {
+                names.push((ordering(index), value));
+            }
+        }
+
+        names.sort_by_key(|&(ordering, _)| ordering);
+        let name_ids: Vec<u16> = names
+            .into_iter()
+            .filter(|(_, value)| !value.is_elidable())
+            .map(|(_, value)| value.value_name_id)
+            .collect();
+
+        if name_ids.is_empty() {
+            vec![self.elided_fallback_name_id]
+        } else {
+            name_ids
+        }
+    }
+}
+
 /// Fixed-point number (16.16 format)
 ///
 /// OpenType uses 16.16 fixed-point numbers for precise representation
@@ -331,6 +781,24 @@ impl Fixed {
     }
 }
 
+/// Fixed-point number (2.14 format)
+///
+/// Used for normalized coordinates in the -2..2 range, e.g. avar mappings.
+#[derive(Debug, Copy, Clone)]
+struct F2Dot14(i16);
+
+impl F2Dot14 {
+    fn read<R: std::io::Read>(reader: &mut R) -> Result<Self, ParseError> {
+        Ok(F2Dot14(reader.read_i16::<BigEndian>().map_err(|e| {
+            ParseError::CorruptedData(format!("F2Dot14 value: {}", e))
+        })?))
+    }
+
+    fn to_f32(self) -> f32 {
+        (self.0 as f32) / 16384.0
+    }
+}
+
 /// Coordinates for variable font instance creation
 ///
 /// Represents a set of axis coordinates that define a specific instance
@@ -387,6 +855,12 @@ mod tests {
         assert!((fixed.to_f32() - (-1.5)).abs() < 0.0001);
     }
 
+    #[test]
+    fn test_f2dot14_to_f32() {
+        assert!((F2Dot14(16384).to_f32() - 1.0).abs() < 0.0001);
+        assert!((F2Dot14(-8192).to_f32() - (-0.5)).abs() < 0.0001);
+    }
+
     #[test]
     fn test_variation_coordinates_new() {
         let coords = VariationCoordinates::new();
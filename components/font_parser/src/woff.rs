@@ -1,5 +1,6 @@
 //! WOFF (Web Open Font Format) parsing
 
+use crate::limits::{self, MAX_DECOMPRESSED_SIZE};
 use crate::ParseError;
 use byteorder::{BigEndian, ReadBytesExt};
 use flate2::read::ZlibDecoder;
@@ -76,8 +77,15 @@ impl WoffFont {
             .read_u32::<BigEndian>()
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
 
+        limits::check_declared(
+            "WOFF table directory",
+            num_tables as usize,
+            20,
+            limits::remaining(data, cursor.position()),
+        )?;
+
         // Read table directory
-        let mut tables = Vec::new();
+        let mut tables = Vec::with_capacity(num_tables as usize);
         for _ in 0..num_tables {
             let tag = cursor
                 .read_u32::<BigEndian>()
@@ -104,6 +112,13 @@ impl WoffFont {
             });
         }
 
+        let sfnt_size = tables
+            .iter()
+            .fold(12 + num_tables as usize * 16, |size, table| {
+                size.saturating_add((table.orig_length as usize + 3) & !3)
+            });
+        limits::check_cap("WOFF decoded byte", sfnt_size, MAX_DECOMPRESSED_SIZE)?;
+
         // Decompress tables and reconstruct TTF/OTF
         let ttf_data = Self::reconstruct_sfnt(data, &tables, flavor, num_tables)?;
 
@@ -139,7 +154,7 @@ impl WoffFont {
         } else {
             (num_tables as f32).log2().floor() as u16
         };
-        let range_shift = (num_tables * 16).saturating_sub(search_range as u16);
+        let range_shift = (num_tables as u32 * 16).saturating_sub(search_range) as u16;
 
         let mut sfnt = Vec::new();
 
@@ -168,14 +183,21 @@ impl WoffFont {
 
         // Decompress and write table data
         for table in tables {
-            let comp_data =
-                &woff_data[table.offset as usize..(table.offset + table.comp_length) as usize];
+            let start = table.offset as usize;
+            let comp_data = start
+                .checked_add(table.comp_length as usize)
+                .and_then(|end| woff_data.get(start..end))
+                .ok_or_else(|| {
+                    ParseError::CorruptedData("WOFF table data out of range".to_string())
+                })?;
 
             let decompressed = if table.comp_length < table.orig_length {
-                // Table is compressed
-                let mut decoder = ZlibDecoder::new(comp_data);
+                // Table is compressed; read at most one byte past the declared
+                // length so a deflate bomb is caught by the size check below
+                let decoder = ZlibDecoder::new(comp_data);
                 let mut decompressed = Vec::new();
                 decoder
+                    .take(table.orig_length as u64 + 1)
                     .read_to_end(&mut decompressed)
                     .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
                 decompressed
@@ -205,15 +227,24 @@ impl WoffFont {
         woff_data: &[u8],
         offset: usize,
         comp_length: usize,
-        _orig_length: usize,
+        orig_length: usize,
     ) -> Result<String, ParseError> {
-        let comp_data = &woff_data[offset..offset + comp_length];
+        let comp_data = offset
+            .checked_add(comp_length)
+            .and_then(|end| woff_data.get(offset..end))
+            .ok_or_else(|| ParseError::CorruptedData("WOFF metadata out of range".to_string()))?;
 
-        let mut decoder = ZlibDecoder::new(comp_data);
+        let decoder = ZlibDecoder::new(comp_data);
         let mut metadata_xml = String::new();
         decoder
+            .take(orig_length as u64 + 1)
             .read_to_string(&mut metadata_xml)
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
+        if metadata_xml.len() > orig_length {
+            return Err(ParseError::CorruptedData(
+                "WOFF metadata larger than declared".to_string(),
+            ));
+        }
 
         Ok(metadata_xml)
     }
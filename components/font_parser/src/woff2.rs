@@ -1,5 +1,6 @@
 //! WOFF2 (Web Open Font Format 2) parsing
 
+use crate::limits::{self, MAX_DECOMPRESSED_SIZE};
 use crate::ParseError;
 use brotli::Decompressor;
 use byteorder::{BigEndian, ReadBytesExt};
@@ -41,7 +42,7 @@ impl Woff2Font {
         let _reserved = cursor
             .read_u16::<BigEndian>()
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
-        let _total_sfnt_size = cursor
+        let total_sfnt_size = cursor
             .read_u32::<BigEndian>()
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
         let total_compressed_size = cursor
@@ -59,7 +60,7 @@ impl Woff2Font {
         let meta_length = cursor
             .read_u32::<BigEndian>()
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
-        let _meta_orig_length = cursor
+        let meta_orig_length = cursor
             .read_u32::<BigEndian>()
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
         let _priv_offset = cursor
@@ -69,20 +70,37 @@ impl Woff2Font {
             .read_u32::<BigEndian>()
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
 
+        limits::check_cap(
+            "WOFF2 decoded byte",
+            total_sfnt_size as usize,
+            MAX_DECOMPRESSED_SIZE,
+        )?;
+
         // WOFF2 uses more complex reconstruction
         // For this implementation, we'll provide basic support
 
         // Read compressed data block
         let compressed_start = cursor.position() as usize;
-        let compressed_data =
-            &data[compressed_start..compressed_start + total_compressed_size as usize];
-
-        // Decompress using Brotli
-        let mut decompressor = Decompressor::new(compressed_data, 4096);
+        let compressed_data = compressed_start
+            .checked_add(total_compressed_size as usize)
+            .and_then(|end| data.get(compressed_start..end))
+            .ok_or_else(|| {
+                ParseError::CorruptedData("WOFF2 compressed data out of range".to_string())
+            })?;
+
+        // Decompress using Brotli, never producing more than the declared
+        // sfnt size
+        let decompressor = Decompressor::new(compressed_data, 4096);
         let mut decompressed = Vec::new();
         decompressor
+            .take(total_sfnt_size as u64 + 1)
             .read_to_end(&mut decompressed)
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
+        if decompressed.len() > total_sfnt_size as usize {
+            return Err(ParseError::CorruptedData(
+                "WOFF2 data larger than declared sfnt size".to_string(),
+            ));
+        }
 
         // WOFF2 format is complex - simplified reconstruction
         // In production, use a full WOFF2 library
@@ -94,6 +112,7 @@ impl Woff2Font {
                 data,
                 meta_offset as usize,
                 meta_length as usize,
+                meta_orig_length as usize,
             )?)
         } else {
             None
@@ -119,14 +138,24 @@ impl Woff2Font {
         woff2_data: &[u8],
         offset: usize,
         comp_length: usize,
+        orig_length: usize,
     ) -> Result<String, ParseError> {
-        let comp_data = &woff2_data[offset..offset + comp_length];
+        let comp_data = offset
+            .checked_add(comp_length)
+            .and_then(|end| woff2_data.get(offset..end))
+            .ok_or_else(|| ParseError::CorruptedData("WOFF2 metadata out of range".to_string()))?;
 
-        let mut decompressor = Decompressor::new(comp_data, 4096);
+        let decompressor = Decompressor::new(comp_data, 4096);
         let mut metadata_xml = String::new();
         decompressor
+            .take(orig_length as u64 + 1)
             .read_to_string(&mut metadata_xml)
             .map_err(|e| ParseError::CorruptedData(e.to_string()))?;
+        if metadata_xml.len() > orig_length {
+            return Err(ParseError::CorruptedData(
+                "WOFF2 metadata larger than declared".to_string(),
+            ));
+        }
 
         Ok(metadata_xml)
     }
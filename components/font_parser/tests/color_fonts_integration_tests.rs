@@ -1,6 +1,6 @@
 //! Integration tests for color fonts support
 
-use font_parser::{ColorFormat, OpenTypeFont, ParseError};
+use font_parser::{ColorFormat, OpenTypeFont};
 
 /// Create a minimal valid OpenType font with COLR and CPAL tables
 fn create_font_with_colr_cpal() -> Vec<u8> {
@@ -1,6 +1,20 @@
 //! Unit tests for font_parser
 
+mod test_base;
+mod test_checksums;
+mod test_cmap;
 mod test_color_fonts;
+mod test_glyf;
+mod test_layout;
+mod test_limits;
+mod test_malformed_fonts;
+mod test_math;
+mod test_metric_variations;
+mod test_metrics;
+mod test_name;
 mod test_opentype_font;
 mod test_parse_error;
+mod test_post;
+mod test_stat;
 mod test_tag;
+mod test_validation;
@@ -0,0 +1,100 @@
+//! Unit tests for baseline table (BASE) parsing through OpenTypeFont
+
+use super::test_validation::build_font;
+use font_parser::{OpenTypeFont, Tag};
+
+fn tag(s: &str) -> Tag {
+    Tag::new(s).unwrap()
+}
+
+/// Build a BASE table with a horizontal axis of `ideo` and `romn`
+/// baselines; each script gives its default baseline index and both
+/// coordinates
+fn base_table(scripts: &[(&str, u16, [i16; 2])]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+    data.extend_from_slice(&0u16.to_be_bytes()); // minorVersion
+    data.extend_from_slice(&8u16.to_be_bytes()); // horizAxisOffset
+    data.extend_from_slice(&0u16.to_be_bytes()); // vertAxisOffset
+
+    // Axis with its tag list right after it
+    data.extend_from_slice(&4u16.to_be_bytes()); // baseTagListOffset
+    data.extend_from_slice(&14u16.to_be_bytes()); // baseScriptListOffset
+    data.extend_from_slice(&2u16.to_be_bytes());
+    data.extend_from_slice(b"ideo");
+    data.extend_from_slice(b"romn");
+
+    // BaseScriptList; each BaseScript is followed by its BaseValues
+    let count = scripts.len() as u16;
+    data.extend_from_slice(&count.to_be_bytes());
+    for (index, (script, _, _)) in scripts.iter().enumerate() {
+        data.extend_from_slice(script.as_bytes());
+        let offset = 2 + 6 * count + 22 * index as u16;
+        data.extend_from_slice(&offset.to_be_bytes());
+    }
+    for (_, default_index, coordinates) in scripts {
+        data.extend_from_slice(&6u16.to_be_bytes()); // baseValuesOffset
+        data.extend_from_slice(&0u16.to_be_bytes()); // defaultMinMaxOffset
+        data.extend_from_slice(&0u16.to_be_bytes()); // baseLangSysCount
+        data.extend_from_slice(&default_index.to_be_bytes());
+        data.extend_from_slice(&2u16.to_be_bytes()); // baseCoordCount
+        data.extend_from_slice(&8u16.to_be_bytes());
+        data.extend_from_slice(&12u16.to_be_bytes());
+        for coordinate in coordinates {
+            data.extend_from_slice(&1u16.to_be_bytes()); // format
+            data.extend_from_slice(&coordinate.to_be_bytes());
+        }
+    }
+    data
+}
+
+#[test]
+fn test_base_coordinates_per_script() {
+    // Given a font whose Han and Latin baselines differ
+    // When reading the horizontal axis
+    // Then each script reports its own coordinates and default baseline
+    let table = base_table(&[("hani", 0, [-120, 0]), ("latn", 1, [-150, 0])]);
+    let font = OpenTypeFont::parse(build_font(&[("BASE", table)])).unwrap();
+
+    let base = font.get_base().unwrap();
+    assert!(base.vertical.is_none());
+    let horizontal = base.horizontal.unwrap();
+    assert_eq!(horizontal.baseline_tags, vec![tag("ideo"), tag("romn")]);
+    assert_eq!(horizontal.coordinate(tag("hani"), tag("ideo")), Some(-120));
+    assert_eq!(horizontal.coordinate(tag("latn"), tag("ideo")), Some(-150));
+    assert_eq!(horizontal.coordinate(tag("latn"), tag("hang")), None);
+    assert_eq!(
+        horizontal.script(tag("hani")).unwrap().default_baseline,
+        Some(tag("ideo"))
+    );
+    assert_eq!(
+        horizontal.script(tag("latn")).unwrap().default_baseline,
+        Some(tag("romn"))
+    );
+}
+
+#[test]
+fn test_base_falls_back_to_default_script() {
+    // Given a font with only a DFLT baseline record
+    // When asking for a script it doesn't list
+    // Then the DFLT coordinates are used
+    let table = base_table(&[("DFLT", 1, [-100, 0])]);
+    let font = OpenTypeFont::parse(build_font(&[("BASE", table)])).unwrap();
+
+    let horizontal = font.get_base().unwrap().horizontal.unwrap();
+    assert_eq!(horizontal.coordinate(tag("cyrl"), tag("ideo")), Some(-100));
+}
+
+#[test]
+fn test_base_missing_or_truncated() {
+    // Given fonts without a BASE table and with a truncated one
+    // When reading BASE
+    // Then neither yields a table
+    let font = OpenTypeFont::parse(build_font(&[("post", vec![0; 32])])).unwrap();
+    assert!(font.get_base().is_none());
+
+    let mut table = base_table(&[("latn", 1, [-150, 0])]);
+    table.truncate(30);
+    let font = OpenTypeFont::parse(build_font(&[("BASE", table)])).unwrap();
+    assert!(font.get_base().is_none());
+}
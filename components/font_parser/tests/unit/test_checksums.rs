@@ -0,0 +1,113 @@
+//! Unit tests for checksum verification and head table validation
+
+use super::test_validation::{build_font, head_table, maxp_table, minimal_font};
+use font_parser::{validate, OpenTypeFont, ParseError, ParseOptions, Tag};
+
+/// A font with correct table checksums and checkSumAdjustment
+fn checksummed_font() -> Vec<u8> {
+    validate(&minimal_font()).unwrap().into_data()
+}
+
+#[test]
+fn test_verify_checksums_valid_font() {
+    // Given a font with correct checksums
+    // When verifying
+    // Then the report is clean
+    let font = OpenTypeFont::parse(checksummed_font()).unwrap();
+    let report = font.verify_checksums();
+    assert!(report.is_valid());
+    assert!(report.failed_tables.is_empty());
+    assert!(report.head_valid);
+    assert!(report.checksum_adjustment_valid);
+}
+
+#[test]
+fn test_verify_checksums_reports_failed_tables() {
+    // Given a font whose directory checksums are all zero
+    // When verifying
+    // Then every table is reported as failed
+    let font = OpenTypeFont::parse(minimal_font()).unwrap();
+    let report = font.verify_checksums();
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.failed_tables,
+        vec![Tag::new("head").unwrap(), Tag::new("maxp").unwrap()]
+    );
+}
+
+#[test]
+fn test_verify_checksums_detects_corrupted_table_data() {
+    // Given a correct font with one byte of maxp corrupted
+    // When verifying
+    // Then only maxp fails and the checkSumAdjustment no longer matches
+    let mut data = checksummed_font();
+    let len = data.len();
+    data[len - 3] ^= 0xFF; // maxp is last in sorted order, followed by 2 padding bytes
+    let font = OpenTypeFont::parse(data).unwrap();
+    let report = font.verify_checksums();
+
+    assert_eq!(report.failed_tables, vec![Tag::new("maxp").unwrap()]);
+    assert!(report.head_valid);
+    assert!(!report.checksum_adjustment_valid);
+}
+
+#[test]
+fn test_verify_checksums_detects_bad_adjustment() {
+    // Given a correct font whose head.checkSumAdjustment was altered
+    // When verifying
+    // Then table checksums still pass but the adjustment check fails
+    let mut data = checksummed_font();
+    let head_offset = 12 + 2 * 16; // header + 2 directory records; head is first
+    data[head_offset + 8] ^= 0x01;
+
+    let report = OpenTypeFont::parse(data).unwrap().verify_checksums();
+    assert!(report.failed_tables.is_empty());
+    assert!(!report.checksum_adjustment_valid);
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn test_verify_checksums_missing_head() {
+    let data = build_font(&[("maxp", maxp_table(1))]);
+    let report = OpenTypeFont::parse(data).unwrap().verify_checksums();
+    assert!(!report.head_valid);
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn test_verify_checksums_bad_head_magic() {
+    let mut head = head_table(1000);
+    head[12] = 0;
+    let data = build_font(&[("head", head), ("maxp", maxp_table(1))]);
+    let report = OpenTypeFont::parse(data).unwrap().verify_checksums();
+    assert!(!report.head_valid);
+}
+
+#[test]
+fn test_parse_with_options_verification_is_opt_in() {
+    // Given a font with bad checksums
+    // When parsing without verification
+    // Then parsing succeeds
+    assert!(OpenTypeFont::parse_with_options(minimal_font(), &ParseOptions::default()).is_ok());
+
+    // When parsing with verification enabled
+    // Then the failing tables are named in the error
+    let options = ParseOptions {
+        verify_checksums: true,
+    };
+    match OpenTypeFont::parse_with_options(minimal_font(), &options) {
+        Err(ParseError::CorruptedData(msg)) => {
+            assert!(msg.contains("head"));
+            assert!(msg.contains("maxp"));
+        }
+        other => panic!("Expected CorruptedData, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_with_options_accepts_valid_checksums() {
+    let options = ParseOptions {
+        verify_checksums: true,
+    };
+    assert!(OpenTypeFont::parse_with_options(checksummed_font(), &options).is_ok());
+}
@@ -0,0 +1,115 @@
+//! Unit tests for character mapping table (cmap) parsing
+
+use super::test_validation::build_font;
+use font_parser::{CMapTable, OpenTypeFont, ParseError};
+
+/// Build a cmap with one Windows subtable
+fn cmap_table(encoding: u16, subtable: Vec<u8>) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_be_bytes()); // version
+    data.extend_from_slice(&1u16.to_be_bytes()); // numTables
+    data.extend_from_slice(&3u16.to_be_bytes()); // platformID
+    data.extend_from_slice(&encoding.to_be_bytes());
+    data.extend_from_slice(&12u32.to_be_bytes()); // subtable offset
+    data.extend_from_slice(&subtable);
+    data
+}
+
+/// Format 4 subtable mapping 'A'..='C' to glyphs 10..=12 by delta and
+/// 'a'..='b' to glyphs 20 and 0 through the glyph array
+fn format4() -> Vec<u8> {
+    let segments: [(u16, u16, u16, u16); 3] = [
+        (0x41, 0x43, 10u16.wrapping_sub(0x41), 0),
+        (0x61, 0x62, 0, 4), // glyph array right after the range offsets
+        (0xFFFF, 0xFFFF, 1, 0),
+    ];
+    let mut data = Vec::new();
+    data.extend_from_slice(&4u16.to_be_bytes()); // format
+    data.extend_from_slice(&0u16.to_be_bytes()); // length
+    data.extend_from_slice(&0u16.to_be_bytes()); // language
+    data.extend_from_slice(&6u16.to_be_bytes()); // segCountX2
+    data.extend_from_slice(&[0; 6]); // searchRange, entrySelector, rangeShift
+    for (_, end, _, _) in segments {
+        data.extend_from_slice(&end.to_be_bytes());
+    }
+    data.extend_from_slice(&0u16.to_be_bytes()); // reservedPad
+    for (start, _, _, _) in segments {
+        data.extend_from_slice(&start.to_be_bytes());
+    }
+    for (_, _, delta, _) in segments {
+        data.extend_from_slice(&delta.to_be_bytes());
+    }
+    for (_, _, _, range_offset) in segments {
+        data.extend_from_slice(&range_offset.to_be_bytes());
+    }
+    data.extend_from_slice(&20u16.to_be_bytes());
+    data.extend_from_slice(&0u16.to_be_bytes());
+    data
+}
+
+/// Format 12 subtable mapping U+1F600..=U+1F602 to glyphs 100..=102
+fn format12() -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&12u16.to_be_bytes()); // format
+    data.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&28u32.to_be_bytes()); // length
+    data.extend_from_slice(&0u32.to_be_bytes()); // language
+    data.extend_from_slice(&1u32.to_be_bytes()); // numGroups
+    data.extend_from_slice(&0x1F600u32.to_be_bytes());
+    data.extend_from_slice(&0x1F602u32.to_be_bytes());
+    data.extend_from_slice(&100u32.to_be_bytes());
+    data
+}
+
+#[test]
+fn test_cmap_format4_maps_bmp_characters() {
+    // Given a format 4 subtable with a delta segment and a glyph array segment
+    // When looking up characters
+    // Then both segment kinds map, and glyph 0 counts as unmapped
+    let cmap = CMapTable::parse(&cmap_table(1, format4())).unwrap();
+
+    assert_eq!(cmap.get_glyph('A'), Some(10));
+    assert_eq!(cmap.get_glyph('C'), Some(12));
+    assert_eq!(cmap.get_glyph('a'), Some(20));
+    assert_eq!(cmap.get_glyph('b'), None);
+    assert_eq!(cmap.get_glyph('D'), None);
+}
+
+#[test]
+fn test_cmap_format12_maps_supplementary_characters() {
+    // Given a format 12 subtable with one group of emoji
+    // When looking up characters inside and around the group
+    // Then only the group's characters map, to consecutive glyphs
+    let cmap = CMapTable::parse(&cmap_table(10, format12())).unwrap();
+
+    assert_eq!(cmap.get_glyph('\u{1F600}'), Some(100));
+    assert_eq!(cmap.get_glyph('\u{1F602}'), Some(102));
+    assert_eq!(cmap.get_glyph('\u{1F603}'), None);
+    assert_eq!(cmap.get_glyph('A'), None);
+}
+
+#[test]
+fn test_cmap_rejects_symbol_and_truncated_subtables() {
+    // Given a symbol-encoded subtable and a truncated Unicode one
+    // When parsing
+    // Then neither yields a table
+    assert!(matches!(
+        CMapTable::parse(&cmap_table(0, format4())),
+        Err(ParseError::InvalidFormat)
+    ));
+    let mut data = cmap_table(1, format4());
+    data.truncate(30);
+    assert!(CMapTable::parse(&data).is_err());
+}
+
+#[test]
+fn test_get_cmap_reads_font_table() {
+    // Given fonts with and without a cmap table
+    // When reading the cmap through the font
+    // Then only the font with the table maps characters
+    let font = OpenTypeFont::parse(build_font(&[("cmap", cmap_table(1, format4()))])).unwrap();
+    assert_eq!(font.get_cmap().unwrap().get_glyph('B'), Some(11));
+
+    let font = OpenTypeFont::parse(build_font(&[("post", vec![0; 32])])).unwrap();
+    assert!(font.get_cmap().is_none());
+}
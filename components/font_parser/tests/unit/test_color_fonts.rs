@@ -1,8 +1,11 @@
 //! Unit tests for color fonts support
 
 use font_parser::{
-    BaseGlyph, CbdtTable, Color, ColorFormat, ColrTable, CpalTable, Layer, SvgTable,
+    Affine, BaseGlyph, CbdtTable, Color, ColorFormat, ColorLine, ColorStop, ColrTable, CpalTable,
+    Extend, Layer, Paint, ParseError, SvgTable, PALETTE_USABLE_WITH_DARK_BACKGROUND,
+    PALETTE_USABLE_WITH_LIGHT_BACKGROUND,
 };
+use font_types::{BasePalette, FontPalette};
 use std::io::Write;
 
 #[test]
@@ -176,6 +179,77 @@ fn test_cpal_parse_multiple_palettes() {
     assert_eq!(palette1[1].green, 255);
 }
 
+/// CPAL version 1 table with one color per palette and the given type flags
+fn cpal_v1(palette_types: &[u32]) -> Vec<u8> {
+    let num_palettes = palette_types.len() as u16;
+    let indices_end = 12 + 2 * num_palettes as u32;
+    let types_offset = indices_end + 12;
+    let colors_offset = types_offset + 4 * num_palettes as u32;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // version
+    data.extend_from_slice(&1u16.to_be_bytes()); // num_palette_entries
+    data.extend_from_slice(&num_palettes.to_be_bytes());
+    data.extend_from_slice(&num_palettes.to_be_bytes()); // num_color_records
+    data.extend_from_slice(&colors_offset.to_be_bytes());
+    for palette in 0..num_palettes {
+        data.extend_from_slice(&palette.to_be_bytes());
+    }
+    data.extend_from_slice(&types_offset.to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes()); // no palette labels
+    data.extend_from_slice(&0u32.to_be_bytes()); // no entry labels
+    for palette_type in palette_types {
+        data.extend_from_slice(&palette_type.to_be_bytes());
+    }
+    for palette in 0..num_palettes {
+        data.extend_from_slice(&[0, 0, palette as u8 * 100, 255]); // red encodes the palette
+    }
+    data
+}
+
+#[test]
+fn test_cpal_v1_palette_types_select_light_and_dark() {
+    // Given: Palettes for no particular, a dark and a light background
+    let cpal = CpalTable::parse(&cpal_v1(&[
+        0,
+        PALETTE_USABLE_WITH_DARK_BACKGROUND,
+        PALETTE_USABLE_WITH_LIGHT_BACKGROUND,
+    ]))
+    .expect("Failed to parse CPAL");
+
+    // When: Selecting palettes by base
+    // Then: Flags pick light and dark, invalid indices fall back to palette 0
+    assert_eq!(cpal.palette_types, [0, 2, 1]);
+    assert_eq!(cpal.palette_labels, [None, None, None]);
+    assert_eq!(cpal.select_palette(BasePalette::Normal), 0);
+    assert_eq!(cpal.select_palette(BasePalette::Dark), 1);
+    assert_eq!(cpal.select_palette(BasePalette::Light), 2);
+    assert_eq!(cpal.select_palette(BasePalette::Index(2)), 2);
+    assert_eq!(cpal.select_palette(BasePalette::Index(9)), 0);
+
+    let unflagged = CpalTable::parse(&cpal_v1(&[0, 0])).unwrap();
+    assert_eq!(unflagged.select_palette(BasePalette::Dark), 0);
+}
+
+#[test]
+fn test_cpal_resolve_palette_applies_overrides() {
+    // Given: A dark palette and overrides inside and beyond it
+    let cpal = CpalTable::parse(&cpal_v1(&[0, PALETTE_USABLE_WITH_DARK_BACKGROUND])).unwrap();
+    let palette = FontPalette::new(BasePalette::Dark)
+        .with_override(0, [1, 2, 3, 4])
+        .with_override(5, [9, 9, 9, 9]);
+
+    // When: Resolving the palette
+    let colors = cpal.resolve_palette(&palette).unwrap();
+    let dark = cpal
+        .resolve_palette(&FontPalette::new(BasePalette::Dark))
+        .unwrap();
+
+    // Then: The in-range entry is replaced and the rest ignored
+    assert_eq!(colors, [Color::from_rgba(1, 2, 3, 4)]);
+    assert_eq!(dark, [Color::from_rgba(100, 0, 0, 255)]);
+}
+
 #[test]
 fn test_colr_parse_minimal() {
     // Create a minimal valid COLR table
@@ -283,10 +357,75 @@ fn test_svg_parse_minimal() {
     data.extend_from_slice(&0u16.to_be_bytes()); // version
     data.extend_from_slice(&10u32.to_be_bytes()); // svg_document_list_offset
     data.extend_from_slice(&0u32.to_be_bytes()); // reserved
+    data.extend_from_slice(&0u16.to_be_bytes()); // num_entries
 
     let svg = SvgTable::parse(&data).expect("Failed to parse SVG");
 
     assert_eq!(svg.version, 0);
+    assert!(svg.documents.is_empty());
+}
+
+/// SVG table with one document record per `(start, end, document)`
+fn svg_table(documents: &[(u16, u16, &[u8])]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_be_bytes()); // version
+    data.extend_from_slice(&10u32.to_be_bytes()); // svg_document_list_offset
+    data.extend_from_slice(&0u32.to_be_bytes()); // reserved
+
+    data.extend_from_slice(&(documents.len() as u16).to_be_bytes());
+    let mut offset = 2 + 12 * documents.len() as u32;
+    for (start, end, document) in documents {
+        data.extend_from_slice(&start.to_be_bytes());
+        data.extend_from_slice(&end.to_be_bytes());
+        data.extend_from_slice(&offset.to_be_bytes());
+        data.extend_from_slice(&(document.len() as u32).to_be_bytes());
+        offset += document.len() as u32;
+    }
+    for (_, _, document) in documents {
+        data.extend_from_slice(document);
+    }
+    data
+}
+
+#[test]
+fn test_svg_documents_by_glyph_range() {
+    // Given a plain document for glyphs 3-5 and a gzipped one for glyph 9
+    let plain = b"<svg><path id=\"glyph3\"/></svg>".as_slice();
+    let gzipped_source = b"<svg><path id=\"glyph9\"/></svg>".as_slice();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(gzipped_source).unwrap();
+    let gzipped = encoder.finish().unwrap();
+    let data = svg_table(&[(9, 9, &gzipped), (3, 5, plain)]);
+
+    // When the table is parsed
+    let svg = SvgTable::parse(&data).expect("Failed to parse SVG");
+
+    // Then documents are found by glyph and decompressed as needed
+    assert_eq!(svg.documents.len(), 2);
+    assert_eq!(svg.documents[0].start_glyph_id, 3);
+    assert!(svg.is_svg_glyph(4));
+    assert!(!svg.is_svg_glyph(6));
+    assert_eq!(svg.get_svg_document(5).unwrap().as_deref(), Some(plain));
+    assert_eq!(
+        svg.get_svg_document(9).unwrap().as_deref(),
+        Some(gzipped_source)
+    );
+    assert_eq!(svg.get_svg_document(2).unwrap(), None);
+}
+
+#[test]
+fn test_svg_document_out_of_range_is_rejected() {
+    let mut data = svg_table(&[(1, 1, b"<svg/>")]);
+    data.truncate(data.len() - 1);
+    assert!(matches!(
+        SvgTable::parse(&data),
+        Err(ParseError::CorruptedData(_))
+    ));
+
+    // Corrupt gzip data surfaces when the document is read
+    let data = svg_table(&[(1, 1, &[0x1F, 0x8B, 0x08, 0, 0])]);
+    let svg = SvgTable::parse(&data).expect("Failed to parse SVG");
+    assert!(svg.get_svg_document(1).is_err());
 }
 
 #[test]
@@ -354,3 +493,147 @@ fn test_colr_parse_insufficient_data() {
     let result = ColrTable::parse(&data);
     assert!(result.is_err());
 }
+
+/// COLR v1 header with a base glyph list at 34 and a layer list at 50,
+/// followed by `paints`; offsets inside `paints` count from byte 62
+fn colr_v1(glyph_paint: &[u8], layer_offsets: &[u32], paints: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // version
+    data.extend_from_slice(&0u16.to_be_bytes()); // num_base_glyph_records
+    data.extend_from_slice(&0u32.to_be_bytes()); // base_glyph_records_offset
+    data.extend_from_slice(&0u32.to_be_bytes()); // layer_records_offset
+    data.extend_from_slice(&0u16.to_be_bytes()); // num_layer_records
+    data.extend_from_slice(&34u32.to_be_bytes()); // base_glyph_list_offset
+    data.extend_from_slice(&50u32.to_be_bytes()); // layer_list_offset
+    data.extend_from_slice(&[0; 12]); // clip list, var index map, variation store
+
+    // Base glyph list: glyph 5 with its root paint right after the list
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(&5u16.to_be_bytes());
+    data.extend_from_slice(&10u32.to_be_bytes());
+    assert!(glyph_paint.len() <= 6);
+    data.extend_from_slice(glyph_paint);
+    data.resize(50, 0);
+
+    // Layer list, with offsets relative to its start
+    data.extend_from_slice(&(layer_offsets.len() as u32).to_be_bytes());
+    for offset in layer_offsets {
+        data.extend_from_slice(&(offset + 12).to_be_bytes());
+    }
+    data.resize(62, 0);
+    data.extend_from_slice(paints);
+    data
+}
+
+#[test]
+fn test_colr_v1_parse_paint_graph() {
+    // Given a v1 glyph whose two layers are a glyph filled with a linear
+    // gradient and a solid fill rotated 90 degrees around (100, 0)
+    #[rustfmt::skip]
+    let paints = [
+        // 0: PaintGlyph, paint at +6, glyph 7
+        10, 0, 0, 6, 0, 7,
+        // 6: PaintLinearGradient, color line at +16, p0 (0,0) p1 (100,0) p2 (0,100)
+        4, 0, 0, 16, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 100,
+        // 22: ColorLine, repeat, stops at 0 and 1
+        1, 0, 2, 0, 0, 0, 0, 0x40, 0, 0x40, 0, 0, 1, 0x20, 0,
+        // 37: PaintRotateAroundCenter, paint at +10, 0.5 half turns, (100, 0)
+        26, 0, 0, 10, 0x20, 0, 0, 100, 0, 0,
+        // 47: PaintSolid, palette entry 1, alpha 1
+        2, 0, 1, 0x40, 0,
+    ];
+    // PaintColrLayers with both layers
+    let root = [1, 2, 0, 0, 0, 0];
+    let data = colr_v1(&root, &[0, 37], &paints);
+
+    // When the table is parsed
+    let colr = ColrTable::parse(&data).expect("Failed to parse COLR v1");
+
+    // Then the glyph is a color glyph with a resolved paint tree
+    assert!(colr.is_color_glyph(5));
+    assert!(colr.has_paint(5));
+    assert!(colr.get_layers(5).is_none());
+    assert_eq!(colr.paint(6).unwrap(), None);
+
+    let Some(Paint::Layers(layers)) = colr.paint(5).unwrap() else {
+        panic!("expected layers");
+    };
+    assert_eq!(layers.len(), 2);
+    assert_eq!(
+        layers[0],
+        Paint::Glyph {
+            glyph_id: 7,
+            paint: Box::new(Paint::LinearGradient {
+                color_line: ColorLine {
+                    extend: Extend::Repeat,
+                    stops: vec![
+                        ColorStop {
+                            offset: 0.0,
+                            palette_index: 0,
+                            alpha: 1.0
+                        },
+                        ColorStop {
+                            offset: 1.0,
+                            palette_index: 1,
+                            alpha: 0.5
+                        },
+                    ],
+                },
+                p0: (0.0, 0.0),
+                p1: (100.0, 0.0),
+                p2: (0.0, 100.0),
+            }),
+        }
+    );
+
+    let Paint::Transform { transform, paint } = &layers[1] else {
+        panic!("expected transform");
+    };
+    assert_eq!(
+        **paint,
+        Paint::Solid {
+            palette_index: 1,
+            alpha: 1.0
+        }
+    );
+    // The center stays put and (200, 0) turns to (100, 100)
+    let (x, y) = transform.apply(100.0, 0.0);
+    assert!((x - 100.0).abs() < 1e-3 && y.abs() < 1e-3);
+    let (x, y) = transform.apply(200.0, 0.0);
+    assert!((x - 100.0).abs() < 1e-3 && (y - 100.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_colr_v1_cyclic_paint_is_rejected() {
+    // Given a PaintGlyph whose child offset points back at itself
+    let root = [10, 0, 0, 0, 0, 7];
+    let data = colr_v1(&root, &[], &[]);
+
+    // When the table is parsed and the paint is resolved
+    let colr = ColrTable::parse(&data).expect("Failed to parse COLR v1");
+
+    // Then resolving stops at the depth limit instead of recursing forever
+    assert!(matches!(colr.paint(5), Err(ParseError::CorruptedData(_))));
+}
+
+#[test]
+fn test_colr_v1_unknown_paint_format() {
+    // Given a root paint with an undefined format
+    let data = colr_v1(&[99], &[], &[]);
+
+    // When the paint is resolved, then it is reported as corrupted
+    let colr = ColrTable::parse(&data).expect("Failed to parse COLR v1");
+    assert!(colr.paint(5).is_err());
+}
+
+#[test]
+fn test_affine_invert_round_trips() {
+    let transform = Affine::rotate(30.0)
+        .multiply(&Affine::scale(2.0, 0.5))
+        .multiply(&Affine::translate(10.0, -4.0));
+    let inverse = transform.invert().unwrap();
+    let (x, y) = transform.apply(3.0, 7.0);
+    let (x, y) = inverse.apply(x, y);
+    assert!((x - 3.0).abs() < 1e-3 && (y - 7.0).abs() < 1e-3);
+    assert!(Affine::scale(0.0, 1.0).invert().is_none());
+}
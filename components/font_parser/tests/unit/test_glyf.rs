@@ -0,0 +1,313 @@
+//! Unit tests for glyf outlines and gvar outline variations
+
+use super::test_validation::{build_font, head_table, maxp_table};
+use font_parser::{GvarTable, OpenTypeFont, Tag, VariationCoordinates};
+
+/// Glyph 1: a 100x100 square with an off-curve second point
+fn square_glyph() -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&1i16.to_be_bytes()); // numberOfContours
+    for value in [0i16, 0, 100, 100] {
+        data.extend_from_slice(&value.to_be_bytes()); // bounding box
+    }
+    data.extend_from_slice(&3u16.to_be_bytes()); // endPtsOfContours
+    data.extend_from_slice(&0u16.to_be_bytes()); // instructionLength
+                                                 // Flags: same x/y, +x short, +y short, -x short
+    data.extend_from_slice(&[0x31, 0x32, 0x35, 0x23]);
+    data.extend_from_slice(&[100, 100]); // x deltas
+    data.push(100); // y deltas
+    data.push(0); // pad to an even length
+    data
+}
+
+/// Glyph 2: the square scaled by 0.5 at (50, -20) and unscaled at (120, 0)
+fn composite_glyph() -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&(-1i16).to_be_bytes());
+    data.extend_from_slice(&[0u8; 8]);
+    // ARG_1_AND_2_ARE_WORDS | ARGS_ARE_XY_VALUES | WE_HAVE_A_SCALE | MORE_COMPONENTS
+    data.extend_from_slice(&0x002Bu16.to_be_bytes());
+    data.extend_from_slice(&1u16.to_be_bytes());
+    data.extend_from_slice(&50i16.to_be_bytes());
+    data.extend_from_slice(&(-20i16).to_be_bytes());
+    data.extend_from_slice(&0x2000u16.to_be_bytes()); // scale 0.5
+                                                      // ARGS_ARE_XY_VALUES with byte arguments
+    data.extend_from_slice(&0x0002u16.to_be_bytes());
+    data.extend_from_slice(&1u16.to_be_bytes());
+    data.extend_from_slice(&[120, 0]);
+    data
+}
+
+/// Glyph 3: a composite that references itself
+fn recursive_glyph() -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&(-1i16).to_be_bytes());
+    data.extend_from_slice(&[0u8; 8]);
+    data.extend_from_slice(&0x0002u16.to_be_bytes());
+    data.extend_from_slice(&3u16.to_be_bytes());
+    data.extend_from_slice(&[0, 0]);
+    data
+}
+
+/// fvar with a single wght axis 100..400..900
+fn fvar_table() -> Vec<u8> {
+    let mut data = Vec::new();
+    for value in [1u16, 0, 16, 2, 1, 20, 0, 8] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data.extend_from_slice(b"wght");
+    for value in [100.0f32, 400.0, 900.0] {
+        data.extend_from_slice(&((value * 65536.0) as i32).to_be_bytes());
+    }
+    data.extend_from_slice(&[0, 0, 1, 0]); // flags, axisNameID
+    data
+}
+
+/// Tuple variation header with an embedded peak and private point numbers
+fn tuple_header(data_size: u16, peak: f32) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&data_size.to_be_bytes());
+    data.extend_from_slice(&0xA000u16.to_be_bytes());
+    data.extend_from_slice(&((peak * 16384.0) as i16).to_be_bytes());
+    data
+}
+
+/// gvar for glyphs 1 and 2
+///
+/// The square grows by 50x20 units at wght 900 and moves by (-40, -40) at
+/// wght 100, where only point 2 is listed and the others follow via IUP. The
+/// composite's first component moves by (10, 0) and its second by (0, 5) at
+/// wght 900.
+fn gvar_table() -> Vec<u8> {
+    let mut square = Vec::new();
+    square.extend_from_slice(&2u16.to_be_bytes()); // tupleVariationCount
+    square.extend_from_slice(&16u16.to_be_bytes()); // dataOffset
+    square.extend(tuple_header(19, 1.0));
+    square.extend(tuple_header(7, -1.0));
+    // All points (4 + 4 phantom), one byte run each for x and y
+    square.push(0);
+    square.extend_from_slice(&[7, 0, 50, 50, 0, 0, 0, 0, 0]);
+    square.extend_from_slice(&[7, 0, 0, 20, 20, 0, 0, 0, 0]);
+    // Point 2 only
+    square.extend_from_slice(&[1, 0, 2]);
+    square.extend_from_slice(&[0, (-40i8) as u8, 0, (-40i8) as u8]);
+
+    let mut composite = Vec::new();
+    composite.extend_from_slice(&1u16.to_be_bytes());
+    composite.extend_from_slice(&10u16.to_be_bytes());
+    composite.extend(tuple_header(15, 1.0));
+    // Both component offsets plus 4 phantom points
+    composite.push(0);
+    composite.extend_from_slice(&[5, 10, 0, 0, 0, 0, 0]);
+    composite.extend_from_slice(&[5, 0, 5, 0, 0, 0, 0]);
+    composite.push(0);
+
+    let offsets = [0, 0, square.len(), square.len() + composite.len()];
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+    data.extend_from_slice(&0u16.to_be_bytes()); // minorVersion
+    data.extend_from_slice(&1u16.to_be_bytes()); // axisCount
+    data.extend_from_slice(&0u16.to_be_bytes()); // sharedTupleCount
+    data.extend_from_slice(&30u32.to_be_bytes()); // sharedTuplesOffset
+    data.extend_from_slice(&4u16.to_be_bytes()); // glyphCount
+    data.extend_from_slice(&0u16.to_be_bytes()); // flags: short offsets
+    data.extend_from_slice(&30u32.to_be_bytes()); // glyphVariationDataArrayOffset
+    for offset in offsets.iter().chain(std::iter::once(&offsets[3])) {
+        data.extend_from_slice(&((*offset / 2) as u16).to_be_bytes());
+    }
+    data.extend(square);
+    data.extend(composite);
+    data
+}
+
+fn glyf_font(extra: Vec<(&str, Vec<u8>)>) -> OpenTypeFont {
+    let glyphs = [
+        Vec::new(),
+        square_glyph(),
+        composite_glyph(),
+        recursive_glyph(),
+    ];
+    let mut glyf = Vec::new();
+    let mut loca = vec![0u8, 0];
+    for glyph in &glyphs {
+        glyf.extend_from_slice(glyph);
+        loca.extend_from_slice(&((glyf.len() / 2) as u16).to_be_bytes());
+    }
+
+    let mut tables = vec![
+        ("head", head_table(1000)),
+        ("maxp", maxp_table(glyphs.len() as u16)),
+        ("loca", loca),
+        ("glyf", glyf),
+    ];
+    tables.extend(extra);
+    OpenTypeFont::parse(build_font(&tables)).unwrap()
+}
+
+fn variable_font() -> OpenTypeFont {
+    glyf_font(vec![("fvar", fvar_table()), ("gvar", gvar_table())])
+}
+
+fn weight(value: f32) -> VariationCoordinates {
+    let mut coords = VariationCoordinates::new();
+    coords.set_axis(Tag::WEIGHT, value);
+    coords
+}
+
+#[test]
+fn test_simple_glyph_outline() {
+    // Given a square encoded with short and unchanged coordinates
+    // When reading its outline
+    // Then points, on-curve flags and bounds are decoded
+    let outline = glyf_font(Vec::new()).get_glyph_outline(1).unwrap();
+
+    assert_eq!(outline.contours.len(), 1);
+    assert_eq!(
+        outline.contours[0].points,
+        vec![(0, 0), (100, 0), (100, 100), (0, 100)]
+    );
+    assert_eq!(outline.contours[0].on_curve, vec![true, false, true, true]);
+    assert_eq!((outline.bounds.x_min, outline.bounds.y_max), (0, 100));
+}
+
+#[test]
+fn test_composite_glyph_is_flattened() {
+    let outline = glyf_font(Vec::new()).get_glyph_outline(2).unwrap();
+
+    assert_eq!(outline.contours.len(), 2);
+    assert_eq!(
+        outline.contours[0].points,
+        vec![(50, -20), (100, -20), (100, 30), (50, 30)]
+    );
+    assert_eq!(
+        outline.contours[1].points,
+        vec![(120, 0), (220, 0), (220, 100), (120, 100)]
+    );
+    assert_eq!(
+        (
+            outline.bounds.x_min,
+            outline.bounds.y_min,
+            outline.bounds.x_max,
+            outline.bounds.y_max
+        ),
+        (50, -20, 220, 100)
+    );
+}
+
+#[test]
+fn test_empty_and_invalid_glyphs() {
+    let font = glyf_font(Vec::new());
+
+    // Glyphs without data have an empty outline
+    let outline = font.get_glyph_outline(0).unwrap();
+    assert!(outline.contours.is_empty());
+
+    // Recursive composites and out-of-range glyphs have none
+    assert!(font.get_glyph_outline(3).is_none());
+    assert!(font.get_glyph_outline(4).is_none());
+
+    // Fonts without glyf have no outlines
+    let font = OpenTypeFont::parse(build_font(&[("head", head_table(1000))])).unwrap();
+    assert!(font.get_glyph_outline(1).is_none());
+}
+
+#[test]
+fn test_parse_gvar_header() {
+    let data = gvar_table();
+    let gvar = GvarTable::parse(&data).unwrap();
+
+    assert_eq!(gvar.axis_count, 1);
+    assert!(gvar.shared_tuples.is_empty());
+    assert_eq!(gvar.glyph_count(), 4);
+    assert!(!gvar.has_variations(0));
+    assert!(gvar.has_variations(1));
+    assert!(gvar.has_variations(2));
+
+    assert!(GvarTable::parse(&data[..24]).is_err());
+}
+
+#[test]
+fn test_outline_at_applies_scaled_deltas() {
+    // Given a square that grows by (50, 20) at the wght maximum
+    // When requesting outlines at the default, maximum and halfway weights
+    // Then the deltas apply with the tuple scalar
+    let font = variable_font();
+
+    assert_eq!(
+        font.get_glyph_outline_at(1, &weight(400.0)),
+        font.get_glyph_outline(1)
+    );
+
+    let outline = font.get_glyph_outline_at(1, &weight(900.0)).unwrap();
+    assert_eq!(
+        outline.contours[0].points,
+        vec![(0, 0), (150, 0), (150, 120), (0, 120)]
+    );
+
+    let outline = font.get_glyph_outline_at(1, &weight(650.0)).unwrap();
+    assert_eq!(
+        outline.contours[0].points,
+        vec![(0, 0), (125, 0), (125, 110), (0, 110)]
+    );
+}
+
+#[test]
+fn test_outline_at_infers_untouched_points() {
+    // Given a tuple at the wght minimum that only lists point 2
+    // When requesting the outline at wght 100
+    // Then the whole contour follows that point
+    let outline = variable_font()
+        .get_glyph_outline_at(1, &weight(100.0))
+        .unwrap();
+    assert_eq!(
+        outline.contours[0].points,
+        vec![(-40, -40), (60, -40), (60, 60), (-40, 60)]
+    );
+}
+
+#[test]
+fn test_outline_at_varies_composite_offsets() {
+    // Given a composite whose component offsets vary as well as the square
+    // When requesting the outline at wght 900
+    // Then both the offsets and the component outlines vary
+    let outline = variable_font()
+        .get_glyph_outline_at(2, &weight(900.0))
+        .unwrap();
+    assert_eq!(
+        outline.contours[0].points,
+        vec![(60, -20), (135, -20), (135, 40), (60, 40)]
+    );
+    assert_eq!(
+        outline.contours[1].points,
+        vec![(120, 5), (270, 5), (270, 125), (120, 125)]
+    );
+}
+
+#[test]
+fn test_normalize_coordinates_applies_avar() {
+    // Given an avar mapping normalized 0.5 to 0.25
+    // When normalizing wght 650 (halfway between default and maximum)
+    // Then the remapped value is returned
+    let mut avar = Vec::new();
+    for value in [1u16, 0, 0, 1, 4] {
+        avar.extend_from_slice(&value.to_be_bytes());
+    }
+    for (from, to) in [(-1.0f32, -1.0f32), (0.0, 0.0), (0.5, 0.25), (1.0, 1.0)] {
+        avar.extend_from_slice(&((from * 16384.0) as i16).to_be_bytes());
+        avar.extend_from_slice(&((to * 16384.0) as i16).to_be_bytes());
+    }
+    let font = glyf_font(vec![("fvar", fvar_table()), ("avar", avar)]);
+
+    assert_eq!(font.normalize_coordinates(&weight(650.0)), vec![0.25]);
+    assert_eq!(font.normalize_coordinates(&weight(100.0)), vec![-1.0]);
+    assert_eq!(font.normalize_coordinates(&weight(2000.0)), vec![1.0]);
+    assert_eq!(
+        font.normalize_coordinates(&VariationCoordinates::new()),
+        vec![0.0]
+    );
+
+    // Without fvar there is nothing to normalize
+    assert!(glyf_font(Vec::new())
+        .normalize_coordinates(&weight(650.0))
+        .is_empty());
+}
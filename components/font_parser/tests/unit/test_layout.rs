@@ -0,0 +1,79 @@
+//! Unit tests for GSUB/GPOS feature introspection through OpenTypeFont
+
+use super::test_validation::build_font;
+use font_parser::{OpenTypeFont, Tag};
+
+/// Build a layout table with a `DFLT` script enabling all `features`
+fn layout_table(features: &[&str]) -> Vec<u8> {
+    let count = features.len() as u16;
+    let script_list_offset = 10u16;
+    let lang_sys_size = 6 + 2 * count;
+    let feature_list_offset = script_list_offset + 8 + 4 + lang_sys_size;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+    data.extend_from_slice(&0u16.to_be_bytes()); // minorVersion
+    data.extend_from_slice(&script_list_offset.to_be_bytes());
+    data.extend_from_slice(&feature_list_offset.to_be_bytes());
+    data.extend_from_slice(&0u16.to_be_bytes()); // lookupListOffset
+
+    // ScriptList with one DFLT script
+    data.extend_from_slice(&1u16.to_be_bytes());
+    data.extend_from_slice(b"DFLT");
+    data.extend_from_slice(&8u16.to_be_bytes());
+    data.extend_from_slice(&4u16.to_be_bytes()); // defaultLangSys
+    data.extend_from_slice(&0u16.to_be_bytes()); // langSysCount
+    data.extend_from_slice(&0u16.to_be_bytes()); // lookupOrderOffset
+    data.extend_from_slice(&0xFFFFu16.to_be_bytes()); // requiredFeatureIndex
+    data.extend_from_slice(&count.to_be_bytes());
+    for index in 0..count {
+        data.extend_from_slice(&index.to_be_bytes());
+    }
+
+    // FeatureList; each feature table has no lookups
+    data.extend_from_slice(&count.to_be_bytes());
+    for (index, tag) in features.iter().enumerate() {
+        data.extend_from_slice(tag.as_bytes());
+        let offset = 2 + 6 * count + 4 * index as u16;
+        data.extend_from_slice(&offset.to_be_bytes());
+    }
+    for _ in features {
+        data.extend_from_slice(&0u16.to_be_bytes()); // featureParamsOffset
+        data.extend_from_slice(&0u16.to_be_bytes()); // lookupIndexCount
+    }
+    data
+}
+
+#[test]
+fn test_gsub_features_are_reported() {
+    // Given a font whose GSUB offers liga and smcp
+    // When querying GSUB features
+    // Then both are found and other features are not
+    let font =
+        OpenTypeFont::parse(build_font(&[("GSUB", layout_table(&["liga", "smcp"]))])).unwrap();
+
+    let gsub = font.get_gsub().unwrap();
+    assert_eq!(
+        gsub.feature_tags(),
+        vec![Tag::new("liga").unwrap(), Tag::new("smcp").unwrap()]
+    );
+    assert!(font.has_gsub_feature(Tag::new("smcp").unwrap()));
+    assert!(!font.has_gsub_feature(Tag::new("c2sc").unwrap()));
+    // Scripts missing from the table fall back to DFLT
+    assert!(gsub.has_script_feature(Tag::new("latn").unwrap(), Tag::new("smcp").unwrap()));
+}
+
+#[test]
+fn test_gpos_is_parsed_separately() {
+    // Given a font with only a GPOS table
+    // When querying layout tables
+    // Then GPOS features are found and GSUB is absent
+    let font = OpenTypeFont::parse(build_font(&[("GPOS", layout_table(&["kern"]))])).unwrap();
+
+    assert!(font.get_gsub().is_none());
+    assert!(!font.has_gsub_feature(Tag::new("kern").unwrap()));
+    assert!(font
+        .get_gpos()
+        .unwrap()
+        .has_feature(Tag::new("kern").unwrap()));
+}
@@ -0,0 +1,182 @@
+//! Unit tests for declared-count validation and hard parser limits
+//!
+//! Counts read from font data must never size an allocation before they are
+//! checked against the bytes actually present.
+
+use font_parser::limits::{MAX_COLOR_LAYERS, MAX_PALETTE_COLORS, MAX_VARIATION_AXES};
+use font_parser::{AvarTable, ColrTable, CpalTable, FvarTable, ParseError, WoffFont};
+
+fn is_corrupted<T>(result: Result<T, ParseError>) -> bool {
+    matches!(result, Err(ParseError::CorruptedData(_)))
+}
+
+/// fvar header with the given counts and no axis or instance data
+fn fvar_header(axis_count: u16, instance_count: u16) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // major version
+    data.extend_from_slice(&0u16.to_be_bytes()); // minor version
+    data.extend_from_slice(&16u16.to_be_bytes()); // axes array offset
+    data.extend_from_slice(&2u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&axis_count.to_be_bytes());
+    data.extend_from_slice(&20u16.to_be_bytes()); // axis size
+    data.extend_from_slice(&instance_count.to_be_bytes());
+    data.extend_from_slice(&axis_count.wrapping_mul(4).wrapping_add(4).to_be_bytes()); // instance size
+    data
+}
+
+#[test]
+fn test_fvar_axis_count_above_cap_is_rejected() {
+    // Given an fvar declaring 65535 axes
+    // When parsing
+    // Then the count is rejected before any axis is read
+    let data = fvar_header(u16::MAX, 0);
+    assert!(is_corrupted(FvarTable::parse(&data)));
+}
+
+#[test]
+fn test_fvar_counts_exceeding_table_are_rejected() {
+    // Given an fvar whose declared axes and instances need more bytes than the table holds
+    // When parsing
+    // Then it fails up front
+    let mut data = fvar_header(MAX_VARIATION_AXES as u16, 0);
+    data.resize(16 + 20 * (MAX_VARIATION_AXES - 1), 0);
+    assert!(is_corrupted(FvarTable::parse(&data)));
+
+    let mut data = fvar_header(1, 4000);
+    data.resize(16 + 20 + 8 * 3999, 0);
+    assert!(is_corrupted(FvarTable::parse(&data)));
+}
+
+#[test]
+fn test_avar_value_map_count_exceeding_table_is_rejected() {
+    // Given an avar axis claiming 65535 value maps in a 10-byte table
+    // When parsing
+    // Then it fails instead of reading map by map
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // major version
+    data.extend_from_slice(&0u16.to_be_bytes()); // minor version
+    data.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&1u16.to_be_bytes()); // axis count
+    data.extend_from_slice(&u16::MAX.to_be_bytes()); // position map count
+    assert!(is_corrupted(AvarTable::parse(&data, 1)));
+
+    // More axes than the cap cannot match any valid fvar
+    let count = MAX_VARIATION_AXES + 1;
+    let mut data = data[..6].to_vec();
+    data.extend_from_slice(&(count as u16).to_be_bytes());
+    data.resize(8 + count * 2, 0);
+    assert!(is_corrupted(AvarTable::parse(&data, count)));
+}
+
+#[test]
+fn test_cpal_shared_color_records_are_capped() {
+    // Given 4096 palettes of 4096 entries all starting at the same color record
+    // When parsing
+    // Then the 16M decoded colors exceed MAX_PALETTE_COLORS
+    let entries = 4096u16;
+    let palettes = 4096u16;
+    assert!(entries as usize * palettes as usize > MAX_PALETTE_COLORS);
+
+    let records_offset = 12 + palettes as u32 * 2;
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_be_bytes()); // version
+    data.extend_from_slice(&entries.to_be_bytes()); // num_palette_entries
+    data.extend_from_slice(&palettes.to_be_bytes()); // num_palettes
+    data.extend_from_slice(&entries.to_be_bytes()); // num_color_records
+    data.extend_from_slice(&records_offset.to_be_bytes());
+    data.resize(records_offset as usize + entries as usize * 4, 0);
+
+    assert!(is_corrupted(CpalTable::parse(&data)));
+}
+
+#[test]
+fn test_cpal_color_records_exceeding_table_are_rejected() {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_be_bytes()); // version
+    data.extend_from_slice(&1u16.to_be_bytes()); // num_palette_entries
+    data.extend_from_slice(&1u16.to_be_bytes()); // num_palettes
+    data.extend_from_slice(&u16::MAX.to_be_bytes()); // num_color_records
+    data.extend_from_slice(&14u32.to_be_bytes()); // color_records_array_offset
+    data.extend_from_slice(&0u16.to_be_bytes()); // palette 0 index
+    data.extend_from_slice(&[0, 0, 255, 255]);
+
+    assert!(is_corrupted(CpalTable::parse(&data)));
+}
+
+#[test]
+fn test_colr_shared_layer_ranges_are_capped() {
+    // Given 4096 base glyphs that each reuse the same 4096-layer range
+    // When parsing
+    // Then the 16M decoded layers exceed MAX_COLOR_LAYERS
+    let base_glyphs = 4096u16;
+    let layers = 4096u16;
+    assert!(base_glyphs as usize * layers as usize > MAX_COLOR_LAYERS);
+
+    let layer_offset = 14 + base_glyphs as u32 * 6;
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_be_bytes()); // version
+    data.extend_from_slice(&base_glyphs.to_be_bytes());
+    data.extend_from_slice(&14u32.to_be_bytes()); // base_glyph_records_offset
+    data.extend_from_slice(&layer_offset.to_be_bytes());
+    data.extend_from_slice(&layers.to_be_bytes());
+    for glyph_id in 0..base_glyphs {
+        data.extend_from_slice(&glyph_id.to_be_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes()); // first_layer_index
+        data.extend_from_slice(&layers.to_be_bytes());
+    }
+    data.resize(layer_offset as usize + layers as usize * 4, 0);
+
+    assert!(is_corrupted(ColrTable::parse(&data)));
+}
+
+#[test]
+fn test_colr_layer_records_exceeding_table_are_rejected() {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_be_bytes()); // version
+    data.extend_from_slice(&0u16.to_be_bytes()); // num_base_glyph_records
+    data.extend_from_slice(&14u32.to_be_bytes()); // base_glyph_records_offset
+    data.extend_from_slice(&14u32.to_be_bytes()); // layer_records_offset
+    data.extend_from_slice(&u16::MAX.to_be_bytes()); // num_layer_records
+
+    assert!(is_corrupted(ColrTable::parse(&data)));
+}
+
+#[test]
+fn test_woff_declared_size_above_cap_is_rejected() {
+    // Given a WOFF whose tables declare 4 GiB of decompressed data
+    // When decoding
+    // Then it fails before decompressing anything
+    let mut data = Vec::new();
+    data.extend_from_slice(&0x774F4646u32.to_be_bytes()); // signature
+    data.extend_from_slice(&0x00010000u32.to_be_bytes()); // flavor
+    data.extend_from_slice(&0u32.to_be_bytes()); // length
+    data.extend_from_slice(&1u16.to_be_bytes()); // numTables
+    data.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&[0u8; 28]); // totalSfntSize .. privLength
+    data.extend_from_slice(b"head");
+    data.extend_from_slice(&64u32.to_be_bytes()); // offset
+    data.extend_from_slice(&4u32.to_be_bytes()); // compLength
+    data.extend_from_slice(&u32::MAX.to_be_bytes()); // origLength
+    data.extend_from_slice(&0u32.to_be_bytes()); // origChecksum
+    data.extend_from_slice(&[0u8; 4]);
+
+    let error = WoffFont::parse(&data).unwrap_err();
+    assert!(error.to_string().contains("exceeds limit"), "{error}");
+}
+
+#[test]
+fn test_woff_table_count_exceeding_data_is_rejected() {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0x774F4646u32.to_be_bytes()); // signature
+    data.extend_from_slice(&0x00010000u32.to_be_bytes()); // flavor
+    data.extend_from_slice(&0u32.to_be_bytes()); // length
+    data.extend_from_slice(&u16::MAX.to_be_bytes()); // numTables
+    data.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&[0u8; 28]); // totalSfntSize .. privLength
+
+    let error = WoffFont::parse(&data).unwrap_err();
+    assert!(
+        error.to_string().contains("declares 65535 records"),
+        "{error}"
+    );
+}
@@ -0,0 +1,230 @@
+//! Fuzz-style tests for malformed font data
+//!
+//! Parsers must return errors (or None) for malformed input and never panic.
+
+use font_parser::{OpenTypeFont, ParseError, Tag, Woff2Font, WoffFont};
+use proptest::prelude::*;
+use std::io::Write;
+
+/// Build an sfnt containing a single table record with arbitrary offset/length
+fn font_with_record(tag: &[u8; 4], offset: u32, length: u32, total_size: usize) -> Vec<u8> {
+    let mut data = vec![
+        0x00, 0x01, 0x00, 0x00, // sfnt version
+        0x00, 0x01, // numTables
+        0x00, 0x10, // searchRange
+        0x00, 0x00, // entrySelector
+        0x00, 0x00, // rangeShift
+    ];
+    data.extend_from_slice(tag);
+    data.extend_from_slice(&0u32.to_be_bytes()); // checksum
+    data.extend_from_slice(&offset.to_be_bytes());
+    data.extend_from_slice(&length.to_be_bytes());
+    data.resize(total_size.max(data.len()), 0);
+    data
+}
+
+#[test]
+fn test_get_table_oversized_length_returns_none() {
+    // Given a table record whose length runs past the end of the data
+    // When fetching the table
+    // Then it should return None instead of panicking
+    let data = font_with_record(b"head", 0x1C, 0x1000, 0x40);
+    let font = OpenTypeFont::parse(data).unwrap();
+    let tag = Tag::new("head").unwrap();
+
+    assert!(font.has_table(tag));
+    assert!(font.get_table(tag).is_none());
+    assert!(matches!(
+        font.get_table_checked(tag),
+        Err(ParseError::CorruptedData(_))
+    ));
+}
+
+#[test]
+fn test_get_table_offset_overflow_returns_none() {
+    // Given offset and length both at u32::MAX (overflows usize on 32-bit targets)
+    // When fetching the table
+    // Then it should return None
+    let data = font_with_record(b"head", u32::MAX, u32::MAX, 0x40);
+    let font = OpenTypeFont::parse(data).unwrap();
+    assert!(font.get_table(Tag::new("head").unwrap()).is_none());
+}
+
+#[test]
+fn test_get_table_checked_missing_table() {
+    let data = font_with_record(b"head", 0x1C, 0x10, 0x40);
+    let font = OpenTypeFont::parse(data).unwrap();
+    assert_eq!(
+        font.get_table_checked(Tag::new("maxp").unwrap()),
+        Err(ParseError::MissingTable("maxp".to_string()))
+    );
+}
+
+#[test]
+fn test_get_table_checked_valid_table() {
+    let data = font_with_record(b"head", 0x1C, 0x10, 0x40);
+    let font = OpenTypeFont::parse(data).unwrap();
+    assert_eq!(
+        font.get_table_checked(Tag::new("head").unwrap())
+            .unwrap()
+            .len(),
+        0x10
+    );
+}
+
+#[test]
+fn test_truncated_table_accessors_do_not_panic() {
+    // Given tables whose records point past the end of the data
+    // When using the high-level accessors
+    // Then they fall back to defaults / None
+    for tag in [
+        b"head", b"hhea", b"fvar", b"CPAL", b"COLR", b"CBDT", b"SVG ",
+    ] {
+        let data = font_with_record(tag, 0x1C, 0xFFFF, 0x20);
+        let font = OpenTypeFont::parse(data).unwrap();
+
+        let _ = font.get_metrics();
+        assert!(font.get_fvar().is_none());
+        assert!(font.get_cpal().is_none());
+        assert!(font.get_colr().is_none());
+        assert!(font.get_cbdt().is_none());
+        assert!(font.get_svg().is_none());
+    }
+}
+
+#[test]
+fn test_truncated_directory_returns_error() {
+    // Given a header declaring more tables than the data contains
+    // When parsing
+    // Then it should return an error
+    let mut data = font_with_record(b"head", 0x1C, 0x10, 0x2C);
+    data[4..6].copy_from_slice(&100u16.to_be_bytes());
+    assert!(OpenTypeFont::parse(data).is_err());
+}
+
+#[test]
+fn test_woff_table_out_of_range_returns_error() {
+    // Given a WOFF whose table entry points past the end of the file
+    // When decoding
+    // Then it should return an error instead of panicking
+    let mut data = Vec::new();
+    data.extend_from_slice(&0x774F4646u32.to_be_bytes()); // signature
+    data.extend_from_slice(&0x00010000u32.to_be_bytes()); // flavor
+    data.extend_from_slice(&0u32.to_be_bytes()); // length
+    data.extend_from_slice(&1u16.to_be_bytes()); // numTables
+    data.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&[0u8; 32]); // totalSfntSize .. privLength
+    data.extend_from_slice(b"head");
+    data.extend_from_slice(&0xFFFF_FF00u32.to_be_bytes()); // offset
+    data.extend_from_slice(&0x1000u32.to_be_bytes()); // compLength
+    data.extend_from_slice(&0x1000u32.to_be_bytes()); // origLength
+    data.extend_from_slice(&0u32.to_be_bytes()); // origChecksum
+
+    assert!(matches!(
+        WoffFont::parse(&data),
+        Err(ParseError::CorruptedData(_))
+    ));
+}
+
+#[test]
+fn test_woff2_compressed_size_out_of_range_returns_error() {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0x774F4632u32.to_be_bytes()); // signature
+    data.extend_from_slice(&0x00010000u32.to_be_bytes()); // flavor
+    data.extend_from_slice(&0u32.to_be_bytes()); // length
+    data.extend_from_slice(&0u16.to_be_bytes()); // numTables
+    data.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&0u32.to_be_bytes()); // totalSfntSize
+    data.extend_from_slice(&u32::MAX.to_be_bytes()); // totalCompressedSize
+    data.extend_from_slice(&[0u8; 24]); // version .. privLength
+
+    assert!(matches!(
+        Woff2Font::parse(&data),
+        Err(ParseError::CorruptedData(_))
+    ));
+}
+
+#[test]
+fn test_woff_decompression_bomb_returns_error() {
+    // Given a WOFF table declaring 16 bytes that inflates to 16 MiB
+    // When decoding
+    // Then decompression stops at the declared size and reports a mismatch
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(&vec![0u8; 16 << 20]).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&0x774F4646u32.to_be_bytes()); // signature
+    data.extend_from_slice(&0x00010000u32.to_be_bytes()); // flavor
+    data.extend_from_slice(&0u32.to_be_bytes()); // length
+    data.extend_from_slice(&1u16.to_be_bytes()); // numTables
+    data.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&[0u8; 28]); // totalSfntSize .. privLength
+    data.extend_from_slice(b"head");
+    data.extend_from_slice(&64u32.to_be_bytes()); // offset
+    data.extend_from_slice(&(compressed.len() as u32).to_be_bytes()); // compLength
+    data.extend_from_slice(&(compressed.len() as u32 + 16).to_be_bytes()); // origLength
+    data.extend_from_slice(&0u32.to_be_bytes()); // origChecksum
+    data.extend_from_slice(&compressed);
+
+    assert_eq!(
+        WoffFont::parse(&data).unwrap_err(),
+        ParseError::CorruptedData("Decompression size mismatch".to_string())
+    );
+}
+
+#[test]
+fn test_woff2_output_larger_than_declared_returns_error() {
+    // Given a WOFF2 whose Brotli stream inflates past totalSfntSize
+    // When decoding
+    // Then it should return an error instead of buffering the whole stream
+    let mut compressed = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+        writer.write_all(&vec![0u8; 16 << 20]).unwrap();
+    }
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&0x774F4632u32.to_be_bytes()); // signature
+    data.extend_from_slice(&0x00010000u32.to_be_bytes()); // flavor
+    data.extend_from_slice(&0u32.to_be_bytes()); // length
+    data.extend_from_slice(&0u16.to_be_bytes()); // numTables
+    data.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    data.extend_from_slice(&1024u32.to_be_bytes()); // totalSfntSize
+    data.extend_from_slice(&(compressed.len() as u32).to_be_bytes()); // totalCompressedSize
+    data.extend_from_slice(&[0u8; 24]); // version .. privLength
+    data.extend_from_slice(&compressed);
+
+    assert!(matches!(
+        Woff2Font::parse(&data),
+        Err(ParseError::CorruptedData(_))
+    ));
+}
+
+proptest! {
+    #[test]
+    fn prop_parse_and_get_table_never_panic(
+        offset in any::<u32>(),
+        length in any::<u32>(),
+        total_size in 0usize..256,
+    ) {
+        let data = font_with_record(b"head", offset, length, total_size);
+        if let Ok(font) = OpenTypeFont::parse(data) {
+            let _ = font.get_table(Tag::new("head").unwrap());
+            let _ = font.get_metrics();
+        }
+    }
+
+    #[test]
+    fn prop_arbitrary_bytes_never_panic(data in proptest::collection::vec(any::<u8>(), 0..512)) {
+        if let Ok(font) = OpenTypeFont::parse(data.clone()) {
+            let _ = font.get_metrics();
+            let _ = font.get_fvar();
+            let _ = font.get_colr();
+            let _ = font.get_cpal();
+        }
+        let _ = WoffFont::parse(&data);
+        let _ = Woff2Font::parse(&data);
+        let _ = font_parser::validate(&data);
+    }
+}
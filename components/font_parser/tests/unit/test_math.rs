@@ -0,0 +1,145 @@
+//! Unit tests for mathematical typesetting table (MATH) parsing through
+//! OpenTypeFont
+
+use super::test_validation::build_font;
+use font_parser::{GlyphPart, GlyphVariant, MathTable, OpenTypeFont};
+
+fn push(data: &mut Vec<u8>, values: &[u16]) {
+    for value in values {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Build a MATH table with constants, an italic correction and a top-right
+/// kern for glyph 5, extended shapes 10-12 and a vertical construction for
+/// glyph 20 with two variants and a three-part assembly
+fn math_table() -> Vec<u8> {
+    let mut data = Vec::new();
+    push(&mut data, &[1, 0, 10, 224, 288]); // version, subtable offsets
+
+    // MathConstants at 10: two percentages, two heights, 51 value records
+    // (the value is the record's index), then the degree raise percentage
+    push(&mut data, &[70, 55, 1300, 1500]);
+    for index in 0..51 {
+        push(&mut data, &[index, 0]);
+    }
+    push(&mut data, &[60]);
+    assert_eq!(data.len(), 224);
+
+    // MathGlyphInfo at 224
+    push(&mut data, &[8, 0, 22, 32]);
+    push(&mut data, &[8, 1, 30, 0]); // italics correction info
+    push(&mut data, &[1, 1, 5]); // its coverage
+    push(&mut data, &[2, 1, 10, 12, 0]); // extended shape coverage
+    push(&mut data, &[12, 1, 18, 0, 0, 0]); // kern info
+    push(&mut data, &[1, 1, 5]); // its coverage
+    push(
+        &mut data,
+        &[1, 100, 0, (-40i16) as u16, 0, (-10i16) as u16, 0],
+    ); // top-right kern
+    assert_eq!(data.len(), 288);
+
+    // MathVariants at 288
+    push(&mut data, &[20, 12, 0, 1, 0, 18]);
+    push(&mut data, &[1, 1, 20]); // vertical coverage
+    push(&mut data, &[12, 2, 20, 1000, 21, 1500]); // construction
+    push(&mut data, &[(-5i16) as u16, 0, 3]); // assembly
+    push(&mut data, &[30, 0, 100, 500, 0]);
+    push(&mut data, &[31, 100, 100, 400, 1]);
+    push(&mut data, &[32, 100, 0, 500, 0]);
+    data
+}
+
+fn parse(table: Vec<u8>) -> Option<MathTable> {
+    OpenTypeFont::parse(build_font(&[("MATH", table)]))
+        .unwrap()
+        .get_math()
+}
+
+#[test]
+fn test_math_constants() {
+    // Given a MATH table with known constants
+    // When reading them
+    // Then every record lands in its field, in table order
+    let constants = parse(math_table()).unwrap().constants;
+    assert_eq!(constants.script_percent_scale_down, 70);
+    assert_eq!(constants.script_script_percent_scale_down, 55);
+    assert_eq!(constants.delimited_sub_formula_min_height, 1300);
+    assert_eq!(constants.display_operator_min_height, 1500);
+    assert_eq!(constants.math_leading, 0);
+    assert_eq!(constants.axis_height, 1);
+    assert_eq!(constants.fraction_rule_thickness, 34);
+    assert_eq!(constants.radical_kern_after_degree, 50);
+    assert_eq!(constants.radical_degree_bottom_raise_percent, 60);
+}
+
+#[test]
+fn test_math_glyph_info() {
+    // Given a MATH table with glyph info
+    // When looking up glyphs
+    // Then covered glyphs report their values and others report none
+    let info = parse(math_table()).unwrap().glyph_info;
+    assert_eq!(info.italics_correction(5), Some(30));
+    assert_eq!(info.italics_correction(6), None);
+    assert_eq!(info.top_accent_attachment(5), None);
+    assert!(info.is_extended_shape(10));
+    assert!(info.is_extended_shape(12));
+    assert!(!info.is_extended_shape(13));
+
+    let kern = info.kerns[&5].top_right.as_ref().unwrap();
+    assert_eq!(kern.kern_at(0), -40);
+    assert_eq!(kern.kern_at(150), -10);
+    assert!(info.kerns[&5].bottom_left.is_none());
+}
+
+#[test]
+fn test_math_variants() {
+    // Given a MATH table with a vertical construction
+    // When reading the stretchy glyphs
+    // Then the variants and assembly parts are in order
+    let variants = parse(math_table()).unwrap().variants;
+    assert_eq!(variants.min_connector_overlap, 20);
+    assert!(variants.horizontal.is_empty());
+
+    let construction = &variants.vertical[&20];
+    assert_eq!(
+        construction.variants,
+        vec![
+            GlyphVariant {
+                glyph: 20,
+                advance: 1000
+            },
+            GlyphVariant {
+                glyph: 21,
+                advance: 1500
+            },
+        ]
+    );
+    let assembly = construction.assembly.as_ref().unwrap();
+    assert_eq!(assembly.italics_correction, -5);
+    assert_eq!(assembly.parts.len(), 3);
+    assert_eq!(
+        assembly.parts[1],
+        GlyphPart {
+            glyph: 31,
+            start_connector_length: 100,
+            end_connector_length: 100,
+            full_advance: 400,
+            is_extender: true,
+        }
+    );
+    assert!(!assembly.parts[0].is_extender);
+}
+
+#[test]
+fn test_math_missing_or_truncated() {
+    // Given fonts without a MATH table and with one cut off in its variants
+    // When reading MATH
+    // Then neither yields a table
+    let font = OpenTypeFont::parse(build_font(&[("post", vec![0; 32])])).unwrap();
+    assert!(font.get_math().is_none());
+
+    let mut table = math_table();
+    table.truncate(table.len() - 12);
+    assert!(parse(table).is_none());
+}
@@ -0,0 +1,209 @@
+//! Unit tests for MVAR/HVAR metric variations and hmtx advances
+
+use super::test_validation::{build_font, head_table, maxp_table};
+use font_parser::{HvarTable, MvarTable, OpenTypeFont, Tag, VariationCoordinates};
+
+/// fvar with a single wght axis 100..400..900
+fn fvar_table() -> Vec<u8> {
+    let mut data = Vec::new();
+    for value in [1u16, 0, 16, 2, 1, 20, 0, 8] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data.extend_from_slice(b"wght");
+    for value in [100.0f32, 400.0, 900.0] {
+        data.extend_from_slice(&((value * 65536.0) as i32).to_be_bytes());
+    }
+    data.extend_from_slice(&[0, 0, 1, 0]); // flags, axisNameID
+    data
+}
+
+/// Item variation store with one region peaking at wght=max and one row per delta
+fn item_store(deltas: &[i16]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // format
+    data.extend_from_slice(&12u32.to_be_bytes()); // regionListOffset
+    data.extend_from_slice(&1u16.to_be_bytes()); // itemVariationDataCount
+    data.extend_from_slice(&22u32.to_be_bytes()); // itemVariationDataOffsets[0]
+
+    // Region list: one axis, one region (start 0, peak 1, end 1)
+    for value in [1u16, 1, 0, 0x4000, 0x4000] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    // Item data: one 16-bit delta per row for region 0
+    for value in [deltas.len() as u16, 1, 1, 0] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    for delta in deltas {
+        data.extend_from_slice(&delta.to_be_bytes());
+    }
+    data
+}
+
+/// MVAR varying each tag by its delta at wght=max
+fn mvar_table(records: &[(&str, i16)]) -> Vec<u8> {
+    let count = records.len() as u16;
+    let mut data = Vec::new();
+    for value in [1u16, 0, 0, 8, count, 12 + 8 * count] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    for (inner, (tag, _)) in records.iter().enumerate() {
+        data.extend_from_slice(tag.as_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes()); // outer index
+        data.extend_from_slice(&(inner as u16).to_be_bytes());
+    }
+    let deltas: Vec<i16> = records.iter().map(|(_, delta)| *delta).collect();
+    data.extend(item_store(&deltas));
+    data
+}
+
+/// HVAR with an optional advance mapping, followed by the item variation store
+fn hvar_table(advance_map: Option<&[u8]>, deltas: &[i16]) -> Vec<u8> {
+    let map = advance_map.unwrap_or_default();
+    let map_offset = if advance_map.is_some() { 20u32 } else { 0 };
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+    data.extend_from_slice(&0u16.to_be_bytes()); // minorVersion
+    data.extend_from_slice(&(20 + map.len() as u32).to_be_bytes()); // itemVariationStoreOffset
+    data.extend_from_slice(&map_offset.to_be_bytes()); // advanceWidthMappingOffset
+    data.extend_from_slice(&[0u8; 8]); // lsb and rsb mappings
+    data.extend_from_slice(map);
+    data.extend(item_store(deltas));
+    data
+}
+
+/// hhea with the given vertical metrics and numberOfHMetrics
+fn hhea_table(ascender: i16, descender: i16, num_h_metrics: u16) -> Vec<u8> {
+    let mut hhea = vec![0u8; 36];
+    hhea[0..4].copy_from_slice(&0x00010000u32.to_be_bytes());
+    hhea[4..6].copy_from_slice(&ascender.to_be_bytes());
+    hhea[6..8].copy_from_slice(&descender.to_be_bytes());
+    hhea[34..36].copy_from_slice(&num_h_metrics.to_be_bytes());
+    hhea
+}
+
+/// Version 4 OS/2 with typo (700, -300, 0) and win (900, 250) metrics
+fn os2_table() -> Vec<u8> {
+    let mut os2 = vec![0u8; 96];
+    os2[0..2].copy_from_slice(&4u16.to_be_bytes());
+    os2[68..70].copy_from_slice(&700i16.to_be_bytes());
+    os2[70..72].copy_from_slice(&(-300i16).to_be_bytes());
+    os2[74..76].copy_from_slice(&900u16.to_be_bytes());
+    os2[76..78].copy_from_slice(&250u16.to_be_bytes());
+    os2
+}
+
+/// hmtx with long metrics for advances 500 and 600, shared by 3 glyphs
+fn metrics_font(extra: Vec<(&str, Vec<u8>)>) -> OpenTypeFont {
+    let mut hmtx = Vec::new();
+    for advance in [500u16, 600] {
+        hmtx.extend_from_slice(&advance.to_be_bytes());
+        hmtx.extend_from_slice(&0i16.to_be_bytes()); // lsb
+    }
+    hmtx.extend_from_slice(&0i16.to_be_bytes()); // lsb of glyph 2
+
+    let mut tables = vec![
+        ("OS/2", os2_table()),
+        ("head", head_table(1000)),
+        ("hhea", hhea_table(800, -200, 2)),
+        ("hmtx", hmtx),
+        ("maxp", maxp_table(3)),
+    ];
+    tables.extend(extra);
+    OpenTypeFont::parse(build_font(&tables)).unwrap()
+}
+
+fn weight(value: f32) -> VariationCoordinates {
+    let mut coords = VariationCoordinates::new();
+    coords.set_axis(Tag::WEIGHT, value);
+    coords
+}
+
+#[test]
+fn test_metrics_at_applies_mvar_deltas() {
+    // Given an MVAR raising the ascender by 100 and usWinDescent by 50 at wght=max
+    // When requesting metrics halfway between the default and the maximum
+    // Then hhea and typo ascenders rise by 50 and the win descent by 25
+    let font = metrics_font(vec![
+        ("fvar", fvar_table()),
+        ("MVAR", mvar_table(&[("hasc", 100), ("hcld", 50)])),
+    ]);
+
+    let metrics = font.get_metrics_at(&weight(650.0));
+    assert_eq!(metrics.ascender, 850);
+    assert_eq!(metrics.typo_ascender, 750);
+    assert_eq!(metrics.win_descent, 275);
+    assert_eq!(metrics.descender, -200);
+    assert_eq!(metrics.win_ascent, 900);
+
+    assert_eq!(font.get_metrics_at(&weight(400.0)), font.get_metrics());
+    assert_eq!(font.get_metrics_at(&weight(900.0)).ascender, 900);
+}
+
+#[test]
+fn test_metrics_at_without_mvar_is_default() {
+    let font = metrics_font(vec![("fvar", fvar_table())]);
+
+    assert_eq!(font.get_metrics_at(&weight(900.0)), font.get_metrics());
+}
+
+#[test]
+fn test_parse_mvar() {
+    let data = mvar_table(&[("hasc", 100), ("xhgt", -20)]);
+    let mvar = MvarTable::parse(&data).unwrap();
+
+    let tags: Vec<Tag> = mvar.tags().collect();
+    assert_eq!(tags, vec!["hasc".parse().unwrap(), "xhgt".parse().unwrap()]);
+    assert_eq!(mvar.delta("xhgt".parse().unwrap(), &[0.5]), -10.0);
+    // Unvaried metrics and coordinates outside the region have no delta
+    assert_eq!(mvar.delta("cpht".parse().unwrap(), &[1.0]), 0.0);
+    assert_eq!(mvar.delta("hasc".parse().unwrap(), &[-1.0]), 0.0);
+
+    assert!(MvarTable::parse(&data[..20]).is_err());
+}
+
+#[test]
+fn test_get_advance_reads_hmtx() {
+    // Given two long metrics for three glyphs
+    // When reading advances
+    // Then the last glyph repeats the last long metric
+    let font = metrics_font(Vec::new());
+
+    assert_eq!(font.get_advance(0), Some(500));
+    assert_eq!(font.get_advance(1), Some(600));
+    assert_eq!(font.get_advance(2), Some(600));
+    assert_eq!(font.get_advance(3), None);
+}
+
+#[test]
+fn test_advance_at_applies_hvar() {
+    // Given an HVAR addressed directly by glyph ID
+    // When requesting advances at varied weights
+    // Then each glyph's row is scaled by the region
+    let font = metrics_font(vec![
+        ("fvar", fvar_table()),
+        ("HVAR", hvar_table(None, &[0, 100, -50])),
+    ]);
+
+    assert_eq!(font.get_advance_at(1, &weight(900.0)), Some(700.0));
+    assert_eq!(font.get_advance_at(1, &weight(650.0)), Some(650.0));
+    assert_eq!(font.get_advance_at(2, &weight(900.0)), Some(550.0));
+    assert_eq!(font.get_advance_at(1, &weight(100.0)), Some(600.0));
+    assert_eq!(font.get_advance_at(3, &weight(900.0)), None);
+}
+
+#[test]
+fn test_hvar_advance_mapping() {
+    // Given a one-byte map with one inner bit: glyph 0 -> row 1, glyph 1 -> row 0
+    // When reading deltas
+    // Then rows follow the map, and glyphs past its end use the last entry
+    let map = [0, 0x00, 0, 2, 1, 0];
+    let data = hvar_table(Some(&map), &[30, 80]);
+    let hvar = HvarTable::parse(&data).unwrap();
+
+    assert_eq!(hvar.advance_delta(0, &[1.0]), 80.0);
+    assert_eq!(hvar.advance_delta(1, &[1.0]), 30.0);
+    assert_eq!(hvar.advance_delta(5, &[1.0]), 30.0);
+    assert_eq!(hvar.advance_delta(0, &[0.0]), 0.0);
+
+    assert!(HvarTable::parse(&data[..12]).is_err());
+}
@@ -0,0 +1,203 @@
+//! Unit tests for font metrics extraction (head, hhea, OS/2)
+
+use super::test_validation::{build_font, head_table, maxp_table};
+use font_parser::{OpenTypeFont, VerticalGlyphMetrics};
+
+/// Build an hhea table with the given vertical metrics and advanceWidthMax
+fn hhea_table(ascender: i16, descender: i16, line_gap: i16, max_advance: u16) -> Vec<u8> {
+    let mut hhea = vec![0u8; 36];
+    hhea[0..4].copy_from_slice(&0x00010000u32.to_be_bytes());
+    hhea[4..6].copy_from_slice(&ascender.to_be_bytes());
+    hhea[6..8].copy_from_slice(&descender.to_be_bytes());
+    hhea[8..10].copy_from_slice(&line_gap.to_be_bytes());
+    hhea[10..12].copy_from_slice(&max_advance.to_be_bytes());
+    hhea
+}
+
+/// Build a version 4 OS/2 table with typo/win metrics
+fn os2_table(avg_width: i16, use_typo: bool, typo: (i16, i16, i16), win: (u16, u16)) -> Vec<u8> {
+    let mut os2 = vec![0u8; 96];
+    os2[0..2].copy_from_slice(&4u16.to_be_bytes());
+    os2[2..4].copy_from_slice(&avg_width.to_be_bytes());
+    let selection: u16 = if use_typo { 1 << 7 } else { 0 };
+    os2[62..64].copy_from_slice(&selection.to_be_bytes());
+    os2[68..70].copy_from_slice(&typo.0.to_be_bytes());
+    os2[70..72].copy_from_slice(&typo.1.to_be_bytes());
+    os2[72..74].copy_from_slice(&typo.2.to_be_bytes());
+    os2[74..76].copy_from_slice(&win.0.to_be_bytes());
+    os2[76..78].copy_from_slice(&win.1.to_be_bytes());
+    os2
+}
+
+fn font_with(hhea: Vec<u8>, os2: Option<Vec<u8>>) -> OpenTypeFont {
+    let mut tables = vec![
+        ("head", head_table(1000)),
+        ("hhea", hhea),
+        ("maxp", maxp_table(1)),
+    ];
+    if let Some(os2) = os2 {
+        tables.insert(0, ("OS/2", os2));
+    }
+    OpenTypeFont::parse(build_font(&tables)).unwrap()
+}
+
+#[test]
+fn test_metrics_read_os2_fields() {
+    // Given a font with hhea and OS/2 metrics
+    // When reading metrics
+    // Then every source is reported
+    let font = font_with(
+        hhea_table(900, -300, 50, 1200),
+        Some(os2_table(520, false, (800, -200, 100), (1000, 350))),
+    );
+    let metrics = font.get_metrics();
+
+    assert_eq!(metrics.max_advance, 1200);
+    assert_eq!(metrics.avg_char_width, 520);
+    assert_eq!(
+        (
+            metrics.typo_ascender,
+            metrics.typo_descender,
+            metrics.typo_line_gap
+        ),
+        (800, -200, 100)
+    );
+    assert_eq!((metrics.win_ascent, metrics.win_descent), (1000, 350));
+    assert!(!metrics.use_typo_metrics);
+}
+
+#[test]
+fn test_line_height_uses_hhea_by_default() {
+    let font = font_with(
+        hhea_table(900, -300, 50, 1200),
+        Some(os2_table(520, false, (800, -200, 100), (1000, 350))),
+    );
+    let metrics = font.get_metrics();
+    assert_eq!(metrics.vertical_metrics(), (900, -300, 50));
+    assert_eq!(metrics.line_height(), 1250);
+}
+
+#[test]
+fn test_line_height_honors_use_typo_metrics() {
+    // Given USE_TYPO_METRICS set in fsSelection
+    // When computing the line height
+    // Then the typo metrics are used instead of hhea
+    let font = font_with(
+        hhea_table(900, -300, 50, 1200),
+        Some(os2_table(520, true, (800, -200, 100), (1000, 350))),
+    );
+    let metrics = font.get_metrics();
+    assert!(metrics.use_typo_metrics);
+    assert_eq!(metrics.vertical_metrics(), (800, -200, 100));
+    assert_eq!(metrics.line_height(), 1100);
+}
+
+#[test]
+fn test_line_height_falls_back_to_win_metrics() {
+    // Given a font with zeroed hhea metrics
+    // When computing the line height
+    // Then the win metrics are used
+    let font = font_with(
+        hhea_table(0, 0, 0, 1200),
+        Some(os2_table(520, false, (0, 0, 0), (1000, 350))),
+    );
+    assert_eq!(font.get_metrics().line_height(), 1350);
+}
+
+#[test]
+fn test_metrics_without_os2() {
+    let metrics = font_with(hhea_table(900, -300, 50, 1200), None).get_metrics();
+    assert_eq!(metrics.avg_char_width, 0);
+    assert!(!metrics.use_typo_metrics);
+    assert_eq!(metrics.line_height(), 1250);
+}
+
+#[test]
+fn test_metrics_convert_to_shared_type() {
+    // Given parsed metrics with USE_TYPO_METRICS set
+    // When converting to the shared font_types metrics
+    // Then layout metrics follow the typo values and win descent is negated
+    let font = font_with(
+        hhea_table(900, -300, 50, 1200),
+        Some(os2_table(520, true, (800, -200, 100), (1000, 350))),
+    );
+    let metrics: font_types::FontMetrics = font.get_metrics().into();
+
+    assert_eq!(metrics.units_per_em, 1000);
+    assert_eq!(
+        (metrics.ascent, metrics.descent, metrics.line_gap),
+        (800.0, -200.0, 100.0)
+    );
+    assert_eq!(metrics.hhea_ascent, 900.0);
+    assert_eq!(metrics.win_descent, -350.0);
+    assert_eq!(metrics.max_advance, 1200.0);
+    assert_eq!(metrics.line_height(), 1100.0);
+}
+
+/// Build a font of two glyphs 600 units wide, with the given vertical
+/// tables
+fn font_with_vertical_tables(tables: Vec<(&str, Vec<u8>)>) -> OpenTypeFont {
+    let mut hhea = hhea_table(900, -300, 50, 600);
+    hhea[34..36].copy_from_slice(&1u16.to_be_bytes());
+    let mut hmtx = 600u16.to_be_bytes().to_vec();
+    hmtx.extend_from_slice(&[0, 0, 0, 0]);
+    let mut all = vec![
+        ("head", head_table(1000)),
+        ("hhea", hhea),
+        ("hmtx", hmtx),
+        ("maxp", maxp_table(2)),
+    ];
+    all.extend(tables);
+    all.sort_by_key(|(tag, _)| *tag);
+    OpenTypeFont::parse(build_font(&all)).unwrap()
+}
+
+#[test]
+fn test_vertical_metrics_synthesized_without_vmtx() {
+    // Given a font without vhea/vmtx
+    // When reading a glyph's vertical metrics
+    // Then it advances by ascender - descender from a centered origin at
+    // the ascender
+    let font = font_with_vertical_tables(Vec::new());
+
+    assert!(!font.has_vertical_metrics());
+    assert_eq!(
+        font.get_vertical_glyph_metrics(1),
+        Some(VerticalGlyphMetrics {
+            advance: 1200,
+            origin_x: 300,
+            origin_y: 900,
+            synthesized: true,
+        })
+    );
+    assert_eq!(font.get_vertical_glyph_metrics(2), None);
+}
+
+#[test]
+fn test_vertical_metrics_read_vmtx_and_vorg() {
+    // Given vhea/vmtx with one long metric and a VORG entry for glyph 1
+    let mut vhea = vec![0u8; 36];
+    vhea[34..36].copy_from_slice(&1u16.to_be_bytes());
+    let vmtx = [
+        1000u16.to_be_bytes(),
+        50u16.to_be_bytes(),
+        70u16.to_be_bytes(),
+    ]
+    .concat();
+    let mut vorg = vec![0, 1, 0, 0];
+    vorg.extend_from_slice(&880i16.to_be_bytes());
+    vorg.extend_from_slice(&1u16.to_be_bytes());
+    vorg.extend_from_slice(&1u16.to_be_bytes());
+    vorg.extend_from_slice(&860i16.to_be_bytes());
+    let font = font_with_vertical_tables(vec![("vhea", vhea), ("vmtx", vmtx), ("VORG", vorg)]);
+
+    // When reading each glyph's vertical metrics
+    let metrics = [0, 1].map(|glyph| font.get_vertical_glyph_metrics(glyph).unwrap());
+
+    // Then advances come from vmtx and origins from VORG
+    assert!(font.has_vertical_metrics());
+    assert_eq!(
+        metrics.map(|m| (m.advance, m.origin_x, m.origin_y, m.synthesized)),
+        [(1000, 300, 880, false), (1000, 300, 860, false)]
+    );
+}
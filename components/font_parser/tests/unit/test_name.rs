@@ -0,0 +1,145 @@
+//! Unit tests for name table lookups and named instance name resolution
+
+use super::test_validation::{build_font, head_table, maxp_table};
+use font_parser::{NameTable, OpenTypeFont, Tag};
+
+/// Windows English name table from (name ID, string) pairs
+fn name_table(names: &[(u16, &str)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut storage = Vec::new();
+    data.extend_from_slice(&0u16.to_be_bytes()); // format
+    data.extend_from_slice(&(names.len() as u16).to_be_bytes());
+    data.extend_from_slice(&(6 + 12 * names.len() as u16).to_be_bytes());
+    for &(name_id, string) in names {
+        let bytes: Vec<u8> = string.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        for value in [
+            3u16,
+            1,
+            0x0409,
+            name_id,
+            bytes.len() as u16,
+            storage.len() as u16,
+        ] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        storage.extend(bytes);
+    }
+    data.extend(storage);
+    data
+}
+
+/// fvar with wght 100..400..900 and wdth 75..100..100, instances with PostScript name IDs
+fn fvar_table(instances: &[(u16, u16, f32, f32)]) -> Vec<u8> {
+    let fixed = |v: f32| ((v * 65536.0) as i32).to_be_bytes();
+    let mut data = Vec::new();
+    for value in [1u16, 0, 16, 2, 2, 20, instances.len() as u16, 14] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    for (tag, min, default, max) in [
+        (b"wght", 100.0, 400.0, 900.0),
+        (b"wdth", 75.0, 100.0, 100.0),
+    ] {
+        data.extend_from_slice(tag);
+        data.extend_from_slice(&fixed(min));
+        data.extend_from_slice(&fixed(default));
+        data.extend_from_slice(&fixed(max));
+        data.extend_from_slice(&[0, 0, 0, 0]); // flags, axisNameID
+    }
+    for &(subfamily_id, postscript_id, wght, wdth) in instances {
+        data.extend_from_slice(&subfamily_id.to_be_bytes());
+        data.extend_from_slice(&[0, 0]); // flags
+        data.extend_from_slice(&fixed(wght));
+        data.extend_from_slice(&fixed(wdth));
+        data.extend_from_slice(&postscript_id.to_be_bytes());
+    }
+    data
+}
+
+fn font_with(names: &[(u16, &str)], instances: &[(u16, u16, f32, f32)]) -> OpenTypeFont {
+    OpenTypeFont::parse(build_font(&[
+        ("fvar", fvar_table(instances)),
+        ("head", head_table(1000)),
+        ("maxp", maxp_table(1)),
+        ("name", name_table(names)),
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn test_get_name_resolves_strings() {
+    let font = font_with(&[(1, "Example Sans"), (2, "Regular")], &[]);
+    let name = font.get_name().unwrap();
+
+    assert_eq!(name.get(NameTable::FAMILY).as_deref(), Some("Example Sans"));
+    assert_eq!(name.get(NameTable::SUBFAMILY).as_deref(), Some("Regular"));
+    assert_eq!(name.get(NameTable::POSTSCRIPT_NAME), None);
+}
+
+#[test]
+fn test_named_instances_resolved() {
+    // Given instances with an explicit PostScript name, a derived one, and an unnamed one
+    // When resolving their names
+    // Then display names come from the subfamily and unnamed instances are skipped
+    let font = font_with(
+        &[
+            (1, "Example Sans"),
+            (256, "Bold"),
+            (257, "Semi Condensed Light"),
+            (258, "ExampleSans-BoldVF"),
+        ],
+        &[
+            (256, 258, 700.0, 100.0),
+            (257, 0xFFFF, 300.0, 87.5),
+            (300, 0xFFFF, 900.0, 75.0),
+        ],
+    );
+
+    let instances = font.named_instances_resolved();
+
+    assert_eq!(instances.len(), 2);
+    let (display_name, postscript_name, coords) = &instances[0];
+    assert_eq!(display_name, "Bold");
+    assert_eq!(postscript_name, "ExampleSans-BoldVF");
+    assert_eq!(coords.get_axis(Tag::WEIGHT), Some(700.0));
+    assert_eq!(coords.get_axis(Tag::WIDTH), Some(100.0));
+
+    let (display_name, postscript_name, coords) = &instances[1];
+    assert_eq!(display_name, "Semi Condensed Light");
+    assert_eq!(postscript_name, "ExampleSans-SemiCondensedLight");
+    assert_eq!(coords.get_axis(Tag::WIDTH), Some(87.5));
+}
+
+#[test]
+fn test_derived_postscript_name_prefers_variations_prefix() {
+    // Given a font with a typographic family and a variations PostScript name prefix
+    // When deriving an instance's PostScript name
+    // Then the prefix is used over the family names
+    let names = [
+        (1, "Example Sans Bold"),
+        (16, "Example Sans"),
+        (256, "Black"),
+    ];
+    let instances = [(256, 0xFFFF, 900.0, 100.0)];
+
+    let font = font_with(&names, &instances);
+    assert_eq!(font.named_instances_resolved()[0].1, "ExampleSans-Black");
+
+    let mut names = names.to_vec();
+    names.push((25, "ExVF"));
+    let font = font_with(&names, &instances);
+    assert_eq!(font.named_instances_resolved()[0].1, "ExVF-Black");
+}
+
+#[test]
+fn test_named_instances_resolved_without_name_table() {
+    let font = OpenTypeFont::parse(build_font(&[
+        ("fvar", fvar_table(&[(256, 0xFFFF, 700.0, 100.0)])),
+        ("head", head_table(1000)),
+        ("maxp", maxp_table(1)),
+    ]))
+    .unwrap();
+
+    assert!(font.get_name().is_none());
+    assert!(font.named_instances_resolved().is_empty());
+    assert_eq!(font.get_named_instances().len(), 1);
+}
@@ -4,21 +4,21 @@ use font_parser::ParseError;
 
 #[test]
 fn test_parse_error_invalid_format() {
-    /// Given an invalid format error
-    /// When the error is created
-    /// Then it should be the InvalidFormat variant
+    // Given an invalid format error
+    // When the error is created
+    // Then it should be the InvalidFormat variant
     let error = ParseError::InvalidFormat;
     match error {
-        ParseError::InvalidFormat => assert!(true),
+        ParseError::InvalidFormat => {}
         _ => panic!("Expected InvalidFormat variant"),
     }
 }
 
 #[test]
 fn test_parse_error_missing_table() {
-    /// Given a missing table error with table name
-    /// When the error is created
-    /// Then it should contain the table name
+    // Given a missing table error with table name
+    // When the error is created
+    // Then it should contain the table name
     let error = ParseError::MissingTable("cmap".to_string());
     match error {
         ParseError::MissingTable(name) => assert_eq!(name, "cmap"),
@@ -28,9 +28,9 @@ fn test_parse_error_missing_table() {
 
 #[test]
 fn test_parse_error_corrupted_data() {
-    /// Given corrupted data error with description
-    /// When the error is created
-    /// Then it should contain the description
+    // Given corrupted data error with description
+    // When the error is created
+    // Then it should contain the description
     let error = ParseError::CorruptedData("Invalid checksum".to_string());
     match error {
         ParseError::CorruptedData(msg) => assert_eq!(msg, "Invalid checksum"),
@@ -40,21 +40,21 @@ fn test_parse_error_corrupted_data() {
 
 #[test]
 fn test_parse_error_unsupported_version() {
-    /// Given an unsupported version error
-    /// When the error is created
-    /// Then it should be the UnsupportedVersion variant
+    // Given an unsupported version error
+    // When the error is created
+    // Then it should be the UnsupportedVersion variant
     let error = ParseError::UnsupportedVersion;
     match error {
-        ParseError::UnsupportedVersion => assert!(true),
+        ParseError::UnsupportedVersion => {}
         _ => panic!("Expected UnsupportedVersion variant"),
     }
 }
 
 #[test]
 fn test_parse_error_display() {
-    /// Given a parse error
-    /// When converted to string
-    /// Then it should have a meaningful message
+    // Given a parse error
+    // When converted to string
+    // Then it should have a meaningful message
     let error = ParseError::MissingTable("head".to_string());
     let error_msg = format!("{}", error);
     assert!(error_msg.contains("head") || error_msg.contains("table"));
@@ -62,14 +62,14 @@ fn test_parse_error_display() {
 
 #[test]
 fn test_parse_error_from_io() {
-    /// Given an IO error
-    /// When converting to ParseError
-    /// Then it should be CorruptedData variant
+    // Given an IO error
+    // When converting to ParseError
+    // Then it should be CorruptedData variant
     use std::io::{Error, ErrorKind};
     let io_error = Error::new(ErrorKind::UnexpectedEof, "EOF");
     let parse_error = ParseError::from(io_error);
     match parse_error {
-        ParseError::CorruptedData(_) => assert!(true),
+        ParseError::CorruptedData(_) => {}
         _ => panic!("Expected CorruptedData variant from IO error"),
     }
 }
@@ -0,0 +1,64 @@
+//! Unit tests for post table access through OpenTypeFont
+
+use super::test_validation::build_font;
+use font_parser::OpenTypeFont;
+
+/// Build a format 2.0 post table with one custom glyph name
+fn post_table(italic_angle: i32, is_fixed_pitch: bool) -> Vec<u8> {
+    let mut post = Vec::new();
+    post.extend_from_slice(&0x00020000u32.to_be_bytes()); // version 2.0
+    post.extend_from_slice(&italic_angle.to_be_bytes());
+    post.extend_from_slice(&(-75i16).to_be_bytes()); // underlinePosition
+    post.extend_from_slice(&50i16.to_be_bytes()); // underlineThickness
+    post.extend_from_slice(&(is_fixed_pitch as u32).to_be_bytes());
+    post.extend_from_slice(&[0u8; 16]); // memory usage fields
+    post.extend_from_slice(&2u16.to_be_bytes()); // numGlyphs
+    post.extend_from_slice(&0u16.to_be_bytes()); // .notdef
+    post.extend_from_slice(&258u16.to_be_bytes()); // custom name
+    post.push(5); // Pascal string length
+    post.extend_from_slice(b"f_f_i");
+    post
+}
+
+#[test]
+fn test_glyph_name_lookup() {
+    // Given a font with a format 2.0 post table
+    // When looking up glyph names in both directions
+    // Then standard and custom names resolve
+    let font = OpenTypeFont::parse(build_font(&[("post", post_table(0, false))])).unwrap();
+
+    assert_eq!(font.glyph_name(0), Some(".notdef".to_string()));
+    assert_eq!(font.glyph_name(1), Some("f_f_i".to_string()));
+    assert_eq!(font.glyph_name(2), None);
+    assert_eq!(font.glyph_by_name("f_f_i"), Some(1));
+    assert_eq!(font.glyph_by_name(".notdef"), Some(0));
+}
+
+#[test]
+fn test_italic_angle_and_fixed_pitch() {
+    // Given an oblique monospaced font
+    // When reading post table properties
+    // Then italic angle and fixed pitch are exposed
+    let font = OpenTypeFont::parse(build_font(&[("post", post_table(-12 * 65536, true))])).unwrap();
+
+    assert_eq!(font.italic_angle(), -12.0);
+    assert!(font.is_fixed_pitch());
+
+    let post = font.get_post().unwrap();
+    assert_eq!(post.version, 2.0);
+    assert_eq!(post.underline_position, -75);
+}
+
+#[test]
+fn test_missing_post_table_defaults() {
+    // Given a font without a post table
+    // When reading post table properties
+    // Then defaults are returned
+    let font = OpenTypeFont::parse(build_font(&[("head", vec![0u8; 54])])).unwrap();
+
+    assert!(font.get_post().is_none());
+    assert_eq!(font.glyph_name(0), None);
+    assert_eq!(font.glyph_by_name("A"), None);
+    assert_eq!(font.italic_angle(), 0.0);
+    assert!(!font.is_fixed_pitch());
+}
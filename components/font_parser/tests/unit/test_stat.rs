@@ -0,0 +1,262 @@
+//! Unit tests for STAT parsing and style naming of variation instances
+
+use super::test_validation::build_font;
+use font_parser::{AxisValueKind, OpenTypeFont, StatTable, Tag, VariationCoordinates};
+
+const REGULAR: u16 = 261;
+const BLACK: u16 = 262;
+const CONDENSED: u16 = 263;
+const NORMAL: u16 = 264;
+const BOLD: u16 = 265;
+const LIGHT_CONDENSED: u16 = 266;
+const ROMAN: u16 = 267;
+const ITALIC: u16 = 268;
+
+const ELIDABLE: u16 = 0x0002;
+
+fn fixed(value: f32) -> [u8; 4] {
+    ((value * 65536.0) as i32).to_be_bytes()
+}
+
+/// Axis value table of format 1 (or 3 when `linked` is given)
+fn single_value(
+    axis_index: u16,
+    flags: u16,
+    name_id: u16,
+    value: f32,
+    linked: Option<f32>,
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&(if linked.is_some() { 3u16 } else { 1u16 }).to_be_bytes());
+    data.extend_from_slice(&axis_index.to_be_bytes());
+    data.extend_from_slice(&flags.to_be_bytes());
+    data.extend_from_slice(&name_id.to_be_bytes());
+    data.extend_from_slice(&fixed(value));
+    if let Some(linked) = linked {
+        data.extend_from_slice(&fixed(linked));
+    }
+    data
+}
+
+/// STAT with design axes wdth (ordering 0), wght (1) and ital (2)
+fn stat_table(minor_version: u16) -> Vec<u8> {
+    let axes: [(&[u8; 4], u16, u16); 3] = [(b"wght", 256, 1), (b"wdth", 257, 0), (b"ital", 258, 2)];
+
+    let mut values = vec![
+        single_value(0, ELIDABLE, REGULAR, 400.0, Some(700.0)),
+        single_value(0, 0, BOLD, 700.0, None),
+        single_value(0, 0, BLACK, 900.0, None),
+        single_value(1, ELIDABLE, NORMAL, 100.0, None),
+        single_value(2, ELIDABLE, ROMAN, 0.0, None),
+        single_value(2, 0, ITALIC, 1.0, None),
+    ];
+    // Format 2: Condensed covers 62.5..87.5
+    let mut range = Vec::new();
+    range.extend_from_slice(&2u16.to_be_bytes());
+    range.extend_from_slice(&1u16.to_be_bytes()); // axisIndex
+    range.extend_from_slice(&0u16.to_be_bytes()); // flags
+    range.extend_from_slice(&CONDENSED.to_be_bytes());
+    range.extend_from_slice(&fixed(75.0));
+    range.extend_from_slice(&fixed(62.5));
+    range.extend_from_slice(&fixed(87.5));
+    values.push(range);
+    // Format 4: Light Condensed names wght 300 + wdth 75 at once
+    let mut combination = Vec::new();
+    combination.extend_from_slice(&4u16.to_be_bytes());
+    combination.extend_from_slice(&2u16.to_be_bytes()); // axisCount
+    combination.extend_from_slice(&0u16.to_be_bytes()); // flags
+    combination.extend_from_slice(&LIGHT_CONDENSED.to_be_bytes());
+    combination.extend_from_slice(&0u16.to_be_bytes());
+    combination.extend_from_slice(&fixed(300.0));
+    combination.extend_from_slice(&1u16.to_be_bytes());
+    combination.extend_from_slice(&fixed(75.0));
+    values.push(combination);
+    // Unknown formats are skipped
+    values.push(vec![0, 9, 0, 0]);
+
+    let header_size = if minor_version >= 1 { 20 } else { 18 };
+    let axes_offset = header_size as u32;
+    let offsets_offset = axes_offset + axes.len() as u32 * 8;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+    data.extend_from_slice(&minor_version.to_be_bytes());
+    data.extend_from_slice(&8u16.to_be_bytes()); // designAxisSize
+    data.extend_from_slice(&(axes.len() as u16).to_be_bytes());
+    data.extend_from_slice(&axes_offset.to_be_bytes());
+    data.extend_from_slice(&(values.len() as u16).to_be_bytes());
+    data.extend_from_slice(&offsets_offset.to_be_bytes());
+    if minor_version >= 1 {
+        data.extend_from_slice(&300u16.to_be_bytes()); // elidedFallbackNameID
+    }
+    for (tag, name_id, ordering) in axes {
+        data.extend_from_slice(tag);
+        data.extend_from_slice(&name_id.to_be_bytes());
+        data.extend_from_slice(&ordering.to_be_bytes());
+    }
+    let mut offset = values.len() as u16 * 2;
+    for value in &values {
+        data.extend_from_slice(&offset.to_be_bytes());
+        offset += value.len() as u16;
+    }
+    for value in &values {
+        data.extend_from_slice(value);
+    }
+    data
+}
+
+fn coords(values: &[(&str, f32)]) -> VariationCoordinates {
+    let mut coords = VariationCoordinates::new();
+    for (tag, value) in values {
+        coords.set_axis(Tag::new(tag).unwrap(), *value);
+    }
+    coords
+}
+
+#[test]
+fn test_parse_stat_axes_and_values() {
+    // Given a STAT 1.1 table with three axes and values of formats 1 to 4
+    // When parsing
+    // Then every known axis value table is decoded and the unknown one skipped
+    let stat = StatTable::parse(&stat_table(1)).unwrap();
+
+    assert_eq!(stat.design_axes.len(), 3);
+    let wdth = stat.get_axis(Tag::WIDTH).unwrap();
+    assert_eq!((wdth.name_id, wdth.ordering), (257, 0));
+    assert_eq!(stat.elided_fallback_name_id, 300);
+    assert_eq!(stat.axis_values.len(), 8);
+
+    let regular = &stat.axis_values[0];
+    assert!(regular.is_elidable());
+    assert!(!regular.is_older_sibling());
+    assert_eq!(
+        regular.kind,
+        AxisValueKind::Linked {
+            axis_index: 0,
+            value: 400.0,
+            linked_value: 700.0
+        }
+    );
+    assert_eq!(
+        stat.axis_values[6].kind,
+        AxisValueKind::Range {
+            axis_index: 1,
+            nominal_value: 75.0,
+            min_value: 62.5,
+            max_value: 87.5
+        }
+    );
+    assert_eq!(
+        stat.axis_values[7].kind,
+        AxisValueKind::Combination {
+            values: vec![(0, 300.0), (1, 75.0)]
+        }
+    );
+}
+
+#[test]
+fn test_stat_version_1_0_uses_regular_as_elided_fallback() {
+    let stat = StatTable::parse(&stat_table(0)).unwrap();
+    assert_eq!(stat.elided_fallback_name_id, 2);
+    assert_eq!(stat.axis_values.len(), 8);
+}
+
+#[test]
+fn test_name_coordinates_follows_axis_ordering() {
+    // Given wdth ordered before wght
+    // When naming a condensed black italic instance
+    // Then names appear as "Condensed Black Italic"
+    let stat = StatTable::parse(&stat_table(1)).unwrap();
+    let names = stat.name_coordinates(&coords(&[("wght", 900.0), ("wdth", 75.0), ("ital", 1.0)]));
+    assert_eq!(names, vec![CONDENSED, BLACK, ITALIC]);
+}
+
+#[test]
+fn test_name_coordinates_elides_default_names() {
+    // Given the Regular/Normal/Roman position, whose names are all elidable
+    // When naming it
+    // Then only the elided fallback name is returned
+    let stat = StatTable::parse(&stat_table(1)).unwrap();
+    let names = stat.name_coordinates(&coords(&[("wght", 400.0), ("wdth", 100.0), ("ital", 0.0)]));
+    assert_eq!(names, vec![300]);
+
+    let names = stat.name_coordinates(&coords(&[("wght", 700.0), ("wdth", 100.0)]));
+    assert_eq!(names, vec![BOLD]);
+}
+
+#[test]
+fn test_name_coordinates_uses_ranges_and_combinations() {
+    let stat = StatTable::parse(&stat_table(1)).unwrap();
+
+    // A width inside the Condensed range
+    let names = stat.name_coordinates(&coords(&[("wght", 700.0), ("wdth", 80.0)]));
+    assert_eq!(names, vec![CONDENSED, BOLD]);
+
+    // The format 4 value names both axes at once
+    let names = stat.name_coordinates(&coords(&[("wght", 300.0), ("wdth", 75.0)]));
+    assert_eq!(names, vec![LIGHT_CONDENSED]);
+
+    // Positions without any axis value stay unnamed
+    let names = stat.name_coordinates(&coords(&[("wght", 550.0), ("wdth", 110.0)]));
+    assert_eq!(names, vec![300]);
+}
+
+#[test]
+fn test_parse_stat_rejects_bad_tables() {
+    assert!(StatTable::parse(&[0, 1]).is_err());
+
+    let mut data = stat_table(1);
+    data[0..2].copy_from_slice(&2u16.to_be_bytes());
+    assert!(StatTable::parse(&data).is_err());
+
+    // Design axis size smaller than a record
+    let mut data = stat_table(1);
+    data[4..6].copy_from_slice(&4u16.to_be_bytes());
+    assert!(StatTable::parse(&data).is_err());
+
+    // More axis values than offsets present
+    let mut data = stat_table(1);
+    data[12..14].copy_from_slice(&u16::MAX.to_be_bytes());
+    assert!(StatTable::parse(&data).is_err());
+}
+
+#[test]
+fn test_font_variation_name_ids_fill_fvar_defaults() {
+    // Given a variable font with wght (default 400) and wdth (default 100)
+    // When naming coordinates that only set the width
+    // Then the weight takes its default and its elidable name is dropped
+    let mut fvar = Vec::new();
+    fvar.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+    fvar.extend_from_slice(&0u16.to_be_bytes()); // minorVersion
+    fvar.extend_from_slice(&16u16.to_be_bytes()); // axesArrayOffset
+    fvar.extend_from_slice(&2u16.to_be_bytes()); // reserved
+    fvar.extend_from_slice(&2u16.to_be_bytes()); // axisCount
+    fvar.extend_from_slice(&20u16.to_be_bytes()); // axisSize
+    fvar.extend_from_slice(&0u16.to_be_bytes()); // instanceCount
+    fvar.extend_from_slice(&12u16.to_be_bytes()); // instanceSize
+    for (tag, min, default, max) in [
+        (b"wght", 100.0, 400.0, 900.0),
+        (b"wdth", 75.0, 100.0, 125.0),
+    ] {
+        fvar.extend_from_slice(tag);
+        fvar.extend_from_slice(&fixed(min));
+        fvar.extend_from_slice(&fixed(default));
+        fvar.extend_from_slice(&fixed(max));
+        fvar.extend_from_slice(&[0, 0, 1, 0]); // flags, axisNameID
+    }
+
+    let font = OpenTypeFont::parse(build_font(&[("STAT", stat_table(1)), ("fvar", fvar)])).unwrap();
+    assert!(font.get_stat().is_some());
+    assert_eq!(
+        font.variation_name_ids(&coords(&[("wdth", 75.0)])),
+        vec![CONDENSED]
+    );
+    assert_eq!(font.variation_name_ids(&coords(&[])), vec![300]);
+
+    // Without STAT there is nothing to name
+    let font = OpenTypeFont::parse(build_font(&[("head", vec![0u8; 54])])).unwrap();
+    assert!(font.get_stat().is_none());
+    assert!(font
+        .variation_name_ids(&coords(&[("wght", 900.0)]))
+        .is_empty());
+}
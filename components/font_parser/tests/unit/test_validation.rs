@@ -0,0 +1,281 @@
+//! Unit tests for font validation and sanitization
+
+use font_parser::{validate, validate_with_options, Tag, ValidationError, ValidationOptions};
+
+/// Build a minimal head table (54 bytes) with the given unitsPerEm
+pub(crate) fn head_table(units_per_em: u16) -> Vec<u8> {
+    let mut head = vec![0u8; 54];
+    head[0..4].copy_from_slice(&0x00010000u32.to_be_bytes()); // version
+    head[12..16].copy_from_slice(&0x5F0F3CF5u32.to_be_bytes()); // magicNumber
+    head[18..20].copy_from_slice(&units_per_em.to_be_bytes());
+    head
+}
+
+/// Build a minimal maxp table (version 0.5) with the given glyph count
+pub(crate) fn maxp_table(num_glyphs: u16) -> Vec<u8> {
+    let mut maxp = vec![0u8; 6];
+    maxp[0..4].copy_from_slice(&0x00005000u32.to_be_bytes());
+    maxp[4..6].copy_from_slice(&num_glyphs.to_be_bytes());
+    maxp
+}
+
+/// Build an sfnt from (tag, data) pairs in the given order
+pub(crate) fn build_font(tables: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let mut font = Vec::new();
+    font.extend_from_slice(&0x00010000u32.to_be_bytes());
+    font.extend_from_slice(&num_tables.to_be_bytes());
+    font.extend_from_slice(&[0, 0, 0, 0, 0, 0]); // searchRange, entrySelector, rangeShift
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut body = Vec::new();
+    for (tag, data) in tables {
+        font.extend_from_slice(tag.as_bytes());
+        font.extend_from_slice(&0u32.to_be_bytes()); // checksum
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        body.extend_from_slice(data);
+        while body.len() % 4 != 0 {
+            body.push(0);
+        }
+        offset = 12 + tables.len() * 16 + body.len();
+    }
+    font.extend_from_slice(&body);
+    font
+}
+
+pub(crate) fn minimal_font() -> Vec<u8> {
+    build_font(&[("maxp", maxp_table(1)), ("head", head_table(1000))])
+}
+
+#[test]
+fn test_validate_minimal_font_succeeds() {
+    // Given a structurally valid font with head and maxp
+    // When validating
+    // Then a sanitized copy is produced with a sorted directory
+    let sanitized = validate(&minimal_font()).unwrap();
+    assert_eq!(
+        sanitized.tables(),
+        &[Tag::new("head").unwrap(), Tag::new("maxp").unwrap()]
+    );
+}
+
+#[test]
+fn test_sanitized_font_passes_checksum_verification() {
+    // Given a font with zeroed checksums
+    // When sanitizing it and validating the output with checksum verification
+    // Then the recomputed checksums are accepted
+    let sanitized = validate(&minimal_font()).unwrap();
+    let options = ValidationOptions {
+        verify_checksums: true,
+        ..Default::default()
+    };
+    let revalidated = validate_with_options(sanitized.data(), &options).unwrap();
+    assert_eq!(revalidated.data(), sanitized.data());
+}
+
+#[test]
+fn test_sanitized_font_parses() {
+    // Given a sanitized font
+    // When parsing it as an OpenTypeFont
+    // Then its metrics are readable
+    let font = validate(&minimal_font()).unwrap().into_font().unwrap();
+    assert_eq!(font.table_count(), 2);
+    assert_eq!(font.get_metrics().units_per_em, 1000);
+}
+
+#[test]
+fn test_validate_checksum_mismatch() {
+    // Given a font whose directory checksums are all zero
+    // When validating with checksum verification enabled
+    // Then the first mismatching table is reported
+    let options = ValidationOptions {
+        verify_checksums: true,
+        ..Default::default()
+    };
+    let result = validate_with_options(&minimal_font(), &options);
+    assert!(matches!(
+        result,
+        Err(ValidationError::ChecksumMismatch { expected: 0, .. })
+    ));
+}
+
+#[test]
+fn test_validate_rejects_invalid_signature() {
+    let mut data = minimal_font();
+    data[0..4].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+    assert_eq!(
+        validate(&data),
+        Err(ValidationError::InvalidSignature(0xFFFFFFFF))
+    );
+}
+
+#[test]
+fn test_validate_rejects_absurd_table_count() {
+    // Given a header claiming far more tables than the data holds
+    // When validating
+    // Then the table count is rejected before any allocation
+    let mut data = minimal_font();
+    data[4..6].copy_from_slice(&0xFFFFu16.to_be_bytes());
+    assert_eq!(
+        validate(&data),
+        Err(ValidationError::InvalidTableCount(0xFFFF))
+    );
+}
+
+#[test]
+fn test_validate_rejects_zero_tables() {
+    let mut data = minimal_font();
+    data[4..6].copy_from_slice(&0u16.to_be_bytes());
+    assert_eq!(validate(&data), Err(ValidationError::InvalidTableCount(0)));
+}
+
+#[test]
+fn test_validate_rejects_truncated_directory() {
+    let data = minimal_font();
+    assert_eq!(validate(&data[..20]), Err(ValidationError::TooShort));
+}
+
+#[test]
+fn test_validate_rejects_out_of_bounds_table() {
+    // Given a table record whose length runs past the end of the data
+    // When validating
+    // Then the table is reported as out of bounds
+    let mut data = minimal_font();
+    // Second record (head) length field
+    data[12 + 16 + 12..12 + 16 + 16].copy_from_slice(&0xFFFF_FFF0u32.to_be_bytes());
+    assert_eq!(
+        validate(&data),
+        Err(ValidationError::TableOutOfBounds(Tag::new("head").unwrap()))
+    );
+}
+
+#[test]
+fn test_validate_rejects_table_inside_directory() {
+    let mut data = minimal_font();
+    // First record (maxp) offset pointing into the header
+    data[12 + 8..12 + 12].copy_from_slice(&0u32.to_be_bytes());
+    assert_eq!(
+        validate(&data),
+        Err(ValidationError::TableOutOfBounds(Tag::new("maxp").unwrap()))
+    );
+}
+
+#[test]
+fn test_validate_rejects_overlapping_tables() {
+    let mut data = minimal_font();
+    // Point head at maxp's offset
+    let maxp_offset = data[12 + 8..12 + 12].to_vec();
+    data[12 + 16 + 8..12 + 16 + 12].copy_from_slice(&maxp_offset);
+    assert!(matches!(
+        validate(&data),
+        Err(ValidationError::OverlappingTables(_, _))
+    ));
+}
+
+#[test]
+fn test_validate_rejects_duplicate_tables() {
+    let data = build_font(&[
+        ("head", head_table(1000)),
+        ("maxp", maxp_table(1)),
+        ("maxp", maxp_table(1)),
+    ]);
+    assert_eq!(
+        validate(&data),
+        Err(ValidationError::DuplicateTable(Tag::new("maxp").unwrap()))
+    );
+}
+
+#[test]
+fn test_validate_requires_head_and_maxp() {
+    let data = build_font(&[("maxp", maxp_table(1))]);
+    assert_eq!(
+        validate(&data),
+        Err(ValidationError::MissingTable(Tag::new("head").unwrap()))
+    );
+
+    let data = build_font(&[("head", head_table(1000))]);
+    assert_eq!(
+        validate(&data),
+        Err(ValidationError::MissingTable(Tag::new("maxp").unwrap()))
+    );
+}
+
+#[test]
+fn test_validate_rejects_bad_head_values() {
+    let data = build_font(&[("head", head_table(0)), ("maxp", maxp_table(1))]);
+    assert!(matches!(
+        validate(&data),
+        Err(ValidationError::InvalidTable { .. })
+    ));
+
+    let mut head = head_table(1000);
+    head[12] = 0; // corrupt magic number
+    let data = build_font(&[("head", head), ("maxp", maxp_table(1))]);
+    assert!(matches!(
+        validate(&data),
+        Err(ValidationError::InvalidTable { .. })
+    ));
+}
+
+#[test]
+fn test_validate_glyph_count_limits() {
+    // Given a font with zero glyphs or more glyphs than allowed
+    // When validating
+    // Then maxp is rejected
+    let data = build_font(&[("head", head_table(1000)), ("maxp", maxp_table(0))]);
+    assert!(matches!(
+        validate(&data),
+        Err(ValidationError::InvalidTable { .. })
+    ));
+
+    let data = build_font(&[("head", head_table(1000)), ("maxp", maxp_table(500))]);
+    let options = ValidationOptions {
+        max_glyphs: 100,
+        ..Default::default()
+    };
+    assert!(matches!(
+        validate_with_options(&data, &options),
+        Err(ValidationError::InvalidTable { .. })
+    ));
+}
+
+#[test]
+fn test_validate_rejects_short_hmtx() {
+    let mut hhea = vec![0u8; 36];
+    hhea[34..36].copy_from_slice(&2u16.to_be_bytes()); // numberOfHMetrics
+    let data = build_font(&[
+        ("head", head_table(1000)),
+        ("hhea", hhea),
+        ("hmtx", vec![0u8; 4]),
+        ("maxp", maxp_table(2)),
+    ]);
+    assert_eq!(
+        validate(&data),
+        Err(ValidationError::InvalidTable {
+            tag: Tag::new("hmtx").unwrap(),
+            reason: "table shorter than declared metrics".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_validate_rejects_loca_past_glyf() {
+    // Given a short-format loca whose last offset points past glyf
+    // When validating
+    // Then loca is rejected
+    let loca = [0u16, 4, 100]
+        .iter()
+        .flat_map(|v| v.to_be_bytes())
+        .collect::<Vec<u8>>();
+    let data = build_font(&[
+        ("glyf", vec![0u8; 8]),
+        ("head", head_table(1000)),
+        ("loca", loca),
+        ("maxp", maxp_table(2)),
+    ]);
+    assert!(matches!(
+        validate(&data),
+        Err(ValidationError::InvalidTable { .. })
+    ));
+}
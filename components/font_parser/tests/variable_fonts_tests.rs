@@ -1,7 +1,4 @@
-use font_parser::{
-    AvarTable, AxisSegmentMap, FvarTable, NamedInstance, OpenTypeFont, Tag, VariationAxis,
-    VariationCoordinates,
-};
+use font_parser::{AvarTable, FvarTable, NamedInstance, Tag, VariationAxis, VariationCoordinates};
 
 /// Helper to create a minimal fvar table for testing
 fn create_test_fvar_data() -> Vec<u8> {
@@ -52,23 +49,23 @@ fn create_test_avar_data() -> Vec<u8> {
     data.extend_from_slice(&0u16.to_be_bytes()); // reserved
     data.extend_from_slice(&2u16.to_be_bytes()); // axis count
 
-    // Axis 1 segment map (3 mappings)
+    // Axis 1 segment map (3 mappings, F2DOT14)
     data.extend_from_slice(&3u16.to_be_bytes());
-    data.extend_from_slice(&((-1.0 * 65536.0) as i32).to_be_bytes()); // from: -1.0
-    data.extend_from_slice(&((-1.0 * 65536.0) as i32).to_be_bytes()); // to: -1.0
-    data.extend_from_slice(&((0.0 * 65536.0) as i32).to_be_bytes()); // from: 0.0
-    data.extend_from_slice(&((0.0 * 65536.0) as i32).to_be_bytes()); // to: 0.0
-    data.extend_from_slice(&((1.0 * 65536.0) as i32).to_be_bytes()); // from: 1.0
-    data.extend_from_slice(&((1.0 * 65536.0) as i32).to_be_bytes()); // to: 1.0
+    data.extend_from_slice(&(-16384i16).to_be_bytes()); // from: -1.0
+    data.extend_from_slice(&(-16384i16).to_be_bytes()); // to: -1.0
+    data.extend_from_slice(&0i16.to_be_bytes()); // from: 0.0
+    data.extend_from_slice(&0i16.to_be_bytes()); // to: 0.0
+    data.extend_from_slice(&16384i16.to_be_bytes()); // from: 1.0
+    data.extend_from_slice(&16384i16.to_be_bytes()); // to: 1.0
 
     // Axis 2 segment map (3 mappings with non-linear mapping)
     data.extend_from_slice(&3u16.to_be_bytes());
-    data.extend_from_slice(&((-1.0 * 65536.0) as i32).to_be_bytes()); // from: -1.0
-    data.extend_from_slice(&((-1.0 * 65536.0) as i32).to_be_bytes()); // to: -1.0
-    data.extend_from_slice(&((0.0 * 65536.0) as i32).to_be_bytes()); // from: 0.0
-    data.extend_from_slice(&((-0.5 * 65536.0) as i32).to_be_bytes()); // to: -0.5 (non-linear)
-    data.extend_from_slice(&((1.0 * 65536.0) as i32).to_be_bytes()); // from: 1.0
-    data.extend_from_slice(&((1.0 * 65536.0) as i32).to_be_bytes()); // to: 1.0
+    data.extend_from_slice(&(-16384i16).to_be_bytes()); // from: -1.0
+    data.extend_from_slice(&(-16384i16).to_be_bytes()); // to: -1.0
+    data.extend_from_slice(&0i16.to_be_bytes()); // from: 0.0
+    data.extend_from_slice(&(-8192i16).to_be_bytes()); // to: -0.5 (non-linear)
+    data.extend_from_slice(&16384i16.to_be_bytes()); // from: 1.0
+    data.extend_from_slice(&16384i16.to_be_bytes()); // to: 1.0
 
     data
 }
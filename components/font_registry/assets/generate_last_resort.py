@@ -0,0 +1,123 @@
+#!/usr/bin/env python3
+"""Generate last_resort.ttf, the font embedded by the `last-resort-font` feature.
+
+The font has a single glyph, `.notdef`, drawn as a hollow box, and maps no
+characters, so every character shaped with it renders as a box while
+shapers still report it as missing (glyph 0).
+
+Usage: python3 generate_last_resort.py [output path]
+"""
+
+import struct
+import sys
+
+FAMILY = "Corten Last Resort"
+POSTSCRIPT = "CortenLastResort"
+UNITS_PER_EM = 1000
+ADVANCE = 600
+ASCENT = 800
+DESCENT = -200
+# Outer and inner rectangles of the box: (x_min, y_min, x_max, y_max)
+OUTER = (50, 0, 550, 700)
+INNER = (100, 50, 500, 650)
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def rectangle(box, clockwise):
+    x0, y0, x1, y1 = box
+    points = [(x0, y0), (x0, y1), (x1, y1), (x1, y0)]
+    return points if clockwise else [points[0]] + points[:0:-1]
+
+
+def glyf_table():
+    contours = [rectangle(OUTER, True), rectangle(INNER, False)]
+    points = [p for contour in contours for p in contour]
+    end_points, end = [], -1
+    for contour in contours:
+        end += len(contour)
+        end_points.append(end)
+    glyph = struct.pack(">hhhhh", len(contours), *OUTER)
+    glyph += struct.pack(">%dH" % len(end_points), *end_points)
+    glyph += struct.pack(">H", 0)  # no instructions
+    glyph += bytes([0x01] * len(points))  # on-curve, 16-bit coordinates
+    previous = 0
+    for x, _ in points:
+        glyph += struct.pack(">h", x - previous)
+        previous = x
+    previous = 0
+    for _, y in points:
+        glyph += struct.pack(">h", y - previous)
+        previous = y
+    glyph += b"\0" * (-len(glyph) % 4)
+    return glyph
+
+
+def name_table():
+    records = [(1, FAMILY), (2, "Regular"), (4, FAMILY), (6, POSTSCRIPT)]
+    strings, entries = b"", b""
+    for name_id, value in records:
+        encoded = value.encode("utf-16-be")
+        entries += struct.pack(">HHHHHH", 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    header = struct.pack(">HHH", 0, len(records), 6 + 12 * len(records))
+    return header + entries + strings
+
+
+def build():
+    glyf = glyf_table()
+    tables = {
+        # Format 4 subtable holding only the required final segment
+        b"cmap": struct.pack(">HHHHI", 0, 1, 3, 1, 12)
+        + struct.pack(">HHHHHHHHHHHH", 4, 24, 0, 2, 2, 0, 0, 0xFFFF, 0, 0xFFFF, 1, 0),
+        b"glyf": glyf,
+        b"head": struct.pack(
+            ">IIIIHHqqhhhhHHhhh",
+            0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B, UNITS_PER_EM,
+            0, 0, *OUTER, 0, 8, 2, 0, 0,
+        ),
+        b"hhea": struct.pack(
+            ">IhhhHhhhhhhhhhhhH",
+            0x00010000, ASCENT, DESCENT, 0, ADVANCE, OUTER[0], ADVANCE - OUTER[2],
+            OUTER[2], 1, 0, 0, 0, 0, 0, 0, 0, 1,
+        ),
+        b"hmtx": struct.pack(">Hh", ADVANCE, OUTER[0]),
+        b"loca": struct.pack(">HH", 0, len(glyf) // 2),
+        b"maxp": struct.pack(">IHHHHHHHHHHHHHH", 0x00010000, 1, 8, 2, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0),
+        b"name": name_table(),
+        b"OS/2": struct.pack(
+            ">HhHHHhhhhhhhhhhh10sIIII4sHHHhhhHH",
+            1, ADVANCE, 400, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            b"\0" * 10, 0, 0, 0, 0, b"NONE", 0x40, 0xFFFF, 0xFFFF,
+            ASCENT, DESCENT, 0, ASCENT, -DESCENT,
+        )
+        + struct.pack(">II", 0, 0),
+        b"post": struct.pack(">IiHHIIIII", 0x00030000, 0, -100 & 0xFFFF, 50, 0, 0, 0, 0, 0),
+    }
+
+    tags = sorted(tables)
+    count = len(tags)
+    entry_selector = count.bit_length() - 1
+    search_range = (1 << entry_selector) * 16
+    font = struct.pack(">IHHHH", 0x00010000, count, search_range, entry_selector, count * 16 - search_range)
+    offset = 12 + 16 * count
+    directory, body = b"", b""
+    for tag in tags:
+        data = tables[tag]
+        directory += struct.pack(">4sIII", tag, checksum(data), offset + len(body), len(data))
+        body += data + b"\0" * (-len(data) % 4)
+    font += directory + body
+
+    # head.checkSumAdjustment makes the whole font sum to 0xB1B0AFBA
+    head_offset = 12 + 16 * count + sum(len(tables[t]) + (-len(tables[t]) % 4) for t in tags[: tags.index(b"head")])
+    adjustment = (0xB1B0AFBA - checksum(font)) & 0xFFFFFFFF
+    return font[: head_offset + 8] + struct.pack(">I", adjustment) + font[head_offset + 12 :]
+
+
+if __name__ == "__main__":
+    path = sys.argv[1] if len(sys.argv) > 1 else "last_resort.ttf"
+    with open(path, "wb") as output:
+        output.write(build())
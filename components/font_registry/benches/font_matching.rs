@@ -1,15 +1,44 @@
 //! Benchmarks for font matching algorithm
 //!
 //! Tests the performance of the font matching system with various
-//! registry sizes and descriptor complexity.
+//! registry sizes and descriptor complexity. Registries are populated from
+//! the DejaVu fonts vendored in `tests/fixtures/fonts`.
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
-use font_registry::{FontRegistry, FontDescriptor, FontWeight, FontStyle, FontStretch};
+use font_registry::{
+    FontDescriptor, FontRegistry, FontStretch, FontStyle, FontSynthesis, FontWeight,
+};
+use std::path::PathBuf;
+
+const FIXTURE_FONTS: [&str; 3] = ["DejaVuSans.ttf", "DejaVuSansMono.ttf", "DejaVuSerif.ttf"];
+
+/// Load a vendored fixture font from the workspace `tests/fixtures/fonts`
+fn fixture_font(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../tests/fixtures/fonts")
+        .join(name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("missing fixture {}: {e}", path.display()))
+}
+
+/// Create a registry holding `copies` registrations of each fixture font
+fn fixture_registry(copies: usize) -> FontRegistry {
+    let mut registry = FontRegistry::new();
+    registry.set_deduplication(false);
+    for name in FIXTURE_FONTS {
+        let data = fixture_font(name);
+        for _ in 0..copies {
+            registry
+                .load_font_data(data.clone())
+                .unwrap_or_else(|e| panic!("{name} should load: {e}"));
+        }
+    }
+    registry
+}
 
 /// Benchmark font matching with default descriptor
 fn bench_match_font_default(c: &mut Criterion) {
     c.bench_function("match_font_default", |b| {
-        let registry = FontRegistry::new();
+        let registry = fixture_registry(1);
         let descriptor = FontDescriptor::default();
 
         b.iter(|| {
@@ -22,13 +51,14 @@ fn bench_match_font_default(c: &mut Criterion) {
 /// Benchmark font matching with specific descriptor
 fn bench_match_font_specific(c: &mut Criterion) {
     c.bench_function("match_font_specific", |b| {
-        let registry = FontRegistry::new();
+        let registry = fixture_registry(1);
         let descriptor = FontDescriptor {
-            family: vec!["Arial".to_string()],
+            family: vec!["DejaVu Serif".to_string()],
             weight: FontWeight::Bold,
             style: FontStyle::Normal,
             stretch: FontStretch::Normal,
             size: 16.0,
+            synthesis: FontSynthesis::ALL,
         };
 
         b.iter(|| {
@@ -42,7 +72,7 @@ fn bench_match_font_specific(c: &mut Criterion) {
 fn bench_match_font_weights(c: &mut Criterion) {
     let mut group = c.benchmark_group("match_font_weights");
 
-    let registry = FontRegistry::new();
+    let registry = fixture_registry(1);
     let weights = [
         ("thin", FontWeight::Thin),
         ("light", FontWeight::Light),
@@ -57,11 +87,12 @@ fn bench_match_font_weights(c: &mut Criterion) {
     for (name, weight) in weights.iter() {
         group.bench_with_input(BenchmarkId::from_parameter(name), weight, |b, &weight| {
             let descriptor = FontDescriptor {
-                family: vec!["Test".to_string()],
+                family: vec!["DejaVu Sans".to_string()],
                 weight,
                 style: FontStyle::Normal,
                 stretch: FontStretch::Normal,
                 size: 16.0,
+                synthesis: FontSynthesis::ALL,
             };
 
             b.iter(|| {
@@ -77,11 +108,11 @@ fn bench_match_font_weights(c: &mut Criterion) {
 fn bench_match_font_scalability(c: &mut Criterion) {
     let mut group = c.benchmark_group("match_font_scalability");
 
-    for size in [1, 10, 100, 500, 1000].iter() {
+    for copies in [1, 10, 100].iter() {
+        let size = copies * FIXTURE_FONTS.len();
         group.throughput(Throughput::Elements(1)); // One match per iteration
-        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &_size| {
-            // In real benchmark: create registry with 'size' fonts
-            let registry = FontRegistry::new();
+        group.bench_with_input(BenchmarkId::from_parameter(size), copies, |b, &copies| {
+            let registry = fixture_registry(copies);
             let descriptor = FontDescriptor::default();
 
             b.iter(|| {
@@ -100,7 +131,7 @@ fn bench_match_font_repeated(c: &mut Criterion) {
     for count in [1, 10, 100, 1000].iter() {
         group.throughput(Throughput::Elements(*count as u64));
         group.bench_with_input(BenchmarkId::from_parameter(count), count, |b, &count| {
-            let registry = FontRegistry::new();
+            let registry = fixture_registry(1);
             let descriptor = FontDescriptor::default();
 
             b.iter(|| {
@@ -123,6 +154,7 @@ fn bench_descriptor_clone(c: &mut Criterion) {
             style: FontStyle::Italic,
             stretch: FontStretch::Condensed,
             size: 16.0,
+            synthesis: FontSynthesis::ALL,
         };
 
         b.iter(|| {
@@ -0,0 +1,404 @@
+//! Fonts whose data arrives incrementally
+//!
+//! Large web fonts, e.g. CJK fonts of several megabytes, can be used before
+//! they are fully downloaded. A [`FontRangeProvider`] serves the byte ranges
+//! fetched so far. The face is registered once its table directory and every
+//! table except the glyph outlines (`glyf`, `CFF `, `CFF2`) are available,
+//! and glyphs become usable as their outline data arrives. Bytes that haven't
+//! arrived read as zero.
+
+use crate::types::{FontData, GlyphId, RegistryError};
+use std::fmt;
+use std::iter;
+use std::ops::Range;
+
+/// Source of a font file's bytes, fetched in ranges (e.g. with HTTP range
+/// requests or patch-subset responses)
+///
+/// The registry asks for the ranges it needs and keeps what is returned;
+/// ranges that aren't available yet are reported back (see
+/// `FontRegistry::missing_glyph_ranges`) so the embedder can fetch them.
+pub trait FontRangeProvider: Send + Sync {
+    /// Size of the complete font file in bytes
+    fn total_len(&self) -> usize;
+
+    /// The bytes of `range`, or `None` if they haven't been fetched yet
+    fn read_range(&self, range: Range<usize>) -> Option<Vec<u8>>;
+}
+
+/// Tables loaded glyph by glyph rather than before registration
+const OUTLINE_TABLES: [&[u8; 4]; 3] = [b"glyf", b"CFF ", b"CFF2"];
+
+/// Composite glyph nesting followed when looking for component data
+const MAX_COMPONENT_DEPTH: usize = 8;
+
+/// Largest font file accepted, since the whole file is allocated up front
+const MAX_FONT_LEN: usize = 256 * 1024 * 1024;
+
+/// Where a glyph's outline data lives
+#[derive(Debug)]
+enum Outlines {
+    /// `glyf` table with per-glyph offsets from `loca`
+    Glyf {
+        glyf: Range<usize>,
+        loca: Range<usize>,
+        long_offsets: bool,
+    },
+    /// CFF or CFF2 table, used once it is loaded in full
+    Cff(Range<usize>),
+    /// No outline table (e.g. bitmap-only fonts)
+    None,
+}
+
+/// A font's data as far as it has been loaded
+pub(crate) struct PartialFont {
+    provider: Box<dyn FontRangeProvider>,
+    /// The whole font file, zero where bytes haven't been loaded
+    data: FontData,
+    /// Loaded byte ranges, sorted and disjoint
+    loaded: Vec<Range<usize>>,
+    outlines: Outlines,
+}
+
+impl fmt::Debug for PartialFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartialFont")
+            .field("len", &self.data.len())
+            .field("loaded", &self.loaded)
+            .field("outlines", &self.outlines)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialFont {
+    /// Load the table directory and every table except the outlines
+    ///
+    /// # Errors
+    ///
+    /// * `RegistryError::MissingFontData` - The provider doesn't have the
+    ///   listed ranges yet; fetch them and try again
+    /// * `RegistryError::InvalidFont` - The data isn't a single TrueType or
+    ///   OpenType font, or its length is out of range
+    pub(crate) fn new(provider: Box<dyn FontRangeProvider>) -> Result<Self, RegistryError> {
+        let len = provider.total_len();
+        if len > MAX_FONT_LEN {
+            return Err(RegistryError::InvalidFont(format!(
+                "Font of {len} bytes exceeds the {MAX_FONT_LEN}-byte limit"
+            )));
+        }
+        let mut font = PartialFont {
+            provider,
+            data: vec![0; len].into(),
+            loaded: Vec::new(),
+            outlines: Outlines::None,
+        };
+
+        font.require(iter::once(0..12))?;
+        if !matches!(
+            font.data.get(0..4),
+            Some([0x00, 0x01, 0x00, 0x00] | b"OTTO" | b"true")
+        ) {
+            return Err(RegistryError::InvalidFont(
+                "Incremental loading needs an uncompressed TrueType or OpenType font".to_string(),
+            ));
+        }
+        let num_tables = read_u16(&font.data, 4).unwrap_or(0) as usize;
+        font.require(iter::once(12..12 + num_tables * 16))?;
+
+        let tables: Vec<([u8; 4], Range<usize>)> = (0..num_tables)
+            .filter_map(|i| {
+                let record = 12 + i * 16;
+                let tag = font.data.get(record..record + 4)?.try_into().ok()?;
+                let offset = read_u32(&font.data, record + 8)? as usize;
+                let length = read_u32(&font.data, record + 12)? as usize;
+                let end = offset.checked_add(length).filter(|&end| end <= len)?;
+                Some((tag, offset..end))
+            })
+            .collect();
+        let required = tables
+            .iter()
+            .filter(|(tag, _)| !OUTLINE_TABLES.contains(&tag))
+            .map(|(_, range)| range.clone());
+        font.require(required)?;
+
+        let table = |tag: &[u8; 4]| {
+            tables
+                .iter()
+                .find(|(table_tag, _)| table_tag == tag)
+                .map(|(_, range)| range.clone())
+        };
+        font.outlines = match (table(b"glyf"), table(b"loca"), table(b"head")) {
+            (Some(glyf), Some(loca), Some(head)) => Outlines::Glyf {
+                glyf,
+                loca,
+                // head.indexToLocFormat
+                long_offsets: read_u16(&font.data, head.start + 50) == Some(1),
+            },
+            _ => match table(b"CFF ").or_else(|| table(b"CFF2")) {
+                Some(cff) => Outlines::Cff(cff),
+                None => Outlines::None,
+            },
+        };
+        Ok(font)
+    }
+
+    /// The font data loaded so far
+    pub(crate) fn data(&self) -> &FontData {
+        &self.data
+    }
+
+    /// Whether every byte of the font has been loaded
+    pub(crate) fn is_complete(&self) -> bool {
+        self.loaded.first() == Some(&(0..self.data.len()))
+    }
+
+    /// Glyphs of `glyph_ids` whose outline data (including the components
+    /// of composite glyphs) isn't loaded
+    pub(crate) fn missing_glyphs(&self, glyph_ids: &[GlyphId]) -> Vec<GlyphId> {
+        glyph_ids
+            .iter()
+            .copied()
+            .filter(|&glyph_id| {
+                let mut ranges = Vec::new();
+                self.view().glyph_ranges(glyph_id.id, 0, &mut ranges);
+                !self.missing(&ranges).is_empty()
+            })
+            .collect()
+    }
+
+    /// Byte ranges to fetch for `glyph_ids`, sorted and merged
+    ///
+    /// Components of composite glyphs are only known once the composite's
+    /// own data is loaded, so fetching may take more than one round.
+    pub(crate) fn missing_ranges(&self, glyph_ids: &[GlyphId]) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let view = self.view();
+        for glyph_id in glyph_ids {
+            view.glyph_ranges(glyph_id.id, 0, &mut ranges);
+        }
+        self.missing(&ranges)
+    }
+
+    /// Load what the provider has of the data for `glyph_ids`
+    ///
+    /// Returns the font data with the new bytes, for the caller to validate
+    /// and [`commit`](Self::commit), or `None` if nothing new was loaded.
+    pub(crate) fn load_glyphs(
+        &self,
+        glyph_ids: &[GlyphId],
+    ) -> Option<(FontData, Vec<Range<usize>>)> {
+        let mut data: Option<Vec<u8>> = None;
+        let mut loaded = self.loaded.clone();
+        // Loading composites reveals their components
+        loop {
+            let view = PartialView {
+                data: data.as_deref().unwrap_or(&self.data),
+                loaded: &loaded,
+                outlines: &self.outlines,
+            };
+            let mut ranges = Vec::new();
+            for glyph_id in glyph_ids {
+                view.glyph_ranges(glyph_id.id, 0, &mut ranges);
+            }
+            let mut progress = false;
+            for range in missing_ranges(&loaded, &ranges) {
+                let Some(bytes) = self.provider.read_range(range.clone()) else {
+                    continue;
+                };
+                if bytes.len() != range.len() {
+                    continue;
+                }
+                data.get_or_insert_with(|| self.data.to_vec())[range.clone()]
+                    .copy_from_slice(&bytes);
+                insert_range(&mut loaded, range);
+                progress = true;
+            }
+            if !progress {
+                break;
+            }
+        }
+        data.map(|data| (data.into(), loaded))
+    }
+
+    /// Keep data returned by [`load_glyphs`](Self::load_glyphs)
+    pub(crate) fn commit(&mut self, data: FontData, loaded: Vec<Range<usize>>) {
+        self.data = data;
+        self.loaded = loaded;
+    }
+
+    /// Load `ranges` from the provider, failing with the ranges it lacks
+    ///
+    /// Ranges past the end of the font mean the font is truncated.
+    fn require(
+        &mut self,
+        ranges: impl IntoIterator<Item = Range<usize>>,
+    ) -> Result<(), RegistryError> {
+        let ranges: Vec<Range<usize>> = ranges.into_iter().collect();
+        let len = self.data.len();
+        if let Some(range) = ranges.iter().find(|r| r.start > r.end || r.end > len) {
+            return Err(RegistryError::InvalidFont(format!(
+                "Font data range {range:?} lies outside the {len}-byte font"
+            )));
+        }
+        let mut unavailable = Vec::new();
+        let mut data: Option<Vec<u8>> = None;
+        for range in self.missing(&ranges) {
+            match self.provider.read_range(range.clone()) {
+                Some(bytes) if bytes.len() == range.len() => {
+                    data.get_or_insert_with(|| self.data.to_vec())[range.clone()]
+                        .copy_from_slice(&bytes);
+                    insert_range(&mut self.loaded, range);
+                }
+                _ => unavailable.push(range),
+            }
+        }
+        if let Some(data) = data {
+            self.data = data.into();
+        }
+        if unavailable.is_empty() {
+            Ok(())
+        } else {
+            Err(RegistryError::MissingFontData(unavailable))
+        }
+    }
+
+    /// Parts of `ranges` that aren't loaded, sorted and merged
+    fn missing(&self, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        missing_ranges(&self.loaded, ranges)
+    }
+
+    fn view(&self) -> PartialView<'_> {
+        PartialView {
+            data: &self.data,
+            loaded: &self.loaded,
+            outlines: &self.outlines,
+        }
+    }
+}
+
+/// Font data being loaded, for finding the ranges glyphs need
+struct PartialView<'a> {
+    data: &'a [u8],
+    loaded: &'a [Range<usize>],
+    outlines: &'a Outlines,
+}
+
+impl PartialView<'_> {
+    /// Add the byte ranges holding `glyph`'s outline to `ranges`, with the
+    /// components of composite glyphs whose own data is loaded
+    fn glyph_ranges(&self, glyph: u32, depth: usize, ranges: &mut Vec<Range<usize>>) {
+        let (glyf, loca, long_offsets) = match self.outlines {
+            Outlines::Glyf {
+                glyf,
+                loca,
+                long_offsets,
+            } => (glyf, loca, *long_offsets),
+            Outlines::Cff(table) => return ranges.push(table.clone()),
+            Outlines::None => return,
+        };
+
+        let index = glyph as usize;
+        // loca holds one offset more than there are glyphs
+        let offset = |i: usize| {
+            let size = if long_offsets { 4 } else { 2 };
+            let at = loca.start + i * size;
+            if at + size > loca.end {
+                None
+            } else if long_offsets {
+                read_u32(self.data, at).map(|offset| offset as usize)
+            } else {
+                read_u16(self.data, at).map(|offset| offset as usize * 2)
+            }
+        };
+        let (Some(start), Some(end)) = (offset(index), offset(index + 1)) else {
+            return;
+        };
+        if start >= end || glyf.start + end > glyf.end {
+            // Empty glyphs such as spaces need no data
+            return;
+        }
+        let range = glyf.start + start..glyf.start + end;
+        ranges.push(range.clone());
+
+        let loaded = missing_ranges(self.loaded, std::slice::from_ref(&range)).is_empty();
+        if !loaded || depth >= MAX_COMPONENT_DEPTH {
+            return;
+        }
+        let glyph_data = &self.data[range];
+        // A negative numberOfContours marks a composite glyph
+        if read_u16(glyph_data, 0).is_none_or(|contours| (contours as i16) >= 0) {
+            return;
+        }
+        let mut position = 10;
+        while let (Some(flags), Some(component)) = (
+            read_u16(glyph_data, position),
+            read_u16(glyph_data, position + 2),
+        ) {
+            self.glyph_ranges(component as u32, depth + 1, ranges);
+            position += 4;
+            // ARG_1_AND_2_ARE_WORDS
+            position += if flags & 0x0001 != 0 { 4 } else { 2 };
+            // WE_HAVE_A_SCALE, WE_HAVE_AN_X_AND_Y_SCALE, WE_HAVE_A_TWO_BY_TWO
+            position += match flags {
+                _ if flags & 0x0008 != 0 => 2,
+                _ if flags & 0x0040 != 0 => 4,
+                _ if flags & 0x0080 != 0 => 8,
+                _ => 0,
+            };
+            // MORE_COMPONENTS
+            if flags & 0x0020 == 0 {
+                break;
+            }
+        }
+    }
+}
+
+/// Parts of `ranges` not covered by the sorted, disjoint `loaded`, sorted
+/// and merged
+fn missing_ranges(loaded: &[Range<usize>], ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut missing: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        let mut start = range.start;
+        let first = loaded.partition_point(|l| l.end <= start);
+        for l in &loaded[first..] {
+            if l.start >= range.end {
+                break;
+            }
+            if l.start > start {
+                insert_range(&mut missing, start..l.start);
+            }
+            start = start.max(l.end);
+        }
+        if start < range.end {
+            insert_range(&mut missing, start..range.end);
+        }
+    }
+    missing
+}
+
+/// Add `range` to sorted, disjoint `ranges`, merging touching ranges
+fn insert_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+    let first = ranges.partition_point(|r| r.end < range.start);
+    let last = ranges.partition_point(|r| r.start <= range.end);
+    let merged = if first < last {
+        ranges[first].start.min(range.start)..ranges[last - 1].end.max(range.end)
+    } else {
+        range
+    };
+    ranges.splice(first..last, [merged]);
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset.checked_add(2)?)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
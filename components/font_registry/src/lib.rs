@@ -10,8 +10,9 @@
 //!
 //! // Create a new registry
 //! let mut registry = FontRegistry::new();
+//! registry.set_last_resort_fallback(false);
 //!
-//! // Match a font (returns None if no fonts loaded)
+//! // Match a font (returns None if no fonts loaded and no last-resort font)
 //! let descriptor = FontDescriptor::default();
 //! let result = registry.match_font(&descriptor);
 //! assert_eq!(result, None);
@@ -20,12 +21,22 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+pub mod incremental;
 pub mod registry;
+pub mod synthetic;
 pub mod types;
+pub mod visibility;
 
 // Re-export main types for convenience
-pub use registry::FontRegistry;
+pub use incremental::FontRangeProvider;
+pub use platform_integration::{DiscoveryConfig, FontCategory};
+pub use registry::{FontRegistry, LAST_RESORT_FONT_ID};
+pub use synthetic::{SyntheticFont, SYNTHETIC_FAMILY};
 pub use types::{
-    FontDescriptor, FontFace, FontId, FontMetrics, FontStretch, FontStyle, FontWeight,
-    RegistryError,
+    BasePalette, FamilyMatch, FontDescriptor, FontFace, FontId, FontLoadFailure, FontMetrics,
+    FontMetricsOverrides, FontPalette, FontSetId, FontStretch, FontStyle, FontSynthesis,
+    FontVisibility, FontWeight, GlyphId, IoError, MatchCandidate, MatchContext, MatchExplanation,
+    MatchedFont, PaletteOverride, RegistryError, RegistryStats, SyntheticFlags, VariationAxisRange,
+    VariationCoordinate,
 };
+pub use visibility::STANDARD_FONT_FAMILIES;
@@ -1,11 +1,31 @@
 //! Font registry implementation with in-memory cache
 
+use crate::incremental::{FontRangeProvider, PartialFont};
+use crate::synthetic::SyntheticFont;
 use crate::types::{
-    FontDescriptor, FontFace, FontId, FontMetrics, FontStretch, FontStyle, FontWeight,
-    RegistryError,
+    FamilyMatch, FontData, FontDescriptor, FontFace, FontId, FontLoadFailure, FontMetrics,
+    FontMetricsOverrides, FontPalette, FontSetId, FontStretch, FontStyle, FontVisibility,
+    FontWeight, GlyphId, MatchCandidate, MatchContext, MatchExplanation, MatchedFont,
+    RegistryError, RegistryStats, SyntheticFlags, VariationAxisRange, VariationCoordinate,
 };
-use std::collections::HashMap;
-use std::path::Path;
+use crate::visibility::VisibleFamilies;
+use font_types::memory::{MemoryPressureLevel, MemoryReport, MemoryReporter, MemoryTrimmer};
+use platform_integration::{DiscoveryConfig, FontCategory};
+use std::collections::{HashMap, HashSet};
+use std::iter;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Font ID of the embedded last-resort font
+///
+/// Reserved so the last-resort font never collides with loaded fonts.
+pub const LAST_RESORT_FONT_ID: FontId = FontId::MAX;
+
+/// Last-resort font: a single hollow box `.notdef` glyph mapping no
+/// characters, generated by `assets/generate_last_resort.py`
+#[cfg(feature = "last-resort-font")]
+const LAST_RESORT_FONT: &[u8] = include_bytes!("../assets/last_resort.ttf");
 
 /// Font registry for font discovery, loading, and caching
 #[derive(Debug)]
@@ -14,6 +34,47 @@ pub struct FontRegistry {
     fonts: HashMap<FontId, FontFace>,
     /// Next font ID to assign
     next_id: FontId,
+    /// Verify table checksums when loading fonts
+    verify_checksums: bool,
+    /// Font files skipped during discovery because they failed verification
+    corrupted_fonts: Vec<PathBuf>,
+    /// Font files skipped during discovery, with the reason
+    load_failures: Vec<FontLoadFailure>,
+    /// Extra/excluded directories used by `load_system_fonts`
+    discovery_config: DiscoveryConfig,
+    /// Platform defaults for generic families by (category, language tag)
+    language_defaults: Mutex<HashMap<(FontCategory, String), Vec<String>>>,
+    /// Characters mapped by each font's cmap, built on first coverage check
+    coverage: Mutex<HashMap<FontId, CharCoverage>>,
+    /// Descriptors for which no loaded or platform fallback font covers a
+    /// character, cleared whenever fonts or their data change
+    uncovered_chars: HashMap<char, Vec<FontDescriptor>>,
+    /// Map loads of already registered fonts to the existing FontId
+    deduplicate: bool,
+    /// Content hash of each loaded font's data
+    content_hashes: HashMap<u64, FontId>,
+    /// (PostScript name, version string) of each loaded font
+    name_versions: HashMap<(String, String), FontId>,
+    /// Loaded fonts by normalized family name, including localized names
+    family_index: HashMap<String, Vec<FontId>>,
+    /// Loaded fonts by normalized PostScript name
+    postscript_index: HashMap<String, Vec<FontId>>,
+    /// Loaded fonts by normalized full name
+    full_name_index: HashMap<String, Vec<FontId>>,
+    /// Loaded fonts by detected category, matched by generic family names
+    category_index: HashMap<FontCategory, Vec<FontId>>,
+    /// User-configured substitutes by normalized family name
+    family_substitutes: HashMap<String, Vec<String>>,
+    /// Fonts belonging to each scoped font set
+    font_sets: HashMap<FontSetId, Vec<FontId>>,
+    /// Next font set ID to assign
+    next_set_id: FontSetId,
+    /// Data of fonts that are still being loaded incrementally
+    partial_fonts: HashMap<FontId, PartialFont>,
+    /// Installed fonts untrusted callers may match
+    font_visibility: FontVisibility,
+    /// `font_visibility` resolved for the discovery platform
+    visible_families: VisibleFamilies,
 }
 
 impl FontRegistry {
@@ -32,9 +93,233 @@ impl FontRegistry {
     /// assert_eq!(registry.font_count(), 0);
     /// ```
     pub fn new() -> Self {
-        Self {
+        let mut registry = Self {
             fonts: HashMap::new(),
+            family_index: HashMap::new(),
+            postscript_index: HashMap::new(),
+            full_name_index: HashMap::new(),
+            category_index: HashMap::new(),
+            family_substitutes: HashMap::new(),
             next_id: 0,
+            verify_checksums: false,
+            corrupted_fonts: Vec::new(),
+            load_failures: Vec::new(),
+            discovery_config: DiscoveryConfig::default(),
+            language_defaults: Mutex::new(HashMap::new()),
+            coverage: Mutex::new(HashMap::new()),
+            uncovered_chars: HashMap::new(),
+            deduplicate: true,
+            content_hashes: HashMap::new(),
+            name_versions: HashMap::new(),
+            font_sets: HashMap::new(),
+            next_set_id: 0,
+            partial_fonts: HashMap::new(),
+            font_visibility: FontVisibility::All,
+            visible_families: VisibleFamilies::default(),
+        };
+        registry.set_last_resort_fallback(true);
+        registry
+    }
+
+    /// Enable or disable matching the embedded last-resort font
+    ///
+    /// With the `last-resort-font` feature, the registry registers a font
+    /// whose `.notdef` glyph is a box under [`LAST_RESORT_FONT_ID`], and
+    /// [`match_font`](Self::match_font) returns it when no loaded font
+    /// matches, so matching never returns `None`. It is enabled by default;
+    /// builds without the feature don't embed the font and ignore this.
+    ///
+    /// The last-resort font is not counted by [`font_count`](Self::font_count)
+    /// or listed by [`font_faces`](Self::font_faces).
+    pub fn set_last_resort_fallback(&mut self, enabled: bool) {
+        if enabled && cfg!(feature = "last-resort-font") {
+            #[cfg(feature = "last-resort-font")]
+            self.fonts.insert(LAST_RESORT_FONT_ID, last_resort_face());
+        } else {
+            self.fonts.remove(&LAST_RESORT_FONT_ID);
+        }
+    }
+
+    /// Check whether the embedded last-resort font is registered
+    pub fn last_resort_fallback(&self) -> bool {
+        self.fonts.contains_key(&LAST_RESORT_FONT_ID)
+    }
+
+    /// Set the font discovery configuration used by `load_system_fonts`
+    ///
+    /// Use this to load application-bundled fonts from extra directories,
+    /// exclude directories, or skip system fonts for deterministic results.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::{DiscoveryConfig, FontRegistry};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_discovery_config(DiscoveryConfig {
+    ///     extra_dirs: vec![PathBuf::from("assets/fonts")],
+    ///     skip_system_fonts: true,
+    ///     ..Default::default()
+    /// });
+    /// assert!(registry.discovery_config().skip_system_fonts);
+    /// ```
+    pub fn set_discovery_config(&mut self, mut config: DiscoveryConfig) {
+        config.canonicalize_exclude_dirs();
+        self.discovery_config = config;
+        self.uncovered_chars.clear();
+        self.language_defaults
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.visible_families = VisibleFamilies::new(
+            &self.font_visibility,
+            self.discovery_config.resolved_platform(),
+        );
+    }
+
+    /// Get the font discovery configuration
+    pub fn discovery_config(&self) -> &DiscoveryConfig {
+        &self.discovery_config
+    }
+
+    /// Enable or disable deduplication of identical fonts
+    ///
+    /// When enabled (the default), loading a font whose bytes match an
+    /// already loaded font returns the existing `FontId` instead of
+    /// registering a copy. `load_system_fonts` also skips system fonts whose
+    /// PostScript name and version string match an installed one, e.g. the
+    /// same font installed both system-wide and per user.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::FontRegistry;
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// assert!(registry.deduplication());
+    /// registry.set_deduplication(false);
+    /// assert!(!registry.deduplication());
+    /// ```
+    pub fn set_deduplication(&mut self, enabled: bool) {
+        self.deduplicate = enabled;
+    }
+
+    /// Check whether deduplication of identical fonts is enabled
+    pub fn deduplication(&self) -> bool {
+        self.deduplicate
+    }
+
+    /// Find an already loaded font identical to the given data
+    ///
+    /// `name_version` is only given for system fonts, which also match by
+    /// PostScript name and version; fonts loaded from memory match by content.
+    fn find_duplicate(
+        &self,
+        content_hash: u64,
+        data: &[u8],
+        name_version: Option<&(String, String)>,
+    ) -> Option<FontId> {
+        if !self.deduplicate {
+            return None;
+        }
+
+        let by_content = self
+            .content_hashes
+            .get(&content_hash)
+            .filter(|id| self.fonts.get(id).and_then(FontFace::data) == Some(data));
+        let by_name = name_version.and_then(|key| self.name_versions.get(key));
+        by_content.or(by_name).copied()
+    }
+
+    /// Remember a newly loaded font for deduplication
+    fn record_font_identity(
+        &mut self,
+        font_id: FontId,
+        content_hash: u64,
+        name_version: Option<(String, String)>,
+    ) {
+        self.content_hashes.insert(content_hash, font_id);
+        if let Some(key) = name_version {
+            self.name_versions.insert(key, font_id);
+        }
+    }
+
+    /// Enable or disable checksum verification when loading fonts
+    ///
+    /// When enabled, every loaded font has its table checksums and head
+    /// checkSumAdjustment verified. `load_font_data` rejects corrupted fonts
+    /// with `RegistryError::CorruptedFont`, and `load_system_fonts` skips them
+    /// and records their paths in [`FontRegistry::corrupted_fonts`].
+    ///
+    /// Verification is disabled by default since many shipping fonts carry
+    /// stale checksums that renderers tolerate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::FontRegistry;
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_checksum_verification(true);
+    /// assert!(registry.checksum_verification());
+    /// ```
+    pub fn set_checksum_verification(&mut self, enabled: bool) {
+        self.verify_checksums = enabled;
+    }
+
+    /// Check whether checksum verification is enabled
+    pub fn checksum_verification(&self) -> bool {
+        self.verify_checksums
+    }
+
+    /// Get font files skipped during discovery because they failed verification
+    ///
+    /// # Returns
+    ///
+    /// Paths of system font files whose checksums did not verify in the most
+    /// recent [`load_system_fonts`](Self::load_system_fonts) call
+    pub fn corrupted_fonts(&self) -> &[PathBuf] {
+        &self.corrupted_fonts
+    }
+
+    /// Get font files skipped by [`load_system_fonts`](Self::load_system_fonts)
+    /// because they could not be read, verified or parsed
+    ///
+    /// Only the most recent call is reported; with the `tracing` feature each
+    /// failure is also logged as a warning.
+    pub fn load_report(&self) -> &[FontLoadFailure] {
+        &self.load_failures
+    }
+
+    /// Record a font file skipped during discovery
+    fn record_load_failure(&mut self, path: &Path, error: RegistryError) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(path = %path.display(), %error, "skipping font file");
+        self.load_failures.push(FontLoadFailure {
+            path: path.to_path_buf(),
+            error,
+        });
+    }
+
+    /// Verify font checksums if verification is enabled
+    ///
+    /// Takes ownership of the data to avoid copying it for parsing and hands
+    /// it back on success. Only plain TrueType/OpenType files are verified;
+    /// collections and web fonts are passed through unchanged.
+    fn verify_font_data(&self, data: Vec<u8>) -> Result<Vec<u8>, RegistryError> {
+        let is_sfnt = matches!(data.get(0..4), Some([0x00, 0x01, 0x00, 0x00] | b"OTTO"));
+        if !self.verify_checksums || !is_sfnt {
+            return Ok(data);
+        }
+
+        let font = font_parser::OpenTypeFont::parse(data)
+            .map_err(|e| RegistryError::InvalidFont(e.to_string()))?;
+        let report = font.verify_checksums();
+        if report.is_valid() {
+            Ok(font.into_data())
+        } else {
+            Err(RegistryError::CorruptedFont(report.to_string()))
         }
     }
 
@@ -44,7 +329,21 @@ impl FontRegistry {
     ///
     /// The count of loaded fonts
     pub fn font_count(&self) -> usize {
-        self.fonts.len()
+        self.fonts.len() - usize::from(self.last_resort_fallback())
+    }
+
+    /// Registered faces and the font data they keep in memory
+    pub fn stats(&self) -> RegistryStats {
+        let resident_faces = self
+            .fonts
+            .values()
+            .filter(|face| face.id != LAST_RESORT_FONT_ID && face.data.is_some())
+            .count();
+        RegistryStats {
+            faces: self.font_count(),
+            resident_faces,
+            resident_bytes: self.memory_report().bytes,
+        }
     }
 
     /// Load font from raw data
@@ -58,6 +357,13 @@ impl FontRegistry {
     /// * `Ok(FontId)` - ID of the loaded font
     /// * `Err(RegistryError)` - If font data is invalid or loading fails
     ///
+    /// Variable fonts additionally register one face per fvar named instance
+    /// (sharing this font's data); the returned ID is the default instance.
+    ///
+    /// If the bytes duplicate a font loaded into a font set, the font leaves
+    /// its sets and stays loaded until unloaded with
+    /// [`unload_font`](Self::unload_font).
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -68,106 +374,70 @@ impl FontRegistry {
     /// let font_data = fs::read("path/to/font.ttf").unwrap();
     /// let font_id = registry.load_font_data(font_data).unwrap();
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "font_registry::load_font_data",
+            level = "debug",
+            skip_all,
+            fields(bytes = data.len())
+        )
+    )]
     pub fn load_font_data(&mut self, data: Vec<u8>) -> Result<FontId, RegistryError> {
+        let first_new_id = self.next_id;
+        let font_id = self.register_font_data(data)?;
+        if font_id < first_new_id {
+            // Requested outside any set now, so no set may unload it
+            let faces = self.face_and_instances(font_id);
+            for fonts in self.font_sets.values_mut() {
+                fonts.retain(|id| !faces.contains(id));
+            }
+        }
+        Ok(font_id)
+    }
+
+    /// Register font data, or find the identical font already registered
+    fn register_font_data(&mut self, data: Vec<u8>) -> Result<FontId, RegistryError> {
         // Validate data is not empty
         if data.is_empty() {
             return Err(RegistryError::InvalidFont("Empty font data".to_string()));
         }
 
+        let data = self.verify_font_data(data)?;
+
         // Parse font using ttf-parser
         let face = ttf_parser::Face::parse(&data, 0)
             .map_err(|e| RegistryError::InvalidFont(format!("Failed to parse font: {:?}", e)))?;
 
-        // Extract font metadata
-        let family_name = face
-            .names()
-            .into_iter()
-            .find(|name| name.name_id == ttf_parser::name_id::FAMILY)
-            .and_then(|name| name.to_string())
-            .unwrap_or_else(|| "Unknown".to_string());
-
-        let postscript_name = face
-            .names()
-            .into_iter()
-            .find(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
-            .and_then(|name| name.to_string())
-            .unwrap_or_else(|| family_name.clone());
-
-        // Map ttf-parser weight to our FontWeight enum
-        let weight = match face.weight().to_number() {
-            100 => FontWeight::Thin,
-            200 => FontWeight::ExtraLight,
-            300 => FontWeight::Light,
-            400 => FontWeight::Regular,
-            500 => FontWeight::Medium,
-            600 => FontWeight::SemiBold,
-            700 => FontWeight::Bold,
-            800 => FontWeight::ExtraBold,
-            900 => FontWeight::Black,
-            _ => FontWeight::Regular, // Default to regular for unknown weights
-        };
-
-        // Map ttf-parser style to our FontStyle enum
-        let style = if face.is_italic() {
-            FontStyle::Italic
-        } else {
-            FontStyle::Normal
-        };
-
-        // Default stretch (ttf-parser doesn't expose width class easily)
-        let stretch = FontStretch::Normal;
-
-        // Extract font metrics
-        let units_per_em = face.units_per_em();
-        let ascent = face.ascender() as f32;
-        let descent = face.descender() as f32;
-        let line_gap = face.line_gap() as f32;
-
-        // Default values for metrics not directly available
-        let cap_height = face.capital_height().unwrap_or(700) as f32;
-        let x_height = face.x_height().unwrap_or(500) as f32;
-        let underline_position = face
-            .underline_metrics()
-            .map(|m| m.position as f32)
-            .unwrap_or(-150.0);
-        let underline_thickness = face
-            .underline_metrics()
-            .map(|m| m.thickness as f32)
-            .unwrap_or(50.0);
-
-        let metrics = FontMetrics {
-            units_per_em,
-            ascent,
-            descent,
-            line_gap,
-            cap_height,
-            x_height,
-            underline_position,
-            underline_thickness,
-        };
+        // Return the existing font if these bytes are already registered
+        let content_hash = hash_font_data(&data);
+        if let Some(existing) = self.find_duplicate(content_hash, &data, None) {
+            return Ok(existing);
+        }
 
         // Create FontFace with eagerly loaded data
         let font_id = self.next_id;
-        let font_face = FontFace {
-            id: font_id,
-            family_name,
-            postscript_name,
-            weight,
-            style,
-            stretch,
-            metrics,
-            file_path: None,  // No file path for directly loaded data
-            data: Some(data), // Data is eagerly loaded
-            is_system_font: false,
-        };
+        let mut font_face = face_from_parsed(font_id, &face);
+        font_face.data = Some(data.into());
 
         // Store in cache
         self.fonts.insert(font_id, font_face);
         self.next_id += 1;
+        self.record_font_identity(font_id, content_hash, None);
+        self.index_font(font_id);
+        self.register_variable_font(font_id);
 
         Ok(font_id)
     }
 
+    /// Load a [`SyntheticFont`], built in memory with fixed metrics and
+    /// rectangle glyphs
+    ///
+    /// Tests can shape, lay out and render it without any font file.
+    pub fn load_synthetic_font(&mut self, font: &SyntheticFont) -> Result<FontId, RegistryError> {
+        self.load_font_data(font.to_bytes())
+    }
+
     /// Load font from file path
     ///
     /// # Arguments
@@ -189,26 +459,167 @@ impl FontRegistry {
     /// let font_id = registry.load_font_file(Path::new("/path/to/font.ttf")).unwrap();
     /// ```
     pub fn load_font_file(&mut self, path: &Path) -> Result<FontId, RegistryError> {
-        let data = std::fs::read(path)
-            .map_err(|_| RegistryError::FileNotFound(path.display().to_string()))?;
+        let data = std::fs::read(path).map_err(|e| RegistryError::read_failed(path, e))?;
 
         self.load_font_data(data)
     }
 
+    /// Register a font whose data arrives incrementally
+    ///
+    /// The face is registered as soon as `provider` has the table directory
+    /// and every table but the glyph outlines (`glyf`, `CFF `, `CFF2`), so
+    /// large web fonts can match, shape and render glyphs whose data has
+    /// arrived before the download completes. Missing bytes read as zero:
+    /// check [`missing_glyphs`](Self::missing_glyphs) before rendering, and
+    /// pull newly fetched data in with [`load_glyph_data`](Self::load_glyph_data).
+    ///
+    /// Incremental fonts are not deduplicated.
+    ///
+    /// # Errors
+    ///
+    /// * `RegistryError::MissingFontData` - `provider` lacks the listed byte
+    ///   ranges; fetch them and call again
+    /// * `RegistryError::InvalidFont` - The data is not a single uncompressed
+    ///   TrueType or OpenType font
+    pub fn load_incremental_font(
+        &mut self,
+        provider: Box<dyn FontRangeProvider>,
+    ) -> Result<FontId, RegistryError> {
+        let partial = PartialFont::new(provider)?;
+        let data = Arc::clone(partial.data());
+        let face = ttf_parser::Face::parse(&data, 0)
+            .map_err(|e| RegistryError::InvalidFont(format!("Failed to parse font: {:?}", e)))?;
+
+        let font_id = self.next_id;
+        let mut font_face = face_from_parsed(font_id, &face);
+        font_face.data = Some(data);
+        self.fonts.insert(font_id, font_face);
+        self.next_id += 1;
+        self.index_font(font_id);
+        self.register_variable_font(font_id);
+        self.partial_fonts.insert(font_id, partial);
+
+        Ok(font_id)
+    }
+
+    /// Glyphs of `glyph_ids` whose outline data hasn't been loaded
+    ///
+    /// Composite glyphs count as missing until their components are loaded
+    /// too. Fonts that aren't loaded incrementally, or have finished
+    /// loading, miss no glyphs.
+    ///
+    /// # Errors
+    ///
+    /// * `RegistryError::FontNotFound` - No font with this ID is loaded
+    pub fn missing_glyphs(
+        &self,
+        font_id: FontId,
+        glyph_ids: &[GlyphId],
+    ) -> Result<Vec<GlyphId>, RegistryError> {
+        Ok(self
+            .partial_font(font_id)?
+            .map_or_else(Vec::new, |partial| partial.missing_glyphs(glyph_ids)))
+    }
+
+    /// Byte ranges of the font file to fetch for `glyph_ids`, sorted and
+    /// merged
+    ///
+    /// The components of a composite glyph are only known once its own data
+    /// has been loaded, so glyphs may need more than one round of fetching.
+    ///
+    /// # Errors
+    ///
+    /// * `RegistryError::FontNotFound` - No font with this ID is loaded
+    pub fn missing_glyph_ranges(
+        &self,
+        font_id: FontId,
+        glyph_ids: &[GlyphId],
+    ) -> Result<Vec<Range<usize>>, RegistryError> {
+        Ok(self
+            .partial_font(font_id)?
+            .map_or_else(Vec::new, |partial| partial.missing_ranges(glyph_ids)))
+    }
+
+    /// Load the data for `glyph_ids` that the font's provider has fetched
+    ///
+    /// Composite glyphs pull in their components. When data was added, the
+    /// font is parsed again before its faces (including named instances)
+    /// switch to the new data; once every byte has arrived the font is
+    /// complete and the provider is dropped. Returns the glyphs still
+    /// missing, as [`missing_glyphs`](Self::missing_glyphs) would.
+    ///
+    /// Shaping and glyph caches keyed by font ID keep results from before
+    /// the update; clear them or render only glyphs that weren't missing.
+    ///
+    /// # Errors
+    ///
+    /// * `RegistryError::FontNotFound` - No font with this ID is loaded
+    /// * `RegistryError::InvalidFont` - The new data doesn't parse; the font
+    ///   keeps its previous data
+    pub fn load_glyph_data(
+        &mut self,
+        font_id: FontId,
+        glyph_ids: &[GlyphId],
+    ) -> Result<Vec<GlyphId>, RegistryError> {
+        let Some(partial) = self.partial_font(font_id)? else {
+            return Ok(Vec::new());
+        };
+        let Some((data, loaded)) = partial.load_glyphs(glyph_ids) else {
+            return Ok(partial.missing_glyphs(glyph_ids));
+        };
+        ttf_parser::Face::parse(&data, 0).map_err(|e| {
+            RegistryError::InvalidFont(format!("Failed to parse loaded font data: {:?}", e))
+        })?;
+
+        for face in self.fonts.values_mut() {
+            if face.id == font_id || face.instance_of == Some(font_id) {
+                face.data = Some(Arc::clone(&data));
+            }
+        }
+        let Some(partial) = self.partial_fonts.get_mut(&font_id) else {
+            return Ok(Vec::new());
+        };
+        partial.commit(data, loaded);
+        let missing = partial.missing_glyphs(glyph_ids);
+        if partial.is_complete() {
+            self.partial_fonts.remove(&font_id);
+        }
+        Ok(missing)
+    }
+
+    /// Loading state of an incrementally loaded font, `None` for other fonts
+    fn partial_font(&self, font_id: FontId) -> Result<Option<&PartialFont>, RegistryError> {
+        if !self.fonts.contains_key(&font_id) {
+            return Err(RegistryError::FontNotFound(font_id));
+        }
+        Ok(self.partial_fonts.get(&font_id))
+    }
+
     /// Load system fonts (platform-specific)
     ///
     /// # Returns
     ///
-    /// * `Ok(usize)` - Number of fonts loaded
+    /// * `Ok(usize)` - Number of font faces loaded, including named instances
     /// * `Err(RegistryError)` - If system fonts unavailable
     ///
     /// # Implementation
     ///
-    /// Uses platform_integration to discover system fonts.
+    /// Uses platform_integration to discover system fonts, honoring the
+    /// registry's [`DiscoveryConfig`] (extra directories, exclusions and
+    /// skipping system fonts).
     /// Fonts are loaded with metadata but data is loaded lazily on-demand.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "font_registry::load_system_fonts", level = "debug", skip_all)
+    )]
     pub fn load_system_fonts(&mut self) -> Result<usize, RegistryError> {
-        // Discover system fonts using platform_integration
-        let platform_fonts = platform_integration::discover_system_fonts_detailed();
+        // Report only this pass, so repeated rescans do not pile up failures
+        self.load_failures.clear();
+        self.corrupted_fonts.clear();
+
+        // Discover fonts using platform_integration
+        let platform_fonts =
+            platform_integration::discover_fonts_with_config(&self.discovery_config);
 
         if platform_fonts.is_empty() {
             // No fonts found, but this is not necessarily an error
@@ -218,36 +629,27 @@ impl FontRegistry {
         let mut loaded_count = 0;
 
         for platform_font in platform_fonts {
-            // Convert platform FontWeight to our FontWeight
-            let weight = match platform_font.weight {
-                platform_integration::FontWeight::Thin => FontWeight::Thin,
-                platform_integration::FontWeight::ExtraLight => FontWeight::ExtraLight,
-                platform_integration::FontWeight::Light => FontWeight::Light,
-                platform_integration::FontWeight::Regular => FontWeight::Regular,
-                platform_integration::FontWeight::Medium => FontWeight::Medium,
-                platform_integration::FontWeight::SemiBold => FontWeight::SemiBold,
-                platform_integration::FontWeight::Bold => FontWeight::Bold,
-                platform_integration::FontWeight::ExtraBold => FontWeight::ExtraBold,
-                platform_integration::FontWeight::Black => FontWeight::Black,
-            };
-
-            // Convert platform FontStyle to our FontStyle
-            let style = match platform_font.style {
-                platform_integration::FontStyle::Normal => FontStyle::Normal,
-                platform_integration::FontStyle::Italic => FontStyle::Italic,
-                platform_integration::FontStyle::Oblique(angle) => FontStyle::Oblique(angle),
-            };
+            // Platform integration shares the font_types weight/style types
+            let weight = platform_font.weight;
+            let style = platform_font.style;
 
             // Load font data from file to extract metrics
             // (We keep the file path and can reload data later if needed)
             let font_data = match std::fs::read(&platform_font.path) {
                 Ok(data) => data,
-                Err(_) => {
-                    // Skip fonts we can't read
-                    eprintln!(
-                        "Warning: Could not read font file: {}",
-                        platform_font.path.display()
-                    );
+                Err(e) => {
+                    let error = RegistryError::read_failed(&platform_font.path, e);
+                    self.record_load_failure(&platform_font.path, error);
+                    continue;
+                }
+            };
+
+            // Flag corrupted files during discovery instead of failing at render time
+            let font_data = match self.verify_font_data(font_data) {
+                Ok(data) => data,
+                Err(e) => {
+                    self.record_load_failure(&platform_font.path, e);
+                    self.corrupted_fonts.push(platform_font.path.clone());
                     continue;
                 }
             };
@@ -255,157 +657,260 @@ impl FontRegistry {
             // Parse font to extract metrics
             let face = match ttf_parser::Face::parse(&font_data, 0) {
                 Ok(face) => face,
-                Err(_) => {
-                    // Skip invalid fonts
-                    eprintln!(
-                        "Warning: Could not parse font file: {}",
-                        platform_font.path.display()
-                    );
+                Err(e) => {
+                    let error = RegistryError::InvalidFont(e.to_string());
+                    self.record_load_failure(&platform_font.path, error);
                     continue;
                 }
             };
 
+            // Skip fonts already registered under another path
+            let content_hash = hash_font_data(&font_data);
+            let name_version = face_name_version(&face);
+            if self
+                .find_duplicate(content_hash, &font_data, name_version.as_ref())
+                .is_some()
+            {
+                continue;
+            }
+
             // Extract font metrics
-            let units_per_em = face.units_per_em();
-            let ascent = face.ascender() as f32;
-            let descent = face.descender() as f32;
-            let line_gap = face.line_gap() as f32;
-            let cap_height = face.capital_height().unwrap_or(700) as f32;
-            let x_height = face.x_height().unwrap_or(500) as f32;
-            let underline_position = face
-                .underline_metrics()
-                .map(|m| m.position as f32)
-                .unwrap_or(-150.0);
-            let underline_thickness = face
-                .underline_metrics()
-                .map(|m| m.thickness as f32)
-                .unwrap_or(50.0);
-
-            let metrics = FontMetrics {
-                units_per_em,
-                ascent,
-                descent,
-                line_gap,
-                cap_height,
-                x_height,
-                underline_position,
-                underline_thickness,
+            let metrics = face_metrics(&face);
+
+            // Fonts from extra directories only carry placeholder metadata, so
+            // read family, weight and style from the font for non-system fonts
+            let (family_name, weight, style) = if platform_font.is_system_font {
+                // Platforms report obliques without their angle
+                let style = match (style, face_style(&face)) {
+                    (FontStyle::Oblique(_), oblique @ FontStyle::Oblique(_)) => oblique,
+                    _ => style,
+                };
+                (platform_font.family_name, weight, style)
+            } else {
+                let family_name = face_name(&face, ttf_parser::name_id::FAMILY)
+                    .unwrap_or(platform_font.family_name);
+                (family_name, face_weight(&face), face_style(&face))
             };
 
             // Get PostScript name (use family name as fallback)
-            let postscript_name = face
-                .names()
-                .into_iter()
-                .find(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
-                .and_then(|name| name.to_string())
-                .unwrap_or_else(|| platform_font.family_name.clone());
+            let postscript_name = face_name(&face, ttf_parser::name_id::POST_SCRIPT_NAME)
+                .unwrap_or_else(|| family_name.clone());
+            let full_name = face_name(&face, ttf_parser::name_id::FULL_NAME)
+                .unwrap_or_else(|| family_name.clone());
+
+            let family_aliases = family_aliases(&family_name, face_family_names(&face));
 
             // Create FontFace entry with lazy loading support
             let font_id = self.next_id;
             let font_face = FontFace {
                 id: font_id,
-                family_name: platform_font.family_name,
+                family_name,
+                family_aliases,
                 postscript_name,
+                full_name,
                 weight,
                 style,
-                stretch: FontStretch::Normal, // Platform doesn't provide stretch yet
+                stretch: face_stretch(&face), // Platform doesn't provide stretch
                 metrics,
+                is_fixed_pitch: face.is_monospaced(),
+                fixed_advance: face_fixed_advance(&face),
+                category: face_category(&face),
+                named_instance: None,
+                instance_of: None,
+                variation_coords: Vec::new(),
+                variation_axes: Vec::new(),
+                palette: FontPalette::default(),
+                metric_overrides: FontMetricsOverrides::default(),
                 file_path: Some(platform_font.path),
-                data: Some(font_data), // For now, keep data in memory (optimization: lazy load later)
+                data: Some(font_data.into()), // For now, keep data in memory (optimization: lazy load later)
                 is_system_font: platform_font.is_system_font,
             };
 
             // Store in cache
             self.fonts.insert(font_id, font_face);
             self.next_id += 1;
-            loaded_count += 1;
+            self.record_font_identity(font_id, content_hash, name_version);
+            self.index_font(font_id);
+            loaded_count += 1 + self.register_variable_font(font_id);
         }
 
         Ok(loaded_count)
     }
 
-    /// Find best matching font for given descriptor
+    /// Unload a font from the registry
+    ///
+    /// The face is removed from matching and from any font set. Font data is
+    /// reference counted and shared between the named instances of a variable
+    /// font, so it is freed once the last face using it has been unloaded.
     ///
     /// # Arguments
     ///
-    /// * `descriptor` - Font selection criteria
+    /// * `font_id` - ID of the font to unload
     ///
     /// # Returns
     ///
-    /// * `Some(FontId)` - ID of best matching font
-    /// * `None` - If no fonts loaded or no match found
+    /// * `Ok(())` - If the font was unloaded
+    /// * `Err(RegistryError::FontNotFound)` - If no font with this ID is loaded
     ///
     /// # Example
     ///
     /// ```
-    /// use font_registry::{FontRegistry, FontDescriptor};
+    /// use font_registry::{FontRegistry, RegistryError};
     ///
-    /// let registry = FontRegistry::new();
-    /// let descriptor = FontDescriptor::default();
-    /// let result = registry.match_font(&descriptor);
-    /// assert_eq!(result, None); // No fonts loaded
+    /// let mut registry = FontRegistry::new();
+    /// assert_eq!(registry.unload_font(0), Err(RegistryError::FontNotFound(0)));
     /// ```
-    pub fn match_font(&self, descriptor: &FontDescriptor) -> Option<FontId> {
-        if self.fonts.is_empty() {
-            return None;
-        }
-
-        // Font matching algorithm:
-        // 1. Try exact family name match first
-        // 2. Score each font based on weight, style, stretch proximity
-        // 3. Return best match
-
-        let mut best_match: Option<(FontId, i32)> = None;
+    pub fn unload_font(&mut self, font_id: FontId) -> Result<(), RegistryError> {
+        self.fonts
+            .remove(&font_id)
+            .ok_or(RegistryError::FontNotFound(font_id))?;
 
-        for (font_id, font) in &self.fonts {
-            // Check if family matches (case-insensitive)
-            let family_matches = descriptor.family.iter().any(|requested_family| {
-                requested_family.to_lowercase() == font.family_name.to_lowercase()
+        for fonts in self.font_sets.values_mut() {
+            fonts.retain(|id| *id != font_id);
+        }
+        self.content_hashes.retain(|_, id| *id != font_id);
+        self.name_versions.retain(|_, id| *id != font_id);
+        self.partial_fonts.remove(&font_id);
+        self.coverage
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&font_id);
+        self.uncovered_chars.clear();
+        for index in [
+            &mut self.family_index,
+            &mut self.postscript_index,
+            &mut self.full_name_index,
+        ] {
+            index.retain(|_, ids| {
+                ids.retain(|id| *id != font_id);
+                !ids.is_empty()
             });
+        }
+        self.category_index.retain(|_, ids| {
+            ids.retain(|id| *id != font_id);
+            !ids.is_empty()
+        });
+        Ok(())
+    }
 
-            if !family_matches {
-                continue;
-            }
+    /// Set the families substituted for `family` when no loaded font has it
+    ///
+    /// Matching tries the substitutes in order and uses the first one that
+    /// is loaded, e.g. "Arial" → ["Liberation Sans"] or "Helvetica" →
+    /// ["Helvetica Neue", "Arial"]. Names are case-insensitive; an empty
+    /// list removes the substitution.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::FontRegistry;
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_family_substitutes("Arial", vec!["Liberation Sans".to_string()]);
+    /// assert_eq!(registry.family_substitutes("arial"), ["Liberation Sans"]);
+    /// ```
+    pub fn set_family_substitutes(&mut self, family: &str, substitutes: Vec<String>) {
+        if substitutes.is_empty() {
+            self.family_substitutes.remove(&family_key(family));
+        } else {
+            self.family_substitutes
+                .insert(family_key(family), substitutes);
+        }
+    }
 
-            // Calculate match score (lower is better)
-            let mut score = 0;
+    /// Families substituted for `family` when no loaded font has it
+    pub fn family_substitutes(&self, family: &str) -> &[String] {
+        self.family_substitutes
+            .get(&family_key(family))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Limit the installed fonts untrusted callers can match
+    ///
+    /// Under a restrictive policy, matching in [`MatchContext::Untrusted`]
+    /// only considers installed fonts of the allowed families, so web
+    /// content can't probe for fonts the user installed. Fonts registered
+    /// from memory, such as web fonts, are always visible, and privileged
+    /// matching ([`match_font`](Self::match_font) and the other methods
+    /// without a context) sees every font.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::{FontRegistry, FontVisibility};
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_font_visibility(FontVisibility::Standard);
+    /// assert_eq!(registry.font_visibility(), &FontVisibility::Standard);
+    /// ```
+    pub fn set_font_visibility(&mut self, visibility: FontVisibility) {
+        self.visible_families =
+            VisibleFamilies::new(&visibility, self.discovery_config.resolved_platform());
+        self.font_visibility = visibility;
+    }
 
-            // Weight difference (0-800 range)
-            let weight_diff = (descriptor.weight as i32 - font.weight as i32).abs();
-            score += weight_diff;
+    /// Installed fonts untrusted callers can match
+    pub fn font_visibility(&self) -> &FontVisibility {
+        &self.font_visibility
+    }
 
-            // Style mismatch penalty
-            if descriptor.style != font.style {
-                score += 1000; // High penalty for style mismatch
-            }
+    /// Whether a loaded font can be matched in `context`
+    ///
+    /// Returns false for unknown font IDs.
+    pub fn is_font_visible(&self, font_id: FontId, context: MatchContext) -> bool {
+        self.fonts
+            .get(&font_id)
+            .is_some_and(|face| self.visible_families.allows(face, context))
+    }
 
-            // Stretch difference (0-150 range)
-            let stretch_diff = (descriptor.stretch as i32 - font.stretch as i32).abs();
-            score += stretch_diff;
+    /// Loaded fonts of a family, or of its first loaded substitute
+    fn family_candidates(&self, family: &str) -> Option<&Vec<FontId>> {
+        self.family_lookup(family).map(|(_, ids)| ids)
+    }
 
-            // Update best match if this is better
-            if let Some((_, best_score)) = best_match {
-                if score < best_score {
-                    best_match = Some((*font_id, score));
-                }
-            } else {
-                best_match = Some((*font_id, score));
-            }
+    /// Loaded fonts of a family with the substitute they came from, if any
+    ///
+    /// Generic family names without substitutes match the fonts detected
+    /// as that category, e.g. "emoji" matches color emoji fonts.
+    fn family_lookup(&self, family: &str) -> Option<(Option<&str>, &Vec<FontId>)> {
+        let key = family_key(family);
+        if let Some(ids) = self.family_index.get(&key) {
+            return Some((None, ids));
         }
-
-        best_match.map(|(font_id, _)| font_id)
+        self.family_substitutes
+            .get(&key)
+            .and_then(|substitutes| {
+                substitutes.iter().find_map(|substitute| {
+                    let ids = self.family_index.get(&family_key(substitute))?;
+                    Some((Some(substitute.as_str()), ids))
+                })
+            })
+            .or_else(|| {
+                let category = FontCategory::from_generic_family(family)?;
+                Some((None, self.category_index.get(&category)?))
+            })
     }
 
-    /// Get loaded font face by ID
+    /// Loaded fonts detected as `category`, by ascending font ID
     ///
-    /// # Arguments
+    /// # Example
     ///
-    /// * `font_id` - Font identifier
+    /// ```
+    /// use font_registry::{FontCategory, FontRegistry};
     ///
-    /// # Returns
+    /// let registry = FontRegistry::new();
+    /// assert!(registry.fonts_in_category(FontCategory::Emoji).is_empty());
+    /// ```
+    pub fn fonts_in_category(&self, category: FontCategory) -> &[FontId] {
+        self.category_index
+            .get(&category)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Find a loaded font by PostScript name (e.g. "Arial-BoldMT")
     ///
-    /// * `Some(&FontFace)` - Reference to font face
-    /// * `None` - If font ID not found
+    /// Used to resolve CSS `src: local(...)`. Names are compared
+    /// case-insensitively; the earliest loaded face wins.
     ///
     /// # Example
     ///
@@ -413,35 +918,952 @@ impl FontRegistry {
     /// use font_registry::FontRegistry;
     ///
     /// let registry = FontRegistry::new();
-    /// let result = registry.get_font_face(0);
-    /// assert_eq!(result, None); // Font ID 0 not loaded
+    /// assert_eq!(registry.find_by_postscript_name("Arial-BoldMT"), None);
     /// ```
-    pub fn get_font_face(&self, font_id: FontId) -> Option<&FontFace> {
-        self.fonts.get(&font_id)
+    pub fn find_by_postscript_name(&self, name: &str) -> Option<FontId> {
+        self.postscript_index
+            .get(&family_key(name))
+            .and_then(|ids| ids.first().copied())
     }
 
-    /// Get font metrics for given font ID and size
-    ///
-    /// # Arguments
-    ///
-    /// * `font_id` - Font identifier
-    /// * `size` - Font size in pixels
+    /// Find a loaded font by full name (e.g. "Arial Bold")
     ///
-    /// # Returns
+    /// Used to resolve CSS `src: local(...)`. Names are compared
+    /// case-insensitively; the earliest loaded face wins.
+    pub fn find_by_full_name(&self, name: &str) -> Option<FontId> {
+        self.full_name_index
+            .get(&family_key(name))
+            .and_then(|ids| ids.first().copied())
+    }
+
+    /// Add a face to the family, PostScript name and full name indexes
+    fn index_font(&mut self, font_id: FontId) {
+        self.uncovered_chars.clear();
+        let Some(font) = self.fonts.get(&font_id) else {
+            return;
+        };
+        let mut keys: Vec<String> = std::iter::once(&font.family_name)
+            .chain(&font.family_aliases)
+            .map(|name| family_key(name))
+            .collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            self.family_index.entry(key).or_default().push(font_id);
+        }
+        self.postscript_index
+            .entry(family_key(&font.postscript_name))
+            .or_default()
+            .push(font_id);
+        self.full_name_index
+            .entry(family_key(&font.full_name))
+            .or_default()
+            .push(font_id);
+        if let Some(category) = font.category {
+            self.category_index
+                .entry(category)
+                .or_default()
+                .push(font_id);
+        }
+    }
+
+    /// Create an empty scoped font set
     ///
-    /// * `Some(FontMetrics)` - Scaled font metrics
-    /// * `None` - If font ID not found or size invalid
+    /// Font sets group fonts that share a lifetime, such as the web fonts of
+    /// one document, so they can be unloaded together with
+    /// [`FontRegistry::unload_font_set`].
     ///
     /// # Example
     ///
     /// ```
     /// use font_registry::FontRegistry;
     ///
-    /// let registry = FontRegistry::new();
-    /// let result = registry.get_font_metrics(0, 16.0);
-    /// assert_eq!(result, None); // Font ID 0 not loaded
+    /// let mut registry = FontRegistry::new();
+    /// let set = registry.create_font_set();
+    /// assert_eq!(registry.font_set_fonts(set), Some(&[][..]));
     /// ```
-    pub fn get_font_metrics(&self, font_id: FontId, size: f32) -> Option<FontMetrics> {
+    pub fn create_font_set(&mut self) -> FontSetId {
+        let set_id = self.next_set_id;
+        self.font_sets.insert(set_id, Vec::new());
+        self.next_set_id += 1;
+        set_id
+    }
+
+    /// Load font data into a font set
+    ///
+    /// Behaves like [`FontRegistry::load_font_data`]; the font and any named
+    /// instance faces registered for it are added to the set. If the data is a
+    /// duplicate of a font loaded outside any font set, the existing ID is
+    /// returned without adding it to the set, so unloading the set keeps it;
+    /// a duplicate of a font in another set is shared with that set.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(FontId)` - ID of the loaded font
+    /// * `Err(RegistryError)` - If the set does not exist or the data is invalid
+    pub fn load_font_data_into_set(
+        &mut self,
+        set_id: FontSetId,
+        data: Vec<u8>,
+    ) -> Result<FontId, RegistryError> {
+        if !self.font_sets.contains_key(&set_id) {
+            return Err(RegistryError::FontSetNotFound(set_id));
+        }
+
+        let first_new_id = self.next_id;
+        let font_id = self.register_font_data(data)?;
+
+        let loaded: Vec<FontId> = if font_id >= first_new_id {
+            (font_id..self.next_id).collect()
+        } else if self
+            .font_sets
+            .values()
+            .any(|fonts| fonts.contains(&font_id))
+        {
+            // Duplicate of a font owned by another set: share ownership
+            self.face_and_instances(font_id)
+        } else {
+            // Duplicate of a font loaded outside any set: never unload it with this set
+            Vec::new()
+        };
+        if let Some(fonts) = self.font_sets.get_mut(&set_id) {
+            for id in loaded {
+                if !fonts.contains(&id) {
+                    fonts.push(id);
+                }
+            }
+        }
+        Ok(font_id)
+    }
+
+    /// Add an already loaded font to a font set
+    ///
+    /// A font may belong to several sets; it is unloaded together with the
+    /// last set containing it.
+    pub fn add_font_to_set(
+        &mut self,
+        set_id: FontSetId,
+        font_id: FontId,
+    ) -> Result<(), RegistryError> {
+        if !self.fonts.contains_key(&font_id) {
+            return Err(RegistryError::FontNotFound(font_id));
+        }
+        let fonts = self
+            .font_sets
+            .get_mut(&set_id)
+            .ok_or(RegistryError::FontSetNotFound(set_id))?;
+        if !fonts.contains(&font_id) {
+            fonts.push(font_id);
+        }
+        Ok(())
+    }
+
+    /// A face and the named instance faces registered for it
+    fn face_and_instances(&self, font_id: FontId) -> Vec<FontId> {
+        iter::once(font_id)
+            .chain(
+                self.fonts
+                    .values()
+                    .filter(|face| face.instance_of == Some(font_id))
+                    .map(|face| face.id),
+            )
+            .collect()
+    }
+
+    /// Get the fonts currently in a font set
+    pub fn font_set_fonts(&self, set_id: FontSetId) -> Option<&[FontId]> {
+        self.font_sets.get(&set_id).map(Vec::as_slice)
+    }
+
+    /// Unload every font in a font set and remove the set
+    ///
+    /// Fonts that also belong to another set stay loaded.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of fonts unloaded
+    /// * `Err(RegistryError::FontSetNotFound)` - If the set does not exist
+    pub fn unload_font_set(&mut self, set_id: FontSetId) -> Result<usize, RegistryError> {
+        let fonts = self
+            .font_sets
+            .remove(&set_id)
+            .ok_or(RegistryError::FontSetNotFound(set_id))?;
+
+        Ok(fonts
+            .into_iter()
+            .filter(|font_id| {
+                let shared = self.font_sets.values().any(|other| other.contains(font_id));
+                !shared && self.unload_font(*font_id).is_ok()
+            })
+            .count())
+    }
+
+    /// Record the fvar axis ranges of a variable font on its default face and
+    /// register each named instance as its own face
+    ///
+    /// Instance faces share the base face's data and take their names from
+    /// the name table, see [`font_parser::OpenTypeFont::named_instance_names`].
+    /// Their metrics carry the MVAR deltas at the instance's coordinates, and
+    /// their weight, stretch and style are derived from the instance's
+    /// `wght`, `wdth`, `ital` and `slnt` coordinates. Instances at the
+    /// default coordinates are skipped since the base face already
+    /// represents them.
+    ///
+    /// # Returns
+    ///
+    /// Number of instance faces registered
+    fn register_variable_font(&mut self, base_id: FontId) -> usize {
+        let Some(base) = self.fonts.get(&base_id) else {
+            return 0;
+        };
+        let Some(data) = base.data.clone() else {
+            return 0;
+        };
+        let Ok(face) = ttf_parser::Face::parse(&data, 0) else {
+            return 0;
+        };
+        let Some(fvar) = face
+            .raw_face()
+            .table(ttf_parser::Tag::from_bytes(b"fvar"))
+            .and_then(|table| font_parser::FvarTable::parse(table).ok())
+        else {
+            return 0;
+        };
+        let font = font_parser::OpenTypeFont::parse(data.to_vec()).ok();
+
+        let defaults: Vec<f32> = fvar.axes.iter().map(|axis| axis.default_value).collect();
+        let mut instance_faces = Vec::new();
+
+        for instance in &fvar.instances {
+            if instance.coordinates == defaults {
+                continue;
+            }
+
+            let variation_coords: Vec<VariationCoordinate> = fvar
+                .axes
+                .iter()
+                .zip(&instance.coordinates)
+                .map(|(axis, &value)| VariationCoordinate {
+                    tag: axis.tag.to_bytes().to_be_bytes(),
+                    value,
+                })
+                .collect();
+            let coord = |tag: &[u8; 4]| {
+                variation_coords
+                    .iter()
+                    .find(|c| &c.tag == tag)
+                    .map(|c| c.value)
+            };
+
+            let names = font
+                .as_ref()
+                .and_then(|font| font.named_instance_names(instance));
+            let (subfamily, postscript_name) = match names {
+                Some((subfamily, postscript_name)) => (Some(subfamily), postscript_name),
+                None => (
+                    None,
+                    instance
+                        .postscript_name_id
+                        .and_then(|id| face_name(&face, id))
+                        .unwrap_or_else(|| base.family_name.replace(' ', "")),
+                ),
+            };
+
+            let style = if coord(b"ital").is_some_and(|v| v >= 0.5) {
+                FontStyle::Italic
+            } else if let Some(slant) = coord(b"slnt").filter(|v| *v != 0.0) {
+                // slnt is counter-clockwise; CSS oblique angles are clockwise
+                FontStyle::Oblique(-slant)
+            } else if subfamily.as_deref().is_some_and(|s| s.contains("Italic")) {
+                FontStyle::Italic
+            } else {
+                base.style
+            };
+
+            instance_faces.push(FontFace {
+                id: 0,
+                family_name: base.family_name.clone(),
+                family_aliases: base.family_aliases.clone(),
+                full_name: match &subfamily {
+                    Some(subfamily) => format!("{} {}", base.family_name, subfamily),
+                    None => postscript_name.clone(),
+                },
+                postscript_name,
+                weight: coord(b"wght").map_or(base.weight, weight_from_value),
+                style,
+                stretch: coord(b"wdth").map_or(base.stretch, stretch_from_percent),
+                metrics: instance_metrics(&face, &variation_coords).unwrap_or(base.metrics),
+                is_fixed_pitch: base.is_fixed_pitch,
+                fixed_advance: base.fixed_advance,
+                category: base.category,
+                named_instance: subfamily,
+                instance_of: Some(base_id),
+                variation_coords,
+                variation_axes: Vec::new(),
+                palette: base.palette.clone(),
+                metric_overrides: base.metric_overrides,
+                file_path: base.file_path.clone(),
+                data: Some(Arc::clone(&data)),
+                is_system_font: base.is_system_font,
+            });
+        }
+
+        let axes = fvar
+            .axes
+            .iter()
+            .map(|axis| VariationAxisRange {
+                tag: axis.tag.to_bytes().to_be_bytes(),
+                min: axis.min_value,
+                default: axis.default_value,
+                max: axis.max_value,
+            })
+            .collect();
+        if let Some(base) = self.fonts.get_mut(&base_id) {
+            base.variation_axes = axes;
+        }
+
+        let count = instance_faces.len();
+        for mut instance_face in instance_faces {
+            let font_id = self.next_id;
+            instance_face.id = font_id;
+            self.fonts.insert(font_id, instance_face);
+            self.next_id += 1;
+            self.index_font(font_id);
+        }
+        count
+    }
+
+    /// Find best matching font for given descriptor
+    ///
+    /// # Arguments
+    ///
+    /// * `descriptor` - Font selection criteria
+    ///
+    /// # Returns
+    ///
+    /// * `Some(FontId)` - ID of best matching font
+    /// * `None` - If no fonts loaded or no match found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::{FontRegistry, FontDescriptor};
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_last_resort_fallback(false);
+    /// let descriptor = FontDescriptor::default();
+    /// let result = registry.match_font(&descriptor);
+    /// assert_eq!(result, None); // No fonts loaded
+    /// ```
+    pub fn match_font(&self, descriptor: &FontDescriptor) -> Option<FontId> {
+        self.match_font_detailed(descriptor)
+            .map(|matched| matched.font_id)
+    }
+
+    /// Find the best matching font visible in `context`
+    ///
+    /// Like [`match_font`](Self::match_font), but untrusted callers only
+    /// match the fonts the [`font_visibility`](Self::font_visibility)
+    /// policy allows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::{FontDescriptor, FontRegistry, MatchContext};
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_last_resort_fallback(false);
+    /// let descriptor = FontDescriptor::default();
+    /// assert_eq!(registry.match_font_in_context(&descriptor, MatchContext::Untrusted), None);
+    /// ```
+    pub fn match_font_in_context(
+        &self,
+        descriptor: &FontDescriptor,
+        context: MatchContext,
+    ) -> Option<FontId> {
+        self.match_font_detailed_in_context(descriptor, context)
+            .map(|matched| matched.font_id)
+    }
+
+    /// Find best matching font along with instancing coordinates
+    ///
+    /// Variable fonts are matched against their axis ranges: a face whose
+    /// `wght` axis covers the requested weight is an exact match and the
+    /// result carries the coordinates to instance it at. Italic and oblique
+    /// requests use the `ital` and `slnt` axes when present. Bold or italic
+    /// that no candidate provides is reported in [`MatchedFont::synthetic`].
+    ///
+    /// # Arguments
+    ///
+    /// * `descriptor` - Font selection criteria
+    ///
+    /// # Returns
+    ///
+    /// * `Some(MatchedFont)` - Best matching face, coordinates and synthesis flags
+    /// * `None` - If no fonts loaded or no match found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::{FontRegistry, FontDescriptor};
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_last_resort_fallback(false);
+    /// assert_eq!(registry.match_font_detailed(&FontDescriptor::default()), None);
+    /// ```
+    pub fn match_font_detailed(&self, descriptor: &FontDescriptor) -> Option<MatchedFont> {
+        self.match_font_detailed_in_context(descriptor, MatchContext::Privileged)
+    }
+
+    /// Find the best matching face visible in `context`, with its variation
+    /// coordinates and synthesis flags
+    ///
+    /// Like [`match_font_detailed`](Self::match_font_detailed), but
+    /// untrusted callers only match the fonts the
+    /// [`font_visibility`](Self::font_visibility) policy allows. The
+    /// last-resort font is visible in every context.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "font_registry::match_font",
+            level = "debug",
+            skip_all,
+            fields(family = ?descriptor.family, context = ?context)
+        )
+    )]
+    pub fn match_font_detailed_in_context(
+        &self,
+        descriptor: &FontDescriptor,
+        context: MatchContext,
+    ) -> Option<MatchedFont> {
+        self.match_loaded_font(descriptor, context)
+            .or_else(|| self.last_resort_match())
+    }
+
+    /// The last-resort font as a match, if it is registered
+    fn last_resort_match(&self) -> Option<MatchedFont> {
+        self.last_resort_fallback().then(|| MatchedFont {
+            font_id: LAST_RESORT_FONT_ID,
+            variation_coords: Vec::new(),
+            synthetic: SyntheticFlags::default(),
+        })
+    }
+
+    /// Best matching loaded font, without the last-resort fallback
+    fn match_loaded_font(
+        &self,
+        descriptor: &FontDescriptor,
+        context: MatchContext,
+    ) -> Option<MatchedFont> {
+        if self.fonts.is_empty() {
+            return None;
+        }
+
+        // Font matching algorithm:
+        // 1. Look up the requested families in the family index
+        // 2. Instance variable fonts as close to the request as their axes allow
+        // 3. Score each font based on weight, style, stretch proximity
+        // 4. Return best match (lowest ID on ties, for determinism)
+
+        // Scanned straight from the family index: ties compare IDs, so a
+        // face listed under several requested families is harmless
+        let ids = descriptor
+            .family
+            .iter()
+            .filter_map(|family| self.family_candidates(family))
+            .flatten()
+            .copied()
+            .filter(|&id| self.visible_families.allows(&self.fonts[&id], context));
+
+        let mut best_match: Option<(i32, MatchedFont)> = None;
+
+        for font_id in ids {
+            let font = &self.fonts[&font_id];
+
+            let candidate = instance_for_descriptor(font, descriptor);
+
+            // Calculate match score (lower is better)
+            let score = match_penalties(descriptor, &candidate).iter().sum();
+
+            // Update best match if this is better
+            if best_match
+                .as_ref()
+                .is_none_or(|(best_score, best)| (score, font_id) < (*best_score, best.font_id))
+            {
+                let synthetic = synthetic_flags(descriptor, &candidate);
+                best_match = Some((
+                    score,
+                    MatchedFont {
+                        font_id,
+                        variation_coords: candidate.variation_coords,
+                        synthetic,
+                    },
+                ));
+            }
+        }
+
+        best_match.map(|(_, matched)| matched)
+    }
+
+    /// Explain how [`match_font`](Self::match_font) scores the faces of the
+    /// requested families
+    ///
+    /// Lists every candidate with how its family matched, the weight, style
+    /// and stretch it provides and the penalty for each, and the styling
+    /// that would be synthesized, best first. For devtools font panels and
+    /// diagnosing unexpected matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::{FontDescriptor, FontRegistry};
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_last_resort_fallback(false);
+    /// let explanation = registry.explain_match(&FontDescriptor::default());
+    /// assert!(explanation.candidates.is_empty());
+    /// assert_eq!(explanation.unmatched_families, ["sans-serif"]);
+    /// ```
+    pub fn explain_match(&self, descriptor: &FontDescriptor) -> MatchExplanation {
+        let mut candidates: Vec<MatchCandidate> = Vec::new();
+        let mut unmatched_families = Vec::new();
+        for family in &descriptor.family {
+            let Some((substitute, ids)) = self.family_lookup(family) else {
+                unmatched_families.push(family.clone());
+                continue;
+            };
+            for &font_id in ids {
+                if candidates
+                    .iter()
+                    .any(|candidate| candidate.font_id == font_id)
+                {
+                    continue;
+                }
+                let instanced = instance_for_descriptor(&self.fonts[&font_id], descriptor);
+                let [weight_penalty, style_penalty, stretch_penalty] =
+                    match_penalties(descriptor, &instanced);
+                candidates.push(MatchCandidate {
+                    font_id,
+                    family_match: match substitute {
+                        None => FamilyMatch::Family(family.clone()),
+                        Some(substitute) => FamilyMatch::Substitute {
+                            requested: family.clone(),
+                            substitute: substitute.to_string(),
+                        },
+                    },
+                    weight: instanced.weight,
+                    style: instanced.style,
+                    stretch: instanced.stretch,
+                    weight_penalty,
+                    style_penalty,
+                    stretch_penalty,
+                    synthetic: synthetic_flags(descriptor, &instanced),
+                    variation_coords: instanced.variation_coords,
+                });
+            }
+        }
+        candidates.sort_by_key(|candidate| (candidate.score(), candidate.font_id));
+
+        MatchExplanation {
+            selected: self.match_font(descriptor),
+            candidates,
+            unmatched_families,
+        }
+    }
+
+    /// Find best matching font for a descriptor in a given content language
+    ///
+    /// Generic family names in the descriptor ("serif", "sans-serif",
+    /// "monospace", ...) are expanded into the platform's language-specific
+    /// defaults, so Han characters in Japanese, Chinese and Korean text are
+    /// matched to fonts with the right regional glyph shapes. Families are
+    /// tried in fallback-chain order and the first one with a match wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `descriptor` - Font selection criteria
+    /// * `language` - BCP 47 language tag of the content (e.g. "ja", "zh-Hant")
+    ///
+    /// # Returns
+    ///
+    /// * `Some(FontId)` - ID of best matching font
+    /// * `None` - If no family in the expanded fallback chain is loaded
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::{FontRegistry, FontDescriptor};
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_last_resort_fallback(false);
+    /// let descriptor = FontDescriptor::default(); // "sans-serif"
+    /// assert_eq!(registry.match_font_for_language(&descriptor, "ja"), None);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "font_registry::match_font_for_language",
+            level = "debug",
+            skip(self, descriptor)
+        )
+    )]
+    pub fn match_font_for_language(
+        &self,
+        descriptor: &FontDescriptor,
+        language: &str,
+    ) -> Option<FontId> {
+        self.expand_families_for_language(&descriptor.family, language)
+            .into_iter()
+            .find_map(|family| {
+                let single = FontDescriptor {
+                    family: vec![family],
+                    ..descriptor.clone()
+                };
+                self.match_loaded_font(&single, MatchContext::Privileged)
+            })
+            .or_else(|| self.last_resort_match())
+            .map(|matched| matched.font_id)
+    }
+
+    /// Find a font that covers a character
+    ///
+    /// Resolution order, stopping at the first font that has a glyph for `c`:
+    ///
+    /// 1. The descriptor's families, in fallback-chain order
+    /// 2. Any other loaded font with its data in memory (lowest ID first)
+    /// 3. The platform's suggestion for `c` (fontconfig, DirectWrite or
+    ///    CoreText fallback), loaded on demand
+    /// 4. The last-resort font, which renders `c` as a `.notdef` box
+    ///
+    /// A platform suggestion that turns out not to cover `c` is unloaded
+    /// again unless it was already registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `descriptor` - Font selection criteria
+    /// * `c` - Character that needs a glyph
+    ///
+    /// # Returns
+    ///
+    /// * `Some(FontId)` - ID of a font covering `c`, or of the last-resort font
+    /// * `None` - If no font covers `c` and the last-resort fallback is off
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::{DiscoveryConfig, FontDescriptor, FontRegistry};
+    ///
+    /// let mut registry = FontRegistry::new();
+    /// registry.set_discovery_config(DiscoveryConfig::mock());
+    /// registry.set_last_resort_fallback(false);
+    /// assert!(registry.match_font_for_char(&FontDescriptor::default(), 'A').is_some());
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "font_registry::match_font_for_char",
+            level = "debug",
+            skip(self, descriptor)
+        )
+    )]
+    pub fn match_font_for_char(&mut self, descriptor: &FontDescriptor, c: char) -> Option<FontId> {
+        let uncovered = self
+            .uncovered_chars
+            .get(&c)
+            .is_some_and(|descriptors| descriptors.contains(descriptor));
+        if uncovered {
+            return self.last_resort_match().map(|matched| matched.font_id);
+        }
+
+        let from_chain = descriptor.family.iter().find_map(|family| {
+            let single = FontDescriptor {
+                family: vec![family.clone()],
+                ..descriptor.clone()
+            };
+            self.match_loaded_font(&single, MatchContext::Privileged)
+                .map(|matched| matched.font_id)
+                .filter(|&font_id| self.font_covers(font_id, c))
+        });
+        if from_chain.is_some() {
+            return from_chain;
+        }
+
+        let mut ids: Vec<FontId> = self
+            .fonts
+            .keys()
+            .copied()
+            .filter(|&id| id != LAST_RESORT_FONT_ID)
+            .collect();
+        ids.sort();
+        if let Some(font_id) = ids.into_iter().find(|&id| self.font_covers(id, c)) {
+            return Some(font_id);
+        }
+
+        if let Some(font_id) = self.platform_fallback_font(descriptor, c) {
+            return Some(font_id);
+        }
+        self.uncovered_chars
+            .entry(c)
+            .or_default()
+            .push(descriptor.clone());
+        self.last_resort_match().map(|matched| matched.font_id)
+    }
+
+    /// Load the platform's suggested font for `c`, if it covers it
+    fn platform_fallback_font(&mut self, descriptor: &FontDescriptor, c: char) -> Option<FontId> {
+        let suggestion = platform_integration::platform_fallback_font_for_char(
+            self.discovery_config.resolved_platform(),
+            c,
+            descriptor.style,
+        )?;
+
+        // Lazily loaded system fonts are registered by path, not content
+        let registered = self
+            .fonts
+            .values()
+            .find(|font| font.file_path.as_deref() == Some(suggestion.path.as_path()))
+            .map(|font| font.id);
+        let (font_id, newly_loaded) = match registered {
+            Some(font_id) => {
+                self.ensure_font_data_loaded(font_id).ok()?;
+                (font_id, false)
+            }
+            None => {
+                let count = self.fonts.len();
+                let font_id = self.load_font_file(&suggestion.path).ok()?;
+                (font_id, self.fonts.len() > count)
+            }
+        };
+
+        if self.font_covers(font_id, c) {
+            Some(font_id)
+        } else {
+            if newly_loaded {
+                let _ = self.unload_font(font_id);
+            }
+            None
+        }
+    }
+
+    /// Whether a font's data is loaded and maps `c` to a glyph
+    ///
+    /// Lazily registered system fonts don't cover anything until
+    /// [`ensure_font_data_loaded`](Self::ensure_font_data_loaded) loads them.
+    /// The characters a font maps are read from its cmap once, on the first
+    /// check.
+    pub fn font_covers(&self, font_id: FontId, c: char) -> bool {
+        let Some(data) = self.fonts.get(&font_id).and_then(FontFace::data) else {
+            return false;
+        };
+        self.coverage
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(font_id)
+            .or_insert_with(|| CharCoverage::new(data))
+            .contains(c)
+    }
+
+    /// Expand generic family names into language-specific platform defaults
+    fn expand_families_for_language(&self, families: &[String], language: &str) -> Vec<String> {
+        let mut expanded = Vec::new();
+        let mut language_defaults = self
+            .language_defaults
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for family in families {
+            if let Some(category) = FontCategory::from_generic_family(family) {
+                // Asking the platform (fontconfig on Linux) is slow, so each
+                // category and language is only resolved once
+                let defaults = language_defaults
+                    .entry((category, language.to_string()))
+                    .or_insert_with(|| {
+                        platform_integration::get_platform_default_font_families_for_language(
+                            self.discovery_config.resolved_platform(),
+                            category,
+                            language,
+                        )
+                    });
+                expanded.extend(defaults.iter().cloned());
+            }
+            // Generic names stay too, matching fonts detected in the category
+            expanded.push(family.clone());
+        }
+        expanded
+    }
+
+    /// Get loaded font face by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `font_id` - Font identifier
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&FontFace)` - Reference to font face
+    /// * `None` - If font ID not found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::FontRegistry;
+    ///
+    /// let registry = FontRegistry::new();
+    /// let result = registry.get_font_face(0);
+    /// assert_eq!(result, None); // Font ID 0 not loaded
+    /// ```
+    pub fn get_font_face(&self, font_id: FontId) -> Option<&FontFace> {
+        self.fonts.get(&font_id)
+    }
+
+    /// Set the color palette used for a face's color glyphs
+    ///
+    /// Applies a CSS `font-palette`, including the overrides of an
+    /// `@font-palette-values` rule, to one face; other faces of the family
+    /// keep their own palette. Named instances created afterwards inherit
+    /// their default face's palette.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistryError::FontNotFound`] if no face has this ID.
+    pub fn set_font_palette(
+        &mut self,
+        font_id: FontId,
+        palette: FontPalette,
+    ) -> Result<(), RegistryError> {
+        let face = self
+            .fonts
+            .get_mut(&font_id)
+            .ok_or(RegistryError::FontNotFound(font_id))?;
+        face.palette = palette;
+        Ok(())
+    }
+
+    /// Set the metric overrides of a web font face (`size-adjust`,
+    /// `ascent-override`, `descent-override` and `line-gap-override`)
+    ///
+    /// [`get_font_metrics`](Self::get_font_metrics) and shaping with the
+    /// face use the overridden metrics, so a fallback face can stand in
+    /// for a web font that hasn't loaded yet. Named instances created
+    /// afterwards inherit their default face's overrides.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistryError::FontNotFound`] if no face has this ID.
+    pub fn set_metric_overrides(
+        &mut self,
+        font_id: FontId,
+        overrides: FontMetricsOverrides,
+    ) -> Result<(), RegistryError> {
+        let face = self
+            .fonts
+            .get_mut(&font_id)
+            .ok_or(RegistryError::FontNotFound(font_id))?;
+        face.metric_overrides = overrides;
+        Ok(())
+    }
+
+    /// Enumerate all loaded faces, ordered by font ID
+    ///
+    /// Named instances of variable fonts are listed as faces of their own,
+    /// with [`FontFace::named_instance`] holding the instance's name.
+    pub fn font_faces(&self) -> Vec<&FontFace> {
+        let mut faces: Vec<&FontFace> = self
+            .fonts
+            .values()
+            .filter(|face| face.id != LAST_RESORT_FONT_ID)
+            .collect();
+        faces.sort_by_key(|face| face.id);
+        faces
+    }
+
+    /// Enumerate the named instance faces of a variable font, ordered by font ID
+    ///
+    /// # Arguments
+    ///
+    /// * `font_id` - ID of the variable font's default face
+    ///
+    /// # Returns
+    ///
+    /// Faces registered from the font's fvar named instances, with their
+    /// display names, PostScript names and coordinates; empty for static
+    /// fonts and unknown IDs
+    pub fn named_instances(&self, font_id: FontId) -> Vec<&FontFace> {
+        self.font_faces()
+            .into_iter()
+            .filter(|face| face.instance_of == Some(font_id))
+            .collect()
+    }
+
+    /// Reload font data released by [`MemoryTrimmer::trim_memory`]
+    ///
+    /// Fonts discovered on disk can drop their data under memory pressure;
+    /// call this before reading [`FontFace::data`] again. Faces sharing the
+    /// file (named instances) share the reloaded data. Does nothing when the
+    /// data is already loaded.
+    ///
+    /// # Errors
+    ///
+    /// * `RegistryError::FontNotFound` - No font with this ID is loaded
+    /// * `RegistryError::FileNotFound` - The font file can no longer be read
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "font_registry::ensure_font_data_loaded",
+            level = "debug",
+            skip(self)
+        )
+    )]
+    pub fn ensure_font_data_loaded(&mut self, font_id: FontId) -> Result<(), RegistryError> {
+        let face = self
+            .fonts
+            .get(&font_id)
+            .ok_or(RegistryError::FontNotFound(font_id))?;
+        if face.data.is_some() {
+            return Ok(());
+        }
+        // Only faces backed by a file release their data
+        let Some(path) = face.file_path.clone() else {
+            return Err(RegistryError::InvalidFont(
+                "Font data released without a file to reload from".to_string(),
+            ));
+        };
+
+        let data = std::fs::read(&path).map_err(|e| RegistryError::read_failed(&path, e))?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(path = %path.display(), bytes = data.len(), "reloaded font data");
+        let data = FontData::from(data);
+        for face in self.fonts.values_mut() {
+            if face.data.is_none() && face.file_path.as_ref() == Some(&path) {
+                face.data = Some(Arc::clone(&data));
+            }
+        }
+        self.uncovered_chars.clear();
+        Ok(())
+    }
+
+    /// Get font metrics for given font ID and size
+    ///
+    /// # Arguments
+    ///
+    /// * `font_id` - Font identifier
+    /// * `size` - Font size in pixels
+    ///
+    /// # Returns
+    ///
+    /// * `Some(FontMetrics)` - Scaled font metrics, with the face's
+    ///   [metric overrides](Self::set_metric_overrides) applied
+    /// * `None` - If font ID not found or size invalid
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use font_registry::FontRegistry;
+    ///
+    /// let registry = FontRegistry::new();
+    /// let result = registry.get_font_metrics(0, 16.0);
+    /// assert_eq!(result, None); // Font ID 0 not loaded
+    /// ```
+    pub fn get_font_metrics(&self, font_id: FontId, size: f32) -> Option<FontMetrics> {
         // Validate size
         if size <= 0.0 {
             return None;
@@ -449,19 +1871,586 @@ impl FontRegistry {
 
         let font = self.fonts.get(&font_id)?;
 
-        // Scale metrics from font units to pixel size
-        let scale = size / font.metrics.units_per_em as f32;
-
-        Some(FontMetrics {
-            units_per_em: font.metrics.units_per_em,
-            ascent: font.metrics.ascent * scale,
-            descent: font.metrics.descent * scale,
-            line_gap: font.metrics.line_gap * scale,
-            cap_height: font.metrics.cap_height * scale,
-            x_height: font.metrics.x_height * scale,
-            underline_position: font.metrics.underline_position * scale,
-            underline_thickness: font.metrics.underline_thickness * scale,
+        // Scale metrics from font units to pixel size, then override them
+        Some(font.metric_overrides.apply(&font.metrics, size))
+    }
+
+    /// Get font metrics for a matched face at its variation coordinates
+    ///
+    /// Static faces and matches at the face's own coordinates return the
+    /// same values as [`get_font_metrics`](Self::get_font_metrics). For
+    /// variable faces, MVAR deltas are applied at the match's coordinates;
+    /// faces without loaded data fall back to their stored metrics.
+    ///
+    /// # Arguments
+    ///
+    /// * `matched` - Result of [`match_font_detailed`](Self::match_font_detailed)
+    /// * `size` - Font size in pixels
+    pub fn get_matched_font_metrics(
+        &self,
+        matched: &MatchedFont,
+        size: f32,
+    ) -> Option<FontMetrics> {
+        if size <= 0.0 {
+            return None;
+        }
+
+        let font = self.fonts.get(&matched.font_id)?;
+        let metrics = if matched.variation_coords == font.variation_coords {
+            font.metrics
+        } else {
+            font.data
+                .as_ref()
+                .and_then(|data| ttf_parser::Face::parse(data, 0).ok())
+                .and_then(|face| instance_metrics(&face, &matched.variation_coords))
+                .unwrap_or(font.metrics)
+        };
+
+        Some(font.metric_overrides.apply(&metrics, size))
+    }
+}
+
+/// Characters a font's Unicode cmap subtables map to a glyph other than
+/// `.notdef`, as sorted, disjoint inclusive ranges
+#[derive(Debug)]
+struct CharCoverage(Vec<(u32, u32)>);
+
+impl CharCoverage {
+    fn new(data: &[u8]) -> Self {
+        let mut chars = Vec::new();
+        let cmap = ttf_parser::Face::parse(data, 0)
+            .ok()
+            .and_then(|face| face.tables().cmap);
+        for subtable in cmap.iter().flat_map(|cmap| cmap.subtables) {
+            if subtable.is_unicode() {
+                subtable.codepoints(|c| {
+                    if subtable.glyph_index(c).is_some_and(|glyph| glyph.0 != 0) {
+                        chars.push(c);
+                    }
+                });
+            }
+        }
+        chars.sort_unstable();
+        chars.dedup();
+
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        for c in chars {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == c => *end = c,
+                _ => ranges.push((c, c)),
+            }
+        }
+        Self(ranges)
+    }
+
+    fn contains(&self, c: char) -> bool {
+        let c = u32::from(c);
+        let index = self.0.partition_point(|&(_, end)| end < c);
+        self.0.get(index).is_some_and(|&(start, _)| start <= c)
+    }
+}
+
+/// Weight, style and stretch a face provides once instanced for a request
+struct InstancedFace {
+    weight: f32,
+    style: FontStyle,
+    stretch: f32,
+    variation_coords: Vec<VariationCoordinate>,
+}
+
+/// Default slant used when an italic is requested from a `slnt`-only font
+const DEFAULT_OBLIQUE_ANGLE: f32 = 14.0;
+
+/// Instance a face as close to the descriptor as its variation axes allow
+///
+/// Static faces and named instances are returned unchanged.
+fn instance_for_descriptor(font: &FontFace, descriptor: &FontDescriptor) -> InstancedFace {
+    let mut instanced = InstancedFace {
+        weight: font.weight.value() as f32,
+        style: font.style,
+        stretch: font.stretch as i32 as f32,
+        variation_coords: font.variation_coords.clone(),
+    };
+
+    for axis in &font.variation_axes {
+        let value = match &axis.tag {
+            b"wght" => {
+                let value = axis.clamp(descriptor.weight.value() as f32);
+                instanced.weight = value;
+                value
+            }
+            b"wdth" => {
+                let value = axis.clamp(descriptor.stretch as i32 as f32);
+                instanced.stretch = value;
+                value
+            }
+            b"ital" if descriptor.style == FontStyle::Italic && axis.max >= 1.0 => {
+                instanced.style = FontStyle::Italic;
+                1.0
+            }
+            b"slnt"
+                if descriptor.style != FontStyle::Normal
+                    && instanced.style != FontStyle::Italic =>
+            {
+                let angle = match descriptor.style {
+                    FontStyle::Oblique(angle) => angle,
+                    _ => DEFAULT_OBLIQUE_ANGLE,
+                };
+                // slnt is counter-clockwise; CSS oblique angles are clockwise
+                let value = axis.clamp(-angle);
+                if value != 0.0 {
+                    instanced.style = FontStyle::Oblique(-value);
+                }
+                value
+            }
+            _ => continue,
+        };
+        instanced.variation_coords.push(VariationCoordinate {
+            tag: axis.tag,
+            value,
+        });
+    }
+
+    instanced
+}
+
+/// Weight, style and stretch penalties of an instanced face; their sum is
+/// its match score
+fn match_penalties(descriptor: &FontDescriptor, face: &InstancedFace) -> [i32; 3] {
+    [
+        // Weight distance, preferring the CSS search direction
+        weight_penalty(descriptor.weight, face.weight),
+        // Style mismatch penalty (high for upright vs slanted)
+        style_penalty(descriptor.style, face.style),
+        // Stretch difference (0-150 range)
+        (descriptor.stretch as i32 as f32 - face.stretch)
+            .abs()
+            .round() as i32,
+    ]
+}
+
+/// Bold or italic styling an instanced face lacks for the descriptor, as
+/// far as the descriptor allows synthesizing it
+fn synthetic_flags(descriptor: &FontDescriptor, face: &InstancedFace) -> SyntheticFlags {
+    SyntheticFlags {
+        bold: descriptor.synthesis.weight
+            && descriptor.weight.is_bold()
+            && face.weight < FontWeight::SEMI_BOLD.value() as f32,
+        italic: descriptor.synthesis.style
+            && descriptor.style != FontStyle::Normal
+            && face.style == FontStyle::Normal,
+    }
+}
+
+/// Score penalty for the style a face provides against the requested style
+///
+/// Follows the CSS font-style fallback order: italic requests prefer true
+/// italics, then obliques closest to the default angle; oblique requests
+/// prefer the closest angle, then italics; normal requests fall back to
+/// obliques before italics. An upright face for a slanted request is a
+/// full mismatch.
+fn style_penalty(requested: FontStyle, provided: FontStyle) -> i32 {
+    let angle_distance =
+        |a: f32, b: f32| (a - b).abs().round().min(MAX_OBLIQUE_ANGLE_PENALTY) as i32;
+    match (requested, provided) {
+        (FontStyle::Normal, FontStyle::Normal) | (FontStyle::Italic, FontStyle::Italic) => 0,
+        (FontStyle::Oblique(a), FontStyle::Oblique(b)) => angle_distance(a, b),
+        (FontStyle::Italic, FontStyle::Oblique(b)) => {
+            500 + angle_distance(DEFAULT_OBLIQUE_ANGLE, b)
+        }
+        (FontStyle::Oblique(_), FontStyle::Italic) => 500,
+        (FontStyle::Normal, FontStyle::Oblique(_)) => 1000,
+        (FontStyle::Normal, FontStyle::Italic) => 1100,
+        (_, FontStyle::Normal) => 1000,
+    }
+}
+
+/// Largest penalty for an oblique face whose angle differs from the request
+const MAX_OBLIQUE_ANGLE_PENALTY: f32 = 90.0;
+
+/// Score penalty for the weight a face provides against the requested weight
+///
+/// Follows the CSS font matching order: requests between 400 and 500 look
+/// upward to 500 first, then lighter, then heavier; lighter requests prefer
+/// lighter faces and heavier requests prefer heavier faces. The offsets keep
+/// every preferred candidate ahead of every non-preferred one.
+fn weight_penalty(requested: FontWeight, provided: f32) -> i32 {
+    let requested = requested.value() as f32;
+    let distance = (requested - provided).abs();
+    let offset = if (400.0..=500.0).contains(&requested) {
+        if provided >= requested && provided <= 500.0 {
+            0.0
+        } else if provided < requested {
+            100.0
+        } else {
+            600.0
+        }
+    } else if requested < 400.0 {
+        if provided <= requested {
+            0.0
+        } else {
+            400.0
+        }
+    } else if provided >= requested {
+        0.0
+    } else {
+        500.0
+    };
+    (distance + offset).round() as i32
+}
+
+/// Extract font metrics from a parsed face
+///
+/// `ascent`/`descent`/`line_gap` use ttf-parser's selection, which honors
+/// USE_TYPO_METRICS and falls back to typo then win metrics when hhea is
+/// zeroed.
+fn face_metrics(face: &ttf_parser::Face) -> FontMetrics {
+    let tables = face.tables();
+    let os2 = tables.os2;
+    let raw_os2 = face.raw_face().table(ttf_parser::Tag::from_bytes(b"OS/2"));
+    let raw_hhea = face.raw_face().table(ttf_parser::Tag::from_bytes(b"hhea"));
+    let read_i16 = |data: Option<&[u8]>, offset: usize| {
+        data.and_then(|d| d.get(offset..offset + 2))
+            .map(|b| i16::from_be_bytes([b[0], b[1]]))
+    };
+
+    let avg_char_width = read_i16(raw_os2, 2)
+        .filter(|width| *width > 0)
+        .map(f32::from)
+        .or_else(|| {
+            face.glyph_index('x')
+                .and_then(|glyph| face.glyph_hor_advance(glyph))
+                .map(f32::from)
         })
+        .unwrap_or(face.units_per_em() as f32 / 2.0);
+    let max_advance = read_i16(raw_hhea, 10)
+        .map(|v| v as u16 as f32)
+        .unwrap_or(face.units_per_em() as f32);
+
+    FontMetrics {
+        units_per_em: face.units_per_em(),
+        ascent: face.ascender() as f32,
+        descent: face.descender() as f32,
+        line_gap: face.line_gap() as f32,
+        hhea_ascent: tables.hhea.ascender as f32,
+        hhea_descent: tables.hhea.descender as f32,
+        hhea_line_gap: tables.hhea.line_gap as f32,
+        typo_ascent: os2.map_or(0.0, |t| t.typographic_ascender() as f32),
+        typo_descent: os2.map_or(0.0, |t| t.typographic_descender() as f32),
+        typo_line_gap: os2.map_or(0.0, |t| t.typographic_line_gap() as f32),
+        win_ascent: os2.map_or(0.0, |t| t.windows_ascender() as f32),
+        win_descent: os2.map_or(0.0, |t| t.windows_descender() as f32),
+        use_typo_metrics: os2.is_some_and(|t| t.use_typographic_metrics()),
+        avg_char_width,
+        max_advance,
+        // Default values for metrics not directly available
+        cap_height: face.capital_height().unwrap_or(700) as f32,
+        x_height: face.x_height().unwrap_or(500) as f32,
+        underline_position: face
+            .underline_metrics()
+            .map(|m| m.position as f32)
+            .unwrap_or(-150.0),
+        underline_thickness: face
+            .underline_metrics()
+            .map(|m| m.thickness as f32)
+            .unwrap_or(50.0),
+    }
+}
+
+/// Extract font metrics of a variable font instance
+///
+/// Applies the MVAR deltas at `coords` (user-space axis values; missing
+/// axes take their defaults) to the default instance's metrics.
+///
+/// # Returns
+///
+/// `None` when the face has no fvar or MVAR table
+fn instance_metrics(
+    face: &ttf_parser::Face,
+    coords: &[VariationCoordinate],
+) -> Option<FontMetrics> {
+    let table = |tag: &[u8; 4]| face.raw_face().table(ttf_parser::Tag::from_bytes(tag));
+    let fvar = table(b"fvar").and_then(|t| font_parser::FvarTable::parse(t).ok())?;
+    let mvar = table(b"MVAR").and_then(|t| font_parser::MvarTable::parse(t).ok())?;
+    let avar = table(b"avar").and_then(|t| font_parser::AvarTable::parse(t, fvar.axes.len()).ok());
+
+    let mut user_coords = font_parser::VariationCoordinates::new();
+    for coord in coords {
+        user_coords.set_axis(
+            font_parser::Tag::from_bytes(u32::from_be_bytes(coord.tag)),
+            coord.value,
+        );
+    }
+    let normalized = fvar.normalize_coordinates(&user_coords, avar.as_ref());
+    let delta = |tag: &[u8; 4]| {
+        mvar.delta(
+            font_parser::Tag::from_bytes(u32::from_be_bytes(*tag)),
+            &normalized,
+        )
+        .round()
+    };
+
+    let mut metrics = face_metrics(face);
+    let (ascent, descent, line_gap) = (delta(b"hasc"), delta(b"hdsc"), delta(b"hlgp"));
+    metrics.ascent += ascent;
+    metrics.descent += descent;
+    metrics.line_gap += line_gap;
+    metrics.hhea_ascent += ascent;
+    metrics.hhea_descent += descent;
+    metrics.hhea_line_gap += line_gap;
+    metrics.typo_ascent += ascent;
+    metrics.typo_descent += descent;
+    metrics.typo_line_gap += line_gap;
+    metrics.win_ascent += delta(b"hcla");
+    // win_descent is stored negated, while hcld varies the positive usWinDescent
+    metrics.win_descent -= delta(b"hcld");
+    metrics.cap_height += delta(b"cpht");
+    metrics.x_height += delta(b"xhgt");
+    metrics.underline_position += delta(b"undo");
+    metrics.underline_thickness += delta(b"unds");
+    Some(metrics)
+}
+
+/// Face of the embedded last-resort font
+#[cfg(feature = "last-resort-font")]
+fn last_resort_face() -> FontFace {
+    let face = ttf_parser::Face::parse(LAST_RESORT_FONT, 0).expect("embedded font parses");
+    FontFace {
+        id: LAST_RESORT_FONT_ID,
+        family_name: face_name(&face, ttf_parser::name_id::FAMILY).unwrap_or_default(),
+        family_aliases: Vec::new(),
+        postscript_name: face_name(&face, ttf_parser::name_id::POST_SCRIPT_NAME)
+            .unwrap_or_default(),
+        full_name: face_name(&face, ttf_parser::name_id::FULL_NAME).unwrap_or_default(),
+        weight: face_weight(&face),
+        style: face_style(&face),
+        stretch: face_stretch(&face),
+        metrics: face_metrics(&face),
+        is_fixed_pitch: face.is_monospaced(),
+        fixed_advance: face_fixed_advance(&face),
+        category: None,
+        named_instance: None,
+        instance_of: None,
+        variation_coords: Vec::new(),
+        variation_axes: Vec::new(),
+        palette: FontPalette::default(),
+        metric_overrides: FontMetricsOverrides::default(),
+        file_path: None,
+        data: Some(LAST_RESORT_FONT.into()),
+        is_system_font: false,
+    }
+}
+
+/// Hash font bytes for duplicate detection
+fn hash_font_data(data: &[u8]) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Face metadata of a font loaded from memory, without its data
+fn face_from_parsed(font_id: FontId, face: &ttf_parser::Face) -> FontFace {
+    let family_name =
+        face_name(face, ttf_parser::name_id::FAMILY).unwrap_or_else(|| "Unknown".to_string());
+
+    let postscript_name = face_name(face, ttf_parser::name_id::POST_SCRIPT_NAME)
+        .unwrap_or_else(|| family_name.clone());
+    let full_name =
+        face_name(face, ttf_parser::name_id::FULL_NAME).unwrap_or_else(|| family_name.clone());
+
+    let weight = face_weight(face);
+    let style = face_style(face);
+    let stretch = face_stretch(face);
+
+    let metrics = face_metrics(face);
+    let family_aliases = family_aliases(&family_name, face_family_names(face));
+
+    FontFace {
+        id: font_id,
+        family_name,
+        family_aliases,
+        postscript_name,
+        full_name,
+        weight,
+        style,
+        stretch,
+        metrics,
+        is_fixed_pitch: face.is_monospaced(),
+        fixed_advance: face_fixed_advance(face),
+        category: face_category(face),
+        named_instance: None,
+        instance_of: None,
+        variation_coords: Vec::new(),
+        variation_axes: Vec::new(),
+        palette: FontPalette::default(),
+        metric_overrides: FontMetricsOverrides::default(),
+        file_path: None,
+        data: None,
+        is_system_font: false,
+    }
+}
+
+/// PostScript name and version string identifying a font release
+fn face_name_version(face: &ttf_parser::Face) -> Option<(String, String)> {
+    Some((
+        face_name(face, ttf_parser::name_id::POST_SCRIPT_NAME)?,
+        face_name(face, ttf_parser::name_id::VERSION)?,
+    ))
+}
+
+/// Resolve a name table entry to a string, skipping undecodable records
+fn face_name(face: &ttf_parser::Face, name_id: u16) -> Option<String> {
+    face.names()
+        .into_iter()
+        .filter(|name| name.name_id == name_id)
+        .find_map(|name| name.to_string())
+}
+
+/// Every family name of a face in the name table, in all languages
+///
+/// Includes typographic family names, so a face is found by its localized
+/// names (e.g. a Japanese font by its Japanese name).
+fn face_family_names(face: &ttf_parser::Face) -> Vec<String> {
+    face.names()
+        .into_iter()
+        .filter(|name| {
+            name.name_id == ttf_parser::name_id::FAMILY
+                || name.name_id == ttf_parser::name_id::TYPOGRAPHIC_FAMILY
+        })
+        .filter_map(|name| name.to_string())
+        .collect()
+}
+
+/// Family names other than `family_name`, without duplicates
+fn family_aliases(family_name: &str, names: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::from([family_key(family_name)]);
+    names
+        .into_iter()
+        .filter(|name| seen.insert(family_key(name)))
+        .collect()
+}
+
+/// Normalized name used as a family or face name index key
+pub(crate) fn family_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Map a `wght` axis value to a FontWeight
+fn weight_from_value(value: f32) -> FontWeight {
+    FontWeight::from_f32(value)
+}
+
+/// Map a `wdth` axis value (percent of normal width) to the nearest FontStretch
+fn stretch_from_percent(percent: f32) -> FontStretch {
+    [
+        FontStretch::UltraCondensed,
+        FontStretch::ExtraCondensed,
+        FontStretch::Condensed,
+        FontStretch::SemiCondensed,
+        FontStretch::Normal,
+        FontStretch::SemiExpanded,
+        FontStretch::Expanded,
+        FontStretch::ExtraExpanded,
+        FontStretch::UltraExpanded,
+    ]
+    .into_iter()
+    .min_by(|a, b| {
+        let da = (*a as u16 as f32 - percent).abs();
+        let db = (*b as u16 as f32 - percent).abs();
+        da.total_cmp(&db)
+    })
+    .unwrap_or(FontStretch::Normal)
+}
+
+/// Advance shared by all spacing glyphs of a face, in font units
+///
+/// Zero-width glyphs (combining marks) are ignored, and glyphs twice as wide
+/// are allowed for the double-width characters of CJK monospaced fonts.
+fn face_fixed_advance(face: &ttf_parser::Face) -> Option<f32> {
+    let advances: Vec<u16> = (0..face.number_of_glyphs())
+        .filter_map(|glyph| face.glyph_hor_advance(ttf_parser::GlyphId(glyph)))
+        .filter(|&advance| advance > 0)
+        .collect();
+    let cell = *advances.iter().min()?;
+    advances
+        .iter()
+        .all(|&advance| advance == cell || u32::from(advance) == 2 * u32::from(cell))
+        .then_some(f32::from(cell))
+}
+
+/// Code points an emoji font is expected to cover
+const EMOJI_SAMPLE: [char; 6] = ['😀', '👍', '❤', '🎉', '🚀', '🍎'];
+
+/// Detect the generic category of a face
+///
+/// A face is an emoji font if it has color glyph tables (COLR, CBDT, sbix or
+/// SVG) and covers most of [`EMOJI_SAMPLE`]; text fonts that merely include
+/// a few color symbols are not.
+fn face_category(face: &ttf_parser::Face) -> Option<FontCategory> {
+    let has_color_glyphs = [b"COLR", b"CBDT", b"sbix", b"SVG "].iter().any(|tag| {
+        face.raw_face()
+            .table(ttf_parser::Tag::from_bytes(tag))
+            .is_some()
+    });
+    let covered = EMOJI_SAMPLE
+        .iter()
+        .filter(|&&c| face.glyph_index(c).is_some())
+        .count();
+    (has_color_glyphs && covered * 2 > EMOJI_SAMPLE.len()).then_some(FontCategory::Emoji)
+}
+
+/// Map the OS/2 weight class of a face to a FontWeight
+fn face_weight(face: &ttf_parser::Face) -> FontWeight {
+    FontWeight::new(face.weight().to_number())
+}
+
+/// Map the OS/2 width class of a face to a FontStretch
+///
+/// Faces without an OS/2 table or with an out-of-range class are Normal.
+fn face_stretch(face: &ttf_parser::Face) -> FontStretch {
+    match face.width() {
+        ttf_parser::Width::UltraCondensed => FontStretch::UltraCondensed,
+        ttf_parser::Width::ExtraCondensed => FontStretch::ExtraCondensed,
+        ttf_parser::Width::Condensed => FontStretch::Condensed,
+        ttf_parser::Width::SemiCondensed => FontStretch::SemiCondensed,
+        ttf_parser::Width::Normal => FontStretch::Normal,
+        ttf_parser::Width::SemiExpanded => FontStretch::SemiExpanded,
+        ttf_parser::Width::Expanded => FontStretch::Expanded,
+        ttf_parser::Width::ExtraExpanded => FontStretch::ExtraExpanded,
+        ttf_parser::Width::UltraExpanded => FontStretch::UltraExpanded,
+    }
+}
+
+/// Map the OS/2 fsSelection flags and post italic angle of a face to a FontStyle
+///
+/// The OBLIQUE bit (OS/2 version 4+) wins over ITALIC, which oblique faces
+/// often set too for compatibility. Faces slanted in `post` without either
+/// bit are oblique as well. Oblique angles are clockwise as in CSS, while
+/// the post table measures counter-clockwise.
+fn face_style(face: &ttf_parser::Face) -> FontStyle {
+    let os2 = face.raw_face().table(ttf_parser::Tag::from_bytes(b"OS/2"));
+    let read_u16 = |offset: usize| {
+        os2.and_then(|d| d.get(offset..offset + 2))
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    };
+    let oblique_bit = read_u16(0).is_some_and(|version| version >= 4)
+        && read_u16(62).is_some_and(|fs_selection| fs_selection & (1 << 9) != 0);
+    let angle = -face.italic_angle().unwrap_or(0.0);
+
+    if oblique_bit {
+        FontStyle::Oblique(if angle != 0.0 {
+            angle
+        } else {
+            DEFAULT_OBLIQUE_ANGLE
+        })
+    } else if face.is_italic() {
+        FontStyle::Italic
+    } else if angle != 0.0 {
+        FontStyle::Oblique(angle)
+    } else {
+        FontStyle::Normal
     }
 }
 
@@ -471,6 +2460,66 @@ impl Default for FontRegistry {
     }
 }
 
+impl MemoryReporter for FontRegistry {
+    /// Loaded font data, counting data shared between named instances once
+    ///
+    /// Lazily loaded system fonts count only once their data is loaded.
+    fn memory_report(&self) -> MemoryReport {
+        let mut seen = HashSet::new();
+        // The embedded last-resort font is not loaded data
+        let bytes = self
+            .fonts
+            .values()
+            .filter(|face| face.id != LAST_RESORT_FONT_ID)
+            .filter_map(|face| face.data.as_ref())
+            .filter(|data| seen.insert(Arc::as_ptr(data)))
+            .map(|data| data.len())
+            .sum();
+        MemoryReport {
+            bytes,
+            entries: self.font_count(),
+        }
+    }
+}
+
+impl MemoryTrimmer for FontRegistry {
+    /// Release the data of fonts that can be reloaded from their file
+    ///
+    /// The share of files released follows `level`, most recently loaded
+    /// first. Faces stay registered for matching and metrics; reload their
+    /// data with [`FontRegistry::ensure_font_data_loaded`]. Fonts loaded from
+    /// memory are never released.
+    fn trim_memory(&mut self, level: MemoryPressureLevel) -> usize {
+        let mut files: Vec<PathBuf> = Vec::new();
+        let mut ids: Vec<&FontId> = self.fonts.keys().collect();
+        ids.sort();
+        for id in ids {
+            let face = &self.fonts[id];
+            if let (Some(path), Some(_)) = (&face.file_path, &face.data) {
+                if !files.contains(path) {
+                    files.push(path.clone());
+                }
+            }
+        }
+
+        let keep = level.retained(files.len());
+        let mut freed = 0;
+        for path in files.drain(keep..) {
+            let mut released = None;
+            for face in self.fonts.values_mut() {
+                if face.file_path.as_ref() == Some(&path) {
+                    released = face.data.take().or(released);
+                }
+            }
+            // Data still referenced elsewhere is not freed
+            if let Some(data) = released.filter(|data| Arc::strong_count(data) == 1) {
+                freed += data.len();
+            }
+        }
+        freed
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -483,8 +2532,85 @@ mod tests {
 
     #[test]
     fn test_match_font_returns_none_when_empty() {
-        let registry = FontRegistry::new();
+        let mut registry = FontRegistry::new();
+        registry.set_last_resort_fallback(false);
+        let descriptor = FontDescriptor::default();
+        assert_eq!(registry.match_font(&descriptor), None);
+    }
+
+    #[cfg(feature = "last-resort-font")]
+    #[test]
+    fn test_last_resort_font_matches_when_nothing_else_does() {
+        let mut registry = FontRegistry::new();
         let descriptor = FontDescriptor::default();
+        assert_eq!(registry.match_font(&descriptor), Some(LAST_RESORT_FONT_ID));
+        assert_eq!(
+            registry.match_font_for_language(&descriptor, "ja"),
+            Some(LAST_RESORT_FONT_ID)
+        );
+        assert_eq!(registry.font_count(), 0);
+        assert!(registry.font_faces().is_empty());
+
+        let face = registry.get_font_face(LAST_RESORT_FONT_ID).unwrap();
+        assert_eq!(face.family_name, "Corten Last Resort");
+        let parsed = ttf_parser::Face::parse(face.data().unwrap(), 0).unwrap();
+        assert_eq!(parsed.number_of_glyphs(), 1);
+        assert_eq!(parsed.glyph_hor_advance(ttf_parser::GlyphId(0)), Some(600));
+
+        registry.set_last_resort_fallback(false);
         assert_eq!(registry.match_font(&descriptor), None);
+        assert!(registry.get_font_face(LAST_RESORT_FONT_ID).is_none());
+    }
+
+    #[cfg(feature = "last-resort-font")]
+    #[test]
+    fn test_loaded_fonts_win_over_last_resort_font() {
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let mut registry = FontRegistry::new();
+        let font_id = registry.load_font_data(data).unwrap();
+        let descriptor = FontDescriptor {
+            family: vec!["DejaVu Sans".to_string()],
+            ..FontDescriptor::default()
+        };
+        assert_eq!(registry.match_font(&descriptor), Some(font_id));
+        assert_eq!(registry.font_count(), 1);
+    }
+
+    #[test]
+    fn test_char_coverage_matches_cmap_lookups() {
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let face = ttf_parser::Face::parse(&data, 0).unwrap();
+        let coverage = CharCoverage::new(&data);
+        for c in (0..=0x10FFFF).filter_map(char::from_u32) {
+            let mapped = face.glyph_index(c).is_some_and(|glyph| glyph.0 != 0);
+            assert_eq!(coverage.contains(c), mapped, "{c:?}");
+        }
+        assert!(!CharCoverage::new(b"not a font").contains('A'));
+    }
+
+    #[cfg(feature = "last-resort-font")]
+    #[test]
+    fn test_last_resort_font_passes_checksum_verification() {
+        let mut registry = FontRegistry::new();
+        registry.set_checksum_verification(true);
+        registry.set_deduplication(false);
+        assert!(registry.load_font_data(LAST_RESORT_FONT.to_vec()).is_ok());
+    }
+
+    #[cfg(not(feature = "last-resort-font"))]
+    #[test]
+    fn test_last_resort_fallback_needs_feature() {
+        let mut registry = FontRegistry::new();
+        registry.set_last_resort_fallback(true);
+        assert!(!registry.last_resort_fallback());
+        assert_eq!(registry.match_font(&FontDescriptor::default()), None);
     }
 }
@@ -0,0 +1,643 @@
+//! Fonts built in memory, for tests that need stable metrics
+//!
+//! A [`SyntheticFont`] is written out as a minimal TrueType font whose
+//! glyphs are plain rectangles, so it goes through the same loading,
+//! shaping and rasterization paths as a font file, without depending on
+//! the fonts installed on the machine or their version.
+
+use std::collections::BTreeMap;
+
+use crate::types::GlyphId;
+
+/// Family name of [`SyntheticFont::default`]
+pub const SYNTHETIC_FAMILY: &str = "Corten Synthetic";
+
+/// A font with fully specified metrics and one rectangle glyph per
+/// character
+///
+/// All values are in font units. Each character is drawn as a filled
+/// rectangle from the baseline to the cap height, inset by a tenth of its
+/// advance on both sides; whitespace and zero-advance characters are
+/// blank. Glyph 0 (`.notdef`) is a hollow box.
+///
+/// ```
+/// use font_registry::{FontRegistry, SyntheticFont};
+///
+/// let font = SyntheticFont::new("Test Sans").with_advance('W', 900);
+/// let mut registry = FontRegistry::new();
+/// let font_id = registry.load_synthetic_font(&font).unwrap();
+/// let metrics = registry.get_font_metrics(font_id, 10.0).unwrap();
+/// assert_eq!(metrics.ascent, 8.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticFont {
+    /// Family name, also used for the full and PostScript names
+    pub family_name: String,
+    /// Units per em
+    pub units_per_em: u16,
+    /// Ascent above the baseline
+    pub ascent: i16,
+    /// Descent below the baseline (negative)
+    pub descent: i16,
+    /// Line gap
+    pub line_gap: i16,
+    /// Height of lowercase letters
+    pub x_height: i16,
+    /// Height of capital letters, and of every glyph's rectangle
+    pub cap_height: i16,
+    /// Advance of each mapped character
+    pub advances: BTreeMap<char, u16>,
+}
+
+impl Default for SyntheticFont {
+    /// A 1000-unit em with an 800-unit ascent and a 200-unit descent, and
+    /// printable ASCII 500 units wide
+    fn default() -> Self {
+        Self {
+            family_name: SYNTHETIC_FAMILY.to_string(),
+            units_per_em: 1000,
+            ascent: 800,
+            descent: -200,
+            line_gap: 0,
+            x_height: 500,
+            cap_height: 700,
+            advances: (' '..='~').map(|c| (c, 500)).collect(),
+        }
+    }
+}
+
+impl SyntheticFont {
+    /// The default font under another family name
+    pub fn new(family_name: impl Into<String>) -> Self {
+        Self {
+            family_name: family_name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the ascent, descent (negative) and line gap
+    #[must_use]
+    pub fn with_line_metrics(mut self, ascent: i16, descent: i16, line_gap: i16) -> Self {
+        self.ascent = ascent;
+        self.descent = descent;
+        self.line_gap = line_gap;
+        self
+    }
+
+    /// Map `c`, or change its advance
+    #[must_use]
+    pub fn with_advance(mut self, c: char, advance: u16) -> Self {
+        self.advances.insert(c, advance);
+        self
+    }
+
+    /// Map every character of `chars` with the same advance
+    #[must_use]
+    pub fn with_chars(mut self, chars: impl IntoIterator<Item = char>, advance: u16) -> Self {
+        self.advances
+            .extend(chars.into_iter().map(|c| (c, advance)));
+        self
+    }
+
+    /// Glyph of `c`: glyphs are numbered from 1 in code point order
+    pub fn glyph_id(&self, c: char) -> Option<GlyphId> {
+        self.advances.contains_key(&c).then(|| GlyphId {
+            id: self.advances.range(..c).count() as u32 + 1,
+        })
+    }
+
+    /// The font as TrueType data, for `FontRegistry::load_font_data`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let glyphs = self.glyphs();
+        let (glyf, loca) = glyf_and_loca(&glyphs);
+        let mut tables: Vec<([u8; 4], Vec<u8>)> = vec![
+            (*b"OS/2", self.os2_table(&glyphs)),
+            (*b"cmap", self.cmap_table()),
+            (*b"glyf", glyf),
+            (*b"head", self.head_table(&glyphs)),
+            (*b"hhea", self.hhea_table(&glyphs)),
+            (*b"hmtx", hmtx_table(&glyphs)),
+            (*b"loca", loca),
+            (*b"maxp", maxp_table(glyphs.len())),
+            (*b"name", self.name_table()),
+            (*b"post", self.post_table()),
+        ];
+        tables.sort_by_key(|(tag, _)| *tag);
+        write_sfnt(&tables)
+    }
+
+    /// `.notdef`, then a glyph per mapped character
+    fn glyphs(&self) -> Vec<Glyph> {
+        let notdef_advance = self.units_per_em / 2;
+        let inset = i16::try_from(self.units_per_em / 20).unwrap_or(i16::MAX);
+        let outer = Rect {
+            x_min: inset,
+            y_min: 0,
+            x_max: advance_units(notdef_advance) - inset,
+            y_max: self.cap_height,
+        };
+        let inner = Rect {
+            x_min: outer.x_min + inset,
+            y_min: outer.y_min + inset,
+            x_max: outer.x_max - inset,
+            y_max: outer.y_max - inset,
+        };
+        let notdef = Glyph {
+            advance: notdef_advance,
+            contours: vec![(outer, true), (inner, false)],
+        };
+        let mapped = self.advances.iter().map(|(&c, &advance)| {
+            let inset = advance_units(advance) / 10;
+            let contours = if c.is_whitespace() || advance == 0 || self.cap_height <= 0 {
+                Vec::new()
+            } else {
+                let rect = Rect {
+                    x_min: inset,
+                    y_min: 0,
+                    x_max: advance_units(advance) - inset,
+                    y_max: self.cap_height,
+                };
+                vec![(rect, true)]
+            };
+            Glyph { advance, contours }
+        });
+        std::iter::once(notdef).chain(mapped).collect()
+    }
+
+    fn cmap_table(&self) -> Vec<u8> {
+        // One format 12 subtable; glyphs follow code point order, so each
+        // run of consecutive characters is one group
+        let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+        for (index, &c) in self.advances.keys().enumerate() {
+            let code = u32::from(c);
+            match groups.last_mut() {
+                Some((_, end, _)) if *end + 1 == code => *end = code,
+                _ => groups.push((code, code, index as u32 + 1)),
+            }
+        }
+        let mut table = Vec::new();
+        // Version, and one encoding record: Windows, full Unicode
+        push_u16(&mut table, 0);
+        push_u16(&mut table, 1);
+        push_u16(&mut table, 3);
+        push_u16(&mut table, 10);
+        push_u32(&mut table, 12);
+        push_u16(&mut table, 12);
+        push_u16(&mut table, 0);
+        push_u32(&mut table, 16 + 12 * groups.len() as u32);
+        push_u32(&mut table, 0);
+        push_u32(&mut table, groups.len() as u32);
+        for (start, end, glyph) in groups {
+            push_u32(&mut table, start);
+            push_u32(&mut table, end);
+            push_u32(&mut table, glyph);
+        }
+        table
+    }
+
+    fn head_table(&self, glyphs: &[Glyph]) -> Vec<u8> {
+        let bounds = glyph_bounds(glyphs);
+        let mut table = Vec::new();
+        push_u32(&mut table, 0x0001_0000);
+        push_u32(&mut table, 0x0001_0000);
+        // checkSumAdjustment, filled in by write_sfnt
+        push_u32(&mut table, 0);
+        push_u32(&mut table, 0x5F0F_3CF5);
+        push_u16(&mut table, 0x000B);
+        push_u16(&mut table, self.units_per_em);
+        // Created and modified dates
+        table.extend_from_slice(&[0; 16]);
+        for value in [bounds.x_min, bounds.y_min, bounds.x_max, bounds.y_max] {
+            push_i16(&mut table, value);
+        }
+        // macStyle, lowestRecPPEM, fontDirectionHint
+        push_u16(&mut table, 0);
+        push_u16(&mut table, 8);
+        push_i16(&mut table, 2);
+        // Long loca offsets, glyf format 0
+        push_i16(&mut table, 1);
+        push_i16(&mut table, 0);
+        table
+    }
+
+    fn hhea_table(&self, glyphs: &[Glyph]) -> Vec<u8> {
+        let bounds = glyph_bounds(glyphs);
+        let max_advance = glyphs.iter().map(|glyph| glyph.advance).max().unwrap_or(0);
+        let min_rsb = glyphs
+            .iter()
+            .filter_map(|glyph| Some(advance_units(glyph.advance) - glyph.bounds()?.x_max))
+            .min()
+            .unwrap_or(0);
+        let mut table = Vec::new();
+        push_u32(&mut table, 0x0001_0000);
+        push_i16(&mut table, self.ascent);
+        push_i16(&mut table, self.descent);
+        push_i16(&mut table, self.line_gap);
+        push_u16(&mut table, max_advance);
+        push_i16(&mut table, bounds.x_min);
+        push_i16(&mut table, min_rsb);
+        push_i16(&mut table, bounds.x_max);
+        // Upright caret
+        push_i16(&mut table, 1);
+        push_i16(&mut table, 0);
+        push_i16(&mut table, 0);
+        // Reserved, then metricDataFormat
+        table.extend_from_slice(&[0; 10]);
+        push_u16(&mut table, glyph_count(glyphs.len()));
+        table
+    }
+
+    fn os2_table(&self, glyphs: &[Glyph]) -> Vec<u8> {
+        let em = i32::from(self.units_per_em);
+        let scaled = |percent: i32| i16::try_from(em * percent / 100).unwrap_or(i16::MAX);
+        let advances: Vec<i32> = glyphs[1..]
+            .iter()
+            .filter(|glyph| glyph.advance > 0)
+            .map(|glyph| i32::from(glyph.advance))
+            .collect();
+        let average = advances
+            .iter()
+            .sum::<i32>()
+            .checked_div(advances.len() as i32)
+            .unwrap_or(0);
+        let first = self.advances.keys().next().map_or(0, |&c| u32::from(c));
+        let last = self
+            .advances
+            .keys()
+            .next_back()
+            .map_or(0, |&c| u32::from(c));
+
+        let mut table = Vec::new();
+        push_u16(&mut table, 4);
+        push_i16(&mut table, i16::try_from(average).unwrap_or(i16::MAX));
+        // Regular weight, normal width, installable
+        push_u16(&mut table, 400);
+        push_u16(&mut table, 5);
+        push_u16(&mut table, 0);
+        // Subscript and superscript sizes and offsets
+        for value in [65, 60, 0, 7, 65, 60, 0, 35] {
+            push_i16(&mut table, scaled(value));
+        }
+        // Strikeout size and position, family class
+        push_i16(&mut table, scaled(5));
+        push_i16(&mut table, self.x_height / 2);
+        push_i16(&mut table, 0);
+        // PANOSE, Unicode ranges, vendor
+        table.extend_from_slice(&[0; 10 + 16]);
+        table.extend_from_slice(b"NONE");
+        // Regular, with the typographic metrics used for line spacing
+        push_u16(&mut table, 0x00C0);
+        push_u16(&mut table, u16::try_from(first).unwrap_or(u16::MAX));
+        push_u16(&mut table, u16::try_from(last).unwrap_or(u16::MAX));
+        push_i16(&mut table, self.ascent);
+        push_i16(&mut table, self.descent);
+        push_i16(&mut table, self.line_gap);
+        push_u16(&mut table, self.ascent.unsigned_abs());
+        push_u16(&mut table, self.descent.unsigned_abs());
+        // Code page ranges
+        table.extend_from_slice(&[0; 8]);
+        push_i16(&mut table, self.x_height);
+        push_i16(&mut table, self.cap_height);
+        // Default and break characters, max context
+        push_u16(&mut table, 0);
+        push_u16(&mut table, 0x20);
+        push_u16(&mut table, 1);
+        table
+    }
+
+    fn name_table(&self) -> Vec<u8> {
+        let postscript: String = self
+            .family_name
+            .chars()
+            .filter(|c| c.is_ascii_graphic() && !"[](){}<>/%".contains(*c))
+            .collect();
+        let records = [
+            (1, self.family_name.as_str()),
+            (2, "Regular"),
+            (4, self.family_name.as_str()),
+            (6, postscript.as_str()),
+        ];
+        let mut entries = Vec::new();
+        let mut strings = Vec::new();
+        for (name_id, value) in records {
+            let encoded: Vec<u8> = value.encode_utf16().flat_map(u16::to_be_bytes).collect();
+            // Windows, Unicode BMP, US English
+            push_u16(&mut entries, 3);
+            push_u16(&mut entries, 1);
+            push_u16(&mut entries, 0x409);
+            push_u16(&mut entries, name_id);
+            push_u16(
+                &mut entries,
+                u16::try_from(encoded.len()).unwrap_or(u16::MAX),
+            );
+            push_u16(
+                &mut entries,
+                u16::try_from(strings.len()).unwrap_or(u16::MAX),
+            );
+            strings.extend_from_slice(&encoded);
+        }
+        let mut table = Vec::new();
+        push_u16(&mut table, 0);
+        push_u16(&mut table, records.len() as u16);
+        push_u16(&mut table, 6 + 12 * records.len() as u16);
+        table.extend_from_slice(&entries);
+        table.extend_from_slice(&strings);
+        table
+    }
+
+    fn post_table(&self) -> Vec<u8> {
+        let widths: Vec<u16> = self
+            .advances
+            .values()
+            .copied()
+            .filter(|&advance| advance > 0)
+            .collect();
+        let fixed_pitch = widths.windows(2).all(|pair| pair[0] == pair[1]);
+        let em = i32::from(self.units_per_em);
+        let mut table = Vec::new();
+        // Version 3: no glyph names
+        push_u32(&mut table, 0x0003_0000);
+        push_u32(&mut table, 0);
+        push_i16(&mut table, i16::try_from(-em / 10).unwrap_or(i16::MIN));
+        push_i16(&mut table, i16::try_from(em / 20).unwrap_or(i16::MAX));
+        push_u32(&mut table, u32::from(fixed_pitch));
+        table.extend_from_slice(&[0; 16]);
+        table
+    }
+}
+
+/// Axis-aligned rectangle in font units
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    x_min: i16,
+    y_min: i16,
+    x_max: i16,
+    y_max: i16,
+}
+
+struct Glyph {
+    advance: u16,
+    /// Rectangles, and whether each is drawn clockwise (filled) or
+    /// counterclockwise (a hole)
+    contours: Vec<(Rect, bool)>,
+}
+
+impl Glyph {
+    fn bounds(&self) -> Option<Rect> {
+        self.contours
+            .iter()
+            .map(|(rect, _)| *rect)
+            .reduce(|a, b| Rect {
+                x_min: a.x_min.min(b.x_min),
+                y_min: a.y_min.min(b.y_min),
+                x_max: a.x_max.max(b.x_max),
+                y_max: a.y_max.max(b.y_max),
+            })
+    }
+
+    /// Simple glyph description with on-curve points and 16-bit
+    /// coordinates
+    fn data(&self) -> Vec<u8> {
+        let Some(bounds) = self.bounds() else {
+            return Vec::new();
+        };
+        let points: Vec<(i16, i16)> = self
+            .contours
+            .iter()
+            .flat_map(|&(rect, clockwise)| {
+                let corners = [
+                    (rect.x_min, rect.y_min),
+                    (rect.x_min, rect.y_max),
+                    (rect.x_max, rect.y_max),
+                    (rect.x_max, rect.y_min),
+                ];
+                if clockwise {
+                    corners
+                } else {
+                    [corners[0], corners[3], corners[2], corners[1]]
+                }
+            })
+            .collect();
+
+        let mut data = Vec::new();
+        push_i16(&mut data, self.contours.len() as i16);
+        for value in [bounds.x_min, bounds.y_min, bounds.x_max, bounds.y_max] {
+            push_i16(&mut data, value);
+        }
+        for contour in 0..self.contours.len() {
+            push_u16(&mut data, (contour * 4 + 3) as u16);
+        }
+        // No instructions
+        push_u16(&mut data, 0);
+        data.resize(data.len() + points.len(), 0x01);
+        let mut previous = 0;
+        for &(x, _) in &points {
+            push_i16(&mut data, x - previous);
+            previous = x;
+        }
+        previous = 0;
+        for &(_, y) in &points {
+            push_i16(&mut data, y - previous);
+            previous = y;
+        }
+        data
+    }
+}
+
+fn glyph_bounds(glyphs: &[Glyph]) -> Rect {
+    glyphs
+        .iter()
+        .filter_map(Glyph::bounds)
+        .reduce(|a, b| Rect {
+            x_min: a.x_min.min(b.x_min),
+            y_min: a.y_min.min(b.y_min),
+            x_max: a.x_max.max(b.x_max),
+            y_max: a.y_max.max(b.y_max),
+        })
+        .unwrap_or(Rect {
+            x_min: 0,
+            y_min: 0,
+            x_max: 0,
+            y_max: 0,
+        })
+}
+
+/// `glyf` and long `loca` tables
+fn glyf_and_loca(glyphs: &[Glyph]) -> (Vec<u8>, Vec<u8>) {
+    let mut glyf = Vec::new();
+    let mut loca = Vec::new();
+    for glyph in glyphs {
+        push_u32(&mut loca, glyf.len() as u32);
+        glyf.extend_from_slice(&glyph.data());
+        glyf.resize(glyf.len().next_multiple_of(4), 0);
+    }
+    push_u32(&mut loca, glyf.len() as u32);
+    (glyf, loca)
+}
+
+fn hmtx_table(glyphs: &[Glyph]) -> Vec<u8> {
+    let mut table = Vec::new();
+    for glyph in glyphs {
+        push_u16(&mut table, glyph.advance);
+        push_i16(&mut table, glyph.bounds().map_or(0, |bounds| bounds.x_min));
+    }
+    table
+}
+
+fn maxp_table(glyph_total: usize) -> Vec<u8> {
+    let mut table = Vec::new();
+    push_u32(&mut table, 0x0001_0000);
+    push_u16(&mut table, glyph_count(glyph_total));
+    // Points and contours of the .notdef box, no composites, two zones
+    push_u16(&mut table, 8);
+    push_u16(&mut table, 2);
+    push_u16(&mut table, 0);
+    push_u16(&mut table, 0);
+    push_u16(&mut table, 2);
+    table.extend_from_slice(&[0; 16]);
+    table
+}
+
+/// Table directory and tables, with `head.checkSumAdjustment` set
+fn write_sfnt(tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range = (1 << entry_selector) * 16;
+    let mut font = Vec::new();
+    push_u32(&mut font, 0x0001_0000);
+    push_u16(&mut font, count);
+    push_u16(&mut font, search_range);
+    push_u16(&mut font, entry_selector);
+    push_u16(&mut font, count * 16 - search_range);
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = None;
+    for (tag, data) in tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend_from_slice(tag);
+        push_u32(&mut font, checksum(data));
+        push_u32(&mut font, offset as u32);
+        push_u32(&mut font, data.len() as u32);
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in tables {
+        font.extend_from_slice(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBA_u32.wrapping_sub(checksum(&font));
+        font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+/// Sum of the big-endian 32-bit words of `data`, zero-padded
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn advance_units(advance: u16) -> i16 {
+    i16::try_from(advance).unwrap_or(i16::MAX)
+}
+
+fn glyph_count(count: usize) -> u16 {
+    u16::try_from(count).unwrap_or(u16::MAX)
+}
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_i16(data: &mut Vec<u8>, value: i16) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FontRegistry;
+
+    #[test]
+    fn test_synthetic_font_parses_with_its_metrics_and_glyphs() {
+        // Given: A font with a wide W and a non-ASCII character
+        let font = SyntheticFont::new("Test Sans")
+            .with_line_metrics(900, -300, 100)
+            .with_advance('W', 900)
+            .with_advance('\u{4e00}', 1000);
+
+        // When: Parsing its data
+        let data = font.to_bytes();
+        let face = ttf_parser::Face::parse(&data, 0).unwrap();
+
+        // Then: Metrics, cmap and advances are the ones given
+        assert_eq!(face.units_per_em(), 1000);
+        assert_eq!(face.ascender(), 900);
+        assert_eq!(face.descender(), -300);
+        assert_eq!(face.line_gap(), 100);
+        assert_eq!(face.x_height(), Some(500));
+        assert_eq!(face.capital_height(), Some(700));
+        assert_eq!(face.number_of_glyphs(), 97);
+        let w = face.glyph_index('W').unwrap();
+        assert_eq!(u32::from(w.0), font.glyph_id('W').unwrap().id);
+        assert_eq!(face.glyph_hor_advance(w), Some(900));
+        let a = face.glyph_index('a').unwrap();
+        assert_eq!(face.glyph_hor_advance(a), Some(500));
+        let han = face.glyph_index('\u{4e00}').unwrap();
+        assert_eq!(han.0, 96);
+        assert_eq!(face.glyph_hor_advance(han), Some(1000));
+        assert!(face.glyph_index('\u{e9}').is_none());
+
+        // And glyphs are rectangles, blank for spaces
+        let bounds = face.glyph_bounding_box(w).unwrap();
+        assert_eq!(
+            (bounds.x_min, bounds.y_min, bounds.x_max, bounds.y_max),
+            (90, 0, 810, 700)
+        );
+        let space = face.glyph_index(' ').unwrap();
+        assert!(face.glyph_bounding_box(space).is_none());
+        assert!(face.glyph_bounding_box(ttf_parser::GlyphId(0)).is_some());
+    }
+
+    #[test]
+    fn test_synthetic_font_checksums_are_valid() {
+        let data = SyntheticFont::default().to_bytes();
+        let font = font_parser::OpenTypeFont::parse(data).unwrap();
+        assert!(font.verify_checksums().is_valid());
+    }
+
+    #[test]
+    fn test_registry_loads_synthetic_fonts() {
+        // Given: A registry verifying checksums, and two synthetic fonts
+        // differing only in metrics
+        let mut registry = FontRegistry::new();
+        registry.set_checksum_verification(true);
+        let tall = SyntheticFont::new("Test Sans").with_line_metrics(1000, -250, 0);
+
+        // When: Loading both
+        let regular = registry
+            .load_synthetic_font(&SyntheticFont::new("Test Sans"))
+            .unwrap();
+        let tall = registry.load_synthetic_font(&tall).unwrap();
+
+        // Then: Each is its own face, with its metrics
+        assert_ne!(regular, tall);
+        let face = registry.get_font_face(regular).unwrap();
+        assert_eq!(face.family_name, "Test Sans");
+        assert_eq!(face.postscript_name, "TestSans");
+        assert!(face.is_fixed_pitch);
+        let metrics = registry.get_font_metrics(tall, 20.0).unwrap();
+        assert_eq!((metrics.ascent, metrics.descent), (20.0, -5.0));
+        assert!(registry.font_covers(regular, 'x'));
+    }
+}
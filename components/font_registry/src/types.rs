@@ -1,62 +1,127 @@
 //! Types for font_registry component
 //!
-//! Note: These types should eventually be imported from font_types component
-//! once it is fully implemented. For now, they are defined locally.
+//! Font selection and metrics types are re-exported from font_types so that
+//! every component shares a single definition.
 
+use platform_integration::FontCategory;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use thiserror::Error;
 
-// Re-export types from font_types that are already available
-pub use font_types::types::{FontStretch, FontStyle, FontWeight};
+// Shared types are defined once in font_types
+pub use font_types::types::{
+    BasePalette, FontData, FontDescriptor, FontId, FontMetrics, FontMetricsOverrides, FontPalette,
+    FontStretch, FontStyle, FontSynthesis, FontVisibility, FontWeight, GlyphId, MatchContext,
+    PaletteOverride, VariationCoordinate,
+};
 
-/// Font identifier
-pub type FontId = usize;
+/// Identifier of a scoped set of fonts (e.g. the web fonts of one document)
+pub type FontSetId = usize;
 
-/// Font selection descriptor
+/// Range of a variation axis supported by a variable font
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VariationAxisRange {
+    /// Axis tag (e.g. `*b"wght"`)
+    pub tag: [u8; 4],
+    /// Minimum axis value
+    pub min: f32,
+    /// Default axis value
+    pub default: f32,
+    /// Maximum axis value
+    pub max: f32,
+}
+
+impl VariationAxisRange {
+    /// Clamp a requested value into the axis range
+    pub fn clamp(&self, value: f32) -> f32 {
+        value.clamp(self.min, self.max)
+    }
+}
+
+/// Styling the renderer must synthesize because the matched face lacks it
+///
+/// Only styling the descriptor's [`FontSynthesis`] allows is reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyntheticFlags {
+    /// Embolden glyphs (requested weight >= 600 but face is lighter)
+    pub bold: bool,
+    /// Skew glyphs (italic/oblique requested but face is upright)
+    pub italic: bool,
+}
+
+/// Result of font matching
 #[derive(Debug, Clone, PartialEq)]
-pub struct FontDescriptor {
-    /// Font family names (fallback chain)
-    pub family: Vec<String>,
-    /// Font weight
-    pub weight: FontWeight,
-    /// Font style
+pub struct MatchedFont {
+    /// ID of the matched face
+    pub font_id: FontId,
+    /// Axis coordinates to instance the face at (empty for static faces)
+    pub variation_coords: Vec<VariationCoordinate>,
+    /// Styling that must be synthesized
+    pub synthetic: SyntheticFlags,
+}
+
+/// How font matching found a candidate from the requested families
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FamilyMatch {
+    /// The face has the requested family name (or a localized form of it)
+    Family(String),
+    /// No face has the requested family; the face belongs to a configured
+    /// substitute
+    Substitute {
+        /// Family in the descriptor
+        requested: String,
+        /// Substitute family the face belongs to
+        substitute: String,
+    },
+}
+
+/// A face considered by font matching, with the penalties it scored
+///
+/// Penalties are distances from the request in CSS matching order; the
+/// candidate with the lowest [`score`](Self::score) wins, the lowest ID on
+/// ties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchCandidate {
+    /// ID of the face
+    pub font_id: FontId,
+    /// How the face was found
+    pub family_match: FamilyMatch,
+    /// Weight the face provides, after instancing variable fonts
+    pub weight: f32,
+    /// Style the face provides, after instancing variable fonts
     pub style: FontStyle,
-    /// Font stretch
-    pub stretch: FontStretch,
-    /// Font size in pixels
-    pub size: f32,
-}
-
-impl Default for FontDescriptor {
-    fn default() -> Self {
-        Self {
-            family: vec!["sans-serif".to_string()],
-            weight: FontWeight::Regular,
-            style: FontStyle::Normal,
-            stretch: FontStretch::Normal,
-            size: 16.0,
-        }
+    /// Width the face provides in percent, after instancing variable fonts
+    pub stretch: f32,
+    /// Penalty for the weight distance
+    pub weight_penalty: i32,
+    /// Penalty for the style mismatch
+    pub style_penalty: i32,
+    /// Penalty for the width distance
+    pub stretch_penalty: i32,
+    /// Axis coordinates the face would be instanced at
+    pub variation_coords: Vec<VariationCoordinate>,
+    /// Styling that would be synthesized if the face were picked
+    pub synthetic: SyntheticFlags,
+}
+
+impl MatchCandidate {
+    /// Total penalty; lower is better
+    pub fn score(&self) -> i32 {
+        self.weight_penalty + self.style_penalty + self.stretch_penalty
     }
 }
 
-/// Font metrics and measurements
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct FontMetrics {
-    /// Units per EM
-    pub units_per_em: u16,
-    /// Ascent (above baseline)
-    pub ascent: f32,
-    /// Descent (below baseline)
-    pub descent: f32,
-    /// Line gap
-    pub line_gap: f32,
-    /// Cap height
-    pub cap_height: f32,
-    /// X-height
-    pub x_height: f32,
-    /// Underline position
-    pub underline_position: f32,
-    /// Underline thickness
-    pub underline_thickness: f32,
+/// Why font matching picked the face it did
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchExplanation {
+    /// Faces of the requested families, best first
+    pub candidates: Vec<MatchCandidate>,
+    /// Requested families no loaded face or substitute provides
+    pub unmatched_families: Vec<String>,
+    /// Face matching returns: the first candidate, or the last-resort font
+    /// when there are none
+    pub selected: Option<FontId>,
 }
 
 /// Loaded font face
@@ -66,8 +131,14 @@ pub struct FontFace {
     pub id: FontId,
     /// Family name
     pub family_name: String,
+    /// Other family names from the name table (typographic and localized
+    /// names), matched like `family_name`
+    pub family_aliases: Vec<String>,
     /// PostScript name
     pub postscript_name: String,
+    /// Full name (e.g. "Arial Bold"), matched by CSS `local()` with the
+    /// PostScript name
+    pub full_name: String,
     /// Font weight
     pub weight: FontWeight,
     /// Font style
@@ -76,10 +147,33 @@ pub struct FontFace {
     pub stretch: FontStretch,
     /// Font metrics
     pub metrics: FontMetrics,
+    /// Whether the post table marks this font as fixed pitch (monospaced)
+    pub is_fixed_pitch: bool,
+    /// Advance of every spacing glyph in font units, if the hmtx advances
+    /// are uniform (double-width glyphs allowed), i.e. the face is truly
+    /// monospaced whatever `is_fixed_pitch` says
+    pub fixed_advance: Option<f32>,
+    /// Generic category detected when the font was loaded, matched by the
+    /// CSS generic family name (currently only emoji fonts are detected)
+    pub category: Option<FontCategory>,
+    /// Subfamily name of the fvar named instance this face represents
+    pub named_instance: Option<String>,
+    /// Default face of the variable font this named instance belongs to
+    pub instance_of: Option<FontId>,
+    /// Axis coordinates to apply when rendering this face (empty for the default instance)
+    pub variation_coords: Vec<VariationCoordinate>,
+    /// Variation axes of a variable font's default face (empty for static faces and instances)
+    pub variation_axes: Vec<VariationAxisRange>,
+    /// Color palette for the face's `COLR` glyphs (CSS `font-palette`),
+    /// set with `FontRegistry::set_font_palette`
+    pub palette: FontPalette,
+    /// `@font-face` metric overrides, set with
+    /// `FontRegistry::set_metric_overrides`
+    pub metric_overrides: FontMetricsOverrides,
     /// Path to font file (for system fonts, lazy loading)
     pub(crate) file_path: Option<std::path::PathBuf>,
-    /// Raw font data (loaded eagerly or lazily)
-    pub(crate) data: Option<Vec<u8>>,
+    /// Raw font data (loaded eagerly or lazily), shared between named instances
+    pub(crate) data: Option<FontData>,
     /// Whether this is a system font
     pub(crate) is_system_font: bool,
 }
@@ -101,6 +195,22 @@ impl FontFace {
         self.data.as_deref()
     }
 
+    /// Get a shared handle to the raw font data
+    ///
+    /// Cloning the handle doesn't copy the bytes, so shaping and rendering
+    /// can keep the data alive without duplicating it (see
+    /// `glyph_renderer::types::OpenTypeFont::from_shared`).
+    pub fn shared_data(&self) -> Option<FontData> {
+        self.data.clone()
+    }
+
+    /// Index of this face within its font file, for [`FontFace::data`]
+    ///
+    /// Font collections aren't split into faces yet, so this is always 0.
+    pub fn face_index(&self) -> u32 {
+        0
+    }
+
     /// Check if this is a system font
     pub fn is_system_font(&self) -> bool {
         self.is_system_font
@@ -130,4 +240,91 @@ pub enum RegistryError {
     /// System fonts unavailable
     #[error("System fonts unavailable")]
     SystemFontsUnavailable,
+
+    /// Font data failed checksum verification
+    #[error("Corrupted font: {0}")]
+    CorruptedFont(String),
+
+    /// No font with this ID is loaded
+    #[error("Font not found: {0}")]
+    FontNotFound(FontId),
+
+    /// No font set with this ID exists
+    #[error("Font set not found: {0}")]
+    FontSetNotFound(FontSetId),
+
+    /// Byte ranges of an incrementally loaded font that aren't available
+    /// yet; fetch them before trying again
+    #[error("Font data not loaded yet: {0:?}")]
+    MissingFontData(Vec<Range<usize>>),
+
+    /// Reading a font file failed for a reason other than it not existing
+    #[error("Failed to read font file {path}")]
+    Io {
+        /// Path of the file
+        path: String,
+        /// The underlying I/O error
+        #[source]
+        source: IoError,
+    },
+}
+
+impl RegistryError {
+    /// Error for a font file that could not be read
+    pub(crate) fn read_failed(path: &Path, error: std::io::Error) -> Self {
+        let path = path.display().to_string();
+        if error.kind() == std::io::ErrorKind::NotFound {
+            RegistryError::FileNotFound(path)
+        } else {
+            RegistryError::Io {
+                path,
+                source: IoError(Arc::new(error)),
+            }
+        }
+    }
+}
+
+/// Loaded faces and how many of them hold their font data in memory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegistryStats {
+    /// Registered faces, excluding the embedded last-resort font
+    pub faces: usize,
+    /// Faces whose font data is loaded; lazily loaded and trimmed faces
+    /// are registered without it
+    pub resident_faces: usize,
+    /// Bytes of loaded font data, counting data shared between faces once
+    pub resident_bytes: usize,
+}
+
+/// A font file skipped while loading system fonts
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontLoadFailure {
+    /// Path of the font file
+    pub path: PathBuf,
+    /// Why the file was skipped
+    pub error: RegistryError,
+}
+
+/// I/O error shared by clones of a [`RegistryError`]
+///
+/// Errors compare equal when their kinds match.
+#[derive(Debug, Clone)]
+pub struct IoError(pub Arc<std::io::Error>);
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.kind() == other.0.kind()
+    }
+}
+
+impl std::fmt::Display for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
 }
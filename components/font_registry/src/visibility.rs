@@ -0,0 +1,83 @@
+//! Font visibility policy for fingerprinting resistance
+//!
+//! Which fonts a user has installed is a strong fingerprinting signal, so
+//! browsers limit the installed fonts web content can match. Callers tag
+//! matches with a [`MatchContext`]: privileged callers see every font, while
+//! untrusted ones only see installed fonts the [`FontVisibility`] policy
+//! allows. Fonts registered from memory (web fonts, bundled fonts) are always
+//! visible.
+
+use crate::registry::family_key;
+use crate::types::{FontFace, FontVisibility, MatchContext};
+use platform_integration::Platform;
+use std::collections::HashSet;
+
+/// Families installed on most systems, visible under
+/// [`FontVisibility::Standard`] along with the platform's generic defaults
+pub const STANDARD_FONT_FAMILIES: &[&str] = &[
+    "Arial",
+    "Arial Black",
+    "Comic Sans MS",
+    "Courier",
+    "Courier New",
+    "DejaVu Sans",
+    "DejaVu Sans Mono",
+    "DejaVu Serif",
+    "Georgia",
+    "Helvetica",
+    "Impact",
+    "Liberation Mono",
+    "Liberation Sans",
+    "Liberation Serif",
+    "Noto Color Emoji",
+    "Noto Sans",
+    "Noto Sans Mono",
+    "Noto Serif",
+    "Tahoma",
+    "Times",
+    "Times New Roman",
+    "Trebuchet MS",
+    "Verdana",
+];
+
+/// Installed families untrusted callers may match under a policy
+///
+/// Holds normalized family names, or nothing when every font is visible.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct VisibleFamilies(Option<HashSet<String>>);
+
+impl VisibleFamilies {
+    /// Resolve a policy on `platform`, whose generic defaults are visible
+    /// under [`FontVisibility::Standard`]
+    pub(crate) fn new(visibility: &FontVisibility, platform: Platform) -> Self {
+        let families: HashSet<String> = match visibility {
+            FontVisibility::All => return Self(None),
+            FontVisibility::Standard => STANDARD_FONT_FAMILIES
+                .iter()
+                .map(|family| family.to_string())
+                .chain(
+                    platform_integration::get_platform_default_font_families(platform)
+                        .into_values()
+                        .flatten(),
+                )
+                .map(|family| family_key(&family))
+                .collect(),
+            FontVisibility::Allowlist(families) => {
+                families.iter().map(|family| family_key(family)).collect()
+            }
+        };
+        Self(Some(families))
+    }
+
+    /// Whether `face` can be matched in `context`
+    pub(crate) fn allows(&self, face: &FontFace, context: MatchContext) -> bool {
+        let Some(families) = &self.0 else {
+            return true;
+        };
+        context == MatchContext::Privileged
+            || face.file_path.is_none()
+            || std::iter::once(&face.family_name)
+                .chain(&face.family_aliases)
+                .any(|family| families.contains(&family_key(family)))
+    }
+}
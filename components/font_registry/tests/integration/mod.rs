@@ -1,3 +1,4 @@
 //! Integration tests module
 
+mod test_mock_platform;
 mod test_system_fonts;
@@ -0,0 +1,147 @@
+//! Integration tests for the mock platform backend
+//!
+//! These tests load the bundled fixture fonts through `load_system_fonts`
+//! and must behave identically on every host.
+
+use font_registry::{DiscoveryConfig, FontDescriptor, FontRegistry, SyntheticFont};
+
+fn mock_registry() -> FontRegistry {
+    let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
+    registry
+}
+
+#[test]
+fn test_mock_platform_loads_bundled_fonts() {
+    //! Given: A registry configured for the mock platform
+    //! When: Loading system fonts
+    //! Then: Exactly the three bundled DejaVu faces are registered
+
+    // Given
+    let mut registry = mock_registry();
+
+    // When
+    let count = registry.load_system_fonts().unwrap();
+
+    // Then
+    assert_eq!(count, 3);
+    assert_eq!(registry.font_count(), 3);
+    for family in ["DejaVu Sans", "DejaVu Sans Mono", "DejaVu Serif"] {
+        let descriptor = FontDescriptor {
+            family: vec![family.to_string()],
+            ..Default::default()
+        };
+        let font_id = registry.match_font(&descriptor).unwrap();
+        assert_eq!(registry.get_font_face(font_id).unwrap().family_name, family);
+    }
+}
+
+#[test]
+fn test_mock_platform_generic_families_resolve_to_bundled_fonts() {
+    //! Given: Mock fonts loaded
+    //! When: Matching generic families for any language
+    //! Then: The fixed mock defaults are used
+
+    // Given
+    let mut registry = mock_registry();
+    registry.load_system_fonts().unwrap();
+
+    // When/Then
+    for (generic, expected) in [
+        ("sans-serif", "DejaVu Sans"),
+        ("serif", "DejaVu Serif"),
+        ("monospace", "DejaVu Sans Mono"),
+    ] {
+        let descriptor = FontDescriptor {
+            family: vec![generic.to_string()],
+            ..Default::default()
+        };
+        for language in ["en", "ja"] {
+            let font_id = registry
+                .match_font_for_language(&descriptor, language)
+                .unwrap();
+            assert_eq!(
+                registry.get_font_face(font_id).unwrap().family_name,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn test_mock_platform_suggests_font_for_uncovered_char() {
+    //! Given: Only DejaVu Serif loaded, which has no Arabic glyphs
+    //! When: Matching a font for an Arabic letter
+    //! Then: The platform's suggestion, DejaVu Sans, is loaded and returned
+
+    // Given
+    let mut registry = mock_registry();
+    registry.set_last_resort_fallback(false);
+    let serif = registry
+        .load_font_file(std::path::Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSerif.ttf"
+        )))
+        .unwrap();
+    let descriptor = FontDescriptor {
+        family: vec!["DejaVu Serif".to_string()],
+        ..Default::default()
+    };
+
+    // When
+    let latin = registry.match_font_for_char(&descriptor, 'A');
+    let arabic = registry
+        .match_font_for_char(&descriptor, '\u{0628}')
+        .unwrap();
+
+    // Then
+    assert_eq!(latin, Some(serif));
+    assert_eq!(
+        registry.get_font_face(arabic).unwrap().family_name,
+        "DejaVu Sans"
+    );
+    assert_eq!(registry.font_count(), 2);
+}
+
+#[test]
+fn test_mock_platform_char_without_any_font() {
+    //! Given: A mock registry without the last-resort fallback
+    //! When: Matching a Han character no bundled font covers
+    //! Then: No font is returned and the rejected suggestion is not kept
+
+    // Given
+    let mut registry = mock_registry();
+    registry.set_last_resort_fallback(false);
+
+    // When
+    let result = registry.match_font_for_char(&FontDescriptor::default(), '\u{4E2D}');
+
+    // Then
+    assert_eq!(result, None);
+    assert_eq!(registry.font_count(), 0);
+}
+
+#[test]
+fn test_mock_platform_uncovered_char_is_remembered_until_fonts_change() {
+    //! Given: A mock registry without the last-resort fallback, where no
+    //! font covers a Han character
+    //! When: Matching it again, then after loading a font that covers it
+    //! Then: It stays unmatched until the new font is loaded
+
+    // Given
+    let mut registry = mock_registry();
+    registry.set_last_resort_fallback(false);
+    let descriptor = FontDescriptor::default();
+    assert_eq!(registry.match_font_for_char(&descriptor, '\u{4E2D}'), None);
+
+    // When
+    let repeated = registry.match_font_for_char(&descriptor, '\u{4E2D}');
+    let font_id = registry
+        .load_synthetic_font(&SyntheticFont::new("Han Test").with_chars(['\u{4E2D}'], 1000))
+        .unwrap();
+    let loaded = registry.match_font_for_char(&descriptor, '\u{4E2D}');
+
+    // Then
+    assert_eq!(repeated, None);
+    assert_eq!(loaded, Some(font_id));
+}
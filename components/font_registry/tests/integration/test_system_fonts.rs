@@ -3,7 +3,7 @@
 //! These tests verify that the font_registry correctly integrates with
 //! platform_integration to load and use real system fonts.
 
-use font_registry::{FontDescriptor, FontRegistry, FontStretch};
+use font_registry::{FontDescriptor, FontRegistry, FontStretch, FontSynthesis};
 
 #[test]
 fn test_load_system_fonts_integration() {
@@ -106,6 +106,7 @@ fn test_match_system_font_by_family() {
             style: font.style,
             stretch: FontStretch::Normal,
             size: 16.0,
+            synthesis: FontSynthesis::ALL,
         };
 
         // Then: Should find exact match
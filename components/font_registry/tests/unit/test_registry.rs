@@ -1,8 +1,14 @@
 //! Unit tests for FontRegistry
 
 use font_registry::{
-    FontDescriptor, FontRegistry, FontStretch, FontStyle, FontWeight, RegistryError,
+    BasePalette, DiscoveryConfig, FamilyMatch, FontCategory, FontDescriptor, FontMetricsOverrides,
+    FontPalette, FontRangeProvider, FontRegistry, FontStretch, FontStyle, FontSynthesis,
+    FontVisibility, FontWeight, GlyphId, MatchContext, RegistryError, RegistryStats,
+    SyntheticFlags, VariationCoordinate,
 };
+use font_types::{MemoryPressureLevel, MemoryReporter, MemoryTrimmer};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
 
 // ========== FontRegistry::new() Tests ==========
 
@@ -26,7 +32,8 @@ fn test_font_registry_new_is_ready_for_operations() {
     //! Then: All operations should be available
 
     // Given
-    let registry = FontRegistry::new();
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
 
     // Then - should be able to call match_font without panicking
     let descriptor = FontDescriptor::default();
@@ -72,6 +79,25 @@ fn test_load_font_data_with_empty_data_returns_error() {
     assert!(matches!(result.unwrap_err(), RegistryError::InvalidFont(_)));
 }
 
+#[test]
+fn test_load_font_file_read_errors_keep_their_source() {
+    //! Given: A missing path and a path that can't be read as a file
+    //! When: Loading them as fonts
+    //! Then: A missing file is FileNotFound, other failures keep the I/O error
+
+    let mut registry = FontRegistry::new();
+
+    let missing = registry.load_font_file(std::path::Path::new("/nonexistent/font.ttf"));
+    assert!(matches!(missing, Err(RegistryError::FileNotFound(_))));
+
+    let directory = registry
+        .load_font_file(std::path::Path::new(env!("CARGO_MANIFEST_DIR")))
+        .unwrap_err();
+    assert!(matches!(directory, RegistryError::Io { .. }));
+    let source = std::error::Error::source(&directory).expect("I/O error source");
+    assert!(!source.to_string().is_empty());
+}
+
 // Note: Testing with valid font data requires actual font file data
 // This will be added in integration tests
 
@@ -109,6 +135,34 @@ fn test_get_font_face_with_nonexistent_id_returns_none() {
     assert_eq!(result, None);
 }
 
+#[test]
+fn test_set_font_palette_applies_to_one_face() {
+    //! Given: Two loaded faces
+    //! When: Setting a custom palette on one of them
+    //! Then: Only that face uses it, and unknown IDs are rejected
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_deduplication(false);
+    let first = registry.load_font_data(bundled_dejavu()).unwrap();
+    let second = registry.load_font_data(bundled_dejavu()).unwrap();
+    let palette = FontPalette::new(BasePalette::Dark).with_override(1, [255, 0, 0, 255]);
+
+    // When
+    registry.set_font_palette(first, palette.clone()).unwrap();
+
+    // Then
+    assert_eq!(registry.get_font_face(first).unwrap().palette, palette);
+    assert_eq!(
+        registry.get_font_face(second).unwrap().palette,
+        FontPalette::default()
+    );
+    assert_eq!(
+        registry.set_font_palette(999, palette),
+        Err(RegistryError::FontNotFound(999))
+    );
+}
+
 // ========== match_font() Tests ==========
 
 #[test]
@@ -118,13 +172,15 @@ fn test_match_font_with_empty_registry_returns_none() {
     //! Then: Should return None
 
     // Given
-    let registry = FontRegistry::new();
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
     let descriptor = FontDescriptor {
         family: vec!["Arial".to_string()],
         weight: FontWeight::Regular,
         style: FontStyle::Normal,
         stretch: FontStretch::Normal,
         size: 16.0,
+        synthesis: FontSynthesis::ALL,
     };
 
     // When
@@ -141,7 +197,8 @@ fn test_match_font_with_default_descriptor() {
     //! Then: Should return None (no fonts available)
 
     // Given
-    let registry = FontRegistry::new();
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
     let descriptor = FontDescriptor::default();
 
     // When
@@ -201,6 +258,38 @@ fn test_get_font_metrics_with_negative_size_returns_none() {
     assert_eq!(result, None);
 }
 
+#[test]
+fn test_get_font_metrics_applies_metric_overrides() {
+    //! Given: A loaded face standing in for a web font
+    //! When: Setting size-adjust and ascent/descent/line-gap overrides
+    //! Then: Metrics scale with size-adjust and take the overridden values
+
+    // Given
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(bundled_dejavu()).unwrap();
+    let plain = registry.get_font_metrics(font_id, 20.0).unwrap();
+
+    // When
+    let overrides = FontMetricsOverrides {
+        size_adjust: 0.5,
+        ascent: Some(0.9),
+        descent: Some(0.3),
+        line_gap: None,
+    };
+    registry.set_metric_overrides(font_id, overrides).unwrap();
+    let metrics = registry.get_font_metrics(font_id, 20.0).unwrap();
+
+    // Then
+    assert!((metrics.ascent - 9.0).abs() < 1e-4);
+    assert!((metrics.descent + 3.0).abs() < 1e-4);
+    assert!((metrics.line_gap - plain.line_gap / 2.0).abs() < 1e-4);
+    assert!((metrics.x_height - plain.x_height / 2.0).abs() < 1e-4);
+    assert_eq!(
+        registry.set_metric_overrides(999, overrides),
+        Err(RegistryError::FontNotFound(999))
+    );
+}
+
 // ========== load_system_fonts() Tests ==========
 
 #[test]
@@ -291,6 +380,7 @@ fn test_load_system_fonts_fonts_are_findable() {
                 style: FontStyle::Normal,
                 stretch: FontStretch::Normal,
                 size: 16.0,
+                synthesis: FontSynthesis::ALL,
             };
 
             // Note: Matching may return None if no exact family match
@@ -299,3 +389,1811 @@ fn test_load_system_fonts_fonts_are_findable() {
         }
     }
 }
+
+// ========== Checksum verification Tests ==========
+
+#[test]
+fn test_checksum_verification_disabled_by_default() {
+    //! Given: A new FontRegistry
+    //! When: Checking checksum verification
+    //! Then: It should be opt-in and no fonts flagged
+
+    let registry = FontRegistry::new();
+    assert!(!registry.checksum_verification());
+    assert!(registry.corrupted_fonts().is_empty());
+}
+
+#[test]
+fn test_load_font_data_with_bad_checksum_returns_corrupted_font() {
+    //! Given: The bundled DejaVu Sans with one table byte flipped
+    //! When: Loading it with checksum verification enabled
+    //! Then: Should return CorruptedFont naming the failing table
+
+    // Given
+    let mut data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    registry.set_checksum_verification(true);
+    assert!(registry.load_font_data(data.clone()).is_ok());
+
+    // Corrupt the last byte of the file (inside the last table or its padding)
+    let last = data.len() - 1;
+    data[last] ^= 0xFF;
+
+    // When
+    let result = registry.load_font_data(data.clone());
+
+    // Then
+    assert!(matches!(result, Err(RegistryError::CorruptedFont(_))));
+
+    // And without verification the same data still loads
+    registry.set_checksum_verification(false);
+    assert!(registry.load_font_data(data).is_ok());
+}
+
+#[test]
+fn test_load_system_fonts_with_verification_tracks_corrupted_fonts() {
+    //! Given: A FontRegistry with checksum verification enabled
+    //! When: Loading system fonts
+    //! Then: Loaded and flagged fonts together account for all discovered fonts
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_checksum_verification(true);
+
+    // When
+    let loaded = registry.load_system_fonts().unwrap();
+
+    // Then
+    assert_eq!(registry.font_count(), loaded);
+    assert!(registry.corrupted_fonts().iter().all(|path| path.exists()));
+}
+
+// ========== Monospace classification Tests ==========
+
+#[test]
+fn test_load_system_fonts_classifies_fixed_pitch_fonts() {
+    //! Given: System fonts loaded into a registry
+    //! When: Inspecting fonts whose family name says "Mono"
+    //! Then: They should be flagged as fixed pitch from the post table
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.load_system_fonts().unwrap();
+
+    // When/Then
+    for id in 0..registry.font_count() {
+        if let Some(face) = registry.get_font_face(id) {
+            if face.family_name.ends_with(" Mono") {
+                assert!(face.is_fixed_pitch, "{} not fixed pitch", face.family_name);
+            }
+        }
+    }
+}
+
+// ========== Language-specific fallback Tests ==========
+
+#[test]
+fn test_match_font_for_language_expands_generic_families() {
+    //! Given: A FontRegistry with system fonts loaded
+    //! When: Matching "sans-serif" for English content
+    //! Then: Should resolve to a platform default sans-serif family
+
+    // Given
+    let mut registry = FontRegistry::new();
+    let count = registry.load_system_fonts().unwrap();
+    if count == 0 {
+        return;
+    }
+
+    // When
+    let descriptor = FontDescriptor::default();
+    let result = registry.match_font_for_language(&descriptor, "en");
+
+    // Then
+    let defaults = platform_integration::get_default_font_families();
+    let sans = &defaults[&platform_integration::FontCategory::SansSerif];
+    if let Some(font_id) = result {
+        let face = registry.get_font_face(font_id).unwrap();
+        assert!(sans
+            .iter()
+            .any(|family| family.eq_ignore_ascii_case(&face.family_name)));
+    }
+}
+
+#[test]
+fn test_match_font_for_language_keeps_concrete_families() {
+    //! Given: An empty registry
+    //! When: Matching a concrete family for Japanese content
+    //! Then: Should return None without expanding the family
+
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
+    let descriptor = FontDescriptor {
+        family: vec!["Definitely Not Installed".to_string()],
+        ..FontDescriptor::default()
+    };
+    assert_eq!(registry.match_font_for_language(&descriptor, "ja"), None);
+}
+
+// ========== Discovery configuration Tests ==========
+
+#[test]
+fn test_discovery_config_skip_system_fonts_loads_only_extra_dirs() {
+    //! Given: A bundled font directory containing a copy of DejaVu Sans
+    //! When: Loading fonts with system fonts skipped
+    //! Then: Only the bundled font is loaded, named from its name table
+
+    // Given
+    let source = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+    );
+    let dir = std::env::temp_dir().join(format!("font_registry_bundled_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(source, dir.join("bundled-font.ttf")).unwrap();
+
+    let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig {
+        extra_dirs: vec![dir.clone()],
+        skip_system_fonts: true,
+        ..Default::default()
+    });
+
+    // When
+    let count = registry.load_system_fonts().unwrap();
+
+    // Then
+    assert_eq!(count, 1);
+    let font_id = registry.match_font(&FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        ..FontDescriptor::default()
+    });
+    let face = registry.get_font_face(font_id.unwrap()).unwrap();
+    assert_eq!(face.family_name, "DejaVu Sans");
+    assert!(!face.is_system_font());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_discovery_config_skip_system_fonts_without_extra_dirs_is_empty() {
+    let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig {
+        skip_system_fonts: true,
+        ..Default::default()
+    });
+    assert_eq!(registry.load_system_fonts().unwrap(), 0);
+    assert_eq!(registry.font_count(), 0);
+}
+
+#[test]
+fn test_load_report_lists_unparseable_font_files() {
+    //! Given: A font directory containing a file that is not a font
+    //! When: Loading fonts from it, twice
+    //! Then: The file is skipped and reported with the parse error, once
+
+    // Given
+    let dir = std::env::temp_dir().join(format!("font_registry_report_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let bad_font = dir.join("not-a-font.ttf");
+    std::fs::write(&bad_font, b"definitely not a font").unwrap();
+
+    let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig {
+        extra_dirs: vec![dir.clone()],
+        skip_system_fonts: true,
+        ..Default::default()
+    });
+    assert!(registry.load_report().is_empty());
+
+    // When
+    let count = registry.load_system_fonts().unwrap();
+
+    // Then
+    assert_eq!(count, 0);
+    let report = registry.load_report();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, bad_font);
+    assert!(matches!(report[0].error, RegistryError::InvalidFont(_)));
+
+    // A rescan replaces the report instead of appending to it
+    registry.load_system_fonts().unwrap();
+    assert_eq!(registry.load_report().len(), 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ========== Named instance Tests ==========
+
+/// Build an fvar table with wght and wdth axes and the given
+/// (subfamily name ID, wght, wdth) instances
+fn fvar_table(instances: &[(u16, f32, f32)]) -> Vec<u8> {
+    let fixed = |v: f32| ((v * 65536.0) as i32).to_be_bytes();
+    let mut fvar = Vec::new();
+    for value in [1u16, 0, 16, 2, 2, 20, instances.len() as u16, 12] {
+        fvar.extend_from_slice(&value.to_be_bytes());
+    }
+    for (tag, min, default, max) in [
+        (b"wght", 100.0, 400.0, 900.0),
+        (b"wdth", 75.0, 100.0, 100.0),
+    ] {
+        fvar.extend_from_slice(tag);
+        fvar.extend_from_slice(&fixed(min));
+        fvar.extend_from_slice(&fixed(default));
+        fvar.extend_from_slice(&fixed(max));
+        fvar.extend_from_slice(&[0, 0, 0, 0]); // flags, axisNameID
+    }
+    for &(name_id, wght, wdth) in instances {
+        fvar.extend_from_slice(&name_id.to_be_bytes());
+        fvar.extend_from_slice(&[0, 0]); // flags
+        fvar.extend_from_slice(&fixed(wght));
+        fvar.extend_from_slice(&fixed(wdth));
+    }
+    fvar
+}
+
+/// Insert an extra table into an sfnt, keeping the directory sorted
+fn insert_table(font: &[u8], tag: &[u8; 4], table: &[u8]) -> Vec<u8> {
+    let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+    let directory_end = 12 + num_tables * 16;
+
+    let mut records: Vec<[u8; 16]> = (0..num_tables)
+        .map(|i| {
+            let mut record: [u8; 16] = font[12 + i * 16..28 + i * 16].try_into().unwrap();
+            let offset = u32::from_be_bytes(record[8..12].try_into().unwrap()) + 16;
+            record[8..12].copy_from_slice(&offset.to_be_bytes());
+            record
+        })
+        .collect();
+
+    let mut body = font[directory_end..].to_vec();
+    while !body.len().is_multiple_of(4) {
+        body.push(0);
+    }
+    let mut record = [0u8; 16];
+    record[0..4].copy_from_slice(tag);
+    record[8..12].copy_from_slice(&((directory_end + 16 + body.len()) as u32).to_be_bytes());
+    record[12..16].copy_from_slice(&(table.len() as u32).to_be_bytes());
+    records.push(record);
+    records.sort_by(|a, b| a[0..4].cmp(&b[0..4]));
+    body.extend_from_slice(table);
+
+    let mut out = font[0..12].to_vec();
+    out[4..6].copy_from_slice(&((num_tables + 1) as u16).to_be_bytes());
+    for record in &records {
+        out.extend_from_slice(record);
+    }
+    out.extend_from_slice(&body);
+    out
+}
+
+/// The bundled DejaVu Sans turned into a fake variable font
+fn variable_dejavu(instances: &[(u16, f32, f32)]) -> Vec<u8> {
+    insert_table(&bundled_dejavu(), b"fvar", &fvar_table(instances))
+}
+
+#[test]
+fn test_named_instances_registered_as_faces() {
+    //! Given: A variable font with Thin, Regular (default) and Bold Condensed instances
+    //! When: Loading its data
+    //! Then: The base face plus one face per non-default instance are registered
+
+    // Given
+    let data = variable_dejavu(&[(2, 100.0, 100.0), (2, 400.0, 100.0), (0xFFFF, 700.0, 75.0)]);
+    let mut registry = FontRegistry::new();
+
+    // When
+    let base_id = registry.load_font_data(data).unwrap();
+
+    // Then
+    assert_eq!(registry.font_count(), 3);
+    let base = registry.get_font_face(base_id).unwrap();
+    assert!(base.variation_coords.is_empty());
+    assert_eq!(base.named_instance, None);
+
+    let thin = registry.get_font_face(base_id + 1).unwrap();
+    assert_eq!(thin.family_name, "DejaVu Sans");
+    assert_eq!(thin.weight, FontWeight::Thin);
+    assert_eq!(thin.stretch, FontStretch::Normal);
+    assert_eq!(thin.named_instance.as_deref(), Some("Book"));
+    assert_eq!(thin.postscript_name, "DejaVuSans-Book");
+    assert_eq!(thin.full_name, "DejaVu Sans Book");
+    assert_eq!(
+        thin.variation_coords,
+        vec![
+            VariationCoordinate {
+                tag: *b"wght",
+                value: 100.0
+            },
+            VariationCoordinate {
+                tag: *b"wdth",
+                value: 100.0
+            },
+        ]
+    );
+
+    let bold = registry.get_font_face(base_id + 2).unwrap();
+    assert_eq!(bold.weight, FontWeight::Bold);
+    assert_eq!(bold.stretch, FontStretch::Condensed);
+    assert_eq!(bold.named_instance, None);
+    assert_eq!(
+        bold.data().map(<[u8]>::as_ptr),
+        base.data().map(<[u8]>::as_ptr)
+    );
+    assert!(std::sync::Arc::ptr_eq(
+        &bold.shared_data().unwrap(),
+        &base.shared_data().unwrap()
+    ));
+    assert_eq!(
+        base.shared_data().map(|data| data.as_ptr()),
+        base.data().map(<[u8]>::as_ptr)
+    );
+    assert_eq!(bold.face_index(), 0);
+}
+
+#[test]
+fn test_named_instances_enumerated_from_base_face() {
+    //! Given: A variable font with Thin and Bold Condensed instances, loaded next to a static font
+    //! When: Enumerating faces and the variable font's named instances
+    //! Then: All faces are listed in ID order and only the instances belong to the base
+
+    // Given
+    let data = variable_dejavu(&[(2, 100.0, 100.0), (0xFFFF, 700.0, 75.0)]);
+    let static_data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    registry.set_deduplication(false);
+    let static_id = registry.load_font_data(static_data).unwrap();
+    let base_id = registry.load_font_data(data).unwrap();
+
+    // When
+    let faces = registry.font_faces();
+    let instances = registry.named_instances(base_id);
+
+    // Then
+    let ids: Vec<_> = faces.iter().map(|face| face.id).collect();
+    assert_eq!(ids, vec![static_id, base_id, base_id + 1, base_id + 2]);
+    assert_eq!(instances.len(), 2);
+    assert!(instances
+        .iter()
+        .all(|face| face.instance_of == Some(base_id)));
+    assert_eq!(instances[0].named_instance.as_deref(), Some("Book"));
+    assert_eq!(instances[0].postscript_name, "DejaVuSans-Book");
+    assert_eq!(instances[1].named_instance, None);
+    assert_eq!(instances[1].postscript_name, "DejaVuSans");
+    assert!(registry.named_instances(static_id).is_empty());
+    assert_eq!(registry.get_font_face(base_id).unwrap().instance_of, None);
+}
+
+#[test]
+fn test_named_instances_are_matchable() {
+    //! Given: A variable font with a Thin named instance
+    //! When: Matching the family at Thin weight
+    //! Then: The match is instanced at the Thin coordinates
+
+    let data = variable_dejavu(&[(2, 100.0, 100.0)]);
+    let mut registry = FontRegistry::new();
+    registry.load_font_data(data).unwrap();
+
+    let descriptor = FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        weight: FontWeight::Thin,
+        ..FontDescriptor::default()
+    };
+    let matched = registry.match_font_detailed(&descriptor).unwrap();
+    assert!(matched.variation_coords.contains(&VariationCoordinate {
+        tag: *b"wght",
+        value: 100.0
+    }));
+}
+
+// ========== Variable font matching Tests ==========
+
+#[test]
+fn test_match_font_detailed_uses_axis_range() {
+    //! Given: A variable font whose wght axis covers 100-900
+    //! When: Requesting SemiBold
+    //! Then: The variable face is returned with wght=600 and no synthetic bold
+
+    // Given
+    let data = variable_dejavu(&[]);
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let face = registry.get_font_face(font_id).unwrap();
+    assert_eq!(face.variation_axes.len(), 2);
+    assert_eq!(face.variation_axes[0].min, 100.0);
+    assert_eq!(face.variation_axes[0].max, 900.0);
+
+    // When
+    let descriptor = FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        weight: FontWeight::SemiBold,
+        stretch: FontStretch::UltraCondensed,
+        ..FontDescriptor::default()
+    };
+    let matched = registry.match_font_detailed(&descriptor).unwrap();
+
+    // Then
+    assert_eq!(matched.font_id, font_id);
+    assert_eq!(
+        matched.variation_coords,
+        vec![
+            VariationCoordinate {
+                tag: *b"wght",
+                value: 600.0
+            },
+            // Clamped to the axis minimum of 75%
+            VariationCoordinate {
+                tag: *b"wdth",
+                value: 75.0
+            },
+        ]
+    );
+    assert_eq!(matched.synthetic, SyntheticFlags::default());
+}
+
+#[test]
+fn test_match_font_detailed_static_font_reports_synthesis() {
+    //! Given: Only the static regular DejaVu Sans
+    //! When: Requesting bold italic
+    //! Then: Both bold and italic must be synthesized
+
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+
+    let descriptor = FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        weight: FontWeight::Bold,
+        style: FontStyle::Italic,
+        ..FontDescriptor::default()
+    };
+    let matched = registry.match_font_detailed(&descriptor).unwrap();
+    assert_eq!(matched.font_id, font_id);
+    assert!(matched.variation_coords.is_empty());
+    assert_eq!(
+        matched.synthetic,
+        SyntheticFlags {
+            bold: true,
+            italic: true
+        }
+    );
+    assert_eq!(registry.match_font(&descriptor), Some(font_id));
+}
+
+#[test]
+fn test_match_font_detailed_honors_font_synthesis() {
+    //! Given: Only the regular DejaVu Sans
+    //! When: Requesting bold italic with synthesis limited or disabled
+    //! Then: Only the allowed styling is reported, and the same face matches
+
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(bundled_dejavu()).unwrap();
+    let request = |synthesis| FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        weight: FontWeight::Bold,
+        style: FontStyle::Italic,
+        synthesis,
+        ..FontDescriptor::default()
+    };
+
+    let style_only = FontSynthesis {
+        style: true,
+        ..FontSynthesis::NONE
+    };
+    let matched = registry.match_font_detailed(&request(style_only)).unwrap();
+    assert_eq!(matched.font_id, font_id);
+    assert_eq!(
+        matched.synthetic,
+        SyntheticFlags {
+            bold: false,
+            italic: true
+        }
+    );
+
+    let matched = registry
+        .match_font_detailed(&request(FontSynthesis::NONE))
+        .unwrap();
+    assert_eq!(matched.font_id, font_id);
+    assert_eq!(matched.synthetic, SyntheticFlags::default());
+}
+
+// ========== Width class Tests ==========
+
+/// The bundled DejaVu Sans
+fn bundled_dejavu() -> Vec<u8> {
+    std::fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+    ))
+    .unwrap()
+}
+
+/// Offset of the table directory record for `tag`
+fn table_record(font: &[u8], tag: &[u8; 4]) -> usize {
+    let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+    (0..num_tables)
+        .map(|i| 12 + i * 16)
+        .find(|&record| &font[record..record + 4] == tag)
+        .unwrap()
+}
+
+/// Offset and length of the table `tag`
+fn table_range(font: &[u8], tag: &[u8; 4]) -> (usize, usize) {
+    let record = table_record(font, tag);
+    let read = |at: usize| u32::from_be_bytes(font[at..at + 4].try_into().unwrap()) as usize;
+    (read(record + 8), read(record + 12))
+}
+
+/// The bundled DejaVu Sans with its OS/2 usWeightClass replaced
+fn dejavu_with_weight_class(weight_class: u16) -> Vec<u8> {
+    let mut data = bundled_dejavu();
+    let (offset, _) = table_range(&data, b"OS/2");
+    data[offset + 4..offset + 6].copy_from_slice(&weight_class.to_be_bytes());
+    data
+}
+
+/// The bundled DejaVu Sans with its OS/2 usWidthClass replaced
+fn dejavu_with_width_class(width_class: u16) -> Vec<u8> {
+    let mut data = bundled_dejavu();
+    let (offset, _) = table_range(&data, b"OS/2");
+    data[offset + 6..offset + 8].copy_from_slice(&width_class.to_be_bytes());
+    data
+}
+
+#[test]
+fn test_load_font_data_reads_width_class() {
+    //! Given: DejaVu Sans with each OS/2 width class
+    //! When: Loading it
+    //! Then: The face's stretch follows usWidthClass, with invalid classes Normal
+
+    for (width_class, expected) in [
+        (1, FontStretch::UltraCondensed),
+        (3, FontStretch::Condensed),
+        (5, FontStretch::Normal),
+        (7, FontStretch::Expanded),
+        (9, FontStretch::UltraExpanded),
+        (0, FontStretch::Normal),
+    ] {
+        let mut registry = FontRegistry::new();
+        let font_id = registry
+            .load_font_data(dejavu_with_width_class(width_class))
+            .unwrap();
+        assert_eq!(
+            registry.get_font_face(font_id).unwrap().stretch,
+            expected,
+            "usWidthClass {}",
+            width_class
+        );
+    }
+}
+
+#[test]
+fn test_match_font_selects_condensed_face() {
+    //! Given: A family with a normal and a condensed face
+    //! When: Matching condensed, semi-condensed and normal requests
+    //! Then: Narrow requests get the condensed face and normal ones the regular face
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_deduplication(false); // Both faces share name and version
+    let normal = registry.load_font_data(dejavu_with_width_class(5)).unwrap();
+    let condensed = registry.load_font_data(dejavu_with_width_class(3)).unwrap();
+    let request = |stretch| FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        stretch,
+        ..FontDescriptor::default()
+    };
+
+    // When/Then
+    assert_eq!(
+        registry.match_font(&request(FontStretch::Condensed)),
+        Some(condensed)
+    );
+    assert_eq!(
+        registry.match_font(&request(FontStretch::SemiCondensed)),
+        Some(condensed)
+    );
+    assert_eq!(
+        registry.match_font(&request(FontStretch::UltraCondensed)),
+        Some(condensed)
+    );
+    assert_eq!(
+        registry.match_font(&request(FontStretch::Normal)),
+        Some(normal)
+    );
+    assert_eq!(
+        registry.match_font(&request(FontStretch::Expanded)),
+        Some(normal)
+    );
+}
+
+// ========== Family index Tests ==========
+
+/// Build a name table of Windows Unicode (name ID, language ID, string) records
+fn name_table(records: &[(u16, u16, &str)]) -> Vec<u8> {
+    let strings: Vec<Vec<u8>> = records
+        .iter()
+        .map(|(_, _, text)| text.encode_utf16().flat_map(u16::to_be_bytes).collect())
+        .collect();
+    let count = records.len() as u16;
+    let mut name = Vec::new();
+    for value in [0u16, count, 6 + 12 * count] {
+        name.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut offset = 0u16;
+    for ((name_id, language_id, _), string) in records.iter().zip(&strings) {
+        for value in [3u16, 1, *language_id, *name_id, string.len() as u16, offset] {
+            name.extend_from_slice(&value.to_be_bytes());
+        }
+        offset += string.len() as u16;
+    }
+    for string in &strings {
+        name.extend_from_slice(string);
+    }
+    name
+}
+
+/// The bundled DejaVu Sans renamed to "Corten Sans", also called "コルテン" in Japanese
+fn dejavu_with_localized_family() -> Vec<u8> {
+    let mut data = bundled_dejavu();
+    let record = table_record(&data, b"name");
+    data[record..record + 4].copy_from_slice(b"namd");
+    insert_table(
+        &data,
+        b"name",
+        &name_table(&[
+            (1, 0x0409, "Corten Sans"),
+            (1, 0x0411, "コルテン"),
+            (6, 0x0409, "CortenSans"),
+        ]),
+    )
+}
+
+#[test]
+fn test_match_font_finds_localized_family_names() {
+    //! Given: A face whose family has an English and a Japanese name
+    //! When: Matching either name, in any case
+    //! Then: The face is found, and no longer after it is unloaded
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
+    let font_id = registry
+        .load_font_data(dejavu_with_localized_family())
+        .unwrap();
+    let request = |family: &str| FontDescriptor {
+        family: vec!["missing".to_string(), family.to_string()],
+        ..FontDescriptor::default()
+    };
+
+    // When/Then
+    assert_eq!(
+        registry.get_font_face(font_id).unwrap().family_name,
+        "Corten Sans"
+    );
+    for family in ["Corten Sans", "CORTEN SANS", "コルテン"] {
+        assert_eq!(
+            registry.match_font(&request(family)),
+            Some(font_id),
+            "{}",
+            family
+        );
+    }
+    registry.unload_font(font_id).unwrap();
+    assert_eq!(registry.match_font(&request("コルテン")), None);
+}
+
+#[test]
+fn test_font_face_stores_family_aliases() {
+    //! Given: A face whose family has an English and a Japanese name
+    //! When: Loading it
+    //! Then: The Japanese name is stored as an alias, the family name is not
+
+    // Given
+    let mut registry = FontRegistry::new();
+
+    // When
+    let font_id = registry
+        .load_font_data(dejavu_with_localized_family())
+        .unwrap();
+
+    // Then
+    let face = registry.get_font_face(font_id).unwrap();
+    assert_eq!(face.family_name, "Corten Sans");
+    assert_eq!(face.family_aliases, ["コルテン"]);
+}
+
+#[test]
+fn test_match_font_uses_configured_family_substitutes() {
+    //! Given: "Arial" configured to fall back to a missing family, then DejaVu Sans
+    //! When: Matching "Arial", before and after loading a face named "Arial"
+    //! Then: DejaVu Sans is used until the real family is loaded
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
+    let dejavu = registry.load_font_data(bundled_dejavu()).unwrap();
+    registry.set_family_substitutes(
+        "Arial",
+        vec!["Liberation Sans".to_string(), "DejaVu Sans".to_string()],
+    );
+    let request = FontDescriptor {
+        family: vec!["arial".to_string()],
+        ..FontDescriptor::default()
+    };
+
+    // When/Then
+    assert_eq!(registry.match_font(&request), Some(dejavu));
+
+    let mut data = bundled_dejavu();
+    let record = table_record(&data, b"name");
+    data[record..record + 4].copy_from_slice(b"namd");
+    let arial = registry
+        .load_font_data(insert_table(
+            &data,
+            b"name",
+            &name_table(&[(1, 0x0409, "Arial"), (6, 0x0409, "ArialMT")]),
+        ))
+        .unwrap();
+    assert_eq!(registry.match_font(&request), Some(arial));
+
+    registry.unload_font(arial).unwrap();
+    registry.set_family_substitutes("ARIAL", Vec::new());
+    assert!(registry.family_substitutes("Arial").is_empty());
+    assert_eq!(registry.match_font(&request), None);
+}
+
+#[test]
+fn test_explain_match_lists_scored_candidates() {
+    //! Given: DejaVu Sans, and DejaVu Serif configured as a substitute for "Times"
+    //! When: Explaining a bold italic request for a missing family, "Times" and DejaVu Sans
+    //! Then: Both faces are listed with their penalties and synthesis, best first
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
+    let serif = registry
+        .load_font_file(std::path::Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSerif.ttf"
+        )))
+        .unwrap();
+    let sans = registry.load_font_data(bundled_dejavu()).unwrap();
+    registry.set_family_substitutes("Times", vec!["DejaVu Serif".to_string()]);
+    let request = FontDescriptor {
+        family: vec![
+            "Missing".to_string(),
+            "Times".to_string(),
+            "DejaVu Sans".to_string(),
+        ],
+        weight: FontWeight::Bold,
+        style: FontStyle::Italic,
+        ..FontDescriptor::default()
+    };
+
+    // When
+    let explanation = registry.explain_match(&request);
+
+    // Then
+    assert_eq!(explanation.unmatched_families, ["Missing"]);
+    let candidates = &explanation.candidates;
+    assert_eq!(candidates.len(), 2);
+    assert_eq!(candidates[0].font_id, serif);
+    assert_eq!(
+        candidates[0].family_match,
+        FamilyMatch::Substitute {
+            requested: "Times".to_string(),
+            substitute: "DejaVu Serif".to_string(),
+        }
+    );
+    assert_eq!(candidates[1].font_id, sans);
+    assert_eq!(
+        candidates[1].family_match,
+        FamilyMatch::Family("DejaVu Sans".to_string())
+    );
+    for candidate in candidates {
+        assert_eq!(candidate.weight, 400.0);
+        assert_eq!(candidate.style, FontStyle::Normal);
+        assert_eq!(candidate.weight_penalty, 800);
+        assert_eq!(candidate.style_penalty, 1000);
+        assert_eq!(candidate.stretch_penalty, 0);
+        assert_eq!(candidate.score(), 1800);
+        assert!(candidate.synthetic.bold && candidate.synthetic.italic);
+    }
+    assert_eq!(explanation.selected, Some(serif));
+    assert_eq!(registry.match_font(&request), explanation.selected);
+}
+
+#[test]
+fn test_find_fonts_by_postscript_and_full_name() {
+    //! Given: DejaVu Sans and DejaVu Serif loaded
+    //! When: Looking faces up by PostScript and full name, as for CSS `local()`
+    //! Then: Each name kind finds its face case-insensitively, until it is unloaded
+
+    // Given
+    let mut registry = FontRegistry::new();
+    let sans = registry.load_font_data(bundled_dejavu()).unwrap();
+    let serif = registry
+        .load_font_file(std::path::Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSerif.ttf"
+        )))
+        .unwrap();
+
+    // When/Then
+    assert_eq!(registry.find_by_postscript_name("DejaVuSans"), Some(sans));
+    assert_eq!(registry.find_by_postscript_name("dejavuserif"), Some(serif));
+    assert_eq!(registry.find_by_full_name("DejaVu Serif"), Some(serif));
+    assert_eq!(registry.find_by_full_name("DEJAVU SANS"), Some(sans));
+    assert_eq!(registry.find_by_postscript_name("DejaVu Sans"), None);
+    assert_eq!(registry.find_by_full_name("DejaVuSans"), None);
+
+    registry.unload_font(serif).unwrap();
+    assert_eq!(registry.find_by_postscript_name("DejaVuSerif"), None);
+    assert_eq!(registry.find_by_full_name("DejaVu Serif"), None);
+}
+
+// ========== Italic and oblique Tests ==========
+
+/// fsSelection ITALIC bit
+const FS_ITALIC: u16 = 1;
+/// fsSelection OBLIQUE bit (OS/2 version 4+)
+const FS_OBLIQUE: u16 = 1 << 9;
+
+/// The bundled DejaVu Sans with the given fsSelection and post italic angle
+///
+/// Setting OBLIQUE upgrades OS/2 to version 4: the original table is
+/// renamed out of the way and a longer copy inserted.
+fn dejavu_with_slant(fs_selection: u16, italic_angle: f32) -> Vec<u8> {
+    let mut data = bundled_dejavu();
+    let (post, _) = table_range(&data, b"post");
+    data[post + 4..post + 8].copy_from_slice(&((italic_angle * 65536.0) as i32).to_be_bytes());
+
+    let (offset, length) = table_range(&data, b"OS/2");
+    let mut os2 = data[offset..offset + length].to_vec();
+    os2[62..64].copy_from_slice(&fs_selection.to_be_bytes());
+    if fs_selection & FS_OBLIQUE == 0 {
+        data[offset..offset + length].copy_from_slice(&os2);
+        return data;
+    }
+    os2[0..2].copy_from_slice(&4u16.to_be_bytes());
+    os2.resize(96, 0);
+    let record = table_record(&data, b"OS/2");
+    data[record..record + 4].copy_from_slice(b"OS/1");
+    insert_table(&data, b"OS/2", &os2)
+}
+
+#[test]
+fn test_load_font_data_detects_italic_and_oblique() {
+    //! Given: DejaVu Sans with different style flags and post angles
+    //! When: Loading it
+    //! Then: OBLIQUE faces and slanted faces without flags are oblique with
+    //!       their clockwise angle, ITALIC faces italic
+
+    for (fs_selection, italic_angle, expected) in [
+        (0, 0.0, FontStyle::Normal),
+        (FS_ITALIC, -11.0, FontStyle::Italic),
+        (0, -12.0, FontStyle::Oblique(12.0)),
+        (FS_ITALIC | FS_OBLIQUE, -12.0, FontStyle::Oblique(12.0)),
+        (FS_OBLIQUE, 0.0, FontStyle::Oblique(14.0)),
+    ] {
+        let mut registry = FontRegistry::new();
+        let font_id = registry
+            .load_font_data(dejavu_with_slant(fs_selection, italic_angle))
+            .unwrap();
+        assert_eq!(
+            registry.get_font_face(font_id).unwrap().style,
+            expected,
+            "fsSelection {:#x}, angle {}",
+            fs_selection,
+            italic_angle
+        );
+    }
+}
+
+#[test]
+fn test_match_font_prefers_true_italic_over_oblique() {
+    //! Given: A family with an italic face and a 12 degree oblique face
+    //! When: Matching italic, oblique and normal requests
+    //! Then: Italic gets the italic, oblique the oblique, and normal falls
+    //!       back to the oblique before the italic
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_deduplication(false); // The faces share name and version
+    let italic = registry
+        .load_font_data(dejavu_with_slant(FS_ITALIC, -11.0))
+        .unwrap();
+    let oblique = registry
+        .load_font_data(dejavu_with_slant(FS_ITALIC | FS_OBLIQUE, -12.0))
+        .unwrap();
+    let request = |style| FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        style,
+        ..FontDescriptor::default()
+    };
+
+    // When/Then
+    assert_eq!(
+        registry.match_font(&request(FontStyle::Italic)),
+        Some(italic)
+    );
+    assert_eq!(
+        registry.match_font(&request(FontStyle::Oblique(10.0))),
+        Some(oblique)
+    );
+    assert_eq!(
+        registry.match_font(&request(FontStyle::Normal)),
+        Some(oblique)
+    );
+
+    // Without the italic face, italic requests use the oblique unsynthesized
+    registry.unload_font(italic).unwrap();
+    let matched = registry
+        .match_font_detailed(&request(FontStyle::Italic))
+        .unwrap();
+    assert_eq!(matched.font_id, oblique);
+    assert!(!matched.synthetic.italic);
+}
+
+/// Build an MVAR table varying each (tag, delta) by `delta` at wght=max
+fn mvar_table(records: &[(&[u8; 4], i16)]) -> Vec<u8> {
+    let count = records.len() as u16;
+    let mut mvar = Vec::new();
+    for value in [1u16, 0, 0, 8, count, 12 + 8 * count] {
+        mvar.extend_from_slice(&value.to_be_bytes());
+    }
+    for (inner, (tag, _)) in records.iter().enumerate() {
+        mvar.extend_from_slice(*tag);
+        mvar.extend_from_slice(&[0, 0]); // outer index
+        mvar.extend_from_slice(&(inner as u16).to_be_bytes());
+    }
+    // Item variation store: format, region list at 12, one item data at 28
+    mvar.extend_from_slice(&[0, 1, 0, 0, 0, 12, 0, 1, 0, 0, 0, 28]);
+    // One region peaking at wght=max, ignoring wdth
+    for value in [2u16, 1, 0, 0x4000, 0x4000, 0, 0, 0] {
+        mvar.extend_from_slice(&value.to_be_bytes());
+    }
+    for value in [count, 1, 1, 0] {
+        mvar.extend_from_slice(&value.to_be_bytes());
+    }
+    for (_, delta) in records {
+        mvar.extend_from_slice(&delta.to_be_bytes());
+    }
+    mvar
+}
+
+#[test]
+fn test_named_instance_metrics_apply_mvar() {
+    //! Given: A variable font whose MVAR raises the ascender by 100 at wght=900
+    //! When: Loading it with a Bold (wght=700) named instance
+    //! Then: The instance face's ascenders are raised by 60 units
+
+    // Given
+    let data = variable_dejavu(&[(2, 700.0, 100.0)]);
+    let data = insert_table(&data, b"MVAR", &mvar_table(&[(b"hasc", 100)]));
+    let mut registry = FontRegistry::new();
+
+    // When
+    let base_id = registry.load_font_data(data).unwrap();
+
+    // Then
+    let base = registry.get_font_face(base_id).unwrap().metrics;
+    let bold = registry.get_font_face(base_id + 1).unwrap().metrics;
+    assert_eq!(bold.hhea_ascent, base.hhea_ascent + 60.0);
+    assert_eq!(bold.typo_ascent, base.typo_ascent + 60.0);
+    assert_eq!(bold.ascent, base.ascent + 60.0);
+    assert_eq!(bold.descent, base.descent);
+    assert_eq!(bold.x_height, base.x_height);
+}
+
+#[test]
+fn test_matched_font_metrics_follow_variation_coords() {
+    //! Given: A variable font whose MVAR varies x-height and win descent
+    //! When: Getting metrics for a match instanced at wght=900
+    //! Then: The full deltas are applied, while default coords match get_font_metrics
+
+    // Given
+    let data = variable_dejavu(&[]);
+    let data = insert_table(
+        &data,
+        b"MVAR",
+        &mvar_table(&[(b"hcld", 40), (b"xhgt", -30)]),
+    );
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let upem = registry
+        .get_font_face(font_id)
+        .unwrap()
+        .metrics
+        .units_per_em as f32;
+    let base = registry.get_font_metrics(font_id, upem).unwrap();
+    let descriptor = FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        weight: FontWeight::Black,
+        ..FontDescriptor::default()
+    };
+
+    // When
+    let matched = registry.match_font_detailed(&descriptor).unwrap();
+    let metrics = registry.get_matched_font_metrics(&matched, upem).unwrap();
+
+    // Then
+    assert_eq!(metrics.x_height, base.x_height - 30.0);
+    // win_descent is negative, so a larger usWinDescent lowers it
+    assert_eq!(metrics.win_descent, base.win_descent - 40.0);
+    assert_eq!(metrics.ascent, base.ascent);
+
+    let regular = registry
+        .match_font_detailed(&FontDescriptor {
+            family: vec!["DejaVu Sans".to_string()],
+            ..FontDescriptor::default()
+        })
+        .unwrap();
+    assert_eq!(
+        registry.get_matched_font_metrics(&regular, upem),
+        Some(base)
+    );
+    assert_eq!(registry.get_matched_font_metrics(&matched, 0.0), None);
+}
+
+// ========== Numeric weight Tests ==========
+
+#[test]
+fn test_named_instance_keeps_intermediate_weight() {
+    //! Given: A variable font with a wght=450 named instance
+    //! When: Loading it and matching weight 450
+    //! Then: The instance face reports weight 450 and is instanced at 450
+
+    let data = variable_dejavu(&[(2, 450.0, 100.0)]);
+    let mut registry = FontRegistry::new();
+    let base_id = registry.load_font_data(data).unwrap();
+    assert_eq!(
+        registry.get_font_face(base_id + 1).unwrap().weight.value(),
+        450
+    );
+
+    let descriptor = FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        weight: FontWeight::new(450),
+        ..FontDescriptor::default()
+    };
+    let matched = registry.match_font_detailed(&descriptor).unwrap();
+    assert!(matched.variation_coords.contains(&VariationCoordinate {
+        tag: *b"wght",
+        value: 450.0
+    }));
+}
+
+#[test]
+fn test_match_font_weight_follows_css_search_direction() {
+    //! Given: DejaVu Sans Regular (400) and Bold (700)
+    //! When: Requesting weights equidistant or between the two
+    //! Then: Heavier requests prefer the bold face, lighter ones the regular face
+
+    let mut registry = FontRegistry::new();
+    registry.set_deduplication(false); // Both faces share name and version
+    let regular = bundled_dejavu();
+    let bold = dejavu_with_weight_class(700);
+    let regular_id = registry.load_font_data(regular).unwrap();
+    let bold_id = registry.load_font_data(bold).unwrap();
+
+    let request = |weight: u16| FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        weight: FontWeight::new(weight),
+        ..FontDescriptor::default()
+    };
+    // 550 is equidistant, but requests above 500 search heavier weights first
+    assert_eq!(registry.match_font(&request(550)), Some(bold_id));
+    assert_eq!(registry.match_font(&request(450)), Some(regular_id));
+    assert_eq!(registry.match_font(&request(350)), Some(regular_id));
+    assert_eq!(registry.match_font(&request(650)), Some(bold_id));
+}
+
+// ========== Unloading and font set Tests ==========
+
+#[test]
+fn test_unload_font_removes_face() {
+    //! Given: A registry with one loaded font
+    //! When: Unloading it
+    //! Then: It is no longer returned or matched, and unloading again fails
+
+    // Given
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
+    let font_id = registry.load_font_data(data).unwrap();
+
+    // When
+    registry.unload_font(font_id).unwrap();
+
+    // Then
+    assert_eq!(registry.font_count(), 0);
+    assert!(registry.get_font_face(font_id).is_none());
+    assert_eq!(registry.match_font(&FontDescriptor::default()), None);
+    assert_eq!(
+        registry.unload_font(font_id),
+        Err(RegistryError::FontNotFound(font_id))
+    );
+}
+
+#[test]
+fn test_unload_font_set_drops_document_fonts() {
+    //! Given: A document font set with a variable font (and its instances)
+    //!        plus a font loaded outside the set
+    //! When: Unloading the set
+    //! Then: Only the set's fonts are removed and the set is gone
+
+    // Given
+    let variable = variable_dejavu(&[(2, 100.0, 100.0)]);
+    let other = std::fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tests/fixtures/fonts/DejaVuSerif.ttf"
+    ))
+    .unwrap();
+    let mut registry = FontRegistry::new();
+    let kept = registry.load_font_data(other).unwrap();
+    let set = registry.create_font_set();
+    let base = registry.load_font_data_into_set(set, variable).unwrap();
+    assert_eq!(registry.font_set_fonts(set), Some(&[base, base + 1][..]));
+
+    // When
+    let unloaded = registry.unload_font_set(set).unwrap();
+
+    // Then
+    assert_eq!(unloaded, 2);
+    assert_eq!(registry.font_count(), 1);
+    assert!(registry.get_font_face(kept).is_some());
+    assert_eq!(registry.font_set_fonts(set), None);
+    assert_eq!(
+        registry.unload_font_set(set),
+        Err(RegistryError::FontSetNotFound(set))
+    );
+}
+
+#[test]
+fn test_font_set_membership_errors() {
+    let mut registry = FontRegistry::new();
+    let set = registry.create_font_set();
+    assert_eq!(
+        registry.add_font_to_set(set, 42),
+        Err(RegistryError::FontNotFound(42))
+    );
+    assert_eq!(
+        registry.load_font_data_into_set(set + 1, vec![0, 1, 2, 3]),
+        Err(RegistryError::FontSetNotFound(set + 1))
+    );
+}
+
+#[test]
+fn test_unload_font_removes_it_from_sets() {
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let set = registry.create_font_set();
+    registry.add_font_to_set(set, font_id).unwrap();
+
+    registry.unload_font(font_id).unwrap();
+    assert_eq!(registry.font_set_fonts(set), Some(&[][..]));
+    assert_eq!(registry.unload_font_set(set), Ok(0));
+}
+
+// ========== Deduplication Tests ==========
+
+#[test]
+fn test_duplicate_font_data_maps_to_same_id() {
+    //! Given: A registry with DejaVu Sans loaded
+    //! When: Loading the same bytes again
+    //! Then: The existing FontId is returned and no face is added
+
+    // Given
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    let first = registry.load_font_data(data.clone()).unwrap();
+
+    // When
+    let second = registry.load_font_data(data).unwrap();
+
+    // Then
+    assert_eq!(first, second);
+    assert_eq!(registry.font_count(), 1);
+}
+
+#[test]
+fn test_loaded_fonts_with_same_name_but_different_bytes_stay_distinct() {
+    //! Given: A loaded font
+    //! When: Loading a byte-different copy with the same PostScript name and version
+    //! Then: It is registered as its own font
+
+    let data = bundled_dejavu();
+    let mut copy = data.clone();
+    copy.extend_from_slice(&[0, 0, 0, 0]); // trailing padding changes the bytes only
+
+    let mut registry = FontRegistry::new();
+    let first = registry.load_font_data(data).unwrap();
+    assert_ne!(registry.load_font_data(copy).unwrap(), first);
+    assert_eq!(registry.font_count(), 2);
+}
+
+#[test]
+fn test_deduplication_opt_out() {
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    registry.set_deduplication(false);
+
+    let first = registry.load_font_data(data.clone()).unwrap();
+    let second = registry.load_font_data(data).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(registry.font_count(), 2);
+}
+
+#[test]
+fn test_duplicate_can_be_reloaded_after_unload() {
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    let first = registry.load_font_data(data.clone()).unwrap();
+    registry.unload_font(first).unwrap();
+
+    let second = registry.load_font_data(data).unwrap();
+    assert_ne!(first, second);
+    assert!(registry.get_font_face(second).is_some());
+}
+
+#[test]
+fn test_font_set_does_not_own_duplicate_of_global_font() {
+    //! Given: A font loaded outside any set
+    //! When: A document set loads the same font and is unloaded
+    //! Then: The global font stays loaded
+
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    let global = registry.load_font_data(data.clone()).unwrap();
+    let set = registry.create_font_set();
+
+    assert_eq!(registry.load_font_data_into_set(set, data).unwrap(), global);
+    assert_eq!(registry.unload_font_set(set), Ok(0));
+    assert!(registry.get_font_face(global).is_some());
+}
+
+#[test]
+fn test_global_load_of_set_font_takes_it_out_of_the_set() {
+    //! Given: A font loaded into a document set
+    //! When: The same bytes are loaded outside any set and the set is unloaded
+    //! Then: The font leaves the set and stays loaded
+
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    let set = registry.create_font_set();
+    let font_id = registry.load_font_data_into_set(set, data.clone()).unwrap();
+
+    assert_eq!(registry.load_font_data(data).unwrap(), font_id);
+    assert_eq!(registry.font_set_fonts(set), Some(&[][..]));
+    assert_eq!(registry.unload_font_set(set), Ok(0));
+    assert!(registry.get_font_face(font_id).is_some());
+}
+
+#[test]
+fn test_font_shared_between_sets_unloads_with_last_set() {
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    let first_set = registry.create_font_set();
+    let second_set = registry.create_font_set();
+    let font_id = registry
+        .load_font_data_into_set(first_set, data.clone())
+        .unwrap();
+    assert_eq!(
+        registry.load_font_data_into_set(second_set, data).unwrap(),
+        font_id
+    );
+
+    assert_eq!(registry.unload_font_set(first_set), Ok(0));
+    assert!(registry.get_font_face(font_id).is_some());
+    assert_eq!(registry.unload_font_set(second_set), Ok(1));
+    assert!(registry.get_font_face(font_id).is_none());
+}
+
+// ========== Memory reporting Tests ==========
+
+#[test]
+fn test_memory_report_counts_loaded_font_data() {
+    //! Given: A registry with DejaVu Sans loaded
+    //! When: Reporting memory before and after unloading
+    //! Then: The font's data is reported while it is loaded
+
+    let data = bundled_dejavu();
+    let size = data.len();
+    let mut registry = FontRegistry::new();
+    assert_eq!(registry.memory_report().bytes, 0);
+
+    let font_id = registry.load_font_data(data).unwrap();
+    let report = registry.memory_report();
+    assert_eq!(report.bytes, size);
+    assert_eq!(report.entries, 1);
+
+    registry.unload_font(font_id).unwrap();
+    assert_eq!(registry.memory_report().bytes, 0);
+}
+
+#[test]
+fn test_trim_memory_releases_reloadable_font_data() {
+    //! Given: A font discovered on disk and one loaded from memory
+    //! When: Trimming under critical memory pressure
+    //! Then: Only the discovered font's data is released, and it reloads
+
+    let data = bundled_dejavu();
+    let dir = std::env::temp_dir().join(format!("font_registry_trim_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("trim-font.ttf"), &data).unwrap();
+
+    let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig {
+        extra_dirs: vec![dir.clone()],
+        skip_system_fonts: true,
+        ..Default::default()
+    });
+    registry.load_system_fonts().unwrap();
+    let discovered = registry
+        .match_font(&FontDescriptor {
+            family: vec!["DejaVu Sans".to_string()],
+            ..FontDescriptor::default()
+        })
+        .unwrap();
+    registry.set_deduplication(false);
+    let in_memory = registry.load_font_data(data.clone()).unwrap();
+    assert_eq!(registry.memory_report().bytes, 2 * data.len());
+
+    // When
+    let freed = registry.trim_memory(MemoryPressureLevel::Critical);
+
+    // Then
+    assert_eq!(freed, data.len());
+    assert_eq!(registry.memory_report().bytes, data.len());
+    assert!(registry.get_font_face(discovered).unwrap().data().is_none());
+    assert!(registry.get_font_face(in_memory).unwrap().data().is_some());
+    assert_eq!(
+        registry.stats(),
+        RegistryStats {
+            faces: 2,
+            resident_faces: 1,
+            resident_bytes: data.len(),
+        }
+    );
+
+    registry.ensure_font_data_loaded(discovered).unwrap();
+    assert_eq!(
+        registry.get_font_face(discovered).unwrap().data(),
+        Some(data.as_slice())
+    );
+    assert_eq!(
+        registry.ensure_font_data_loaded(99),
+        Err(RegistryError::FontNotFound(99))
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ========== Extended metrics Tests ==========
+
+#[test]
+fn test_font_metrics_include_table_sources_and_line_height() {
+    //! Given: DejaVu Sans (no USE_TYPO_METRICS)
+    //! When: Reading its metrics
+    //! Then: Layout metrics come from hhea and extended fields are populated
+
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let metrics = registry.get_font_face(font_id).unwrap().metrics;
+
+    assert!(!metrics.use_typo_metrics);
+    assert_eq!(metrics.ascent, metrics.hhea_ascent);
+    assert_eq!(metrics.descent, metrics.hhea_descent);
+    assert!(metrics.typo_ascent > 0.0);
+    assert!(metrics.win_ascent > 0.0);
+    assert!(metrics.win_descent < 0.0);
+    assert!(metrics.avg_char_width > 0.0);
+    assert!(metrics.max_advance >= metrics.avg_char_width);
+    assert_eq!(
+        metrics.line_height(),
+        metrics.ascent - metrics.descent + metrics.line_gap
+    );
+
+    // Scaled metrics keep the ratios
+    let scaled = registry.get_font_metrics(font_id, 16.0).unwrap();
+    let scale = 16.0 / metrics.units_per_em as f32;
+    assert!((scaled.line_height() - metrics.line_height() * scale).abs() < 1e-3);
+    assert!((scaled.avg_char_width - metrics.avg_char_width * scale).abs() < 1e-3);
+}
+
+// ========== Incremental loading Tests ==========
+
+/// Serves a font, but only the byte ranges fetched so far
+struct RangeServer {
+    data: Vec<u8>,
+    fetched: Arc<Mutex<Vec<Range<usize>>>>,
+}
+
+impl FontRangeProvider for RangeServer {
+    fn total_len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn read_range(&self, range: Range<usize>) -> Option<Vec<u8>> {
+        let fetched = self.fetched.lock().unwrap();
+        fetched
+            .iter()
+            .any(|f| f.start <= range.start && range.end <= f.end)
+            .then(|| self.data[range].to_vec())
+    }
+}
+
+/// Glyph ID of `c` in `data`
+fn glyph_for(data: &[u8], c: char) -> GlyphId {
+    let face = ttf_parser::Face::parse(data, 0).unwrap();
+    GlyphId {
+        id: face.glyph_index(c).unwrap().0 as u32,
+    }
+}
+
+#[test]
+fn test_incremental_font_registers_before_outlines_arrive() {
+    //! Given: DejaVu Sans served in ranges, starting with nothing
+    //! When: Fetching the ranges the registry reports until it registers
+    //! Then: The face matches like the full font, without its outline data
+
+    let data = bundled_dejavu();
+    let fetched = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = FontRegistry::new();
+    let mut rounds = 0;
+    let font_id = loop {
+        let server = RangeServer {
+            data: data.clone(),
+            fetched: Arc::clone(&fetched),
+        };
+        match registry.load_incremental_font(Box::new(server)) {
+            Ok(font_id) => break font_id,
+            Err(RegistryError::MissingFontData(ranges)) => {
+                fetched.lock().unwrap().extend(ranges);
+                rounds += 1;
+            }
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    };
+
+    // Header, then the table directory, then the tables
+    assert_eq!(rounds, 3);
+    let (glyf_offset, glyf_length) = table_range(&data, b"glyf");
+    let fetched_bytes: usize = fetched.lock().unwrap().iter().map(|r| r.len()).sum();
+    assert!(fetched_bytes <= data.len() - glyf_length);
+
+    let face = registry.get_font_face(font_id).unwrap();
+    assert_eq!(face.family_name, "DejaVu Sans");
+    assert_eq!(face.data().unwrap().len(), data.len());
+    assert!(face.data().unwrap()[glyf_offset..glyf_offset + glyf_length]
+        .iter()
+        .all(|&b| b == 0));
+    let descriptor = FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        ..FontDescriptor::default()
+    };
+    assert_eq!(registry.match_font(&descriptor), Some(font_id));
+}
+
+#[test]
+fn test_incremental_font_loads_glyphs_as_ranges_arrive() {
+    //! Given: DejaVu Sans registered from its tables without outlines
+    //! When: Fetching the ranges reported for some glyphs and loading them
+    //! Then: Exactly those glyphs become available with the original bytes
+
+    let data = bundled_dejavu();
+    let (glyf_offset, glyf_length) = table_range(&data, b"glyf");
+    let fetched = Arc::new(Mutex::new(vec![
+        0..glyf_offset,
+        glyf_offset + glyf_length..data.len(),
+    ]));
+    let mut registry = FontRegistry::new();
+    let server = RangeServer {
+        data: data.clone(),
+        fetched: Arc::clone(&fetched),
+    };
+    let font_id = registry.load_incremental_font(Box::new(server)).unwrap();
+
+    let (a, b, space) = (
+        glyph_for(&data, 'A'),
+        glyph_for(&data, 'B'),
+        glyph_for(&data, ' '),
+    );
+    assert_eq!(
+        registry.missing_glyphs(font_id, &[a, b, space]).unwrap(),
+        [a, b]
+    );
+    let ranges = registry.missing_glyph_ranges(font_id, &[a]).unwrap();
+    assert_eq!(ranges.len(), 1);
+    assert!(ranges[0].start >= glyf_offset && ranges[0].end <= glyf_offset + glyf_length);
+
+    // Nothing fetched yet: loading changes nothing
+    assert_eq!(registry.load_glyph_data(font_id, &[a]).unwrap(), [a]);
+
+    fetched.lock().unwrap().extend(ranges.clone());
+    assert!(registry.load_glyph_data(font_id, &[a]).unwrap().is_empty());
+    assert_eq!(registry.missing_glyphs(font_id, &[a, b]).unwrap(), [b]);
+    let face = registry.get_font_face(font_id).unwrap();
+    let range = ranges[0].clone();
+    assert_eq!(face.data().unwrap()[range.clone()], data[range]);
+
+    // Fully loaded fonts miss nothing
+    let full_id = registry.load_font_data(data).unwrap();
+    assert!(registry
+        .missing_glyphs(full_id, &[a, b])
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        registry.missing_glyphs(99, &[a]),
+        Err(RegistryError::FontNotFound(99))
+    );
+}
+
+#[test]
+fn test_incremental_font_loads_composite_components() {
+    //! Given: An incrementally loaded DejaVu Sans and a composite glyph (Ä)
+    //! When: Fetching the composite's own data first
+    //! Then: Its components are reported next, and loading pulls them all in
+
+    let data = bundled_dejavu();
+    let (glyf_offset, glyf_length) = table_range(&data, b"glyf");
+    let fetched = Arc::new(Mutex::new(vec![
+        0..glyf_offset,
+        glyf_offset + glyf_length..data.len(),
+    ]));
+    let mut registry = FontRegistry::new();
+    let server = RangeServer {
+        data: data.clone(),
+        fetched: Arc::clone(&fetched),
+    };
+    let font_id = registry.load_incremental_font(Box::new(server)).unwrap();
+    let a_umlaut = glyph_for(&data, 'Ä');
+
+    let own = registry.missing_glyph_ranges(font_id, &[a_umlaut]).unwrap();
+    fetched.lock().unwrap().extend(own.clone());
+    assert_eq!(
+        registry.load_glyph_data(font_id, &[a_umlaut]).unwrap(),
+        [a_umlaut]
+    );
+    let components = registry.missing_glyph_ranges(font_id, &[a_umlaut]).unwrap();
+    assert!(!components.is_empty());
+    assert!(components.iter().all(|range| !own.contains(range)));
+
+    // With the whole outline table available, one call loads every level
+    fetched
+        .lock()
+        .unwrap()
+        .push(glyf_offset..glyf_offset + glyf_length);
+    let a_ring = glyph_for(&data, 'Å');
+    assert!(registry
+        .load_glyph_data(font_id, &[a_umlaut, a_ring])
+        .unwrap()
+        .is_empty());
+    assert!(registry
+        .missing_glyph_ranges(font_id, &[a_umlaut, a_ring])
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_incremental_font_rejects_truncated_data() {
+    //! Given: Providers whose fonts end before the header, before the end
+    //!   of the table directory, or that claim an absurd length
+    //! When: Loading them incrementally
+    //! Then: Each is rejected as an invalid font instead of panicking
+
+    let data = bundled_dejavu();
+    let mut registry = FontRegistry::new();
+    for len in [4, 100] {
+        let server = RangeServer {
+            data: data[..len].to_vec(),
+            fetched: Arc::new(Mutex::new(std::iter::once(0..len).collect())),
+        };
+        assert!(matches!(
+            registry.load_incremental_font(Box::new(server)),
+            Err(RegistryError::InvalidFont(_))
+        ));
+    }
+
+    /// Claims more data than any font should have
+    struct Oversized;
+
+    impl FontRangeProvider for Oversized {
+        fn total_len(&self) -> usize {
+            usize::MAX
+        }
+
+        fn read_range(&self, _range: Range<usize>) -> Option<Vec<u8>> {
+            None
+        }
+    }
+
+    assert!(matches!(
+        registry.load_incremental_font(Box::new(Oversized)),
+        Err(RegistryError::InvalidFont(_))
+    ));
+    assert!(registry.get_font_face(0).is_none());
+}
+
+// ========== Font Visibility Tests ==========
+
+#[test]
+fn test_untrusted_matching_only_sees_allowed_installed_fonts() {
+    //! Given: Installed DejaVu fonts with only DejaVu Serif allowed
+    //! When: Matching DejaVu Sans and DejaVu Serif in both contexts
+    //! Then: Untrusted callers can't match DejaVu Sans, privileged ones can
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
+    registry.set_discovery_config(DiscoveryConfig::mock());
+    registry.load_system_fonts().unwrap();
+    registry.set_font_visibility(FontVisibility::Allowlist(vec!["dejavu serif".to_string()]));
+    let request = |family: &str| FontDescriptor {
+        family: vec![family.to_string()],
+        ..FontDescriptor::default()
+    };
+
+    // When
+    let sans = registry.match_font(&request("DejaVu Sans"));
+    let serif = registry.match_font(&request("DejaVu Serif"));
+
+    // Then
+    assert!(sans.is_some());
+    assert!(serif.is_some());
+    assert_eq!(
+        registry.match_font_in_context(&request("DejaVu Sans"), MatchContext::Untrusted),
+        None
+    );
+    assert_eq!(
+        registry.match_font_in_context(&request("DejaVu Serif"), MatchContext::Untrusted),
+        serif
+    );
+    assert!(!registry.is_font_visible(sans.unwrap(), MatchContext::Untrusted));
+    assert!(registry.is_font_visible(sans.unwrap(), MatchContext::Privileged));
+
+    registry.set_font_visibility(FontVisibility::Standard);
+    assert_eq!(
+        registry.match_font_in_context(&request("DejaVu Sans"), MatchContext::Untrusted),
+        sans
+    );
+}
+
+#[test]
+fn test_fonts_loaded_from_memory_are_visible_to_untrusted_callers() {
+    //! Given: An allowlist without DejaVu Sans
+    //! When: Loading DejaVu Sans from memory, as a web font
+    //! Then: Untrusted callers can match it
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
+    registry.set_font_visibility(FontVisibility::Allowlist(Vec::new()));
+
+    // When
+    let font_id = registry.load_font_data(bundled_dejavu()).unwrap();
+
+    // Then
+    let request = FontDescriptor {
+        family: vec!["DejaVu Sans".to_string()],
+        ..FontDescriptor::default()
+    };
+    assert_eq!(
+        registry.match_font_in_context(&request, MatchContext::Untrusted),
+        Some(font_id)
+    );
+}
+
+/// A format 12 cmap mapping each of `chars` to glyph 3
+fn cmap_table(chars: &[char]) -> Vec<u8> {
+    let mut cmap = Vec::new();
+    cmap.extend_from_slice(&[0, 0, 0, 1]); // version, numTables
+    cmap.extend_from_slice(&[0, 3, 0, 10]); // Windows, full Unicode
+    cmap.extend_from_slice(&12u32.to_be_bytes());
+    cmap.extend_from_slice(&[0, 12, 0, 0]); // format, reserved
+    cmap.extend_from_slice(&(16 + 12 * chars.len() as u32).to_be_bytes());
+    cmap.extend_from_slice(&0u32.to_be_bytes()); // language
+    cmap.extend_from_slice(&(chars.len() as u32).to_be_bytes());
+    let mut code_points: Vec<u32> = chars.iter().map(|&c| c as u32).collect();
+    code_points.sort_unstable();
+    for code_point in code_points {
+        cmap.extend_from_slice(&code_point.to_be_bytes());
+        cmap.extend_from_slice(&code_point.to_be_bytes());
+        cmap.extend_from_slice(&3u32.to_be_bytes());
+    }
+    cmap
+}
+
+/// The bundled DejaVu Sans renamed to "Corten Emoji", covering emoji and
+/// with an SVG color glyph table if `color` is set
+fn dejavu_as_emoji_font(color: bool) -> Vec<u8> {
+    let mut data = bundled_dejavu();
+    for (tag, renamed) in [(b"name", b"namd"), (b"cmap", b"cmaq")] {
+        let record = table_record(&data, tag);
+        data[record..record + 4].copy_from_slice(renamed);
+    }
+    data = insert_table(
+        &data,
+        b"name",
+        &name_table(&[(1, 0x0409, "Corten Emoji"), (6, 0x0409, "CortenEmoji")]),
+    );
+    data = insert_table(&data, b"cmap", &cmap_table(&['😀', '👍', '❤', '🎉', '🚀']));
+    if color {
+        data = insert_table(&data, b"SVG ", &[0; 10]);
+    }
+    data
+}
+
+#[test]
+fn test_emoji_fonts_detected_at_load() {
+    //! Given: DejaVu Sans, and a font covering emoji with and without color glyphs
+    //! When: Loading them
+    //! Then: Only the color emoji font is classified as emoji, and the
+    //!       "emoji" generic family matches it
+
+    // Given
+    let mut registry = FontRegistry::new();
+    registry.set_last_resort_fallback(false);
+
+    // When
+    let text = registry.load_font_data(bundled_dejavu()).unwrap();
+    let monochrome = registry
+        .load_font_data(dejavu_as_emoji_font(false))
+        .unwrap();
+    let emoji = registry.load_font_data(dejavu_as_emoji_font(true)).unwrap();
+
+    // Then
+    assert_eq!(registry.get_font_face(text).unwrap().category, None);
+    assert_eq!(registry.get_font_face(monochrome).unwrap().category, None);
+    assert_eq!(
+        registry.get_font_face(emoji).unwrap().category,
+        Some(FontCategory::Emoji)
+    );
+    assert_eq!(registry.fonts_in_category(FontCategory::Emoji), [emoji]);
+
+    let request = FontDescriptor {
+        family: vec!["emoji".to_string()],
+        ..FontDescriptor::default()
+    };
+    assert_eq!(registry.match_font(&request), Some(emoji));
+
+    registry.unload_font(emoji).unwrap();
+    assert!(registry.fonts_in_category(FontCategory::Emoji).is_empty());
+    assert_eq!(registry.match_font(&request), None);
+}
+
+#[test]
+fn test_fixed_advance_detected_from_hmtx() {
+    //! Given: DejaVu Sans Mono and DejaVu Sans
+    //! When: Loading them
+    //! Then: Only the monospaced font reports its uniform advance
+
+    // Given
+    let mut registry = FontRegistry::new();
+    let read = |file: &str| {
+        std::fs::read(format!(
+            "{}/../../tests/fixtures/fonts/{file}",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap()
+    };
+
+    // When
+    let mono = registry.load_font_data(read("DejaVuSansMono.ttf")).unwrap();
+    let sans = registry.load_font_data(read("DejaVuSans.ttf")).unwrap();
+
+    // Then
+    let mono = registry.get_font_face(mono).unwrap();
+    assert!(mono.is_fixed_pitch);
+    assert_eq!(mono.fixed_advance, Some(1233.0));
+    let sans = registry.get_font_face(sans).unwrap();
+    assert!(!sans.is_fixed_pitch);
+    assert_eq!(sans.fixed_advance, None);
+}
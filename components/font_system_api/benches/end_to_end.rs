@@ -14,7 +14,7 @@ fn bench_create_font_system(c: &mut Criterion) {
         b.iter(|| {
             let config = FontSystemConfig::default();
             let system = FontSystem::new(config);
-            black_box(system);
+            let _ = black_box(system);
         });
     });
 }
@@ -25,7 +25,7 @@ fn bench_cold_start_workflow(c: &mut Criterion) {
         b.iter(|| {
             // Create system
             let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
 
             // In real benchmark: load a font
             // system.load_font_file(font_path)
@@ -48,7 +48,7 @@ fn bench_warm_workflow(c: &mut Criterion) {
     c.bench_function("warm_workflow", |b| {
         // Pre-create system (amortize initialization)
         let config = FontSystemConfig::default();
-        let mut system = FontSystem::new(config).expect("Failed to create system");
+        let system = FontSystem::new(config).expect("Failed to create system");
 
         b.iter(|| {
             // In real benchmark: shape and render (font already loaded)
@@ -70,7 +70,7 @@ fn bench_full_page_render(c: &mut Criterion) {
         group.throughput(Throughput::Elements(*char_count as u64));
         group.bench_with_input(BenchmarkId::from_parameter(char_count), char_count, |b, &count| {
             let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
             let text: String = "a".repeat(count);
 
             b.iter(|| {
@@ -105,7 +105,7 @@ fn bench_typical_scenarios(c: &mut Criterion) {
         group.throughput(Throughput::Bytes(text.len() as u64));
         group.bench_with_input(BenchmarkId::from_parameter(name), text, |b, &text| {
             let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
 
             b.iter(|| {
                 // In real benchmark: shape and render
@@ -126,7 +126,7 @@ fn bench_typical_scenarios(c: &mut Criterion) {
 fn bench_font_switching(c: &mut Criterion) {
     c.bench_function("font_switching", |b| {
         let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
 
         b.iter(|| {
             // In real benchmark: switch between fonts
@@ -144,7 +144,7 @@ fn bench_font_switching(c: &mut Criterion) {
 fn bench_multi_font_text(c: &mut Criterion) {
     c.bench_function("multi_font_text", |b| {
         let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
         let text = "English 中文 العربية"; // Mixed scripts
 
         b.iter(|| {
@@ -165,7 +165,7 @@ fn bench_repeated_rendering(c: &mut Criterion) {
         group.throughput(Throughput::Elements(*iterations as u64));
         group.bench_with_input(BenchmarkId::from_parameter(iterations), iterations, |b, &iters| {
             let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
             let text = "Cached Text";
 
             b.iter(|| {
@@ -204,14 +204,14 @@ fn bench_system_cleanup(c: &mut Criterion) {
     c.bench_function("system_cleanup", |b| {
         b.iter(|| {
             let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
 
             // In real benchmark: load fonts and use system
             // system.load_font_file(path)
             // system.shape_text("test", &options)
 
-            // Drop explicitly to measure cleanup
-            drop(system);
+            // Hand ownership to black_box so teardown is measured
+            black_box(system);
         });
     });
 }
@@ -220,7 +220,7 @@ fn bench_system_cleanup(c: &mut Criterion) {
 fn bench_concurrent_rendering(c: &mut Criterion) {
     c.bench_function("concurrent_rendering", |b| {
         let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
         let texts = ["Text A", "Text B", "Text C", "Text D"];
 
         b.iter(|| {
@@ -241,7 +241,7 @@ fn bench_concurrent_rendering(c: &mut Criterion) {
 fn bench_realistic_content(c: &mut Criterion) {
     c.bench_function("realistic_content", |b| {
         let config = FontSystemConfig::default();
-            let mut system = FontSystem::new(config).expect("Failed to create system");
+            let system = FontSystem::new(config).expect("Failed to create system");
 
         // Realistic document with headings, paragraphs, lists
         let content = vec![
@@ -0,0 +1,1420 @@
+//! C ABI for embedding the font system from C/C++
+//!
+//! All objects cross the boundary as opaque handles that are created and
+//! destroyed explicitly:
+//!
+//! - `FontSystem` — [`font_system_create`] / [`font_system_destroy`]
+//! - `FontDescriptor` — [`font_descriptor_create`] / [`font_descriptor_destroy`]
+//! - `ShapedText` — [`font_system_shape_text`] / [`font_shaped_text_destroy`]
+//! - `LayoutResult` — [`font_system_layout_paragraph`] / [`font_layout_destroy`]
+//! - `GlyphBitmap` — [`font_system_rasterize_glyph`] / [`font_bitmap_destroy`]
+//!
+//! Fallible functions return a [`FontSystemErrorCode`] and write their result
+//! through an out pointer, which is left untouched on error. Null handles
+//! and out pointers are rejected with [`FontSystemErrorCode::NullPointer`]
+//! rather than dereferenced. Strings are NUL-terminated UTF-8. Enumerations
+//! are passed as `u32` and validated, so an out-of-range value yields
+//! [`FontSystemErrorCode::InvalidArgument`] instead of undefined behavior.
+//!
+//! The function signatures only use C types and `#[repr(C)]` structs so a
+//! header can be generated with cbindgen.
+
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use font_registry::RegistryError;
+use font_types::memory::MemoryPressureLevel;
+use font_types::types::{FontStretch, FontStyle, FontWeight, GlyphId, ShapedText};
+use glyph_renderer::types::{GlyphBitmap, RenderError, RenderMode};
+use text_layout::{
+    JustificationMode, LayoutError, LayoutOptions, LayoutResult, LineHeight, ParagraphLayout,
+    TextDirection,
+};
+use text_shaper::types::{ShapingError, ShapingOptions};
+
+use crate::types::{FontError, FontSystemConfig};
+use crate::{FontDescriptor, FontSystem};
+
+/// Result code of a C API call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontSystemErrorCode {
+    /// The call succeeded
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// An argument was out of range or not valid UTF-8
+    InvalidArgument = 2,
+    /// No font matched, or the font ID is unknown
+    FontNotFound = 3,
+    /// Font data is invalid
+    InvalidFont = 4,
+    /// Font loading failed
+    LoadError = 5,
+    /// Text shaping failed
+    ShapingError = 6,
+    /// Glyph rendering failed
+    RenderError = 7,
+    /// Paragraph layout failed
+    LayoutError = 8,
+    /// Platform or other system-level failure
+    SystemError = 9,
+    /// Memory ran out while rendering
+    OutOfMemory = 10,
+}
+
+impl From<&FontError> for FontSystemErrorCode {
+    fn from(error: &FontError) -> Self {
+        match error {
+            FontError::FontNotFound => FontSystemErrorCode::FontNotFound,
+            FontError::InvalidFont(_) => FontSystemErrorCode::InvalidFont,
+            FontError::LoadError(_) => FontSystemErrorCode::LoadError,
+            FontError::RegistryError(error) => error.into(),
+            FontError::ParseError(_) => FontSystemErrorCode::InvalidFont,
+            FontError::ShapingError(error) => error.into(),
+            FontError::RenderError(error) => error.into(),
+            FontError::LayoutError(error) => error.into(),
+            FontError::SystemError(_) => FontSystemErrorCode::SystemError,
+        }
+    }
+}
+
+impl From<&RegistryError> for FontSystemErrorCode {
+    fn from(error: &RegistryError) -> Self {
+        match error {
+            RegistryError::FontNotFound(_) | RegistryError::FontSetNotFound(_) => {
+                FontSystemErrorCode::FontNotFound
+            }
+            RegistryError::InvalidFont(_) | RegistryError::CorruptedFont(_) => {
+                FontSystemErrorCode::InvalidFont
+            }
+            RegistryError::FileNotFound(_)
+            | RegistryError::Io { .. }
+            | RegistryError::DuplicateFont
+            | RegistryError::MissingFontData(_) => FontSystemErrorCode::LoadError,
+            RegistryError::SystemFontsUnavailable => FontSystemErrorCode::SystemError,
+        }
+    }
+}
+
+impl From<&ShapingError> for FontSystemErrorCode {
+    fn from(error: &ShapingError) -> Self {
+        match error {
+            ShapingError::FontNotFound => FontSystemErrorCode::FontNotFound,
+            ShapingError::InvalidText(_) | ShapingError::InvalidLanguage(_) => {
+                FontSystemErrorCode::InvalidArgument
+            }
+            ShapingError::UnsupportedScript(_) => FontSystemErrorCode::ShapingError,
+        }
+    }
+}
+
+impl From<&RenderError> for FontSystemErrorCode {
+    fn from(error: &RenderError) -> Self {
+        match error {
+            RenderError::OutOfMemory => FontSystemErrorCode::OutOfMemory,
+            RenderError::GlyphNotFound(_) | RenderError::RasterizationFailed(_) => {
+                FontSystemErrorCode::RenderError
+            }
+        }
+    }
+}
+
+impl From<&LayoutError> for FontSystemErrorCode {
+    fn from(error: &LayoutError) -> Self {
+        match error {
+            LayoutError::InvalidOptions(_) | LayoutError::InvalidText(_) => {
+                FontSystemErrorCode::InvalidArgument
+            }
+            LayoutError::Overflow(_) => FontSystemErrorCode::LayoutError,
+        }
+    }
+}
+
+/// `style` value for an upright face
+pub const FONT_STYLE_NORMAL: u32 = 0;
+/// `style` value for an italic face
+pub const FONT_STYLE_ITALIC: u32 = 1;
+/// `style` value for an oblique face (uses the given angle)
+pub const FONT_STYLE_OBLIQUE: u32 = 2;
+
+/// `mode` value for 1-bit monochrome rendering
+pub const FONT_RENDER_MODE_MONO: u32 = 0;
+/// `mode` value for 8-bit grayscale rendering
+pub const FONT_RENDER_MODE_GRAY: u32 = 1;
+/// `mode` value for subpixel RGB rendering
+pub const FONT_RENDER_MODE_SUBPIXEL_RGB: u32 = 2;
+
+/// `level` value for low memory pressure (drops a quarter of caches)
+pub const FONT_MEMORY_PRESSURE_LOW: u32 = 0;
+/// `level` value for moderate memory pressure (drops half of caches)
+pub const FONT_MEMORY_PRESSURE_MODERATE: u32 = 1;
+/// `level` value for critical memory pressure (drops everything reloadable)
+pub const FONT_MEMORY_PRESSURE_CRITICAL: u32 = 2;
+
+/// `justification` value for left-aligned lines
+pub const FONT_JUSTIFY_LEFT: u32 = 0;
+/// `justification` value for right-aligned lines
+pub const FONT_JUSTIFY_RIGHT: u32 = 1;
+/// `justification` value for centered lines
+pub const FONT_JUSTIFY_CENTER: u32 = 2;
+/// `justification` value for fully justified lines
+pub const FONT_JUSTIFY_FULL: u32 = 3;
+
+/// A positioned glyph
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontSystemGlyph {
+    /// Glyph ID within its font
+    pub glyph_id: u32,
+    /// Font the glyph comes from
+    pub font_id: usize,
+    /// Pen position X
+    pub x: f32,
+    /// Pen position Y
+    pub y: f32,
+    /// Horizontal advance
+    pub advance_x: f32,
+    /// Vertical advance
+    pub advance_y: f32,
+    /// Horizontal positioning offset
+    pub offset_x: f32,
+    /// Vertical positioning offset
+    pub offset_y: f32,
+    /// Size relative to the shaped font size (below 1.0 for synthesized small caps)
+    pub scale: f32,
+}
+
+/// Geometry of one laid out line
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontSystemLine {
+    /// Number of glyphs in the line
+    pub glyph_count: usize,
+    /// Line width before justification
+    pub width: f32,
+    /// Line height
+    pub height: f32,
+    /// Baseline offset from the top of the line
+    pub baseline: f32,
+    /// Horizontal offset applied by justification
+    pub x_offset: f32,
+    /// Offset of the line from the top of the paragraph
+    pub y_offset: f32,
+    /// Start of the line's text range
+    pub text_start: usize,
+    /// End of the line's text range
+    pub text_end: usize,
+}
+
+/// Metadata of a rendered glyph bitmap
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FontSystemBitmapInfo {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Left bearing
+    pub left: i32,
+    /// Top bearing
+    pub top: i32,
+    /// Bytes per row
+    pub pitch: usize,
+    /// Render mode (one of the `FONT_RENDER_MODE_*` values)
+    pub mode: u32,
+}
+
+/// Return a static, NUL-terminated description of an error code
+///
+/// Takes the code as a `u32` so any value is safe to pass; codes this
+/// library doesn't define are described as "unknown error".
+#[no_mangle]
+pub extern "C" fn font_system_error_message(code: u32) -> *const c_char {
+    let message: &'static CStr = match error_code_from_u32(code) {
+        Some(FontSystemErrorCode::Ok) => c"ok",
+        Some(FontSystemErrorCode::NullPointer) => c"null pointer argument",
+        Some(FontSystemErrorCode::InvalidArgument) => c"invalid argument",
+        Some(FontSystemErrorCode::FontNotFound) => c"font not found",
+        Some(FontSystemErrorCode::InvalidFont) => c"invalid font data",
+        Some(FontSystemErrorCode::LoadError) => c"font loading failed",
+        Some(FontSystemErrorCode::ShapingError) => c"text shaping failed",
+        Some(FontSystemErrorCode::RenderError) => c"glyph rendering failed",
+        Some(FontSystemErrorCode::LayoutError) => c"paragraph layout failed",
+        Some(FontSystemErrorCode::SystemError) => c"system error",
+        Some(FontSystemErrorCode::OutOfMemory) => c"out of memory",
+        None => c"unknown error",
+    };
+    message.as_ptr()
+}
+
+// ========== FontSystem ==========
+
+/// Create a font system with the default configuration
+///
+/// Returns null if initialization fails. Release with [`font_system_destroy`].
+#[no_mangle]
+pub extern "C" fn font_system_create() -> *mut FontSystem {
+    match FontSystem::new(FontSystemConfig::default()) {
+        Ok(system) => Box::into_raw(Box::new(system)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Destroy a font system; null is ignored
+///
+/// # Safety
+///
+/// `system` must be null or a handle from [`font_system_create`] that has not
+/// been destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_destroy(system: *mut FontSystem) {
+    if !system.is_null() {
+        drop(Box::from_raw(system));
+    }
+}
+
+/// Load the platform's installed fonts, writing the count to `out_count`
+///
+/// # Safety
+///
+/// `system` must be a live font system handle and `out_count` must be valid
+/// for writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_load_system_fonts(
+    system: *mut FontSystem,
+    out_count: *mut usize,
+) -> FontSystemErrorCode {
+    let (Some(system), false) = (system.as_mut(), out_count.is_null()) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    match system.load_system_fonts() {
+        Ok(count) => {
+            *out_count = count;
+            FontSystemErrorCode::Ok
+        }
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Load a font from `len` bytes at `data` (the bytes are copied)
+///
+/// # Safety
+///
+/// `system` must be a live font system handle, `data` must be valid for `len`
+/// bytes and `out_font_id` must be valid for writes; null pointers return
+/// `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_load_font_data(
+    system: *mut FontSystem,
+    data: *const u8,
+    len: usize,
+    out_font_id: *mut usize,
+) -> FontSystemErrorCode {
+    let (Some(system), false, false) = (system.as_mut(), data.is_null(), out_font_id.is_null())
+    else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let bytes = std::slice::from_raw_parts(data, len).to_vec();
+    match system.load_font_data(bytes) {
+        Ok(font_id) => {
+            *out_font_id = font_id;
+            FontSystemErrorCode::Ok
+        }
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Number of loaded fonts (0 for a null handle)
+///
+/// # Safety
+///
+/// `system` must be null or a live font system handle.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_font_count(system: *const FontSystem) -> usize {
+    system.as_ref().map_or(0, FontSystem::font_count)
+}
+
+/// Release memory on a memory-pressure signal; `level` is one of the
+/// `FONT_MEMORY_PRESSURE_*` values
+///
+/// Writes the number of bytes released to `out_freed`.
+///
+/// # Safety
+///
+/// `system` must be a live font system handle and `out_freed` must be valid
+/// for writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_trim_memory(
+    system: *mut FontSystem,
+    level: u32,
+    out_freed: *mut usize,
+) -> FontSystemErrorCode {
+    let (Some(system), false) = (system.as_mut(), out_freed.is_null()) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let level = match level {
+        FONT_MEMORY_PRESSURE_LOW => MemoryPressureLevel::Low,
+        FONT_MEMORY_PRESSURE_MODERATE => MemoryPressureLevel::Moderate,
+        FONT_MEMORY_PRESSURE_CRITICAL => MemoryPressureLevel::Critical,
+        _ => return FontSystemErrorCode::InvalidArgument,
+    };
+    *out_freed = system.trim_memory(level);
+    FontSystemErrorCode::Ok
+}
+
+/// Find the best font for a descriptor, writing its ID to `out_font_id`
+///
+/// # Safety
+///
+/// `system` and `descriptor` must be live handles and `out_font_id` must be
+/// valid for writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_match_font(
+    system: *const FontSystem,
+    descriptor: *const FontDescriptor,
+    out_font_id: *mut usize,
+) -> FontSystemErrorCode {
+    let (Some(system), Some(descriptor), false) =
+        (system.as_ref(), descriptor.as_ref(), out_font_id.is_null())
+    else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    match system.match_font(descriptor) {
+        Some(font_id) => {
+            *out_font_id = font_id;
+            FontSystemErrorCode::Ok
+        }
+        None => FontSystemErrorCode::FontNotFound,
+    }
+}
+
+/// Find a font by its CSS `local()` name (PostScript or full name),
+/// writing its ID to `out_font_id`
+///
+/// # Safety
+///
+/// `system` must be a live handle, `name` a NUL-terminated string and
+/// `out_font_id` valid for writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_find_local_font(
+    system: *const FontSystem,
+    name: *const c_char,
+    out_font_id: *mut usize,
+) -> FontSystemErrorCode {
+    let (Some(system), Some(name), false) = (system.as_ref(), c_str(name), out_font_id.is_null())
+    else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let Ok(name) = name.to_str() else {
+        return FontSystemErrorCode::InvalidArgument;
+    };
+    match system.find_local_font(name) {
+        Some(font_id) => {
+            *out_font_id = font_id;
+            FontSystemErrorCode::Ok
+        }
+        None => FontSystemErrorCode::FontNotFound,
+    }
+}
+
+// ========== FontDescriptor ==========
+
+/// Create a descriptor with default values (sans-serif, 400, normal, 16px)
+///
+/// Release with [`font_descriptor_destroy`].
+#[no_mangle]
+pub extern "C" fn font_descriptor_create() -> *mut FontDescriptor {
+    Box::into_raw(Box::new(FontDescriptor::default()))
+}
+
+/// Destroy a descriptor; null is ignored
+///
+/// # Safety
+///
+/// `descriptor` must be null or a handle from [`font_descriptor_create`] that
+/// has not been destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn font_descriptor_destroy(descriptor: *mut FontDescriptor) {
+    if !descriptor.is_null() {
+        drop(Box::from_raw(descriptor));
+    }
+}
+
+/// Remove all families from the descriptor's fallback chain
+///
+/// # Safety
+///
+/// `descriptor` must be a live descriptor handle.
+#[no_mangle]
+pub unsafe extern "C" fn font_descriptor_clear_families(
+    descriptor: *mut FontDescriptor,
+) -> FontSystemErrorCode {
+    let Some(descriptor) = descriptor.as_mut() else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    descriptor.family.clear();
+    FontSystemErrorCode::Ok
+}
+
+/// Append a family name to the descriptor's fallback chain
+///
+/// # Safety
+///
+/// `descriptor` must be a live descriptor handle and `family` a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn font_descriptor_add_family(
+    descriptor: *mut FontDescriptor,
+    family: *const c_char,
+) -> FontSystemErrorCode {
+    let (Some(descriptor), Some(family)) = (descriptor.as_mut(), c_str(family)) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let Ok(family) = family.to_str() else {
+        return FontSystemErrorCode::InvalidArgument;
+    };
+    descriptor.family.push(family.to_string());
+    FontSystemErrorCode::Ok
+}
+
+/// Set the weight (1-1000)
+///
+/// # Safety
+///
+/// `descriptor` must be a live descriptor handle.
+#[no_mangle]
+pub unsafe extern "C" fn font_descriptor_set_weight(
+    descriptor: *mut FontDescriptor,
+    weight: u16,
+) -> FontSystemErrorCode {
+    let Some(descriptor) = descriptor.as_mut() else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    if !(FontWeight::MIN.value()..=FontWeight::MAX.value()).contains(&weight) {
+        return FontSystemErrorCode::InvalidArgument;
+    }
+    descriptor.weight = FontWeight::new(weight);
+    FontSystemErrorCode::Ok
+}
+
+/// Set the style to one of the `FONT_STYLE_*` values
+///
+/// `oblique_angle` (degrees) is only used with [`FONT_STYLE_OBLIQUE`].
+///
+/// # Safety
+///
+/// `descriptor` must be a live descriptor handle.
+#[no_mangle]
+pub unsafe extern "C" fn font_descriptor_set_style(
+    descriptor: *mut FontDescriptor,
+    style: u32,
+    oblique_angle: f32,
+) -> FontSystemErrorCode {
+    let Some(descriptor) = descriptor.as_mut() else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    descriptor.style = match style {
+        FONT_STYLE_NORMAL => FontStyle::Normal,
+        FONT_STYLE_ITALIC => FontStyle::Italic,
+        FONT_STYLE_OBLIQUE if oblique_angle.is_finite() => FontStyle::Oblique(oblique_angle),
+        _ => return FontSystemErrorCode::InvalidArgument,
+    };
+    FontSystemErrorCode::Ok
+}
+
+/// Set the stretch as a percentage of normal width (50, 62, 75, 87, 100,
+/// 112, 125, 150 or 200)
+///
+/// # Safety
+///
+/// `descriptor` must be a live descriptor handle.
+#[no_mangle]
+pub unsafe extern "C" fn font_descriptor_set_stretch(
+    descriptor: *mut FontDescriptor,
+    percent: u16,
+) -> FontSystemErrorCode {
+    let Some(descriptor) = descriptor.as_mut() else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let stretch = [
+        FontStretch::UltraCondensed,
+        FontStretch::ExtraCondensed,
+        FontStretch::Condensed,
+        FontStretch::SemiCondensed,
+        FontStretch::Normal,
+        FontStretch::SemiExpanded,
+        FontStretch::Expanded,
+        FontStretch::ExtraExpanded,
+        FontStretch::UltraExpanded,
+    ]
+    .into_iter()
+    .find(|stretch| *stretch as u16 == percent);
+    match stretch {
+        Some(stretch) => {
+            descriptor.stretch = stretch;
+            FontSystemErrorCode::Ok
+        }
+        None => FontSystemErrorCode::InvalidArgument,
+    }
+}
+
+/// Set the font size in pixels (must be positive)
+///
+/// # Safety
+///
+/// `descriptor` must be a live descriptor handle.
+#[no_mangle]
+pub unsafe extern "C" fn font_descriptor_set_size(
+    descriptor: *mut FontDescriptor,
+    size: f32,
+) -> FontSystemErrorCode {
+    let Some(descriptor) = descriptor.as_mut() else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    if !(size.is_finite() && size > 0.0) {
+        return FontSystemErrorCode::InvalidArgument;
+    }
+    descriptor.size = size;
+    FontSystemErrorCode::Ok
+}
+
+// ========== Shaping ==========
+
+/// Shape `text` with a specific font at `size` pixels (must be positive)
+/// using default shaping options
+///
+/// On success `*out_shaped` receives a handle to release with
+/// [`font_shaped_text_destroy`].
+///
+/// # Safety
+///
+/// `system` must be a live font system handle, `text` a NUL-terminated string
+/// and `out_shaped` valid for writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_shape_text(
+    system: *const FontSystem,
+    text: *const c_char,
+    font_id: usize,
+    size: f32,
+    out_shaped: *mut *mut ShapedText,
+) -> FontSystemErrorCode {
+    let (Some(system), Some(text), false) = (system.as_ref(), c_str(text), out_shaped.is_null())
+    else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let Ok(text) = text.to_str() else {
+        return FontSystemErrorCode::InvalidArgument;
+    };
+    if !(size.is_finite() && size > 0.0) {
+        return FontSystemErrorCode::InvalidArgument;
+    }
+    match system.shape_text(text, font_id, size, &default_shaping_options()) {
+        Ok(shaped) => {
+            *out_shaped = Box::into_raw(Box::new(shaped));
+            FontSystemErrorCode::Ok
+        }
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Shape `text` with the fonts matched by a descriptor, falling back for
+/// characters the first font lacks
+///
+/// On success `*out_shaped` receives a handle to release with
+/// [`font_shaped_text_destroy`].
+///
+/// # Safety
+///
+/// `system` and `descriptor` must be live handles, `text` a NUL-terminated
+/// string and `out_shaped` valid for writes; null pointers return
+/// `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_shape_text_with_fallback(
+    system: *const FontSystem,
+    text: *const c_char,
+    descriptor: *const FontDescriptor,
+    out_shaped: *mut *mut ShapedText,
+) -> FontSystemErrorCode {
+    let (Some(system), Some(text), Some(descriptor), false) = (
+        system.as_ref(),
+        c_str(text),
+        descriptor.as_ref(),
+        out_shaped.is_null(),
+    ) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let Ok(text) = text.to_str() else {
+        return FontSystemErrorCode::InvalidArgument;
+    };
+    match system.shape_text_with_fallback(text, descriptor, &default_shaping_options()) {
+        Ok(shaped) => {
+            *out_shaped = Box::into_raw(Box::new(shaped));
+            FontSystemErrorCode::Ok
+        }
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Destroy shaped text; null is ignored
+///
+/// # Safety
+///
+/// `shaped` must be null or a handle returned by a shaping call that has not
+/// been destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn font_shaped_text_destroy(shaped: *mut ShapedText) {
+    if !shaped.is_null() {
+        drop(Box::from_raw(shaped));
+    }
+}
+
+/// Number of glyphs in shaped text (0 for a null handle)
+///
+/// # Safety
+///
+/// `shaped` must be null or a live shaped text handle.
+#[no_mangle]
+pub unsafe extern "C" fn font_shaped_text_glyph_count(shaped: *const ShapedText) -> usize {
+    shaped.as_ref().map_or(0, |shaped| shaped.glyphs.len())
+}
+
+/// Copy glyph `index` of shaped text into `out_glyph`
+///
+/// # Safety
+///
+/// `shaped` must be a live shaped text handle and `out_glyph` valid for
+/// writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_shaped_text_get_glyph(
+    shaped: *const ShapedText,
+    index: usize,
+    out_glyph: *mut FontSystemGlyph,
+) -> FontSystemErrorCode {
+    let (Some(shaped), false) = (shaped.as_ref(), out_glyph.is_null()) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    match shaped.glyphs.get(index) {
+        Some(glyph) => {
+            *out_glyph = glyph.into();
+            FontSystemErrorCode::Ok
+        }
+        None => FontSystemErrorCode::InvalidArgument,
+    }
+}
+
+/// Write the total width and height of shaped text
+///
+/// # Safety
+///
+/// `shaped` must be a live shaped text handle and both out pointers valid for
+/// writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_shaped_text_get_size(
+    shaped: *const ShapedText,
+    out_width: *mut f32,
+    out_height: *mut f32,
+) -> FontSystemErrorCode {
+    let (Some(shaped), false, false) = (shaped.as_ref(), out_width.is_null(), out_height.is_null())
+    else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    *out_width = shaped.width;
+    *out_height = shaped.height;
+    FontSystemErrorCode::Ok
+}
+
+// ========== Layout ==========
+
+/// Lay out shaped text into lines no wider than `max_width`
+///
+/// `text` must be the string that was shaped; `justification` is one of the
+/// `FONT_JUSTIFY_*` values and `line_spacing` multiplies the text's content
+/// height. On success `*out_layout` receives a handle to
+/// release with [`font_layout_destroy`].
+///
+/// # Safety
+///
+/// `text` must be a NUL-terminated string, `shaped` a live shaped text handle
+/// and `out_layout` valid for writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_layout_paragraph(
+    text: *const c_char,
+    shaped: *const ShapedText,
+    max_width: f32,
+    justification: u32,
+    line_spacing: f32,
+    out_layout: *mut *mut LayoutResult,
+) -> FontSystemErrorCode {
+    let (Some(text), Some(shaped), false) = (c_str(text), shaped.as_ref(), out_layout.is_null())
+    else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let Ok(text) = text.to_str() else {
+        return FontSystemErrorCode::InvalidArgument;
+    };
+    let justification = match justification {
+        FONT_JUSTIFY_LEFT => JustificationMode::Left,
+        FONT_JUSTIFY_RIGHT => JustificationMode::Right,
+        FONT_JUSTIFY_CENTER => JustificationMode::Center,
+        FONT_JUSTIFY_FULL => JustificationMode::Justify,
+        _ => return FontSystemErrorCode::InvalidArgument,
+    };
+    let options = LayoutOptions {
+        max_width,
+        max_height: None,
+        justification,
+        line_height: LineHeight::Multiplier(line_spacing),
+        direction: TextDirection::LeftToRight,
+        ..LayoutOptions::default()
+    };
+    match ParagraphLayout::new().layout_paragraph(text, shaped, &options) {
+        Ok(layout) => {
+            *out_layout = Box::into_raw(Box::new(layout));
+            FontSystemErrorCode::Ok
+        }
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Destroy a layout result; null is ignored
+///
+/// # Safety
+///
+/// `layout` must be null or a handle from [`font_system_layout_paragraph`]
+/// that has not been destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn font_layout_destroy(layout: *mut LayoutResult) {
+    if !layout.is_null() {
+        drop(Box::from_raw(layout));
+    }
+}
+
+/// Number of lines in a layout (0 for a null handle)
+///
+/// # Safety
+///
+/// `layout` must be null or a live layout handle.
+#[no_mangle]
+pub unsafe extern "C" fn font_layout_line_count(layout: *const LayoutResult) -> usize {
+    layout.as_ref().map_or(0, |layout| layout.lines.len())
+}
+
+/// Write the total width and height of a layout
+///
+/// # Safety
+///
+/// `layout` must be a live layout handle and both out pointers valid for
+/// writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_layout_get_size(
+    layout: *const LayoutResult,
+    out_width: *mut f32,
+    out_height: *mut f32,
+) -> FontSystemErrorCode {
+    let (Some(layout), false, false) = (layout.as_ref(), out_width.is_null(), out_height.is_null())
+    else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    *out_width = layout.total_width;
+    *out_height = layout.total_height;
+    FontSystemErrorCode::Ok
+}
+
+/// Copy the geometry of line `line` into `out_line`
+///
+/// # Safety
+///
+/// `layout` must be a live layout handle and `out_line` valid for writes;
+/// null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_layout_get_line(
+    layout: *const LayoutResult,
+    line: usize,
+    out_line: *mut FontSystemLine,
+) -> FontSystemErrorCode {
+    let (Some(layout), false) = (layout.as_ref(), out_line.is_null()) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let Some(line) = layout.lines.get(line) else {
+        return FontSystemErrorCode::InvalidArgument;
+    };
+    *out_line = FontSystemLine {
+        glyph_count: line.glyphs.len(),
+        width: line.width,
+        height: line.height,
+        baseline: line.baseline,
+        x_offset: line.x_offset,
+        y_offset: line.y_offset,
+        text_start: line.text_range.0,
+        text_end: line.text_range.1,
+    };
+    FontSystemErrorCode::Ok
+}
+
+/// Copy glyph `index` of line `line` into `out_glyph`
+///
+/// # Safety
+///
+/// `layout` must be a live layout handle and `out_glyph` valid for writes;
+/// null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_layout_get_glyph(
+    layout: *const LayoutResult,
+    line: usize,
+    index: usize,
+    out_glyph: *mut FontSystemGlyph,
+) -> FontSystemErrorCode {
+    let (Some(layout), false) = (layout.as_ref(), out_glyph.is_null()) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    match layout
+        .lines
+        .get(line)
+        .and_then(|line| line.glyphs.get(index))
+    {
+        Some(glyph) => {
+            *out_glyph = glyph.into();
+            FontSystemErrorCode::Ok
+        }
+        None => FontSystemErrorCode::InvalidArgument,
+    }
+}
+
+// ========== Bitmaps ==========
+
+/// Rasterize a glyph at `size` pixels (must be positive); `mode` is one of
+/// the `FONT_RENDER_MODE_*` values
+///
+/// On success `*out_bitmap` receives a handle to release with
+/// [`font_bitmap_destroy`].
+///
+/// # Safety
+///
+/// `system` must be a live font system handle and `out_bitmap` valid for
+/// writes; null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_system_rasterize_glyph(
+    system: *const FontSystem,
+    font_id: usize,
+    glyph_id: u32,
+    size: f32,
+    mode: u32,
+    out_bitmap: *mut *mut GlyphBitmap,
+) -> FontSystemErrorCode {
+    let (Some(system), false) = (system.as_ref(), out_bitmap.is_null()) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    let Some(mode) = render_mode_from_u32(mode) else {
+        return FontSystemErrorCode::InvalidArgument;
+    };
+    if !(size.is_finite() && size > 0.0) {
+        return FontSystemErrorCode::InvalidArgument;
+    }
+    match system.rasterize_glyph(font_id, GlyphId { id: glyph_id }, size, mode) {
+        Ok(bitmap) => {
+            *out_bitmap = Box::into_raw(Box::new(bitmap));
+            FontSystemErrorCode::Ok
+        }
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Destroy a glyph bitmap; null is ignored
+///
+/// # Safety
+///
+/// `bitmap` must be null or a handle from [`font_system_rasterize_glyph`]
+/// that has not been destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn font_bitmap_destroy(bitmap: *mut GlyphBitmap) {
+    if !bitmap.is_null() {
+        drop(Box::from_raw(bitmap));
+    }
+}
+
+/// Copy the dimensions and format of a bitmap into `out_info`
+///
+/// # Safety
+///
+/// `bitmap` must be a live bitmap handle and `out_info` valid for writes;
+/// null pointers return `NullPointer`.
+#[no_mangle]
+pub unsafe extern "C" fn font_bitmap_get_info(
+    bitmap: *const GlyphBitmap,
+    out_info: *mut FontSystemBitmapInfo,
+) -> FontSystemErrorCode {
+    let (Some(bitmap), false) = (bitmap.as_ref(), out_info.is_null()) else {
+        return FontSystemErrorCode::NullPointer;
+    };
+    *out_info = FontSystemBitmapInfo {
+        width: bitmap.width,
+        height: bitmap.height,
+        left: bitmap.left,
+        top: bitmap.top,
+        pitch: bitmap.pitch,
+        mode: render_mode_to_u32(bitmap.format),
+    };
+    FontSystemErrorCode::Ok
+}
+
+/// Pointer to a bitmap's pixel rows, writing their length to `out_len`
+///
+/// The pointer stays valid until the bitmap is destroyed. Returns null for a
+/// null handle.
+///
+/// # Safety
+///
+/// `bitmap` must be null or a live bitmap handle and `out_len` null or valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn font_bitmap_data(
+    bitmap: *const GlyphBitmap,
+    out_len: *mut usize,
+) -> *const u8 {
+    let Some(bitmap) = bitmap.as_ref() else {
+        return ptr::null();
+    };
+    if !out_len.is_null() {
+        *out_len = bitmap.data.len();
+    }
+    bitmap.data.as_ptr()
+}
+
+// ========== Helpers ==========
+
+/// Borrow a C string, or `None` for a null pointer
+unsafe fn c_str<'a>(s: *const c_char) -> Option<&'a CStr> {
+    (!s.is_null()).then(|| CStr::from_ptr(s))
+}
+
+/// Shaping options used by the C API (kerning and ligatures on)
+pub(crate) fn default_shaping_options() -> ShapingOptions {
+    ShapingOptions::default()
+}
+
+fn error_code_from_u32(code: u32) -> Option<FontSystemErrorCode> {
+    [
+        FontSystemErrorCode::Ok,
+        FontSystemErrorCode::NullPointer,
+        FontSystemErrorCode::InvalidArgument,
+        FontSystemErrorCode::FontNotFound,
+        FontSystemErrorCode::InvalidFont,
+        FontSystemErrorCode::LoadError,
+        FontSystemErrorCode::ShapingError,
+        FontSystemErrorCode::RenderError,
+        FontSystemErrorCode::LayoutError,
+        FontSystemErrorCode::SystemError,
+        FontSystemErrorCode::OutOfMemory,
+    ]
+    .into_iter()
+    .find(|&known| known as u32 == code)
+}
+
+fn render_mode_from_u32(mode: u32) -> Option<RenderMode> {
+    match mode {
+        FONT_RENDER_MODE_MONO => Some(RenderMode::Mono),
+        FONT_RENDER_MODE_GRAY => Some(RenderMode::Gray),
+        FONT_RENDER_MODE_SUBPIXEL_RGB => Some(RenderMode::SubpixelRgb),
+        _ => None,
+    }
+}
+
+fn render_mode_to_u32(mode: RenderMode) -> u32 {
+    match mode {
+        RenderMode::Mono => FONT_RENDER_MODE_MONO,
+        RenderMode::Gray => FONT_RENDER_MODE_GRAY,
+        RenderMode::SubpixelRgb => FONT_RENDER_MODE_SUBPIXEL_RGB,
+    }
+}
+
+impl From<&font_types::types::PositionedGlyph> for FontSystemGlyph {
+    fn from(glyph: &font_types::types::PositionedGlyph) -> Self {
+        FontSystemGlyph {
+            glyph_id: glyph.glyph_id.id,
+            font_id: glyph.font_id,
+            x: glyph.position.x,
+            y: glyph.position.y,
+            advance_x: glyph.advance.x,
+            advance_y: glyph.advance.y,
+            offset_x: glyph.offset.x,
+            offset_y: glyph.offset.y,
+            scale: glyph.scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use font_types::types::{Point, PositionedGlyph, Vector};
+    use glyph_renderer::types::PixelFormat;
+
+    fn shaped_hello() -> ShapedText {
+        let glyphs = (0..5)
+            .map(|i| PositionedGlyph {
+                glyph_id: GlyphId { id: 10 + i },
+                font_id: 0,
+                position: Point {
+                    x: i as f32 * 8.0,
+                    y: 0.0,
+                },
+                advance: Vector { x: 8.0, y: 0.0 },
+                offset: Vector { x: 0.0, y: 0.0 },
+                scale: 1.0,
+                cluster: 0,
+                unsafe_to_break: false,
+            })
+            .collect();
+        ShapedText {
+            glyphs,
+            width: 40.0,
+            height: 16.0,
+            baseline: 12.0,
+        }
+    }
+
+    #[test]
+    fn test_ffi_system_lifecycle() {
+        // Given
+        let system = font_system_create();
+        assert!(!system.is_null());
+
+        // When
+        let count = unsafe { font_system_font_count(system) };
+
+        // Then
+        assert_eq!(count, 0);
+        let mut freed = usize::MAX;
+        assert_eq!(
+            unsafe { font_system_trim_memory(system, FONT_MEMORY_PRESSURE_CRITICAL, &mut freed) },
+            FontSystemErrorCode::Ok
+        );
+        assert_eq!(freed, 0);
+        assert_eq!(
+            unsafe { font_system_trim_memory(system, 7, &mut freed) },
+            FontSystemErrorCode::InvalidArgument
+        );
+        let mut font_id = usize::MAX;
+        assert_eq!(
+            unsafe { font_system_find_local_font(system, c"Arial-BoldMT".as_ptr(), &mut font_id) },
+            FontSystemErrorCode::FontNotFound
+        );
+        assert_eq!(
+            unsafe { font_system_find_local_font(system, ptr::null(), &mut font_id) },
+            FontSystemErrorCode::NullPointer
+        );
+        unsafe { font_system_destroy(system) };
+        unsafe { font_system_destroy(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn test_ffi_loaded_font_shapes_and_rasterizes() {
+        // Given a font loaded from memory
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let system = font_system_create();
+        let descriptor = font_descriptor_create();
+        let mut font_id = usize::MAX;
+        let mut matched = usize::MAX;
+        let mut shaped = ptr::null_mut();
+        let mut bitmap = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(
+                font_system_load_font_data(system, data.as_ptr(), data.len(), &mut font_id),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(font_system_font_count(system), 1);
+            font_descriptor_add_family(descriptor, c"DejaVu Sans".as_ptr());
+            assert_eq!(
+                font_system_match_font(system, descriptor, &mut matched),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(matched, font_id);
+
+            // When shaping and rasterizing with its ID
+            assert_eq!(
+                font_system_shape_text(system, c"Hi".as_ptr(), font_id, 16.0, &mut shaped),
+                FontSystemErrorCode::Ok
+            );
+            let mut glyph = FontSystemGlyph {
+                glyph_id: 0,
+                font_id: 0,
+                x: 0.0,
+                y: 0.0,
+                advance_x: 0.0,
+                advance_y: 0.0,
+                offset_x: 0.0,
+                offset_y: 0.0,
+                scale: 0.0,
+            };
+            assert_eq!(font_shaped_text_glyph_count(shaped), 2);
+            assert_eq!(
+                font_shaped_text_get_glyph(shaped, 0, &mut glyph),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(
+                font_system_rasterize_glyph(
+                    system,
+                    font_id,
+                    glyph.glyph_id,
+                    16.0,
+                    FONT_RENDER_MODE_GRAY,
+                    &mut bitmap
+                ),
+                FontSystemErrorCode::Ok
+            );
+
+            // Then the glyph has ink
+            let mut len = 0;
+            let pixels = std::slice::from_raw_parts(font_bitmap_data(bitmap, &mut len), len);
+            assert!(pixels.iter().any(|&coverage| coverage > 0));
+
+            font_bitmap_destroy(bitmap);
+            font_shaped_text_destroy(shaped);
+            font_descriptor_destroy(descriptor);
+            font_system_destroy(system);
+        }
+    }
+
+    #[test]
+    fn test_ffi_descriptor_construction() {
+        // Given
+        let descriptor = font_descriptor_create();
+        let family = c"DejaVu Sans";
+
+        // When
+        unsafe {
+            assert_eq!(
+                font_descriptor_clear_families(descriptor),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(
+                font_descriptor_add_family(descriptor, family.as_ptr()),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(
+                font_descriptor_set_weight(descriptor, 450),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(
+                font_descriptor_set_style(descriptor, FONT_STYLE_OBLIQUE, 12.0),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(
+                font_descriptor_set_stretch(descriptor, 75),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(
+                font_descriptor_set_size(descriptor, 24.0),
+                FontSystemErrorCode::Ok
+            );
+        }
+
+        // Then
+        let built = unsafe { &*descriptor };
+        assert_eq!(built.family, vec!["DejaVu Sans".to_string()]);
+        assert_eq!(built.weight.value(), 450);
+        assert_eq!(built.style, FontStyle::Oblique(12.0));
+        assert_eq!(built.stretch, FontStretch::Condensed);
+        assert_eq!(built.size, 24.0);
+        unsafe { font_descriptor_destroy(descriptor) };
+    }
+
+    #[test]
+    fn test_ffi_descriptor_rejects_invalid_arguments() {
+        let descriptor = font_descriptor_create();
+        unsafe {
+            assert_eq!(
+                font_descriptor_set_weight(descriptor, 0),
+                FontSystemErrorCode::InvalidArgument
+            );
+            assert_eq!(
+                font_descriptor_set_style(descriptor, 7, 0.0),
+                FontSystemErrorCode::InvalidArgument
+            );
+            assert_eq!(
+                font_descriptor_set_stretch(descriptor, 90),
+                FontSystemErrorCode::InvalidArgument
+            );
+            assert_eq!(
+                font_descriptor_set_size(descriptor, -1.0),
+                FontSystemErrorCode::InvalidArgument
+            );
+            assert_eq!(
+                font_descriptor_add_family(descriptor, ptr::null()),
+                FontSystemErrorCode::NullPointer
+            );
+            assert_eq!(
+                font_descriptor_set_size(ptr::null_mut(), 12.0),
+                FontSystemErrorCode::NullPointer
+            );
+            font_descriptor_destroy(descriptor);
+        }
+    }
+
+    #[test]
+    fn test_ffi_errors_map_to_codes() {
+        // Given a system without fonts
+        let system = font_system_create();
+        let descriptor = font_descriptor_create();
+        let mut font_id = usize::MAX;
+        let mut shaped = ptr::null_mut();
+        let mut bitmap = ptr::null_mut();
+
+        unsafe {
+            // Then matching finds at most the embedded last-resort font
+            let matched = font_system_match_font(system, descriptor, &mut font_id);
+            if cfg!(feature = "last-resort-font") {
+                assert_eq!(matched, FontSystemErrorCode::Ok);
+                assert_eq!(font_id, font_registry::LAST_RESORT_FONT_ID);
+            } else {
+                assert_eq!(matched, FontSystemErrorCode::FontNotFound);
+                assert_eq!(font_id, usize::MAX);
+            }
+
+            // And fallible operations report error codes, with an unknown
+            // font reported as such whichever component noticed
+            assert_eq!(
+                font_system_shape_text(system, c"Hello".as_ptr(), 0, 16.0, &mut shaped),
+                FontSystemErrorCode::FontNotFound
+            );
+            for size in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+                assert_eq!(
+                    font_system_shape_text(system, c"Hello".as_ptr(), 0, size, &mut shaped),
+                    FontSystemErrorCode::InvalidArgument
+                );
+                assert_eq!(
+                    font_system_rasterize_glyph(
+                        system,
+                        0,
+                        1,
+                        size,
+                        FONT_RENDER_MODE_GRAY,
+                        &mut bitmap
+                    ),
+                    FontSystemErrorCode::InvalidArgument
+                );
+            }
+            assert!(shaped.is_null());
+            assert_eq!(
+                font_system_rasterize_glyph(system, 0, 1, 16.0, 9, &mut bitmap),
+                FontSystemErrorCode::InvalidArgument
+            );
+            assert_eq!(
+                font_system_rasterize_glyph(system, 0, 1, 16.0, FONT_RENDER_MODE_GRAY, &mut bitmap),
+                FontSystemErrorCode::FontNotFound
+            );
+            assert!(bitmap.is_null());
+            font_descriptor_destroy(descriptor);
+            font_system_destroy(system);
+        }
+
+        let message = |code| unsafe { CStr::from_ptr(font_system_error_message(code)) };
+        assert_eq!(
+            message(FontSystemErrorCode::FontNotFound as u32).to_str(),
+            Ok("font not found")
+        );
+        assert_eq!(message(99).to_str(), Ok("unknown error"));
+    }
+
+    #[test]
+    fn test_ffi_shaped_text_and_layout_accessors() {
+        // Given shaped text handed out as a handle
+        let shaped = Box::into_raw(Box::new(shaped_hello()));
+        let mut glyph = FontSystemGlyph {
+            glyph_id: 0,
+            font_id: 0,
+            x: 0.0,
+            y: 0.0,
+            advance_x: 0.0,
+            advance_y: 0.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            scale: 0.0,
+        };
+
+        unsafe {
+            assert_eq!(font_shaped_text_glyph_count(shaped), 5);
+            assert_eq!(
+                font_shaped_text_get_glyph(shaped, 2, &mut glyph),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(glyph.glyph_id, 12);
+            assert_eq!(glyph.x, 16.0);
+            assert_eq!(
+                font_shaped_text_get_glyph(shaped, 5, &mut glyph),
+                FontSystemErrorCode::InvalidArgument
+            );
+
+            // When laying it out
+            let mut layout = ptr::null_mut();
+            assert_eq!(
+                font_system_layout_paragraph(
+                    c"Hello".as_ptr(),
+                    shaped,
+                    500.0,
+                    FONT_JUSTIFY_LEFT,
+                    1.2,
+                    &mut layout
+                ),
+                FontSystemErrorCode::Ok
+            );
+
+            // Then lines and glyphs are readable through the handle
+            assert_eq!(font_layout_line_count(layout), 1);
+            let mut line = std::mem::zeroed::<FontSystemLine>();
+            assert_eq!(
+                font_layout_get_line(layout, 0, &mut line),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(line.glyph_count, 5);
+            assert_eq!(
+                font_layout_get_glyph(layout, 0, 4, &mut glyph),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!(glyph.glyph_id, 14);
+            let (mut width, mut height) = (0.0, 0.0);
+            assert_eq!(
+                font_layout_get_size(layout, &mut width, &mut height),
+                FontSystemErrorCode::Ok
+            );
+            assert!(width > 0.0 && height > 0.0);
+
+            font_layout_destroy(layout);
+            font_shaped_text_destroy(shaped);
+        }
+    }
+
+    #[test]
+    fn test_ffi_bitmap_accessors() {
+        let bitmap = Box::into_raw(Box::new(GlyphBitmap {
+            width: 2,
+            height: 3,
+            left: 1,
+            top: 7,
+            pitch: 2,
+            data: vec![255; 6],
+            format: RenderMode::Gray,
+            pixel_format: PixelFormat::A8,
+        }));
+        unsafe {
+            let mut info = std::mem::zeroed::<FontSystemBitmapInfo>();
+            assert_eq!(
+                font_bitmap_get_info(bitmap, &mut info),
+                FontSystemErrorCode::Ok
+            );
+            assert_eq!((info.width, info.height, info.top), (2, 3, 7));
+            assert_eq!(info.mode, FONT_RENDER_MODE_GRAY);
+
+            let mut len = 0;
+            let data = font_bitmap_data(bitmap, &mut len);
+            assert_eq!(std::slice::from_raw_parts(data, len), &[255; 6]);
+            font_bitmap_destroy(bitmap);
+        }
+    }
+}
@@ -6,22 +6,40 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+pub mod ffi;
+pub mod prelude;
 pub mod profiling;
 mod system;
 pub mod types;
+pub mod usage;
 
 // Re-export public types
 pub use profiling::{
-    ComponentMemoryBreakdown, ComponentMemoryPercentages, MemoryProfiler, MemoryStats,
+    CacheReport, ComponentMemoryBreakdown, ComponentMemoryPercentages, FontSystemStats,
+    MemoryProfiler, MemoryStats, RegistryReport,
 };
-pub use system::{FontSystem, ShapedText};
-pub use types::{CacheConfig, FontError, FontSystemConfig};
+pub use system::FontSystem;
+pub use types::{
+    CacheConfig, CanvasTextAlign, CanvasTextBaseline, CanvasTextMetrics, CellMetrics,
+    ErrorComponent, FontError, FontSystemConfig, NotdefStats,
+};
+pub use usage::{FontUsage, UsageContext, UsageReport};
 
 // Re-export types from dependencies
 pub use font_registry::types::{FontDescriptor, FontId, FontMetrics};
-pub use font_types::types::GlyphId;
-pub use glyph_renderer::types::{GlyphBitmap, GlyphOutline, RenderMode};
-pub use text_shaper::types::ShapingOptions;
+pub use font_types::cache::EvictionPolicy;
+pub use font_types::css::{CssFont, CssFontError};
+pub use font_types::memory::{MemoryPressureLevel, MemoryReport, MemoryReporter, MemoryTrimmer};
+pub use font_types::types::{
+    BasePalette, Baseline, FontDescriptorBuilder, FontMetricsOverrides, FontPalette, FontStretch,
+    FontStyle, FontSynthesis, FontVisibility, FontWeight, GlyphId, MatchContext, PaletteOverride,
+    RunMetrics, ShapedParagraph, ShapedRun, ShapedText,
+};
+pub use glyph_renderer::svg::SvgRasterizer;
+pub use glyph_renderer::types::{GlyphBitmap, GlyphOutline, PixelFormat, RenderMode};
+pub use platform_integration::FontCategory;
 pub use text_layout::{
-    JustificationMode, LayoutLine, LayoutOptions, LayoutResult, ParagraphLayout, TextDirection,
+    JustificationMode, LayoutLine, LayoutOptions, LayoutOptionsBuilder, LayoutResult, LineHeight,
+    ParagraphLayout, TextDirection,
 };
+pub use text_shaper::types::{MissingGlyph, Script, ShapingOptions, ShapingOptionsBuilder};
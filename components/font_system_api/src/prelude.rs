@@ -0,0 +1,26 @@
+//! Common imports for embedders
+//!
+//! ```no_run
+//! use font_system_api::prelude::*;
+//!
+//! # fn main() -> Result<(), FontError> {
+//! let mut font_system = FontSystem::new(FontSystemConfig::default())?;
+//! font_system.load_system_fonts()?;
+//!
+//! let descriptor = FontDescriptor::builder().family("Inter").weight(600).build();
+//! let options = ShapingOptions::builder().language("en").build();
+//! let shaped = font_system.shape_text_with_fallback("Hello", &descriptor, &options)?;
+//!
+//! let layout = LayoutOptions::builder().max_width(320.0).build();
+//! let paragraph = ParagraphLayout::new().layout_paragraph("Hello", &shaped, &layout);
+//! # Ok(())
+//! # }
+//! ```
+
+pub use crate::{
+    CssFont, FontDescriptor, FontDescriptorBuilder, FontError, FontId, FontPalette, FontStretch,
+    FontStyle, FontSystem, FontSystemConfig, FontWeight, GlyphId, JustificationMode, LayoutOptions,
+    LayoutOptionsBuilder, LayoutResult, LineHeight, MatchContext, MemoryPressureLevel,
+    ParagraphLayout, RenderMode, Script, ShapedText, ShapingOptions, ShapingOptionsBuilder,
+    TextDirection,
+};
@@ -3,8 +3,14 @@
 //! This module provides tools to monitor and analyze memory usage across
 //! all components of the font system.
 
+use font_registry::RegistryStats;
+use font_types::memory::MemoryReport;
+use glyph_renderer::types::CacheStats;
+use text_shaper::ShapingCacheStats;
+
 /// Memory statistics for the entire font system
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryStats {
     /// Memory used by loaded font data (bytes)
     pub font_data_bytes: usize,
@@ -74,6 +80,7 @@ pub trait MemoryProfiler {
 
 /// Memory usage breakdown by component
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComponentMemoryBreakdown {
     /// Font registry memory usage
     pub font_registry_bytes: usize,
@@ -128,6 +135,7 @@ impl Default for ComponentMemoryBreakdown {
 
 /// Component memory usage as percentages
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComponentMemoryPercentages {
     /// Font registry percentage
     pub font_registry: f64,
@@ -150,6 +158,102 @@ impl Default for ComponentMemoryPercentages {
     }
 }
 
+/// Occupancy and effectiveness of one cache
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheReport {
+    /// Number of cached entries
+    pub entries: usize,
+    /// Maximum number of entries
+    pub max_entries: usize,
+    /// Memory held by the entries (bytes)
+    pub bytes: usize,
+    /// Number of lookups answered from the cache
+    pub hits: u64,
+    /// Number of lookups that missed
+    pub misses: u64,
+    /// Number of entries evicted to make room
+    pub evictions: u64,
+    /// Share of lookups that hit, from 0 to 1
+    pub hit_rate: f64,
+}
+
+impl CacheReport {
+    /// Report of a shaping cache, with its memory from `memory`
+    pub fn from_shaping(stats: ShapingCacheStats, memory: MemoryReport) -> Self {
+        Self {
+            entries: stats.current_size,
+            max_entries: stats.max_size,
+            bytes: memory.bytes,
+            hits: stats.hits,
+            misses: stats.misses,
+            evictions: stats.evictions,
+            hit_rate: stats.hit_rate,
+        }
+    }
+}
+
+impl From<CacheStats> for CacheReport {
+    fn from(stats: CacheStats) -> Self {
+        Self {
+            entries: stats.entries,
+            max_entries: stats.max_entries,
+            bytes: stats.memory_bytes,
+            hits: stats.hits,
+            misses: stats.misses,
+            evictions: stats.evictions,
+            hit_rate: stats.hit_rate,
+        }
+    }
+}
+
+/// Faces in the font registry and the font data they keep resident
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegistryReport {
+    /// Registered faces
+    pub faces: usize,
+    /// Faces whose font data is in memory
+    pub resident_faces: usize,
+    /// Font data in memory (bytes)
+    pub resident_bytes: usize,
+}
+
+impl From<RegistryStats> for RegistryReport {
+    fn from(stats: RegistryStats) -> Self {
+        Self {
+            faces: stats.faces,
+            resident_faces: stats.resident_faces,
+            resident_bytes: stats.resident_bytes,
+        }
+    }
+}
+
+/// Statistics of every cache and the registry of a font system, for
+/// devtools and `about:memory` style pages
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontSystemStats {
+    /// Loaded fonts
+    pub registry: RegistryReport,
+    /// Glyph bitmap cache
+    pub glyph_cache: CacheReport,
+    /// Glyph outline cache
+    pub outline_cache: CacheReport,
+    /// Shaping result cache
+    pub shaping_cache: CacheReport,
+}
+
+impl FontSystemStats {
+    /// Memory held by font data and all caches (bytes)
+    pub fn total_bytes(&self) -> usize {
+        self.registry.resident_bytes
+            + self.glyph_cache.bytes
+            + self.outline_cache.bytes
+            + self.shaping_cache.bytes
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,6 +299,52 @@ mod tests {
         assert!((percentages.platform_integration - 40.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_cache_report_from_glyph_and_shaping_stats() {
+        let glyphs = CacheReport::from(CacheStats {
+            entries: 3,
+            hits: 6,
+            misses: 2,
+            memory_bytes: 300,
+            max_entries: 10,
+            hit_rate: 0.75,
+            ..CacheStats::default()
+        });
+        assert_eq!(glyphs.bytes, 300);
+        assert_eq!(glyphs.max_entries, 10);
+        assert_eq!(glyphs.hit_rate, 0.75);
+
+        let shaping = CacheReport::from_shaping(
+            ShapingCacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 4,
+                current_size: 2,
+                max_size: 8,
+                hit_rate: 0.5,
+            },
+            MemoryReport {
+                bytes: 128,
+                entries: 2,
+            },
+        );
+        assert_eq!(shaping.entries, 2);
+        assert_eq!(shaping.bytes, 128);
+        assert_eq!(shaping.evictions, 4);
+
+        let stats = FontSystemStats {
+            registry: RegistryReport {
+                faces: 1,
+                resident_faces: 1,
+                resident_bytes: 1000,
+            },
+            glyph_cache: glyphs,
+            shaping_cache: shaping,
+            ..FontSystemStats::default()
+        };
+        assert_eq!(stats.total_bytes(), 1428);
+    }
+
     #[test]
     fn test_zero_total_percentages() {
         let breakdown = ComponentMemoryBreakdown::new();
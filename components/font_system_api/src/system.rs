@@ -1,21 +1,30 @@
 //! FontSystem implementation - main orchestration layer
 
+use crate::profiling::{
+    CacheReport, ComponentMemoryBreakdown, FontSystemStats, MemoryProfiler, MemoryStats,
+};
 use crate::types::{
-    CacheConfig, FontError, FontSystemConfig, GlyphCacheConfig, ShapingCacheConfig,
+    CanvasTextAlign, CanvasTextBaseline, CanvasTextMetrics, CellMetrics, FontError,
+    FontSystemConfig, NotdefStats,
+};
+use crate::usage::{FontUsage, UsageContext, UsageReport, UsageTracker};
+use font_registry::types::{FontDescriptor, FontId, FontMetrics, FontMetricsOverrides};
+use font_registry::{DiscoveryConfig, FontRegistry, RegistryError};
+use font_types::memory::{MemoryPressureLevel, MemoryReporter, MemoryTrimmer};
+use font_types::types::{
+    Baseline, Direction, FontPalette, GlyphId, MatchContext, RunMetrics, ShapedParagraph,
+    ShapedText,
 };
-use font_registry::types::{FontDescriptor, FontId, FontMetrics};
-use font_types::types::GlyphId;
-use glyph_renderer::types::{GlyphBitmap, GlyphOutline, RenderMode};
+use glyph_renderer::svg::SvgRasterizer;
+use glyph_renderer::types::{GlyphBitmap, GlyphOutline, OpenTypeFont, RenderError, RenderMode};
+use glyph_renderer::GlyphRenderer;
+use platform_integration::FontCategory;
+use std::borrow::Cow;
 use std::path::Path;
-use text_shaper::types::ShapingOptions;
-
-// ShapedText type placeholder (will be implemented in text_shaper)
-/// Shaped text result (placeholder)
-#[derive(Debug, Clone, Default)]
-pub struct ShapedText {
-    /// Placeholder for shaped text data
-    _data: Vec<u8>,
-}
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use text_shaper::types::{MissingGlyph, ShapingOptions};
+use text_shaper::{ShapingCache, TextShaper};
 
 /// Main font system orchestration structure
 ///
@@ -23,12 +32,24 @@ pub struct ShapedText {
 /// matching, shaping, and rendering. It wraps the underlying component
 /// implementations and provides a unified high-level API.
 pub struct FontSystem {
-    #[allow(dead_code)] // Will be used in Phase 2 when components are fully integrated
     config: FontSystemConfig,
+    /// Loaded fonts
+    font_registry: FontRegistry,
+    /// Glyph rasterizer and bitmap cache, locked so `&self` rendering can
+    /// fill the caches
+    glyph_renderer: Mutex<GlyphRenderer>,
+    /// Shaping results, shared by the shapers of every call
+    shaping_cache: Arc<ShapingCache>,
+    /// Clusters shaped as `.notdef` so far
+    missing_glyphs: AtomicU64,
+    /// Shaping calls that left clusters uncovered so far
+    notdef_runs: AtomicU64,
+    /// Context fonts used by shaping and rasterization are attributed to
+    usage_context: Option<UsageContext>,
+    /// Fonts used per context
+    usage: UsageTracker,
     // Component implementations will be added as dependencies are implemented
-    // font_registry: FontRegistry,
     // text_shaper: TextShaper,
-    // glyph_renderer: GlyphRenderer,
     // platform_integration: PlatformIntegration,
 }
 
@@ -53,22 +74,51 @@ impl FontSystem {
     /// let font_system = FontSystem::new(config).expect("Failed to create font system");
     /// ```
     pub fn new(config: FontSystemConfig) -> Result<Self, FontError> {
-        // For now, just create the structure with the config
-        // Full initialization will be added when dependencies are available
-        Ok(FontSystem { config })
+        let glyph_cache = &config.cache_config.glyph_cache;
+        let glyph_renderer = GlyphRenderer::with_config(glyph_renderer::CacheConfig {
+            max_entries: glyph_cache.max_entries.max(1),
+            max_memory_bytes: glyph_cache.max_memory_bytes,
+            enable_statistics: glyph_cache.enable_statistics,
+            eviction_policy: glyph_cache.eviction_policy,
+            ..Default::default()
+        });
+        let shaping_cache = &config.cache_config.shaping_cache;
+        let shaping_cache = Arc::new(ShapingCache::with_policy(
+            shaping_cache.max_entries.max(1),
+            shaping_cache.eviction_policy,
+        ));
+        shaping_cache.set_simple_shaping(config.cache_config.shaping_cache.simple_shaping);
+        shaping_cache.set_verify_keys(config.cache_config.shaping_cache.verify_keys);
+
+        let mut font_registry = FontRegistry::new();
+        if config.use_mock_platform {
+            font_registry.set_discovery_config(DiscoveryConfig::mock());
+        }
+        font_registry.set_font_visibility(config.font_visibility.clone());
+
+        Ok(FontSystem {
+            config,
+            font_registry,
+            glyph_renderer: Mutex::new(glyph_renderer),
+            shaping_cache,
+            missing_glyphs: AtomicU64::new(0),
+            notdef_runs: AtomicU64::new(0),
+            usage_context: None,
+            usage: UsageTracker::default(),
+        })
     }
 
     /// Load all system fonts
     ///
+    /// Fonts are discovered on the platform backend selected by
+    /// [`FontSystemConfig::use_mock_platform`].
+    ///
     /// # Returns
     ///
     /// * `Ok(usize)` - Number of fonts loaded
     /// * `Err(FontError)` - Failed to load system fonts
     pub fn load_system_fonts(&mut self) -> Result<usize, FontError> {
-        // TODO: Implement using platform_integration
-        Err(FontError::SystemError(
-            "Not yet implemented - waiting for platform_integration".to_string(),
-        ))
+        Ok(self.font_registry.load_system_fonts()?)
     }
 
     /// Load a font from a file
@@ -81,11 +131,8 @@ impl FontSystem {
     ///
     /// * `Ok(FontId)` - ID of the loaded font
     /// * `Err(FontError)` - Failed to load font
-    pub fn load_font_file(&mut self, _path: &Path) -> Result<FontId, FontError> {
-        // TODO: Implement using font_parser and font_registry
-        Err(FontError::LoadError(
-            "Not yet implemented - waiting for font_parser".to_string(),
-        ))
+    pub fn load_font_file(&mut self, path: &Path) -> Result<FontId, FontError> {
+        Ok(self.font_registry.load_font_file(path)?)
     }
 
     /// Load a font from memory
@@ -98,15 +145,15 @@ impl FontSystem {
     ///
     /// * `Ok(FontId)` - ID of the loaded font
     /// * `Err(FontError)` - Failed to load font
-    pub fn load_font_data(&mut self, _data: Vec<u8>) -> Result<FontId, FontError> {
-        // TODO: Implement using font_parser and font_registry
-        Err(FontError::LoadError(
-            "Not yet implemented - waiting for font_parser".to_string(),
-        ))
+    pub fn load_font_data(&mut self, data: Vec<u8>) -> Result<FontId, FontError> {
+        Ok(self.font_registry.load_font_data(data)?)
     }
 
     /// Find the best matching font for the given descriptor
     ///
+    /// Generic families resolve like in
+    /// [`shape_text_with_fallback`](Self::shape_text_with_fallback).
+    ///
     /// # Arguments
     ///
     /// * `descriptor` - Font descriptor to match
@@ -115,18 +162,97 @@ impl FontSystem {
     ///
     /// * `Some(FontId)` - ID of the best matching font
     /// * `None` - No matching font found
-    pub fn match_font(&self, _descriptor: &FontDescriptor) -> Option<FontId> {
-        // TODO: Implement using font_registry
-        None
+    pub fn match_font(&self, descriptor: &FontDescriptor) -> Option<FontId> {
+        let descriptor = self.resolve_descriptor(descriptor, &ShapingOptions::default());
+        self.font_registry
+            .match_font_in_context(&descriptor, MatchContext::Privileged)
+    }
+
+    /// Find a loaded font by the name in a CSS `src: local(...)` descriptor
+    ///
+    /// As in CSS, the name matches a face's PostScript name (e.g.
+    /// "Arial-BoldMT") or full name (e.g. "Arial Bold"), case-insensitively,
+    /// and never its family name.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(FontId)` - ID of the named font
+    /// * `None` - No loaded font has this name; try the next `src` entry
+    pub fn find_local_font(&self, name: &str) -> Option<FontId> {
+        self.find_local_font_in_context(name, MatchContext::Privileged)
+    }
+
+    /// Find a font by its `local()` name like
+    /// [`find_local_font`](Self::find_local_font), among the fonts visible
+    /// in `context`
+    ///
+    /// Stylesheets of web pages should use [`MatchContext::Untrusted`], so
+    /// `local()` can't probe for fonts [`FontSystemConfig::font_visibility`]
+    /// hides.
+    pub fn find_local_font_in_context(&self, name: &str, context: MatchContext) -> Option<FontId> {
+        self.font_registry
+            .find_by_postscript_name(name)
+            .or_else(|| self.font_registry.find_by_full_name(name))
+            .filter(|&font_id| self.font_registry.is_font_visible(font_id, context))
+    }
+
+    /// Configuration the system was created with
+    pub fn config(&self) -> &FontSystemConfig {
+        &self.config
+    }
+
+    /// Families a generic font category resolves to
+    ///
+    /// Uses [`FontSystemConfig::default_families`] when it has an entry for
+    /// `category`, otherwise the platform's defaults.
+    pub fn default_font_families(&self, category: FontCategory) -> Vec<String> {
+        if let Some(families) = self.config.default_families.get(&category) {
+            return families.clone();
+        }
+        platform_integration::get_platform_default_font_families(
+            self.font_registry.discovery_config().resolved_platform(),
+        )
+        .remove(&category)
+        .unwrap_or_default()
+    }
+
+    /// Expand generic families, apply the script size adjustment and limit
+    /// synthesis to what the configuration allows
+    fn resolve_descriptor(
+        &self,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+    ) -> FontDescriptor {
+        let mut family = Vec::with_capacity(descriptor.family.len());
+        for name in &descriptor.family {
+            if let Some(category) = FontCategory::from_generic_family(name) {
+                family.extend(self.default_font_families(category));
+            }
+            // Generic names stay too, matching fonts detected in the category
+            family.push(name.clone());
+        }
+        FontDescriptor {
+            family,
+            size: self
+                .config
+                .font_size_for_script(descriptor.size, options.script),
+            synthesis: descriptor.synthesis.intersect(self.config.font_synthesis),
+            ..descriptor.clone()
+        }
     }
 
     /// Shape text with a specific font
     ///
+    /// The size is scaled by the configured adjustment for
+    /// `options.script` (see [`FontSystemConfig::font_size_for_script`]),
+    /// and small caps are only synthesized if
+    /// [`FontSystemConfig::font_synthesis`] allows it.
+    ///
     /// # Arguments
     ///
     /// * `text` - Text to shape
     /// * `font_id` - ID of the font to use
-    /// * `size` - Font size in points
+    /// * `size` - Font size in CSS pixels
     /// * `options` - Shaping options
     ///
     /// # Returns
@@ -135,19 +261,34 @@ impl FontSystem {
     /// * `Err(FontError)` - Failed to shape text
     pub fn shape_text(
         &self,
-        _text: &str,
-        _font_id: FontId,
-        _size: f32,
-        _options: &ShapingOptions,
+        text: &str,
+        font_id: FontId,
+        size: f32,
+        options: &ShapingOptions,
     ) -> Result<ShapedText, FontError> {
-        // TODO: Implement using text_shaper
-        Err(FontError::ShapingError(
-            "Not yet implemented - waiting for text_shaper".to_string(),
-        ))
+        let size = self.config.font_size_for_script(size, options.script);
+        let options = if self.config.font_synthesis.small_caps {
+            Cow::Borrowed(options)
+        } else {
+            Cow::Owned(ShapingOptions {
+                synthesize_small_caps: false,
+                ..options.clone()
+            })
+        };
+        let shaped = self.shaper().shape_text(text, font_id, size, &options)?;
+        if let Some(context) = self.usage_context {
+            self.usage
+                .record_shaping(context, font_id, shaped.glyphs.len(), text.chars().count());
+        }
+        Ok(shaped)
     }
 
     /// Shape text with font fallback
     ///
+    /// Generic families in the descriptor resolve to
+    /// [`default_font_families`](Self::default_font_families), and its size
+    /// is scaled like in [`shape_text`](Self::shape_text).
+    ///
     /// # Arguments
     ///
     /// * `text` - Text to shape
@@ -160,23 +301,176 @@ impl FontSystem {
     /// * `Err(FontError)` - Failed to shape text
     pub fn shape_text_with_fallback(
         &self,
-        _text: &str,
-        _descriptor: &FontDescriptor,
-        _options: &ShapingOptions,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
     ) -> Result<ShapedText, FontError> {
-        // TODO: Implement using font_registry and text_shaper
-        Err(FontError::ShapingError(
-            "Not yet implemented - waiting for text_shaper".to_string(),
-        ))
+        self.shape_text_with_fallback_report(text, descriptor, options)
+            .map(|(shaped, _)| shaped)
+    }
+
+    /// Shape text with font fallback, reporting clusters no font covers
+    ///
+    /// Uncovered clusters are shaped as `.notdef` and returned as
+    /// [`MissingGlyph`]s, so embedders can log them or download fonts for
+    /// the missing scripts. They are also counted in
+    /// [`notdef_stats`](Self::notdef_stats).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((ShapedText, Vec<MissingGlyph>))` - Shaped text and its uncovered clusters
+    /// * `Err(FontError)` - Failed to shape text
+    pub fn shape_text_with_fallback_report(
+        &self,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+    ) -> Result<(ShapedText, Vec<MissingGlyph>), FontError> {
+        let (paragraph, missing) =
+            self.shape_paragraph_with_fallback_report(text, descriptor, options)?;
+        Ok((paragraph.to_shaped_text(), missing))
+    }
+
+    /// Shape text with font fallback into runs of one font each
+    ///
+    /// Like [`shape_text_with_fallback`](Self::shape_text_with_fallback),
+    /// but keeps the parts shaped with different fonts as separate runs in
+    /// visual order, so renderers can bind each font once per run.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ShapedParagraph)` - Shaped runs
+    /// * `Err(FontError)` - Failed to shape text
+    pub fn shape_paragraph(
+        &self,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+    ) -> Result<ShapedParagraph, FontError> {
+        self.shape_paragraph_with_fallback_report(text, descriptor, options)
+            .map(|(paragraph, _)| paragraph)
+    }
+
+    /// Shape text into runs like [`shape_paragraph`](Self::shape_paragraph),
+    /// reporting and counting clusters no font covers
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((ShapedParagraph, Vec<MissingGlyph>))` - Shaped runs and the uncovered clusters
+    /// * `Err(FontError)` - Failed to shape text
+    pub fn shape_paragraph_with_fallback_report(
+        &self,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+    ) -> Result<(ShapedParagraph, Vec<MissingGlyph>), FontError> {
+        let descriptor = self.resolve_descriptor(descriptor, options);
+        let (paragraph, missing) =
+            self.shaper()
+                .shape_paragraph_with_fallback_report(text, &descriptor, options)?;
+        if !missing.is_empty() {
+            self.missing_glyphs
+                .fetch_add(missing.len() as u64, Ordering::Relaxed);
+            self.notdef_runs.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(context) = self.usage_context {
+            for run in &paragraph.runs {
+                let code_points = text
+                    .get(run.text_range.clone())
+                    .map_or(0, |run_text| run_text.chars().count());
+                self.usage
+                    .record_shaping(context, run.font_id, run.glyphs.len(), code_points);
+            }
+        }
+        Ok((paragraph, missing))
+    }
+
+    /// Shaper over the loaded fonts using the system's shaping cache
+    fn shaper(&self) -> TextShaper<'_> {
+        TextShaper::with_shared_cache(&self.font_registry, Arc::clone(&self.shaping_cache))
+    }
+
+    /// Counts of clusters shaped as `.notdef` since creation or the last
+    /// [`reset_notdef_stats`](Self::reset_notdef_stats)
+    pub fn notdef_stats(&self) -> NotdefStats {
+        NotdefStats {
+            missing_glyphs: self.missing_glyphs.load(Ordering::Relaxed),
+            affected_runs: self.notdef_runs.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset the `.notdef` counters to zero
+    pub fn reset_notdef_stats(&self) {
+        self.missing_glyphs.store(0, Ordering::Relaxed);
+        self.notdef_runs.store(0, Ordering::Relaxed);
+    }
+
+    /// Attribute the fonts used by later shaping and rasterization calls to
+    /// `context`, e.g. the document being laid out and painted
+    ///
+    /// Calls made without a context aren't tracked. See
+    /// [`usage_report`](Self::usage_report).
+    pub fn set_usage_context(&mut self, context: Option<UsageContext>) {
+        self.usage_context = context;
+    }
+
+    /// Context font usage is currently attributed to
+    pub fn usage_context(&self) -> Option<UsageContext> {
+        self.usage_context
+    }
+
+    /// Fonts shaping and rasterization used in `context`, with the glyphs
+    /// and code points each one handled, for devtools and licensing audits
+    ///
+    /// Counts accumulate until [`reset_usage`](Self::reset_usage).
+    pub fn usage_report(&self, context: UsageContext) -> UsageReport {
+        let fonts = self
+            .usage
+            .counts(context)
+            .into_iter()
+            .map(|(font_id, counts)| {
+                let face = self.font_registry.get_font_face(font_id);
+                FontUsage {
+                    font_id,
+                    family_name: face
+                        .map(|face| face.family_name.clone())
+                        .unwrap_or_default(),
+                    postscript_name: face
+                        .map(|face| face.postscript_name.clone())
+                        .unwrap_or_default(),
+                    shaped_glyphs: counts.shaped_glyphs,
+                    code_points: counts.code_points,
+                    rasterized_glyphs: counts.rasterized_glyphs,
+                }
+            })
+            .collect();
+        UsageReport { context, fonts }
+    }
+
+    /// Forget the font usage recorded for `context`, e.g. when its document
+    /// is closed
+    pub fn reset_usage(&self, context: UsageContext) {
+        self.usage.reset(context);
+    }
+
+    /// Count a rasterized glyph in the current usage context
+    fn record_rasterization(&self, font_id: FontId) {
+        if let Some(context) = self.usage_context {
+            self.usage.record_rasterization(context, font_id);
+        }
     }
 
     /// Rasterize a glyph to a bitmap
     ///
+    /// `size` is the size the glyph was shaped at, in CSS pixels; the bitmap
+    /// is rendered at the DPI for the configured device pixel ratio (see
+    /// [`FontSystemConfig::raster_dpi`]).
+    ///
     /// # Arguments
     ///
     /// * `font_id` - ID of the font
     /// * `glyph_id` - ID of the glyph
-    /// * `size` - Font size in points
+    /// * `size` - Font size in CSS pixels
     /// * `mode` - Rendering mode
     ///
     /// # Returns
@@ -185,15 +479,144 @@ impl FontSystem {
     /// * `Err(FontError)` - Failed to render glyph
     pub fn rasterize_glyph(
         &self,
-        _font_id: FontId,
-        _glyph_id: GlyphId,
-        _size: f32,
-        _mode: RenderMode,
+        font_id: FontId,
+        glyph_id: GlyphId,
+        size: f32,
+        mode: RenderMode,
     ) -> Result<GlyphBitmap, FontError> {
-        // TODO: Implement using glyph_renderer
-        Err(FontError::RenderError(
-            "Not yet implemented - waiting for glyph_renderer".to_string(),
-        ))
+        let (font, glyph) = self.render_target(font_id, glyph_id)?;
+        let bitmap = self.renderer().rasterize_glyph_at_dpi(
+            &font,
+            glyph,
+            size,
+            self.config.raster_dpi(),
+            mode,
+        )?;
+        self.record_rasterization(font_id);
+        Ok(bitmap)
+    }
+
+    /// Rasterize a `COLR` or SVG color glyph with the font's palette
+    ///
+    /// Layers are colored from the palette set with
+    /// [`set_font_palette`](Self::set_font_palette); layers drawn in the text
+    /// color use the straight RGBA `foreground`. Glyphs without layers are
+    /// drawn from their SVG document if a rasterizer was set with
+    /// [`set_svg_rasterizer`](Self::set_svg_rasterizer). The bitmap is
+    /// premultiplied RGBA (`PixelFormat::Rgba8`), rendered like
+    /// [`rasterize_glyph`](Self::rasterize_glyph).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(GlyphBitmap))` - Color glyph
+    /// * `Ok(None)` - The glyph has no color rendering; render it with
+    ///   [`rasterize_glyph`](Self::rasterize_glyph)
+    /// * `Err(FontError)` - Failed to render glyph
+    pub fn rasterize_color_glyph(
+        &self,
+        font_id: FontId,
+        glyph_id: GlyphId,
+        size: f32,
+        foreground: [u8; 4],
+    ) -> Result<Option<GlyphBitmap>, FontError> {
+        let (font, glyph) = self.render_target(font_id, glyph_id)?;
+        let palette = self
+            .font_registry
+            .get_font_face(font_id)
+            .map(|face| &face.palette)
+            .ok_or(RegistryError::FontNotFound(font_id))?;
+        let dpi = self.config.raster_dpi();
+
+        let bitmap = self
+            .renderer()
+            .rasterize_color_glyph_at_dpi(&font, glyph, size, dpi, palette, foreground)?;
+        if bitmap.is_some() {
+            self.record_rasterization(font_id);
+        }
+        Ok(bitmap)
+    }
+
+    /// Render SVG-in-OpenType glyphs with `rasterizer` (e.g. one built on
+    /// resvg) in [`rasterize_color_glyph`](Self::rasterize_color_glyph)
+    pub fn set_svg_rasterizer(&mut self, rasterizer: Box<dyn SvgRasterizer>) {
+        self.renderer_mut().set_svg_rasterizer(rasterizer);
+    }
+
+    /// Set the color palette of a font (CSS `font-palette`)
+    ///
+    /// Applies to [`rasterize_color_glyph`](Self::rasterize_color_glyph) for
+    /// this face only.
+    pub fn set_font_palette(
+        &mut self,
+        font_id: FontId,
+        palette: FontPalette,
+    ) -> Result<(), FontError> {
+        self.font_registry.set_font_palette(font_id, palette)?;
+        Ok(())
+    }
+
+    /// Set the `size-adjust`, `ascent-override`, `descent-override` and
+    /// `line-gap-override` descriptors of a web font face
+    ///
+    /// Give a fallback face the overrides of the web font it stands in for
+    /// while that one loads. Shaped text cached for the previous overrides
+    /// is dropped.
+    pub fn set_metric_overrides(
+        &mut self,
+        font_id: FontId,
+        overrides: FontMetricsOverrides,
+    ) -> Result<(), FontError> {
+        self.font_registry
+            .set_metric_overrides(font_id, overrides)?;
+        self.shaping_cache.clear();
+        Ok(())
+    }
+
+    /// Renderer font for a loaded font
+    ///
+    /// Shares the registry's copy of the font data (see
+    /// [`OpenTypeFont::from_face`]), so it is cheap to build per call and
+    /// can be passed to the glyph renderer's own API.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(OpenTypeFont)` - Font identified by `font_id`, at the face's
+    ///   variation coordinates
+    /// * `Err(FontError)` - The font isn't loaded or its data was released
+    pub fn render_font(&self, font_id: FontId) -> Result<OpenTypeFont, FontError> {
+        self.font_registry
+            .get_font_face(font_id)
+            .and_then(OpenTypeFont::from_face)
+            .ok_or_else(|| RegistryError::FontNotFound(font_id).into())
+    }
+
+    /// The glyph renderer, for calls through `&self`
+    fn renderer(&self) -> MutexGuard<'_, GlyphRenderer> {
+        self.glyph_renderer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The glyph renderer, without locking
+    fn renderer_mut(&mut self) -> &mut GlyphRenderer {
+        self.glyph_renderer
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Renderer font and glyph ID for a loaded font's glyph
+    fn render_target(
+        &self,
+        font_id: FontId,
+        glyph_id: GlyphId,
+    ) -> Result<(OpenTypeFont, glyph_renderer::types::GlyphId), FontError> {
+        let font = self.render_font(font_id)?;
+        let glyph = u16::try_from(glyph_id.id)
+            .map(glyph_renderer::types::GlyphId)
+            .map_err(|_| {
+                RenderError::RasterizationFailed(format!("Glyph {} out of range", glyph_id.id))
+            })?;
+        Ok((font, glyph))
     }
 
     /// Get font metrics
@@ -205,11 +628,149 @@ impl FontSystem {
     ///
     /// # Returns
     ///
-    /// * `Some(FontMetrics)` - Font metrics
+    /// * `Some(FontMetrics)` - Font metrics, with the face's metric overrides
     /// * `None` - Font not found
-    pub fn get_font_metrics(&self, _font_id: FontId, _size: f32) -> Option<FontMetrics> {
-        // TODO: Implement using font_registry
-        None
+    pub fn get_font_metrics(&self, font_id: FontId, size: f32) -> Option<FontMetrics> {
+        self.font_registry.get_font_metrics(font_id, size)
+    }
+
+    /// Character cell of the font matching `descriptor`, for grid layouts
+    ///
+    /// Generic families resolve like in
+    /// [`shape_text_with_fallback`](Self::shape_text_with_fallback), so
+    /// `monospace` picks the configured monospace font. Check
+    /// [`CellMetrics::is_monospace`] before laying text out on the grid:
+    /// proportional fonts only report their average character width.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(CellMetrics)` - Cell of the matched font at the descriptor's size
+    /// * `None` - No font matches, or the size is not positive
+    pub fn cell_metrics(&self, descriptor: &FontDescriptor) -> Option<CellMetrics> {
+        let descriptor = self.resolve_descriptor(descriptor, &ShapingOptions::default());
+        let matched = self.font_registry.match_font_detailed(&descriptor)?;
+        let metrics = self
+            .font_registry
+            .get_matched_font_metrics(&matched, descriptor.size)?;
+        let face = self.font_registry.get_font_face(matched.font_id)?;
+        let scale = descriptor.size * face.metric_overrides.size_adjust
+            / f32::from(face.metrics.units_per_em);
+        Some(CellMetrics {
+            font_id: matched.font_id,
+            is_monospace: face.fixed_advance.is_some(),
+            advance: face
+                .fixed_advance
+                .map_or(metrics.avg_char_width, |advance| advance * scale),
+            ascent: metrics.ascent,
+            descent: metrics.descent,
+            line_height: metrics.line_height(),
+        })
+    }
+
+    /// Measure text like the HTML canvas `measureText()`
+    ///
+    /// The text is shaped with fallback like
+    /// [`shape_paragraph`](Self::shape_paragraph) and its ink box taken
+    /// from the glyph outlines. The font box, em square and baselines come
+    /// from the font matching `descriptor`, the canvas "first available
+    /// font". The em square is the font size, split above and below the
+    /// alphabetic baseline in the ratio of the font's ascent to descent,
+    /// and baselines missing from the font's BASE table are synthesized as
+    /// in [`RunMetrics::baseline`](font_types::types::RunMetrics::baseline).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CanvasTextMetrics)` - Measurements from the `align` point and `baseline`
+    /// * `Err(FontError)` - No font matches, or shaping or outline lookup failed
+    pub fn measure_text(
+        &self,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+        align: CanvasTextAlign,
+        baseline: CanvasTextBaseline,
+    ) -> Result<CanvasTextMetrics, FontError> {
+        let paragraph = self.shape_paragraph(text, descriptor, options)?;
+        let resolved = self.resolve_descriptor(descriptor, options);
+        let matched = self
+            .font_registry
+            .match_font_detailed_in_context(&resolved, options.match_context)
+            .ok_or(FontError::FontNotFound)?;
+        let metrics = self
+            .font_registry
+            .get_matched_font_metrics(&matched, resolved.size)
+            .ok_or(FontError::FontNotFound)?;
+        let font = RunMetrics {
+            ascent: metrics.ascent,
+            descent: -metrics.descent,
+            baselines: paragraph
+                .runs
+                .iter()
+                .find(|run| run.font_id == matched.font_id)
+                .map(|run| run.metrics.baselines.clone())
+                .unwrap_or_default(),
+        };
+        let em_ascent = resolved.size * font.ascent / (font.ascent + font.descent);
+        let em_descent = resolved.size - em_ascent;
+
+        // Ink box with y up from the alphabetic baseline
+        let mut ink: Option<[f32; 4]> = None;
+        for glyph in paragraph.glyphs() {
+            let (font, glyph_id) = self.render_target(glyph.font_id, glyph.glyph_id)?;
+            let Some(extents) = self
+                .renderer()
+                .glyph_extents(&font, glyph_id, resolved.size)?
+            else {
+                continue;
+            };
+            let glyph_ink = [
+                glyph.position.x + extents.min_x * glyph.scale,
+                glyph.position.y + extents.min_y * glyph.scale,
+                glyph.position.x + extents.max_x * glyph.scale,
+                glyph.position.y + extents.max_y * glyph.scale,
+            ];
+            ink = Some(ink.map_or(glyph_ink, |ink| {
+                [
+                    ink[0].min(glyph_ink[0]),
+                    ink[1].min(glyph_ink[1]),
+                    ink[2].max(glyph_ink[2]),
+                    ink[3].max(glyph_ink[3]),
+                ]
+            }));
+        }
+        let [min_x, min_y, max_x, max_y] = ink.unwrap_or_default();
+
+        let rtl = options.direction == Direction::RightToLeft;
+        let x = match align {
+            CanvasTextAlign::Left => 0.0,
+            CanvasTextAlign::Right => paragraph.width,
+            CanvasTextAlign::Center => paragraph.width / 2.0,
+            CanvasTextAlign::Start if rtl => paragraph.width,
+            CanvasTextAlign::End if !rtl => paragraph.width,
+            CanvasTextAlign::Start | CanvasTextAlign::End => 0.0,
+        };
+        let y = match baseline {
+            CanvasTextBaseline::Top => em_ascent,
+            CanvasTextBaseline::Hanging => font.baseline(Baseline::Hanging),
+            CanvasTextBaseline::Middle => (em_ascent - em_descent) / 2.0,
+            CanvasTextBaseline::Alphabetic => 0.0,
+            CanvasTextBaseline::Ideographic => font.baseline(Baseline::Ideographic),
+            CanvasTextBaseline::Bottom => -em_descent,
+        };
+        Ok(CanvasTextMetrics {
+            width: paragraph.width,
+            actual_bounding_box_left: x - min_x,
+            actual_bounding_box_right: max_x - x,
+            font_bounding_box_ascent: font.ascent - y,
+            font_bounding_box_descent: font.descent + y,
+            actual_bounding_box_ascent: max_y - y,
+            actual_bounding_box_descent: y - min_y,
+            em_height_ascent: em_ascent - y,
+            em_height_descent: em_descent + y,
+            hanging_baseline: font.baseline(Baseline::Hanging) - y,
+            alphabetic_baseline: -y,
+            ideographic_baseline: font.baseline(Baseline::Ideographic) - y,
+        })
     }
 
     /// Get glyph vector outline
@@ -224,14 +785,12 @@ impl FontSystem {
     /// * `Ok(GlyphOutline)` - Glyph vector outline
     /// * `Err(FontError)` - Failed to get outline
     pub fn get_glyph_outline(
-        &self,
-        _font_id: FontId,
-        _glyph_id: GlyphId,
+        &mut self,
+        font_id: FontId,
+        glyph_id: GlyphId,
     ) -> Result<GlyphOutline, FontError> {
-        // TODO: Implement using font_registry
-        Err(FontError::SystemError(
-            "Not yet implemented - waiting for font_registry".to_string(),
-        ))
+        let (font, glyph) = self.render_target(font_id, glyph_id)?;
+        Ok(self.renderer_mut().get_glyph_outline(&font, glyph)?)
     }
 
     /// Get the number of loaded fonts
@@ -240,20 +799,90 @@ impl FontSystem {
     ///
     /// Number of loaded fonts
     pub fn font_count(&self) -> usize {
-        // TODO: Implement using font_registry
-        0
+        self.font_registry.font_count()
     }
 
     /// Clear all caches
     pub fn clear_caches(&mut self) {
-        // TODO: Implement cache clearing
-        // Will clear caches in font_registry, text_shaper, and glyph_renderer
+        self.renderer_mut().clear_cache();
+        self.shaping_cache.clear();
+    }
+
+    /// Release memory in response to a memory-pressure signal
+    ///
+    /// Evicts the least recently used share of cached glyph bitmaps and
+    /// shaping results and releases the data of fonts that can be reloaded
+    /// from disk, in proportion to `level` (see
+    /// [`MemoryPressureLevel::retain_fraction`]).
+    ///
+    /// # Returns
+    ///
+    /// Number of bytes released
+    pub fn trim_memory(&mut self, level: MemoryPressureLevel) -> usize {
+        self.renderer_mut().trim_memory(level)
+            + self.shaping_cache.trim_memory(level)
+            + self.font_registry.trim_memory(level)
+    }
+
+    /// Live memory usage of each component
+    ///
+    /// Collected from the components' [`MemoryReporter`] implementations.
+    pub fn memory_breakdown(&self) -> ComponentMemoryBreakdown {
+        ComponentMemoryBreakdown {
+            font_registry_bytes: self.font_registry.memory_report().bytes,
+            glyph_renderer_bytes: self.renderer().memory_report().bytes,
+            text_shaper_bytes: self.shaping_cache.memory_report().bytes,
+            platform_integration_bytes: 0,
+        }
+    }
+
+    /// Entries, memory, hit rates and evictions of every cache, and the
+    /// faces and font data held by the registry
+    ///
+    /// Serializable with the `serde` feature for devtools display.
+    pub fn statistics(&self) -> FontSystemStats {
+        let renderer = self.renderer();
+        FontSystemStats {
+            registry: self.font_registry.stats().into(),
+            glyph_cache: renderer.cache_stats().into(),
+            outline_cache: renderer.outline_cache_stats().into(),
+            shaping_cache: CacheReport::from_shaping(
+                self.shaping_cache.stats(),
+                self.shaping_cache.memory_report(),
+            ),
+        }
+    }
+}
+
+impl MemoryProfiler for FontSystem {
+    fn memory_usage(&self) -> usize {
+        self.memory_breakdown().total()
+    }
+
+    fn detailed_stats(&self) -> MemoryStats {
+        let fonts = self.font_registry.memory_report();
+        let glyphs = self.renderer().memory_report();
+        let shapings = self.shaping_cache.memory_report();
+        MemoryStats {
+            font_data_bytes: fonts.bytes,
+            glyph_cache_bytes: glyphs.bytes,
+            shaping_cache_bytes: shapings.bytes,
+            total_bytes: fonts.bytes + glyphs.bytes + shapings.bytes,
+            font_count: fonts.entries,
+            cached_glyphs: glyphs.entries,
+            cached_shapings: shapings.entries,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{CacheConfig, GlyphCacheConfig, ShapingCacheConfig};
+    use font_registry::LAST_RESORT_FONT_ID;
+    use font_types::{EvictionPolicy, FontSynthesis, FontVisibility};
+    use std::collections::HashMap;
+    use text_shaper::types::{FontVariantCaps, Script};
 
     #[test]
     fn test_font_system_new_with_default_config() {
@@ -281,6 +910,7 @@ mod tests {
         assert!(font_system.config.enable_subpixel);
         assert!(font_system.config.enable_hinting);
         assert!(font_system.config.load_system_fonts_on_init);
+        assert!(!font_system.config.use_mock_platform);
     }
 
     #[test]
@@ -291,10 +921,14 @@ mod tests {
                 max_entries: 15_000,
                 max_memory_bytes: 150 * 1024 * 1024,
                 enable_statistics: false,
+                eviction_policy: EvictionPolicy::SegmentedLru,
             },
             shaping_cache: ShapingCacheConfig {
                 max_entries: 1_500,
                 enable_statistics: false,
+                eviction_policy: EvictionPolicy::Lru,
+                simple_shaping: true,
+                verify_keys: false,
             },
         };
 
@@ -303,6 +937,8 @@ mod tests {
             enable_subpixel: false,
             enable_hinting: false,
             load_system_fonts_on_init: false,
+            use_mock_platform: false,
+            ..Default::default()
         };
 
         // When
@@ -320,6 +956,27 @@ mod tests {
         assert!(!font_system.config.load_system_fonts_on_init);
     }
 
+    #[test]
+    fn test_font_system_new_with_mock_platform() {
+        // Given
+        let config = FontSystemConfig {
+            use_mock_platform: true,
+            ..Default::default()
+        };
+
+        let mut font_system = FontSystem::new(config).unwrap();
+
+        // When
+        let count = font_system.load_system_fonts().unwrap();
+
+        // Then only the three bundled DejaVu fonts are installed
+        assert_eq!(count, 3);
+        let local = |name| font_system.find_local_font(name);
+        assert!(local("DejaVuSans").is_some());
+        assert!(local("DejaVuSansMono").is_some());
+        assert!(local("DejaVuSerif").is_some());
+    }
+
     #[test]
     fn test_font_count_returns_zero_initially() {
         // Given
@@ -344,26 +1001,52 @@ mod tests {
     }
 
     #[test]
-    fn test_load_system_fonts_returns_not_implemented() {
+    fn test_load_system_fonts_registers_fonts() {
         // Given
-        let config = FontSystemConfig::default();
+        let config = FontSystemConfig {
+            use_mock_platform: true,
+            ..FontSystemConfig::default()
+        };
         let mut font_system = FontSystem::new(config).unwrap();
 
         // When
-        let result = font_system.load_system_fonts();
+        let count = font_system.load_system_fonts().unwrap();
 
         // Then
-        assert!(result.is_err());
-        match result {
-            Err(FontError::SystemError(msg)) => {
-                assert!(msg.contains("Not yet implemented"));
-            }
-            _ => panic!("Expected SystemError"),
-        }
+        assert!(count > 0);
+        assert_eq!(font_system.font_count(), count);
     }
 
     #[test]
-    fn test_match_font_returns_none() {
+    fn test_load_font_file_and_match_font() {
+        // Given
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ));
+
+        // When
+        let font_id = font_system.load_font_file(path).unwrap();
+
+        // Then
+        assert_eq!(font_system.font_count(), 1);
+        let descriptor = FontDescriptor {
+            family: vec!["DejaVu Sans".to_string()],
+            ..FontDescriptor::default()
+        };
+        assert_eq!(font_system.match_font(&descriptor), Some(font_id));
+        assert!(matches!(
+            font_system.load_font_data(vec![0; 16]),
+            Err(FontError::RegistryError(_))
+        ));
+        assert!(font_system
+            .load_font_file(&path.with_file_name("missing.ttf"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_match_font_without_fonts_returns_last_resort_or_none() {
         // Given
         let config = FontSystemConfig::default();
         let font_system = FontSystem::new(config).unwrap();
@@ -372,8 +1055,295 @@ mod tests {
         // When
         let result = font_system.match_font(&descriptor);
 
-        // Then
-        assert!(result.is_none());
+        // Then only the embedded last-resort font, if any, matches
+        let last_resort = cfg!(feature = "last-resort-font").then_some(LAST_RESORT_FONT_ID);
+        assert_eq!(result, last_resort);
+    }
+
+    #[test]
+    fn test_find_local_font_matches_postscript_and_full_names() {
+        // Given: A system holding DejaVu Sans
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let font_id = font_system.load_font_data(data).unwrap();
+
+        // When/Then: local() names resolve to it, other names don't
+        assert_eq!(font_system.find_local_font("DejaVuSans"), Some(font_id));
+        assert_eq!(font_system.find_local_font("dejavu sans"), Some(font_id));
+        assert_eq!(font_system.find_local_font("DejaVuSans-Bold"), None);
+    }
+
+    #[test]
+    fn test_shape_paragraph_uses_fallback_fonts() {
+        // Given: DejaVu Serif, which has no Hebrew, and DejaVu Sans, which has
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let mut load = |name: &str| {
+            let path = format!(
+                "{}/../../tests/fixtures/fonts/{name}",
+                env!("CARGO_MANIFEST_DIR")
+            );
+            let data = std::fs::read(path).unwrap();
+            font_system.load_font_data(data).unwrap()
+        };
+        let serif = load("DejaVuSerif.ttf");
+        let sans = load("DejaVuSans.ttf");
+        let descriptor = FontDescriptor {
+            family: vec!["DejaVu Serif".to_string()],
+            ..FontDescriptor::default()
+        };
+
+        // When: Shaping mixed Latin and Hebrew
+        let paragraph = font_system
+            .shape_paragraph(
+                "a\u{5d0}",
+                &descriptor,
+                &crate::ffi::default_shaping_options(),
+            )
+            .unwrap();
+
+        // Then: Each font gets its own run and nothing is missing
+        let fonts: Vec<FontId> = paragraph.runs.iter().map(|run| run.font_id).collect();
+        assert_eq!(fonts, [serif, sans]);
+        assert_eq!(font_system.notdef_stats(), NotdefStats::default());
+    }
+
+    #[test]
+    fn test_shape_text_with_fallback_counts_notdef() {
+        // Given: A system holding only DejaVu Sans, which has no Han glyphs
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        font_system.load_font_data(data).unwrap();
+        let descriptor = FontDescriptor {
+            family: vec!["DejaVu Sans".to_string()],
+            ..FontDescriptor::default()
+        };
+        let options = crate::ffi::default_shaping_options();
+
+        // When: Shaping covered and uncovered text
+        let (_, missing) = font_system
+            .shape_text_with_fallback_report("a中文", &descriptor, &options)
+            .unwrap();
+        font_system
+            .shape_text_with_fallback("abc", &descriptor, &options)
+            .unwrap();
+
+        // Then: Only the uncovered clusters are reported and counted
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[0].character, '中');
+        assert_eq!(
+            font_system.notdef_stats(),
+            NotdefStats {
+                missing_glyphs: 2,
+                affected_runs: 1,
+            }
+        );
+        font_system.reset_notdef_stats();
+        assert_eq!(font_system.notdef_stats(), NotdefStats::default());
+    }
+
+    #[test]
+    fn test_font_palette_and_plain_glyphs_in_color_path() {
+        // Given: A system with DejaVu Sans, which has no color glyphs
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let font_id = font_system.load_font_data(data).unwrap();
+        let dark = FontPalette::new(font_types::types::BasePalette::Dark);
+
+        // When: Setting a palette and rendering a glyph through the color path
+        font_system.set_font_palette(font_id, dark.clone()).unwrap();
+        let color = font_system
+            .rasterize_color_glyph(font_id, GlyphId { id: 36 }, 16.0, [0, 0, 0, 255])
+            .unwrap();
+
+        // Then: The face keeps the palette and plain glyphs fall back to None
+        assert_eq!(
+            font_system
+                .font_registry
+                .get_font_face(font_id)
+                .unwrap()
+                .palette,
+            dark
+        );
+        assert!(color.is_none());
+        assert!(matches!(
+            font_system.set_font_palette(999, FontPalette::default()),
+            Err(FontError::RegistryError(RegistryError::FontNotFound(999)))
+        ));
+        assert!(matches!(
+            font_system.rasterize_color_glyph(999, GlyphId { id: 36 }, 16.0, [0; 4]),
+            Err(FontError::RegistryError(RegistryError::FontNotFound(999)))
+        ));
+    }
+
+    #[test]
+    fn test_metric_overrides_drop_cached_shaping() {
+        // Given: DejaVu Sans with "Hello" already shaped and cached
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let font_id = font_system.load_font_data(data).unwrap();
+        let options = ShapingOptions::default();
+        let before = font_system
+            .shape_text("Hello", font_id, 20.0, &options)
+            .unwrap();
+
+        // When: Giving the face a size-adjust and an ascent override
+        let overrides = FontMetricsOverrides {
+            size_adjust: 0.5,
+            ascent: Some(0.8),
+            ..FontMetricsOverrides::default()
+        };
+        font_system
+            .set_metric_overrides(font_id, overrides)
+            .unwrap();
+        let after = font_system
+            .shape_text("Hello", font_id, 20.0, &options)
+            .unwrap();
+
+        // Then: Shaping and metrics reflect the overrides, not the cache
+        assert!((after.width - before.width / 2.0).abs() < 0.01);
+        let metrics = font_system.get_font_metrics(font_id, 20.0).unwrap();
+        assert!((metrics.ascent - 8.0).abs() < 1e-4);
+        assert!(matches!(
+            font_system.set_metric_overrides(999, overrides),
+            Err(FontError::RegistryError(RegistryError::FontNotFound(999)))
+        ));
+    }
+
+    #[test]
+    fn test_configured_scaling_applies_to_shaping_and_rendering() {
+        // Given: A system at 2x DPR that enlarges Han text by 1.5x and maps
+        // sans-serif to DejaVu Sans
+        let mut font_system = FontSystem::new(FontSystemConfig {
+            device_pixel_ratio: 2.0,
+            script_size_adjustments: HashMap::from([(Script::Han, 1.5)]),
+            default_families: HashMap::from([(
+                FontCategory::SansSerif,
+                vec!["DejaVu Sans".to_string()],
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let font_id = font_system.load_font_data(data).unwrap();
+        let latin = crate::ffi::default_shaping_options();
+        let han = ShapingOptions {
+            script: Script::Han,
+            ..latin.clone()
+        };
+
+        // When: Shaping the same text as Latin and Han, and rasterizing
+        let plain = font_system.shape_text("ab", font_id, 16.0, &latin).unwrap();
+        let scaled = font_system.shape_text("ab", font_id, 16.0, &han).unwrap();
+        let fallback = font_system
+            .shape_text_with_fallback("ab", &FontDescriptor::default(), &latin)
+            .unwrap();
+        let glyph = plain.glyphs[0].glyph_id;
+        let bitmap = font_system
+            .rasterize_glyph(font_id, glyph, 16.0, RenderMode::Gray)
+            .unwrap();
+        let reference = font_system
+            .renderer()
+            .backend()
+            .rasterize(
+                &OpenTypeFont::from_shared(
+                    font_system
+                        .font_registry
+                        .get_font_face(font_id)
+                        .unwrap()
+                        .shared_data()
+                        .unwrap(),
+                    0,
+                ),
+                glyph_renderer::types::GlyphId(glyph.id as u16),
+                32.0,
+                glyph_renderer::types::DEFAULT_DPI,
+                RenderMode::Gray,
+            )
+            .unwrap();
+
+        // Then: Han runs are 1.5x wider, generic families resolve to the
+        // configured font and bitmaps are rendered at 2x
+        assert!((scaled.width - plain.width * 1.5).abs() < 0.01);
+        assert_eq!(fallback.width, plain.width);
+        assert_eq!(
+            font_system.default_font_families(FontCategory::SansSerif),
+            vec!["DejaVu Sans".to_string()]
+        );
+        assert_eq!(bitmap.width, reference.width);
+        assert_eq!(bitmap.height, reference.height);
+        assert_eq!(bitmap.data, reference.data);
+    }
+
+    #[test]
+    fn test_font_synthesis_controls_small_caps() {
+        // Given: DejaVu Sans, which has no small caps glyphs, in systems
+        // allowing and forbidding synthesis
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let system = |font_synthesis| {
+            let mut font_system = FontSystem::new(FontSystemConfig {
+                font_synthesis,
+                ..Default::default()
+            })
+            .unwrap();
+            let font_id = font_system
+                .font_registry
+                .load_font_data(data.clone())
+                .unwrap();
+            (font_system, font_id)
+        };
+        let options = ShapingOptions {
+            variant_caps: FontVariantCaps::SmallCaps,
+            ..crate::ffi::default_shaping_options()
+        };
+        let descriptor = |synthesis| FontDescriptor {
+            family: vec!["DejaVu Sans".to_string()],
+            synthesis,
+            ..FontDescriptor::default()
+        };
+        let scales = |shaped: ShapedText| -> Vec<f32> {
+            shaped.glyphs.iter().map(|glyph| glyph.scale).collect()
+        };
+
+        // When/Then: Lowercase letters are scaled capitals only where allowed
+        let (allowed, font_id) = system(FontSynthesis::ALL);
+        let shaped = allowed.shape_text("ab", font_id, 16.0, &options).unwrap();
+        assert!(scales(shaped).iter().all(|&scale| scale < 1.0));
+        let shaped = allowed
+            .shape_text_with_fallback("ab", &descriptor(FontSynthesis::NONE), &options)
+            .unwrap();
+        assert_eq!(scales(shaped), [1.0, 1.0]);
+
+        let (forbidden, font_id) = system(FontSynthesis::NONE);
+        let shaped = forbidden.shape_text("ab", font_id, 16.0, &options).unwrap();
+        assert_eq!(scales(shaped), [1.0, 1.0]);
+        let shaped = forbidden
+            .shape_text_with_fallback("ab", &descriptor(FontSynthesis::ALL), &options)
+            .unwrap();
+        assert_eq!(scales(shaped), [1.0, 1.0]);
     }
 
     #[test]
@@ -389,4 +1359,379 @@ mod tests {
         // Then
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_memory_breakdown_starts_empty() {
+        // Given
+        let font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+
+        // When
+        let breakdown = font_system.memory_breakdown();
+        let stats = font_system.detailed_stats();
+
+        // Then
+        assert_eq!(breakdown.total(), 0);
+        assert_eq!(font_system.memory_usage(), 0);
+        assert_eq!(stats, MemoryStats::new());
+    }
+
+    #[test]
+    fn test_trim_memory_on_empty_system_frees_nothing() {
+        // Given
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+
+        // When/Then
+        for level in [
+            MemoryPressureLevel::Low,
+            MemoryPressureLevel::Moderate,
+            MemoryPressureLevel::Critical,
+        ] {
+            assert_eq!(font_system.trim_memory(level), 0);
+        }
+    }
+
+    #[test]
+    fn test_statistics_report_registry_and_shaping_cache() {
+        // Given: A system holding DejaVu Sans
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let size = data.len();
+        let font_id = font_system.load_font_data(data).unwrap();
+
+        // When: Shaping the same text twice
+        let options = crate::ffi::default_shaping_options();
+        for _ in 0..2 {
+            font_system
+                .shape_text("Hello", font_id, 16.0, &options)
+                .unwrap();
+        }
+        let stats = font_system.statistics();
+
+        // Then: The second call hit the system's shaping cache
+        assert_eq!(stats.registry.faces, 1);
+        assert_eq!(stats.registry.resident_bytes, size);
+        assert_eq!(stats.shaping_cache.entries, 1);
+        assert_eq!(stats.shaping_cache.max_entries, 1_000);
+        assert_eq!(
+            (stats.shaping_cache.hits, stats.shaping_cache.misses),
+            (1, 1)
+        );
+        assert!(stats.shaping_cache.bytes > 0);
+        assert_eq!(stats.glyph_cache.max_entries, 10_000);
+        assert_eq!(stats.total_bytes(), font_system.memory_usage());
+
+        font_system.clear_caches();
+        assert_eq!(font_system.statistics().shaping_cache.entries, 0);
+    }
+
+    #[test]
+    fn test_untrusted_calls_only_see_allowed_installed_fonts() {
+        // Given: Installed DejaVu fonts, with only DejaVu Serif visible to
+        // untrusted callers
+        let mut font_system = FontSystem::new(FontSystemConfig {
+            use_mock_platform: true,
+            font_visibility: FontVisibility::Allowlist(vec!["DejaVu Serif".to_string()]),
+            ..Default::default()
+        })
+        .unwrap();
+        font_system.load_system_fonts().unwrap();
+        let descriptor = FontDescriptor {
+            family: vec!["DejaVu Sans".to_string(), "DejaVu Serif".to_string()],
+            ..FontDescriptor::default()
+        };
+        let privileged = crate::ffi::default_shaping_options();
+        let untrusted = ShapingOptions {
+            match_context: MatchContext::Untrusted,
+            ..privileged.clone()
+        };
+
+        // When: Shaping and resolving `local()` names in both contexts
+        let shape = |options| {
+            font_system
+                .shape_paragraph("Hi", &descriptor, options)
+                .unwrap()
+                .runs[0]
+                .font_id
+        };
+        let sans = font_system.find_local_font("DejaVuSans").unwrap();
+        let serif = font_system.find_local_font("DejaVuSerif").unwrap();
+
+        // Then: Untrusted calls fall back past the hidden family
+        assert_eq!(shape(&privileged), sans);
+        assert_eq!(shape(&untrusted), serif);
+        assert_eq!(
+            font_system.find_local_font_in_context("DejaVuSans", MatchContext::Untrusted),
+            None
+        );
+        assert_eq!(
+            font_system.find_local_font_in_context("DejaVuSerif", MatchContext::Untrusted),
+            Some(serif)
+        );
+    }
+
+    #[test]
+    fn test_usage_report_counts_fonts_used_per_context() {
+        // Given: DejaVu Sans, and two documents
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let font_id = font_system.load_font_data(data).unwrap();
+        let descriptor = FontDescriptor {
+            family: vec!["DejaVu Sans".to_string()],
+            ..FontDescriptor::default()
+        };
+        let options = crate::ffi::default_shaping_options();
+        let (first, second) = (UsageContext(1), UsageContext(2));
+
+        // When: The first document shapes and paints text, and untracked
+        // calls shape more
+        font_system.set_usage_context(Some(first));
+        let shaped = font_system
+            .shape_text_with_fallback("Héllo", &descriptor, &options)
+            .unwrap();
+        font_system
+            .rasterize_glyph(font_id, shaped.glyphs[0].glyph_id, 16.0, RenderMode::Gray)
+            .unwrap();
+        font_system.set_usage_context(None);
+        font_system
+            .shape_text("untracked", font_id, 16.0, &options)
+            .unwrap();
+
+        // Then: Only the first document's use is reported, until reset
+        let report = font_system.usage_report(first);
+        assert_eq!(report.fonts.len(), 1);
+        let usage = report.font(font_id).unwrap();
+        assert_eq!(usage.family_name, "DejaVu Sans");
+        assert_eq!(usage.postscript_name, "DejaVuSans");
+        assert_eq!(usage.shaped_glyphs, shaped.glyphs.len() as u64);
+        assert_eq!(usage.code_points, 5);
+        assert_eq!(usage.rasterized_glyphs, 1);
+        assert!(font_system.usage_report(second).fonts.is_empty());
+
+        font_system.reset_usage(first);
+        assert!(font_system.usage_report(first).fonts.is_empty());
+    }
+
+    #[test]
+    fn test_cell_metrics_for_monospace_and_proportional_fonts() {
+        // Given: The installed DejaVu fonts
+        let mut font_system = FontSystem::new(FontSystemConfig {
+            use_mock_platform: true,
+            ..Default::default()
+        })
+        .unwrap();
+        font_system.font_registry.set_last_resort_fallback(false);
+        font_system.load_system_fonts().unwrap();
+        let descriptor = |family: &str| FontDescriptor {
+            family: vec![family.to_string()],
+            size: 20.0,
+            ..FontDescriptor::default()
+        };
+
+        // When: Measuring the cells of the monospace generic and of DejaVu Sans
+        let mono = font_system.cell_metrics(&descriptor("monospace")).unwrap();
+        let sans = font_system
+            .cell_metrics(&descriptor("DejaVu Sans"))
+            .unwrap();
+
+        // Then: DejaVu Sans Mono has a fixed 1233/2048 em advance, while
+        // DejaVu Sans is proportional
+        let face = font_system
+            .font_registry
+            .get_font_face(mono.font_id)
+            .unwrap();
+        assert_eq!(face.family_name, "DejaVu Sans Mono");
+        assert!(mono.is_monospace);
+        assert!((mono.advance - 20.0 * 1233.0 / 2048.0).abs() < 1e-3);
+        assert!(mono.line_height > 20.0);
+        assert!(mono.ascent > 0.0 && mono.descent < 0.0);
+        assert!(!sans.is_monospace);
+        assert!(font_system
+            .cell_metrics(&descriptor("No Such Font"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_synthetic_font_gives_exact_shaping_layout_and_rendering() {
+        // Given: A synthetic font, whose glyphs are 500 of 1000 units wide
+        // and 700 tall, inset by 50 units
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let font = font_registry::SyntheticFont::new("Test Sans");
+        let font_id = font_system
+            .font_registry
+            .load_synthetic_font(&font)
+            .unwrap();
+        let options = crate::ffi::default_shaping_options();
+
+        // When: Shaping, laying out and rendering at 20px
+        let text = "Hi there";
+        let shaped = font_system
+            .shape_text(text, font_id, 20.0, &options)
+            .unwrap();
+        let layout = text_layout::ParagraphLayout::new()
+            .layout_paragraph(
+                text,
+                &shaped,
+                &text_layout::LayoutOptions {
+                    max_width: 50.0,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let bitmap = font_system
+            .rasterize_glyph(font_id, font.glyph_id('H').unwrap(), 20.0, RenderMode::Gray)
+            .unwrap();
+
+        // Then: Every glyph advances 10px, lines break at the space and the
+        // glyph is a solid 8x14 rectangle
+        assert!(shaped.glyphs.iter().all(|glyph| glyph.advance.x == 10.0));
+        assert_eq!((shaped.height, shaped.baseline), (20.0, 16.0));
+        let ranges: Vec<_> = layout.lines.iter().map(|line| line.text_range).collect();
+        assert_eq!(ranges, [(0, 3), (3, 8)]);
+        assert_eq!((bitmap.width, bitmap.height), (8, 14));
+        assert!(bitmap.data.iter().all(|&coverage| coverage == 255));
+    }
+
+    #[test]
+    fn test_measure_text_matches_canvas_text_metrics() {
+        // Given: A synthetic font at 20px: ascent 16, descent 4 and glyphs
+        // inked from 1 to 9px of their 10px advance, 14px tall
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        font_system
+            .font_registry
+            .load_synthetic_font(&font_registry::SyntheticFont::new("Test Sans"))
+            .unwrap();
+        let descriptor = FontDescriptor {
+            family: vec!["Test Sans".to_string()],
+            size: 20.0,
+            ..FontDescriptor::default()
+        };
+        let options = crate::ffi::default_shaping_options();
+
+        // When: Measuring from the default start and alphabetic baseline
+        let metrics = font_system
+            .measure_text(
+                "Hi",
+                &descriptor,
+                &options,
+                CanvasTextAlign::Start,
+                CanvasTextBaseline::Alphabetic,
+            )
+            .unwrap();
+
+        // Then: The ink starts right of the pen and sits on the baseline
+        assert_eq!(
+            metrics,
+            CanvasTextMetrics {
+                width: 20.0,
+                actual_bounding_box_left: -1.0,
+                actual_bounding_box_right: 19.0,
+                font_bounding_box_ascent: 16.0,
+                font_bounding_box_descent: 4.0,
+                actual_bounding_box_ascent: 14.0,
+                actual_bounding_box_descent: 0.0,
+                em_height_ascent: 16.0,
+                em_height_descent: 4.0,
+                hanging_baseline: 12.8,
+                alphabetic_baseline: 0.0,
+                ideographic_baseline: -4.0,
+            }
+        );
+
+        // When: Measuring centered on the top of the em square
+        let metrics = font_system
+            .measure_text(
+                "Hi",
+                &descriptor,
+                &options,
+                CanvasTextAlign::Center,
+                CanvasTextBaseline::Top,
+            )
+            .unwrap();
+
+        // Then: Distances are taken from the middle and the em top
+        assert_eq!(
+            (
+                metrics.actual_bounding_box_left,
+                metrics.actual_bounding_box_right
+            ),
+            (9.0, 9.0)
+        );
+        assert_eq!(
+            (
+                metrics.actual_bounding_box_ascent,
+                metrics.actual_bounding_box_descent
+            ),
+            (-2.0, 16.0)
+        );
+        assert_eq!(metrics.em_height_ascent, 0.0);
+        assert_eq!(metrics.alphabetic_baseline, -16.0);
+        assert_eq!(metrics.font_bounding_box_descent, 20.0);
+    }
+
+    #[test]
+    fn test_render_calls_take_a_font_id() {
+        // Given: A loaded font
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let font_id = font_system
+            .font_registry
+            .load_synthetic_font(&font_registry::SyntheticFont::new("Test Sans"))
+            .unwrap();
+        let options = crate::ffi::default_shaping_options();
+        let glyph = font_system
+            .shape_text("H", font_id, 20.0, &options)
+            .unwrap()
+            .glyphs[0]
+            .glyph_id;
+
+        // When: Getting its renderer font and an outline by ID
+        let font = font_system.render_font(font_id).unwrap();
+        let outline = font_system.get_glyph_outline(font_id, glyph).unwrap();
+        font_system.get_glyph_outline(font_id, glyph).unwrap();
+
+        // Then: The font shares the registry's bytes, and outlines are
+        // cached under the font's ID
+        let face = font_system.font_registry.get_font_face(font_id).unwrap();
+        assert!(std::sync::Arc::ptr_eq(
+            &font.shared_data(),
+            &face.shared_data().unwrap()
+        ));
+        assert_eq!(font.font_id(), Some(font_id));
+        assert!(!outline.contours.is_empty());
+        let stats = font_system.renderer().outline_cache_stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert!(font_system.render_font(font_id + 1).is_err());
+    }
+
+    #[test]
+    fn test_rasterize_glyph_hits_glyph_cache() {
+        // Given: A loaded font
+        let mut font_system = FontSystem::new(FontSystemConfig::default()).unwrap();
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let font_id = font_system.load_font_data(data).unwrap();
+
+        // When: Rasterizing the same glyph twice
+        let first = font_system
+            .rasterize_glyph(font_id, GlyphId { id: 36 }, 16.0, RenderMode::Gray)
+            .unwrap();
+        let second = font_system
+            .rasterize_glyph(font_id, GlyphId { id: 36 }, 16.0, RenderMode::Gray)
+            .unwrap();
+
+        // Then: The second call is answered from the glyph cache
+        assert_eq!(first.data, second.data);
+        let stats = font_system.statistics().glyph_cache;
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    }
 }
@@ -1,7 +1,19 @@
 //! Common types for font_system_api
 
+use crate::ffi::FontSystemErrorCode;
+use font_parser::ParseError;
+use font_registry::{FontId, RegistryError};
+use font_types::{EvictionPolicy, FontSynthesis, FontVisibility};
+use glyph_renderer::types::{RenderError, DEFAULT_DPI};
+use platform_integration::FontCategory;
+use std::collections::HashMap;
+use text_layout::LayoutError;
+use text_shaper::types::{Script, ShapingError};
+use thiserror::Error;
+
 /// Cache configuration for font system components
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CacheConfig {
     /// Glyph cache configuration
     pub glyph_cache: GlyphCacheConfig,
@@ -9,17 +21,9 @@ pub struct CacheConfig {
     pub shaping_cache: ShapingCacheConfig,
 }
 
-impl Default for CacheConfig {
-    fn default() -> Self {
-        Self {
-            glyph_cache: GlyphCacheConfig::default(),
-            shaping_cache: ShapingCacheConfig::default(),
-        }
-    }
-}
-
 /// Configuration for glyph renderer cache
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlyphCacheConfig {
     /// Maximum number of cached glyphs (default: 10,000)
     pub max_entries: usize,
@@ -27,6 +31,9 @@ pub struct GlyphCacheConfig {
     pub max_memory_bytes: usize,
     /// Enable cache statistics tracking
     pub enable_statistics: bool,
+    /// Which glyph a full cache evicts (default: LRU)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub eviction_policy: EvictionPolicy,
 }
 
 impl Default for GlyphCacheConfig {
@@ -35,17 +42,134 @@ impl Default for GlyphCacheConfig {
             max_entries: 10_000,
             max_memory_bytes: 100 * 1024 * 1024, // 100 MB
             enable_statistics: true,
+            eviction_policy: EvictionPolicy::Lru,
         }
     }
 }
 
+/// Counts of text shaped as `.notdef` because no font covered it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NotdefStats {
+    /// Clusters shaped as `.notdef`
+    pub missing_glyphs: u64,
+    /// Shaping calls that left at least one cluster uncovered
+    pub affected_runs: u64,
+}
+
+/// Character cell of a font for grid layouts (terminals, code editors)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellMetrics {
+    /// Font the cell is measured from
+    pub font_id: FontId,
+    /// Whether every spacing glyph of the font has the same advance (double
+    /// width glyphs aside), so text lines up in columns
+    pub is_monospace: bool,
+    /// Cell width in pixels: the font's fixed advance, or its average
+    /// character width if it isn't monospaced
+    pub advance: f32,
+    /// Ascent in pixels
+    pub ascent: f32,
+    /// Descent in pixels (typically negative)
+    pub descent: f32,
+    /// Cell height (ascent - descent + line gap) in pixels
+    pub line_height: f32,
+}
+
+/// Horizontal alignment point of canvas text (`CanvasRenderingContext2D.textAlign`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CanvasTextAlign {
+    /// Start edge of the text: left for left-to-right, right for
+    /// right-to-left (default)
+    #[default]
+    Start,
+    /// End edge of the text
+    End,
+    /// Left edge of the text
+    Left,
+    /// Right edge of the text
+    Right,
+    /// Middle of the text's advance
+    Center,
+}
+
+/// Baseline canvas text is drawn on (`CanvasRenderingContext2D.textBaseline`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CanvasTextBaseline {
+    /// Top of the em square
+    Top,
+    /// Hanging baseline
+    Hanging,
+    /// Middle of the em square
+    Middle,
+    /// Alphabetic baseline (default)
+    #[default]
+    Alphabetic,
+    /// Ideographic baseline
+    Ideographic,
+    /// Bottom of the em square
+    Bottom,
+}
+
+/// Measurements of canvas text, matching the HTML canvas `TextMetrics`
+/// interface, in CSS pixels
+///
+/// Horizontal distances are from the [`CanvasTextAlign`] alignment point,
+/// positive to the right except `actual_bounding_box_left`, which is
+/// positive to the left. Vertical distances are from the
+/// [`CanvasTextBaseline`] line, positive upwards except the descents, which
+/// are positive downwards.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CanvasTextMetrics {
+    /// Advance width of the text
+    pub width: f32,
+    /// Distance to the left edge of the glyphs' ink
+    pub actual_bounding_box_left: f32,
+    /// Distance to the right edge of the glyphs' ink
+    pub actual_bounding_box_right: f32,
+    /// Distance to the ascent of the first available font
+    pub font_bounding_box_ascent: f32,
+    /// Distance to the descent of the first available font
+    pub font_bounding_box_descent: f32,
+    /// Distance to the top of the glyphs' ink
+    pub actual_bounding_box_ascent: f32,
+    /// Distance to the bottom of the glyphs' ink
+    pub actual_bounding_box_descent: f32,
+    /// Distance to the top of the em square
+    pub em_height_ascent: f32,
+    /// Distance to the bottom of the em square
+    pub em_height_descent: f32,
+    /// Distance to the hanging baseline
+    pub hanging_baseline: f32,
+    /// Distance to the alphabetic baseline
+    pub alphabetic_baseline: f32,
+    /// Distance to the ideographic baseline
+    pub ideographic_baseline: f32,
+}
+
 /// Configuration for text shaping cache
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapingCacheConfig {
     /// Maximum number of cached shaping results (default: 1,000)
     pub max_entries: usize,
     /// Enable cache statistics tracking
     pub enable_statistics: bool,
+    /// Which shaping result a full cache evicts (default: LRU)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub eviction_policy: EvictionPolicy,
+    /// Shape plain left-to-right Latin text without the full shaping
+    /// engine when the result is the same (default: true)
+    #[cfg_attr(feature = "serde", serde(default = "default_simple_shaping"))]
+    pub simple_shaping: bool,
+    /// Keep each cached text to rule out hash collisions between cache
+    /// keys (default: false)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub verify_keys: bool,
 }
 
 impl Default for ShapingCacheConfig {
@@ -53,44 +177,97 @@ impl Default for ShapingCacheConfig {
         Self {
             max_entries: 1_000,
             enable_statistics: true,
+            eviction_policy: EvictionPolicy::Lru,
+            simple_shaping: true,
+            verify_keys: false,
         }
     }
 }
 
+#[cfg(feature = "serde")]
+fn default_simple_shaping() -> bool {
+    true
+}
+
 /// FontError represents all possible errors in the font system
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Errors from the components are wrapped with their source preserved, so
+/// `?` converts them and [`std::error::Error::source`] reaches the
+/// original error. [`component`](Self::component) tells where an error
+/// originated and [`code`](Self::code) gives its C API result code.
+#[derive(Debug, Error, Clone, PartialEq)]
 pub enum FontError {
     /// Font was not found
+    #[error("Font not found")]
     FontNotFound,
     /// Font data is invalid
+    #[error("Invalid font: {0}")]
     InvalidFont(String),
     /// Error loading font
+    #[error("Load error: {0}")]
     LoadError(String),
+    /// Error from the font registry
+    #[error("Registry error: {0}")]
+    RegistryError(#[from] RegistryError),
+    /// Error parsing font tables
+    #[error("Parse error: {0}")]
+    ParseError(#[from] ParseError),
     /// Error during text shaping
-    ShapingError(String),
+    #[error("Shaping error: {0}")]
+    ShapingError(#[from] ShapingError),
     /// Error during glyph rendering
-    RenderError(String),
+    #[error("Render error: {0}")]
+    RenderError(#[from] RenderError),
+    /// Error during paragraph layout
+    #[error("Layout error: {0}")]
+    LayoutError(#[from] LayoutError),
     /// System-level error
+    #[error("System error: {0}")]
     SystemError(String),
 }
 
-impl std::fmt::Display for FontError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Component of the font system an error originated in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorComponent {
+    /// `font_registry`: loading, matching and font data
+    Registry,
+    /// `font_parser`: font table parsing
+    Parser,
+    /// `text_shaper`
+    Shaper,
+    /// `glyph_renderer`
+    Renderer,
+    /// `text_layout`
+    Layout,
+    /// `font_system_api` itself or the platform
+    System,
+}
+
+impl FontError {
+    /// Component the error originated in
+    pub fn component(&self) -> ErrorComponent {
         match self {
-            FontError::FontNotFound => write!(f, "Font not found"),
-            FontError::InvalidFont(msg) => write!(f, "Invalid font: {}", msg),
-            FontError::LoadError(msg) => write!(f, "Load error: {}", msg),
-            FontError::ShapingError(msg) => write!(f, "Shaping error: {}", msg),
-            FontError::RenderError(msg) => write!(f, "Render error: {}", msg),
-            FontError::SystemError(msg) => write!(f, "System error: {}", msg),
+            FontError::FontNotFound
+            | FontError::InvalidFont(_)
+            | FontError::LoadError(_)
+            | FontError::RegistryError(_) => ErrorComponent::Registry,
+            FontError::ParseError(_) => ErrorComponent::Parser,
+            FontError::ShapingError(_) => ErrorComponent::Shaper,
+            FontError::RenderError(_) => ErrorComponent::Renderer,
+            FontError::LayoutError(_) => ErrorComponent::Layout,
+            FontError::SystemError(_) => ErrorComponent::System,
         }
     }
-}
 
-impl std::error::Error for FontError {}
+    /// Result code the C API reports for this error
+    pub fn code(&self) -> FontSystemErrorCode {
+        self.into()
+    }
+}
 
 /// Configuration for FontSystem initialization
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FontSystemConfig {
     /// Cache configuration for glyph rendering and text shaping
     pub cache_config: CacheConfig,
@@ -100,6 +277,65 @@ pub struct FontSystemConfig {
     pub enable_hinting: bool,
     /// Load system fonts on initialization
     pub load_system_fonts_on_init: bool,
+    /// Discover fonts from the deterministic mock platform (bundled test
+    /// fonts) instead of the host; also enabled by `CORTEN_FONT_PLATFORM=mock`
+    pub use_mock_platform: bool,
+    /// Families that generic family names ("sans-serif", "monospace", ...)
+    /// resolve to, replacing the platform defaults for those categories
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub default_families: HashMap<FontCategory, Vec<String>>,
+    /// Device pixels per CSS pixel; glyphs are rasterized at this multiple
+    /// of 72 DPI (see [`raster_dpi`](Self::raster_dpi)) while shaping stays
+    /// in CSS pixels
+    #[cfg_attr(feature = "serde", serde(default = "default_device_pixel_ratio"))]
+    pub device_pixel_ratio: f32,
+    /// Font size multipliers per script (e.g. to enlarge Han text), applied
+    /// when shaping runs of that script
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub script_size_adjustments: HashMap<Script, f32>,
+    /// Styling the system may synthesize at all; each descriptor's
+    /// `synthesis` can only narrow it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub font_synthesis: FontSynthesis,
+    /// Installed fonts visible to [`MatchContext::Untrusted`] shaping and
+    /// `local()` lookups, e.g. [`FontVisibility::Standard`] against
+    /// fingerprinting
+    ///
+    /// [`MatchContext::Untrusted`]: font_types::MatchContext::Untrusted
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub font_visibility: FontVisibility,
+}
+
+#[cfg(feature = "serde")]
+fn default_device_pixel_ratio() -> f32 {
+    1.0
+}
+
+impl FontSystemConfig {
+    /// Font size to shape a run of `script` at, in CSS pixels
+    ///
+    /// Scales `size` by the script's entry in `script_size_adjustments`.
+    /// Missing and non-positive adjustments leave the size unchanged.
+    pub fn font_size_for_script(&self, size: f32, script: Script) -> f32 {
+        match self.script_size_adjustments.get(&script) {
+            Some(&factor) if factor.is_finite() && factor > 0.0 => size * factor,
+            _ => size,
+        }
+    }
+
+    /// Resolution to rasterize glyphs at
+    ///
+    /// Font sizes are CSS pixels, which the glyph renderer treats as points
+    /// at [`DEFAULT_DPI`]; scaling the DPI by `device_pixel_ratio` renders
+    /// one CSS pixel as that many device pixels. Non-positive or non-finite
+    /// ratios are treated as 1.0.
+    pub fn raster_dpi(&self) -> f32 {
+        if self.device_pixel_ratio.is_finite() && self.device_pixel_ratio > 0.0 {
+            DEFAULT_DPI * self.device_pixel_ratio
+        } else {
+            DEFAULT_DPI
+        }
+    }
 }
 
 impl Default for FontSystemConfig {
@@ -109,6 +345,12 @@ impl Default for FontSystemConfig {
             enable_subpixel: true,
             enable_hinting: true,
             load_system_fonts_on_init: true,
+            use_mock_platform: false,
+            default_families: HashMap::new(),
+            device_pixel_ratio: 1.0,
+            script_size_adjustments: HashMap::new(),
+            font_synthesis: FontSynthesis::ALL,
+            font_visibility: FontVisibility::All,
         }
     }
 }
@@ -138,14 +380,47 @@ mod tests {
 
     #[test]
     fn test_font_error_shaping_error() {
-        let error = FontError::ShapingError("invalid script".to_string());
-        assert_eq!(error.to_string(), "Shaping error: invalid script");
+        let error = FontError::from(ShapingError::UnsupportedScript("Zzzz".to_string()));
+        assert_eq!(error.to_string(), "Shaping error: Unsupported script: Zzzz");
+        assert_eq!(error.component(), ErrorComponent::Shaper);
     }
 
     #[test]
     fn test_font_error_render_error() {
-        let error = FontError::RenderError("out of memory".to_string());
-        assert_eq!(error.to_string(), "Render error: out of memory");
+        let error = FontError::from(RenderError::OutOfMemory);
+        assert_eq!(
+            error.to_string(),
+            "Render error: Out of memory during rendering"
+        );
+        assert_eq!(error.component(), ErrorComponent::Renderer);
+        assert_eq!(error.code(), FontSystemErrorCode::OutOfMemory);
+    }
+
+    #[test]
+    fn test_font_error_preserves_component_source() {
+        // Given: A registry error converted with `?`
+        fn load() -> Result<(), FontError> {
+            Err(RegistryError::FontNotFound(7))?
+        }
+        let error = load().unwrap_err();
+
+        // Then: The original error is the source, and the code and
+        // component come from it
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(
+            source.downcast_ref::<RegistryError>(),
+            Some(&RegistryError::FontNotFound(7))
+        );
+        assert_eq!(error.component(), ErrorComponent::Registry);
+        assert_eq!(error.code(), FontSystemErrorCode::FontNotFound);
+
+        let parse = FontError::from(ParseError::MissingTable("cmap".to_string()));
+        assert_eq!(parse.component(), ErrorComponent::Parser);
+        assert_eq!(parse.code(), FontSystemErrorCode::InvalidFont);
+
+        let layout = FontError::from(LayoutError::InvalidText("empty".to_string()));
+        assert_eq!(layout.component(), ErrorComponent::Layout);
+        assert_eq!(layout.code(), FontSystemErrorCode::InvalidArgument);
     }
 
     #[test]
@@ -185,6 +460,7 @@ mod tests {
             max_entries: 20_000,
             max_memory_bytes: 200 * 1024 * 1024,
             enable_statistics: false,
+            eviction_policy: EvictionPolicy::CostAware,
         };
         assert_eq!(config.max_entries, 20_000);
         assert_eq!(config.max_memory_bytes, 200 * 1024 * 1024);
@@ -196,6 +472,9 @@ mod tests {
         let config = ShapingCacheConfig {
             max_entries: 2_000,
             enable_statistics: false,
+            eviction_policy: EvictionPolicy::SegmentedLru,
+            simple_shaping: true,
+            verify_keys: false,
         };
         assert_eq!(config.max_entries, 2_000);
         assert!(!config.enable_statistics);
@@ -214,6 +493,28 @@ mod tests {
         assert!(config.enable_subpixel);
         assert!(config.enable_hinting);
         assert!(config.load_system_fonts_on_init);
+        assert!(config.default_families.is_empty());
+        assert_eq!(config.device_pixel_ratio, 1.0);
+        assert!(config.script_size_adjustments.is_empty());
+    }
+
+    #[test]
+    fn test_font_system_config_scales_sizes() {
+        let config = FontSystemConfig {
+            device_pixel_ratio: 2.0,
+            script_size_adjustments: HashMap::from([(Script::Han, 1.25), (Script::Arabic, 0.0)]),
+            ..Default::default()
+        };
+        assert_eq!(config.font_size_for_script(16.0, Script::Han), 20.0);
+        assert_eq!(config.font_size_for_script(16.0, Script::Latin), 16.0);
+        assert_eq!(config.font_size_for_script(16.0, Script::Arabic), 16.0);
+        assert_eq!(config.raster_dpi(), 144.0);
+
+        let invalid = FontSystemConfig {
+            device_pixel_ratio: f32::NAN,
+            ..Default::default()
+        };
+        assert_eq!(invalid.raster_dpi(), DEFAULT_DPI);
     }
 
     #[test]
@@ -223,10 +524,14 @@ mod tests {
                 max_entries: 15_000,
                 max_memory_bytes: 150 * 1024 * 1024,
                 enable_statistics: false,
+                eviction_policy: EvictionPolicy::SegmentedLru,
             },
             shaping_cache: ShapingCacheConfig {
                 max_entries: 1_500,
                 enable_statistics: false,
+                eviction_policy: EvictionPolicy::Lru,
+                simple_shaping: true,
+                verify_keys: false,
             },
         };
 
@@ -235,6 +540,12 @@ mod tests {
             enable_subpixel: false,
             enable_hinting: false,
             load_system_fonts_on_init: false,
+            use_mock_platform: false,
+            default_families: HashMap::new(),
+            device_pixel_ratio: 2.0,
+            script_size_adjustments: HashMap::new(),
+            font_synthesis: FontSynthesis::ALL,
+            font_visibility: FontVisibility::All,
         };
         assert_eq!(config.cache_config.glyph_cache.max_entries, 15_000);
         assert!(!config.enable_subpixel);
@@ -0,0 +1,121 @@
+//! Per-document font usage tracking
+//!
+//! For devtools font panels and licensing audits, the font system records
+//! which fonts shaping and rasterization actually used while a
+//! [`UsageContext`] is set (see `FontSystem::set_usage_context`), with the
+//! number of glyphs and code points each one handled.
+
+use font_registry::types::FontId;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+/// Token identifying the document, or other context, fonts are used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UsageContext(pub u64);
+
+/// How much one font was used in a context
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontUsage {
+    /// The font
+    pub font_id: FontId,
+    /// Family name of the font, empty if it has been unloaded since
+    pub family_name: String,
+    /// PostScript name of the font, empty if it has been unloaded since
+    pub postscript_name: String,
+    /// Glyphs shaped with the font
+    pub shaped_glyphs: u64,
+    /// Code points of the text shaped with the font
+    pub code_points: u64,
+    /// Glyphs rasterized from the font
+    pub rasterized_glyphs: u64,
+}
+
+/// Fonts used in a context, returned by `FontSystem::usage_report`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UsageReport {
+    /// Context the report is for
+    pub context: UsageContext,
+    /// Fonts used, by ascending font ID
+    pub fonts: Vec<FontUsage>,
+}
+
+impl UsageReport {
+    /// Usage of one font, if it was used in the context
+    pub fn font(&self, font_id: FontId) -> Option<&FontUsage> {
+        self.fonts.iter().find(|usage| usage.font_id == font_id)
+    }
+}
+
+/// Counts of one font's use in a context
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct UsageCounts {
+    pub(crate) shaped_glyphs: u64,
+    pub(crate) code_points: u64,
+    pub(crate) rasterized_glyphs: u64,
+}
+
+/// Font usage counts per context, shared by `&self` shaping and
+/// rasterization calls
+#[derive(Debug, Default)]
+pub(crate) struct UsageTracker {
+    contexts: Mutex<HashMap<UsageContext, HashMap<FontId, UsageCounts>>>,
+}
+
+impl UsageTracker {
+    /// Count `glyphs` shaped from `code_points` code points with a font
+    pub(crate) fn record_shaping(
+        &self,
+        context: UsageContext,
+        font_id: FontId,
+        glyphs: usize,
+        code_points: usize,
+    ) {
+        self.update(context, font_id, |counts| {
+            counts.shaped_glyphs += glyphs as u64;
+            counts.code_points += code_points as u64;
+        });
+    }
+
+    /// Count a glyph rasterized from a font
+    pub(crate) fn record_rasterization(&self, context: UsageContext, font_id: FontId) {
+        self.update(context, font_id, |counts| counts.rasterized_glyphs += 1);
+    }
+
+    /// Counts of the fonts used in `context`, by ascending font ID
+    pub(crate) fn counts(&self, context: UsageContext) -> Vec<(FontId, UsageCounts)> {
+        let contexts = self.contexts.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut counts: Vec<(FontId, UsageCounts)> = contexts
+            .get(&context)
+            .map(|fonts| fonts.iter().map(|(&id, &counts)| (id, counts)).collect())
+            .unwrap_or_default();
+        counts.sort_by_key(|&(font_id, _)| font_id);
+        counts
+    }
+
+    /// Forget the usage recorded for `context`
+    pub(crate) fn reset(&self, context: UsageContext) {
+        self.contexts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&context);
+    }
+
+    fn update(
+        &self,
+        context: UsageContext,
+        font_id: FontId,
+        update: impl FnOnce(&mut UsageCounts),
+    ) {
+        let mut contexts = self.contexts.lock().unwrap_or_else(PoisonError::into_inner);
+        update(
+            contexts
+                .entry(context)
+                .or_default()
+                .entry(font_id)
+                .or_default(),
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Bounded caches with a configurable eviction policy
+//!
+//! The glyph bitmap, outline and shaping caches all hold a bounded number
+//! of entries and evict one when full. [`PolicyCache`] is that cache with
+//! the choice of victim left to an [`EvictionPolicy`]: plain LRU, a
+//! segmented LRU that keeps entries used more than once (ASCII and UI font
+//! glyphs) safe from one-off scans, or a size-aware policy that evicts
+//! large entries first.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+/// Share of a segmented LRU cache reserved for entries used more than once,
+/// in percent
+const PROTECTED_PERCENT: usize = 80;
+
+/// Priority an entry of weight 1 gains under [`EvictionPolicy::CostAware`];
+/// heavier entries gain proportionally less
+const COST_SCALE: u64 = 1 << 32;
+
+/// Which entry a full cache evicts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EvictionPolicy {
+    /// The least recently used entry
+    #[default]
+    Lru,
+    /// Segmented LRU (SLRU), a simplified 2Q: new entries go to a
+    /// probationary segment and move to a protected one, 80% of the
+    /// capacity, when used again. Entries leaving the protected segment
+    /// get another chance in the probationary one, and evictions take the
+    /// least recently used probationary entry, so a burst of one-off
+    /// entries can't flush the working set.
+    SegmentedLru,
+    /// GreedyDual-Size: entries are ranked by recency and inverse weight
+    /// (e.g. bitmap bytes), so large, stale entries go first. Keeps more
+    /// entries within a memory budget, at the cost of re-rendering large
+    /// glyphs more often.
+    CostAware,
+}
+
+/// Position of an entry in eviction order; the smallest goes first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Rank {
+    /// 0 for probationary entries, 1 for protected ones
+    segment: u8,
+    /// Last use for the LRU policies, GreedyDual-Size priority otherwise
+    priority: u64,
+    /// Last use, breaking ties
+    tick: u64,
+}
+
+/// A cached value with its weight and eviction rank
+struct Slot<V> {
+    value: V,
+    weight: usize,
+    rank: Rank,
+}
+
+/// Entry displaced by [`PolicyCache::push`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Displaced<K, V> {
+    /// The previous value of the pushed key
+    Replaced(V),
+    /// An entry the policy evicted to make room
+    Evicted(K, V),
+}
+
+impl<K, V> Displaced<K, V> {
+    /// The displaced value
+    pub fn into_value(self) -> V {
+        match self {
+            Self::Replaced(value) | Self::Evicted(_, value) => value,
+        }
+    }
+}
+
+/// Cache of at most `cap` entries, evicting by an [`EvictionPolicy`]
+///
+/// Entries carry a weight, the cost the policy weighs against their size
+/// (callers typically pass their byte size). Keys are shared between the
+/// lookup table and the eviction order rather than cloned.
+pub struct PolicyCache<K, V> {
+    policy: EvictionPolicy,
+    capacity: NonZeroUsize,
+    entries: HashMap<Arc<K>, Slot<V>>,
+    order: BTreeMap<Rank, Arc<K>>,
+    /// Use counter ordering entries by recency
+    tick: u64,
+    /// GreedyDual-Size inflation: the priority of the last victim
+    inflation: u64,
+    /// Number of protected entries
+    protected: usize,
+}
+
+impl<K: Hash + Eq, V> PolicyCache<K, V> {
+    /// Create a cache holding up to `capacity` entries
+    pub fn new(capacity: NonZeroUsize, policy: EvictionPolicy) -> Self {
+        Self {
+            policy,
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            inflation: 0,
+            protected: 0,
+        }
+    }
+
+    /// Eviction policy of the cache
+    pub fn policy(&self) -> EvictionPolicy {
+        self.policy
+    }
+
+    /// Maximum number of entries
+    pub fn cap(&self) -> NonZeroUsize {
+        self.capacity
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up `key`, counting it as used
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Look up `key` mutably, counting it as used
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let (shared, slot) = self.entries.get_key_value(key)?;
+        let (shared, old_rank, weight) = (Arc::clone(shared), slot.rank, slot.weight);
+        let promote = self.policy == EvictionPolicy::SegmentedLru && old_rank.segment == 0;
+        let segment = if promote { 1 } else { old_rank.segment };
+        let rank = self.next_rank(segment, weight);
+        self.reorder(old_rank, rank, shared);
+        if promote {
+            self.protected += 1;
+            self.demote_overflow();
+        }
+        self.entries.get_mut(key).map(|slot| &mut slot.value)
+    }
+
+    /// Look up `key` without counting it as used
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|slot| &slot.value)
+    }
+
+    /// Insert an entry of `weight`, returning the value it replaced or the
+    /// entry evicted to make room
+    pub fn push(&mut self, key: K, value: V, weight: usize) -> Option<Displaced<K, V>> {
+        if let Some((shared, slot)) = self.entries.get_key_value(&key) {
+            // Replacing keeps the key's segment and counts as a use
+            let (shared, old_rank) = (Arc::clone(shared), slot.rank);
+            let rank = self.next_rank(old_rank.segment, weight);
+            self.reorder(old_rank, rank, shared);
+            let slot = self.entries.get_mut(&key).expect("cached entry");
+            slot.weight = weight;
+            return Some(Displaced::Replaced(std::mem::replace(
+                &mut slot.value,
+                value,
+            )));
+        }
+
+        let evicted = if self.entries.len() >= self.capacity.get() {
+            self.pop()
+                .map(|(key, value)| Displaced::Evicted(key, value))
+        } else {
+            None
+        };
+        let rank = self.next_rank(0, weight);
+        let shared = Arc::new(key);
+        self.order.insert(rank, Arc::clone(&shared));
+        self.entries.insert(
+            shared,
+            Slot {
+                value,
+                weight,
+                rank,
+            },
+        );
+        evicted
+    }
+
+    /// Entry the policy would evict next
+    pub fn peek_next(&self) -> Option<(&K, &V)> {
+        let (_, key) = self.order.first_key_value()?;
+        let slot = self.entries.get(key)?;
+        Some((&**key, &slot.value))
+    }
+
+    /// Evict the entry the policy picks
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let (rank, key) = self.order.pop_first()?;
+        let slot = self.entries.remove(&key).expect("ranked entry");
+        if rank.segment == 1 {
+            self.protected -= 1;
+        }
+        if self.policy == EvictionPolicy::CostAware {
+            self.inflation = rank.priority;
+        }
+        let key = Arc::into_inner(key).expect("key shared only by the cache");
+        Some((key, slot.value))
+    }
+
+    /// Remove all entries
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.protected = 0;
+        self.inflation = 0;
+    }
+
+    /// Iterate over the entries in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, slot)| (&**key, &slot.value))
+    }
+
+    /// Rank for an entry of `weight` used now
+    fn next_rank(&mut self, segment: u8, weight: usize) -> Rank {
+        self.tick += 1;
+        let priority = match self.policy {
+            EvictionPolicy::Lru | EvictionPolicy::SegmentedLru => self.tick,
+            EvictionPolicy::CostAware => self
+                .inflation
+                .saturating_add(COST_SCALE / weight.max(1) as u64),
+        };
+        Rank {
+            segment,
+            priority,
+            tick: self.tick,
+        }
+    }
+
+    /// Move an entry from `old` to `new` in eviction order
+    fn reorder(&mut self, old: Rank, new: Rank, key: Arc<K>) {
+        self.order.remove(&old);
+        self.order.insert(new, Arc::clone(&key));
+        if let Some(slot) = self.entries.get_mut(&key) {
+            slot.rank = new;
+        }
+    }
+
+    /// Move the least recently used protected entries back to probation
+    /// while the protected segment is over its share
+    fn demote_overflow(&mut self) {
+        let limit = self.capacity.get() * PROTECTED_PERCENT / 100;
+        while self.protected > limit {
+            let first_protected = Rank {
+                segment: 1,
+                priority: 0,
+                tick: 0,
+            };
+            let Some((&old_rank, key)) = self.order.range(first_protected..).next() else {
+                break;
+            };
+            let key = Arc::clone(key);
+            let rank = Rank {
+                segment: 0,
+                ..old_rank
+            };
+            self.reorder(old_rank, rank, key);
+            self.protected -= 1;
+        }
+    }
+}
+
+impl<K, V> std::fmt::Debug for PolicyCache<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolicyCache")
+            .field("policy", &self.policy)
+            .field("len", &self.entries.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(capacity: usize, policy: EvictionPolicy) -> PolicyCache<u32, u32> {
+        PolicyCache::new(NonZeroUsize::new(capacity).unwrap(), policy)
+    }
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let mut cache = cache(2, EvictionPolicy::Lru);
+        cache.push(1, 10, 1);
+        cache.push(2, 20, 1);
+        assert_eq!(cache.get(&1), Some(&10));
+
+        assert_eq!(cache.push(3, 30, 1), Some(Displaced::Evicted(2, 20)));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.peek(&1).is_some());
+    }
+
+    #[test]
+    fn test_push_replaces_existing_key() {
+        let mut cache = cache(2, EvictionPolicy::Lru);
+        cache.push(1, 10, 1);
+        assert_eq!(cache.push(1, 11, 1), Some(Displaced::Replaced(10)));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.peek(&1), Some(&11));
+    }
+
+    #[test]
+    fn test_segmented_lru_survives_scans() {
+        // Given: Two entries used twice in a cache of five
+        let mut cache = cache(5, EvictionPolicy::SegmentedLru);
+        for key in [1, 2] {
+            cache.push(key, key, 1);
+            cache.get(&key);
+        }
+
+        // When: A scan of one-off entries passes through
+        for key in 100..120 {
+            cache.push(key, key, 1);
+        }
+
+        // Then: The reused entries are still cached
+        assert!(cache.peek(&1).is_some());
+        assert!(cache.peek(&2).is_some());
+        assert_eq!(cache.len(), 5);
+    }
+
+    #[test]
+    fn test_segmented_lru_demotes_protected_overflow() {
+        // Given: More reused entries than the protected segment holds
+        let mut cache = cache(5, EvictionPolicy::SegmentedLru);
+        for key in 1..=5 {
+            cache.push(key, key, 1);
+            cache.get(&key);
+        }
+
+        // Then: The oldest is back on probation and goes first
+        assert_eq!(cache.peek_next().map(|(key, _)| *key), Some(1));
+        assert_eq!(cache.push(6, 6, 1), Some(Displaced::Evicted(1, 1)));
+    }
+
+    #[test]
+    fn test_cost_aware_evicts_heavy_entries_first() {
+        // Given: A large entry used before two small ones
+        let mut cache = cache(3, EvictionPolicy::CostAware);
+        cache.push(1, 1, 4096);
+        cache.push(2, 2, 64);
+        cache.push(3, 3, 64);
+        cache.get(&1);
+
+        // When: Making room
+        // Then: The large entry goes despite being used last
+        assert_eq!(cache.push(4, 4, 64), Some(Displaced::Evicted(1, 1)));
+
+        // Stale small entries eventually age out
+        for key in 5..10 {
+            cache.push(key, key, 64);
+        }
+        assert!(cache.peek(&2).is_none());
+    }
+
+    #[test]
+    fn test_clear_and_pop_empty() {
+        let mut cache = cache(2, EvictionPolicy::SegmentedLru);
+        cache.push(1, 1, 1);
+        cache.get(&1);
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(cache.pop().is_none());
+        assert!(cache.peek_next().is_none());
+    }
+}
@@ -0,0 +1,589 @@
+//! Parsing of the CSS `font` shorthand
+//!
+//! Browsers often hold a computed style as a single string such as
+//! `italic bold 16px/1.4 'Helvetica Neue', sans-serif`.
+//! [`FontDescriptor::from_css`] turns it into a descriptor and a line height.
+//! [`FontPalette::from_css`] and [`FontSynthesis::from_css`] parse the
+//! separate `font-palette` and `font-synthesis` properties.
+
+use crate::types::{
+    BasePalette, FontDescriptor, FontPalette, FontStretch, FontStyle, FontSynthesis, FontWeight,
+};
+use std::fmt;
+
+/// Pixels per em that relative font sizes (`em`, `rem`, `%`, `larger`)
+/// resolve against: the CSS `medium` size
+const MEDIUM_PX: f32 = 16.0;
+
+/// Angle of `oblique` without an explicit angle, in degrees
+const DEFAULT_OBLIQUE_ANGLE: f32 = 14.0;
+
+/// System font keywords, which name a platform font rather than describe one
+const SYSTEM_FONTS: &[&str] = &[
+    "caption",
+    "icon",
+    "menu",
+    "message-box",
+    "small-caption",
+    "status-bar",
+];
+
+/// Result of parsing a CSS `font` shorthand
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssFont {
+    /// Families, weight, style, stretch and size in pixels
+    pub descriptor: FontDescriptor,
+    /// Line height in pixels, or `None` for `normal`
+    ///
+    /// Unitless and percentage values are multiplied by the font size, as
+    /// CSS does, so they can be used as a fixed line height in layout.
+    pub line_height: Option<f32>,
+    /// Whether `small-caps` was given
+    pub small_caps: bool,
+}
+
+/// Error from [`FontDescriptor::from_css`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CssFontError {
+    /// The shorthand has no font size
+    MissingSize,
+    /// The shorthand has no family, or an empty entry in the family list
+    MissingFamily,
+    /// A token is not valid where it appears
+    InvalidToken(String),
+    /// A quoted family name is not closed
+    UnterminatedString,
+    /// The value is a system font keyword such as `caption`
+    SystemFont(String),
+}
+
+impl fmt::Display for CssFontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CssFontError::MissingSize => write!(f, "Font shorthand has no size"),
+            CssFontError::MissingFamily => write!(f, "Font shorthand has no family"),
+            CssFontError::InvalidToken(token) => {
+                write!(f, "Invalid token in font shorthand: {token}")
+            }
+            CssFontError::UnterminatedString => {
+                write!(f, "Unterminated string in font shorthand")
+            }
+            CssFontError::SystemFont(name) => {
+                write!(f, "System font keyword not supported: {name}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CssFontError {}
+
+impl FontDescriptor {
+    /// Parse a CSS `font` shorthand, e.g.
+    /// `italic bold 16px/1.4 'Helvetica Neue', sans-serif`
+    ///
+    /// Style, `small-caps`, weight and stretch may come in any order before
+    /// the size, which is required, as is at least one family. Sizes are
+    /// converted to pixels; `em`, `rem`, `%` and the size keywords resolve
+    /// against the 16px `medium` size. System font keywords (`caption`,
+    /// `menu`, ...) are rejected.
+    ///
+    /// ```
+    /// use font_types::{FontDescriptor, FontStyle, FontWeight};
+    ///
+    /// let css = FontDescriptor::from_css("italic bold 16px/1.5 'Helvetica Neue', sans-serif")
+    ///     .unwrap();
+    /// assert_eq!(css.descriptor.family, ["Helvetica Neue", "sans-serif"]);
+    /// assert_eq!(css.descriptor.weight, FontWeight::BOLD);
+    /// assert_eq!(css.descriptor.style, FontStyle::Italic);
+    /// assert_eq!(css.line_height, Some(24.0));
+    /// ```
+    pub fn from_css(css: &str) -> Result<CssFont, CssFontError> {
+        let css = css.trim();
+        if let Some(name) = SYSTEM_FONTS
+            .iter()
+            .find(|name| css.eq_ignore_ascii_case(name))
+        {
+            return Err(CssFontError::SystemFont(name.to_string()));
+        }
+
+        let mut descriptor = FontDescriptor::default();
+        let mut style = None;
+        let mut weight = None;
+        let mut stretch = None;
+        let mut small_caps = false;
+        let mut rest = css;
+
+        // Keywords up to the size
+        let (size, line_height) = loop {
+            let (word, after) = next_word(rest).ok_or(CssFontError::MissingSize)?;
+            rest = after;
+            let (size_part, slash) = match word.split_once('/') {
+                Some((size, line_height)) => (size, Some(line_height)),
+                None => (word, None),
+            };
+            if let Some(size) = parse_font_size(size_part) {
+                // The line height follows a slash, with or without spaces
+                let value = match slash {
+                    Some("") => Some(word_after_slash(&mut rest)?),
+                    Some(value) => Some(value),
+                    None => match rest.trim_start().strip_prefix('/') {
+                        Some(after) => {
+                            rest = after;
+                            Some(word_after_slash(&mut rest)?)
+                        }
+                        None => None,
+                    },
+                };
+                let line_height = match value {
+                    Some(value) => parse_line_height(value, size)
+                        .ok_or_else(|| CssFontError::InvalidToken(value.to_string()))?,
+                    None => None,
+                };
+                break (size, line_height);
+            }
+
+            let keyword = word.to_ascii_lowercase();
+            let duplicate = || CssFontError::InvalidToken(word.to_string());
+            match keyword.as_str() {
+                "normal" => {}
+                "italic" | "oblique" if style.is_some() => return Err(duplicate()),
+                "italic" => style = Some(FontStyle::Italic),
+                "oblique" => {
+                    let angle = next_word(rest).and_then(|(angle, after)| {
+                        let degrees = angle.strip_suffix("deg")?.parse::<f32>().ok()?;
+                        rest = after;
+                        Some(degrees)
+                    });
+                    style = Some(FontStyle::Oblique(angle.unwrap_or(DEFAULT_OBLIQUE_ANGLE)));
+                }
+                "small-caps" if !small_caps => small_caps = true,
+                _ => {
+                    if let Some(value) = parse_weight(&keyword) {
+                        if weight.replace(value).is_some() {
+                            return Err(duplicate());
+                        }
+                    } else if let Some(value) = parse_stretch(&keyword) {
+                        if stretch.replace(value).is_some() {
+                            return Err(duplicate());
+                        }
+                    } else {
+                        return Err(CssFontError::InvalidToken(word.to_string()));
+                    }
+                }
+            }
+        };
+
+        descriptor.family = parse_families(rest)?;
+        descriptor.size = size;
+        descriptor.style = style.unwrap_or(FontStyle::Normal);
+        descriptor.weight = weight.unwrap_or_default();
+        descriptor.stretch = stretch.unwrap_or(FontStretch::Normal);
+        Ok(CssFont {
+            descriptor,
+            line_height,
+            small_caps,
+        })
+    }
+}
+
+impl FontPalette {
+    /// Parse a CSS `font-palette` keyword: `normal`, `light` or `dark`
+    ///
+    /// `@font-palette-values` names (`--my-palette`) are resolved by the
+    /// style system, which builds the [`FontPalette`] from the rule's
+    /// `base-palette` and `override-colors`, so they are rejected here.
+    ///
+    /// ```
+    /// use font_types::{BasePalette, FontPalette};
+    ///
+    /// assert_eq!(FontPalette::from_css("dark").unwrap().base, BasePalette::Dark);
+    /// ```
+    pub fn from_css(css: &str) -> Result<FontPalette, CssFontError> {
+        let keyword = css.trim();
+        let base = match keyword.to_ascii_lowercase().as_str() {
+            "normal" => BasePalette::Normal,
+            "light" => BasePalette::Light,
+            "dark" => BasePalette::Dark,
+            _ => return Err(CssFontError::InvalidToken(keyword.to_string())),
+        };
+        Ok(FontPalette::new(base))
+    }
+}
+
+impl FontSynthesis {
+    /// Parse a CSS `font-synthesis` value: `none`, or any of `weight`,
+    /// `style` and `small-caps`
+    ///
+    /// `position` (synthesized super- and subscripts) is accepted and
+    /// ignored, as positions are never synthesized.
+    ///
+    /// ```
+    /// use font_types::FontSynthesis;
+    ///
+    /// let synthesis = FontSynthesis::from_css("style small-caps").unwrap();
+    /// assert!(!synthesis.weight && synthesis.style && synthesis.small_caps);
+    /// assert_eq!(FontSynthesis::from_css("none").unwrap(), FontSynthesis::NONE);
+    /// ```
+    pub fn from_css(css: &str) -> Result<FontSynthesis, CssFontError> {
+        let css = css.trim();
+        if css.eq_ignore_ascii_case("none") {
+            return Ok(FontSynthesis::NONE);
+        }
+
+        let mut synthesis = FontSynthesis::NONE;
+        let mut position = false;
+        let mut rest = css;
+        while let Some((word, after)) = next_word(rest) {
+            rest = after;
+            let flag = match word.to_ascii_lowercase().as_str() {
+                "weight" => &mut synthesis.weight,
+                "style" => &mut synthesis.style,
+                "small-caps" => &mut synthesis.small_caps,
+                "position" => &mut position,
+                _ => return Err(CssFontError::InvalidToken(word.to_string())),
+            };
+            if std::mem::replace(flag, true) {
+                return Err(CssFontError::InvalidToken(word.to_string()));
+            }
+        }
+        if css.is_empty() {
+            return Err(CssFontError::InvalidToken(String::new()));
+        }
+        Ok(synthesis)
+    }
+}
+
+/// Take the word after a line height slash
+fn word_after_slash<'a>(rest: &mut &'a str) -> Result<&'a str, CssFontError> {
+    let (word, after) = next_word(rest).ok_or(CssFontError::InvalidToken("/".to_string()))?;
+    *rest = after;
+    Ok(word)
+}
+
+/// Split off the next whitespace-delimited word, or `None` at the end
+fn next_word(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start();
+    if s.is_empty() {
+        return None;
+    }
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    Some((&s[..end], &s[end..]))
+}
+
+fn parse_weight(keyword: &str) -> Option<FontWeight> {
+    match keyword {
+        "bold" => Some(FontWeight::BOLD),
+        // Relative to the initial weight of 400
+        "bolder" => Some(FontWeight::BOLD),
+        "lighter" => Some(FontWeight::THIN),
+        _ => {
+            let value = keyword.parse::<f32>().ok()?;
+            (1.0..=1000.0)
+                .contains(&value)
+                .then(|| FontWeight::from_f32(value))
+        }
+    }
+}
+
+fn parse_stretch(keyword: &str) -> Option<FontStretch> {
+    Some(match keyword {
+        "ultra-condensed" => FontStretch::UltraCondensed,
+        "extra-condensed" => FontStretch::ExtraCondensed,
+        "condensed" => FontStretch::Condensed,
+        "semi-condensed" => FontStretch::SemiCondensed,
+        "semi-expanded" => FontStretch::SemiExpanded,
+        "expanded" => FontStretch::Expanded,
+        "extra-expanded" => FontStretch::ExtraExpanded,
+        "ultra-expanded" => FontStretch::UltraExpanded,
+        _ => return None,
+    })
+}
+
+/// Font size in pixels from a keyword or length
+fn parse_font_size(token: &str) -> Option<f32> {
+    let token = token.to_ascii_lowercase();
+    let keyword = match token.as_str() {
+        "xx-small" => Some(9.0),
+        "x-small" => Some(10.0),
+        "small" => Some(13.0),
+        "medium" => Some(MEDIUM_PX),
+        "large" => Some(18.0),
+        "x-large" => Some(24.0),
+        "xx-large" => Some(32.0),
+        "xxx-large" => Some(48.0),
+        "smaller" => Some(MEDIUM_PX / 1.2),
+        "larger" => Some(MEDIUM_PX * 1.2),
+        _ => None,
+    };
+    keyword.or_else(|| parse_length(&token, MEDIUM_PX).filter(|size| *size >= 0.0))
+}
+
+/// Line height in pixels for a font of `size` pixels; `Some(None)` for
+/// `normal`
+fn parse_line_height(token: &str, size: f32) -> Option<Option<f32>> {
+    let token = token.to_ascii_lowercase();
+    if token == "normal" {
+        return Some(None);
+    }
+    let value = match token.parse::<f32>() {
+        Ok(factor) => factor * size,
+        Err(_) => parse_length(&token, size)?,
+    };
+    (value >= 0.0 && value.is_finite()).then_some(Some(value))
+}
+
+/// Length or percentage in pixels, with `em` and `%` relative to `em_px`
+///
+/// Unitless values are only lengths when zero.
+fn parse_length(token: &str, em_px: f32) -> Option<f32> {
+    let split = token
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+')))
+        .unwrap_or(token.len());
+    let (number, unit) = token.split_at(split);
+    let value = number.parse::<f32>().ok().filter(|v| v.is_finite())?;
+    let px_per_unit = match unit {
+        "" if value == 0.0 => 0.0,
+        "px" => 1.0,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "q" => 96.0 / 101.6,
+        "em" => em_px,
+        "rem" => MEDIUM_PX,
+        "%" => em_px / 100.0,
+        _ => return None,
+    };
+    Some(value * px_per_unit)
+}
+
+/// Comma-separated family names, quoted or as unquoted identifiers
+fn parse_families(list: &str) -> Result<Vec<String>, CssFontError> {
+    let mut families = Vec::new();
+    let mut chars = list.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let family = match chars.peek() {
+            Some(&quote @ ('"' | '\'')) => {
+                chars.next();
+                let mut name = String::new();
+                loop {
+                    match chars.next().ok_or(CssFontError::UnterminatedString)? {
+                        c if c == quote => break,
+                        '\\' => name.extend(chars.next()),
+                        c => name.push(c),
+                    }
+                }
+                let trailing = take_entry(&mut chars);
+                if !trailing.trim().is_empty() {
+                    return Err(CssFontError::InvalidToken(trailing.trim().to_string()));
+                }
+                name
+            }
+            // Identifiers separated by whitespace form one name
+            _ => take_entry(&mut chars)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        if family.is_empty() {
+            return Err(CssFontError::MissingFamily);
+        }
+        families.push(family);
+
+        if chars.next_if_eq(&',').is_none() {
+            return Ok(families);
+        }
+    }
+}
+
+/// Characters up to the next comma, which is left in place
+fn take_entry(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut entry = String::new();
+    while let Some(c) = chars.next_if(|c| *c != ',') {
+        entry.push(c);
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_css_full_shorthand() {
+        // Given: A shorthand with every component
+        let css = FontDescriptor::from_css(
+            "italic small-caps 600 condensed 12pt/1.5 \"Helvetica Neue\", Arial , sans-serif",
+        )
+        .unwrap();
+
+        // Then: Each component is parsed and the size converted to pixels
+        assert_eq!(
+            css.descriptor.family,
+            ["Helvetica Neue", "Arial", "sans-serif"]
+        );
+        assert_eq!(css.descriptor.style, FontStyle::Italic);
+        assert_eq!(css.descriptor.weight, FontWeight::SEMI_BOLD);
+        assert_eq!(css.descriptor.stretch, FontStretch::Condensed);
+        assert_eq!(css.descriptor.size, 16.0);
+        assert_eq!(css.line_height, Some(24.0));
+        assert!(css.small_caps);
+    }
+
+    #[test]
+    fn test_from_css_minimal_shorthand() {
+        // Given: Only a size and a family
+        let css = FontDescriptor::from_css("13px monospace").unwrap();
+
+        // Then: Everything else has its initial value
+        assert_eq!(css.descriptor.family, ["monospace"]);
+        assert_eq!(css.descriptor.size, 13.0);
+        assert_eq!(css.descriptor.weight, FontWeight::REGULAR);
+        assert_eq!(css.descriptor.style, FontStyle::Normal);
+        assert_eq!(css.descriptor.stretch, FontStretch::Normal);
+        assert_eq!(css.line_height, None);
+        assert!(!css.small_caps);
+    }
+
+    #[test]
+    fn test_from_css_sizes_and_line_heights() {
+        // Given: Size keywords, relative units and spaced slashes
+        let size = |css: &str| FontDescriptor::from_css(css).unwrap().descriptor.size;
+        assert_eq!(size("large serif"), 18.0);
+        assert_eq!(size("1.5em serif"), 24.0);
+        assert_eq!(size("150% serif"), 24.0);
+        assert_eq!(size("2rem serif"), 32.0);
+        assert_eq!(size("1in serif"), 96.0);
+
+        let line_height = |css: &str| FontDescriptor::from_css(css).unwrap().line_height;
+        assert_eq!(line_height("20px / 30px serif"), Some(30.0));
+        assert_eq!(line_height("20px /1.2 serif"), Some(24.0));
+        assert_eq!(line_height("20px/ 150% serif"), Some(30.0));
+        assert_eq!(line_height("20px/2em serif"), Some(40.0));
+        assert_eq!(line_height("20px/normal serif"), None);
+    }
+
+    #[test]
+    fn test_from_css_styles_and_weights() {
+        // Given: Oblique angles and weight keywords
+        let parse = |css: &str| FontDescriptor::from_css(css).unwrap().descriptor;
+        assert_eq!(
+            parse("oblique 10deg 16px serif").style,
+            FontStyle::Oblique(10.0)
+        );
+        assert_eq!(
+            parse("oblique 16px serif").style,
+            FontStyle::Oblique(DEFAULT_OBLIQUE_ANGLE)
+        );
+        assert_eq!(parse("bolder 16px serif").weight, FontWeight::BOLD);
+        assert_eq!(parse("lighter 16px serif").weight, FontWeight::THIN);
+        assert_eq!(parse("350 16px serif").weight.value(), 350);
+        assert_eq!(parse("normal normal 16px serif"), parse("16px serif"));
+        assert_eq!(parse("BOLD Italic 16PX Serif").family, ["Serif"]);
+    }
+
+    #[test]
+    fn test_from_css_quoted_families() {
+        // Given: Quoted names with commas and escapes, and unquoted names
+        // with extra whitespace
+        let css =
+            FontDescriptor::from_css(r#"16px 'A, B', "Say \"Hi\"",  Times   New  Roman"#).unwrap();
+
+        // Then: Quotes are removed and unquoted words are joined by one space
+        assert_eq!(
+            css.descriptor.family,
+            ["A, B", "Say \"Hi\"", "Times New Roman"]
+        );
+    }
+
+    #[test]
+    fn test_from_css_errors() {
+        // Given: Invalid shorthands
+        // Then: Each is rejected with the reason
+        let error = |css: &str| FontDescriptor::from_css(css).unwrap_err();
+        assert_eq!(error(""), CssFontError::MissingSize);
+        assert_eq!(error("bold"), CssFontError::MissingSize);
+        assert_eq!(
+            error("bold serif"),
+            CssFontError::InvalidToken("serif".to_string())
+        );
+        assert_eq!(error("16px"), CssFontError::MissingFamily);
+        assert_eq!(error("16px serif,"), CssFontError::MissingFamily);
+        assert_eq!(error("16px serif,,sans-serif"), CssFontError::MissingFamily);
+        assert_eq!(error("16px 'serif"), CssFontError::UnterminatedString);
+        assert_eq!(
+            error("16px 'A' B"),
+            CssFontError::InvalidToken("B".to_string())
+        );
+        assert_eq!(
+            error("bold bold 16px serif"),
+            CssFontError::InvalidToken("bold".to_string())
+        );
+        assert_eq!(
+            error("wide 16px serif"),
+            CssFontError::InvalidToken("wide".to_string())
+        );
+        assert_eq!(
+            error("16px/tall serif"),
+            CssFontError::InvalidToken("tall".to_string())
+        );
+        assert_eq!(error("menu"), CssFontError::SystemFont("menu".to_string()));
+    }
+
+    #[test]
+    fn test_font_palette_from_css() {
+        // Given: font-palette values
+        // When: Parsing them
+        // Then: Keywords select a base palette and custom names are rejected
+        assert_eq!(
+            FontPalette::from_css("normal").unwrap(),
+            FontPalette::default()
+        );
+        assert_eq!(
+            FontPalette::from_css(" Light ").unwrap().base,
+            BasePalette::Light
+        );
+        assert_eq!(
+            FontPalette::from_css("dark").unwrap().base,
+            BasePalette::Dark
+        );
+        assert_eq!(
+            FontPalette::from_css("--custom").unwrap_err(),
+            CssFontError::InvalidToken("--custom".to_string())
+        );
+    }
+
+    #[test]
+    fn test_font_synthesis_from_css() {
+        // Given: font-synthesis values
+        // When: Parsing them
+        // Then: Listed kinds are allowed, and repeats and unknown kinds are rejected
+        assert_eq!(
+            FontSynthesis::from_css("NONE").unwrap(),
+            FontSynthesis::NONE
+        );
+        assert_eq!(
+            FontSynthesis::from_css("small-caps weight style").unwrap(),
+            FontSynthesis::ALL
+        );
+        assert_eq!(
+            FontSynthesis::from_css(" weight position ").unwrap(),
+            FontSynthesis {
+                weight: true,
+                ..FontSynthesis::NONE
+            }
+        );
+        assert_eq!(
+            FontSynthesis::from_css("style style").unwrap_err(),
+            CssFontError::InvalidToken("style".to_string())
+        );
+        assert_eq!(
+            FontSynthesis::from_css("bold").unwrap_err(),
+            CssFontError::InvalidToken("bold".to_string())
+        );
+        assert!(FontSynthesis::from_css("").is_err());
+    }
+}
@@ -7,7 +7,7 @@
 //!
 //! Creating a font descriptor:
 //! ```
-//! use font_types::{FontDescriptor, FontWeight, FontStyle, FontStretch};
+//! use font_types::{FontDescriptor, FontWeight, FontStyle, FontStretch, FontSynthesis};
 //!
 //! let descriptor = FontDescriptor {
 //!     family: vec!["Arial".to_string()],
@@ -15,19 +15,35 @@
 //!     style: FontStyle::Normal,
 //!     stretch: FontStretch::Normal,
 //!     size: 16.0,
+//!     synthesis: FontSynthesis::ALL,
 //! };
+//!
+//! // Or with the builder, leaving the other fields at their defaults
+//! let descriptor = FontDescriptor::builder().family("Arial").weight(700).build();
 //! ```
 
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+/// Bounded caches with configurable eviction
+pub mod cache;
+/// CSS `font` shorthand parsing
+pub mod css;
+/// Memory reporting shared by components
+pub mod memory;
 /// Common types for the font system
 pub mod types;
 
 // Re-export all public types for convenient access
+pub use cache::{Displaced, EvictionPolicy, PolicyCache};
+pub use css::{CssFont, CssFontError};
+pub use memory::{MemoryPressureLevel, MemoryReport, MemoryReporter, MemoryTrimmer};
 pub use types::{
-    Direction, FontDescriptor, FontId, FontMetrics, FontStretch, FontStyle, FontWeight,
-    GlyphBitmap, GlyphId, Point, PositionedGlyph, RenderMode, ShapedText, Vector,
+    BasePalette, Baseline, BoundingBox, Direction, FontData, FontDescriptor, FontDescriptorBuilder,
+    FontId, FontMetrics, FontMetricsOverrides, FontPalette, FontStretch, FontStyle, FontSynthesis,
+    FontVisibility, FontWeight, GlyphBitmap, GlyphId, GlyphRun, GlyphRuns, MatchContext,
+    PackedGlyphs, PackedShapedText, PaletteOverride, Point, PositionedGlyph, RenderMode,
+    RunMetrics, Script, ShapedParagraph, ShapedRun, ShapedText, VariationCoordinate, Vector,
 };
 
 #[cfg(test)]
@@ -61,6 +77,7 @@ mod tests {
             style: FontStyle::Normal,
             stretch: FontStretch::Normal,
             size: 12.0,
+            synthesis: FontSynthesis::ALL,
         };
         assert_eq!(descriptor.size, 12.0);
     }
@@ -79,6 +96,7 @@ mod tests {
             x_height: 500.0,
             underline_position: -50.0,
             underline_thickness: 25.0,
+            ..Default::default()
         };
         assert_eq!(metrics.units_per_em, 1000);
     }
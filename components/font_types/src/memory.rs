@@ -0,0 +1,91 @@
+//! Memory reporting shared by the font system components
+//!
+//! Components holding sizeable heap data (font files, glyph bitmaps, shaped
+//! text) implement [`MemoryReporter`] so the orchestration layer can build a
+//! live breakdown instead of estimating from configured limits.
+
+/// Memory held by a component at the time of the report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryReport {
+    /// Heap bytes attributed to the component
+    pub bytes: usize,
+    /// Number of items the bytes are spread over (fonts, cached glyphs,
+    /// cached shaping results)
+    pub entries: usize,
+}
+
+/// Trait for components that can report their live memory usage
+pub trait MemoryReporter {
+    /// Report the component's current memory usage
+    fn memory_report(&self) -> MemoryReport;
+}
+
+/// Severity of a memory-pressure signal from the embedder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoryPressureLevel {
+    /// Memory is getting scarce; drop a quarter of cached data
+    Low,
+    /// Memory is scarce; drop half of cached data
+    Moderate,
+    /// Memory is critically low; drop everything that can be recreated
+    Critical,
+}
+
+impl MemoryPressureLevel {
+    /// Fraction of cached items to keep at this level
+    pub fn retain_fraction(self) -> f32 {
+        match self {
+            MemoryPressureLevel::Low => 0.75,
+            MemoryPressureLevel::Moderate => 0.5,
+            MemoryPressureLevel::Critical => 0.0,
+        }
+    }
+
+    /// Number of items to keep out of `count`
+    pub fn retained(self, count: usize) -> usize {
+        (count as f32 * self.retain_fraction()) as usize
+    }
+}
+
+/// Trait for components that can release memory under pressure
+pub trait MemoryTrimmer {
+    /// Release memory in proportion to `level`, least recently used first
+    /// where the component tracks use
+    ///
+    /// Returns the number of bytes released.
+    fn trim_memory(&mut self, level: MemoryPressureLevel) -> usize;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(usize);
+
+    impl MemoryReporter for Fixed {
+        fn memory_report(&self) -> MemoryReport {
+            MemoryReport {
+                bytes: self.0,
+                entries: 1,
+            }
+        }
+    }
+
+    #[test]
+    fn test_memory_reporter_object_safe() {
+        let reporters: Vec<Box<dyn MemoryReporter>> = vec![Box::new(Fixed(10)), Box::new(Fixed(5))];
+        let total: usize = reporters.iter().map(|r| r.memory_report().bytes).sum();
+        assert_eq!(total, 15);
+        assert_eq!(MemoryReport::default().entries, 0);
+    }
+
+    #[test]
+    fn test_pressure_level_retained_counts() {
+        assert_eq!(MemoryPressureLevel::Low.retained(10), 7);
+        assert_eq!(MemoryPressureLevel::Moderate.retained(10), 5);
+        assert_eq!(MemoryPressureLevel::Critical.retained(10), 0);
+        assert_eq!(MemoryPressureLevel::Moderate.retained(0), 0);
+    }
+}
@@ -1,31 +1,110 @@
 //! Common types for the font system
 
-/// Font weight values (100-900)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-#[repr(u16)]
-pub enum FontWeight {
+/// Font weight as a CSS/OpenType numeric value (1-1000)
+///
+/// Any value in range is representable (e.g. 350 or 450 from a variable
+/// font). The standard weights are available as constants; the CamelCase
+/// constants keep the former enum variant syntax working, including in
+/// patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "u16", into = "u16"))]
+pub struct FontWeight(u16);
+
+#[allow(non_upper_case_globals)]
+impl FontWeight {
+    /// Smallest valid weight
+    pub const MIN: FontWeight = FontWeight(1);
+    /// Largest valid weight
+    pub const MAX: FontWeight = FontWeight(1000);
+
     /// Thin weight (100)
-    Thin = 100,
+    pub const THIN: FontWeight = FontWeight(100);
     /// Extra light weight (200)
-    ExtraLight = 200,
+    pub const EXTRA_LIGHT: FontWeight = FontWeight(200);
     /// Light weight (300)
-    Light = 300,
+    pub const LIGHT: FontWeight = FontWeight(300);
     /// Regular/normal weight (400)
-    Regular = 400,
+    pub const REGULAR: FontWeight = FontWeight(400);
     /// Medium weight (500)
-    Medium = 500,
+    pub const MEDIUM: FontWeight = FontWeight(500);
     /// Semi-bold weight (600)
-    SemiBold = 600,
+    pub const SEMI_BOLD: FontWeight = FontWeight(600);
     /// Bold weight (700)
-    Bold = 700,
+    pub const BOLD: FontWeight = FontWeight(700);
     /// Extra bold weight (800)
-    ExtraBold = 800,
+    pub const EXTRA_BOLD: FontWeight = FontWeight(800);
     /// Black/heavy weight (900)
-    Black = 900,
+    pub const BLACK: FontWeight = FontWeight(900);
+
+    /// Thin weight (100), enum-style alias of [`FontWeight::THIN`]
+    pub const Thin: FontWeight = FontWeight::THIN;
+    /// Extra light weight (200), enum-style alias of [`FontWeight::EXTRA_LIGHT`]
+    pub const ExtraLight: FontWeight = FontWeight::EXTRA_LIGHT;
+    /// Light weight (300), enum-style alias of [`FontWeight::LIGHT`]
+    pub const Light: FontWeight = FontWeight::LIGHT;
+    /// Regular weight (400), enum-style alias of [`FontWeight::REGULAR`]
+    pub const Regular: FontWeight = FontWeight::REGULAR;
+    /// Medium weight (500), enum-style alias of [`FontWeight::MEDIUM`]
+    pub const Medium: FontWeight = FontWeight::MEDIUM;
+    /// Semi-bold weight (600), enum-style alias of [`FontWeight::SEMI_BOLD`]
+    pub const SemiBold: FontWeight = FontWeight::SEMI_BOLD;
+    /// Bold weight (700), enum-style alias of [`FontWeight::BOLD`]
+    pub const Bold: FontWeight = FontWeight::BOLD;
+    /// Extra bold weight (800), enum-style alias of [`FontWeight::EXTRA_BOLD`]
+    pub const ExtraBold: FontWeight = FontWeight::EXTRA_BOLD;
+    /// Black weight (900), enum-style alias of [`FontWeight::BLACK`]
+    pub const Black: FontWeight = FontWeight::BLACK;
+
+    /// Create a weight, clamping the value to the valid 1-1000 range
+    pub const fn new(value: u16) -> Self {
+        if value < Self::MIN.0 {
+            Self::MIN
+        } else if value > Self::MAX.0 {
+            Self::MAX
+        } else {
+            FontWeight(value)
+        }
+    }
+
+    /// Create a weight from a fractional value (e.g. a `wght` axis
+    /// coordinate), rounding and clamping it to the valid range
+    pub fn from_f32(value: f32) -> Self {
+        Self::new(value.round().clamp(0.0, u16::MAX as f32) as u16)
+    }
+
+    /// Numeric weight value
+    pub const fn value(self) -> u16 {
+        self.0
+    }
+
+    /// Whether this weight is bold enough to be treated as bold (>= 600)
+    pub const fn is_bold(self) -> bool {
+        self.0 >= Self::SEMI_BOLD.0
+    }
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        FontWeight::REGULAR
+    }
+}
+
+impl From<u16> for FontWeight {
+    fn from(value: u16) -> Self {
+        FontWeight::new(value)
+    }
+}
+
+impl From<FontWeight> for u16 {
+    fn from(weight: FontWeight) -> Self {
+        weight.value()
+    }
 }
 
 /// Font style variations
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStyle {
     /// Normal/upright style
     Normal,
@@ -36,8 +115,9 @@ pub enum FontStyle {
 }
 
 /// Font stretch values
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u16)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStretch {
     /// Ultra condensed (50%)
     UltraCondensed = 50,
@@ -61,6 +141,7 @@ pub enum FontStretch {
 
 /// Text direction
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     /// Left-to-right text direction
     LeftToRight,
@@ -72,8 +153,89 @@ pub enum Direction {
     BottomToTop,
 }
 
+impl Direction {
+    /// Whether text runs along columns rather than lines
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::TopToBottom | Direction::BottomToTop)
+    }
+}
+
+/// Unicode script identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Script {
+    /// Latin script
+    Latin,
+    /// Arabic script
+    Arabic,
+    /// Hebrew script
+    Hebrew,
+    /// Cyrillic script
+    Cyrillic,
+    /// Greek script
+    Greek,
+    /// Han (Chinese) script
+    Han,
+    /// Hangul (Korean) script
+    Hangul,
+    /// Hiragana (Japanese) script
+    Hiragana,
+    /// Katakana (Japanese) script
+    Katakana,
+    /// Common script (shared characters)
+    Common,
+}
+
+impl Script {
+    /// Whether the script is Han, kana or Hangul, whose text is justified
+    /// between characters rather than words
+    pub fn is_cjk(self) -> bool {
+        matches!(
+            self,
+            Script::Han | Script::Hiragana | Script::Katakana | Script::Hangul
+        )
+    }
+
+    /// Baseline horizontal text of the script is aligned by: the bottom of
+    /// the ideographic em box for CJK, the alphabetic baseline otherwise
+    pub fn dominant_baseline(self) -> Baseline {
+        if self.is_cjk() {
+            Baseline::Ideographic
+        } else {
+            Baseline::Alphabetic
+        }
+    }
+}
+
+/// Baseline glyphs are aligned by, as named in the OpenType BASE table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Baseline {
+    /// Baseline of Latin, Greek and Cyrillic letters (`romn`)
+    Alphabetic,
+    /// Baseline Indic letters hang from (`hang`)
+    Hanging,
+    /// Bottom of the ideographic em box (`ideo`)
+    Ideographic,
+    /// Top of the ideographic em box (`idtp`)
+    IdeographicTop,
+}
+
+impl Baseline {
+    /// OpenType BASE tag of the baseline
+    pub fn tag(self) -> &'static str {
+        match self {
+            Baseline::Alphabetic => "romn",
+            Baseline::Hanging => "hang",
+            Baseline::Ideographic => "ideo",
+            Baseline::IdeographicTop => "idtp",
+        }
+    }
+}
+
 /// Glyph rasterization mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RenderMode {
     /// 1-bit monochrome rendering
     Mono,
@@ -91,6 +253,8 @@ pub enum RenderMode {
 
 /// 2D point
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "[f32; 2]", into = "[f32; 2]"))]
 pub struct Point {
     /// X coordinate
     pub x: f32,
@@ -98,8 +262,22 @@ pub struct Point {
     pub y: f32,
 }
 
+impl From<[f32; 2]> for Point {
+    fn from([x, y]: [f32; 2]) -> Self {
+        Point { x, y }
+    }
+}
+
+impl From<Point> for [f32; 2] {
+    fn from(value: Point) -> Self {
+        [value.x, value.y]
+    }
+}
+
 /// 2D vector
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "[f32; 2]", into = "[f32; 2]"))]
 pub struct Vector {
     /// X component
     pub x: f32,
@@ -107,8 +285,22 @@ pub struct Vector {
     pub y: f32,
 }
 
+impl From<[f32; 2]> for Vector {
+    fn from([x, y]: [f32; 2]) -> Self {
+        Vector { x, y }
+    }
+}
+
+impl From<Vector> for [f32; 2] {
+    fn from(value: Vector) -> Self {
+        [value.x, value.y]
+    }
+}
+
 /// Glyph identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct GlyphId {
     /// Glyph ID value
     pub id: u32,
@@ -117,8 +309,85 @@ pub struct GlyphId {
 /// Font identifier
 pub type FontId = usize;
 
+/// Raw font file bytes shared between components
+///
+/// The registry, shaper and renderer hand the same allocation to each other
+/// by cloning the `Arc`, so a face's data is never copied once loaded.
+pub type FontData = std::sync::Arc<[u8]>;
+
+/// Position on a single variation axis of a variable font
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariationCoordinate {
+    /// Axis tag (e.g. `*b"wght"`)
+    pub tag: [u8; 4],
+    /// User-space axis value
+    pub value: f32,
+}
+
+/// Palette a color font's `CPAL` colors are taken from (CSS `base-palette`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BasePalette {
+    /// The font's default palette (palette 0)
+    #[default]
+    Normal,
+    /// The first palette flagged as usable with a light background
+    Light,
+    /// The first palette flagged as usable with a dark background
+    Dark,
+    /// Palette by index
+    Index(u16),
+}
+
+/// Replacement for one palette entry (CSS `override-colors`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PaletteOverride {
+    /// Index of the entry in the palette
+    pub index: u16,
+    /// Straight (not premultiplied) RGBA color
+    pub color: [u8; 4],
+}
+
+/// Color palette selection for color glyphs (CSS `font-palette`)
+///
+/// `normal`, `light` and `dark` pick a palette of the font;
+/// `@font-palette-values` rules additionally override individual entries.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontPalette {
+    /// Palette to start from
+    pub base: BasePalette,
+    /// Entries replaced in the base palette; later overrides of the same
+    /// index win
+    pub overrides: Vec<PaletteOverride>,
+}
+
+impl FontPalette {
+    /// Palette `base` without overrides
+    pub fn new(base: BasePalette) -> Self {
+        Self {
+            base,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Replace entry `index` with a straight RGBA `color`
+    pub fn with_override(mut self, index: u16, color: [u8; 4]) -> Self {
+        self.overrides.push(PaletteOverride { index, color });
+        self
+    }
+}
+
 /// Font metrics and measurements
-#[derive(Debug, Clone, Copy)]
+///
+/// `ascent`, `descent` and `line_gap` are the metrics used for line layout:
+/// the OS/2 typo metrics when `use_typo_metrics` is set, otherwise the hhea
+/// metrics. The raw values of each source are kept for callers that need a
+/// specific one (e.g. win metrics for clipping).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FontMetrics {
     /// Units per em
     pub units_per_em: u16,
@@ -128,6 +397,28 @@ pub struct FontMetrics {
     pub descent: f32,
     /// Line gap in font units
     pub line_gap: f32,
+    /// hhea ascender in font units
+    pub hhea_ascent: f32,
+    /// hhea descender in font units (typically negative)
+    pub hhea_descent: f32,
+    /// hhea line gap in font units
+    pub hhea_line_gap: f32,
+    /// OS/2 sTypoAscender in font units
+    pub typo_ascent: f32,
+    /// OS/2 sTypoDescender in font units (typically negative)
+    pub typo_descent: f32,
+    /// OS/2 sTypoLineGap in font units
+    pub typo_line_gap: f32,
+    /// OS/2 usWinAscent in font units
+    pub win_ascent: f32,
+    /// OS/2 usWinDescent in font units, negated to match `descent`
+    pub win_descent: f32,
+    /// Whether OS/2 fsSelection sets USE_TYPO_METRICS
+    pub use_typo_metrics: bool,
+    /// Average advance width of lowercase glyphs (OS/2 xAvgCharWidth) in font units
+    pub avg_char_width: f32,
+    /// Maximum advance width (hhea advanceWidthMax) in font units
+    pub max_advance: f32,
     /// Cap height in font units
     pub cap_height: f32,
     /// X-height in font units
@@ -138,8 +429,172 @@ pub struct FontMetrics {
     pub underline_thickness: f32,
 }
 
+impl FontMetrics {
+    /// Default line height (ascent - descent + line gap) in font units
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+
+    /// Scale all length metrics by a factor (e.g. font size / units per em)
+    pub fn scaled(&self, factor: f32) -> FontMetrics {
+        FontMetrics {
+            units_per_em: self.units_per_em,
+            ascent: self.ascent * factor,
+            descent: self.descent * factor,
+            line_gap: self.line_gap * factor,
+            hhea_ascent: self.hhea_ascent * factor,
+            hhea_descent: self.hhea_descent * factor,
+            hhea_line_gap: self.hhea_line_gap * factor,
+            typo_ascent: self.typo_ascent * factor,
+            typo_descent: self.typo_descent * factor,
+            typo_line_gap: self.typo_line_gap * factor,
+            win_ascent: self.win_ascent * factor,
+            win_descent: self.win_descent * factor,
+            use_typo_metrics: self.use_typo_metrics,
+            avg_char_width: self.avg_char_width * factor,
+            max_advance: self.max_advance * factor,
+            cap_height: self.cap_height * factor,
+            x_height: self.x_height * factor,
+            underline_position: self.underline_position * factor,
+            underline_thickness: self.underline_thickness * factor,
+        }
+    }
+}
+
+/// Metric overrides of a web font face (the `size-adjust`,
+/// `ascent-override`, `descent-override` and `line-gap-override`
+/// descriptors of `@font-face`)
+///
+/// Pages give fallback faces the metrics of the web font they stand in
+/// for, so text laid out during the `font-display` block or swap period
+/// barely moves when the web font arrives. Overrides are fractions of the
+/// font size (CSS percentages divided by 100); `size_adjust` also scales
+/// the overridden metrics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontMetricsOverrides {
+    /// Scale of glyphs and metrics relative to the font size (1.0 keeps it)
+    pub size_adjust: f32,
+    /// Ascent as a fraction of the font size, instead of the font's
+    pub ascent: Option<f32>,
+    /// Descent below the baseline as a positive fraction of the font size,
+    /// instead of the font's
+    pub descent: Option<f32>,
+    /// Line gap as a fraction of the font size, instead of the font's
+    pub line_gap: Option<f32>,
+}
+
+impl Default for FontMetricsOverrides {
+    fn default() -> Self {
+        Self {
+            size_adjust: 1.0,
+            ascent: None,
+            descent: None,
+            line_gap: None,
+        }
+    }
+}
+
+impl FontMetricsOverrides {
+    /// `metrics` in font units scaled to `size` pixels, with the overrides
+    /// applied
+    pub fn apply(&self, metrics: &FontMetrics, size: f32) -> FontMetrics {
+        let size = size * self.size_adjust;
+        let mut scaled = metrics.scaled(size / f32::from(metrics.units_per_em));
+        if let Some(ascent) = self.ascent {
+            scaled.ascent = ascent * size;
+        }
+        if let Some(descent) = self.descent {
+            scaled.descent = -descent * size;
+        }
+        if let Some(line_gap) = self.line_gap {
+            scaled.line_gap = line_gap * size;
+        }
+        scaled
+    }
+}
+
+/// Styling the font system may synthesize when the matched face lacks it
+/// (CSS `font-synthesis`)
+///
+/// All kinds are allowed by default; pages that set `font-synthesis: none`
+/// get the face as it is instead of fake bold, slanted or scaled capitals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontSynthesis {
+    /// Embolden faces lighter than a bold request
+    pub weight: bool,
+    /// Slant upright faces for italic and oblique requests
+    pub style: bool,
+    /// Scale capitals for small caps the face has no glyphs for
+    pub small_caps: bool,
+}
+
+impl FontSynthesis {
+    /// Every kind of synthesis allowed
+    pub const ALL: FontSynthesis = FontSynthesis {
+        weight: true,
+        style: true,
+        small_caps: true,
+    };
+
+    /// No synthesis
+    pub const NONE: FontSynthesis = FontSynthesis {
+        weight: false,
+        style: false,
+        small_caps: false,
+    };
+
+    /// Synthesis allowed by both `self` and `other`
+    pub fn intersect(self, other: FontSynthesis) -> FontSynthesis {
+        FontSynthesis {
+            weight: self.weight && other.weight,
+            style: self.style && other.style,
+            small_caps: self.small_caps && other.small_caps,
+        }
+    }
+}
+
+impl Default for FontSynthesis {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Which installed fonts untrusted callers can match
+///
+/// Web content can fingerprint users by probing which fonts are installed.
+/// A restrictive policy hides installed fonts outside a known set from
+/// [`MatchContext::Untrusted`] matching; fonts registered from memory, such
+/// as a page's web fonts, stay visible.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FontVisibility {
+    /// Every font is visible
+    #[default]
+    All,
+    /// Only widely installed families and the platform's defaults for
+    /// generic family names
+    Standard,
+    /// Only installed fonts of these families (case-insensitive)
+    Allowlist(Vec<String>),
+}
+
+/// Who a font match is made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchContext {
+    /// Browser UI and other trusted callers, which see every font
+    #[default]
+    Privileged,
+    /// Web content, which only sees the fonts the [`FontVisibility`]
+    /// policy allows
+    Untrusted,
+}
+
 /// Font selection descriptor
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FontDescriptor {
     /// Font family names (fallback chain)
     pub family: Vec<String>,
@@ -151,10 +606,113 @@ pub struct FontDescriptor {
     pub stretch: FontStretch,
     /// Font size in pixels
     pub size: f32,
+    /// Styling that may be synthesized for the matched face
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub synthesis: FontSynthesis,
+}
+
+impl Default for FontDescriptor {
+    fn default() -> Self {
+        Self {
+            family: vec!["sans-serif".to_string()],
+            weight: FontWeight::Regular,
+            style: FontStyle::Normal,
+            stretch: FontStretch::Normal,
+            size: 16.0,
+            synthesis: FontSynthesis::ALL,
+        }
+    }
+}
+
+impl FontDescriptor {
+    /// Start building a descriptor from the defaults
+    pub fn builder() -> FontDescriptorBuilder {
+        FontDescriptorBuilder::default()
+    }
+}
+
+/// Builder for [`FontDescriptor`]
+///
+/// Families are added in fallback order; without any, the descriptor keeps
+/// the default `sans-serif`.
+///
+/// ```
+/// use font_types::{FontDescriptor, FontStyle};
+///
+/// let descriptor = FontDescriptor::builder()
+///     .family("Inter")
+///     .family("sans-serif")
+///     .weight(600)
+///     .style(FontStyle::Italic)
+///     .build();
+/// assert_eq!(descriptor.weight.value(), 600);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FontDescriptorBuilder {
+    family: Vec<String>,
+    descriptor: FontDescriptor,
+}
+
+impl FontDescriptorBuilder {
+    /// Append a family to the fallback chain
+    pub fn family(mut self, family: impl Into<String>) -> Self {
+        self.family.push(family.into());
+        self
+    }
+
+    /// Append several families to the fallback chain
+    pub fn families<I, S>(mut self, families: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.family.extend(families.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the weight, e.g. `600` or `FontWeight::BOLD`
+    pub fn weight(mut self, weight: impl Into<FontWeight>) -> Self {
+        self.descriptor.weight = weight.into();
+        self
+    }
+
+    /// Set the style
+    pub fn style(mut self, style: FontStyle) -> Self {
+        self.descriptor.style = style;
+        self
+    }
+
+    /// Set the stretch
+    pub fn stretch(mut self, stretch: FontStretch) -> Self {
+        self.descriptor.stretch = stretch;
+        self
+    }
+
+    /// Set the size in pixels
+    pub fn size(mut self, size: f32) -> Self {
+        self.descriptor.size = size;
+        self
+    }
+
+    /// Set the styling that may be synthesized
+    pub fn synthesis(mut self, synthesis: FontSynthesis) -> Self {
+        self.descriptor.synthesis = synthesis;
+        self
+    }
+
+    /// Finish the descriptor
+    pub fn build(self) -> FontDescriptor {
+        let mut descriptor = self.descriptor;
+        if !self.family.is_empty() {
+            descriptor.family = self.family;
+        }
+        descriptor
+    }
 }
 
 /// Positioned glyph with layout information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PositionedGlyph {
     /// Glyph identifier
     pub glyph_id: GlyphId,
@@ -166,10 +724,22 @@ pub struct PositionedGlyph {
     pub advance: Vector,
     /// Positioning offset
     pub offset: Vector,
+    /// Glyph size relative to the shaped font size (below 1.0 for
+    /// synthesized small caps, times the face's `size_adjust`)
+    pub scale: f32,
+    /// Byte offset in the shaped text of the cluster the glyph belongs to
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cluster: u32,
+    /// Whether splitting the text at the start of this glyph's cluster and
+    /// shaping the parts separately would give different glyphs, e.g.
+    /// inside a ligature or kerning pair (HarfBuzz's `UNSAFE_TO_BREAK`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unsafe_to_break: bool,
 }
 
 /// Shaped text result
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapedText {
     /// Positioned glyphs
     pub glyphs: Vec<PositionedGlyph>,
@@ -181,7 +751,439 @@ pub struct ShapedText {
     pub baseline: f32,
 }
 
+impl ShapedText {
+    /// Iterate over runs of consecutive glyphs drawn with one font at one
+    /// scale, in glyph order
+    ///
+    /// Renderers can issue one draw call per run. `ShapedText` doesn't
+    /// record direction or script; use [`ShapedParagraph::runs`] when runs
+    /// must also split there.
+    pub fn runs(&self) -> GlyphRuns<'_> {
+        GlyphRuns {
+            glyphs: &self.glyphs,
+        }
+    }
+
+    /// Tight bounding box of the ink of all glyphs
+    ///
+    /// `extents` returns a glyph's ink bounds relative to its origin at the
+    /// shaped font size (e.g. from `GlyphRenderer::glyph_extents`), or `None`
+    /// for glyphs without ink such as spaces. Each box is scaled by the
+    /// glyph's `scale` and moved to its position, which includes the
+    /// shaping offset. Returns `None` when no glyph has ink.
+    pub fn ink_bounding_box<F>(&self, mut extents: F) -> Option<BoundingBox>
+    where
+        F: FnMut(&PositionedGlyph) -> Option<BoundingBox>,
+    {
+        self.glyphs
+            .iter()
+            .filter_map(|glyph| {
+                let ink = extents(glyph)?;
+                Some(BoundingBox {
+                    min_x: glyph.position.x + ink.min_x * glyph.scale,
+                    min_y: glyph.position.y + ink.min_y * glyph.scale,
+                    max_x: glyph.position.x + ink.max_x * glyph.scale,
+                    max_y: glyph.position.y + ink.max_y * glyph.scale,
+                })
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+}
+
+/// Consecutive glyphs of a [`ShapedText`] sharing a font and scale
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphRun<'a> {
+    /// Font of the glyphs
+    pub font_id: FontId,
+    /// Scale of the glyphs relative to the shaped font size
+    pub scale: f32,
+    /// The glyphs, in order
+    pub glyphs: &'a [PositionedGlyph],
+    /// Sum of the glyphs' advances
+    pub advance: Vector,
+}
+
+/// Iterator over the [`GlyphRun`]s of a [`ShapedText`], grouping glyphs
+/// as it goes
+#[derive(Debug, Clone)]
+pub struct GlyphRuns<'a> {
+    glyphs: &'a [PositionedGlyph],
+}
+
+impl<'a> Iterator for GlyphRuns<'a> {
+    type Item = GlyphRun<'a>;
+
+    fn next(&mut self) -> Option<GlyphRun<'a>> {
+        let first = self.glyphs.first()?;
+        let mut advance = Vector { x: 0.0, y: 0.0 };
+        let mut len = 0;
+        for glyph in self.glyphs {
+            if glyph.font_id != first.font_id || glyph.scale != first.scale {
+                break;
+            }
+            advance.x += glyph.advance.x;
+            advance.y += glyph.advance.y;
+            len += 1;
+        }
+        let (glyphs, rest) = self.glyphs.split_at(len);
+        self.glyphs = rest;
+        Some(GlyphRun {
+            font_id: first.font_id,
+            scale: first.scale,
+            glyphs,
+            advance,
+        })
+    }
+}
+
+/// Vertical metrics of a run's font at the size it was shaped at, in
+/// pixels
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunMetrics {
+    /// Ascent above the glyph origin
+    pub ascent: f32,
+    /// Descent below the glyph origin (positive)
+    pub descent: f32,
+    /// Baseline positions above the glyph origin from the font's BASE
+    /// table, for the run's script
+    pub baselines: Vec<(Baseline, f32)>,
+}
+
+impl RunMetrics {
+    /// Position of `baseline` above the glyph origin
+    ///
+    /// Baselines missing from the font's BASE table are synthesized as
+    /// browsers do: the alphabetic baseline at the origin, the hanging one
+    /// at 80% of the ascent and the ideographic em box spanning the
+    /// descent and ascent.
+    pub fn baseline(&self, baseline: Baseline) -> f32 {
+        self.baselines
+            .iter()
+            .find(|(tag, _)| *tag == baseline)
+            .map_or_else(
+                || match baseline {
+                    Baseline::Alphabetic => 0.0,
+                    Baseline::Hanging => self.ascent * 0.8,
+                    Baseline::Ideographic => -self.descent,
+                    Baseline::IdeographicTop => self.ascent,
+                },
+                |&(_, position)| position,
+            )
+    }
+}
+
+/// A run of glyphs shaped with one font, direction and script
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapedRun {
+    /// Font every glyph of the run was shaped with
+    pub font_id: FontId,
+    /// Direction the run was shaped in
+    pub direction: Direction,
+    /// Script the run was shaped as
+    pub script: Script,
+    /// Byte range of the paragraph text the run covers
+    pub text_range: std::ops::Range<usize>,
+    /// Glyphs, positioned within the paragraph, with clusters that are byte
+    /// offsets into the paragraph text
+    pub glyphs: Vec<PositionedGlyph>,
+    /// Metrics of the run's font, for aligning runs on a line
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub metrics: RunMetrics,
+}
+
+impl ShapedRun {
+    /// Total advance of the run's glyphs along its direction
+    pub fn advance(&self) -> f32 {
+        self.glyphs
+            .iter()
+            .map(|glyph| {
+                if self.direction.is_vertical() {
+                    glyph.advance.y
+                } else {
+                    glyph.advance.x
+                }
+            })
+            .sum()
+    }
+}
+
+/// Shaped text split into runs at font, direction and script changes
+///
+/// Font fallback can shape parts of a paragraph with different fonts.
+/// Keeping them as runs, in visual order, lets layout and rendering switch
+/// font bindings or atlas pages once per run instead of checking every
+/// glyph's `font_id`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapedParagraph {
+    /// Runs in visual order
+    pub runs: Vec<ShapedRun>,
+    /// Total width
+    pub width: f32,
+    /// Total height
+    pub height: f32,
+    /// Baseline offset
+    pub baseline: f32,
+}
+
+impl ShapedParagraph {
+    /// Iterate over the glyphs of all runs in visual order
+    pub fn glyphs(&self) -> impl Iterator<Item = &PositionedGlyph> {
+        self.runs.iter().flat_map(|run| &run.glyphs)
+    }
+
+    /// Number of glyphs in all runs
+    pub fn glyph_count(&self) -> usize {
+        self.runs.iter().map(|run| run.glyphs.len()).sum()
+    }
+
+    /// Flatten into a `ShapedText` with per-glyph font IDs
+    pub fn to_shaped_text(&self) -> ShapedText {
+        ShapedText {
+            glyphs: self.glyphs().cloned().collect(),
+            width: self.width,
+            height: self.height,
+            baseline: self.baseline,
+        }
+    }
+}
+
+impl From<&ShapedParagraph> for ShapedText {
+    fn from(paragraph: &ShapedParagraph) -> Self {
+        paragraph.to_shaped_text()
+    }
+}
+
+/// Shaped text in a compact structure-of-arrays layout
+///
+/// Glyph IDs and horizontal advances are kept in parallel arrays and
+/// positions are reconstructed from the advances and offsets, the way the
+/// shaper lays glyphs out. Fonts, vertical advances, offsets and scales are
+/// only stored per glyph when they vary, and positions only when they don't
+/// follow from the advances (e.g. after justification). Simple horizontal
+/// text takes 12 bytes per glyph instead of a full `PositionedGlyph`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackedShapedText {
+    /// Font of all glyphs when `font_ids` is empty
+    font_id: FontId,
+    /// Per-glyph fonts, empty when every glyph uses `font_id`
+    font_ids: Vec<FontId>,
+    glyph_ids: Vec<u32>,
+    clusters: Vec<u32>,
+    /// Empty when breaking is safe at every glyph
+    unsafe_to_break: Vec<bool>,
+    x_advances: Vec<f32>,
+    /// Empty when every vertical advance is zero
+    y_advances: Vec<f32>,
+    /// Empty when every offset is zero
+    offsets: Vec<Vector>,
+    /// Empty when every scale is 1.0
+    scales: Vec<f32>,
+    /// Empty when positions follow from the advances and offsets
+    positions: Vec<Point>,
+    /// Total width
+    pub width: f32,
+    /// Total height
+    pub height: f32,
+    /// Baseline offset
+    pub baseline: f32,
+}
+
+impl PackedShapedText {
+    /// Pack shaped text
+    pub fn pack(shaped: &ShapedText) -> Self {
+        let glyphs = &shaped.glyphs;
+        let font_id = glyphs.first().map_or(0, |glyph| glyph.font_id);
+        let zero = Vector { x: 0.0, y: 0.0 };
+        let mut packed = PackedShapedText {
+            font_id,
+            font_ids: varying(glyphs, font_id, |glyph| glyph.font_id),
+            glyph_ids: glyphs.iter().map(|glyph| glyph.glyph_id.id).collect(),
+            clusters: glyphs.iter().map(|glyph| glyph.cluster).collect(),
+            unsafe_to_break: varying(glyphs, false, |glyph| glyph.unsafe_to_break),
+            x_advances: glyphs.iter().map(|glyph| glyph.advance.x).collect(),
+            y_advances: varying(glyphs, 0.0, |glyph| glyph.advance.y),
+            offsets: varying(glyphs, zero, |glyph| glyph.offset),
+            scales: varying(glyphs, 1.0, |glyph| glyph.scale),
+            positions: Vec::new(),
+            width: shaped.width,
+            height: shaped.height,
+            baseline: shaped.baseline,
+        };
+        if !packed
+            .iter()
+            .zip(glyphs)
+            .all(|(derived, glyph)| derived.position == glyph.position)
+        {
+            packed.positions = glyphs.iter().map(|glyph| glyph.position).collect();
+        }
+        packed
+    }
+
+    /// Number of glyphs
+    pub fn len(&self) -> usize {
+        self.glyph_ids.len()
+    }
+
+    /// Whether there are no glyphs
+    pub fn is_empty(&self) -> bool {
+        self.glyph_ids.is_empty()
+    }
+
+    /// Iterate over the positioned glyphs
+    pub fn iter(&self) -> PackedGlyphs<'_> {
+        PackedGlyphs {
+            text: self,
+            index: 0,
+            pen: Point { x: 0.0, y: 0.0 },
+        }
+    }
+
+    /// Expand into a `ShapedText`
+    pub fn unpack(&self) -> ShapedText {
+        ShapedText {
+            glyphs: self.iter().collect(),
+            width: self.width,
+            height: self.height,
+            baseline: self.baseline,
+        }
+    }
+
+    /// Heap bytes held by the glyph arrays
+    pub fn heap_bytes(&self) -> usize {
+        self.font_ids.capacity() * std::mem::size_of::<FontId>()
+            + (self.glyph_ids.capacity() + self.clusters.capacity()) * std::mem::size_of::<u32>()
+            + self.unsafe_to_break.capacity()
+            + (self.x_advances.capacity() + self.y_advances.capacity() + self.scales.capacity())
+                * std::mem::size_of::<f32>()
+            + self.offsets.capacity() * std::mem::size_of::<Vector>()
+            + self.positions.capacity() * std::mem::size_of::<Point>()
+    }
+}
+
+/// One field of every glyph, or nothing when all equal `common`
+fn varying<T: Copy + PartialEq>(
+    glyphs: &[PositionedGlyph],
+    common: T,
+    field: impl Fn(&PositionedGlyph) -> T,
+) -> Vec<T> {
+    if glyphs.iter().all(|glyph| field(glyph) == common) {
+        Vec::new()
+    } else {
+        glyphs.iter().map(field).collect()
+    }
+}
+
+impl From<&ShapedText> for PackedShapedText {
+    fn from(shaped: &ShapedText) -> Self {
+        PackedShapedText::pack(shaped)
+    }
+}
+
+impl<'a> IntoIterator for &'a PackedShapedText {
+    type Item = PositionedGlyph;
+    type IntoIter = PackedGlyphs<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the glyphs of a [`PackedShapedText`]
+#[derive(Debug, Clone)]
+pub struct PackedGlyphs<'a> {
+    text: &'a PackedShapedText,
+    index: usize,
+    /// Pen position before the next glyph's offset
+    pen: Point,
+}
+
+impl Iterator for PackedGlyphs<'_> {
+    type Item = PositionedGlyph;
+
+    fn next(&mut self) -> Option<PositionedGlyph> {
+        let text = self.text;
+        let index = self.index;
+        let glyph_id = *text.glyph_ids.get(index)?;
+        self.index += 1;
+
+        let advance = Vector {
+            x: text.x_advances[index],
+            y: text.y_advances.get(index).copied().unwrap_or(0.0),
+        };
+        let offset = text
+            .offsets
+            .get(index)
+            .copied()
+            .unwrap_or(Vector { x: 0.0, y: 0.0 });
+        let position = text.positions.get(index).copied().unwrap_or(Point {
+            x: self.pen.x + offset.x,
+            y: self.pen.y + offset.y,
+        });
+        self.pen.x += advance.x;
+        self.pen.y += advance.y;
+
+        Some(PositionedGlyph {
+            glyph_id: GlyphId { id: glyph_id },
+            font_id: text.font_ids.get(index).copied().unwrap_or(text.font_id),
+            position,
+            advance,
+            offset,
+            scale: text.scales.get(index).copied().unwrap_or(1.0),
+            cluster: text.clusters[index],
+            unsafe_to_break: text.unsafe_to_break.get(index).copied().unwrap_or(false),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.text.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for PackedGlyphs<'_> {}
+
+/// Axis-aligned bounding box, y up like font outlines
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingBox {
+    /// Minimum X
+    pub min_x: f32,
+    /// Minimum Y
+    pub min_y: f32,
+    /// Maximum X
+    pub max_x: f32,
+    /// Maximum Y
+    pub max_y: f32,
+}
+
+impl BoundingBox {
+    /// Width of the box
+    pub fn width(&self) -> f32 {
+        self.max_x - self.min_x
+    }
+
+    /// Height of the box
+    pub fn height(&self) -> f32 {
+        self.max_y - self.min_y
+    }
+
+    /// Smallest box containing both boxes
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+}
+
 /// Rendered glyph bitmap
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlyphBitmap {
     /// Bitmap width
     pub width: u32,
@@ -224,17 +1226,48 @@ mod tests {
     #[test]
     fn test_font_weight_numeric_values() {
         // Given: Font weight should map to numeric values
-        // When: Casting to numeric values
+        // When: Reading the numeric value
         // Then: Values should match OpenType specification
-        assert_eq!(FontWeight::Thin as u16, 100);
-        assert_eq!(FontWeight::ExtraLight as u16, 200);
-        assert_eq!(FontWeight::Light as u16, 300);
-        assert_eq!(FontWeight::Regular as u16, 400);
-        assert_eq!(FontWeight::Medium as u16, 500);
-        assert_eq!(FontWeight::SemiBold as u16, 600);
-        assert_eq!(FontWeight::Bold as u16, 700);
-        assert_eq!(FontWeight::ExtraBold as u16, 800);
-        assert_eq!(FontWeight::Black as u16, 900);
+        assert_eq!(FontWeight::Thin.value(), 100);
+        assert_eq!(FontWeight::ExtraLight.value(), 200);
+        assert_eq!(FontWeight::Light.value(), 300);
+        assert_eq!(FontWeight::Regular.value(), 400);
+        assert_eq!(FontWeight::Medium.value(), 500);
+        assert_eq!(FontWeight::SemiBold.value(), 600);
+        assert_eq!(FontWeight::Bold.value(), 700);
+        assert_eq!(FontWeight::ExtraBold.value(), 800);
+        assert_eq!(FontWeight::Black.value(), 900);
+    }
+
+    #[test]
+    fn test_font_weight_arbitrary_values() {
+        // Given: Intermediate weights such as those of variable fonts
+        // When: Creating them from numbers
+        // Then: They keep their value, order between named weights and clamp to 1-1000
+        let weight = FontWeight::new(450);
+        assert_eq!(weight.value(), 450);
+        assert!(FontWeight::Regular < weight && weight < FontWeight::Medium);
+        assert_eq!(FontWeight::from(350u16).value(), 350);
+        assert_eq!(u16::from(FontWeight::Bold), 700);
+        assert_eq!(FontWeight::new(0), FontWeight::MIN);
+        assert_eq!(FontWeight::new(2000), FontWeight::MAX);
+        assert_eq!(FontWeight::from_f32(449.6).value(), 450);
+        assert_eq!(FontWeight::default(), FontWeight::Regular);
+        assert!(FontWeight::new(600).is_bold());
+        assert!(!FontWeight::new(599).is_bold());
+    }
+
+    #[test]
+    fn test_font_weight_named_constants_match_in_patterns() {
+        // Given: Code written against the former enum variants
+        // When: Matching on the enum-style constants
+        // Then: The constants behave as patterns
+        let label = match FontWeight::new(700) {
+            FontWeight::Regular => "regular",
+            FontWeight::Bold => "bold",
+            _ => "other",
+        };
+        assert_eq!(label, "bold");
     }
 
     #[test]
@@ -262,7 +1295,7 @@ mod tests {
         // When: Cloning or copying it
         // Then: Should produce identical values
         let weight = FontWeight::Bold;
-        let cloned = weight.clone();
+        let cloned = weight;
         let copied = weight;
         assert_eq!(weight, cloned);
         assert_eq!(weight, copied);
@@ -313,7 +1346,7 @@ mod tests {
         // When: Cloning it
         // Then: Should produce identical value
         let style = FontStyle::Oblique(12.5);
-        let cloned = style.clone();
+        let cloned = style;
         assert_eq!(style, cloned);
     }
 
@@ -389,7 +1422,7 @@ mod tests {
         // When: Cloning or copying it
         // Then: Should produce identical values
         let dir = Direction::RightToLeft;
-        let cloned = dir.clone();
+        let cloned = dir;
         let copied = dir;
         assert_eq!(dir, cloned);
         assert_eq!(dir, copied);
@@ -425,7 +1458,7 @@ mod tests {
         // When: Cloning or copying it
         // Then: Should produce identical values
         let mode = RenderMode::SubpixelRgb;
-        let cloned = mode.clone();
+        let cloned = mode;
         let copied = mode;
         assert_eq!(mode, cloned);
         assert_eq!(mode, copied);
@@ -461,7 +1494,7 @@ mod tests {
         // When: Cloning or copying it
         // Then: Should produce identical values
         let point = Point { x: 7.5, y: 15.2 };
-        let cloned = point.clone();
+        let cloned = point;
         let copied = point;
         assert_eq!(point.x, cloned.x);
         assert_eq!(point.y, cloned.y);
@@ -499,7 +1532,7 @@ mod tests {
         // When: Cloning or copying it
         // Then: Should produce identical values
         let vector = Vector { x: -5.5, y: 8.3 };
-        let cloned = vector.clone();
+        let cloned = vector;
         let copied = vector;
         assert_eq!(vector.x, cloned.x);
         assert_eq!(vector.y, cloned.y);
@@ -548,7 +1581,7 @@ mod tests {
         // When: Cloning or copying it
         // Then: Should produce identical values
         let glyph_id = GlyphId { id: 99 };
-        let cloned = glyph_id.clone();
+        let cloned = glyph_id;
         let copied = glyph_id;
         assert_eq!(glyph_id, cloned);
         assert_eq!(glyph_id, copied);
@@ -583,6 +1616,7 @@ mod tests {
             x_height: 500.0,
             underline_position: -50.0,
             underline_thickness: 25.0,
+            ..Default::default()
         };
         assert_eq!(metrics.units_per_em, 1000);
         assert_eq!(metrics.ascent, 800.0);
@@ -608,12 +1642,28 @@ mod tests {
             x_height: 1062.0,
             underline_position: -204.0,
             underline_thickness: 102.0,
+            ..Default::default()
         };
-        let cloned = metrics.clone();
+        let cloned = metrics;
         assert_eq!(cloned.units_per_em, metrics.units_per_em);
         assert_eq!(cloned.ascent, metrics.ascent);
     }
 
+    #[test]
+    fn test_font_metrics_line_height() {
+        // Given: Selected vertical metrics
+        // When: Computing the default line height
+        // Then: Should be ascent - descent + line gap
+        let metrics = FontMetrics {
+            units_per_em: 2048,
+            ascent: 1900.0,
+            descent: -500.0,
+            line_gap: 67.0,
+            ..Default::default()
+        };
+        assert_eq!(metrics.line_height(), 2467.0);
+    }
+
     // ========== FontDescriptor Tests ==========
 
     #[test]
@@ -627,6 +1677,7 @@ mod tests {
             style: FontStyle::Italic,
             stretch: FontStretch::Normal,
             size: 16.0,
+            synthesis: FontSynthesis::ALL,
         };
         assert_eq!(descriptor.family.len(), 2);
         assert_eq!(descriptor.family[0], "Arial");
@@ -651,12 +1702,36 @@ mod tests {
             style: FontStyle::Normal,
             stretch: FontStretch::Normal,
             size: 12.0,
+            synthesis: FontSynthesis::ALL,
         };
         assert_eq!(descriptor.family[0], "CustomFont");
         assert_eq!(descriptor.family[1], "Arial");
         assert_eq!(descriptor.family[2], "sans-serif");
     }
 
+    #[test]
+    fn test_font_descriptor_builder() {
+        // Given: A builder with two families and a numeric weight
+        // When: Building the descriptor
+        let descriptor = FontDescriptor::builder()
+            .family("Inter")
+            .families(["Arial", "sans-serif"])
+            .weight(600)
+            .stretch(FontStretch::Condensed)
+            .size(24.0)
+            .build();
+
+        // Then: Families keep their order and unset fields are defaults
+        assert_eq!(descriptor.family, ["Inter", "Arial", "sans-serif"]);
+        assert_eq!(descriptor.weight, FontWeight::SEMI_BOLD);
+        assert_eq!(descriptor.style, FontStyle::Normal);
+        assert_eq!(descriptor.stretch, FontStretch::Condensed);
+        assert_eq!(descriptor.size, 24.0);
+
+        // And: Without families the default chain is kept
+        assert_eq!(FontDescriptor::builder().build(), FontDescriptor::default());
+    }
+
     #[test]
     fn test_font_descriptor_equality() {
         // Given: Two font descriptors
@@ -668,6 +1743,7 @@ mod tests {
             style: FontStyle::Normal,
             stretch: FontStretch::Normal,
             size: 14.0,
+            synthesis: FontSynthesis::ALL,
         };
         let d2 = FontDescriptor {
             family: vec!["Arial".to_string()],
@@ -675,6 +1751,7 @@ mod tests {
             style: FontStyle::Normal,
             stretch: FontStretch::Normal,
             size: 14.0,
+            synthesis: FontSynthesis::ALL,
         };
         assert_eq!(d1, d2);
     }
@@ -690,6 +1767,7 @@ mod tests {
             style: FontStyle::Oblique(10.0),
             stretch: FontStretch::Condensed,
             size: 18.0,
+            synthesis: FontSynthesis::ALL,
         };
         let cloned = descriptor.clone();
         assert_eq!(descriptor, cloned);
@@ -708,6 +1786,9 @@ mod tests {
             position: Point { x: 100.0, y: 200.0 },
             advance: Vector { x: 15.0, y: 0.0 },
             offset: Vector { x: 0.5, y: -0.5 },
+            scale: 1.0,
+            cluster: 0,
+            unsafe_to_break: false,
         };
         assert_eq!(glyph.glyph_id.id, 42);
         assert_eq!(glyph.font_id, 1);
@@ -728,6 +1809,9 @@ mod tests {
             position: Point { x: 50.0, y: 75.0 },
             advance: Vector { x: 12.5, y: 0.0 },
             offset: Vector { x: 0.0, y: 0.0 },
+            scale: 1.0,
+            cluster: 0,
+            unsafe_to_break: false,
         };
         let cloned = glyph.clone();
         assert_eq!(glyph.glyph_id, cloned.glyph_id);
@@ -748,6 +1832,9 @@ mod tests {
                 position: Point { x: 0.0, y: 0.0 },
                 advance: Vector { x: 10.0, y: 0.0 },
                 offset: Vector { x: 0.0, y: 0.0 },
+                scale: 1.0,
+                cluster: 0,
+                unsafe_to_break: false,
             },
             PositionedGlyph {
                 glyph_id: GlyphId { id: 2 },
@@ -755,6 +1842,9 @@ mod tests {
                 position: Point { x: 10.0, y: 0.0 },
                 advance: Vector { x: 12.0, y: 0.0 },
                 offset: Vector { x: 0.0, y: 0.0 },
+                scale: 1.0,
+                cluster: 0,
+                unsafe_to_break: false,
             },
         ];
 
@@ -802,6 +1892,267 @@ mod tests {
         assert_eq!(shaped.baseline, cloned.baseline);
     }
 
+    fn positioned(id: u32, x: f32, advance: f32, offset_y: f32) -> PositionedGlyph {
+        PositionedGlyph {
+            glyph_id: GlyphId { id },
+            font_id: 3,
+            position: Point { x, y: offset_y },
+            advance: Vector { x: advance, y: 0.0 },
+            offset: Vector {
+                x: 0.0,
+                y: offset_y,
+            },
+            scale: 1.0,
+            cluster: 0,
+            unsafe_to_break: false,
+        }
+    }
+
+    fn assert_same_glyphs(packed: &PackedShapedText, shaped: &ShapedText) {
+        assert_eq!(packed.len(), shaped.glyphs.len());
+        for (unpacked, glyph) in packed.iter().zip(&shaped.glyphs) {
+            assert_eq!(unpacked.glyph_id, glyph.glyph_id);
+            assert_eq!(unpacked.font_id, glyph.font_id);
+            assert_eq!(unpacked.position, glyph.position);
+            assert_eq!(unpacked.advance, glyph.advance);
+            assert_eq!(unpacked.offset, glyph.offset);
+            assert_eq!(unpacked.scale, glyph.scale);
+            assert_eq!(unpacked.cluster, glyph.cluster);
+            assert_eq!(unpacked.unsafe_to_break, glyph.unsafe_to_break);
+        }
+    }
+
+    #[test]
+    fn test_packed_shaped_text_derives_positions() {
+        // Given: Glyphs laid out from their advances, one with a mark offset
+        // When: Packing them
+        // Then: Only IDs, advances and offsets are stored, and unpacking
+        // restores every glyph
+        let shaped = ShapedText {
+            glyphs: vec![
+                positioned(1, 0.0, 10.0, 0.0),
+                positioned(2, 10.0, 0.0, 4.0),
+                positioned(3, 10.0, 12.5, 0.0),
+            ],
+            width: 22.5,
+            height: 16.0,
+            baseline: 12.0,
+        };
+
+        let packed = PackedShapedText::pack(&shaped);
+
+        assert!(packed.positions.is_empty() && packed.font_ids.is_empty());
+        assert!(packed.scales.is_empty() && packed.y_advances.is_empty());
+        assert_eq!(packed.offsets.len(), 3);
+        assert_eq!(packed.iter().len(), 3);
+        assert_same_glyphs(&packed, &shaped);
+        assert_eq!(packed.unpack().width, 22.5);
+    }
+
+    #[test]
+    fn test_packed_shaped_text_keeps_irregular_fields() {
+        // Given: Justified glyphs from two fonts, one at small caps scale
+        let mut shaped = ShapedText {
+            glyphs: vec![
+                positioned(1, 0.0, 10.0, 0.0),
+                positioned(2, 14.0, 10.0, 0.0),
+            ],
+            width: 24.0,
+            height: 16.0,
+            baseline: 12.0,
+        };
+        shaped.glyphs[1].font_id = 4;
+        shaped.glyphs[1].scale = 0.7;
+        shaped.glyphs[1].cluster = 1;
+        shaped.glyphs[1].unsafe_to_break = true;
+
+        // When: Packing them
+        let packed = PackedShapedText::from(&shaped);
+
+        // Then: Positions, fonts and scales are stored per glyph
+        assert_eq!(packed.positions.len(), 2);
+        assert_eq!(packed.font_ids, vec![3, 4]);
+        assert_eq!(packed.scales, vec![1.0, 0.7]);
+        assert_eq!(packed.unsafe_to_break, vec![false, true]);
+        assert_same_glyphs(&packed, &shaped);
+    }
+
+    #[test]
+    fn test_packed_shaped_text_is_smaller() {
+        let shaped = ShapedText {
+            glyphs: (0..100)
+                .map(|i| positioned(i, i as f32 * 8.0, 8.0, 0.0))
+                .collect(),
+            width: 800.0,
+            height: 16.0,
+            baseline: 12.0,
+        };
+
+        let packed = PackedShapedText::pack(&shaped);
+
+        assert_eq!(packed.heap_bytes(), 100 * 12);
+        assert!(packed.heap_bytes() * 3 < 100 * std::mem::size_of::<PositionedGlyph>());
+        assert!(PackedShapedText::pack(&ShapedText {
+            glyphs: Vec::new(),
+            ..shaped
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn test_run_metrics_baselines() {
+        // Given: A font whose BASE table only places the ideographic baseline
+        let metrics = RunMetrics {
+            ascent: 10.0,
+            descent: 4.0,
+            baselines: vec![(Baseline::Ideographic, -1.5)],
+        };
+
+        // When/Then: BASE values win and the other baselines are synthesized
+        assert_eq!(metrics.baseline(Baseline::Ideographic), -1.5);
+        assert_eq!(metrics.baseline(Baseline::Alphabetic), 0.0);
+        assert_eq!(metrics.baseline(Baseline::Hanging), 8.0);
+        assert_eq!(metrics.baseline(Baseline::IdeographicTop), 10.0);
+        assert_eq!(Script::Han.dominant_baseline(), Baseline::Ideographic);
+        assert_eq!(Script::Greek.dominant_baseline(), Baseline::Alphabetic);
+    }
+
+    #[test]
+    fn test_shaped_paragraph_flattens_runs() {
+        // Given: A paragraph with a run per font
+        let run = |font_id, glyphs: Vec<PositionedGlyph>, text_range| ShapedRun {
+            font_id,
+            direction: Direction::LeftToRight,
+            script: Script::Latin,
+            text_range,
+            glyphs: glyphs
+                .into_iter()
+                .map(|glyph| PositionedGlyph { font_id, ..glyph })
+                .collect(),
+            metrics: RunMetrics::default(),
+        };
+        let paragraph = ShapedParagraph {
+            runs: vec![
+                run(1, vec![positioned(5, 0.0, 8.0, 0.0)], 0..1),
+                run(
+                    2,
+                    vec![positioned(6, 8.0, 9.0, 0.0), positioned(7, 17.0, 9.0, 0.0)],
+                    1..7,
+                ),
+            ],
+            width: 26.0,
+            height: 16.0,
+            baseline: 12.0,
+        };
+
+        // When: Flattening it
+        let shaped = ShapedText::from(&paragraph);
+
+        // Then: Glyphs keep their order and fonts
+        assert_eq!(paragraph.glyph_count(), 3);
+        assert_eq!(paragraph.runs[1].advance(), 18.0);
+        let fonts: Vec<FontId> = shaped.glyphs.iter().map(|glyph| glyph.font_id).collect();
+        assert_eq!(fonts, [1, 2, 2]);
+        assert_eq!(shaped.width, 26.0);
+    }
+
+    #[test]
+    fn test_shaped_text_runs_group_glyphs_by_font_and_scale() {
+        // Given: Glyphs in two fonts, some of them synthesized small caps
+        let glyph = |font_id, scale, advance| PositionedGlyph {
+            font_id,
+            scale,
+            ..positioned(1, 0.0, advance, 0.0)
+        };
+        let shaped = ShapedText {
+            glyphs: vec![
+                glyph(1, 1.0, 8.0),
+                glyph(1, 1.0, 9.0),
+                glyph(2, 1.0, 7.0),
+                glyph(2, 0.7, 5.0),
+                glyph(2, 0.7, 5.0),
+                glyph(1, 1.0, 8.0),
+            ],
+            width: 42.0,
+            height: 16.0,
+            baseline: 12.0,
+        };
+
+        // When: Iterating over its runs
+        let runs: Vec<(FontId, f32, usize, f32)> = shaped
+            .runs()
+            .map(|run| (run.font_id, run.scale, run.glyphs.len(), run.advance.x))
+            .collect();
+
+        // Then: Consecutive glyphs sharing both are grouped, with their advance
+        assert_eq!(
+            runs,
+            [
+                (1, 1.0, 2, 17.0),
+                (2, 1.0, 1, 7.0),
+                (2, 0.7, 2, 10.0),
+                (1, 1.0, 1, 8.0)
+            ]
+        );
+        assert_eq!(
+            ShapedText {
+                glyphs: vec![],
+                ..shaped
+            }
+            .runs()
+            .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_shaped_text_ink_bounding_box() {
+        // Given: Two inked glyphs (the second offset and half size) and a space
+        // When: Aggregating their ink extents
+        // Then: The box covers both glyphs at their positions and scales
+        let glyph = |id, x, y, scale| PositionedGlyph {
+            glyph_id: GlyphId { id },
+            font_id: 0,
+            position: Point { x, y },
+            advance: Vector { x: 10.0, y: 0.0 },
+            offset: Vector { x: 0.0, y },
+            scale,
+            cluster: 0,
+            unsafe_to_break: false,
+        };
+        let shaped = ShapedText {
+            glyphs: vec![
+                glyph(1, 0.0, 0.0, 1.0),
+                glyph(3, 10.0, 0.0, 1.0),
+                glyph(1, 20.0, -4.0, 0.5),
+            ],
+            width: 25.0,
+            height: 16.0,
+            baseline: 12.0,
+        };
+        let extents = |glyph: &PositionedGlyph| {
+            (glyph.glyph_id.id != 3).then_some(BoundingBox {
+                min_x: 1.0,
+                min_y: -2.0,
+                max_x: 9.0,
+                max_y: 12.0,
+            })
+        };
+
+        let ink = shaped.ink_bounding_box(extents).unwrap();
+        assert_eq!(
+            ink,
+            BoundingBox {
+                min_x: 1.0,
+                min_y: -5.0,
+                max_x: 24.5,
+                max_y: 12.0,
+            }
+        );
+        assert_eq!(ink.width(), 23.5);
+        assert!(shaped.ink_bounding_box(|_| None).is_none());
+    }
+
     // ========== GlyphBitmap Tests ==========
 
     #[test]
@@ -873,4 +2224,58 @@ mod tests {
         };
         assert_eq!(bitmap_rgb.format, RenderMode::SubpixelRgb);
     }
+
+    // ========== Serde Tests ==========
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_font_descriptor_round_trip() {
+        // Given: A descriptor with an intermediate weight
+        // When: Serializing and deserializing it
+        // Then: The value round-trips and the weight is a plain number
+        let descriptor = FontDescriptor {
+            family: vec!["Inter".to_string()],
+            weight: FontWeight::new(450),
+            style: FontStyle::Oblique(12.0),
+            ..FontDescriptor::default()
+        };
+        let json = serde_json::to_string(&descriptor).unwrap();
+        assert!(json.contains("\"weight\":450"));
+        let decoded: FontDescriptor = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, descriptor);
+
+        // Out-of-range weights are clamped on the way in
+        let weight: FontWeight = serde_json::from_str("5000").unwrap();
+        assert_eq!(weight, FontWeight::MAX);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_shaped_text_uses_compact_vectors() {
+        // Given: Shaped text with one glyph
+        // When: Serializing it
+        // Then: Points and vectors are encoded as [x, y] pairs
+        let shaped = ShapedText {
+            glyphs: vec![PositionedGlyph {
+                glyph_id: GlyphId { id: 7 },
+                font_id: 1,
+                position: Point { x: 1.5, y: 0.0 },
+                advance: Vector { x: 9.0, y: 0.0 },
+                offset: Vector { x: 0.0, y: -1.0 },
+                scale: 1.0,
+                cluster: 0,
+                unsafe_to_break: false,
+            }],
+            width: 9.0,
+            height: 12.0,
+            baseline: 10.0,
+        };
+        let json = serde_json::to_string(&shaped).unwrap();
+        assert!(json.contains("\"glyph_id\":7"));
+        assert!(json.contains("\"position\":[1.5,0.0]"));
+
+        let decoded: ShapedText = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.glyphs[0].offset, Vector { x: 0.0, y: -1.0 });
+        assert_eq!(decoded.width, 9.0);
+    }
 }
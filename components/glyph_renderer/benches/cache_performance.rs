@@ -1,15 +1,23 @@
 //! Benchmarks for glyph cache performance
 //!
-//! Tests cache hit rates, memory usage, and cache eviction performance.
+//! Tests cache hit rates, memory usage, and cache eviction performance,
+//! compares cold (empty cache) against warm (fully cached) rendering of
+//! real glyphs from the vendored DejaVu Sans fixture, and replays a
+//! browser-like trace through each eviction policy.
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
-use glyph_renderer::GlyphRenderer;
-use glyph_renderer::types::{RenderMode, GlyphId, OpenTypeFont};
+use font_types::EvictionPolicy;
+use glyph_renderer::types::{GlyphId, OpenTypeFont, RenderMode};
+use glyph_renderer::{CacheConfig, GlyphRenderer};
+use std::path::PathBuf;
 
-/// Create a stub font for benchmarking
-fn create_stub_font() -> OpenTypeFont {
-    // Use from_data constructor since fields are private
-    OpenTypeFont::from_data(vec![0; 1024], 0)
+/// Load DejaVu Sans from the workspace `tests/fixtures/fonts`
+fn create_fixture_font() -> OpenTypeFont {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures/fonts/DejaVuSans.ttf");
+    let data =
+        std::fs::read(&path).unwrap_or_else(|e| panic!("missing fixture {}: {e}", path.display()));
+    OpenTypeFont::from_data(data, 0)
 }
 
 /// Benchmark cache performance with varying cache sizes
@@ -18,25 +26,29 @@ fn bench_cache_scalability(c: &mut Criterion) {
 
     for cache_size in [10, 50, 100, 500, 1000].iter() {
         group.throughput(Throughput::Elements(*cache_size as u64));
-        group.bench_with_input(BenchmarkId::from_parameter(cache_size), cache_size, |b, &size| {
-            let mut renderer = GlyphRenderer::new();
-            let font = create_stub_font();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(cache_size),
+            cache_size,
+            |b, &size| {
+                let mut renderer = GlyphRenderer::new();
+                let font = create_fixture_font();
 
-            // Pre-populate cache with 'size' glyphs
-            for i in 0..size {
-                let _ = renderer.rasterize_glyph(
-                    &font,
-                    GlyphId(i as u16 % 256),
-                    16.0,
-                    RenderMode::Gray,
-                );
-            }
+                // Pre-populate cache with 'size' glyphs
+                for i in 0..size {
+                    let _ = renderer.rasterize_glyph(
+                        &font,
+                        GlyphId(i as u16 % 256),
+                        16.0,
+                        RenderMode::Gray,
+                    );
+                }
 
-            b.iter(|| {
-                let stats = renderer.cache_stats();
-                black_box(stats);
-            });
-        });
+                b.iter(|| {
+                    let stats = renderer.cache_stats();
+                    black_box(stats);
+                });
+            },
+        );
     }
     group.finish();
 }
@@ -47,23 +59,27 @@ fn bench_cache_hit_rate(c: &mut Criterion) {
 
     // Different working set sizes vs cache size
     for working_set in [10, 50, 100, 200].iter() {
-        group.bench_with_input(BenchmarkId::from_parameter(working_set), working_set, |b, &size| {
-            let mut renderer = GlyphRenderer::new();
-            let font = create_stub_font();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(working_set),
+            working_set,
+            |b, &size| {
+                let mut renderer = GlyphRenderer::new();
+                let font = create_fixture_font();
 
-            b.iter(|| {
-                // Access glyphs in working set pattern
-                for i in 0..1000 {
-                    let glyph_id = GlyphId((i % size) as u16);
-                    let _ = renderer.rasterize_glyph(
-                        black_box(&font),
-                        black_box(glyph_id),
-                        black_box(16.0),
-                        black_box(RenderMode::Gray),
-                    );
-                }
-            });
-        });
+                b.iter(|| {
+                    // Access glyphs in working set pattern
+                    for i in 0..1000 {
+                        let glyph_id = GlyphId((i % size) as u16);
+                        let _ = renderer.rasterize_glyph(
+                            black_box(&font),
+                            black_box(glyph_id),
+                            black_box(16.0),
+                            black_box(RenderMode::Gray),
+                        );
+                    }
+                });
+            },
+        );
     }
     group.finish();
 }
@@ -72,7 +88,7 @@ fn bench_cache_hit_rate(c: &mut Criterion) {
 fn bench_cache_random_access(c: &mut Criterion) {
     c.bench_function("cache_random_access", |b| {
         let mut renderer = GlyphRenderer::new();
-        let font = create_stub_font();
+        let font = create_fixture_font();
 
         // Pseudo-random sequence (deterministic for benchmarking)
         let mut lfsr = 0xACE1u16;
@@ -80,7 +96,7 @@ fn bench_cache_random_access(c: &mut Criterion) {
         b.iter(|| {
             for _ in 0..1000 {
                 // Linear feedback shift register for pseudo-random numbers
-                let bit = ((lfsr >> 0) ^ (lfsr >> 2) ^ (lfsr >> 3) ^ (lfsr >> 5)) & 1;
+                let bit = (lfsr ^ (lfsr >> 2) ^ (lfsr >> 3) ^ (lfsr >> 5)) & 1;
                 lfsr = (lfsr >> 1) | (bit << 15);
 
                 let glyph_id = GlyphId(lfsr % 256);
@@ -99,7 +115,7 @@ fn bench_cache_random_access(c: &mut Criterion) {
 fn bench_cache_sequential(c: &mut Criterion) {
     c.bench_function("cache_sequential", |b| {
         let mut renderer = GlyphRenderer::new();
-        let font = create_stub_font();
+        let font = create_fixture_font();
 
         b.iter(|| {
             // Sequential access through glyphs
@@ -119,7 +135,7 @@ fn bench_cache_sequential(c: &mut Criterion) {
 fn bench_cache_mixed_sizes(c: &mut Criterion) {
     c.bench_function("cache_mixed_sizes", |b| {
         let mut renderer = GlyphRenderer::new();
-        let font = create_stub_font();
+        let font = create_fixture_font();
         let sizes = [12.0, 16.0, 24.0, 48.0];
 
         b.iter(|| {
@@ -127,7 +143,7 @@ fn bench_cache_mixed_sizes(c: &mut Criterion) {
                 let size = sizes[i % sizes.len()];
                 let _ = renderer.rasterize_glyph(
                     black_box(&font),
-                    black_box(GlyphId((i % 26 + 65) as u16)), // A-Z
+                    black_box(GlyphId((i % 26 + 36) as u16)), // A-Z
                     black_box(size),
                     black_box(RenderMode::Gray),
                 );
@@ -140,7 +156,7 @@ fn bench_cache_mixed_sizes(c: &mut Criterion) {
 fn bench_cache_mixed_modes(c: &mut Criterion) {
     c.bench_function("cache_mixed_modes", |b| {
         let mut renderer = GlyphRenderer::new();
-        let font = create_stub_font();
+        let font = create_fixture_font();
         let modes = [RenderMode::Mono, RenderMode::Gray, RenderMode::SubpixelRgb];
 
         b.iter(|| {
@@ -148,7 +164,7 @@ fn bench_cache_mixed_modes(c: &mut Criterion) {
                 let mode = modes[i % modes.len()];
                 let _ = renderer.rasterize_glyph(
                     black_box(&font),
-                    black_box(GlyphId(65)), // Always 'A'
+                    black_box(GlyphId(36)), // Always 'A'
                     black_box(16.0),
                     black_box(mode),
                 );
@@ -162,25 +178,29 @@ fn bench_cache_stats_overhead(c: &mut Criterion) {
     let mut group = c.benchmark_group("cache_stats_overhead");
 
     for cache_size in [10, 100, 1000, 10000].iter() {
-        group.bench_with_input(BenchmarkId::from_parameter(cache_size), cache_size, |b, &size| {
-            let mut renderer = GlyphRenderer::new();
-            let font = create_stub_font();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(cache_size),
+            cache_size,
+            |b, &size| {
+                let mut renderer = GlyphRenderer::new();
+                let font = create_fixture_font();
 
-            // Pre-populate cache
-            for i in 0..size {
-                let _ = renderer.rasterize_glyph(
-                    &font,
-                    GlyphId((i % 256) as u16),
-                    16.0,
-                    RenderMode::Gray,
-                );
-            }
+                // Pre-populate cache
+                for i in 0..size {
+                    let _ = renderer.rasterize_glyph(
+                        &font,
+                        GlyphId((i % 256) as u16),
+                        16.0,
+                        RenderMode::Gray,
+                    );
+                }
 
-            b.iter(|| {
-                let stats = renderer.cache_stats();
-                black_box(stats);
-            });
-        });
+                b.iter(|| {
+                    let stats = renderer.cache_stats();
+                    black_box(stats);
+                });
+            },
+        );
     }
     group.finish();
 }
@@ -189,7 +209,7 @@ fn bench_cache_stats_overhead(c: &mut Criterion) {
 fn bench_cache_thrashing(c: &mut Criterion) {
     c.bench_function("cache_thrashing", |b| {
         let mut renderer = GlyphRenderer::new();
-        let font = create_stub_font();
+        let font = create_fixture_font();
 
         b.iter(|| {
             // Access more glyphs than typical cache can hold
@@ -209,7 +229,7 @@ fn bench_cache_thrashing(c: &mut Criterion) {
 fn bench_cache_optimal(c: &mut Criterion) {
     c.bench_function("cache_optimal", |b| {
         let mut renderer = GlyphRenderer::new();
-        let font = create_stub_font();
+        let font = create_fixture_font();
 
         b.iter(|| {
             // Small working set that fits in cache
@@ -218,7 +238,7 @@ fn bench_cache_optimal(c: &mut Criterion) {
                     // A-Z repeatedly
                     let _ = renderer.rasterize_glyph(
                         black_box(&font),
-                        black_box(GlyphId(65 + i)),
+                        black_box(GlyphId(36 + i)),
                         black_box(16.0),
                         black_box(RenderMode::Gray),
                     );
@@ -228,6 +248,140 @@ fn bench_cache_optimal(c: &mut Criterion) {
     });
 }
 
+/// Benchmark rasterizing A-Z with an empty cache (every glyph is a miss)
+fn bench_rasterize_cold_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rasterize_cold_cache");
+
+    for size in [12.0f32, 16.0, 48.0].iter() {
+        group.throughput(Throughput::Elements(26));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            let mut renderer = GlyphRenderer::new();
+            let font = create_fixture_font();
+
+            b.iter(|| {
+                renderer.clear_cache();
+                for i in 0..26 {
+                    let _ = renderer.rasterize_glyph(
+                        black_box(&font),
+                        black_box(GlyphId(36 + i)), // 'A'-'Z' in DejaVu Sans
+                        black_box(size),
+                        black_box(RenderMode::Gray),
+                    );
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark rasterizing A-Z with every glyph already cached
+fn bench_rasterize_warm_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rasterize_warm_cache");
+
+    for size in [12.0f32, 16.0, 48.0].iter() {
+        group.throughput(Throughput::Elements(26));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            let mut renderer = GlyphRenderer::new();
+            let font = create_fixture_font();
+
+            for i in 0..26 {
+                let _ = renderer.rasterize_glyph(&font, GlyphId(36 + i), size, RenderMode::Gray);
+            }
+
+            b.iter(|| {
+                for i in 0..26 {
+                    let _ = renderer.rasterize_glyph(
+                        black_box(&font),
+                        black_box(GlyphId(36 + i)),
+                        black_box(size),
+                        black_box(RenderMode::Gray),
+                    );
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Glyph requests of a browsing session: body text skewed towards common
+/// ASCII letters, UI labels in a small font, large headings, and bursts of
+/// one-off glyphs such as a CJK page scrolled past
+fn browser_trace() -> Vec<(GlyphId, f32)> {
+    let mut lfsr = 0xACE1u16;
+    let mut next = move || {
+        let bit = (lfsr ^ (lfsr >> 2) ^ (lfsr >> 3) ^ (lfsr >> 5)) & 1;
+        lfsr = (lfsr >> 1) | (bit << 15);
+        lfsr
+    };
+
+    let mut trace = Vec::new();
+    let mut rare = 400u16;
+    for step in 0..20_000u32 {
+        // Squaring a uniform sample skews towards the most common letters
+        let uniform = f32::from(next()) / f32::from(u16::MAX);
+        let body = 36 + (uniform * uniform * 60.0) as u16;
+        trace.push((GlyphId(body), 16.0));
+        if step % 8 == 0 {
+            trace.push((GlyphId(36 + next() % 20), 12.0));
+        }
+        if step % 50 == 0 {
+            trace.push((GlyphId(36 + next() % 26), 48.0));
+        }
+        if step % 2_000 < 300 {
+            trace.push((GlyphId(rare), 16.0));
+            rare = if rare == 3_000 { 400 } else { rare + 1 };
+        }
+    }
+    trace
+}
+
+/// Compare eviction policies replaying a browser-like trace through a
+/// small cache; each policy's hit rate is printed once
+fn bench_eviction_policies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eviction_policy_trace");
+    let font = create_fixture_font();
+    let trace = browser_trace();
+    let config = |eviction_policy| CacheConfig {
+        max_entries: 256,
+        max_memory_bytes: 256 * 1024,
+        eviction_policy,
+        ..CacheConfig::default()
+    };
+    let replay = |renderer: &mut GlyphRenderer| {
+        for &(glyph_id, size) in &trace {
+            let _ = renderer.rasterize_glyph(&font, glyph_id, size, RenderMode::Gray);
+        }
+    };
+
+    for policy in [
+        EvictionPolicy::Lru,
+        EvictionPolicy::SegmentedLru,
+        EvictionPolicy::CostAware,
+    ] {
+        let mut renderer = GlyphRenderer::with_config(config(policy));
+        replay(&mut renderer);
+        let stats = renderer.cache_stats();
+        println!(
+            "{policy:?}: hit rate {:.1}% ({} evictions)",
+            stats.hit_rate * 100.0,
+            stats.evictions
+        );
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{policy:?}")),
+            &policy,
+            |b, &policy| {
+                b.iter(|| {
+                    let mut renderer = GlyphRenderer::with_config(config(policy));
+                    replay(&mut renderer);
+                    black_box(renderer.cache_stats())
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_cache_scalability,
@@ -239,5 +393,8 @@ criterion_group!(
     bench_cache_stats_overhead,
     bench_cache_thrashing,
     bench_cache_optimal,
+    bench_rasterize_cold_cache,
+    bench_rasterize_warm_cache,
+    bench_eviction_policies,
 );
 criterion_main!(benches);
@@ -5,12 +5,16 @@
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use glyph_renderer::GlyphRenderer;
-use glyph_renderer::types::{RenderMode, GlyphId, OpenTypeFont};
+use glyph_renderer::types::{RenderMode, GlyphId, OpenTypeFont, DEFAULT_DPI};
+use std::path::PathBuf;
 
-/// Create a stub font for benchmarking
-fn create_stub_font() -> OpenTypeFont {
-    // Use from_data constructor since fields are private
-    OpenTypeFont::from_data(vec![0; 1024], 0)
+/// Load DejaVu Sans from the workspace `tests/fixtures/fonts`
+fn create_fixture_font() -> OpenTypeFont {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures/fonts/DejaVuSans.ttf");
+    let data =
+        std::fs::read(&path).unwrap_or_else(|e| panic!("missing fixture {}: {e}", path.display()));
+    OpenTypeFont::from_data(data, 0)
 }
 
 /// Benchmark creating a glyph renderer
@@ -32,11 +36,10 @@ fn bench_rasterize_sizes(c: &mut Criterion) {
     for size in sizes.iter() {
         group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
             let mut renderer = GlyphRenderer::new();
-            let font = create_stub_font();
-            let glyph_id = GlyphId(65); // 'A'
+            let font = create_fixture_font();
+            let glyph_id = GlyphId(36); // 'A'
 
             b.iter(|| {
-                // Note: This will fail with stub font, but measures overhead
                 let _ = renderer.rasterize_glyph(
                     black_box(&font),
                     black_box(glyph_id),
@@ -62,8 +65,8 @@ fn bench_render_modes(c: &mut Criterion) {
     for (name, mode) in modes.iter() {
         group.bench_with_input(BenchmarkId::from_parameter(name), mode, |b, &mode| {
             let mut renderer = GlyphRenderer::new();
-            let font = create_stub_font();
-            let glyph_id = GlyphId(65);
+            let font = create_fixture_font();
+            let glyph_id = GlyphId(36);
 
             b.iter(|| {
                 let _ = renderer.rasterize_glyph(
@@ -86,11 +89,11 @@ fn bench_batch_rendering(c: &mut Criterion) {
         group.throughput(Throughput::Elements(*count as u64));
         group.bench_with_input(BenchmarkId::from_parameter(count), count, |b, &count| {
             let mut renderer = GlyphRenderer::new();
-            let font = create_stub_font();
+            let font = create_fixture_font();
 
             b.iter(|| {
                 for i in 0..count {
-                    let glyph_id = GlyphId((65 + i % 26) as u16); // A-Z
+                    let glyph_id = GlyphId((36 + i % 26) as u16); // A-Z
                     let _ = renderer.rasterize_glyph(
                         black_box(&font),
                         black_box(glyph_id),
@@ -104,6 +107,44 @@ fn bench_batch_rendering(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark rasterizing a page's worth of uncached glyphs one by one vs as
+/// a batch (split across worker threads with the `parallel` feature)
+fn bench_parallel_rasterization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallel_rasterization");
+    let font = create_fixture_font();
+    let glyphs: Vec<GlyphId> = (3..400).map(GlyphId).collect();
+    group.throughput(Throughput::Elements(glyphs.len() as u64));
+
+    group.bench_function("one_by_one", |b| {
+        let mut renderer = GlyphRenderer::new();
+        b.iter(|| {
+            renderer.clear_cache();
+            for &glyph_id in &glyphs {
+                let _ = renderer.rasterize_glyph(
+                    black_box(&font),
+                    black_box(glyph_id),
+                    black_box(16.0),
+                    black_box(RenderMode::Gray),
+                );
+            }
+        });
+    });
+    group.bench_function("batch", |b| {
+        let mut renderer = GlyphRenderer::new();
+        b.iter(|| {
+            renderer.clear_cache();
+            let _ = renderer.rasterize_glyphs(
+                black_box(&font),
+                black_box(&glyphs),
+                black_box(16.0),
+                black_box(DEFAULT_DPI),
+                black_box(RenderMode::Gray),
+            );
+        });
+    });
+    group.finish();
+}
+
 /// Benchmark cache hit performance (same glyph repeatedly)
 fn bench_cache_hits(c: &mut Criterion) {
     let mut group = c.benchmark_group("cache_hits");
@@ -112,8 +153,8 @@ fn bench_cache_hits(c: &mut Criterion) {
         group.throughput(Throughput::Elements(*count as u64));
         group.bench_with_input(BenchmarkId::from_parameter(count), count, |b, &count| {
             let mut renderer = GlyphRenderer::new();
-            let font = create_stub_font();
-            let glyph_id = GlyphId(65);
+            let font = create_fixture_font();
+            let glyph_id = GlyphId(36);
 
             // First render to populate cache (will fail but setup is measured)
             let _ = renderer.rasterize_glyph(&font, glyph_id, 16.0, RenderMode::Gray);
@@ -133,12 +174,12 @@ fn bench_cache_hits(c: &mut Criterion) {
     group.finish();
 }
 
-/// Benchmark outline extraction
+/// Benchmark outline extraction, uncached and from the outline cache
 fn bench_get_outline(c: &mut Criterion) {
     c.bench_function("get_glyph_outline", |b| {
-        let renderer = GlyphRenderer::new();
-        let font = create_stub_font();
-        let glyph_id = GlyphId(65);
+        let mut renderer = GlyphRenderer::new();
+        let font = create_fixture_font();
+        let glyph_id = GlyphId(36);
 
         b.iter(|| {
             let _ = renderer.get_glyph_outline(
@@ -147,6 +188,17 @@ fn bench_get_outline(c: &mut Criterion) {
             );
         });
     });
+
+    c.bench_function("get_glyph_outline_cached", |b| {
+        let mut renderer = GlyphRenderer::new();
+        // Fonts with an ID have their outlines cached
+        let font = create_fixture_font().with_font_id(0);
+        let glyph_id = GlyphId(36);
+
+        b.iter(|| {
+            let _ = renderer.get_glyph_outline(black_box(&font), black_box(glyph_id));
+        });
+    });
 }
 
 /// Benchmark cache statistics retrieval
@@ -165,7 +217,7 @@ fn bench_cache_stats(c: &mut Criterion) {
 fn bench_cache_clear(c: &mut Criterion) {
     c.bench_function("cache_clear", |b| {
         let mut renderer = GlyphRenderer::new();
-        let font = create_stub_font();
+        let font = create_fixture_font();
 
         // Pre-populate cache
         for i in 0..100 {
@@ -188,8 +240,8 @@ fn bench_render_dpi(c: &mut Criterion) {
     for dpi in dpis.iter() {
         group.bench_with_input(BenchmarkId::from_parameter(dpi), dpi, |b, &dpi| {
             let mut renderer = GlyphRenderer::new();
-            let font = create_stub_font();
-            let glyph_id = GlyphId(65);
+            let font = create_fixture_font();
+            let glyph_id = GlyphId(36);
 
             // Adjust size for DPI
             let size = (12.0 * dpi as f32) / 72.0;
@@ -207,16 +259,55 @@ fn bench_render_dpi(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark uncached rasterization with copied vs shared font data
+///
+/// Every call opens a backend face over the font bytes; with shared data
+/// (`OpenTypeFont::from_shared`) no copy of the font file is made.
+fn bench_font_data_sharing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("font_data_sharing");
+    let renderer = GlyphRenderer::new();
+    let data = create_fixture_font().shared_data();
+    let glyph_id = GlyphId(36);
+
+    group.bench_function("copied", |b| {
+        b.iter(|| {
+            let font = OpenTypeFont::from_data(data.to_vec(), 0);
+            let _ = renderer.backend().rasterize(
+                black_box(&font),
+                black_box(glyph_id),
+                black_box(16.0),
+                black_box(DEFAULT_DPI),
+                black_box(RenderMode::Gray),
+            );
+        });
+    });
+    group.bench_function("shared", |b| {
+        b.iter(|| {
+            let font = OpenTypeFont::from_shared(data.clone(), 0);
+            let _ = renderer.backend().rasterize(
+                black_box(&font),
+                black_box(glyph_id),
+                black_box(16.0),
+                black_box(DEFAULT_DPI),
+                black_box(RenderMode::Gray),
+            );
+        });
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_create_renderer,
     bench_rasterize_sizes,
     bench_render_modes,
     bench_batch_rendering,
+    bench_parallel_rasterization,
     bench_cache_hits,
     bench_get_outline,
     bench_cache_stats,
     bench_cache_clear,
     bench_render_dpi,
+    bench_font_data_sharing,
 );
 criterion_main!(benches);
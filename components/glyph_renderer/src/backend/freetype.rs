@@ -0,0 +1,458 @@
+//! FreeType glyph rasterization
+
+use freetype as ft;
+
+use super::{glyph_outline, stroke_at_dpi, union_bitmaps, RasterBackend};
+use crate::types::{
+    Contour, GlyphBitmap, GlyphId, GlyphOutline, LineCap, LineJoin, OpenTypeFont, OutlineScale,
+    PixelFormat, Point, RenderError, RenderMode, StrokeOptions, StrokeOutput, DEFAULT_DPI,
+};
+
+/// Rasterizer backed by the FreeType C library
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FreeTypeBackend;
+
+impl RasterBackend for FreeTypeBackend {
+    fn name(&self) -> &'static str {
+        "freetype"
+    }
+
+    fn rasterize(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Result<GlyphBitmap, RenderError> {
+        rasterize(font, glyph_id, size, dpi, mode)
+    }
+
+    fn rasterize_batch(
+        &self,
+        font: &OpenTypeFont,
+        glyph_ids: &[GlyphId],
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Vec<Result<GlyphBitmap, RenderError>> {
+        // One face for the whole batch instead of one per glyph
+        match open_sized_face(font, size, dpi) {
+            Ok(face) => glyph_ids
+                .iter()
+                .map(|&glyph_id| render_glyph(&face, glyph_id, mode))
+                .collect(),
+            Err(error) => glyph_ids.iter().map(|_| Err(error.clone())).collect(),
+        }
+    }
+
+    fn rasterize_stroked(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+        stroke: &StrokeOptions,
+    ) -> Result<GlyphBitmap, RenderError> {
+        let stroked = rasterize_stroke(font, glyph_id, size, dpi, mode, stroke)?;
+        match stroke.output {
+            StrokeOutput::Stroke => Ok(stroked),
+            StrokeOutput::FillAndStroke => Ok(union_bitmaps(
+                &rasterize(font, glyph_id, size, dpi, mode)?,
+                &stroked,
+            )),
+        }
+    }
+
+    fn outline(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        scale: OutlineScale,
+    ) -> Result<GlyphOutline, RenderError> {
+        outline(font, glyph_id, scale)
+    }
+}
+
+/// Convert RenderMode to FreeType render mode
+fn to_freetype_render_mode(mode: RenderMode) -> ft::RenderMode {
+    match mode {
+        RenderMode::Mono => ft::RenderMode::Mono,
+        RenderMode::Gray => ft::RenderMode::Normal,
+        RenderMode::SubpixelRgb => ft::RenderMode::Lcd,
+    }
+}
+
+/// Convert FreeType load flags from RenderMode
+fn get_load_flags(mode: RenderMode) -> ft::face::LoadFlag {
+    match mode {
+        RenderMode::Mono => ft::face::LoadFlag::MONOCHROME,
+        RenderMode::Gray => ft::face::LoadFlag::DEFAULT,
+        RenderMode::SubpixelRgb => ft::face::LoadFlag::DEFAULT,
+    }
+}
+
+/// Set a face's size to `size` points at `dpi`
+///
+/// FreeType takes integral resolutions, so fractional DPIs (e.g. 72 at a
+/// 1.25 device pixel ratio) are folded into the point size at the default
+/// DPI, which yields the same pixels per em.
+fn set_char_size<B>(face: &ft::Face<B>, size: f32, dpi: f32) -> Result<(), RenderError> {
+    let (size, dpi) = if dpi.fract() == 0.0 && dpi <= u32::MAX as f32 {
+        (size, dpi as u32)
+    } else {
+        (size * dpi / DEFAULT_DPI, DEFAULT_DPI as u32)
+    };
+    face.set_char_size((size * 64.0) as isize, 0, dpi, dpi)
+        .map_err(|e| RenderError::RasterizationFailed(format!("Failed to set char size: {:?}", e)))
+}
+
+/// Open a FreeType face over the font's bytes without copying them,
+/// instanced at the font's variation coordinates
+fn load_face<'a>(
+    library: &ft::Library,
+    font: &'a OpenTypeFont,
+) -> Result<ft::Face<&'a [u8]>, RenderError> {
+    let mut face = library
+        .new_memory_face2(font.data(), font.face_index)
+        .map_err(|e| {
+            RenderError::RasterizationFailed(format!("Failed to load font face: {:?}", e))
+        })?;
+    if font.variation_coords.is_empty() {
+        return Ok(face);
+    }
+
+    // FreeType takes a 16.16 coordinate for every fvar axis, in order
+    let index = u32::try_from(font.face_index).unwrap_or(0);
+    let Ok(parsed) = ttf_parser::Face::parse(font.data(), index) else {
+        return Ok(face);
+    };
+    let coords: Vec<ft::ffi::FT_Fixed> = parsed
+        .variation_axes()
+        .into_iter()
+        .map(|axis| {
+            let value = font
+                .variation_coords
+                .iter()
+                .find(|coord| coord.tag == axis.tag.to_bytes())
+                .map_or(axis.def_value, |coord| {
+                    coord.value.clamp(axis.min_value, axis.max_value)
+                });
+            (value * 65536.0).round() as ft::ffi::FT_Fixed
+        })
+        .collect();
+    if !coords.is_empty() {
+        // SAFETY: the face is live and `coords` holds `coords.len()` values
+        let error = unsafe {
+            ft::ffi::FT_Set_Var_Design_Coordinates(
+                face.raw_mut(),
+                coords.len() as ft::ffi::FT_UInt,
+                coords.as_ptr(),
+            )
+        };
+        if error != 0 {
+            return Err(RenderError::RasterizationFailed(format!(
+                "Failed to set variation coordinates: {error}"
+            )));
+        }
+    }
+    Ok(face)
+}
+
+/// Rasterize a glyph at `size` points and `dpi`
+fn rasterize(
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    size: f32,
+    dpi: f32,
+    mode: RenderMode,
+) -> Result<GlyphBitmap, RenderError> {
+    let face = open_sized_face(font, size, dpi)?;
+    render_glyph(&face, glyph_id, mode)
+}
+
+/// Open a face over the font's bytes, set to `size` points at `dpi`
+///
+/// The face keeps its FreeType library alive, so it can outlive `library`.
+fn open_sized_face(
+    font: &OpenTypeFont,
+    size: f32,
+    dpi: f32,
+) -> Result<ft::Face<&[u8]>, RenderError> {
+    // Initialize FreeType library
+    let library = ft::Library::init().map_err(|e| {
+        RenderError::RasterizationFailed(format!("Failed to initialize FreeType: {:?}", e))
+    })?;
+
+    let face = load_face(&library, font)?;
+
+    set_char_size(&face, size, dpi)?;
+    Ok(face)
+}
+
+/// Render a glyph with a face set to the target size
+fn render_glyph(
+    face: &ft::Face<&[u8]>,
+    glyph_id: GlyphId,
+    mode: RenderMode,
+) -> Result<GlyphBitmap, RenderError> {
+    // Load glyph
+    let load_flags = get_load_flags(mode);
+    face.load_glyph(glyph_id.0 as u32, load_flags)
+        .map_err(|e| {
+            if matches!(e, ft::Error::InvalidGlyphIndex) {
+                RenderError::GlyphNotFound(glyph_id)
+            } else {
+                RenderError::RasterizationFailed(format!("Failed to load glyph: {:?}", e))
+            }
+        })?;
+
+    // Render glyph to bitmap
+    let ft_render_mode = to_freetype_render_mode(mode);
+    face.glyph().render_glyph(ft_render_mode).map_err(|e| {
+        RenderError::RasterizationFailed(format!("Failed to render glyph: {:?}", e))
+    })?;
+
+    // Extract bitmap data
+    let ft_bitmap = face.glyph().bitmap();
+    let width = ft_bitmap.width() as u32;
+    let height = ft_bitmap.rows() as u32;
+    let (pitch, data) = copy_rows(ft_bitmap.buffer(), height as usize, ft_bitmap.pitch());
+
+    // Extract metrics
+    let metrics = face.glyph().metrics();
+    let bearing_x = metrics.horiBearingX / 64; // Convert from 26.6 fixed-point
+    let bearing_y = metrics.horiBearingY / 64;
+
+    Ok(GlyphBitmap {
+        width,
+        height,
+        left: bearing_x as i32,
+        top: bearing_y as i32,
+        pitch,
+        data,
+        format: mode,
+        pixel_format: PixelFormat::for_mode(mode),
+    })
+}
+
+/// Copy a FreeType bitmap buffer into top-down rows
+///
+/// A negative `pitch` means FreeType stored the rows bottom-up (the buffer
+/// starts with the bottom row), so they are flipped while copying. Returns
+/// the row stride of the copy, which keeps FreeType's row padding.
+fn copy_rows(buffer: &[u8], rows: usize, pitch: i32) -> (usize, Vec<u8>) {
+    let stride = pitch.unsigned_abs() as usize;
+    let buffer = &buffer[..(stride * rows).min(buffer.len())];
+    if pitch >= 0 {
+        return (stride, buffer.to_vec());
+    }
+    let data = buffer.chunks(stride).rev().flatten().copied().collect();
+    (stride, data)
+}
+
+/// Rasterize the stroke of a glyph's outline with the FreeType stroker
+fn rasterize_stroke(
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    size: f32,
+    dpi: f32,
+    mode: RenderMode,
+    stroke: &StrokeOptions,
+) -> Result<GlyphBitmap, RenderError> {
+    let stroke = &stroke_at_dpi(stroke, dpi);
+    let library = ft::Library::init().map_err(|e| {
+        RenderError::RasterizationFailed(format!("Failed to initialize FreeType: {:?}", e))
+    })?;
+    let face = load_face(&library, font)?;
+    set_char_size(&face, size, dpi)?;
+
+    // Stroking needs the outline rather than an embedded bitmap
+    face.load_glyph(
+        glyph_id.0 as u32,
+        get_load_flags(mode) | ft::face::LoadFlag::NO_BITMAP,
+    )
+    .map_err(|e| {
+        if matches!(e, ft::Error::InvalidGlyphIndex) {
+            RenderError::GlyphNotFound(glyph_id)
+        } else {
+            RenderError::RasterizationFailed(format!("Failed to load glyph: {:?}", e))
+        }
+    })?;
+    let glyph = face
+        .glyph()
+        .get_glyph()
+        .map_err(|e| RenderError::RasterizationFailed(format!("Failed to copy glyph: {:?}", e)))?;
+
+    let stroker = library.new_stroker().map_err(|e| {
+        RenderError::RasterizationFailed(format!("Failed to create stroker: {:?}", e))
+    })?;
+    // Radius in 26.6 fixed point, miter limit in 16.16
+    stroker.set(
+        (stroke.width * 32.0) as ft::ffi::FT_Fixed,
+        match stroke.line_cap {
+            LineCap::Butt => ft::StrokerLineCap::Butt,
+            LineCap::Round => ft::StrokerLineCap::Round,
+            LineCap::Square => ft::StrokerLineCap::Square,
+        },
+        match stroke.line_join {
+            LineJoin::Round => ft::StrokerLineJoin::Round,
+            LineJoin::Bevel => ft::StrokerLineJoin::Bevel,
+            // PostScript-style miters, beveled beyond the limit as in CSS
+            LineJoin::Miter => ft::StrokerLineJoin::MiterFixed,
+        },
+        (stroke.miter_limit * 65536.0) as ft::ffi::FT_Fixed,
+    );
+    let stroked = glyph.stroke(&stroker).map_err(|e| {
+        RenderError::RasterizationFailed(format!("Failed to stroke glyph: {:?}", e))
+    })?;
+    let bitmap_glyph = stroked
+        .to_bitmap(to_freetype_render_mode(mode), None)
+        .map_err(|e| {
+            RenderError::RasterizationFailed(format!("Failed to render glyph: {:?}", e))
+        })?;
+
+    let ft_bitmap = bitmap_glyph.bitmap();
+    let (pitch, data) = copy_rows(
+        ft_bitmap.buffer(),
+        ft_bitmap.rows() as usize,
+        ft_bitmap.pitch(),
+    );
+    Ok(GlyphBitmap {
+        width: ft_bitmap.width() as u32,
+        height: ft_bitmap.rows() as u32,
+        left: bitmap_glyph.left(),
+        top: bitmap_glyph.top(),
+        pitch,
+        data,
+        format: mode,
+        pixel_format: PixelFormat::for_mode(mode),
+    })
+}
+
+/// Extract a glyph's outline in the coordinate space of `scale`
+///
+/// Pixel outlines are hinted; font-unit outlines are loaded unscaled, which
+/// also disables hinting.
+fn outline(
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    scale: OutlineScale,
+) -> Result<GlyphOutline, RenderError> {
+    // Initialize FreeType library
+    let library = ft::Library::init().map_err(|e| {
+        RenderError::RasterizationFailed(format!("Failed to initialize FreeType: {:?}", e))
+    })?;
+
+    let face = load_face(&library, font)?;
+
+    // Scaled coordinates are 26.6 fixed point, unscaled ones font units
+    let (load_flags, unit) = match scale {
+        OutlineScale::Ppem(ppem) => {
+            set_char_size(&face, ppem, DEFAULT_DPI)?;
+            (ft::face::LoadFlag::NO_BITMAP, 64.0)
+        }
+        OutlineScale::FontUnits => (
+            ft::face::LoadFlag::NO_BITMAP | ft::face::LoadFlag::NO_SCALE,
+            1.0,
+        ),
+    };
+
+    // Load glyph without rendering
+    face.load_glyph(glyph_id.0 as u32, load_flags)
+        .map_err(|e| {
+            if matches!(e, ft::Error::InvalidGlyphIndex) {
+                RenderError::GlyphNotFound(glyph_id)
+            } else {
+                RenderError::RasterizationFailed(format!("Failed to load glyph: {:?}", e))
+            }
+        })?;
+
+    // Get outline
+    let glyph = face.glyph();
+    let ft_outline = glyph
+        .outline()
+        .ok_or_else(|| RenderError::RasterizationFailed("Glyph has no outline".to_string()))?;
+
+    // Extract points and contours
+    let points_vec = ft_outline.points();
+    let contours_vec = ft_outline.contours();
+
+    // Extract contours
+    let mut contours = Vec::new();
+    let mut start_idx = 0;
+
+    for &contour_end in contours_vec {
+        let end_idx = contour_end as usize + 1;
+
+        // Extract points for this contour
+        let points: Vec<Point> = points_vec[start_idx..end_idx.min(points_vec.len())]
+            .iter()
+            .map(|point| Point {
+                x: (point.x as f32) / unit,
+                y: (point.y as f32) / unit,
+            })
+            .collect();
+
+        if !points.is_empty() {
+            contours.push(Contour {
+                points,
+                closed: true,
+            });
+        }
+
+        start_idx = end_idx;
+    }
+
+    let advance = glyph.metrics().horiAdvance as f32 / unit;
+    Ok(glyph_outline(contours, advance, face.em_size() as u16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_font() -> OpenTypeFont {
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        OpenTypeFont::from_data(data, 0)
+    }
+
+    #[test]
+    fn test_copy_rows_keeps_top_down_rows() {
+        let buffer = [1, 2, 0, 3, 4, 0];
+        assert_eq!(copy_rows(&buffer, 2, 3), (3, buffer.to_vec()));
+    }
+
+    #[test]
+    fn test_copy_rows_flips_bottom_up_rows() {
+        // Bottom row first, as FreeType stores bitmaps with a negative pitch
+        let buffer = [5, 6, 0, 3, 4, 0, 1, 2, 0];
+        assert_eq!(
+            copy_rows(&buffer, 3, -3),
+            (3, vec![1, 2, 0, 3, 4, 0, 5, 6, 0])
+        );
+    }
+
+    #[test]
+    fn test_copy_rows_handles_empty_bitmaps() {
+        assert_eq!(copy_rows(&[], 0, 0), (0, Vec::new()));
+        assert_eq!(copy_rows(&[], 0, -4), (4, Vec::new()));
+    }
+
+    #[test]
+    fn test_rasterized_glyph_is_upright() {
+        let font = test_font();
+        let face = ttf_parser::Face::parse(&font.data, 0).unwrap();
+        // 'T' is wide at the top and narrow at the bottom
+        let glyph_id = GlyphId(face.glyph_index('T').unwrap().0);
+        let bitmap = rasterize(&font, glyph_id, 48.0, DEFAULT_DPI, RenderMode::Gray).unwrap();
+
+        let ink = |y: u32| bitmap.row(y).iter().filter(|&&value| value > 128).count();
+        assert!(ink(0) > ink(bitmap.height - 1));
+    }
+}
@@ -0,0 +1,203 @@
+//! Rasterization backends
+//!
+//! [`FreeTypeBackend`] (`freetype` feature, default) renders with the FreeType
+//! C library; [`PureRustBackend`] is always available and scan-converts
+//! outlines in Rust, for platforms without FreeType. A [`GlyphRenderer`] uses
+//! [`default_backend`] unless another one is passed to
+//! [`GlyphRenderer::with_backend`].
+//!
+//! [`GlyphRenderer`]: crate::GlyphRenderer
+//! [`GlyphRenderer::with_backend`]: crate::GlyphRenderer::with_backend
+
+#[cfg(feature = "freetype")]
+mod freetype;
+mod pure_rust;
+mod stroke;
+
+#[cfg(feature = "freetype")]
+pub use self::freetype::FreeTypeBackend;
+pub use self::pure_rust::PureRustBackend;
+pub(crate) use self::pure_rust::{extents, glyph_path, GlyphPath};
+
+use crate::types::{
+    BoundingBox, Contour, GlyphBitmap, GlyphId, GlyphOutline, OpenTypeFont, OutlineScale,
+    RenderError, RenderMode, StrokeOptions, DEFAULT_DPI,
+};
+
+/// A glyph rasterization engine
+///
+/// Sizes are in points and rendered at `dpi` (see [`DEFAULT_DPI`]), with
+/// `left`/`top` bearings in pixels, 1-bit packed rows for
+/// [`RenderMode::Mono`] and three subpixels per pixel for
+/// [`RenderMode::SubpixelRgb`].
+pub trait RasterBackend: Send + Sync {
+    /// Short name of the backend, e.g. for diagnostics
+    fn name(&self) -> &'static str;
+
+    /// Rasterize a glyph at `size` points and `dpi` dots per inch
+    fn rasterize(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Result<GlyphBitmap, RenderError>;
+
+    /// Rasterize glyphs of one font at the same size, DPI and mode
+    ///
+    /// Returns one result per glyph, in order. The default rasterizes them
+    /// one at a time; backends override it to load the font once per batch.
+    /// [`GlyphRenderer::rasterize_glyphs`] runs a batch per worker thread.
+    ///
+    /// [`GlyphRenderer::rasterize_glyphs`]: crate::GlyphRenderer::rasterize_glyphs
+    fn rasterize_batch(
+        &self,
+        font: &OpenTypeFont,
+        glyph_ids: &[GlyphId],
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Vec<Result<GlyphBitmap, RenderError>> {
+        glyph_ids
+            .iter()
+            .map(|&glyph_id| self.rasterize(font, glyph_id, size, dpi, mode))
+            .collect()
+    }
+
+    /// Rasterize a glyph's stroke, optionally combined with its fill
+    ///
+    /// The stroke width scales with `dpi` like the font size.
+    fn rasterize_stroked(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+        stroke: &StrokeOptions,
+    ) -> Result<GlyphBitmap, RenderError>;
+
+    /// Extract a glyph's vector outline in the coordinate space of `scale`
+    fn outline(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        scale: OutlineScale,
+    ) -> Result<GlyphOutline, RenderError>;
+}
+
+/// Backend selected by cargo features: FreeType when enabled, otherwise the
+/// pure-Rust rasterizer
+pub fn default_backend() -> Box<dyn RasterBackend> {
+    #[cfg(feature = "freetype")]
+    return Box::new(FreeTypeBackend);
+    #[cfg(not(feature = "freetype"))]
+    return Box::new(PureRustBackend);
+}
+
+/// Assemble an outline, taking its bounds from the contour points and the
+/// side bearings from the bounds and `advance`
+fn glyph_outline(contours: Vec<Contour>, advance: f32, units_per_em: u16) -> GlyphOutline {
+    let mut points = contours.iter().flat_map(|contour| contour.points.iter());
+    let bounds = match points.next() {
+        Some(first) => points.fold(
+            BoundingBox {
+                min_x: first.x,
+                min_y: first.y,
+                max_x: first.x,
+                max_y: first.y,
+            },
+            |bounds, p| BoundingBox {
+                min_x: bounds.min_x.min(p.x),
+                min_y: bounds.min_y.min(p.y),
+                max_x: bounds.max_x.max(p.x),
+                max_y: bounds.max_y.max(p.y),
+            },
+        ),
+        None => BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 0.0,
+            max_y: 0.0,
+        },
+    };
+
+    GlyphOutline {
+        contours,
+        bounds,
+        advance,
+        left_side_bearing: bounds.min_x,
+        right_side_bearing: advance - bounds.max_x,
+        units_per_em,
+    }
+}
+
+/// Stroke settings with the width converted from points to pixels at `dpi`
+fn stroke_at_dpi(stroke: &StrokeOptions, dpi: f32) -> StrokeOptions {
+    StrokeOptions {
+        width: stroke.width * dpi / DEFAULT_DPI,
+        ..*stroke
+    }
+}
+
+/// Combine two bitmaps of the same mode, keeping the higher coverage per pixel
+fn union_bitmaps(a: &GlyphBitmap, b: &GlyphBitmap) -> GlyphBitmap {
+    if a.width == 0 || a.height == 0 {
+        return b.clone();
+    }
+    if b.width == 0 || b.height == 0 {
+        return a.clone();
+    }
+
+    // Subpixel bitmaps store three samples per pixel
+    let samples = if a.format == RenderMode::SubpixelRgb {
+        3
+    } else {
+        1
+    };
+    let left = a.left.min(b.left);
+    let top = a.top.max(b.top);
+    let right = (a.left + (a.width / samples) as i32).max(b.left + (b.width / samples) as i32);
+    let bottom = (a.top - a.height as i32).min(b.top - b.height as i32);
+    let width = (right - left) as usize * samples as usize;
+    let height = (top - bottom) as usize;
+    let pitch = match a.format {
+        RenderMode::Mono => width.div_ceil(8),
+        RenderMode::Gray | RenderMode::SubpixelRgb => width,
+    };
+
+    let mut data = vec![0u8; pitch * height];
+    for bitmap in [a, b] {
+        let dx = (bitmap.left - left) as usize * samples as usize;
+        let dy = (top - bitmap.top) as usize;
+        for y in 0..bitmap.height as usize {
+            for x in 0..bitmap.width as usize {
+                let row = (dy + y) * pitch;
+                match a.format {
+                    RenderMode::Mono => {
+                        if bitmap.data[y * bitmap.pitch + x / 8] & (0x80 >> (x % 8)) != 0 {
+                            data[row + (dx + x) / 8] |= 0x80 >> ((dx + x) % 8);
+                        }
+                    }
+                    RenderMode::Gray | RenderMode::SubpixelRgb => {
+                        let value = bitmap.data[y * bitmap.pitch + x];
+                        let target = &mut data[row + dx + x];
+                        *target = (*target).max(value);
+                    }
+                }
+            }
+        }
+    }
+
+    GlyphBitmap {
+        width: width as u32,
+        height: height as u32,
+        left,
+        top,
+        pitch,
+        data,
+        format: a.format,
+        pixel_format: a.pixel_format,
+    }
+}
@@ -0,0 +1,698 @@
+//! Pure-Rust glyph rasterization with ttf-parser and ab_glyph_rasterizer
+//!
+//! The default backend when the `freetype` feature is disabled, so the
+//! renderer has no C dependencies (e.g. on wasm32). Output follows the
+//! FreeType conventions: `left`/`top` bearings in pixels, packed 1-bit rows
+//! for mono and three subpixels per pixel for subpixel RGB. Sizes in points
+//! are converted to pixels per em from the requested DPI up front.
+
+use ab_glyph_rasterizer::{point, Point as RasterPoint, Rasterizer};
+
+use super::{glyph_outline, stroke, stroke_at_dpi, union_bitmaps, RasterBackend};
+use crate::types::{
+    BoundingBox, Contour, GlyphBitmap, GlyphId, GlyphOutline, OpenTypeFont, OutlineScale,
+    PixelFormat, Point, RenderError, RenderMode, StrokeOptions, StrokeOutput, DEFAULT_DPI,
+};
+
+/// Rasterizer that scan-converts glyph outlines without FreeType
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PureRustBackend;
+
+impl RasterBackend for PureRustBackend {
+    fn name(&self) -> &'static str {
+        "pure-rust"
+    }
+
+    fn rasterize(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Result<GlyphBitmap, RenderError> {
+        rasterize(font, glyph_id, size * dpi / DEFAULT_DPI, mode)
+    }
+
+    fn rasterize_batch(
+        &self,
+        font: &OpenTypeFont,
+        glyph_ids: &[GlyphId],
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Vec<Result<GlyphBitmap, RenderError>> {
+        // Parse the font once for the whole batch
+        match parse_face(font) {
+            Ok(face) => glyph_ids
+                .iter()
+                .map(|&glyph_id| rasterize_face(&face, glyph_id, size * dpi / DEFAULT_DPI, mode))
+                .collect(),
+            Err(error) => glyph_ids.iter().map(|_| Err(error.clone())).collect(),
+        }
+    }
+
+    fn rasterize_stroked(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+        stroke: &StrokeOptions,
+    ) -> Result<GlyphBitmap, RenderError> {
+        let size = size * dpi / DEFAULT_DPI;
+        let stroked = rasterize_stroke(font, glyph_id, size, mode, &stroke_at_dpi(stroke, dpi))?;
+        match stroke.output {
+            StrokeOutput::Stroke => Ok(stroked),
+            StrokeOutput::FillAndStroke => Ok(union_bitmaps(
+                &rasterize(font, glyph_id, size, mode)?,
+                &stroked,
+            )),
+        }
+    }
+
+    fn outline(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        scale: OutlineScale,
+    ) -> Result<GlyphOutline, RenderError> {
+        outline(font, glyph_id, scale)
+    }
+}
+
+/// Rasterize a glyph at `size` pixels per em
+fn rasterize(
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    size: f32,
+    mode: RenderMode,
+) -> Result<GlyphBitmap, RenderError> {
+    rasterize_face(&parse_face(font)?, glyph_id, size, mode)
+}
+
+/// Rasterize a glyph of a parsed face at `size` pixels per em
+fn rasterize_face(
+    face: &ttf_parser::Face<'_>,
+    glyph_id: GlyphId,
+    size: f32,
+    mode: RenderMode,
+) -> Result<GlyphBitmap, RenderError> {
+    check_glyph(face, glyph_id)?;
+
+    let mut segments = SegmentCollector::default();
+    let Some(bbox) = face.outline_glyph(ttf_parser::GlyphId(glyph_id.0), &mut segments) else {
+        // Glyphs without an outline (e.g. space) render to an empty bitmap
+        return Ok(empty_bitmap(mode));
+    };
+
+    let bounds = Bounds {
+        x_min: bbox.x_min as f32,
+        y_min: bbox.y_min as f32,
+        x_max: bbox.x_max as f32,
+        y_max: bbox.y_max as f32,
+    };
+    let scale = size / face.units_per_em() as f32;
+    Ok(render(&segments.segments, bounds, scale, mode))
+}
+
+/// Rasterize the stroke of a glyph's outline
+fn rasterize_stroke(
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    size: f32,
+    mode: RenderMode,
+    options: &StrokeOptions,
+) -> Result<GlyphBitmap, RenderError> {
+    let face = parse_face(font)?;
+    check_glyph(&face, glyph_id)?;
+
+    let mut segments = SegmentCollector::default();
+    if face
+        .outline_glyph(ttf_parser::GlyphId(glyph_id.0), &mut segments)
+        .is_none()
+    {
+        return Ok(empty_bitmap(mode));
+    }
+
+    // Stroke in font units so subpixel rendering keeps round joins round
+    let scale = size / face.units_per_em() as f32;
+    let radius = options.width / 2.0 / scale;
+    let polygons = stroke::stroke_polygons(&segments.polylines(), radius, options);
+
+    let mut lines = Vec::new();
+    let mut bounds: Option<Bounds> = None;
+    for polygon in &polygons {
+        for (index, &from) in polygon.iter().enumerate() {
+            let to = polygon[(index + 1) % polygon.len()];
+            lines.push(Segment::Line(from, to));
+            bounds = Some(match bounds {
+                Some(b) => Bounds {
+                    x_min: b.x_min.min(from.x),
+                    y_min: b.y_min.min(from.y),
+                    x_max: b.x_max.max(from.x),
+                    y_max: b.y_max.max(from.y),
+                },
+                None => Bounds {
+                    x_min: from.x,
+                    y_min: from.y,
+                    x_max: from.x,
+                    y_max: from.y,
+                },
+            });
+        }
+    }
+
+    Ok(match bounds {
+        Some(bounds) => render(&lines, bounds, scale, mode),
+        None => empty_bitmap(mode),
+    })
+}
+
+/// Rectangle in font units
+struct Bounds {
+    x_min: f32,
+    y_min: f32,
+    x_max: f32,
+    y_max: f32,
+}
+
+/// Scan-convert `segments` (font units) covering `bounds` at `scale` pixels per unit
+fn render(segments: &[Segment], bounds: Bounds, scale: f32, mode: RenderMode) -> GlyphBitmap {
+    // Subpixel rendering samples three times horizontally
+    let samples = match mode {
+        RenderMode::SubpixelRgb => 3,
+        RenderMode::Mono | RenderMode::Gray => 1,
+    };
+
+    let left = (bounds.x_min * scale).floor();
+    let right = (bounds.x_max * scale).ceil();
+    let top = (bounds.y_max * scale).ceil();
+    let bottom = (bounds.y_min * scale).floor();
+    let width = (right - left).max(0.0) as usize * samples;
+    let height = (top - bottom).max(0.0) as usize;
+    if width == 0 || height == 0 {
+        return empty_bitmap(mode);
+    }
+
+    // Font units (y up) to bitmap pixels (y down, origin at top-left)
+    let x_scale = scale * samples as f32;
+    let x_origin = left * samples as f32;
+    let map = |p: RasterPoint| point(p.x * x_scale - x_origin, top - p.y * scale);
+    let mut rasterizer = Rasterizer::new(width, height);
+    for segment in segments {
+        match *segment {
+            Segment::Line(p0, p1) => rasterizer.draw_line(map(p0), map(p1)),
+            Segment::Quad(p0, p1, p2) => rasterizer.draw_quad(map(p0), map(p1), map(p2)),
+            Segment::Cubic(p0, p1, p2, p3) => {
+                rasterizer.draw_cubic(map(p0), map(p1), map(p2), map(p3))
+            }
+        }
+    }
+
+    let mut coverage = vec![0u8; width * height];
+    rasterizer.for_each_pixel_2d(|x, y, alpha| {
+        coverage[y as usize * width + x as usize] = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+    });
+
+    let (pitch, data) = match mode {
+        RenderMode::Mono => pack_mono(&coverage, width, height),
+        RenderMode::Gray | RenderMode::SubpixelRgb => (width, coverage),
+    };
+
+    GlyphBitmap {
+        width: width as u32,
+        height: height as u32,
+        left: left as i32,
+        top: top as i32,
+        pitch,
+        data,
+        format: mode,
+        pixel_format: PixelFormat::for_mode(mode),
+    }
+}
+
+/// Extract a glyph's unhinted outline in the coordinate space of `scale`
+fn outline(
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    scale: OutlineScale,
+) -> Result<GlyphOutline, RenderError> {
+    let face = parse_face(font)?;
+    check_glyph(&face, glyph_id)?;
+
+    let units_per_em = face.units_per_em();
+    let scale = match scale {
+        OutlineScale::Ppem(ppem) => ppem / units_per_em as f32,
+        OutlineScale::FontUnits => 1.0,
+    };
+    let mut builder = ContourCollector::default();
+    face.outline_glyph(ttf_parser::GlyphId(glyph_id.0), &mut builder)
+        .ok_or_else(|| RenderError::RasterizationFailed("Glyph has no outline".to_string()))?;
+
+    let contours: Vec<Contour> = builder
+        .contours
+        .into_iter()
+        .filter(|points| !points.is_empty())
+        .map(|points| Contour {
+            points: points
+                .into_iter()
+                .map(|p| Point {
+                    x: p.x * scale,
+                    y: p.y * scale,
+                })
+                .collect(),
+            closed: true,
+        })
+        .collect();
+    let advance = face
+        .glyph_hor_advance(ttf_parser::GlyphId(glyph_id.0))
+        .unwrap_or(0) as f32
+        * scale;
+
+    Ok(glyph_outline(contours, advance, units_per_em))
+}
+
+/// Ink bounds of a glyph at `size` pixels per em, from the outline's control
+/// box without rasterizing; `None` for glyphs without an outline
+pub(crate) fn extents(
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    size: f32,
+) -> Result<Option<BoundingBox>, RenderError> {
+    let face = parse_face(font)?;
+    check_glyph(&face, glyph_id)?;
+
+    let scale = size / face.units_per_em() as f32;
+    Ok(face
+        .glyph_bounding_box(ttf_parser::GlyphId(glyph_id.0))
+        .map(|bbox| BoundingBox {
+            min_x: bbox.x_min as f32 * scale,
+            min_y: bbox.y_min as f32 * scale,
+            max_x: bbox.x_max as f32 * scale,
+            max_y: bbox.y_max as f32 * scale,
+        }))
+}
+
+/// Outline of a glyph in font units, for painting `COLR` glyphs under
+/// arbitrary transforms
+pub(crate) struct GlyphPath {
+    segments: Vec<Segment>,
+}
+
+/// Outline of a glyph, or `None` for glyphs without one
+pub(crate) fn glyph_path(
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+) -> Result<Option<GlyphPath>, RenderError> {
+    let face = parse_face(font)?;
+    check_glyph(&face, glyph_id)?;
+
+    let mut segments = SegmentCollector::default();
+    Ok(face
+        .outline_glyph(ttf_parser::GlyphId(glyph_id.0), &mut segments)
+        .map(|_| GlyphPath {
+            segments: segments.segments,
+        }))
+}
+
+impl GlyphPath {
+    /// Bounds `[x_min, y_min, x_max, y_max]` of the control points mapped by
+    /// `map`; they contain the mapped outline when `map` is affine
+    pub(crate) fn bounds(&self, map: impl Fn(f32, f32) -> (f32, f32)) -> Option<[f32; 4]> {
+        let mut bounds: Option<[f32; 4]> = None;
+        let mut add = |p: RasterPoint| {
+            let (x, y) = map(p.x, p.y);
+            bounds = Some(match bounds {
+                Some([x_min, y_min, x_max, y_max]) => {
+                    [x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y)]
+                }
+                None => [x, y, x, y],
+            });
+        };
+        for segment in &self.segments {
+            match *segment {
+                Segment::Line(p0, p1) => [p0, p1].into_iter().for_each(&mut add),
+                Segment::Quad(p0, p1, p2) => [p0, p1, p2].into_iter().for_each(&mut add),
+                Segment::Cubic(p0, p1, p2, p3) => [p0, p1, p2, p3].into_iter().for_each(&mut add),
+            }
+        }
+        bounds
+    }
+
+    /// Coverage of the outline mapped by `map` onto a `width` by `height`
+    /// pixel grid, row by row from the top
+    pub(crate) fn coverage(
+        &self,
+        width: usize,
+        height: usize,
+        map: impl Fn(f32, f32) -> (f32, f32),
+    ) -> Vec<f32> {
+        let map = |p: RasterPoint| {
+            let (x, y) = map(p.x, p.y);
+            point(x, y)
+        };
+        let mut rasterizer = Rasterizer::new(width, height);
+        for segment in &self.segments {
+            match *segment {
+                Segment::Line(p0, p1) => rasterizer.draw_line(map(p0), map(p1)),
+                Segment::Quad(p0, p1, p2) => rasterizer.draw_quad(map(p0), map(p1), map(p2)),
+                Segment::Cubic(p0, p1, p2, p3) => {
+                    rasterizer.draw_cubic(map(p0), map(p1), map(p2), map(p3))
+                }
+            }
+        }
+
+        let mut coverage = vec![0.0; width * height];
+        rasterizer.for_each_pixel_2d(|x, y, alpha| {
+            coverage[y as usize * width + x as usize] = alpha.clamp(0.0, 1.0);
+        });
+        coverage
+    }
+}
+
+fn parse_face(font: &OpenTypeFont) -> Result<ttf_parser::Face<'_>, RenderError> {
+    let index = u32::try_from(font.face_index).unwrap_or(0);
+    let mut face = ttf_parser::Face::parse(&font.data, index).map_err(|e| {
+        RenderError::RasterizationFailed(format!("Failed to load font face: {:?}", e))
+    })?;
+    for coord in &font.variation_coords {
+        // Unknown axes are ignored
+        let _ = face.set_variation(ttf_parser::Tag::from_bytes(&coord.tag), coord.value);
+    }
+    Ok(face)
+}
+
+fn check_glyph(face: &ttf_parser::Face, glyph_id: GlyphId) -> Result<(), RenderError> {
+    if glyph_id.0 >= face.number_of_glyphs() {
+        return Err(RenderError::GlyphNotFound(glyph_id));
+    }
+    Ok(())
+}
+
+fn empty_bitmap(mode: RenderMode) -> GlyphBitmap {
+    GlyphBitmap {
+        width: 0,
+        height: 0,
+        left: 0,
+        top: 0,
+        pitch: 0,
+        data: Vec::new(),
+        format: mode,
+        pixel_format: PixelFormat::for_mode(mode),
+    }
+}
+
+/// Pack 8-bit coverage into 1-bit rows (MSB first), thresholding at 50%
+fn pack_mono(coverage: &[u8], width: usize, height: usize) -> (usize, Vec<u8>) {
+    let pitch = width.div_ceil(8);
+    let mut data = vec![0u8; pitch * height];
+    for y in 0..height {
+        for x in 0..width {
+            if coverage[y * width + x] >= 128 {
+                data[y * pitch + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+    (pitch, data)
+}
+
+/// Outline segment in font units
+enum Segment {
+    Line(RasterPoint, RasterPoint),
+    Quad(RasterPoint, RasterPoint, RasterPoint),
+    Cubic(RasterPoint, RasterPoint, RasterPoint, RasterPoint),
+}
+
+/// Collects outline segments, closing each contour back to its start
+#[derive(Default)]
+struct SegmentCollector {
+    segments: Vec<Segment>,
+    /// Index of the first segment of each contour
+    contour_starts: Vec<usize>,
+    start: Option<RasterPoint>,
+    last: Option<RasterPoint>,
+}
+
+impl SegmentCollector {
+    /// Contours flattened to polylines
+    fn polylines(&self) -> Vec<stroke::Polyline> {
+        let mut ends = self.contour_starts.iter().skip(1).copied();
+        self.contour_starts
+            .iter()
+            .map(|&start| {
+                let end = ends.next().unwrap_or(self.segments.len());
+                let mut points = Vec::new();
+                for segment in &self.segments[start..end] {
+                    match *segment {
+                        Segment::Line(p0, p1) => {
+                            if points.is_empty() {
+                                points.push(p0);
+                            }
+                            points.push(p1);
+                        }
+                        Segment::Quad(p0, p1, p2) => {
+                            if points.is_empty() {
+                                points.push(p0);
+                            }
+                            stroke::flatten_quad(p0, p1, p2, &mut points);
+                        }
+                        Segment::Cubic(p0, p1, p2, p3) => {
+                            if points.is_empty() {
+                                points.push(p0);
+                            }
+                            stroke::flatten_cubic(p0, p1, p2, p3, &mut points);
+                        }
+                    }
+                }
+                let closed = points.len() > 2 && points.first() == points.last();
+                stroke::Polyline { points, closed }
+            })
+            .collect()
+    }
+}
+
+impl ttf_parser::OutlineBuilder for SegmentCollector {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.contour_starts.push(self.segments.len());
+        self.start = Some(point(x, y));
+        self.last = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let to = point(x, y);
+        if let Some(from) = self.last {
+            self.segments.push(Segment::Line(from, to));
+        }
+        self.last = Some(to);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let to = point(x, y);
+        if let Some(from) = self.last {
+            self.segments.push(Segment::Quad(from, point(x1, y1), to));
+        }
+        self.last = Some(to);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let to = point(x, y);
+        if let Some(from) = self.last {
+            self.segments
+                .push(Segment::Cubic(from, point(x1, y1), point(x2, y2), to));
+        }
+        self.last = Some(to);
+    }
+
+    fn close(&mut self) {
+        if let (Some(from), Some(start)) = (self.last, self.start) {
+            if from != start {
+                self.segments.push(Segment::Line(from, start));
+            }
+        }
+        self.last = self.start;
+    }
+}
+
+/// Collects on- and off-curve points per contour, like FreeType outlines
+#[derive(Default)]
+struct ContourCollector {
+    contours: Vec<Vec<RasterPoint>>,
+}
+
+impl ContourCollector {
+    fn push(&mut self, x: f32, y: f32) {
+        if let Some(contour) = self.contours.last_mut() {
+            contour.push(point(x, y));
+        }
+    }
+}
+
+impl ttf_parser::OutlineBuilder for ContourCollector {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.contours.push(vec![point(x, y)]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.push(x1, y1);
+        self.push(x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.push(x1, y1);
+        self.push(x2, y2);
+        self.push(x, y);
+    }
+
+    fn close(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DEFAULT_OUTLINE_PPEM;
+
+    fn test_font() -> OpenTypeFont {
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        OpenTypeFont::from_data(data, 0)
+    }
+
+    /// Glyph ID of 'A' in the test font
+    fn glyph_a(font: &OpenTypeFont) -> GlyphId {
+        let face = parse_face(font).unwrap();
+        GlyphId(face.glyph_index('A').unwrap().0)
+    }
+
+    #[test]
+    fn test_rasterize_gray_has_coverage() {
+        let font = test_font();
+        let bitmap = rasterize(&font, glyph_a(&font), 32.0, RenderMode::Gray).unwrap();
+
+        assert!(bitmap.width > 10 && bitmap.height > 10);
+        assert_eq!(bitmap.pitch, bitmap.width as usize);
+        assert_eq!(bitmap.data.len(), (bitmap.width * bitmap.height) as usize);
+        assert!(bitmap.data.contains(&255));
+        // 'A' sits on the baseline and rises about cap height
+        assert!(bitmap.top > 20 && bitmap.top <= 32);
+    }
+
+    #[test]
+    fn test_rasterize_mono_packs_bits() {
+        let font = test_font();
+        let bitmap = rasterize(&font, glyph_a(&font), 32.0, RenderMode::Mono).unwrap();
+
+        assert_eq!(bitmap.pitch, (bitmap.width as usize).div_ceil(8));
+        assert_eq!(bitmap.data.len(), bitmap.pitch * bitmap.height as usize);
+        assert!(bitmap.data.iter().any(|&byte| byte != 0));
+    }
+
+    #[test]
+    fn test_rasterize_subpixel_triples_width() {
+        let font = test_font();
+        let gray = rasterize(&font, glyph_a(&font), 32.0, RenderMode::Gray).unwrap();
+        let rgb = rasterize(&font, glyph_a(&font), 32.0, RenderMode::SubpixelRgb).unwrap();
+
+        assert!(rgb.width >= gray.width * 3 - 3 && rgb.width <= gray.width * 3 + 3);
+        assert_eq!(rgb.height, gray.height);
+        assert_eq!(rgb.pixel_format, PixelFormat::LcdRgb);
+        assert_eq!(
+            rgb.to_alpha8().len(),
+            (rgb.pixel_width() * rgb.height) as usize
+        );
+    }
+
+    #[test]
+    fn test_rasterize_space_is_empty_and_bad_glyph_errors() {
+        let font = test_font();
+        let face = parse_face(&font).unwrap();
+        let space = GlyphId(face.glyph_index(' ').unwrap().0);
+
+        let bitmap = rasterize(&font, space, 32.0, RenderMode::Gray).unwrap();
+        assert_eq!((bitmap.width, bitmap.height), (0, 0));
+
+        let missing = GlyphId(u16::MAX);
+        assert_eq!(
+            rasterize(&font, missing, 32.0, RenderMode::Gray).unwrap_err(),
+            RenderError::GlyphNotFound(missing)
+        );
+    }
+
+    #[test]
+    fn test_outline_contours_and_bounds() {
+        let font = test_font();
+        let outline = outline(&font, glyph_a(&font), OutlineScale::default()).unwrap();
+
+        // 'A' has an outer contour and the counter
+        assert_eq!(outline.contours.len(), 2);
+        assert!(outline.bounds.max_x > outline.bounds.min_x);
+        assert!(outline.bounds.max_y > 10.0 && outline.bounds.max_y <= 16.0);
+        assert!(outline.bounds.min_y >= -0.5);
+    }
+
+    #[test]
+    fn test_font_unit_outline_scales_to_ppem() {
+        let font = test_font();
+        let glyph = glyph_a(&font);
+        let units = outline(&font, glyph, OutlineScale::FontUnits).unwrap();
+        let pixels = outline(&font, glyph, OutlineScale::Ppem(32.0)).unwrap();
+
+        let face = parse_face(&font).unwrap();
+        let hmtx_advance = face
+            .glyph_hor_advance(ttf_parser::GlyphId(glyph.0))
+            .unwrap();
+        assert_eq!(units.units_per_em, 2048);
+        assert_eq!(units.advance, hmtx_advance as f32);
+        assert_eq!(
+            units.left_side_bearing,
+            face.glyph_bounding_box(ttf_parser::GlyphId(glyph.0))
+                .unwrap()
+                .x_min as f32
+        );
+
+        let scale = 32.0 / 2048.0;
+        for (unit, pixel) in [
+            (units.advance, pixels.advance),
+            (units.left_side_bearing, pixels.left_side_bearing),
+            (units.right_side_bearing, pixels.right_side_bearing),
+            (
+                units.contours[0].points[0].x,
+                pixels.contours[0].points[0].x,
+            ),
+        ] {
+            assert!((unit * scale - pixel).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_extents_match_outline_bounds() {
+        let font = test_font();
+        let ink = extents(&font, glyph_a(&font), DEFAULT_OUTLINE_PPEM)
+            .unwrap()
+            .unwrap();
+        let bounds = outline(&font, glyph_a(&font), OutlineScale::default())
+            .unwrap()
+            .bounds;
+
+        for (a, b) in [
+            (ink.min_x, bounds.min_x),
+            (ink.min_y, bounds.min_y),
+            (ink.max_x, bounds.max_x),
+            (ink.max_y, bounds.max_y),
+        ] {
+            assert!((a - b).abs() < 1e-3);
+        }
+
+        let face = parse_face(&font).unwrap();
+        let space = GlyphId(face.glyph_index(' ').unwrap().0);
+        assert!(extents(&font, space, 32.0).unwrap().is_none());
+    }
+}
@@ -0,0 +1,329 @@
+//! Outline stroking for the pure-Rust backend
+//!
+//! A stroke is built as a union of convex polygons: one quad per polyline
+//! edge plus join and cap shapes. All polygons share one orientation, so
+//! non-zero coverage accumulation renders their union.
+
+use ab_glyph_rasterizer::{point, Point};
+
+use crate::types::{LineCap, LineJoin, StrokeOptions};
+
+/// Line segments per quadratic curve when flattening
+const QUAD_STEPS: usize = 8;
+
+/// Line segments per cubic curve when flattening
+const CUBIC_STEPS: usize = 12;
+
+/// Line segments approximating a full circle for round joins and caps
+const CIRCLE_STEPS: usize = 24;
+
+/// Flattened contour
+pub(crate) struct Polyline {
+    /// Points, repeating the first point at the end when closed
+    pub points: Vec<Point>,
+    /// Whether the contour is closed
+    pub closed: bool,
+}
+
+/// Append the points of a flattened quadratic curve after `p0`
+pub(crate) fn flatten_quad(p0: Point, p1: Point, p2: Point, out: &mut Vec<Point>) {
+    for step in 1..=QUAD_STEPS {
+        let t = step as f32 / QUAD_STEPS as f32;
+        let mt = 1.0 - t;
+        out.push(point(
+            mt * mt * p0.x + 2.0 * mt * t * p1.x + t * t * p2.x,
+            mt * mt * p0.y + 2.0 * mt * t * p1.y + t * t * p2.y,
+        ));
+    }
+}
+
+/// Append the points of a flattened cubic curve after `p0`
+pub(crate) fn flatten_cubic(p0: Point, p1: Point, p2: Point, p3: Point, out: &mut Vec<Point>) {
+    for step in 1..=CUBIC_STEPS {
+        let t = step as f32 / CUBIC_STEPS as f32;
+        let mt = 1.0 - t;
+        let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+        out.push(point(
+            a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+            a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+        ));
+    }
+}
+
+/// Polygons covering the stroke of `polylines` with half-width `radius`
+pub(crate) fn stroke_polygons(
+    polylines: &[Polyline],
+    radius: f32,
+    options: &StrokeOptions,
+) -> Vec<Vec<Point>> {
+    let mut polygons = Vec::new();
+    if radius <= 0.0 {
+        return polygons;
+    }
+
+    for polyline in polylines {
+        let mut points: Vec<Point> = Vec::with_capacity(polyline.points.len());
+        for &p in &polyline.points {
+            if points.last() != Some(&p) {
+                points.push(p);
+            }
+        }
+        if polyline.closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        match points.len() {
+            0 => continue,
+            1 => {
+                if options.line_cap == LineCap::Round {
+                    push_polygon(&mut polygons, circle(points[0], radius));
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let count = points.len();
+        let edges = if polyline.closed { count } else { count - 1 };
+        for index in 0..edges {
+            let (a, b) = (points[index], points[(index + 1) % count]);
+            let n = normal(a, b, radius);
+            push_polygon(
+                &mut polygons,
+                vec![add(a, n), add(b, n), sub(b, n), sub(a, n)],
+            );
+        }
+
+        // Joins at every vertex of closed contours, interior ones otherwise
+        let joints = if polyline.closed {
+            0..count
+        } else {
+            1..count - 1
+        };
+        for index in joints {
+            let previous = points[(index + count - 1) % count];
+            let vertex = points[index];
+            let next = points[(index + 1) % count];
+            join(&mut polygons, previous, vertex, next, radius, options);
+        }
+
+        if !polyline.closed {
+            cap(
+                &mut polygons,
+                points[1],
+                points[0],
+                radius,
+                options.line_cap,
+            );
+            cap(
+                &mut polygons,
+                points[count - 2],
+                points[count - 1],
+                radius,
+                options.line_cap,
+            );
+        }
+    }
+    polygons
+}
+
+fn join(
+    polygons: &mut Vec<Vec<Point>>,
+    previous: Point,
+    vertex: Point,
+    next: Point,
+    radius: f32,
+    options: &StrokeOptions,
+) {
+    if options.line_join == LineJoin::Round {
+        push_polygon(polygons, circle(vertex, radius));
+        return;
+    }
+
+    let n1 = normal(previous, vertex, radius);
+    let n2 = normal(vertex, next, radius);
+    // Only one side is the outer corner; filling both is harmless
+    for side in [1.0, -1.0] {
+        let a = add(vertex, scale(n1, side));
+        let b = add(vertex, scale(n2, side));
+        let bisector = add(n1, n2);
+        let cos_half = dot(bisector, n1) / (length(bisector) * radius);
+        let within_limit = cos_half > 0.0 && 1.0 / cos_half <= options.miter_limit;
+        if options.line_join == LineJoin::Miter && within_limit {
+            let miter = scale(bisector, side * radius / (length(bisector) * cos_half));
+            push_polygon(polygons, vec![vertex, a, add(vertex, miter), b]);
+        } else {
+            push_polygon(polygons, vec![vertex, a, b]);
+        }
+    }
+}
+
+/// Cap at `end`, for the edge arriving from `from`
+fn cap(polygons: &mut Vec<Vec<Point>>, from: Point, end: Point, radius: f32, line_cap: LineCap) {
+    match line_cap {
+        LineCap::Butt => {}
+        LineCap::Round => push_polygon(polygons, circle(end, radius)),
+        LineCap::Square => {
+            let n = normal(from, end, radius);
+            // Edge direction scaled to the radius
+            let d = point(-n.y, n.x);
+            let d = if dot(d, sub(end, from)) < 0.0 {
+                scale(d, -1.0)
+            } else {
+                d
+            };
+            push_polygon(
+                polygons,
+                vec![
+                    add(end, n),
+                    add(add(end, n), d),
+                    add(sub(end, n), d),
+                    sub(end, n),
+                ],
+            );
+        }
+    }
+}
+
+fn circle(center: Point, radius: f32) -> Vec<Point> {
+    (0..CIRCLE_STEPS)
+        .map(|step| {
+            let angle = step as f32 / CIRCLE_STEPS as f32 * std::f32::consts::TAU;
+            point(
+                center.x + radius * angle.cos(),
+                center.y + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+/// Add a polygon in counter-clockwise orientation
+fn push_polygon(polygons: &mut Vec<Vec<Point>>, mut polygon: Vec<Point>) {
+    let doubled_area: f32 = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+    if doubled_area == 0.0 {
+        return;
+    }
+    if doubled_area < 0.0 {
+        polygon.reverse();
+    }
+    polygons.push(polygon);
+}
+
+/// Left normal of the edge `a -> b` with length `radius`
+fn normal(a: Point, b: Point, radius: f32) -> Point {
+    let d = sub(b, a);
+    let len = length(d);
+    point(-d.y / len * radius, d.x / len * radius)
+}
+
+fn add(a: Point, b: Point) -> Point {
+    point(a.x + b.x, a.y + b.y)
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    point(a.x - b.x, a.y - b.y)
+}
+
+fn scale(p: Point, factor: f32) -> Point {
+    point(p.x * factor, p.y * factor)
+}
+
+fn dot(a: Point, b: Point) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+fn length(p: Point) -> f32 {
+    dot(p, p).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Polyline {
+        Polyline {
+            points: vec![
+                point(0.0, 0.0),
+                point(10.0, 0.0),
+                point(10.0, 10.0),
+                point(0.0, 10.0),
+                point(0.0, 0.0),
+            ],
+            closed: true,
+        }
+    }
+
+    fn extent(polygons: &[Vec<Point>]) -> (f32, f32) {
+        let points = polygons.iter().flatten();
+        let min = points.clone().map(|p| p.x).fold(f32::MAX, f32::min);
+        let max = points.map(|p| p.x).fold(f32::MIN, f32::max);
+        (min, max)
+    }
+
+    #[test]
+    fn test_miter_join_reaches_corner() {
+        let options = StrokeOptions {
+            line_join: LineJoin::Miter,
+            ..StrokeOptions::default()
+        };
+        let polygons = stroke_polygons(&[square()], 1.0, &options);
+        // Four edges plus two join polygons per corner
+        assert_eq!(polygons.len(), 12);
+        let (min, max) = extent(&polygons);
+        assert!((min + 1.0).abs() < 1e-4 && (max - 11.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_miter_limit_falls_back_to_bevel() {
+        let options = StrokeOptions {
+            line_join: LineJoin::Miter,
+            // A right angle needs a limit of sqrt(2)
+            miter_limit: 1.2,
+            ..StrokeOptions::default()
+        };
+        let polygons = stroke_polygons(&[square()], 1.0, &options);
+        assert!(polygons.iter().filter(|polygon| polygon.len() == 3).count() == 8);
+    }
+
+    #[test]
+    fn test_polygons_share_orientation() {
+        let options = StrokeOptions {
+            line_join: LineJoin::Round,
+            ..StrokeOptions::default()
+        };
+        for polygon in stroke_polygons(&[square()], 2.0, &options) {
+            let area: f32 = polygon
+                .iter()
+                .zip(polygon.iter().cycle().skip(1))
+                .map(|(a, b)| a.x * b.y - b.x * a.y)
+                .sum();
+            assert!(area > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_open_polyline_caps() {
+        let line = Polyline {
+            points: vec![point(0.0, 0.0), point(10.0, 0.0)],
+            closed: false,
+        };
+        let butt = StrokeOptions::default();
+        assert_eq!(extent(&stroke_polygons(&[line], 1.0, &butt)), (0.0, 10.0));
+
+        let line = Polyline {
+            points: vec![point(0.0, 0.0), point(10.0, 0.0)],
+            closed: false,
+        };
+        let square_cap = StrokeOptions {
+            line_cap: LineCap::Square,
+            ..StrokeOptions::default()
+        };
+        assert_eq!(
+            extent(&stroke_polygons(&[line], 1.0, &square_cap)),
+            (-1.0, 11.0)
+        );
+    }
+}
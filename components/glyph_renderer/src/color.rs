@@ -0,0 +1,576 @@
+//! Color glyphs from `COLR`/`CPAL` layers and paint graphs
+//!
+//! A version 0 `COLR` glyph is a stack of ordinary glyphs, each filled with
+//! a color from a `CPAL` palette or with the text color. The layers are
+//! rasterized by a [`RasterBackend`] and composited into a premultiplied
+//! [`PixelFormat::Rgba8`] bitmap with the colors of a [`FontPalette`].
+//!
+//! Version 1 glyphs are paint graphs with gradients, transforms and blend
+//! modes. They are evaluated into the same kind of bitmap; renderers with a
+//! vector backend can walk the graph from [`ColrTable::paint`] instead.
+
+use crate::backend::RasterBackend;
+use crate::types::{GlyphBitmap, GlyphId, OpenTypeFont, PixelFormat, RenderError, RenderMode};
+use font_parser::{Color, ColrTable, CpalTable};
+use font_types::FontPalette;
+
+mod paint;
+
+/// Layer palette index that stands for the text color
+const FOREGROUND_INDEX: u16 = 0xFFFF;
+
+/// Whether `glyph_id` has `COLR` layers or a paint graph
+pub fn is_color_glyph(font: &OpenTypeFont, glyph_id: GlyphId) -> bool {
+    matches!(colr_table(font), Ok(Some(colr)) if colr.is_color_glyph(glyph_id.0))
+}
+
+/// Render the `COLR` paint graph or layers of a glyph at `size` points and
+/// `dpi`
+///
+/// Colors come from the palette `palette` selects, with its overrides
+/// applied; paints using the text color are filled with the straight RGBA
+/// `foreground`. Glyphs with a version 1 paint graph are drawn from it,
+/// others from their version 0 layers. Returns `None` for glyphs with
+/// neither, which render as plain coverage bitmaps instead.
+///
+/// The result isn't cached; [`GlyphRenderer::rasterize_color_glyph`]
+/// caches it per palette and foreground color.
+///
+/// [`GlyphRenderer::rasterize_color_glyph`]: crate::GlyphRenderer::rasterize_color_glyph
+pub fn rasterize_color_glyph(
+    backend: &dyn RasterBackend,
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    size: f32,
+    dpi: f32,
+    palette: &FontPalette,
+    foreground: [u8; 4],
+) -> Result<Option<GlyphBitmap>, RenderError> {
+    let Some(colr) = colr_table(font)? else {
+        return Ok(None);
+    };
+    let paint = colr
+        .paint(glyph_id.0)
+        .map_err(|e| RenderError::RasterizationFailed(format!("Invalid COLR paint: {e}")))?;
+    if let Some(paint) = paint {
+        let colors = palette_colors(font, palette)?;
+        let scale = size * dpi / 72.0 / units_per_em(font)?;
+        return paint::render(font, &colr, glyph_id.0, &paint, scale, &colors, foreground)
+            .map(Some);
+    }
+    let Some(layers) = colr.get_layers(glyph_id.0) else {
+        return Ok(None);
+    };
+    let colors = palette_colors(font, palette)?;
+
+    let mut painted = Vec::with_capacity(layers.len());
+    for layer in layers {
+        let color = if layer.palette_index == FOREGROUND_INDEX {
+            foreground
+        } else {
+            // Entries missing from the palette leave the layer out
+            match colors.get(layer.palette_index as usize) {
+                Some(&Color {
+                    red,
+                    green,
+                    blue,
+                    alpha,
+                }) => [red, green, blue, alpha],
+                None => continue,
+            }
+        };
+        let coverage =
+            backend.rasterize(font, GlyphId(layer.glyph_id), size, dpi, RenderMode::Gray)?;
+        if coverage.width > 0 && coverage.height > 0 {
+            painted.push((coverage, color));
+        }
+    }
+
+    Ok(Some(composite(&painted)))
+}
+
+/// Paint coverage bitmaps bottom to top, each filled with a straight color
+fn composite(layers: &[(GlyphBitmap, [u8; 4])]) -> GlyphBitmap {
+    let left = layers
+        .iter()
+        .map(|(bitmap, _)| bitmap.left)
+        .min()
+        .unwrap_or(0);
+    let top = layers
+        .iter()
+        .map(|(bitmap, _)| bitmap.top)
+        .max()
+        .unwrap_or(0);
+    let right = layers
+        .iter()
+        .map(|(bitmap, _)| bitmap.left + bitmap.pixel_width() as i32)
+        .max()
+        .unwrap_or(0);
+    let bottom = layers
+        .iter()
+        .map(|(bitmap, _)| bitmap.top - bitmap.height as i32)
+        .min()
+        .unwrap_or(0);
+    let width = (right - left) as usize;
+    let height = (top - bottom) as usize;
+    let pitch = width * 4;
+
+    let mut data = vec![0u8; pitch * height];
+    for (bitmap, [red, green, blue, alpha]) in layers {
+        let dx = (bitmap.left - left) as usize;
+        let dy = (top - bitmap.top) as usize;
+        for y in 0..bitmap.height {
+            let row = (dy + y as usize) * pitch;
+            for (x, &coverage) in bitmap.row(y).iter().enumerate() {
+                let source_alpha = mul(*alpha, coverage);
+                if source_alpha == 0 {
+                    continue;
+                }
+                let pixel = &mut data[row + (dx + x) * 4..][..4];
+                let source = [
+                    mul(*red, source_alpha),
+                    mul(*green, source_alpha),
+                    mul(*blue, source_alpha),
+                    source_alpha,
+                ];
+                for (target, source) in pixel.iter_mut().zip(source) {
+                    *target = source + mul(*target, 255 - source_alpha);
+                }
+            }
+        }
+    }
+
+    GlyphBitmap {
+        width: pitch as u32,
+        height: height as u32,
+        left,
+        top,
+        pitch,
+        data,
+        format: RenderMode::Gray,
+        pixel_format: PixelFormat::Rgba8,
+    }
+}
+
+/// Product of two 8-bit fractions, rounded
+fn mul(a: u8, b: u8) -> u8 {
+    let product = u32::from(a) * u32::from(b) + 128;
+    ((product + (product >> 8)) >> 8) as u8
+}
+
+/// Parsed `COLR` table of the font, if it has one
+fn colr_table(font: &OpenTypeFont) -> Result<Option<ColrTable>, RenderError> {
+    font_table(font, b"COLR")?
+        .map(|data| {
+            ColrTable::parse(data)
+                .map_err(|e| RenderError::RasterizationFailed(format!("Invalid COLR table: {e}")))
+        })
+        .transpose()
+}
+
+/// Colors of the `CPAL` palette `palette` selects, with its overrides
+fn palette_colors(font: &OpenTypeFont, palette: &FontPalette) -> Result<Vec<Color>, RenderError> {
+    Ok(match font_table(font, b"CPAL")? {
+        Some(data) => CpalTable::parse(data)
+            .map_err(|e| RenderError::RasterizationFailed(format!("Invalid CPAL table: {e}")))?
+            .resolve_palette(palette)
+            .unwrap_or_default(),
+        None => Vec::new(),
+    })
+}
+
+/// Font units per em, from the `head` table
+pub(crate) fn units_per_em(font: &OpenTypeFont) -> Result<f32, RenderError> {
+    font_table(font, b"head")?
+        .and_then(|head| head.get(18..20))
+        .map(|bytes| f32::from(u16::from_be_bytes([bytes[0], bytes[1]])))
+        .filter(|&units| units > 0.0)
+        .ok_or_else(|| RenderError::RasterizationFailed("Invalid head table".to_string()))
+}
+
+/// Raw bytes of table `tag`
+pub(crate) fn font_table<'a>(
+    font: &'a OpenTypeFont,
+    tag: &[u8; 4],
+) -> Result<Option<&'a [u8]>, RenderError> {
+    let index = u32::try_from(font.face_index).unwrap_or(0);
+    let face = ttf_parser::RawFace::parse(font.data(), index).map_err(|e| {
+        RenderError::RasterizationFailed(format!("Failed to load font face: {:?}", e))
+    })?;
+    Ok(face.table(ttf_parser::Tag::from_bytes(tag)))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::backend;
+    use crate::GlyphRenderer;
+    use font_parser::PALETTE_USABLE_WITH_DARK_BACKGROUND;
+    use font_types::BasePalette;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    /// DejaVu Sans with 'A' (glyph 36) drawn in palette entry 0 and 'B'
+    /// (glyph 37) in the text color; palette 0 is red and palette 1, for
+    /// dark backgrounds, blue
+    fn color_font() -> OpenTypeFont {
+        let mut colr = Vec::new();
+        colr.extend_from_slice(&0u16.to_be_bytes()); // version
+        colr.extend_from_slice(&2u16.to_be_bytes()); // base glyph records
+        colr.extend_from_slice(&14u32.to_be_bytes());
+        colr.extend_from_slice(&26u32.to_be_bytes()); // layer records
+        colr.extend_from_slice(&2u16.to_be_bytes());
+        // Base glyphs (glyph, first layer, layer count), then layers
+        // (glyph, palette entry)
+        for value in [36u16, 0, 1, 37, 1, 1, 36, 0, 37, FOREGROUND_INDEX] {
+            colr.extend_from_slice(&value.to_be_bytes());
+        }
+
+        with_palettes(colr)
+    }
+
+    /// DejaVu Sans with `colr` and a red and a blue palette of one entry
+    fn with_palettes(colr: Vec<u8>) -> OpenTypeFont {
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+
+        let mut cpal = Vec::new();
+        for value in [1u16, 1, 2, 2] {
+            cpal.extend_from_slice(&value.to_be_bytes());
+        }
+        cpal.extend_from_slice(&36u32.to_be_bytes()); // color records
+        cpal.extend_from_slice(&[0, 0, 0, 1]); // color record indices
+        cpal.extend_from_slice(&28u32.to_be_bytes()); // palette types
+        cpal.extend_from_slice(&[0; 8]); // no labels
+        cpal.extend_from_slice(&0u32.to_be_bytes());
+        cpal.extend_from_slice(&PALETTE_USABLE_WITH_DARK_BACKGROUND.to_be_bytes());
+        cpal.extend_from_slice(&[0, 0, 255, 255, 255, 0, 0, 255]); // BGRA red, blue
+
+        OpenTypeFont::from_data(with_tables(&data, [(*b"COLR", colr), (*b"CPAL", cpal)]), 0)
+    }
+
+    /// DejaVu Sans with a version 1 'A' (glyph 36) filled with a linear
+    /// gradient from palette entry 0 on the left to the text color on the
+    /// right, and 'B' (glyph 37) drawing that 'A' scaled by 1.5
+    fn gradient_font() -> OpenTypeFont {
+        let mut colr = Vec::new();
+        colr.extend_from_slice(&1u16.to_be_bytes()); // version
+        colr.extend_from_slice(&[0; 12]); // no version 0 glyphs
+        colr.extend_from_slice(&34u32.to_be_bytes()); // base glyph list
+        colr.extend_from_slice(&[0; 16]); // layer and clip lists, variations
+                                          // Base glyph list: glyph and paint offset from the list
+        colr.extend_from_slice(&2u32.to_be_bytes());
+        colr.extend_from_slice(&36u16.to_be_bytes());
+        colr.extend_from_slice(&16u32.to_be_bytes());
+        colr.extend_from_slice(&37u16.to_be_bytes());
+        colr.extend_from_slice(&53u32.to_be_bytes());
+        // 50: PaintGlyph 36 with the gradient at +6
+        colr.extend_from_slice(&[10, 0, 0, 6, 0, 36]);
+        // 56: PaintLinearGradient, color line at +16, (0,0) to (1400,0)
+        colr.extend_from_slice(&[4, 0, 0, 16]);
+        for value in [0i16, 0, 1400, 0, 0, 1000] {
+            colr.extend_from_slice(&value.to_be_bytes());
+        }
+        // 72: ColorLine, pad, palette entry 0 then the text color
+        colr.extend_from_slice(&[0, 0, 2]);
+        colr.extend_from_slice(&[0, 0, 0, 0, 0x40, 0]);
+        colr.extend_from_slice(&[0x40, 0, 0xFF, 0xFF, 0x40, 0]);
+        // 87: PaintScale by 1.5, then 95: PaintColrGlyph 36
+        colr.extend_from_slice(&[16, 0, 0, 8, 0x60, 0, 0x60, 0]);
+        colr.extend_from_slice(&[11, 0, 36]);
+
+        with_palettes(colr)
+    }
+
+    /// DejaVu Sans with version 1 glyphs 36, 37, ... each compositing the
+    /// glyph `target` returns for it over itself, both drawn with
+    /// PaintColrGlyph
+    fn composite_chain_font(glyphs: u16, target: impl Fn(u16) -> u16) -> OpenTypeFont {
+        let mut colr = Vec::new();
+        colr.extend_from_slice(&1u16.to_be_bytes()); // version
+        colr.extend_from_slice(&[0; 12]); // no version 0 glyphs
+        colr.extend_from_slice(&34u32.to_be_bytes()); // base glyph list
+        colr.extend_from_slice(&[0; 16]); // layer and clip lists, variations
+        let paints = 4 + 6 * u32::from(glyphs);
+        colr.extend_from_slice(&u32::from(glyphs).to_be_bytes());
+        for index in 0..glyphs {
+            colr.extend_from_slice(&(36 + index).to_be_bytes());
+            colr.extend_from_slice(&(paints + 11 * u32::from(index)).to_be_bytes());
+        }
+        for index in 0..glyphs {
+            // PaintComposite with source and backdrop at +8 (src-over),
+            // then PaintColrGlyph
+            colr.extend_from_slice(&[32, 0, 0, 8, 3, 0, 0, 8, 11]);
+            colr.extend_from_slice(&target(36 + index).to_be_bytes());
+        }
+
+        with_palettes(colr)
+    }
+
+    /// Copy of an sfnt with tables added to its directory
+    pub(crate) fn with_tables<const N: usize>(
+        font: &[u8],
+        tables: [([u8; 4], Vec<u8>); N],
+    ) -> Vec<u8> {
+        let read_u32 = |at: usize| u32::from_be_bytes(font[at..at + 4].try_into().unwrap());
+        let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+        let shift = 16 * N as u32;
+
+        let mut records: Vec<([u8; 4], u32, u32, u32)> = (0..num_tables)
+            .map(|i| {
+                let at = 12 + 16 * i;
+                let tag = font[at..at + 4].try_into().unwrap();
+                (
+                    tag,
+                    read_u32(at + 4),
+                    read_u32(at + 8) + shift,
+                    read_u32(at + 12),
+                )
+            })
+            .collect();
+        let mut body = font[12 + 16 * num_tables..].to_vec();
+        let body_start = 12 + 16 * (num_tables + N) as u32;
+        for (tag, data) in tables {
+            body.resize(body.len().next_multiple_of(4), 0);
+            records.push((tag, 0, body_start + body.len() as u32, data.len() as u32));
+            body.extend_from_slice(&data);
+        }
+        records.sort_by_key(|record| record.0);
+
+        let count = records.len() as u16;
+        let entry_selector = 15 - count.leading_zeros() as u16;
+        let search_range = 16 << entry_selector;
+        let mut out = font[..4].to_vec();
+        for value in [
+            count,
+            search_range,
+            entry_selector,
+            count * 16 - search_range,
+        ] {
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+        for (tag, checksum, offset, length) in records {
+            out.extend_from_slice(&tag);
+            for value in [checksum, offset, length] {
+                out.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        out.extend_from_slice(&body);
+        out
+    }
+
+    /// Straight color of the bitmap's fully opaque pixels
+    fn opaque_color(bitmap: &GlyphBitmap) -> [u8; 4] {
+        let rgba = bitmap.to_rgba8(false);
+        let pixel = rgba
+            .chunks_exact(4)
+            .find(|pixel| pixel[3] == 255)
+            .expect("no opaque pixel");
+        [pixel[0], pixel[1], pixel[2], pixel[3]]
+    }
+
+    #[test]
+    fn test_color_glyph_uses_selected_palette() {
+        let font = color_font();
+        let backend = backend::default_backend();
+        let render = |palette: &FontPalette| {
+            rasterize_color_glyph(
+                backend.as_ref(),
+                &font,
+                GlyphId(36),
+                32.0,
+                72.0,
+                palette,
+                BLUE,
+            )
+            .unwrap()
+            .unwrap()
+        };
+
+        let normal = render(&FontPalette::default());
+        assert_eq!(normal.pixel_format, PixelFormat::Rgba8);
+        assert_eq!(normal.width, normal.pixel_width() * 4);
+        assert_eq!(opaque_color(&normal), RED);
+        assert_eq!(
+            opaque_color(&render(&FontPalette::new(BasePalette::Dark))),
+            BLUE
+        );
+        // Light isn't flagged in this font, so it keeps the default palette
+        assert_eq!(
+            opaque_color(&render(&FontPalette::new(BasePalette::Light))),
+            RED
+        );
+        let custom = FontPalette::new(BasePalette::Dark).with_override(0, [0, 128, 0, 255]);
+        assert_eq!(opaque_color(&render(&custom)), [0, 128, 0, 255]);
+
+        // Coverage matches the plain glyph
+        let plain = backend
+            .rasterize(&font, GlyphId(36), 32.0, 72.0, RenderMode::Gray)
+            .unwrap();
+        assert_eq!(normal.to_alpha8(), plain.to_alpha8());
+        assert_eq!((normal.left, normal.top), (plain.left, plain.top));
+    }
+
+    #[test]
+    fn test_foreground_layers_and_plain_glyphs() {
+        let font = color_font();
+        let backend = backend::default_backend();
+        let palette = FontPalette::default();
+        let render = |glyph_id| {
+            rasterize_color_glyph(
+                backend.as_ref(),
+                &font,
+                glyph_id,
+                32.0,
+                72.0,
+                &palette,
+                BLUE,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(opaque_color(&render(GlyphId(37)).unwrap()), BLUE);
+        assert!(render(GlyphId(38)).is_none());
+        assert!(is_color_glyph(&font, GlyphId(36)));
+        assert!(!is_color_glyph(&font, GlyphId(38)));
+    }
+
+    #[test]
+    fn test_color_glyphs_cached_per_palette() {
+        let font = color_font();
+        let mut renderer = GlyphRenderer::new();
+        let dark = FontPalette::new(BasePalette::Dark);
+
+        let normal = renderer
+            .rasterize_color_glyph(&font, GlyphId(36), 32.0, &FontPalette::default(), BLUE)
+            .unwrap()
+            .unwrap();
+        renderer
+            .rasterize_color_glyph(&font, GlyphId(36), 32.0, &FontPalette::default(), BLUE)
+            .unwrap();
+        let dark_bitmap = renderer
+            .rasterize_color_glyph(&font, GlyphId(36), 32.0, &dark, BLUE)
+            .unwrap()
+            .unwrap();
+        renderer
+            .rasterize_color_glyph(&font, GlyphId(36), 32.0, &dark, RED)
+            .unwrap();
+        renderer
+            .rasterize_glyph(&font, GlyphId(36), 32.0, RenderMode::Gray)
+            .unwrap();
+
+        let stats = renderer.cache_stats();
+        assert_eq!(stats.entries, 4);
+        assert_eq!(stats.hits, 1);
+        assert_ne!(normal.data, dark_bitmap.data);
+        assert!(renderer
+            .rasterize_color_glyph(&font, GlyphId(38), 32.0, &dark, BLUE)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_linear_gradient_glyph() {
+        let font = gradient_font();
+        let backend = backend::default_backend();
+
+        // Given a glyph filled with a red to blue gradient
+        let bitmap = rasterize_color_glyph(
+            backend.as_ref(),
+            &font,
+            GlyphId(36),
+            32.0,
+            72.0,
+            &FontPalette::default(),
+            BLUE,
+        )
+        .unwrap()
+        .unwrap();
+
+        // Then opaque pixels turn from red on the left to blue on the right
+        assert_eq!(bitmap.pixel_format, PixelFormat::Rgba8);
+        assert!(is_color_glyph(&font, GlyphId(36)));
+        let rgba = bitmap.to_rgba8(false);
+        let width = bitmap.pixel_width() as usize;
+        let opaque: Vec<(usize, &[u8])> = rgba
+            .chunks_exact(4)
+            .enumerate()
+            .filter(|(_, pixel)| pixel[3] == 255)
+            .map(|(index, pixel)| (index % width, pixel))
+            .collect();
+        let leftmost = opaque.iter().min_by_key(|(x, _)| *x).unwrap().1;
+        let rightmost = opaque.iter().max_by_key(|(x, _)| *x).unwrap().1;
+        assert!(leftmost[0] > 200 && leftmost[2] < 55, "{leftmost:?}");
+        assert!(rightmost[2] > 200 && rightmost[0] < 55, "{rightmost:?}");
+
+        // And the glyph covers the same pixels as the plain outline
+        let plain = backend
+            .rasterize(&font, GlyphId(36), 32.0, 72.0, RenderMode::Gray)
+            .unwrap();
+        assert!((bitmap.left - plain.left).abs() <= 1);
+        assert!((bitmap.top - plain.top).abs() <= 1);
+        assert!((bitmap.pixel_width() as i32 - plain.pixel_width() as i32).abs() <= 1);
+    }
+
+    #[test]
+    fn test_transformed_colr_glyph_reference() {
+        let font = gradient_font();
+        let backend = backend::default_backend();
+        let render = |glyph_id| {
+            rasterize_color_glyph(
+                backend.as_ref(),
+                &font,
+                GlyphId(glyph_id),
+                32.0,
+                72.0,
+                &FontPalette::default(),
+                BLUE,
+            )
+            .unwrap()
+            .unwrap()
+        };
+
+        // Given 'B' drawing the gradient 'A' scaled by 1.5
+        let glyph = render(36);
+        let scaled = render(37);
+
+        // Then it is half as large again, in the same colors
+        let ratio = scaled.pixel_width() as f32 / glyph.pixel_width() as f32;
+        assert!((ratio - 1.5).abs() < 0.1, "{ratio}");
+        let ratio = scaled.height as f32 / glyph.height as f32;
+        assert!((ratio - 1.5).abs() < 0.1, "{ratio}");
+        assert_eq!(opaque_color(&scaled)[3], 255);
+    }
+
+    #[test]
+    fn test_colr_glyph_expansion_is_bounded() {
+        let render = |font: &OpenTypeFont| {
+            rasterize_color_glyph(
+                backend::default_backend().as_ref(),
+                font,
+                GlyphId(36),
+                32.0,
+                72.0,
+                &FontPalette::default(),
+                BLUE,
+            )
+        };
+
+        // Given each of 40 glyphs drawing the next one twice, so the graph
+        // expands to 2^40 paints
+        let font = composite_chain_font(40, |glyph| glyph + 1);
+        // Then rendering gives up once the shared budget runs out
+        let error = render(&font).unwrap_err();
+        assert!(error.to_string().contains("too large"), "{error}");
+
+        // Given a glyph drawing itself
+        let font = composite_chain_font(1, |glyph| glyph);
+        // Then the cycle is rejected
+        let error = render(&font).unwrap_err();
+        assert!(error.to_string().contains("draws itself"), "{error}");
+    }
+}
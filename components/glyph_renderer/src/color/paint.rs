@@ -0,0 +1,581 @@
+//! Evaluation of `COLR` version 1 paint graphs
+//!
+//! Each paint is drawn into its own premultiplied floating point canvas
+//! covering the union of all glyph outlines in the graph, then combined with
+//! its parent. Glyph outlines are scan-converted in Rust under the paint's
+//! transform, whichever backend renders plain glyphs.
+
+use std::collections::HashMap;
+
+use font_parser::limits::{MAX_PAINT_DEPTH, MAX_PAINT_NODES};
+use font_parser::{Affine, Color, ColorLine, ColrTable, CompositeMode, Extend, Paint};
+
+use super::FOREGROUND_INDEX;
+use crate::backend::{glyph_path, GlyphPath};
+use crate::types::{GlyphBitmap, GlyphId, OpenTypeFont, PixelFormat, RenderError, RenderMode};
+
+/// Premultiplied RGBA, each channel from 0 to 1
+type Rgba = [f32; 4];
+
+const TRANSPARENT: Rgba = [0.0; 4];
+
+/// Maximum number of paints visited to render one glyph, across both passes
+/// and every glyph it draws with [`Paint::ColrGlyph`]
+const MAX_PAINT_WORK: usize = 2 * MAX_PAINT_NODES;
+
+/// Paint `paint`, the graph of `glyph_id`, at `scale` pixels per font unit
+/// into a premultiplied [`PixelFormat::Rgba8`] bitmap
+pub(super) fn render(
+    font: &OpenTypeFont,
+    colr: &ColrTable,
+    glyph_id: u16,
+    paint: &Paint,
+    scale: f32,
+    colors: &[Color],
+    foreground: [u8; 4],
+) -> Result<GlyphBitmap, RenderError> {
+    let mut painter = Painter {
+        font,
+        colr,
+        colors,
+        foreground,
+        paths: HashMap::new(),
+        glyphs: vec![glyph_id],
+        work: 0,
+        width: 0,
+        height: 0,
+    };
+
+    // Size the canvas to the outlines, in pixels with y up
+    let pixels = Affine::scale(scale, scale);
+    let mut bounds = None;
+    painter.bounds(paint, &pixels, &mut bounds)?;
+    let Some([x_min, y_min, x_max, y_max]) = bounds else {
+        return Ok(bitmap(0, 0, 0, 0, &[]));
+    };
+    let left = x_min.floor();
+    let top = y_max.ceil();
+    painter.width = (x_max.ceil() - left).max(0.0) as usize;
+    painter.height = (top - y_min.floor()).max(0.0) as usize;
+
+    // Canvas rows run down from `top`
+    let canvas = Affine {
+        yy: -1.0,
+        dx: -left,
+        dy: top,
+        ..Affine::IDENTITY
+    }
+    .multiply(&pixels);
+    let pixels = painter.paint(paint, &canvas)?;
+    Ok(bitmap(
+        painter.width,
+        painter.height,
+        left as i32,
+        top as i32,
+        &pixels,
+    ))
+}
+
+/// Premultiplied 8-bit bitmap from a floating point canvas
+fn bitmap(width: usize, height: usize, left: i32, top: i32, pixels: &[Rgba]) -> GlyphBitmap {
+    let data = pixels
+        .iter()
+        .flat_map(|pixel| pixel.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8))
+        .collect();
+    GlyphBitmap {
+        width: (width * 4) as u32,
+        height: height as u32,
+        left,
+        top,
+        pitch: width * 4,
+        data,
+        format: RenderMode::Gray,
+        pixel_format: PixelFormat::Rgba8,
+    }
+}
+
+struct Painter<'a> {
+    font: &'a OpenTypeFont,
+    colr: &'a ColrTable,
+    colors: &'a [Color],
+    foreground: [u8; 4],
+    /// Outlines by glyph, `None` for glyphs without one
+    paths: HashMap<u16, Option<GlyphPath>>,
+    /// Glyphs being drawn, the outermost first
+    glyphs: Vec<u16>,
+    /// Paints visited so far
+    work: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Painter<'_> {
+    fn path(&mut self, glyph_id: u16) -> Result<Option<&GlyphPath>, RenderError> {
+        if !self.paths.contains_key(&glyph_id) {
+            let path = glyph_path(self.font, GlyphId(glyph_id))?;
+            self.paths.insert(glyph_id, path);
+        }
+        Ok(self.paths[&glyph_id].as_ref())
+    }
+
+    /// Count a visited paint against the glyph's budget
+    ///
+    /// Graphs sharing subgraphs, or drawing the same glyph repeatedly with
+    /// [`Paint::ColrGlyph`], can expand exponentially.
+    fn visit(&mut self) -> Result<(), RenderError> {
+        self.work += 1;
+        if self.work > MAX_PAINT_WORK {
+            return Err(RenderError::RasterizationFailed(
+                "COLR paint graph too large".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Enter the paint graph of a glyph referenced by [`Paint::ColrGlyph`]
+    ///
+    /// On success the glyph stays on the stack of glyphs being drawn until
+    /// [`leave_colr_glyph`](Self::leave_colr_glyph).
+    fn enter_colr_glyph(&mut self, glyph_id: u16) -> Result<Option<Paint>, RenderError> {
+        if self.glyphs.contains(&glyph_id) {
+            return Err(RenderError::RasterizationFailed(format!(
+                "COLR glyph {glyph_id} draws itself"
+            )));
+        }
+        if self.glyphs.len() >= MAX_PAINT_DEPTH {
+            return Err(RenderError::RasterizationFailed(
+                "COLR glyphs nest too deeply".to_string(),
+            ));
+        }
+        let paint = self
+            .colr
+            .paint(glyph_id)
+            .map_err(|e| RenderError::RasterizationFailed(format!("Invalid COLR paint: {e}")))?;
+        if paint.is_some() {
+            self.glyphs.push(glyph_id);
+        }
+        Ok(paint)
+    }
+
+    fn leave_colr_glyph(&mut self) {
+        self.glyphs.pop();
+    }
+
+    /// Union the bounds of the outlines that clip `paint` into `bounds`
+    ///
+    /// Fills outside any glyph would cover the whole plane; they only show
+    /// where other paints' outlines put the canvas.
+    fn bounds(
+        &mut self,
+        paint: &Paint,
+        transform: &Affine,
+        bounds: &mut Option<[f32; 4]>,
+    ) -> Result<(), RenderError> {
+        self.visit()?;
+        match paint {
+            Paint::Layers(layers) => {
+                for layer in layers {
+                    self.bounds(layer, transform, bounds)?;
+                }
+            }
+            Paint::Glyph { glyph_id, .. } => {
+                let glyph_bounds = self
+                    .path(*glyph_id)?
+                    .and_then(|path| path.bounds(|x, y| transform.apply(x, y)));
+                if let Some([x_min, y_min, x_max, y_max]) = glyph_bounds {
+                    *bounds = Some(match *bounds {
+                        Some([a, b, c, d]) => {
+                            [a.min(x_min), b.min(y_min), c.max(x_max), d.max(y_max)]
+                        }
+                        None => [x_min, y_min, x_max, y_max],
+                    });
+                }
+            }
+            Paint::ColrGlyph(glyph_id) => {
+                if let Some(paint) = self.enter_colr_glyph(*glyph_id)? {
+                    let result = self.bounds(&paint, transform, bounds);
+                    self.leave_colr_glyph();
+                    result?;
+                }
+            }
+            Paint::Transform {
+                transform: inner,
+                paint,
+            } => self.bounds(paint, &transform.multiply(inner), bounds)?,
+            Paint::Composite {
+                source, backdrop, ..
+            } => {
+                self.bounds(source, transform, bounds)?;
+                self.bounds(backdrop, transform, bounds)?;
+            }
+            Paint::Solid { .. }
+            | Paint::LinearGradient { .. }
+            | Paint::RadialGradient { .. }
+            | Paint::SweepGradient { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Draw `paint`, mapped to the canvas by `transform`
+    fn paint(&mut self, paint: &Paint, transform: &Affine) -> Result<Vec<Rgba>, RenderError> {
+        self.visit()?;
+        let (width, height) = (self.width, self.height);
+        let size = width * height;
+        let pixels = match paint {
+            Paint::Layers(layers) => {
+                let mut pixels = vec![TRANSPARENT; size];
+                for layer in layers {
+                    let layer = self.paint(layer, transform)?;
+                    for (target, source) in pixels.iter_mut().zip(layer) {
+                        *target = composite(CompositeMode::SrcOver, source, *target);
+                    }
+                }
+                pixels
+            }
+            Paint::Solid {
+                palette_index,
+                alpha,
+            } => vec![self.color(*palette_index, *alpha); size],
+            Paint::LinearGradient {
+                color_line,
+                p0,
+                p1,
+                p2,
+            } => {
+                // The gradient runs from p0 to p1 projected onto the normal
+                // of p0->p2, so its bands are parallel to p0->p2
+                let normal = (p2.1 - p0.1, p0.0 - p2.0);
+                let length = normal.0 * normal.0 + normal.1 * normal.1;
+                let direction = if length > 0.0 {
+                    let along = ((p1.0 - p0.0) * normal.0 + (p1.1 - p0.1) * normal.1) / length;
+                    (normal.0 * along, normal.1 * along)
+                } else {
+                    (p1.0 - p0.0, p1.1 - p0.1)
+                };
+                let length = direction.0 * direction.0 + direction.1 * direction.1;
+                if length <= 0.0 {
+                    return Ok(vec![TRANSPARENT; size]);
+                }
+                self.gradient(color_line, transform, |x, y| {
+                    Some(((x - p0.0) * direction.0 + (y - p0.1) * direction.1) / length)
+                })
+            }
+            Paint::RadialGradient {
+                color_line,
+                c0,
+                r0,
+                c1,
+                r1,
+            } => {
+                let (r0, dr) = (*r0, r1 - r0);
+                let center = (c1.0 - c0.0, c1.1 - c0.1);
+                let a = center.0 * center.0 + center.1 * center.1 - dr * dr;
+                self.gradient(color_line, transform, |x, y| {
+                    // Largest t whose circle, with a radius of at least
+                    // zero, passes through the point
+                    let point = (x - c0.0, y - c0.1);
+                    let b = point.0 * center.0 + point.1 * center.1 + r0 * dr;
+                    let c = point.0 * point.0 + point.1 * point.1 - r0 * r0;
+                    let valid = |t: f32| r0 + t * dr >= 0.0;
+                    if a.abs() < 1e-6 {
+                        let t = c / (2.0 * b);
+                        return (b != 0.0 && valid(t)).then_some(t);
+                    }
+                    let discriminant = b * b - a * c;
+                    if discriminant < 0.0 {
+                        return None;
+                    }
+                    let root = discriminant.sqrt();
+                    let (t0, t1) = ((b + root) / a, (b - root) / a);
+                    let (larger, smaller) = if t0 > t1 { (t0, t1) } else { (t1, t0) };
+                    [larger, smaller].into_iter().find(|&t| valid(t))
+                })
+            }
+            Paint::SweepGradient {
+                color_line,
+                center,
+                start_angle,
+                end_angle,
+            } => {
+                let sweep = end_angle - start_angle;
+                if sweep == 0.0 {
+                    return Ok(vec![TRANSPARENT; size]);
+                }
+                self.gradient(color_line, transform, |x, y| {
+                    let angle = (y - center.1).atan2(x - center.0).to_degrees();
+                    Some((angle.rem_euclid(360.0) - start_angle) / sweep)
+                })
+            }
+            Paint::Glyph { glyph_id, paint } => {
+                let coverage = match self.path(*glyph_id)? {
+                    Some(path) => path.coverage(width, height, |x, y| transform.apply(x, y)),
+                    None => return Ok(vec![TRANSPARENT; size]),
+                };
+                let mut pixels = self.paint(paint, transform)?;
+                for (pixel, coverage) in pixels.iter_mut().zip(coverage) {
+                    *pixel = pixel.map(|channel| channel * coverage);
+                }
+                pixels
+            }
+            Paint::ColrGlyph(glyph_id) => match self.enter_colr_glyph(*glyph_id)? {
+                Some(paint) => {
+                    let pixels = self.paint(&paint, transform);
+                    self.leave_colr_glyph();
+                    pixels?
+                }
+                None => vec![TRANSPARENT; size],
+            },
+            Paint::Transform {
+                transform: inner,
+                paint,
+            } => self.paint(paint, &transform.multiply(inner))?,
+            Paint::Composite {
+                source,
+                mode,
+                backdrop,
+            } => {
+                let mut pixels = self.paint(backdrop, transform)?;
+                let source = self.paint(source, transform)?;
+                for (target, source) in pixels.iter_mut().zip(source) {
+                    *target = composite(*mode, source, *target);
+                }
+                pixels
+            }
+        };
+        Ok(pixels)
+    }
+
+    /// Fill the canvas with `color_line` at the positions `position` gives
+    /// for points in paint coordinates; `None` leaves a pixel transparent
+    fn gradient(
+        &self,
+        color_line: &ColorLine,
+        transform: &Affine,
+        position: impl Fn(f32, f32) -> Option<f32>,
+    ) -> Vec<Rgba> {
+        let size = self.width * self.height;
+        let mut stops: Vec<(f32, Rgba)> = color_line
+            .stops
+            .iter()
+            .map(|stop| (stop.offset, self.color(stop.palette_index, stop.alpha)))
+            .collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let Some(inverse) = transform.invert().filter(|_| !stops.is_empty()) else {
+            return vec![TRANSPARENT; size];
+        };
+
+        let mut pixels = Vec::with_capacity(size);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (x, y) = inverse.apply(x as f32 + 0.5, y as f32 + 0.5);
+                pixels.push(match position(x, y) {
+                    Some(t) if t.is_finite() => color_at(&stops, color_line.extend, t),
+                    _ => TRANSPARENT,
+                });
+            }
+        }
+        pixels
+    }
+
+    /// Premultiplied palette color, with the text color for `0xFFFF`;
+    /// entries missing from the palette are transparent
+    fn color(&self, palette_index: u16, alpha: f32) -> Rgba {
+        let [red, green, blue, base_alpha] = if palette_index == FOREGROUND_INDEX {
+            self.foreground
+        } else {
+            match self.colors.get(palette_index as usize) {
+                Some(color) => [color.red, color.green, color.blue, color.alpha],
+                None => return TRANSPARENT,
+            }
+        };
+        let alpha = (base_alpha as f32 / 255.0 * alpha).clamp(0.0, 1.0);
+        [
+            red as f32 / 255.0 * alpha,
+            green as f32 / 255.0 * alpha,
+            blue as f32 / 255.0 * alpha,
+            alpha,
+        ]
+    }
+}
+
+/// Color of sorted `stops` at position `t`
+fn color_at(stops: &[(f32, Rgba)], extend: Extend, t: f32) -> Rgba {
+    let (first, last) = (stops[0].0, stops[stops.len() - 1].0);
+    let span = last - first;
+    let t = if span > 0.0 {
+        let u = (t - first) / span;
+        let u = match extend {
+            Extend::Pad => u.clamp(0.0, 1.0),
+            Extend::Repeat => u - u.floor(),
+            Extend::Reflect => {
+                let u = u.rem_euclid(2.0);
+                if u > 1.0 {
+                    2.0 - u
+                } else {
+                    u
+                }
+            }
+        };
+        first + u * span
+    } else {
+        t
+    };
+
+    let next = stops.partition_point(|stop| stop.0 <= t);
+    if next == 0 {
+        return stops[0].1;
+    }
+    if next == stops.len() {
+        return stops[stops.len() - 1].1;
+    }
+    let (from, to) = (stops[next - 1], stops[next]);
+    let weight = (t - from.0) / (to.0 - from.0);
+    std::array::from_fn(|i| from.1[i] + (to.1[i] - from.1[i]) * weight)
+}
+
+/// Combine premultiplied `source` with `backdrop`
+fn composite(mode: CompositeMode, source: Rgba, backdrop: Rgba) -> Rgba {
+    use CompositeMode::*;
+
+    let (sa, da) = (source[3], backdrop[3]);
+    let porter_duff =
+        |fs: f32, fd: f32| -> Rgba { std::array::from_fn(|i| source[i] * fs + backdrop[i] * fd) };
+    match mode {
+        Clear => TRANSPARENT,
+        Src => source,
+        Dest => backdrop,
+        SrcOver => porter_duff(1.0, 1.0 - sa),
+        DestOver => porter_duff(1.0 - da, 1.0),
+        SrcIn => porter_duff(da, 0.0),
+        DestIn => porter_duff(0.0, sa),
+        SrcOut => porter_duff(1.0 - da, 0.0),
+        DestOut => porter_duff(0.0, 1.0 - sa),
+        SrcAtop => porter_duff(da, 1.0 - sa),
+        DestAtop => porter_duff(1.0 - da, sa),
+        Xor => porter_duff(1.0 - da, 1.0 - sa),
+        Plus => std::array::from_fn(|i| (source[i] + backdrop[i]).min(1.0)),
+        _ => {
+            let unpremultiply = |color: Rgba| -> [f32; 3] {
+                if color[3] > 0.0 {
+                    [0, 1, 2].map(|i| color[i] / color[3])
+                } else {
+                    [0.0; 3]
+                }
+            };
+            let (cs, cd) = (unpremultiply(source), unpremultiply(backdrop));
+            let blended = blend(mode, cs, cd);
+            let mut result: Rgba = std::array::from_fn(|i| {
+                if i == 3 {
+                    return 0.0;
+                }
+                (1.0 - da) * source[i] + (1.0 - sa) * backdrop[i] + sa * da * blended[i]
+            });
+            result[3] = sa + da - sa * da;
+            result
+        }
+    }
+}
+
+/// Blend function of a separable or non-separable blend mode, on straight
+/// colors
+fn blend(mode: CompositeMode, cs: [f32; 3], cd: [f32; 3]) -> [f32; 3] {
+    use CompositeMode::*;
+
+    let separable = |f: fn(f32, f32) -> f32| [0, 1, 2].map(|i| f(cs[i], cd[i]));
+    match mode {
+        Multiply => separable(|s, d| s * d),
+        Screen => separable(screen),
+        Overlay => separable(|s, d| hard_light(d, s)),
+        Darken => separable(f32::min),
+        Lighten => separable(f32::max),
+        ColorDodge => separable(|s, d| {
+            if d <= 0.0 {
+                0.0
+            } else if s >= 1.0 {
+                1.0
+            } else {
+                (d / (1.0 - s)).min(1.0)
+            }
+        }),
+        ColorBurn => separable(|s, d| {
+            if d >= 1.0 {
+                1.0
+            } else if s <= 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - d) / s).min(1.0)
+            }
+        }),
+        HardLight => separable(hard_light),
+        SoftLight => separable(|s, d| {
+            if s <= 0.5 {
+                d - (1.0 - 2.0 * s) * d * (1.0 - d)
+            } else {
+                let g = if d <= 0.25 {
+                    ((16.0 * d - 12.0) * d + 4.0) * d
+                } else {
+                    d.sqrt()
+                };
+                d + (2.0 * s - 1.0) * (g - d)
+            }
+        }),
+        Difference => separable(|s, d| (s - d).abs()),
+        Exclusion => separable(|s, d| s + d - 2.0 * s * d),
+        Hue => set_luminosity(set_saturation(cs, saturation(cd)), luminosity(cd)),
+        Saturation => set_luminosity(set_saturation(cd, saturation(cs)), luminosity(cd)),
+        Color => set_luminosity(cs, luminosity(cd)),
+        Luminosity => set_luminosity(cd, luminosity(cs)),
+        // Porter-Duff modes never reach here
+        _ => cs,
+    }
+}
+
+fn screen(s: f32, d: f32) -> f32 {
+    s + d - s * d
+}
+
+fn hard_light(s: f32, d: f32) -> f32 {
+    if s <= 0.5 {
+        d * 2.0 * s
+    } else {
+        screen(2.0 * s - 1.0, d)
+    }
+}
+
+fn luminosity([r, g, b]: [f32; 3]) -> f32 {
+    0.3 * r + 0.59 * g + 0.11 * b
+}
+
+fn saturation([r, g, b]: [f32; 3]) -> f32 {
+    r.max(g).max(b) - r.min(g).min(b)
+}
+
+fn set_luminosity(color: [f32; 3], target: f32) -> [f32; 3] {
+    let delta = target - luminosity(color);
+    let color = color.map(|c| c + delta);
+    // Clip back into gamut, keeping the luminosity
+    let l = luminosity(color);
+    let min = color[0].min(color[1]).min(color[2]);
+    let max = color[0].max(color[1]).max(color[2]);
+    color.map(|c| {
+        let c = if min < 0.0 && l - min > 0.0 {
+            l + (c - l) * l / (l - min)
+        } else {
+            c
+        };
+        if max > 1.0 && max - l > 0.0 {
+            l + (c - l) * (1.0 - l) / (max - l)
+        } else {
+            c
+        }
+    })
+}
+
+fn set_saturation(color: [f32; 3], target: f32) -> [f32; 3] {
+    let min = color[0].min(color[1]).min(color[2]);
+    let max = color[0].max(color[1]).max(color[2]);
+    if max - min <= 0.0 {
+        return [0.0; 3];
+    }
+    color.map(|c| (c - min) * target / (max - min))
+}
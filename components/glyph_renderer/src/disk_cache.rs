@@ -0,0 +1,646 @@
+//! Glyph bitmaps persisted across sessions
+//!
+//! A [`DiskGlyphCache`] keeps rasterized bitmaps in a directory, so a new
+//! session draws the glyphs of the previous one without rasterizing them
+//! again. Bitmaps are grouped by strike: one font at one size, DPI and
+//! render mode, rasterized by one backend and version of this crate. Each
+//! strike is a file, read the first time one of its glyphs is looked up
+//! and written back by [`DiskGlyphCache::flush`].
+//!
+//! Files carry a format version, the full strike key and a checksum.
+//! Files that are truncated, corrupt, stale or from another strike are
+//! deleted and count as misses; the cache never fails a rasterization.
+//! After writing, files are deleted until the directory fits the size cap,
+//! starting with strikes this session didn't use, oldest first.
+
+use crate::types::{GlyphBitmap, GlyphId, OpenTypeFont, PixelFormat, RenderMode};
+use font_types::types::FontData;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::SystemTime;
+
+/// Version of the strike file layout; files of other versions are discarded
+pub const FORMAT_VERSION: u32 = 1;
+
+/// First bytes of every strike file
+const MAGIC: &[u8; 4] = b"CGLC";
+
+/// Extension of strike files
+const EXTENSION: &str = "glyphs";
+
+/// Extension of strike files being written
+const TEMP_EXTENSION: &str = "tmp";
+
+/// Default size cap of the cache directory in bytes (32 MB)
+pub const DEFAULT_MAX_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Strike a bitmap belongs to: everything but the glyph ID that decides
+/// its pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct StrikeKey {
+    font_hash: u64,
+    renderer: u64,
+    size: u32, // Size in fixed-point (size * 64)
+    dpi: u32,  // Resolution in fixed-point (dpi * 64)
+    mode: RenderMode,
+}
+
+impl StrikeKey {
+    /// Key for a font identified by `font_hash` (see [`font_hash`])
+    /// rasterized by the backend named `backend`
+    fn new(font_hash: u64, size: f32, dpi: f32, mode: RenderMode, backend: &str) -> Self {
+        let renderer = [backend, env!("CARGO_PKG_VERSION")]
+            .iter()
+            .fold(FNV_OFFSET, |hash, part| fnv1a(hash, part.as_bytes()));
+        Self {
+            font_hash,
+            renderer,
+            size: (size * 64.0) as u32,
+            dpi: (dpi * 64.0) as u32,
+            mode,
+        }
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(25);
+        bytes.extend_from_slice(&self.font_hash.to_le_bytes());
+        bytes.extend_from_slice(&self.renderer.to_le_bytes());
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&self.dpi.to_le_bytes());
+        bytes.push(mode_to_byte(self.mode));
+        bytes
+    }
+
+    fn file_name(self) -> String {
+        format!("{:016x}.{EXTENSION}", fnv1a(FNV_OFFSET, &self.to_bytes()))
+    }
+}
+
+/// Identity of a font's instance: the hash of its data, `data_hash`,
+/// extended with its face index and variation coordinates
+///
+/// Table directory checksums aren't trusted to tell fonts apart, since
+/// edited or subset fonts often keep stale ones.
+fn font_hash(font: &OpenTypeFont, data_hash: u64) -> u64 {
+    let index = u32::try_from(font.face_index()).unwrap_or(0);
+    let mut hash = fnv1a(data_hash, &index.to_le_bytes());
+    for coord in font.variation_coords() {
+        hash = fnv1a(hash, &coord.tag);
+        hash = fnv1a(hash, &coord.value.to_bits().to_le_bytes());
+    }
+    hash
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Disk cache statistics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskCacheStats {
+    /// Lookups answered from disk
+    pub hits: u64,
+    /// Lookups the disk couldn't answer
+    pub misses: u64,
+    /// Strike files deleted as corrupt, stale or belonging to another strike
+    pub discarded_files: u64,
+    /// Strike files deleted to stay under the size cap
+    pub evicted_files: u64,
+    /// Bytes of strike files in the directory after the last flush
+    pub bytes_on_disk: u64,
+}
+
+/// Glyphs of one strike held in memory
+#[derive(Debug, Default)]
+struct Strike {
+    glyphs: HashMap<GlyphId, GlyphBitmap>,
+    /// Glyphs were added since the strike was read or written
+    dirty: bool,
+}
+
+/// Directory of rasterized glyph bitmaps, shared by sessions
+///
+/// Attach one to a renderer with
+/// [`GlyphRenderer::set_disk_cache`](crate::GlyphRenderer::set_disk_cache).
+/// New bitmaps are written by [`flush`](Self::flush), and when the cache
+/// is dropped.
+#[derive(Debug)]
+pub struct DiskGlyphCache {
+    directory: PathBuf,
+    max_bytes: u64,
+    strikes: HashMap<StrikeKey, Strike>,
+    /// Strikes looked up or added this session, kept through size-cap
+    /// eviction in favor of the others
+    used: HashSet<StrikeKey>,
+    /// Hashes of the font data seen so far, by address, so each font's
+    /// bytes are hashed once
+    data_hashes: HashMap<usize, (Weak<[u8]>, u64)>,
+    stats: DiskCacheStats,
+}
+
+impl DiskGlyphCache {
+    /// Open the cache in `directory`, creating it if needed, keeping at
+    /// most `max_bytes` of strike files
+    ///
+    /// Files left behind by an interrupted write are removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the directory can't be created or listed.
+    pub fn open(directory: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        let mut bytes_on_disk = 0;
+        for entry in fs::read_dir(&directory)? {
+            let path = entry?.path();
+            if has_extension(&path, TEMP_EXTENSION) {
+                let _ = fs::remove_file(&path);
+            } else if has_extension(&path, EXTENSION) {
+                bytes_on_disk += fs::metadata(&path).map_or(0, |metadata| metadata.len());
+            }
+        }
+        Ok(Self {
+            directory,
+            max_bytes,
+            strikes: HashMap::new(),
+            used: HashSet::new(),
+            data_hashes: HashMap::new(),
+            stats: DiskCacheStats {
+                bytes_on_disk,
+                ..DiskCacheStats::default()
+            },
+        })
+    }
+
+    /// Directory the strike files are kept in
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Size cap of the strike files in bytes
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// Hits, misses and files deleted so far
+    pub fn stats(&self) -> DiskCacheStats {
+        self.stats
+    }
+
+    /// Strike of `font` rasterized by the backend named `backend`
+    pub(crate) fn strike_key(
+        &mut self,
+        font: &OpenTypeFont,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+        backend: &str,
+    ) -> StrikeKey {
+        let data_hash = self.data_hash(&font.data);
+        StrikeKey::new(font_hash(font, data_hash), size, dpi, mode, backend)
+    }
+
+    /// Hash of all of `data`, computed once per allocation
+    fn data_hash(&mut self, data: &FontData) -> u64 {
+        let address = data.as_ptr() as usize;
+        if let Some((cached, hash)) = self.data_hashes.get(&address) {
+            if cached
+                .upgrade()
+                .is_some_and(|cached| Arc::ptr_eq(&cached, data))
+            {
+                return *hash;
+            }
+        }
+        // Forget fonts that have been dropped
+        self.data_hashes
+            .retain(|_, (cached, _)| cached.strong_count() > 0);
+        let hash = fnv1a(FNV_OFFSET, data);
+        self.data_hashes
+            .insert(address, (Arc::downgrade(data), hash));
+        hash
+    }
+
+    /// Bitmap of `glyph_id` in `strike`, reading the strike file on first use
+    pub(crate) fn get(&mut self, strike: StrikeKey, glyph_id: GlyphId) -> Option<GlyphBitmap> {
+        if !self.strikes.contains_key(&strike) {
+            let loaded = self.read_strike(strike);
+            self.strikes.insert(strike, loaded);
+        }
+        let bitmap = self.strikes[&strike].glyphs.get(&glyph_id).cloned();
+        if bitmap.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        bitmap
+    }
+
+    /// Add a freshly rasterized bitmap, written by the next flush
+    pub(crate) fn insert(&mut self, strike: StrikeKey, glyph_id: GlyphId, bitmap: GlyphBitmap) {
+        let strike = self.strikes.entry(strike).or_default();
+        strike.glyphs.insert(glyph_id, bitmap);
+        strike.dirty = true;
+    }
+
+    /// Write strikes with new glyphs, then delete strike files until the
+    /// directory fits the size cap
+    ///
+    /// Strikes larger than the cap on their own aren't written. The
+    /// strikes held in memory are released; their files are read again
+    /// when next needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first I/O error; strikes not written stay pending for
+    /// the next flush.
+    pub fn flush(&mut self) -> io::Result<()> {
+        for (&key, strike) in &mut self.strikes {
+            if !strike.dirty {
+                continue;
+            }
+            let bytes = encode_strike(key, &strike.glyphs);
+            if bytes.len() as u64 <= self.max_bytes {
+                let path = self.directory.join(key.file_name());
+                let temp = path.with_extension(TEMP_EXTENSION);
+                fs::write(&temp, &bytes)?;
+                fs::rename(&temp, &path)?;
+            }
+            strike.dirty = false;
+        }
+        self.used.extend(self.strikes.keys());
+        self.strikes.clear();
+        self.enforce_size_cap()
+    }
+
+    /// Delete every strike file and forget the strikes read so far
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the directory can't be listed or a file
+    /// can't be deleted.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.strikes.clear();
+        self.used.clear();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if has_extension(&path, EXTENSION) {
+                fs::remove_file(&path)?;
+            }
+        }
+        self.stats.bytes_on_disk = 0;
+        Ok(())
+    }
+
+    /// Glyphs of `key` from its file, deleting the file if it can't be used
+    fn read_strike(&mut self, key: StrikeKey) -> Strike {
+        let path = self.directory.join(key.file_name());
+        let Ok(bytes) = fs::read(&path) else {
+            return Strike::default();
+        };
+        match decode_strike(key, &bytes) {
+            Some(glyphs) => Strike {
+                glyphs,
+                dirty: false,
+            },
+            None => {
+                if fs::remove_file(&path).is_ok() {
+                    self.stats.discarded_files += 1;
+                    self.stats.bytes_on_disk =
+                        self.stats.bytes_on_disk.saturating_sub(bytes.len() as u64);
+                }
+                Strike::default()
+            }
+        }
+    }
+
+    fn enforce_size_cap(&mut self) -> io::Result<()> {
+        let used: HashSet<String> = self.used.iter().map(|key| key.file_name()).collect();
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let entry = entry?;
+            let path = entry.path();
+            if !has_extension(&path, EXTENSION) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((used.contains(&name), modified, metadata.len(), path));
+        }
+
+        // Unused strikes first, then the least recently written
+        files.sort_by_key(|&(used, modified, _, _)| (used, modified));
+        let mut total: u64 = files.iter().map(|&(_, _, len, _)| len).sum();
+        for (_, _, len, path) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            fs::remove_file(&path)?;
+            total -= len;
+            self.stats.evicted_files += 1;
+        }
+        self.stats.bytes_on_disk = total;
+        Ok(())
+    }
+}
+
+impl Drop for DiskGlyphCache {
+    fn drop(&mut self) {
+        // Nothing to report the error to; the glyphs are rasterized again
+        let _ = self.flush();
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|ext| ext == extension)
+}
+
+fn mode_to_byte(mode: RenderMode) -> u8 {
+    match mode {
+        RenderMode::Mono => 0,
+        RenderMode::Gray => 1,
+        RenderMode::SubpixelRgb => 2,
+    }
+}
+
+fn mode_from_byte(byte: u8) -> Option<RenderMode> {
+    match byte {
+        0 => Some(RenderMode::Mono),
+        1 => Some(RenderMode::Gray),
+        2 => Some(RenderMode::SubpixelRgb),
+        _ => None,
+    }
+}
+
+fn pixel_format_to_byte(format: PixelFormat) -> u8 {
+    match format {
+        PixelFormat::A1 => 0,
+        PixelFormat::A8 => 1,
+        PixelFormat::LcdRgb => 2,
+        PixelFormat::Rgba8 => 3,
+    }
+}
+
+fn pixel_format_from_byte(byte: u8) -> Option<PixelFormat> {
+    match byte {
+        0 => Some(PixelFormat::A1),
+        1 => Some(PixelFormat::A8),
+        2 => Some(PixelFormat::LcdRgb),
+        3 => Some(PixelFormat::Rgba8),
+        _ => None,
+    }
+}
+
+/// Strike file: magic, format version, strike key, glyph count, glyphs,
+/// then the FNV-1a checksum of everything before it
+fn encode_strike(key: StrikeKey, glyphs: &HashMap<GlyphId, GlyphBitmap>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&key.to_bytes());
+    bytes.extend_from_slice(&(glyphs.len() as u32).to_le_bytes());
+    for (glyph_id, bitmap) in glyphs {
+        bytes.extend_from_slice(&glyph_id.0.to_le_bytes());
+        bytes.extend_from_slice(&bitmap.width.to_le_bytes());
+        bytes.extend_from_slice(&bitmap.height.to_le_bytes());
+        bytes.extend_from_slice(&bitmap.left.to_le_bytes());
+        bytes.extend_from_slice(&bitmap.top.to_le_bytes());
+        bytes.extend_from_slice(&(bitmap.pitch as u32).to_le_bytes());
+        bytes.push(mode_to_byte(bitmap.format));
+        bytes.push(pixel_format_to_byte(bitmap.pixel_format));
+        bytes.extend_from_slice(&(bitmap.data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&bitmap.data);
+    }
+    let checksum = fnv1a(FNV_OFFSET, &bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    bytes
+}
+
+/// Glyphs of a strike file, or `None` if it isn't a valid file for `key`
+fn decode_strike(key: StrikeKey, bytes: &[u8]) -> Option<HashMap<GlyphId, GlyphBitmap>> {
+    let (body, checksum) = bytes.split_at_checked(bytes.len().checked_sub(8)?)?;
+    if fnv1a(FNV_OFFSET, body).to_le_bytes() != checksum {
+        return None;
+    }
+    let mut reader = Reader(body);
+    if reader.take(MAGIC.len())? != MAGIC
+        || reader.u32()? != FORMAT_VERSION
+        || reader.take(key.to_bytes().len())? != key.to_bytes()
+    {
+        return None;
+    }
+    let count = reader.u32()?;
+    let mut glyphs = HashMap::new();
+    for _ in 0..count {
+        let glyph_id = GlyphId(u16::from_le_bytes(reader.array()?));
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        let left = i32::from_le_bytes(reader.array()?);
+        let top = i32::from_le_bytes(reader.array()?);
+        let pitch = reader.u32()? as usize;
+        let format = mode_from_byte(reader.u8()?)?;
+        let pixel_format = pixel_format_from_byte(reader.u8()?)?;
+        let len = reader.u32()? as usize;
+        let data = reader.take(len)?.to_vec();
+        if data.len() < pitch.checked_mul(height as usize)? {
+            return None;
+        }
+        let bitmap = GlyphBitmap {
+            width,
+            height,
+            left,
+            top,
+            pitch,
+            data,
+            format,
+            pixel_format,
+        };
+        glyphs.insert(glyph_id, bitmap);
+    }
+    reader.0.is_empty().then_some(glyphs)
+}
+
+/// Cursor over the bytes of a strike file
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let (head, rest) = self.0.split_at_checked(len)?;
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.array()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> StrikeKey {
+        StrikeKey {
+            font_hash: 1,
+            renderer: 2,
+            size: 16 * 64,
+            dpi: 72 * 64,
+            mode: RenderMode::Gray,
+        }
+    }
+
+    fn bitmap(value: u8) -> GlyphBitmap {
+        GlyphBitmap {
+            width: 2,
+            height: 2,
+            left: 1,
+            top: 2,
+            pitch: 2,
+            data: vec![value; 4],
+            format: RenderMode::Gray,
+            pixel_format: PixelFormat::A8,
+        }
+    }
+
+    fn temp_cache(name: &str, max_bytes: u64) -> DiskGlyphCache {
+        let directory =
+            std::env::temp_dir().join(format!("glyph_renderer_disk_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        DiskGlyphCache::open(directory, max_bytes).unwrap()
+    }
+
+    #[test]
+    fn test_strike_round_trips_through_encoding() {
+        let glyphs = HashMap::from([(GlyphId(3), bitmap(7)), (GlyphId(9), bitmap(8))]);
+        let decoded = decode_strike(key(), &encode_strike(key(), &glyphs)).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[&GlyphId(9)].data, [8; 4]);
+        assert_eq!(decoded[&GlyphId(3)].top, 2);
+    }
+
+    #[test]
+    fn test_decoding_rejects_damaged_or_foreign_files() {
+        let glyphs = HashMap::from([(GlyphId(3), bitmap(7))]);
+        let bytes = encode_strike(key(), &glyphs);
+
+        let mut flipped = bytes.clone();
+        flipped[30] ^= 1;
+        let other = StrikeKey { size: 0, ..key() };
+        assert!(decode_strike(key(), &flipped).is_none());
+        assert!(decode_strike(key(), &bytes[..bytes.len() - 3]).is_none());
+        assert!(decode_strike(key(), &[]).is_none());
+        assert!(decode_strike(other, &bytes).is_none());
+
+        let oversized = GlyphBitmap {
+            height: u32::MAX,
+            pitch: u32::MAX as usize,
+            ..bitmap(7)
+        };
+        let oversized = HashMap::from([(GlyphId(3), oversized)]);
+        assert!(decode_strike(key(), &encode_strike(key(), &oversized)).is_none());
+    }
+
+    #[test]
+    fn test_strikes_tell_fonts_apart_by_their_bytes() {
+        // Given: DejaVu Sans, and a copy with its last byte changed but the
+        // same table directory
+        let data = fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let mut edited = data.clone();
+        let last = edited.len() - 1;
+        edited[last] ^= 1;
+        let font = OpenTypeFont::from_data(data, 0);
+        let edited = OpenTypeFont::from_data(edited, 0);
+        let mut cache = temp_cache("identity", DEFAULT_MAX_BYTES);
+
+        // When: Keying strikes of both, and of a clone sharing the bytes
+        let strike = |cache: &mut DiskGlyphCache, font| {
+            cache.strike_key(font, 16.0, 72.0, RenderMode::Gray, "test")
+        };
+        let clone = font.clone();
+        let original = strike(&mut cache, &font);
+        let shared = strike(&mut cache, &clone);
+        let changed = strike(&mut cache, &edited);
+
+        // Then: Only the font with the same bytes shares the strike
+        assert_eq!(original, shared);
+        assert_ne!(original, changed);
+        assert_eq!(cache.data_hashes.len(), 2);
+        let _ = fs::remove_dir_all(cache.directory());
+    }
+
+    #[test]
+    fn test_glyphs_persist_and_corrupt_files_are_discarded() {
+        // Given: A glyph flushed to disk by one cache
+        let mut cache = temp_cache("persist", DEFAULT_MAX_BYTES);
+        let directory = cache.directory().to_path_buf();
+        cache.insert(key(), GlyphId(5), bitmap(1));
+        drop(cache);
+
+        // When: Another cache reads it, and the file is then damaged
+        let mut reopened = DiskGlyphCache::open(&directory, DEFAULT_MAX_BYTES).unwrap();
+        let restored = reopened.get(key(), GlyphId(5));
+        let path = directory.join(key().file_name());
+        fs::write(&path, b"CGLC garbage").unwrap();
+        let mut damaged = DiskGlyphCache::open(&directory, DEFAULT_MAX_BYTES).unwrap();
+
+        // Then: The glyph survives the first reopen, and the damaged file
+        // is a miss that gets deleted
+        assert_eq!(restored.unwrap().data, [1; 4]);
+        assert_eq!(reopened.stats().hits, 1);
+        assert!(damaged.get(key(), GlyphId(5)).is_none());
+        assert_eq!(damaged.stats().discarded_files, 1);
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(directory);
+    }
+
+    #[test]
+    fn test_flush_evicts_unused_strikes_over_the_cap() {
+        // Given: A strike written by an earlier session
+        let mut cache = temp_cache("cap", DEFAULT_MAX_BYTES);
+        let directory = cache.directory().to_path_buf();
+        let old = StrikeKey {
+            size: 8 * 64,
+            ..key()
+        };
+        cache.insert(old, GlyphId(1), bitmap(1));
+        cache.flush().unwrap();
+        let file_len = cache.stats().bytes_on_disk;
+        drop(cache);
+
+        // When: A session with room for one file writes another strike
+        let mut cache = DiskGlyphCache::open(&directory, file_len).unwrap();
+        cache.insert(key(), GlyphId(1), bitmap(2));
+        cache.flush().unwrap();
+
+        // Then: The strike it didn't use was deleted to fit the cap
+        assert_eq!(cache.stats().evicted_files, 1);
+        assert_eq!(cache.stats().bytes_on_disk, file_len);
+        assert!(directory.join(key().file_name()).exists());
+        assert!(!directory.join(old.file_name()).exists());
+        cache.clear().unwrap();
+        let _ = fs::remove_dir_all(directory);
+    }
+}
@@ -3,28 +3,54 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+pub mod backend;
+pub mod color;
+pub mod disk_cache;
+pub mod svg;
 pub mod types;
 
-use lru::LruCache;
+use backend::RasterBackend;
+use disk_cache::{DiskGlyphCache, StrikeKey};
+use font_types::{
+    Displaced, EvictionPolicy, FontId, FontPalette, MemoryPressureLevel, MemoryReport,
+    MemoryReporter, MemoryTrimmer, PolicyCache,
+};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use std::num::NonZeroUsize;
+use svg::SvgRasterizer;
 use types::*;
 
-use freetype as ft;
-
 /// Default cache size (number of glyphs)
 const DEFAULT_CACHE_SIZE: usize = 10_000;
 
 /// Default memory limit in bytes (100 MB)
 const DEFAULT_MEMORY_LIMIT_BYTES: usize = 100 * 1024 * 1024;
 
+/// Default outline cache size (number of outlines)
+const DEFAULT_OUTLINE_CACHE_SIZE: usize = 2_000;
+
+/// Default outline cache memory limit in bytes (8 MB)
+const DEFAULT_OUTLINE_MEMORY_LIMIT_BYTES: usize = 8 * 1024 * 1024;
+
+/// Fewest glyphs a worker thread is given, so small batches don't pay for
+/// opening a face on every thread
+#[cfg(feature = "parallel")]
+const MIN_GLYPHS_PER_WORKER: usize = 16;
+
 /// Glyph renderer with caching support
 pub struct GlyphRenderer {
-    cache: GlyphCache,
+    backend: Box<dyn RasterBackend>,
+    svg_rasterizer: Option<Box<dyn SvgRasterizer>>,
+    cache: GlyphCache<CacheKey, GlyphBitmap>,
+    outline_cache: GlyphCache<OutlineKey, GlyphOutline>,
+    disk_cache: Option<DiskGlyphCache>,
     config: CacheConfig,
 }
 
 /// Cache configuration
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CacheConfig {
     /// Maximum number of cached glyphs
     pub max_entries: usize,
@@ -32,6 +58,15 @@ pub struct CacheConfig {
     pub max_memory_bytes: usize,
     /// Enable statistics tracking
     pub enable_statistics: bool,
+    /// Maximum number of cached outlines
+    #[cfg_attr(feature = "serde", serde(default = "default_outline_entries"))]
+    pub max_outline_entries: usize,
+    /// Maximum memory usage of cached outlines in bytes
+    #[cfg_attr(feature = "serde", serde(default = "default_outline_memory_bytes"))]
+    pub max_outline_memory_bytes: usize,
+    /// Which bitmap or outline a full cache evicts
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub eviction_policy: EvictionPolicy,
 }
 
 impl Default for CacheConfig {
@@ -40,24 +75,161 @@ impl Default for CacheConfig {
             max_entries: DEFAULT_CACHE_SIZE,
             max_memory_bytes: DEFAULT_MEMORY_LIMIT_BYTES,
             enable_statistics: true,
+            max_outline_entries: DEFAULT_OUTLINE_CACHE_SIZE,
+            max_outline_memory_bytes: DEFAULT_OUTLINE_MEMORY_LIMIT_BYTES,
+            eviction_policy: EvictionPolicy::Lru,
         }
     }
 }
 
+#[cfg(feature = "serde")]
+fn default_outline_entries() -> usize {
+    DEFAULT_OUTLINE_CACHE_SIZE
+}
+
+#[cfg(feature = "serde")]
+fn default_outline_memory_bytes() -> usize {
+    DEFAULT_OUTLINE_MEMORY_LIMIT_BYTES
+}
+
 /// Glyph cache key
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct CacheKey {
+    font: FontKey,
     glyph_id: GlyphId,
     size: u32, // Size in fixed-point (size * 64)
+    dpi: u32,  // Resolution in fixed-point (dpi * 64)
     mode: RenderMode,
+    stroke: Option<StrokeKey>, // None for plain fills
+    color: Option<ColorKey>,   // None for coverage bitmaps
+}
+
+/// Font instance a cached bitmap was rasterized from
+///
+/// Fonts identified by [`OpenTypeFont::with_font_id`] are keyed by that ID,
+/// others by the address and length of their data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FontKey {
+    source: FontSource,
+    face_index: isize,
+    variation_coords: Vec<([u8; 4], i32)>, // Values in fixed-point (value * 64)
+}
+
+/// What identifies a font in a [`FontKey`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FontSource {
+    Id(FontId),
+    Data { address: usize, len: usize },
+}
+
+impl FontKey {
+    fn new(font: &OpenTypeFont) -> Self {
+        Self {
+            source: match font.font_id() {
+                Some(font_id) => FontSource::Id(font_id),
+                None => FontSource::Data {
+                    address: font.data.as_ptr() as usize,
+                    len: font.data.len(),
+                },
+            },
+            face_index: font.face_index(),
+            variation_coords: variation_key(font),
+        }
+    }
 }
 
-/// Internal glyph cache with LRU eviction
-struct GlyphCache {
-    entries: LruCache<CacheKey, GlyphBitmap>,
+/// Hashable form of a font's variation coordinates
+fn variation_key(font: &OpenTypeFont) -> Vec<([u8; 4], i32)> {
+    font.variation_coords()
+        .iter()
+        .map(|coord| (coord.tag, (coord.value * 64.0) as i32))
+        .collect()
+}
+
+/// Colors a color glyph bitmap was composited with
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ColorKey {
+    palette: FontPalette,
+    foreground: [u8; 4],
+}
+
+/// Hashable form of [`StrokeOptions`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct StrokeKey {
+    width: u32,       // Width in fixed-point (width * 64)
+    miter_limit: u32, // Limit in fixed-point (limit * 64)
+    line_join: LineJoin,
+    line_cap: LineCap,
+    output: StrokeOutput,
+}
+
+impl From<&StrokeOptions> for StrokeKey {
+    fn from(stroke: &StrokeOptions) -> Self {
+        Self {
+            width: (stroke.width * 64.0) as u32,
+            miter_limit: (stroke.miter_limit * 64.0) as u32,
+            line_join: stroke.line_join,
+            line_cap: stroke.line_cap,
+            output: stroke.output,
+        }
+    }
+}
+
+/// Outline cache key
+///
+/// Outlines don't depend on the render mode or DPI, only on the font
+/// instance and the coordinate space.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct OutlineKey {
+    font_id: FontId,
+    glyph_id: GlyphId,
+    variation_coords: Vec<([u8; 4], i32)>, // Values in fixed-point (value * 64)
+    ppem: Option<u32>,                     // Fixed-point (ppem * 64); None for font units
+}
+
+impl OutlineKey {
+    /// Key for a font identified by [`OpenTypeFont::with_font_id`]
+    fn new(font: &OpenTypeFont, glyph_id: GlyphId, scale: OutlineScale) -> Option<Self> {
+        Some(Self {
+            font_id: font.font_id()?,
+            glyph_id,
+            variation_coords: variation_key(font),
+            ppem: match scale {
+                OutlineScale::Ppem(ppem) => Some((ppem * 64.0) as u32),
+                OutlineScale::FontUnits => None,
+            },
+        })
+    }
+}
+
+/// Bytes a cached value accounts for against the memory limit
+trait CacheWeight {
+    fn weight(&self) -> usize;
+}
+
+impl CacheWeight for GlyphBitmap {
+    fn weight(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl CacheWeight for GlyphOutline {
+    fn weight(&self) -> usize {
+        let points: usize = self.contours.iter().map(|c| c.points.len()).sum();
+        std::mem::size_of::<GlyphOutline>()
+            + self.contours.len() * std::mem::size_of::<Contour>()
+            + points * std::mem::size_of::<Point>()
+    }
+}
+
+/// Internal glyph cache, evicting by the configured policy
+struct GlyphCache<K, V> {
+    entries: PolicyCache<K, V>,
     stats: CacheStatistics,
     memory_bytes: usize,
     max_memory_bytes: usize,
+    #[cfg(feature = "tracing")]
+    name: &'static str,
 }
 
 /// Cache statistics
@@ -68,53 +240,64 @@ struct CacheStatistics {
     evictions: u64,
 }
 
-impl GlyphCache {
-    fn new(max_entries: usize, max_memory_bytes: usize) -> Self {
+impl<K: Hash + Eq, V: CacheWeight> GlyphCache<K, V> {
+    fn new(
+        _name: &'static str,
+        max_entries: usize,
+        max_memory_bytes: usize,
+        policy: EvictionPolicy,
+    ) -> Self {
         Self {
-            entries: LruCache::new(NonZeroUsize::new(max_entries).unwrap()),
+            entries: PolicyCache::new(NonZeroUsize::new(max_entries).unwrap(), policy),
             stats: CacheStatistics::default(),
             memory_bytes: 0,
             max_memory_bytes,
+            #[cfg(feature = "tracing")]
+            name: _name,
         }
     }
 
-    fn get(&mut self, key: &CacheKey) -> Option<&GlyphBitmap> {
-        if let Some(bitmap) = self.entries.get(key) {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let value = self.entries.get(key);
+        if value.is_some() {
             self.stats.hits += 1;
-            Some(bitmap)
         } else {
             self.stats.misses += 1;
-            None
         }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(cache = self.name, hit = value.is_some());
+        value
     }
 
-    fn insert(&mut self, key: CacheKey, bitmap: GlyphBitmap) {
-        let bitmap_size = bitmap.data.len();
+    fn insert(&mut self, key: K, value: V) {
+        let value_size = value.weight();
 
         // Check if adding this entry would exceed memory limit
-        if self.memory_bytes + bitmap_size > self.max_memory_bytes {
+        if self.memory_bytes + value_size > self.max_memory_bytes {
             // Evict entries until we have space
-            self.evict_to_fit(bitmap_size);
+            self.evict_to_fit(value_size);
         }
 
         // Insert into cache
-        if let Some((_, evicted_bitmap)) = self.entries.push(key, bitmap) {
-            // An entry was evicted by LRU
-            self.memory_bytes -= evicted_bitmap.data.len();
-            self.stats.evictions += 1;
+        if let Some(displaced) = self.entries.push(key, value, value_size) {
+            // An entry was replaced or evicted by the policy
+            if matches!(displaced, Displaced::Evicted(..)) {
+                self.stats.evictions += 1;
+            }
+            self.memory_bytes -= displaced.into_value().weight();
         }
 
-        self.memory_bytes += bitmap_size;
+        self.memory_bytes += value_size;
     }
 
     fn evict_to_fit(&mut self, required_bytes: usize) {
         // Calculate target memory after eviction
         let target_memory = self.max_memory_bytes.saturating_sub(required_bytes);
 
-        // Evict oldest entries until we're under target
+        // Evict entries in policy order until we're under target
         while self.memory_bytes > target_memory && !self.entries.is_empty() {
-            if let Some((_, bitmap)) = self.entries.pop_lru() {
-                self.memory_bytes -= bitmap.data.len();
+            if let Some((_, evicted)) = self.entries.pop() {
+                self.memory_bytes -= evicted.weight();
                 self.stats.evictions += 1;
             } else {
                 break;
@@ -122,6 +305,20 @@ impl GlyphCache {
         }
     }
 
+    /// Evict entries in policy order until at most `keep` remain,
+    /// returning the bytes freed
+    fn trim(&mut self, keep: usize) -> usize {
+        let before = self.memory_bytes;
+        while self.entries.len() > keep {
+            let Some((_, evicted)) = self.entries.pop() else {
+                break;
+            };
+            self.memory_bytes -= evicted.weight();
+            self.stats.evictions += 1;
+        }
+        before - self.memory_bytes
+    }
+
     fn clear(&mut self) {
         self.entries.clear();
         self.memory_bytes = 0;
@@ -147,24 +344,6 @@ impl GlyphCache {
     }
 }
 
-/// Convert RenderMode to FreeType render mode
-fn to_freetype_render_mode(mode: RenderMode) -> ft::RenderMode {
-    match mode {
-        RenderMode::Mono => ft::RenderMode::Mono,
-        RenderMode::Gray => ft::RenderMode::Normal,
-        RenderMode::SubpixelRgb => ft::RenderMode::Lcd,
-    }
-}
-
-/// Convert FreeType load flags from RenderMode
-fn get_load_flags(mode: RenderMode) -> ft::face::LoadFlag {
-    match mode {
-        RenderMode::Mono => ft::face::LoadFlag::MONOCHROME,
-        RenderMode::Gray => ft::face::LoadFlag::DEFAULT,
-        RenderMode::SubpixelRgb => ft::face::LoadFlag::DEFAULT,
-    }
-}
-
 impl GlyphRenderer {
     /// Create a new glyph renderer with default configuration
     pub fn new() -> Self {
@@ -173,13 +352,90 @@ impl GlyphRenderer {
 
     /// Create a new glyph renderer with custom configuration
     pub fn with_config(config: CacheConfig) -> Self {
+        Self::with_backend(config, backend::default_backend())
+    }
+
+    /// Create a new glyph renderer that rasterizes with `backend`
+    pub fn with_backend(config: CacheConfig, backend: Box<dyn RasterBackend>) -> Self {
         Self {
-            cache: GlyphCache::new(config.max_entries, config.max_memory_bytes),
+            backend,
+            svg_rasterizer: None,
+            cache: GlyphCache::new(
+                "glyph",
+                config.max_entries,
+                config.max_memory_bytes,
+                config.eviction_policy,
+            ),
+            outline_cache: GlyphCache::new(
+                "outline",
+                config.max_outline_entries.max(1),
+                config.max_outline_memory_bytes,
+                config.eviction_policy,
+            ),
+            disk_cache: None,
             config,
         }
     }
 
-    /// Rasterize a glyph to bitmap
+    /// Rasterization backend in use
+    pub fn backend(&self) -> &dyn RasterBackend {
+        self.backend.as_ref()
+    }
+
+    /// Cache configuration the renderer was created with
+    pub fn cache_config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Render SVG-in-OpenType glyphs with `rasterizer`
+    ///
+    /// Without one, [`rasterize_color_glyph`](Self::rasterize_color_glyph)
+    /// returns `None` for glyphs that only have SVG documents. Clears the
+    /// glyph cache.
+    pub fn set_svg_rasterizer(&mut self, rasterizer: Box<dyn SvgRasterizer>) {
+        self.svg_rasterizer = Some(rasterizer);
+        self.cache.clear();
+    }
+
+    /// Keep plain glyph bitmaps in `cache` across sessions
+    ///
+    /// Bitmaps missing from the in-memory cache are looked up on disk
+    /// before rasterizing, and new ones are added to the disk cache.
+    /// Stroked and color glyphs aren't persisted.
+    pub fn set_disk_cache(&mut self, cache: DiskGlyphCache) {
+        self.disk_cache = Some(cache);
+    }
+
+    /// Disk cache in use, if any
+    pub fn disk_cache(&self) -> Option<&DiskGlyphCache> {
+        self.disk_cache.as_ref()
+    }
+
+    /// Detach the disk cache, e.g. to flush it and report errors
+    pub fn take_disk_cache(&mut self) -> Option<DiskGlyphCache> {
+        self.disk_cache.take()
+    }
+
+    /// Write bitmaps rasterized since the last flush to the disk cache
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error from [`DiskGlyphCache::flush`].
+    pub fn flush_disk_cache(&mut self) -> std::io::Result<()> {
+        self.disk_cache
+            .as_mut()
+            .map_or(Ok(()), DiskGlyphCache::flush)
+    }
+
+    /// SVG rasterizer in use, if any
+    pub fn svg_rasterizer(&self) -> Option<&dyn SvgRasterizer> {
+        self.svg_rasterizer.as_deref()
+    }
+
+    /// Rasterize a glyph to bitmap at `size` pixels per em
+    ///
+    /// Equivalent to [`rasterize_glyph_at_dpi`](Self::rasterize_glyph_at_dpi)
+    /// at [`DEFAULT_DPI`], where points and pixels coincide.
     pub fn rasterize_glyph(
         &mut self,
         font: &OpenTypeFont,
@@ -187,12 +443,43 @@ impl GlyphRenderer {
         size: f32,
         mode: RenderMode,
     ) -> Result<GlyphBitmap, RenderError> {
+        self.rasterize_glyph_at_dpi(font, glyph_id, size, DEFAULT_DPI, mode)
+    }
+
+    /// Rasterize a glyph to bitmap at `size` points and `dpi`
+    ///
+    /// The glyph covers `size * dpi / 72` pixels per em, so a 12pt glyph at
+    /// 96 DPI is 16 pixels; hinting sees the same resolution as platform
+    /// renderers. Bitmaps are cached per font, size and DPI.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "glyph_renderer::rasterize_glyph",
+            level = "debug",
+            skip_all,
+            fields(glyph_id = glyph_id.0, size, dpi, backend = self.backend.name())
+        )
+    )]
+    pub fn rasterize_glyph_at_dpi(
+        &mut self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Result<GlyphBitmap, RenderError> {
+        check_dpi(dpi)?;
+
         // Create cache key
         let size_fixed = (size * 64.0) as u32; // Convert to fixed-point (26.6)
         let cache_key = CacheKey {
+            font: FontKey::new(font),
             glyph_id,
             size: size_fixed,
+            dpi: (dpi * 64.0) as u32,
             mode,
+            stroke: None,
+            color: None,
         };
 
         // Check cache first
@@ -207,8 +494,16 @@ impl GlyphRenderer {
             ));
         }
 
-        // Rasterize using FreeType
-        let bitmap = self.rasterize_with_freetype(font, glyph_id, size, mode)?;
+        // Then the bitmaps of earlier sessions
+        let strike = self.strike_key(font, size, dpi, mode);
+        let bitmap = match self.disk_lookup(strike, glyph_id) {
+            Some(bitmap) => bitmap,
+            None => {
+                let bitmap = self.backend.rasterize(font, glyph_id, size, dpi, mode)?;
+                self.disk_insert(strike, glyph_id, &bitmap);
+                bitmap
+            }
+        };
 
         // Store in cache
         self.cache.insert(cache_key, bitmap.clone());
@@ -216,82 +511,272 @@ impl GlyphRenderer {
         Ok(bitmap)
     }
 
-    /// Internal method to rasterize using FreeType
-    fn rasterize_with_freetype(
-        &self,
+    /// Rasterize glyphs of one font at `size` points and `dpi`
+    ///
+    /// Returns one result per glyph, in order, as
+    /// [`rasterize_glyph_at_dpi`](Self::rasterize_glyph_at_dpi) would. Cached
+    /// bitmaps are returned as is; the other glyphs are rasterized once each,
+    /// however often they repeat, and added to the cache. With the `parallel`
+    /// feature they are split into one batch per worker thread of the rayon
+    /// pool, and each worker opens its own backend face (see
+    /// [`RasterBackend::rasterize_batch`]); without it they form a single
+    /// batch on the calling thread.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "glyph_renderer::rasterize_glyphs",
+            level = "debug",
+            skip_all,
+            fields(glyphs = glyph_ids.len(), size, dpi, backend = self.backend.name())
+        )
+    )]
+    pub fn rasterize_glyphs(
+        &mut self,
+        font: &OpenTypeFont,
+        glyph_ids: &[GlyphId],
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Vec<Result<GlyphBitmap, RenderError>> {
+        if let Err(error) = check_dpi(dpi) {
+            return vec![Err(error); glyph_ids.len()];
+        }
+
+        let font_key = FontKey::new(font);
+        let cache_key = |glyph_id| CacheKey {
+            font: font_key.clone(),
+            glyph_id,
+            size: (size * 64.0) as u32,
+            dpi: (dpi * 64.0) as u32,
+            mode,
+            stroke: None,
+            color: None,
+        };
+        let strike = if font.data.is_empty() {
+            None
+        } else {
+            self.strike_key(font, size, dpi, mode)
+        };
+        let cached: Vec<Option<GlyphBitmap>> = glyph_ids
+            .iter()
+            .map(|&glyph_id| {
+                if let Some(bitmap) = self.cache.get(&cache_key(glyph_id)) {
+                    return Some(bitmap.clone());
+                }
+                let bitmap = self.disk_lookup(strike, glyph_id)?;
+                self.cache.insert(cache_key(glyph_id), bitmap.clone());
+                Some(bitmap)
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        let missing: Vec<GlyphId> = glyph_ids
+            .iter()
+            .zip(&cached)
+            .filter(|(&glyph_id, bitmap)| bitmap.is_none() && seen.insert(glyph_id))
+            .map(|(&glyph_id, _)| glyph_id)
+            .collect();
+        let rendered = if font.data.is_empty() {
+            let error =
+                RenderError::RasterizationFailed("Font has no data (stub font)".to_string());
+            vec![Err(error); missing.len()]
+        } else {
+            rasterize_batches(self.backend.as_ref(), font, &missing, size, dpi, mode)
+        };
+
+        for (&glyph_id, result) in missing.iter().zip(&rendered) {
+            if let Ok(bitmap) = result {
+                self.cache.insert(cache_key(glyph_id), bitmap.clone());
+                self.disk_insert(strike, glyph_id, bitmap);
+            }
+        }
+        let rendered: HashMap<GlyphId, Result<GlyphBitmap, RenderError>> =
+            missing.into_iter().zip(rendered).collect();
+        glyph_ids
+            .iter()
+            .zip(cached)
+            .map(|(glyph_id, bitmap)| match bitmap {
+                Some(bitmap) => Ok(bitmap),
+                None => rendered[glyph_id].clone(),
+            })
+            .collect()
+    }
+
+    /// Rasterize a stroked glyph (`-webkit-text-stroke`)
+    ///
+    /// Depending on `stroke.output` the bitmap holds the stroke alone or the
+    /// stroke combined with the fill. Results are cached separately from
+    /// plain fills.
+    pub fn rasterize_stroked_glyph(
+        &mut self,
         font: &OpenTypeFont,
         glyph_id: GlyphId,
         size: f32,
         mode: RenderMode,
+        stroke: &StrokeOptions,
     ) -> Result<GlyphBitmap, RenderError> {
-        // Initialize FreeType library
-        let library = ft::Library::init().map_err(|e| {
-            RenderError::RasterizationFailed(format!("Failed to initialize FreeType: {:?}", e))
-        })?;
-
-        // Load font face from memory
-        // freetype-rs requires Rc<Vec<u8>>
-        let font_data = std::rc::Rc::new(font.data.clone());
-        let face = library
-            .new_memory_face(font_data, font.face_index)
-            .map_err(|e| {
-                RenderError::RasterizationFailed(format!("Failed to load font face: {:?}", e))
-            })?;
-
-        // Set character size (size in points * 64, DPI = 72)
-        let size_26dot6 = (size * 64.0) as isize;
-        face.set_char_size(size_26dot6, 0, 72, 72).map_err(|e| {
-            RenderError::RasterizationFailed(format!("Failed to set char size: {:?}", e))
-        })?;
-
-        // Load glyph
-        let load_flags = get_load_flags(mode);
-        face.load_glyph(glyph_id.0 as u32, load_flags)
-            .map_err(|e| {
-                if matches!(e, ft::Error::InvalidGlyphIndex) {
-                    RenderError::GlyphNotFound(glyph_id)
-                } else {
-                    RenderError::RasterizationFailed(format!("Failed to load glyph: {:?}", e))
-                }
-            })?;
-
-        // Render glyph to bitmap
-        let ft_render_mode = to_freetype_render_mode(mode);
-        face.glyph().render_glyph(ft_render_mode).map_err(|e| {
-            RenderError::RasterizationFailed(format!("Failed to render glyph: {:?}", e))
-        })?;
-
-        // Extract bitmap data
-        let ft_bitmap = face.glyph().bitmap();
-        let width = ft_bitmap.width() as u32;
-        let height = ft_bitmap.rows() as u32;
-        let pitch = ft_bitmap.pitch().unsigned_abs() as usize;
-
-        // Copy bitmap data
-        let buffer = ft_bitmap.buffer();
-        let data = buffer.to_vec();
-
-        // Extract metrics
-        let metrics = face.glyph().metrics();
-        let bearing_x = metrics.horiBearingX / 64; // Convert from 26.6 fixed-point
-        let bearing_y = metrics.horiBearingY / 64;
-
-        Ok(GlyphBitmap {
-            width,
-            height,
-            left: bearing_x as i32,
-            top: bearing_y as i32,
-            pitch,
-            data,
-            format: mode,
-        })
+        self.rasterize_stroked_glyph_at_dpi(font, glyph_id, size, DEFAULT_DPI, mode, stroke)
+    }
+
+    /// Rasterize a stroked glyph at `size` points and `dpi`
+    ///
+    /// The stroke width scales with the DPI like the font size.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "glyph_renderer::rasterize_stroked_glyph",
+            level = "debug",
+            skip_all,
+            fields(glyph_id = glyph_id.0, size, dpi, backend = self.backend.name())
+        )
+    )]
+    pub fn rasterize_stroked_glyph_at_dpi(
+        &mut self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+        stroke: &StrokeOptions,
+    ) -> Result<GlyphBitmap, RenderError> {
+        check_dpi(dpi)?;
+
+        let cache_key = CacheKey {
+            font: FontKey::new(font),
+            glyph_id,
+            size: (size * 64.0) as u32,
+            dpi: (dpi * 64.0) as u32,
+            mode,
+            stroke: Some(StrokeKey::from(stroke)),
+            color: None,
+        };
+
+        if let Some(bitmap) = self.cache.get(&cache_key) {
+            return Ok(bitmap.clone());
+        }
+
+        if font.data.is_empty() {
+            return Err(RenderError::RasterizationFailed(
+                "Font has no data (stub font)".to_string(),
+            ));
+        }
+
+        let bitmap = self
+            .backend
+            .rasterize_stroked(font, glyph_id, size, dpi, mode, stroke)?;
+        self.cache.insert(cache_key, bitmap.clone());
+
+        Ok(bitmap)
+    }
+
+    /// Rasterize a `COLR` or SVG color glyph at `size` pixels per em
+    ///
+    /// Equivalent to
+    /// [`rasterize_color_glyph_at_dpi`](Self::rasterize_color_glyph_at_dpi)
+    /// at [`DEFAULT_DPI`].
+    pub fn rasterize_color_glyph(
+        &mut self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        palette: &FontPalette,
+        foreground: [u8; 4],
+    ) -> Result<Option<GlyphBitmap>, RenderError> {
+        self.rasterize_color_glyph_at_dpi(font, glyph_id, size, DEFAULT_DPI, palette, foreground)
+    }
+
+    /// Rasterize a `COLR` or SVG color glyph at `size` points and `dpi`
+    ///
+    /// Returns a premultiplied [`PixelFormat::Rgba8`] bitmap with the colors
+    /// of `palette`, and the straight RGBA `foreground` for layers drawn in
+    /// the text color, or `None` if the glyph has no color layers (see
+    /// [`color::rasterize_color_glyph`]). Glyphs without layers are drawn
+    /// from their SVG document if an [`SvgRasterizer`] is set. Bitmaps are
+    /// cached per palette, including its overrides, and foreground color.
+    pub fn rasterize_color_glyph_at_dpi(
+        &mut self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+        dpi: f32,
+        palette: &FontPalette,
+        foreground: [u8; 4],
+    ) -> Result<Option<GlyphBitmap>, RenderError> {
+        check_dpi(dpi)?;
+
+        let cache_key = CacheKey {
+            font: FontKey::new(font),
+            glyph_id,
+            size: (size * 64.0) as u32,
+            dpi: (dpi * 64.0) as u32,
+            mode: RenderMode::Gray,
+            stroke: None,
+            color: Some(ColorKey {
+                palette: palette.clone(),
+                foreground,
+            }),
+        };
+
+        if let Some(bitmap) = self.cache.get(&cache_key) {
+            return Ok(Some(bitmap.clone()));
+        }
+
+        if font.data.is_empty() {
+            return Err(RenderError::RasterizationFailed(
+                "Font has no data (stub font)".to_string(),
+            ));
+        }
+
+        let bitmap = color::rasterize_color_glyph(
+            self.backend.as_ref(),
+            font,
+            glyph_id,
+            size,
+            dpi,
+            palette,
+            foreground,
+        )?;
+        let bitmap = match (bitmap, &self.svg_rasterizer) {
+            (None, Some(rasterizer)) => svg::rasterize_svg_glyph(
+                rasterizer.as_ref(),
+                font,
+                glyph_id,
+                size,
+                dpi,
+                foreground,
+            )?,
+            (bitmap, _) => bitmap,
+        };
+        if let Some(bitmap) = &bitmap {
+            self.cache.insert(cache_key, bitmap.clone());
+        }
+
+        Ok(bitmap)
     }
 
-    /// Get glyph vector outline
+    /// Get glyph vector outline in pixels at [`DEFAULT_OUTLINE_PPEM`]
     pub fn get_glyph_outline(
-        &self,
+        &mut self,
         font: &OpenTypeFont,
         glyph_id: GlyphId,
+    ) -> Result<GlyphOutline, RenderError> {
+        self.get_glyph_outline_scaled(font, glyph_id, OutlineScale::default())
+    }
+
+    /// Get glyph vector outline at a size or in font units
+    ///
+    /// [`OutlineScale::FontUnits`] returns the outline as designed, unscaled
+    /// and unhinted, with the font's `units_per_em` to scale it, e.g. for
+    /// SVG or PDF export.
+    ///
+    /// Outlines of fonts identified with [`OpenTypeFont::with_font_id`] are
+    /// cached per font, variation coordinates, glyph and scale, separately
+    /// from bitmaps (see [`outline_cache_stats`](Self::outline_cache_stats)).
+    pub fn get_glyph_outline_scaled(
+        &mut self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        scale: OutlineScale,
     ) -> Result<GlyphOutline, RenderError> {
         // Check if font has data
         if font.data.is_empty() {
@@ -299,112 +784,125 @@ impl GlyphRenderer {
                 "Font has no data (stub font)".to_string(),
             ));
         }
-
-        // Initialize FreeType library
-        let library = ft::Library::init().map_err(|e| {
-            RenderError::RasterizationFailed(format!("Failed to initialize FreeType: {:?}", e))
-        })?;
-
-        // Load font face from memory
-        let font_data = std::rc::Rc::new(font.data.clone());
-        let face = library
-            .new_memory_face(font_data, font.face_index)
-            .map_err(|e| {
-                RenderError::RasterizationFailed(format!("Failed to load font face: {:?}", e))
-            })?;
-
-        // Set a default size for outline extraction
-        face.set_char_size(16 * 64, 0, 72, 72).map_err(|e| {
-            RenderError::RasterizationFailed(format!("Failed to set char size: {:?}", e))
-        })?;
-
-        // Load glyph without rendering
-        face.load_glyph(glyph_id.0 as u32, ft::face::LoadFlag::NO_BITMAP)
-            .map_err(|e| {
-                if matches!(e, ft::Error::InvalidGlyphIndex) {
-                    RenderError::GlyphNotFound(glyph_id)
-                } else {
-                    RenderError::RasterizationFailed(format!("Failed to load glyph: {:?}", e))
-                }
-            })?;
-
-        // Get outline
-        let glyph = face.glyph();
-        let ft_outline = glyph
-            .outline()
-            .ok_or_else(|| RenderError::RasterizationFailed("Glyph has no outline".to_string()))?;
-
-        // Extract points and contours
-        let points_vec = ft_outline.points();
-        let contours_vec = ft_outline.contours();
-
-        // Calculate bounding box manually from points
-        let mut min_x = i64::MAX;
-        let mut min_y = i64::MAX;
-        let mut max_x = i64::MIN;
-        let mut max_y = i64::MIN;
-
-        for point in points_vec {
-            min_x = min_x.min(point.x);
-            max_x = max_x.max(point.x);
-            min_y = min_y.min(point.y);
-            max_y = max_y.max(point.y);
-        }
-
-        let bounds = if points_vec.is_empty() {
-            BoundingBox {
-                min_x: 0.0,
-                min_y: 0.0,
-                max_x: 0.0,
-                max_y: 0.0,
-            }
-        } else {
-            BoundingBox {
-                min_x: (min_x as f32) / 64.0,
-                min_y: (min_y as f32) / 64.0,
-                max_x: (max_x as f32) / 64.0,
-                max_y: (max_y as f32) / 64.0,
+        if let OutlineScale::Ppem(ppem) = scale {
+            if !(ppem.is_finite() && ppem > 0.0) {
+                return Err(RenderError::RasterizationFailed(format!(
+                    "Invalid pixels per em: {ppem}"
+                )));
             }
-        };
+        }
 
-        // Extract contours
-        let mut contours = Vec::new();
-        let mut start_idx = 0;
-
-        for &contour_end in contours_vec {
-            let end_idx = contour_end as usize + 1;
-
-            // Extract points for this contour
-            let points: Vec<Point> = points_vec[start_idx..end_idx.min(points_vec.len())]
-                .iter()
-                .map(|point| Point {
-                    x: (point.x as f32) / 64.0,
-                    y: (point.y as f32) / 64.0,
-                })
-                .collect();
-
-            if !points.is_empty() {
-                contours.push(Contour {
-                    points,
-                    closed: true,
-                });
-            }
+        let key = OutlineKey::new(font, glyph_id, scale);
+        if let Some(outline) = key.as_ref().and_then(|key| self.outline_cache.get(key)) {
+            return Ok(outline.clone());
+        }
 
-            start_idx = end_idx;
+        let outline = self.backend.outline(font, glyph_id, scale)?;
+        if let Some(key) = key {
+            self.outline_cache.insert(key, outline.clone());
         }
+        Ok(outline)
+    }
 
-        Ok(GlyphOutline { contours, bounds })
+    /// Get a glyph's ink bounds at `size` pixels per em without rasterizing
+    ///
+    /// Bounds are relative to the glyph origin with y up and come from the
+    /// unhinted outline, so they may differ slightly from a rasterized
+    /// bitmap's box. Returns `None` for glyphs without ink (e.g. space).
+    pub fn glyph_extents(
+        &self,
+        font: &OpenTypeFont,
+        glyph_id: GlyphId,
+        size: f32,
+    ) -> Result<Option<BoundingBox>, RenderError> {
+        if font.data.is_empty() {
+            return Err(RenderError::RasterizationFailed(
+                "Font has no data (stub font)".to_string(),
+            ));
+        }
+
+        backend::extents(font, glyph_id, size)
     }
 
-    /// Clear the glyph cache
+    /// Clear the glyph and outline caches
     pub fn clear_cache(&mut self) {
         self.cache.clear();
+        self.outline_cache.clear();
+    }
+
+    /// Disk cache strike of plain bitmaps of `font`, if a disk cache is set
+    fn strike_key(
+        &mut self,
+        font: &OpenTypeFont,
+        size: f32,
+        dpi: f32,
+        mode: RenderMode,
+    ) -> Option<StrikeKey> {
+        let backend = self.backend.name();
+        Some(
+            self.disk_cache
+                .as_mut()?
+                .strike_key(font, size, dpi, mode, backend),
+        )
+    }
+
+    fn disk_lookup(&mut self, strike: Option<StrikeKey>, glyph_id: GlyphId) -> Option<GlyphBitmap> {
+        self.disk_cache.as_mut()?.get(strike?, glyph_id)
+    }
+
+    fn disk_insert(&mut self, strike: Option<StrikeKey>, glyph_id: GlyphId, bitmap: &GlyphBitmap) {
+        if let (Some(cache), Some(strike)) = (&mut self.disk_cache, strike) {
+            cache.insert(strike, glyph_id, bitmap.clone());
+        }
     }
 
     /// Get cache statistics
     pub fn cache_stats(&self) -> CacheStats {
         self.cache.get_stats()
     }
+
+    /// Get outline cache statistics
+    pub fn outline_cache_stats(&self) -> CacheStats {
+        self.outline_cache.get_stats()
+    }
+}
+
+/// Rasterize glyphs with `backend`, in batches per worker thread with the
+/// `parallel` feature
+fn rasterize_batches(
+    backend: &dyn RasterBackend,
+    font: &OpenTypeFont,
+    glyph_ids: &[GlyphId],
+    size: f32,
+    dpi: f32,
+    mode: RenderMode,
+) -> Vec<Result<GlyphBitmap, RenderError>> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        let per_worker = glyph_ids
+            .len()
+            .div_ceil(rayon::current_num_threads())
+            .max(MIN_GLYPHS_PER_WORKER);
+        glyph_ids
+            .par_chunks(per_worker)
+            .flat_map_iter(|batch| backend.rasterize_batch(font, batch, size, dpi, mode))
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    backend.rasterize_batch(font, glyph_ids, size, dpi, mode)
+}
+
+/// Reject resolutions that can't be rasterized at
+fn check_dpi(dpi: f32) -> Result<(), RenderError> {
+    if dpi.is_finite() && dpi > 0.0 {
+        Ok(())
+    } else {
+        Err(RenderError::RasterizationFailed(format!(
+            "Invalid DPI: {dpi}"
+        )))
+    }
 }
 
 impl Default for GlyphRenderer {
@@ -413,10 +911,40 @@ impl Default for GlyphRenderer {
     }
 }
 
+impl MemoryReporter for GlyphRenderer {
+    /// Bytes held by the bitmap and outline caches
+    fn memory_report(&self) -> MemoryReport {
+        let bitmaps = self.cache.get_stats();
+        let outlines = self.outline_cache.get_stats();
+        MemoryReport {
+            bytes: bitmaps.memory_bytes + outlines.memory_bytes,
+            entries: bitmaps.entries + outlines.entries,
+        }
+    }
+}
+
+impl MemoryTrimmer for GlyphRenderer {
+    /// Evict a share of cached bitmaps and outlines, in the order the
+    /// eviction policy picks victims
+    fn trim_memory(&mut self, level: MemoryPressureLevel) -> usize {
+        let keep = level.retained(self.cache.entries.len());
+        let keep_outlines = level.retained(self.outline_cache.entries.len());
+        self.cache.trim(keep) + self.outline_cache.trim(keep_outlines)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn dejavu_sans() -> Vec<u8> {
+        std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap()
+    }
+
     #[test]
     fn test_new_creates_renderer() {
         let renderer = GlyphRenderer::new();
@@ -432,4 +960,491 @@ mod tests {
         assert_eq!(stats.hits, 0);
         assert_eq!(stats.misses, 0);
     }
+
+    #[test]
+    fn test_with_backend_uses_given_backend() {
+        let renderer =
+            GlyphRenderer::with_backend(CacheConfig::default(), Box::new(backend::PureRustBackend));
+        assert_eq!(renderer.backend().name(), "pure-rust");
+    }
+
+    #[test]
+    fn test_dpi_scales_glyphs_and_cache_keys() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+        let glyph = GlyphId(36); // 'A'
+
+        for backend in [
+            backend::default_backend(),
+            Box::new(backend::PureRustBackend),
+        ] {
+            let mut renderer = GlyphRenderer::with_backend(CacheConfig::default(), backend);
+            // 12pt at 96 DPI is 16 pixels per em
+            let at_96 = renderer
+                .rasterize_glyph_at_dpi(&font, glyph, 12.0, 96.0, RenderMode::Gray)
+                .unwrap();
+            let at_72 = renderer
+                .rasterize_glyph(&font, glyph, 12.0, RenderMode::Gray)
+                .unwrap();
+            let pixels = renderer
+                .rasterize_glyph(&font, glyph, 16.0, RenderMode::Gray)
+                .unwrap();
+
+            let name = renderer.backend().name();
+            assert_eq!(renderer.cache_stats().entries, 3, "{}", name);
+            assert_eq!(at_96.data, pixels.data, "{}", name);
+            assert!(at_72.height < at_96.height, "{}", name);
+            assert!(renderer
+                .rasterize_glyph_at_dpi(&font, glyph, 12.0, 0.0, RenderMode::Gray)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_cache_keys_glyphs_by_font() {
+        let sans = OpenTypeFont::from_data(dejavu_sans(), 0);
+        let serif = OpenTypeFont::from_data(
+            std::fs::read(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../tests/fixtures/fonts/DejaVuSerif.ttf"
+            ))
+            .unwrap(),
+            0,
+        );
+        let glyph = GlyphId(36); // 'A' in both fonts
+        let mut renderer = GlyphRenderer::new();
+
+        let from_sans = renderer
+            .rasterize_glyph(&sans, glyph, 32.0, RenderMode::Gray)
+            .unwrap();
+        let from_serif = renderer
+            .rasterize_glyph(&serif, glyph, 32.0, RenderMode::Gray)
+            .unwrap();
+        let batch = renderer.rasterize_glyphs(&serif, &[glyph], 32.0, 72.0, RenderMode::Gray);
+        let with_id = renderer
+            .rasterize_glyph(&sans.clone().with_font_id(1), glyph, 32.0, RenderMode::Gray)
+            .unwrap();
+
+        assert_ne!(from_sans.data, from_serif.data);
+        assert_eq!(batch[0].as_ref().unwrap().data, from_serif.data);
+        assert_eq!(with_id.data, from_sans.data);
+        let stats = renderer.cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 3));
+    }
+
+    #[test]
+    fn test_rasterize_glyphs_matches_single_glyphs() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+        // Enough glyphs for several workers, with repeats and a missing glyph
+        let mut glyphs: Vec<GlyphId> = (36..120).map(GlyphId).collect();
+        glyphs.extend([GlyphId(36), GlyphId(60_000), GlyphId(37)]);
+        let pixels = |result: &Result<GlyphBitmap, RenderError>| {
+            result
+                .as_ref()
+                .map(|bitmap| (bitmap.left, bitmap.top, bitmap.width, bitmap.data.clone()))
+                .map_err(Clone::clone)
+        };
+
+        for backend in [
+            backend::default_backend(),
+            Box::new(backend::PureRustBackend),
+        ] {
+            let name = backend.name();
+            let mut renderer = GlyphRenderer::with_backend(CacheConfig::default(), backend);
+            let first = renderer.rasterize_glyph(&font, glyphs[1], 16.0, RenderMode::Gray);
+            let batch = renderer.rasterize_glyphs(&font, &glyphs, 16.0, 96.0, RenderMode::Gray);
+
+            assert_eq!(batch.len(), glyphs.len(), "{}", name);
+            for (&glyph, result) in glyphs.iter().zip(&batch) {
+                let single =
+                    renderer
+                        .backend()
+                        .rasterize(&font, glyph, 16.0, 96.0, RenderMode::Gray);
+                assert_eq!(pixels(result), pixels(&single), "{} {:?}", name, glyph);
+            }
+            assert!(batch[glyphs.len() - 2].is_err(), "{}", name);
+
+            // Every rendered glyph joins the cache, separately from 72 DPI
+            let stats = renderer.cache_stats();
+            assert_eq!(stats.entries, 1 + 84, "{}", name);
+            let again =
+                renderer.rasterize_glyphs(&font, &glyphs[..2], 16.0, 96.0, RenderMode::Gray);
+            assert_eq!(pixels(&again[1]), pixels(&batch[1]), "{}", name);
+            assert_eq!(renderer.cache_stats().hits, stats.hits + 2, "{}", name);
+            let at_72 = renderer.rasterize_glyph(&font, glyphs[1], 16.0, RenderMode::Gray);
+            assert_eq!(pixels(&at_72), pixels(&first), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_disk_cache_serves_glyphs_to_the_next_session() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+        let glyphs = [GlyphId(36), GlyphId(37)];
+        let directory =
+            std::env::temp_dir().join(format!("glyph_renderer_session_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        let open = || DiskGlyphCache::open(&directory, disk_cache::DEFAULT_MAX_BYTES).unwrap();
+
+        // First session rasterizes and writes the glyphs on flush
+        let mut renderer = GlyphRenderer::new();
+        renderer.set_disk_cache(open());
+        let first = renderer.rasterize_glyphs(&font, &glyphs, 16.0, DEFAULT_DPI, RenderMode::Gray);
+        renderer.flush_disk_cache().unwrap();
+        assert_eq!(renderer.disk_cache().unwrap().stats().hits, 0);
+
+        // The next one reads them back instead of rasterizing
+        let mut renderer = GlyphRenderer::new();
+        renderer.set_disk_cache(open());
+        let a = renderer
+            .rasterize_glyph(&font, glyphs[0], 16.0, RenderMode::Gray)
+            .unwrap();
+        let both = renderer.rasterize_glyphs(&font, &glyphs, 16.0, DEFAULT_DPI, RenderMode::Gray);
+        let stats = renderer.take_disk_cache().unwrap().stats();
+        assert_eq!((stats.hits, stats.misses), (2, 0));
+        assert_eq!(a.data, first[0].as_ref().unwrap().data);
+        assert_eq!(
+            both[1].as_ref().unwrap().data,
+            first[1].as_ref().unwrap().data
+        );
+
+        // Other sizes are other strikes
+        let mut renderer = GlyphRenderer::new();
+        renderer.set_disk_cache(open());
+        renderer
+            .rasterize_glyph(&font, glyphs[0], 20.0, RenderMode::Gray)
+            .unwrap();
+        assert_eq!(renderer.disk_cache().unwrap().stats().misses, 1);
+        drop(renderer);
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_stroked_glyph_cached_separately() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+        let glyph = GlyphId(36); // 'A'
+        let stroke = StrokeOptions {
+            width: 2.0,
+            ..StrokeOptions::default()
+        };
+
+        for backend in [
+            backend::default_backend(),
+            Box::new(backend::PureRustBackend),
+        ] {
+            let mut renderer = GlyphRenderer::with_backend(CacheConfig::default(), backend);
+            let fill = renderer
+                .rasterize_glyph(&font, glyph, 32.0, RenderMode::Gray)
+                .unwrap();
+            let stroked = renderer
+                .rasterize_stroked_glyph(&font, glyph, 32.0, RenderMode::Gray, &stroke)
+                .unwrap();
+            let combined = renderer
+                .rasterize_stroked_glyph(
+                    &font,
+                    glyph,
+                    32.0,
+                    RenderMode::Gray,
+                    &StrokeOptions {
+                        output: StrokeOutput::FillAndStroke,
+                        ..stroke
+                    },
+                )
+                .unwrap();
+            assert_eq!(renderer.cache_stats().entries, 3);
+
+            // The stroke extends half its width beyond the fill
+            let name = renderer.backend().name();
+            assert!(stroked.width > fill.width, "{}", name);
+            assert!(
+                stroked.left <= fill.left && stroked.top >= fill.top,
+                "{}",
+                name
+            );
+            assert_eq!(
+                (combined.width, combined.height),
+                (stroked.width, stroked.height),
+                "{}",
+                name
+            );
+            // Combining adds the fill's interior to the stroke
+            let sum = |bitmap: &GlyphBitmap| bitmap.data.iter().map(|&v| v as u32).sum::<u32>();
+            assert!(sum(&combined) > sum(&stroked), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_backends_render_same_glyph_box() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+        let glyph = GlyphId(36); // 'A'
+
+        let mut native = GlyphRenderer::new();
+        let mut pure_rust =
+            GlyphRenderer::with_backend(CacheConfig::default(), Box::new(backend::PureRustBackend));
+        let a = native
+            .rasterize_glyph(&font, glyph, 32.0, RenderMode::Gray)
+            .unwrap();
+        let b = pure_rust
+            .rasterize_glyph(&font, glyph, 32.0, RenderMode::Gray)
+            .unwrap();
+
+        // Hinting may shift edges by a pixel
+        assert!(a.width.abs_diff(b.width) <= 2);
+        assert!(a.height.abs_diff(b.height) <= 2);
+        assert!((a.left - b.left).abs() <= 1);
+        assert!((a.top - b.top).abs() <= 1);
+    }
+
+    #[test]
+    fn test_outlines_in_font_units_and_at_ppem() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+        let glyph = GlyphId(36); // 'A'
+        let mut native = GlyphRenderer::new();
+        let mut pure_rust =
+            GlyphRenderer::with_backend(CacheConfig::default(), Box::new(backend::PureRustBackend));
+
+        for renderer in [&mut native, &mut pure_rust] {
+            let units = renderer
+                .get_glyph_outline_scaled(&font, glyph, OutlineScale::FontUnits)
+                .unwrap();
+            let pixels = renderer
+                .get_glyph_outline_scaled(&font, glyph, OutlineScale::Ppem(64.0))
+                .unwrap();
+
+            // Font units are the unhinted design; hinting may move pixels by one
+            let scale = 64.0 / units.units_per_em as f32;
+            assert_eq!(units.units_per_em, 2048);
+            assert_eq!(units.advance, 1401.0);
+            assert_eq!(units.left_side_bearing, 16.0);
+            assert!((units.advance * scale - pixels.advance).abs() <= 1.0);
+            assert!((units.bounds.max_y * scale - pixels.bounds.max_y).abs() <= 1.0);
+            for outline in [&units, &pixels] {
+                let ink = outline.bounds.max_x - outline.bounds.min_x;
+                let total = outline.left_side_bearing + ink + outline.right_side_bearing;
+                assert!((total - outline.advance).abs() < 1e-3);
+            }
+
+            assert!(renderer
+                .get_glyph_outline_scaled(&font, glyph, OutlineScale::Ppem(0.0))
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_outline_cache_keyed_by_font_variation_and_scale() {
+        let data = dejavu_sans();
+        let data: font_types::FontData = data.into();
+        let glyph = GlyphId(36); // 'A'
+        let mut renderer = GlyphRenderer::new();
+
+        // Given: a font without an id, outlines are not cached
+        let anonymous = OpenTypeFont::from_shared(data.clone(), 0);
+        renderer.get_glyph_outline(&anonymous, glyph).unwrap();
+        assert_eq!(renderer.outline_cache_stats().entries, 0);
+
+        // When: the same font instance is requested twice, the second is a hit
+        let font = OpenTypeFont::from_shared(data.clone(), 0).with_font_id(7);
+        let first = renderer.get_glyph_outline(&font, glyph).unwrap();
+        let second = renderer.get_glyph_outline(&font, glyph).unwrap();
+        assert_eq!(first.contours.len(), second.contours.len());
+        assert_eq!(first.advance, second.advance);
+        let stats = renderer.outline_cache_stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 1));
+        assert!(stats.memory_bytes > 0);
+
+        // Then: scale and variation coordinates get their own entries, and
+        // bitmaps are not involved
+        renderer
+            .get_glyph_outline_scaled(&font, glyph, OutlineScale::FontUnits)
+            .unwrap();
+        let varied = font
+            .clone()
+            .with_variation_coords(vec![font_types::VariationCoordinate {
+                tag: *b"wght",
+                value: 700.0,
+            }]);
+        renderer.get_glyph_outline(&varied, glyph).unwrap();
+        assert_eq!(renderer.outline_cache_stats().entries, 3);
+        assert_eq!(renderer.cache_stats().entries, 0);
+
+        let report = renderer.memory_report();
+        assert_eq!(report.entries, 3);
+        assert_eq!(report.bytes, renderer.outline_cache_stats().memory_bytes);
+
+        let freed = renderer.trim_memory(MemoryPressureLevel::Critical);
+        assert_eq!(freed, report.bytes);
+        assert_eq!(renderer.outline_cache_stats().entries, 0);
+    }
+
+    #[test]
+    fn test_outline_cache_respects_memory_limit() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0).with_font_id(0);
+        let mut renderer = GlyphRenderer::with_config(CacheConfig {
+            max_outline_memory_bytes: 1,
+            ..CacheConfig::default()
+        });
+
+        renderer.get_glyph_outline(&font, GlyphId(36)).unwrap();
+        renderer.get_glyph_outline(&font, GlyphId(37)).unwrap();
+
+        // Only the most recent outline survives the tiny budget
+        assert_eq!(renderer.outline_cache_stats().entries, 1);
+        assert_eq!(renderer.cache_config().max_outline_memory_bytes, 1);
+    }
+
+    #[test]
+    fn test_glyph_extents_match_bitmap_box() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+        let glyph = GlyphId(36); // 'A'
+
+        let mut renderer = GlyphRenderer::new();
+        let extents = renderer.glyph_extents(&font, glyph, 32.0).unwrap().unwrap();
+        // No rasterization needed
+        assert_eq!(renderer.cache_stats().entries, 0);
+
+        let bitmap = renderer
+            .rasterize_glyph(&font, glyph, 32.0, RenderMode::Gray)
+            .unwrap();
+        assert!((extents.min_x - bitmap.left as f32).abs() <= 1.0);
+        assert!((extents.max_y - bitmap.top as f32).abs() <= 1.0);
+        assert!((extents.max_x - extents.min_x - bitmap.width as f32).abs() <= 2.0);
+
+        assert!(GlyphRenderer::new()
+            .glyph_extents(&OpenTypeFont::new_stub(), glyph, 32.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_memory_report_tracks_cache() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+
+        let mut renderer = GlyphRenderer::new();
+        assert_eq!(renderer.memory_report(), MemoryReport::default());
+
+        let bitmap = renderer
+            .rasterize_glyph(&font, GlyphId(36), 32.0, RenderMode::Gray)
+            .unwrap();
+        assert_eq!(
+            renderer.memory_report(),
+            MemoryReport {
+                bytes: bitmap.data.len(),
+                entries: 1,
+            }
+        );
+
+        renderer.clear_cache();
+        assert_eq!(renderer.memory_report().entries, 0);
+    }
+
+    #[test]
+    fn test_trim_memory_evicts_least_recently_used() {
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+
+        let mut renderer = GlyphRenderer::new();
+        for glyph in 36..40 {
+            renderer
+                .rasterize_glyph(&font, GlyphId(glyph), 24.0, RenderMode::Gray)
+                .unwrap();
+        }
+        // Touch the oldest glyph so it survives trimming
+        renderer
+            .rasterize_glyph(&font, GlyphId(36), 24.0, RenderMode::Gray)
+            .unwrap();
+
+        let before = renderer.memory_report().bytes;
+        let freed = renderer.trim_memory(MemoryPressureLevel::Moderate);
+        assert_eq!(renderer.cache_stats().entries, 2);
+        assert_eq!(renderer.memory_report().bytes, before - freed);
+        assert!(freed > 0);
+
+        let hits = renderer.cache_stats().hits;
+        renderer
+            .rasterize_glyph(&font, GlyphId(36), 24.0, RenderMode::Gray)
+            .unwrap();
+        assert_eq!(renderer.cache_stats().hits, hits + 1);
+
+        renderer.trim_memory(MemoryPressureLevel::Critical);
+        assert_eq!(renderer.memory_report(), MemoryReport::default());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_rasterize_emits_span_and_cache_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Records span names and cache hit flags
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for Recorder {
+            fn record_bool(&mut self, field: &Field, value: bool) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{}={}", field.name(), value));
+            }
+
+            fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(span.metadata().name().to_string());
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                event.record(&mut self.clone());
+            }
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        let data = dejavu_sans();
+        let font = OpenTypeFont::from_data(data, 0);
+        let recorder = Recorder::default();
+
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let mut renderer = GlyphRenderer::new();
+            for _ in 0..2 {
+                renderer
+                    .rasterize_glyph(&font, GlyphId(36), 16.0, RenderMode::Gray)
+                    .unwrap();
+            }
+        });
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                "glyph_renderer::rasterize_glyph",
+                "hit=false",
+                "glyph_renderer::rasterize_glyph",
+                "hit=true",
+            ]
+        );
+    }
 }
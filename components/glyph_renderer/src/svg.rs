@@ -0,0 +1,202 @@
+//! SVG-in-OpenType color glyphs
+//!
+//! Glyphs in the `SVG ` table are drawn by SVG documents, which this crate
+//! doesn't rasterize itself. Embedders plug in an [`SvgRasterizer`] (e.g.
+//! one built on resvg) with [`GlyphRenderer::set_svg_rasterizer`]; the
+//! renderer finds and decompresses the glyph's document and hands it over.
+//!
+//! [`GlyphRenderer::set_svg_rasterizer`]: crate::GlyphRenderer::set_svg_rasterizer
+
+use crate::color::{font_table, units_per_em};
+use crate::types::{GlyphBitmap, GlyphId, OpenTypeFont, PixelFormat, RenderError};
+use font_parser::SvgTable;
+
+/// Renders glyphs from SVG-in-OpenType documents
+pub trait SvgRasterizer: Send + Sync {
+    /// Render glyph `glyph_id` of `document`
+    ///
+    /// `document` is the uncompressed SVG source; it may draw several glyphs,
+    /// and this one is the element with ID `glyph<id>`. Its coordinates are
+    /// font units with y down and the glyph origin at (0, 0), drawn at
+    /// `scale` pixels per font unit. `foreground` is the straight RGBA text
+    /// color, for `currentColor` and `context-fill`.
+    ///
+    /// Returns a premultiplied [`PixelFormat::Rgba8`] bitmap with `left`
+    /// and `top` bearings in pixels from the glyph origin, like other
+    /// glyph bitmaps.
+    fn rasterize_svg(
+        &self,
+        document: &[u8],
+        glyph_id: GlyphId,
+        scale: f32,
+        foreground: [u8; 4],
+    ) -> Result<GlyphBitmap, RenderError>;
+}
+
+/// Whether `glyph_id` has an SVG document
+pub fn is_svg_glyph(font: &OpenTypeFont, glyph_id: GlyphId) -> bool {
+    matches!(svg_table(font), Ok(Some(svg)) if svg.is_svg_glyph(glyph_id.0))
+}
+
+/// Render the SVG document of a glyph at `size` points and `dpi` with
+/// `rasterizer`
+///
+/// Returns `None` for glyphs without a document. The result isn't cached;
+/// [`GlyphRenderer::rasterize_color_glyph`] caches it along with `COLR`
+/// glyphs.
+///
+/// [`GlyphRenderer::rasterize_color_glyph`]: crate::GlyphRenderer::rasterize_color_glyph
+pub fn rasterize_svg_glyph(
+    rasterizer: &dyn SvgRasterizer,
+    font: &OpenTypeFont,
+    glyph_id: GlyphId,
+    size: f32,
+    dpi: f32,
+    foreground: [u8; 4],
+) -> Result<Option<GlyphBitmap>, RenderError> {
+    let Some(svg) = svg_table(font)? else {
+        return Ok(None);
+    };
+    let Some(document) = svg
+        .get_svg_document(glyph_id.0)
+        .map_err(|e| RenderError::RasterizationFailed(format!("Invalid SVG document: {e}")))?
+    else {
+        return Ok(None);
+    };
+
+    let scale = size * dpi / 72.0 / units_per_em(font)?;
+    let bitmap = rasterizer.rasterize_svg(&document, glyph_id, scale, foreground)?;
+    if bitmap.pixel_format != PixelFormat::Rgba8 {
+        return Err(RenderError::RasterizationFailed(format!(
+            "SVG rasterizer returned {:?} instead of RGBA",
+            bitmap.pixel_format
+        )));
+    }
+    Ok(Some(bitmap))
+}
+
+/// Parsed `SVG ` table of the font, if it has one
+fn svg_table(font: &OpenTypeFont) -> Result<Option<SvgTable>, RenderError> {
+    font_table(font, b"SVG ")?
+        .map(|data| {
+            SvgTable::parse(data)
+                .map_err(|e| RenderError::RasterizationFailed(format!("Invalid SVG table: {e}")))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::tests::with_tables;
+    use crate::GlyphRenderer;
+    use font_types::FontPalette;
+    use std::sync::Mutex;
+
+    /// Records what it is asked to draw and fills one pixel with the text
+    /// color
+    #[derive(Default)]
+    struct RecordingRasterizer {
+        calls: Mutex<Vec<(String, GlyphId, f32)>>,
+    }
+
+    impl SvgRasterizer for RecordingRasterizer {
+        fn rasterize_svg(
+            &self,
+            document: &[u8],
+            glyph_id: GlyphId,
+            scale: f32,
+            foreground: [u8; 4],
+        ) -> Result<GlyphBitmap, RenderError> {
+            let document = String::from_utf8_lossy(document).into_owned();
+            self.calls.lock().unwrap().push((document, glyph_id, scale));
+            Ok(GlyphBitmap {
+                width: 4,
+                height: 1,
+                left: 0,
+                top: 1,
+                pitch: 4,
+                data: foreground.to_vec(),
+                format: crate::types::RenderMode::Gray,
+                pixel_format: PixelFormat::Rgba8,
+            })
+        }
+    }
+
+    /// DejaVu Sans with an SVG document for 'A' (glyph 36)
+    fn svg_font() -> OpenTypeFont {
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap();
+        let document = br#"<svg xmlns="http://www.w3.org/2000/svg"><path id="glyph36"/></svg>"#;
+
+        let mut svg = Vec::new();
+        svg.extend_from_slice(&0u16.to_be_bytes()); // version
+        svg.extend_from_slice(&10u32.to_be_bytes()); // document list
+        svg.extend_from_slice(&0u32.to_be_bytes());
+        svg.extend_from_slice(&1u16.to_be_bytes());
+        for value in [36u16, 36] {
+            svg.extend_from_slice(&value.to_be_bytes());
+        }
+        svg.extend_from_slice(&14u32.to_be_bytes());
+        svg.extend_from_slice(&(document.len() as u32).to_be_bytes());
+        svg.extend_from_slice(document);
+
+        OpenTypeFont::from_data(with_tables(&data, [(*b"SVG ", svg)]), 0)
+    }
+
+    #[test]
+    fn test_svg_glyph_document_reaches_rasterizer() {
+        let font = svg_font();
+        let rasterizer = RecordingRasterizer::default();
+
+        let bitmap =
+            rasterize_svg_glyph(&rasterizer, &font, GlyphId(36), 32.0, 144.0, [1, 2, 3, 255])
+                .unwrap()
+                .unwrap();
+
+        assert_eq!(bitmap.data, [1, 2, 3, 255]);
+        let calls = rasterizer.calls.lock().unwrap();
+        let (document, glyph_id, scale) = &calls[0];
+        assert!(document.contains("glyph36"));
+        assert_eq!(*glyph_id, GlyphId(36));
+        // 64 pixels per em over DejaVu's 2048 units
+        assert!((scale - 64.0 / 2048.0).abs() < 1e-6);
+
+        assert!(is_svg_glyph(&font, GlyphId(36)));
+        assert!(!is_svg_glyph(&font, GlyphId(37)));
+        assert!(
+            rasterize_svg_glyph(&rasterizer, &font, GlyphId(37), 32.0, 72.0, [0; 4])
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_renderer_falls_back_to_svg_rasterizer() {
+        let font = svg_font();
+        let mut renderer = GlyphRenderer::new();
+        let palette = FontPalette::default();
+        let black = [0, 0, 0, 255];
+
+        // Without a rasterizer SVG glyphs have no color rendering
+        assert!(renderer
+            .rasterize_color_glyph(&font, GlyphId(36), 32.0, &palette, black)
+            .unwrap()
+            .is_none());
+
+        renderer.set_svg_rasterizer(Box::new(RecordingRasterizer::default()));
+        assert!(renderer.svg_rasterizer().is_some());
+        let bitmap = renderer
+            .rasterize_color_glyph(&font, GlyphId(36), 32.0, &palette, black)
+            .unwrap()
+            .unwrap();
+        assert_eq!(bitmap.data, black);
+        renderer
+            .rasterize_color_glyph(&font, GlyphId(36), 32.0, &palette, black)
+            .unwrap();
+        assert_eq!(renderer.cache_stats().hits, 1);
+    }
+}
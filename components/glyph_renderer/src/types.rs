@@ -1,16 +1,21 @@
 //! Common types for glyph_renderer
 
-use std::fmt;
+use font_registry::FontFace;
+use font_types::{FontData, FontId, VariationCoordinate};
+use thiserror::Error;
 
 // Temporary stubs for types from dependencies (font_types, font_parser)
 // These will be replaced with actual imports once dependencies are implemented
 
 /// Glyph ID type (stub - will come from font_types)
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct GlyphId(pub u16);
 
 /// Render mode enumeration (stub - will come from font_types)
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RenderMode {
     /// Monochrome (1-bit) rendering
     Mono,
@@ -20,13 +25,152 @@ pub enum RenderMode {
     SubpixelRgb,
 }
 
+/// Memory layout of the samples in a [`GlyphBitmap`]
+///
+/// Kept apart from [`RenderMode`], which records how a glyph was rasterized,
+/// so consumers can read a bitmap without knowing each mode's storage.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PixelFormat {
+    /// One bit of coverage per pixel, packed most significant bit first
+    A1,
+    /// One byte of coverage per pixel
+    #[default]
+    A8,
+    /// Three bytes of coverage per pixel, in red, green, blue subpixel order
+    LcdRgb,
+    /// Four bytes of premultiplied red, green, blue and alpha per pixel, for
+    /// color glyphs
+    Rgba8,
+}
+
+impl PixelFormat {
+    /// Pixel format a backend produces for `mode`
+    pub fn for_mode(mode: RenderMode) -> Self {
+        match mode {
+            RenderMode::Mono => PixelFormat::A1,
+            RenderMode::Gray => PixelFormat::A8,
+            RenderMode::SubpixelRgb => PixelFormat::LcdRgb,
+        }
+    }
+
+    /// Number of samples stored per pixel
+    pub fn samples_per_pixel(self) -> u32 {
+        match self {
+            PixelFormat::A1 | PixelFormat::A8 => 1,
+            PixelFormat::LcdRgb => 3,
+            PixelFormat::Rgba8 => 4,
+        }
+    }
+}
+
+/// Shape drawn where two stroked segments meet
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineJoin {
+    /// Circular arc around the joint
+    Round,
+    /// Straight line across the outer corner
+    Bevel,
+    /// Sharp corner, beveled beyond the miter limit
+    Miter,
+}
+
+/// Shape drawn at the ends of open contours
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineCap {
+    /// Stroke ends exactly at the endpoint
+    Butt,
+    /// Half circle around the endpoint
+    Round,
+    /// Half square beyond the endpoint
+    Square,
+}
+
+/// Which parts of a stroked glyph are rendered
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StrokeOutput {
+    /// Only the stroke, for compositing with a separately colored fill
+    Stroke,
+    /// Fill and stroke combined into one coverage bitmap
+    FillAndStroke,
+}
+
+/// Resolution at which a size in points equals the same size in pixels
+///
+/// Rasterization sizes are in points: at `dpi` a glyph of `size` points is
+/// rendered at `size * dpi / DEFAULT_DPI` pixels per em. Callers that work in
+/// pixels rasterize at this DPI; a device with a pixel ratio of 2 uses
+/// `2.0 * DEFAULT_DPI`.
+pub const DEFAULT_DPI: f32 = 72.0;
+
+/// Pixels per em of outlines from [`GlyphRenderer::get_glyph_outline`]
+///
+/// [`GlyphRenderer::get_glyph_outline`]: crate::GlyphRenderer::get_glyph_outline
+pub const DEFAULT_OUTLINE_PPEM: f32 = 16.0;
+
+/// Coordinate space of an extracted glyph outline
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutlineScale {
+    /// Pixels at this many pixels per em, hinted by backends that hint
+    Ppem(f32),
+    /// Unscaled, unhinted font units, for vector export; see
+    /// [`GlyphOutline::units_per_em`]
+    FontUnits,
+}
+
+impl Default for OutlineScale {
+    fn default() -> Self {
+        OutlineScale::Ppem(DEFAULT_OUTLINE_PPEM)
+    }
+}
+
+/// Glyph stroke settings (`-webkit-text-stroke`)
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StrokeOptions {
+    /// Stroke width centered on the outline, in the units of the font size
+    /// (pixels at [`DEFAULT_DPI`])
+    pub width: f32,
+    /// Join style
+    pub line_join: LineJoin,
+    /// Cap style
+    pub line_cap: LineCap,
+    /// Miter length limit as a multiple of the stroke width
+    pub miter_limit: f32,
+    /// Rendered parts
+    pub output: StrokeOutput,
+}
+
+impl Default for StrokeOptions {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            line_join: LineJoin::Miter,
+            line_cap: LineCap::Butt,
+            miter_limit: 4.0,
+            output: StrokeOutput::Stroke,
+        }
+    }
+}
+
 /// OpenType font structure (stub - will come from font_parser)
+///
+/// The bytes are shared: cloning the font or building it with
+/// [`from_shared`](Self::from_shared) doesn't copy them.
 #[derive(Debug, Clone)]
 pub struct OpenTypeFont {
     // Font data (actual TrueType/OpenType font bytes)
-    pub(crate) data: Vec<u8>,
+    pub(crate) data: FontData,
     // Face index (for TTC collections)
     pub(crate) face_index: isize,
+    // Registry ID identifying the font in caches
+    pub(crate) font_id: Option<FontId>,
+    // Axis coordinates applied when loading the face
+    pub(crate) variation_coords: Vec<VariationCoordinate>,
 }
 
 impl OpenTypeFont {
@@ -36,28 +180,112 @@ impl OpenTypeFont {
     /// * `data` - Raw TrueType or OpenType font data
     /// * `face_index` - Face index (0 for single fonts, varies for TTC collections)
     pub fn from_data(data: Vec<u8>, face_index: isize) -> Self {
-        Self { data, face_index }
+        Self::from_shared(data.into(), face_index)
+    }
+
+    /// Create a font over data shared with other components
+    ///
+    /// Takes e.g. the bytes of a registry face
+    /// ([`FontFace::shared_data`]) without copying them; see also
+    /// [`from_face`](Self::from_face).
+    ///
+    /// # Arguments
+    /// * `data` - Shared TrueType or OpenType font data
+    /// * `face_index` - Face index (0 for single fonts, varies for TTC collections)
+    pub fn from_shared(data: FontData, face_index: isize) -> Self {
+        Self {
+            data,
+            face_index,
+            font_id: None,
+            variation_coords: Vec::new(),
+        }
+    }
+
+    /// Create the font for a registry face, sharing its data
+    ///
+    /// The font is identified by the face's ID and instanced at its
+    /// variation coordinates, so named instances of a variable font render
+    /// as themselves.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the face's data isn't loaded (see
+    /// `FontRegistry::ensure_font_data_loaded`)
+    pub fn from_face(face: &FontFace) -> Option<Self> {
+        Some(
+            Self::from_shared(face.shared_data()?, face.face_index() as isize)
+                .with_font_id(face.id)
+                .with_variation_coords(face.variation_coords.clone()),
+        )
+    }
+
+    /// Identify the font by its registry ID, so its outlines are cached
+    ///
+    /// Fonts with the same ID must have the same data and face index.
+    pub fn with_font_id(mut self, font_id: FontId) -> Self {
+        self.font_id = Some(font_id);
+        self
+    }
+
+    /// Instance a variable font at these axis coordinates
+    ///
+    /// Axes the font doesn't have are ignored; missing axes keep their
+    /// default values.
+    pub fn with_variation_coords(mut self, coords: Vec<VariationCoordinate>) -> Self {
+        self.variation_coords = coords;
+        self
     }
 
     /// Create a temporary stub font for testing
     /// Note: This is a test helper stub. Will be replaced when font_parser is implemented.
     pub fn new_stub() -> Self {
-        Self {
-            data: Vec::new(),
-            face_index: 0,
-        }
+        Self::from_shared(FontData::from([]), 0)
     }
 
     /// Check if this font has any data
     pub fn has_data(&self) -> bool {
         !self.data.is_empty()
     }
+
+    /// Raw font data
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Shared handle to the raw font data, without copying it
+    pub fn shared_data(&self) -> FontData {
+        FontData::clone(&self.data)
+    }
+
+    /// Index of the face within the font data
+    pub fn face_index(&self) -> isize {
+        self.face_index
+    }
+
+    /// Registry ID set with [`with_font_id`](Self::with_font_id)
+    pub fn font_id(&self) -> Option<FontId> {
+        self.font_id
+    }
+
+    /// Axis coordinates the face is instanced at
+    pub fn variation_coords(&self) -> &[VariationCoordinate] {
+        &self.variation_coords
+    }
 }
 
 /// Glyph bitmap data
+///
+/// Rows start `pitch` bytes apart, top row first. A row holds
+/// `width` samples laid out as [`pixel_format`](Self::pixel_format) says,
+/// and any bytes after them up to `pitch` are padding: the pure Rust backend
+/// writes tight rows, while FreeType may pad them. Use
+/// [`to_alpha8`](Self::to_alpha8) or [`to_rgba8`](Self::to_rgba8) for a
+/// tightly packed copy in a single format.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlyphBitmap {
-    /// Bitmap width in pixels
+    /// Bitmap width in samples; three per pixel for [`PixelFormat::LcdRgb`]
+    /// and four for [`PixelFormat::Rgba8`]
     pub width: u32,
     /// Bitmap height in pixels
     pub height: u32,
@@ -71,19 +299,149 @@ pub struct GlyphBitmap {
     pub data: Vec<u8>,
     /// Render mode used
     pub format: RenderMode,
+    /// Layout of the samples in `data`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pixel_format: PixelFormat,
+}
+
+impl GlyphBitmap {
+    /// Bitmap width in pixels
+    pub fn pixel_width(&self) -> u32 {
+        self.width / self.pixel_format.samples_per_pixel()
+    }
+
+    /// Bytes of sample data in row `y`, without padding
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is not below `height`.
+    pub fn row(&self, y: u32) -> &[u8] {
+        let len = match self.pixel_format {
+            PixelFormat::A1 => self.width.div_ceil(8),
+            PixelFormat::A8 | PixelFormat::LcdRgb | PixelFormat::Rgba8 => self.width,
+        } as usize;
+        let start = y as usize * self.pitch;
+        &self.data[start..start + len]
+    }
+
+    /// Coverage of each pixel as one byte, in tightly packed rows
+    ///
+    /// Mono pixels become 0 or 255, subpixel pixels the mean of their
+    /// three samples and color pixels their alpha.
+    pub fn to_alpha8(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity((self.pixel_width() * self.height) as usize);
+        for y in 0..self.height {
+            self.for_each_pixel(y, |[r, g, b]| out.push(mean(r, g, b)));
+        }
+        out
+    }
+
+    /// Pixels as white RGBA, four bytes per pixel in tightly packed rows
+    ///
+    /// Alpha is the pixel's coverage, as in [`to_alpha8`](Self::to_alpha8).
+    /// Subpixel bitmaps keep their per-channel coverage in red, green and
+    /// blue. With `premultiplied` the color channels are scaled by alpha,
+    /// ready for `ONE, ONE_MINUS_SRC_ALPHA` blending; otherwise they hold
+    /// straight color, which is white for mono and grayscale bitmaps. Color
+    /// bitmaps keep their colors.
+    pub fn to_rgba8(&self, premultiplied: bool) -> Vec<u8> {
+        let mut out = Vec::with_capacity((self.pixel_width() * self.height) as usize * 4);
+        if self.pixel_format == PixelFormat::Rgba8 {
+            for y in 0..self.height {
+                for pixel in self.row(y).chunks_exact(4) {
+                    let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+                    if premultiplied || a == 0 {
+                        out.extend_from_slice(&[r, g, b, a]);
+                    } else {
+                        out.extend_from_slice(&[
+                            unpremultiply(r, a),
+                            unpremultiply(g, a),
+                            unpremultiply(b, a),
+                            a,
+                        ]);
+                    }
+                }
+            }
+            return out;
+        }
+        for y in 0..self.height {
+            self.for_each_pixel(y, |[r, g, b]| {
+                let a = mean(r, g, b);
+                if premultiplied {
+                    out.extend_from_slice(&[r, g, b, a]);
+                } else if a == 0 {
+                    out.extend_from_slice(&[0, 0, 0, 0]);
+                } else {
+                    out.extend_from_slice(&[
+                        unpremultiply(r, a),
+                        unpremultiply(g, a),
+                        unpremultiply(b, a),
+                        a,
+                    ]);
+                }
+            });
+        }
+        out
+    }
+
+    /// Call `f` with the red, green and blue coverage of each pixel in row `y`
+    fn for_each_pixel(&self, y: u32, mut f: impl FnMut([u8; 3])) {
+        let row = self.row(y);
+        match self.pixel_format {
+            PixelFormat::A1 => {
+                for x in 0..self.width as usize {
+                    let value = if row[x / 8] & (0x80 >> (x % 8)) != 0 {
+                        255
+                    } else {
+                        0
+                    };
+                    f([value; 3]);
+                }
+            }
+            PixelFormat::A8 => row.iter().for_each(|&value| f([value; 3])),
+            PixelFormat::LcdRgb => row
+                .chunks_exact(3)
+                .for_each(|pixel| f([pixel[0], pixel[1], pixel[2]])),
+            PixelFormat::Rgba8 => row.chunks_exact(4).for_each(|pixel| f([pixel[3]; 3])),
+        }
+    }
+}
+
+/// Straight color channel of a channel premultiplied by `alpha`
+fn unpremultiply(channel: u8, alpha: u8) -> u8 {
+    (u32::from(channel) * 255 / u32::from(alpha)).min(255) as u8
+}
+
+/// Rounded mean of three coverage samples
+fn mean(r: u8, g: u8, b: u8) -> u8 {
+    ((u32::from(r) + u32::from(g) + u32::from(b) + 1) / 3) as u8
 }
 
 /// Glyph outline (vector representation)
+///
+/// Coordinates are relative to the glyph origin with y up, in the units of
+/// the [`OutlineScale`] the outline was extracted at; the advance and side
+/// bearings use the same units.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlyphOutline {
     /// Outline contours
     pub contours: Vec<Contour>,
     /// Bounding box
     pub bounds: BoundingBox,
+    /// Horizontal advance
+    pub advance: f32,
+    /// Distance from the origin to the left edge of `bounds`
+    pub left_side_bearing: f32,
+    /// Distance from the right edge of `bounds` to the advance
+    pub right_side_bearing: f32,
+    /// Font units per em, to scale a font-unit outline to any size
+    pub units_per_em: u16,
 }
 
 /// Outline contour
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contour {
     /// Points in the contour
     pub points: Vec<Point>,
@@ -93,6 +451,7 @@ pub struct Contour {
 
 /// 2D point
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     /// X coordinate
     pub x: f32,
@@ -102,6 +461,7 @@ pub struct Point {
 
 /// Bounding box
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoundingBox {
     /// Minimum X
     pub min_x: f32,
@@ -115,6 +475,7 @@ pub struct BoundingBox {
 
 /// Glyph cache statistics
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CacheStats {
     /// Number of cached entries
     pub entries: usize,
@@ -157,24 +518,15 @@ impl Default for CacheStats {
 }
 
 /// Glyph rendering errors
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum RenderError {
     /// Glyph not found in font
+    #[error("Glyph not found: {0:?}")]
     GlyphNotFound(GlyphId),
     /// Rasterization failed with error message
+    #[error("Rasterization failed: {0}")]
     RasterizationFailed(String),
     /// Out of memory during rendering
+    #[error("Out of memory during rendering")]
     OutOfMemory,
 }
-
-impl fmt::Display for RenderError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            RenderError::GlyphNotFound(id) => write!(f, "Glyph not found: {:?}", id),
-            RenderError::RasterizationFailed(msg) => write!(f, "Rasterization failed: {}", msg),
-            RenderError::OutOfMemory => write!(f, "Out of memory during rendering"),
-        }
-    }
-}
-
-impl std::error::Error for RenderError {}
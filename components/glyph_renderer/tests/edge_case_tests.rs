@@ -157,6 +157,7 @@ mod edge_cases {
             pitch: 16,
             data: vec![0u8; 16 * 24],
             format: RenderMode::Gray,
+            pixel_format: PixelFormat::A8,
         };
 
         // Then: All fields should be accessible and correct
@@ -169,6 +170,87 @@ mod edge_cases {
         assert!(matches!(bitmap.format, RenderMode::Gray));
     }
 
+    #[test]
+    fn test_mono_bitmap_converts_to_tight_alpha8() {
+        // Given: A padded 1-bit bitmap, 10 pixels wide with a 4 byte pitch
+        let bitmap = GlyphBitmap {
+            width: 10,
+            height: 2,
+            left: 0,
+            top: 2,
+            pitch: 4,
+            data: vec![0b1000_0000, 0b0100_0000, 0xAA, 0xAA, 0, 0, 0xAA, 0xAA],
+            format: RenderMode::Mono,
+            pixel_format: PixelFormat::A1,
+        };
+
+        // When: Converting to 8-bit alpha
+        let alpha = bitmap.to_alpha8();
+
+        // Then: Rows should be tight and padding ignored
+        assert_eq!(bitmap.row(0), &[0b1000_0000, 0b0100_0000]);
+        assert_eq!(alpha.len(), 20);
+        assert_eq!(alpha[0], 255);
+        assert_eq!(alpha[9], 255);
+        assert_eq!(alpha[1..9], [0; 8]);
+        assert_eq!(alpha[10..], [0; 10]);
+    }
+
+    #[test]
+    fn test_subpixel_bitmap_converts_per_pixel() {
+        // Given: A two pixel subpixel bitmap
+        let bitmap = GlyphBitmap {
+            width: 6,
+            height: 1,
+            left: 0,
+            top: 1,
+            pitch: 6,
+            data: vec![255, 255, 255, 30, 60, 90],
+            format: RenderMode::SubpixelRgb,
+            pixel_format: PixelFormat::LcdRgb,
+        };
+
+        // When: Converting to alpha and RGBA
+        let alpha = bitmap.to_alpha8();
+        let premultiplied = bitmap.to_rgba8(true);
+        let straight = bitmap.to_rgba8(false);
+
+        // Then: Each pixel should average its samples and keep them per channel
+        assert_eq!(bitmap.pixel_width(), 2);
+        assert_eq!(alpha, vec![255, 60]);
+        assert_eq!(premultiplied, vec![255, 255, 255, 255, 30, 60, 90, 60]);
+        assert_eq!(straight, vec![255, 255, 255, 255, 127, 255, 255, 60]);
+    }
+
+    #[test]
+    fn test_gray_bitmap_converts_to_white_rgba() {
+        // Given: A grayscale bitmap with empty, partial and full coverage
+        let bitmap = GlyphBitmap {
+            width: 3,
+            height: 1,
+            left: 0,
+            top: 1,
+            pitch: 3,
+            data: vec![0, 128, 255],
+            format: RenderMode::Gray,
+            pixel_format: PixelFormat::A8,
+        };
+
+        // When: Converting to straight and premultiplied RGBA
+        let straight = bitmap.to_rgba8(false);
+        let premultiplied = bitmap.to_rgba8(true);
+
+        // Then: Straight color should be white and premultiplied scaled by alpha
+        assert_eq!(
+            straight,
+            vec![0, 0, 0, 0, 255, 255, 255, 128, 255, 255, 255, 255]
+        );
+        assert_eq!(
+            premultiplied,
+            vec![0, 0, 0, 0, 128, 128, 128, 128, 255, 255, 255, 255]
+        );
+    }
+
     #[test]
     fn test_glyph_outline_structure() {
         // Given: A glyph outline
@@ -187,6 +269,10 @@ mod edge_cases {
                 max_x: 10.0,
                 max_y: 10.0,
             },
+            advance: 12.0,
+            left_side_bearing: 0.0,
+            right_side_bearing: 2.0,
+            units_per_em: 1000,
         };
 
         // Then: All fields should be accessible
@@ -9,6 +9,7 @@ use glyph_renderer::GlyphRenderer;
 /// Minimal TrueType font data for testing
 /// This is a base64-encoded minimal TTF font with a single glyph (space)
 /// Generated using FontForge for testing purposes
+#[allow(dead_code)] // Used once tests switch from the stub to real font data
 const MINIMAL_FONT_DATA: &[u8] = include_bytes!("test_data/minimal_test.ttf");
 
 /// Helper to create a test font from minimal font data
@@ -162,7 +163,7 @@ mod freetype_tests {
     #[test]
     fn test_get_glyph_outline_produces_contours() {
         // Given: A renderer and test font
-        let renderer = GlyphRenderer::new();
+        let mut renderer = GlyphRenderer::new();
         let font = create_test_font();
         let glyph_id = GlyphId(0);
 
@@ -172,7 +173,6 @@ mod freetype_tests {
         // Then: Should produce outline with contours (when implemented)
         if let Ok(outline) = result {
             // Even space might have empty outline, but structure should be valid
-            assert!(outline.contours.len() >= 0);
             // Bounding box should be reasonable
             assert!(outline.bounds.min_x <= outline.bounds.max_x);
             assert!(outline.bounds.min_y <= outline.bounds.max_y);
@@ -203,7 +203,7 @@ mod freetype_tests {
     #[test]
     fn test_outline_extraction_for_invalid_glyph() {
         // Given: A renderer and test font
-        let renderer = GlyphRenderer::new();
+        let mut renderer = GlyphRenderer::new();
         let font = create_test_font();
         let invalid_glyph_id = GlyphId(9999);
 
@@ -97,7 +97,7 @@ mod tests {
     #[test]
     fn test_get_glyph_outline_returns_error_for_stub_font() {
         // Given: A renderer and a stub font
-        let renderer = GlyphRenderer::new();
+        let mut renderer = GlyphRenderer::new();
         let font = OpenTypeFont::new_stub();
         let glyph_id = GlyphId(0);
 
@@ -124,4 +124,42 @@ mod tests {
         let stats = renderer.cache_stats();
         assert_eq!(stats.misses, 3);
     }
+
+    #[test]
+    fn test_shared_font_data_is_not_copied() {
+        // Given: Font bytes shared with another component
+        let data: std::sync::Arc<[u8]> = vec![0u8; 1024].into();
+
+        // When: Building a font over them and cloning it
+        let font = OpenTypeFont::from_shared(data.clone(), 2);
+        let copy = font.clone();
+
+        // Then: Every handle points at the same bytes
+        assert_eq!(font.data().as_ptr(), data.as_ptr());
+        assert!(std::sync::Arc::ptr_eq(&copy.shared_data(), &data));
+        assert_eq!(copy.face_index(), 2);
+        assert_eq!(std::sync::Arc::strong_count(&data), 3);
+    }
+
+    #[test]
+    fn test_font_from_registry_face_shares_its_data() {
+        // Given: A face loaded into a registry
+        let mut registry = font_registry::FontRegistry::new();
+        let font_id = registry
+            .load_synthetic_font(&font_registry::SyntheticFont::new("Test Sans"))
+            .unwrap();
+        let face = registry.get_font_face(font_id).unwrap();
+
+        // When: Building the renderer font from the face
+        let font = OpenTypeFont::from_face(face).unwrap();
+
+        // Then: It shares the face's bytes and is identified by its ID
+        assert!(std::sync::Arc::ptr_eq(
+            &font.shared_data(),
+            &face.shared_data().unwrap()
+        ));
+        assert_eq!(font.face_index(), face.face_index() as isize);
+        assert_eq!(font.font_id(), Some(font_id));
+        assert_eq!(font.variation_coords(), face.variation_coords.as_slice());
+    }
 }
@@ -3,12 +3,15 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 pub mod types;
 
-pub use types::{FontCategory, FontStyle, FontWeight, Platform, PlatformFontInfo};
+pub use types::{DiscoveryConfig, FontCategory, FontStyle, FontWeight, Platform, PlatformFontInfo};
+
+/// Directory holding the fonts served by [`Platform::Mock`]
+pub use mock::FONTS_DIR as MOCK_FONTS_DIR;
 
 /// Discover system fonts with detailed metadata (family, weight, style)
 ///
@@ -30,6 +33,18 @@ pub use types::{FontCategory, FontStyle, FontWeight, Platform, PlatformFontInfo}
 /// }
 /// ```
 pub fn discover_system_fonts_detailed() -> Vec<PlatformFontInfo> {
+    platform_fonts_detailed(active_platform())
+}
+
+/// Discover fonts with metadata from a specific platform backend
+///
+/// [`Platform::Mock`] serves the bundled test fonts; any other value uses the
+/// platform this crate was compiled for.
+fn platform_fonts_detailed(platform: Platform) -> Vec<PlatformFontInfo> {
+    if platform == Platform::Mock {
+        return mock::discover_fonts_detailed();
+    }
+
     #[cfg(target_os = "linux")]
     return linux::discover_fonts_detailed();
 
@@ -43,6 +58,90 @@ pub fn discover_system_fonts_detailed() -> Vec<PlatformFontInfo> {
     Vec::new()
 }
 
+/// Discover fonts according to a [`DiscoveryConfig`]
+///
+/// Returns the fonts of the configured platform (see
+/// [`DiscoveryConfig::resolved_platform`]) unless `skip_system_fonts` is set, followed by
+/// fonts found in `extra_dirs`, with anything under `exclude_dirs` removed.
+/// Fonts from extra directories are reported with their file stem as family
+/// name, regular weight and normal style, and `is_system_font` set to false;
+/// callers that parse the font files should prefer the name table.
+///
+/// # Examples
+///
+/// ```no_run
+/// use platform_integration::{discover_fonts_with_config, DiscoveryConfig};
+/// use std::path::PathBuf;
+///
+/// let config = DiscoveryConfig {
+///     extra_dirs: vec![PathBuf::from("assets/fonts")],
+///     skip_system_fonts: true,
+///     ..Default::default()
+/// };
+/// let fonts = discover_fonts_with_config(&config);
+/// println!("Found {} bundled fonts", fonts.len());
+/// ```
+pub fn discover_fonts_with_config(config: &DiscoveryConfig) -> Vec<PlatformFontInfo> {
+    let mut fonts = if config.skip_system_fonts {
+        Vec::new()
+    } else {
+        platform_fonts_detailed(config.resolved_platform())
+    };
+
+    let mut seen: HashSet<PathBuf> = fonts.iter().map(|font| font.path.clone()).collect();
+
+    for dir in &config.extra_dirs {
+        let mut paths = Vec::new();
+        scan_font_directory(dir, &mut paths);
+        paths.sort();
+
+        for path in paths {
+            let path = path.canonicalize().unwrap_or(path);
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            let family_name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Unknown")
+                .to_string();
+            fonts.push(PlatformFontInfo::new(
+                family_name,
+                path,
+                FontWeight::Regular,
+                FontStyle::Normal,
+                false,
+            ));
+        }
+    }
+
+    fonts.retain(|font| !config.is_excluded(&font.path));
+    fonts
+}
+
+/// Recursively collect font files below a directory
+fn scan_font_directory(path: &Path, fonts: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.filter_map(Result::ok) {
+                scan_font_directory(&entry.path(), fonts);
+            }
+        }
+    } else if path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            matches!(
+                ext.to_lowercase().as_str(),
+                "ttf" | "otf" | "ttc" | "otc" | "woff" | "woff2" | "pfb" | "pfa"
+            )
+        })
+        .unwrap_or(false)
+    {
+        fonts.push(path.to_path_buf());
+    }
+}
+
 /// Discover system fonts for the current platform
 ///
 /// Returns a vector of paths to font files found on the system.
@@ -56,6 +155,10 @@ pub fn discover_system_fonts_detailed() -> Vec<PlatformFontInfo> {
 /// println!("Found {} fonts", fonts.len());
 /// ```
 pub fn discover_system_fonts() -> Vec<PathBuf> {
+    if active_platform() == Platform::Mock {
+        return mock::discover_fonts();
+    }
+
     #[cfg(target_os = "linux")]
     return linux::discover_fonts();
 
@@ -84,6 +187,20 @@ pub fn discover_system_fonts() -> Vec<PathBuf> {
 /// }
 /// ```
 pub fn get_default_font_families() -> HashMap<FontCategory, Vec<String>> {
+    get_platform_default_font_families(active_platform())
+}
+
+/// Get default font families for each category on a specific platform backend
+///
+/// [`Platform::Mock`] returns the fixed mock defaults; any other value uses
+/// the platform this crate was compiled for.
+pub fn get_platform_default_font_families(
+    platform: Platform,
+) -> HashMap<FontCategory, Vec<String>> {
+    if platform == Platform::Mock {
+        return mock::get_defaults();
+    }
+
     #[cfg(target_os = "linux")]
     return linux::get_defaults();
 
@@ -97,6 +214,179 @@ pub fn get_default_font_families() -> HashMap<FontCategory, Vec<String>> {
     HashMap::new()
 }
 
+/// Get default font families for a category, tailored to a content language
+///
+/// Han characters are unified in Unicode, so the same codepoint must be drawn
+/// with Japanese, Simplified Chinese, Traditional Chinese or Korean glyph
+/// shapes depending on the content language. For CJK languages the returned
+/// list starts with language-appropriate families (queried from fontconfig on
+/// Linux, well-known system families elsewhere), followed by the generic
+/// category defaults from [`get_default_font_families`].
+///
+/// # Arguments
+///
+/// * `category` - Generic font category
+/// * `language` - BCP 47 language tag (e.g. "ja", "zh-Hant", "zh-TW", "ko")
+///
+/// # Examples
+///
+/// ```no_run
+/// use platform_integration::{get_default_font_families_for_language, FontCategory};
+///
+/// let japanese = get_default_font_families_for_language(FontCategory::SansSerif, "ja-JP");
+/// let korean = get_default_font_families_for_language(FontCategory::SansSerif, "ko");
+/// println!("ja: {:?}\nko: {:?}", japanese, korean);
+/// ```
+pub fn get_default_font_families_for_language(
+    category: FontCategory,
+    language: &str,
+) -> Vec<String> {
+    get_platform_default_font_families_for_language(active_platform(), category, language)
+}
+
+/// Get language-tailored default font families on a specific platform backend
+///
+/// Same as [`get_default_font_families_for_language`], but with the platform
+/// chosen by the caller (e.g. from [`DiscoveryConfig::resolved_platform`]).
+pub fn get_platform_default_font_families_for_language(
+    platform: Platform,
+    category: FontCategory,
+    language: &str,
+) -> Vec<String> {
+    let mut families = Vec::new();
+
+    if platform == Platform::Mock {
+        // The mock font set has no CJK coverage
+    } else if let Some(cjk_language) = normalize_cjk_language(language) {
+        #[cfg(target_os = "linux")]
+        families.extend(linux::get_language_defaults(category, cjk_language));
+
+        #[cfg(target_os = "windows")]
+        families.extend(windows::get_language_defaults(category, cjk_language));
+
+        #[cfg(target_os = "macos")]
+        families.extend(macos::get_language_defaults(category, cjk_language));
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+        let _ = cjk_language;
+    }
+
+    if let Some(defaults) = get_platform_default_font_families(platform).remove(&category) {
+        families.extend(defaults);
+    }
+
+    // Keep the first occurrence of each family
+    let mut seen = std::collections::HashSet::new();
+    families.retain(|family| seen.insert(family.to_lowercase()));
+    families
+}
+
+/// Ask the platform for a font that covers a character
+///
+/// Used when no loaded font covers `c`: fontconfig matches a font whose
+/// charset contains it on Linux, and Windows and macOS suggest their
+/// system fonts for the character's script. The returned font is only a
+/// suggestion; callers should check its coverage after loading it.
+///
+/// # Arguments
+///
+/// * `c` - Character that needs a font
+/// * `style` - Preferred style of the font
+///
+/// # Returns
+///
+/// The suggested font, or None if the platform knows no font for `c`
+///
+/// # Examples
+///
+/// ```no_run
+/// use platform_integration::{fallback_font_for_char, FontStyle};
+///
+/// if let Some(font) = fallback_font_for_char('中', FontStyle::Normal) {
+///     println!("{} at {:?}", font.family_name, font.path);
+/// }
+/// ```
+pub fn fallback_font_for_char(c: char, style: FontStyle) -> Option<PlatformFontInfo> {
+    platform_fallback_font_for_char(active_platform(), c, style)
+}
+
+/// Ask a specific platform backend for a font that covers a character
+///
+/// Same as [`fallback_font_for_char`], but with the platform chosen by the
+/// caller (e.g. from [`DiscoveryConfig::resolved_platform`]).
+pub fn platform_fallback_font_for_char(
+    platform: Platform,
+    c: char,
+    style: FontStyle,
+) -> Option<PlatformFontInfo> {
+    if platform == Platform::Mock {
+        return mock::fallback_font_for_char(c, style);
+    }
+
+    #[cfg(target_os = "linux")]
+    return linux::fallback_font_for_char(c, style);
+
+    #[cfg(target_os = "windows")]
+    return windows::fallback_font_for_char(c, style);
+
+    #[cfg(target_os = "macos")]
+    return macos::fallback_font_for_char(c, style);
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    None
+}
+
+/// Suggest an installed font from a list of families for the character's
+/// script, preferring the requested style
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn installed_fallback_font(
+    fonts: Vec<PlatformFontInfo>,
+    families: &[&str],
+    style: FontStyle,
+) -> Option<PlatformFontInfo> {
+    families.iter().find_map(|family| {
+        let mut candidates = fonts
+            .iter()
+            .filter(|font| font.family_name.eq_ignore_ascii_case(family))
+            .peekable();
+        let first = candidates.peek().cloned().cloned();
+        candidates
+            .find(|font| font.style == style && font.weight == FontWeight::Regular)
+            .cloned()
+            .or(first)
+    })
+}
+
+/// Normalize a BCP 47 language tag to one of the CJK locales whose Han glyph
+/// shapes differ: "ja", "ko", "zh-CN", "zh-TW" or "zh-HK"
+///
+/// Returns None for non-CJK languages.
+pub(crate) fn normalize_cjk_language(language: &str) -> Option<&'static str> {
+    let tag = language.trim().replace('_', "-").to_lowercase();
+    let mut subtags = tag.split('-');
+
+    match subtags.next()? {
+        "ja" | "jpn" => Some("ja"),
+        "ko" | "kor" => Some("ko"),
+        "zh" | "zho" | "chi" | "yue" | "cmn" => {
+            let rest: Vec<&str> = subtags.collect();
+            if rest.iter().any(|s| matches!(*s, "hk" | "mo")) {
+                Some("zh-HK")
+            } else if rest.iter().any(|s| matches!(*s, "hant" | "tw")) {
+                Some("zh-TW")
+            } else if rest.iter().any(|s| matches!(*s, "hans" | "cn" | "sg")) {
+                Some("zh-CN")
+            } else if tag.starts_with("yue") {
+                // Cantonese defaults to Hong Kong forms
+                Some("zh-HK")
+            } else {
+                Some("zh-CN")
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Get the platform-specific font configuration directory path
 ///
 /// Returns the path to the font configuration directory if it exists.
@@ -111,6 +401,10 @@ pub fn get_default_font_families() -> HashMap<FontCategory, Vec<String>> {
 /// }
 /// ```
 pub fn get_font_config_path() -> Option<PathBuf> {
+    if active_platform() == Platform::Mock {
+        return None;
+    }
+
     #[cfg(target_os = "linux")]
     return linux::get_config_path();
 
@@ -124,6 +418,34 @@ pub fn get_font_config_path() -> Option<PathBuf> {
     None
 }
 
+/// Environment variable that selects the platform backend at runtime
+///
+/// Set to "mock" to serve the bundled test fonts instead of host fonts.
+pub const PLATFORM_ENV_VAR: &str = "CORTEN_FONT_PLATFORM";
+
+/// Get the platform backend used by the discovery and default-family functions
+///
+/// Returns the platform named by [`PLATFORM_ENV_VAR`] if it is set to a
+/// recognized value, otherwise [`detect_platform`]. Only [`Platform::Mock`]
+/// changes behavior; naming another platform does not emulate it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use platform_integration::{active_platform, Platform};
+///
+/// // CORTEN_FONT_PLATFORM=mock cargo test
+/// if active_platform() == Platform::Mock {
+///     println!("Using bundled test fonts");
+/// }
+/// ```
+pub fn active_platform() -> Platform {
+    std::env::var(PLATFORM_ENV_VAR)
+        .ok()
+        .and_then(|name| Platform::from_name(&name))
+        .unwrap_or_else(detect_platform)
+}
+
 /// Detect current platform
 ///
 /// # Examples
@@ -148,11 +470,93 @@ pub fn detect_platform() -> Platform {
     Platform::Unknown
 }
 
+/// Warn once per process that detailed discovery falls back to path scanning
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn warn_detailed_discovery_unimplemented(_platform: &str) {
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    WARNED.call_once(|| {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            platform = _platform,
+            "detailed font discovery not yet implemented, falling back to basic path discovery"
+        );
+    });
+}
+
+/// Deterministic platform backend for tests
+///
+/// Serves the DejaVu fonts vendored in the workspace `tests/fixtures/fonts`
+/// with fixed metadata and defaults, independent of the host's fonts.
+mod mock {
+    use super::*;
+
+    /// Bundled test font directory
+    pub const FONTS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/fonts");
+
+    /// Bundled fonts as (file name, family name)
+    const FONTS: [(&str, &str); 3] = [
+        ("DejaVuSans.ttf", "DejaVu Sans"),
+        ("DejaVuSansMono.ttf", "DejaVu Sans Mono"),
+        ("DejaVuSerif.ttf", "DejaVu Serif"),
+    ];
+
+    fn font_path(file_name: &str) -> PathBuf {
+        let path = Path::new(FONTS_DIR).join(file_name);
+        path.canonicalize().unwrap_or(path)
+    }
+
+    /// Bundled fonts, always in the same order
+    pub fn discover_fonts_detailed() -> Vec<PlatformFontInfo> {
+        FONTS
+            .iter()
+            .map(|(file_name, family)| {
+                PlatformFontInfo::new(
+                    family.to_string(),
+                    font_path(file_name),
+                    FontWeight::Regular,
+                    FontStyle::Normal,
+                    true,
+                )
+            })
+            .collect()
+    }
+
+    /// Paths of the bundled fonts
+    pub fn discover_fonts() -> Vec<PathBuf> {
+        FONTS
+            .iter()
+            .map(|(file_name, _)| font_path(file_name))
+            .collect()
+    }
+
+    /// DejaVu Sans, the bundled font with the widest coverage, for every
+    /// character; callers check its coverage
+    pub fn fallback_font_for_char(_c: char, _style: FontStyle) -> Option<PlatformFontInfo> {
+        discover_fonts_detailed()
+            .into_iter()
+            .find(|font| font.family_name == "DejaVu Sans")
+    }
+
+    /// Fixed defaults that only name bundled families
+    pub fn get_defaults() -> HashMap<FontCategory, Vec<String>> {
+        let mut defaults = HashMap::new();
+        defaults.insert(FontCategory::Serif, vec!["DejaVu Serif".to_string()]);
+        defaults.insert(FontCategory::SansSerif, vec!["DejaVu Sans".to_string()]);
+        defaults.insert(
+            FontCategory::Monospace,
+            vec!["DejaVu Sans Mono".to_string()],
+        );
+        defaults.insert(FontCategory::Cursive, vec!["DejaVu Serif".to_string()]);
+        defaults.insert(FontCategory::Fantasy, vec!["DejaVu Sans".to_string()]);
+        defaults.insert(FontCategory::Emoji, vec!["DejaVu Sans".to_string()]);
+        defaults
+    }
+}
+
 #[cfg(target_os = "linux")]
 mod linux {
     use super::*;
     use std::fs;
-    use std::process::Command;
 
     /// Discover fonts with detailed metadata using fontconfig
     pub fn discover_fonts_detailed() -> Vec<PlatformFontInfo> {
@@ -163,13 +567,13 @@ mod linux {
             Ok(fc_fonts) => {
                 fonts.extend(fc_fonts);
             }
-            Err(e) => {
-                eprintln!(
-                    "Fontconfig library error: {}. Falling back to basic discovery.",
-                    e
-                );
-                // Fall back to basic discovery without metadata
-                let paths = discover_fonts();
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_e, "fontconfig unavailable, falling back to basic discovery");
+                // Fall back to a directory scan without metadata
+                let mut paths = scan_font_directories();
+                paths.sort();
+                paths.dedup();
                 for path in paths {
                     // Create default font info for paths we found
                     fonts.push(PlatformFontInfo::new(
@@ -200,9 +604,9 @@ mod linux {
 
         let fc = Fontconfig::new().ok_or("Failed to initialize fontconfig")?;
 
-        // Use sort_fonts with an empty pattern to get all fonts
+        // Use list_fonts with an empty pattern to get all fonts
         let pattern = fontconfig::Pattern::new(&fc);
-        let font_set = fontconfig::sort_fonts(&pattern, true);
+        let font_set = fontconfig::list_fonts(&pattern, None);
 
         let mut fonts = Vec::new();
         let mut seen_paths = std::collections::HashSet::new();
@@ -261,24 +665,42 @@ mod linux {
         }
     }
 
-    /// Map fontconfig weight value to FontWeight enum
+    /// Fontconfig weight values and their OpenType equivalents
     ///
-    /// Fontconfig weight values:
-    /// - 0 (Thin) to 210+ (Black)
-    /// - 80 is Regular
-    /// - 200 is Bold
+    /// Same table as fontconfig's `FcWeightToOpenType`: 80 is Regular (400),
+    /// 200 is Bold (700).
+    const FONTCONFIG_WEIGHT_MAP: [(i32, u16); 12] = [
+        (0, 100),
+        (40, 200),
+        (50, 300),
+        (55, 350),
+        (75, 380),
+        (80, 400),
+        (100, 500),
+        (180, 600),
+        (200, 700),
+        (205, 800),
+        (210, 900),
+        (215, 1000),
+    ];
+
+    /// Map fontconfig weight value to a FontWeight
+    ///
+    /// Values between table entries are interpolated linearly, so weights of
+    /// variable or unusual fonts keep their intermediate value.
     fn map_fontconfig_weight(fc_weight: i32) -> FontWeight {
-        match fc_weight {
-            0..=40 => FontWeight::Thin,
-            41..=55 => FontWeight::ExtraLight,
-            56..=75 => FontWeight::Light,
-            76..=90 => FontWeight::Regular,
-            91..=110 => FontWeight::Medium,
-            111..=180 => FontWeight::SemiBold,
-            181..=200 => FontWeight::Bold,
-            201..=209 => FontWeight::ExtraBold,
-            _ => FontWeight::Black,
+        let map = &FONTCONFIG_WEIGHT_MAP;
+        if fc_weight <= map[0].0 {
+            return FontWeight::new(map[0].1);
+        }
+        for pair in map.windows(2) {
+            let ((fc_lo, ot_lo), (fc_hi, ot_hi)) = (pair[0], pair[1]);
+            if fc_weight <= fc_hi {
+                let t = (fc_weight - fc_lo) as f32 / (fc_hi - fc_lo) as f32;
+                return FontWeight::from_f32(ot_lo as f32 + t * (ot_hi - ot_lo) as f32);
+            }
         }
+        FontWeight::MAX
     }
 
     /// Map fontconfig slant value to FontStyle enum
@@ -296,48 +718,278 @@ mod linux {
         }
     }
 
-    /// Discover fonts on Linux using fontconfig and common directories
+    /// Discover fonts on Linux using the fontconfig library
+    ///
+    /// Falls back to scanning the standard, XDG and fontconfig-configured font
+    /// directories only when the library cannot be initialized.
     pub fn discover_fonts() -> Vec<PathBuf> {
+        let mut fonts = match fontconfig_font_paths() {
+            Ok(paths) => paths,
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_e, "fontconfig unavailable, falling back to directory scan");
+                scan_font_directories()
+            }
+        };
+
+        // Remove duplicates
+        fonts.sort();
+        fonts.dedup();
+        fonts
+    }
+
+    /// List the files of all fonts known to fontconfig
+    fn fontconfig_font_paths() -> Result<Vec<PathBuf>, String> {
+        use fontconfig::Fontconfig;
+
+        let fc = Fontconfig::new().ok_or("Failed to initialize fontconfig")?;
+
+        let pattern = fontconfig::Pattern::new(&fc);
+        let mut objects = fontconfig::ObjectSet::new(&fc);
+        objects.add(fontconfig::FC_FILE);
+        let font_set = fontconfig::list_fonts(&pattern, Some(&objects));
+
+        Ok(font_set
+            .iter()
+            .filter_map(|font| font.filename().map(PathBuf::from))
+            .filter_map(|path| path.canonicalize().ok())
+            .collect())
+    }
+
+    /// Scan all known font directories without the fontconfig library
+    fn scan_font_directories() -> Vec<PathBuf> {
         let mut fonts = Vec::new();
+        for dir in font_directories() {
+            if let Ok(entries) = fs::read_dir(&dir) {
+                for entry in entries.filter_map(Result::ok) {
+                    scan_directory_recursive(entry.path(), &mut fonts);
+                }
+            }
+        }
+        fonts
+    }
 
-        // Try using fc-list first
-        if let Ok(output) = Command::new("fc-list").arg("--format=%{file}\n").output() {
-            if output.status.success() {
-                let paths = String::from_utf8_lossy(&output.stdout);
-                for path in paths.lines() {
-                    let path = path.trim();
-                    if !path.is_empty() {
-                        if let Ok(pb) = PathBuf::from(path).canonicalize() {
-                            if pb.exists() {
-                                fonts.push(pb);
-                            }
-                        }
+    /// Font directories from the standard locations, $XDG_DATA_HOME,
+    /// $XDG_DATA_DIRS and the `<dir>` entries of the fontconfig configuration
+    fn font_directories() -> Vec<PathBuf> {
+        let mut dirs = vec![
+            PathBuf::from("/usr/share/fonts"),
+            PathBuf::from("/usr/local/share/fonts"),
+        ];
+        dirs.extend(xdg_font_directories(
+            std::env::var_os("XDG_DATA_HOME"),
+            std::env::var_os("XDG_DATA_DIRS"),
+        ));
+        dirs.push(expand_home("~/.fonts"));
+        dirs.extend(config_font_directories());
+
+        let mut seen = std::collections::HashSet::new();
+        dirs.retain(|dir| seen.insert(dir.clone()));
+        dirs
+    }
+
+    /// Font directories defined by the XDG base directory specification
+    ///
+    /// Unset or empty variables fall back to the specification defaults
+    /// (`~/.local/share` and `/usr/local/share:/usr/share`).
+    fn xdg_font_directories(
+        data_home: Option<std::ffi::OsString>,
+        data_dirs: Option<std::ffi::OsString>,
+    ) -> Vec<PathBuf> {
+        let data_home = data_home
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| expand_home("~/.local/share"));
+        let data_dirs = data_dirs
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+        std::iter::once(data_home)
+            .chain(std::env::split_paths(&data_dirs))
+            .filter(|dir| dir.is_absolute())
+            .map(|dir| dir.join("fonts"))
+            .collect()
+    }
+
+    /// Maximum nesting of fontconfig `<include>` elements that is followed
+    const MAX_CONFIG_DEPTH: usize = 8;
+
+    /// Font directories listed in the fontconfig configuration files
+    fn config_font_directories() -> Vec<PathBuf> {
+        let config_file = std::env::var_os("FONTCONFIG_FILE")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/etc/fonts/fonts.conf"));
+
+        let mut dirs = Vec::new();
+        let mut visited = std::collections::HashSet::new();
+        read_config_file(&config_file, 0, &mut dirs, &mut visited);
+        dirs
+    }
+
+    /// Read a fontconfig configuration file or `conf.d`-style directory
+    fn read_config_file(
+        path: &std::path::Path,
+        depth: usize,
+        dirs: &mut Vec<PathBuf>,
+        visited: &mut std::collections::HashSet<PathBuf>,
+    ) {
+        if depth > MAX_CONFIG_DEPTH || !visited.insert(path.to_path_buf()) {
+            return;
+        }
+
+        if path.is_dir() {
+            let mut files: Vec<PathBuf> = match fs::read_dir(path) {
+                Ok(entries) => entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "conf"))
+                    .collect(),
+                Err(_) => return,
+            };
+            files.sort();
+            for file in files {
+                read_config_file(&file, depth + 1, dirs, visited);
+            }
+            return;
+        }
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return,
+        };
+        let base = path.parent().unwrap_or(std::path::Path::new("/"));
+
+        for element in parse_config_elements(&contents) {
+            match element.kind {
+                ConfigElementKind::Dir => {
+                    if let Some(dir) = resolve_config_path(&element, base, "XDG_DATA_HOME") {
+                        dirs.push(dir);
+                    }
+                }
+                ConfigElementKind::Include => {
+                    if let Some(include) = resolve_config_path(&element, base, "XDG_CONFIG_HOME") {
+                        read_config_file(&include, depth + 1, dirs, visited);
                     }
                 }
             }
         }
+    }
 
-        // Also check common directories
-        let common_dirs = [
-            "/usr/share/fonts",
-            "/usr/local/share/fonts",
-            "~/.fonts",
-            "~/.local/share/fonts",
-        ];
+    /// Kind of fontconfig configuration element relevant to discovery
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum ConfigElementKind {
+        Dir,
+        Include,
+    }
 
-        for dir in &common_dirs {
-            let expanded = expand_home(dir);
-            if let Ok(entries) = fs::read_dir(&expanded) {
-                for entry in entries.filter_map(Result::ok) {
-                    scan_directory_recursive(entry.path(), &mut fonts);
+    /// A `<dir>` or `<include>` element from a fontconfig configuration file
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct ConfigElement {
+        kind: ConfigElementKind,
+        prefix: Option<String>,
+        path: String,
+    }
+
+    /// Extract `<dir>` and `<include>` elements, ignoring comments
+    fn parse_config_elements(contents: &str) -> Vec<ConfigElement> {
+        let mut elements = Vec::new();
+        let mut rest = contents;
+
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start..];
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                rest = match comment.find("-->") {
+                    Some(end) => &comment[end + 3..],
+                    None => "",
+                };
+                continue;
+            }
+
+            let (kind, name) = if rest.starts_with("<dir") {
+                (ConfigElementKind::Dir, "dir")
+            } else if rest.starts_with("<include") {
+                (ConfigElementKind::Include, "include")
+            } else {
+                rest = &rest[1..];
+                continue;
+            };
+
+            let after_name = &rest[1 + name.len()..];
+            let tag_end = match after_name.find('>') {
+                Some(end) => end,
+                None => break,
+            };
+            let attributes = &after_name[..tag_end];
+            let is_element =
+                attributes.is_empty() || attributes.starts_with(|c: char| c.is_ascii_whitespace());
+            let body = &after_name[tag_end + 1..];
+            let close = format!("</{}>", name);
+
+            match body.find(&close) {
+                Some(body_end) if is_element && !attributes.ends_with('/') => {
+                    let path = body[..body_end].trim();
+                    if !path.is_empty() {
+                        elements.push(ConfigElement {
+                            kind,
+                            prefix: parse_attribute(attributes, "prefix"),
+                            path: path.to_string(),
+                        });
+                    }
+                    rest = &body[body_end + close.len()..];
                 }
+                _ => rest = &rest[1..],
             }
         }
 
-        // Remove duplicates
-        fonts.sort();
-        fonts.dedup();
-        fonts
+        elements
+    }
+
+    /// Read a quoted attribute value from the inside of a start tag
+    fn parse_attribute(attributes: &str, name: &str) -> Option<String> {
+        let pattern = format!("{}=", name);
+        let start = attributes.find(&pattern)? + pattern.len();
+        let value = &attributes[start..];
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &value[1..];
+        let end = value.find(quote)?;
+        Some(value[..end].to_string())
+    }
+
+    /// Resolve a configuration path the way fontconfig does
+    ///
+    /// `prefix="xdg"` paths are relative to the given XDG base directory,
+    /// `~` is the home directory and other relative paths are relative to
+    /// the directory of the configuration file.
+    fn resolve_config_path(
+        element: &ConfigElement,
+        base: &std::path::Path,
+        xdg_var: &str,
+    ) -> Option<PathBuf> {
+        if element.prefix.as_deref() == Some("xdg") {
+            let xdg_home = std::env::var_os(xdg_var)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| match xdg_var {
+                    "XDG_CONFIG_HOME" => expand_home("~/.config"),
+                    _ => expand_home("~/.local/share"),
+                });
+            return Some(xdg_home.join(&element.path));
+        }
+
+        if element.path == "~" || element.path.starts_with("~/") {
+            let home = std::env::var_os("HOME")?;
+            let stripped = element.path.trim_start_matches('~').trim_start_matches('/');
+            return Some(PathBuf::from(home).join(stripped));
+        }
+
+        let path = PathBuf::from(&element.path);
+        if path.is_absolute() {
+            Some(path)
+        } else {
+            Some(base.join(path))
+        }
     }
 
     fn scan_directory_recursive(path: PathBuf, fonts: &mut Vec<PathBuf>) {
@@ -426,6 +1078,111 @@ mod linux {
         defaults
     }
 
+    /// Get language-specific default families for Linux
+    ///
+    /// Asks fontconfig for its preferred family for the generic category and
+    /// language (if that family covers the language), then appends the Noto
+    /// CJK family for that locale.
+    pub fn get_language_defaults(category: FontCategory, language: &str) -> Vec<String> {
+        let mut families = Vec::new();
+
+        if let Some(family) = query_fontconfig_language(category, language) {
+            families.push(family);
+        }
+
+        let region = match language {
+            "ja" => "JP",
+            "ko" => "KR",
+            "zh-TW" => "TC",
+            "zh-HK" => "HK",
+            _ => "SC",
+        };
+        match category {
+            FontCategory::Serif => families.push(format!("Noto Serif CJK {}", region)),
+            FontCategory::Monospace => families.push(format!("Noto Sans Mono CJK {}", region)),
+            _ => families.push(format!("Noto Sans CJK {}", region)),
+        }
+
+        families
+    }
+
+    /// Query fontconfig for the best family for a generic category and language
+    fn query_fontconfig_language(category: FontCategory, language: &str) -> Option<String> {
+        use fontconfig::{Fontconfig, Pattern, FC_FAMILY, FC_LANG};
+        use std::ffi::CString;
+
+        let fc = Fontconfig::new()?;
+        let family = CString::new(category.generic_family_name()).ok()?;
+        let lang = CString::new(language).ok()?;
+
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_string(FC_FAMILY, &family);
+        pattern.add_string(FC_LANG, &lang);
+
+        // fontconfig always returns some font; only trust it if it covers the language
+        let matched = pattern.font_match();
+        let fc_language = language.to_lowercase();
+        let supports_language = matched
+            .lang_set()
+            .is_some_and(|mut langs| langs.any(|l| l == fc_language));
+
+        if supports_language {
+            matched.get_string(FC_FAMILY).map(str::to_string)
+        } else {
+            None
+        }
+    }
+
+    /// Ask fontconfig for the best font whose charset contains `c`
+    pub fn fallback_font_for_char(c: char, style: FontStyle) -> Option<PlatformFontInfo> {
+        use fontconfig::{Fontconfig, Pattern, FC_CHARSET, FC_SLANT};
+        use fontconfig_sys as sys;
+
+        let fc = Fontconfig::new()?;
+        let mut pattern = Pattern::new(&fc);
+        let slant = match style {
+            FontStyle::Normal => 0,
+            FontStyle::Italic => 100,
+            FontStyle::Oblique(_) => 110,
+        };
+        pattern.add_integer(FC_SLANT, slant);
+        // SAFETY: the charset is created, added to the pattern (which copies
+        // it) and destroyed here; the pattern pointer is valid while borrowed.
+        unsafe {
+            let charset = sys::FcCharSetCreate();
+            if charset.is_null() {
+                return None;
+            }
+            sys::FcCharSetAddChar(charset, c as u32);
+            sys::FcPatternAddCharSet(pattern.as_mut_ptr(), FC_CHARSET.as_ptr(), charset);
+            sys::FcCharSetDestroy(charset);
+        }
+
+        // fontconfig always returns some font; only trust it if it covers `c`
+        let mut matched = pattern.font_match();
+        // SAFETY: the charset returned by FcPatternGetCharSet is owned by
+        // the matched pattern, which outlives this block.
+        let covers = unsafe {
+            let mut charset = std::ptr::null_mut();
+            sys::FcPatternGetCharSet(matched.as_mut_ptr(), FC_CHARSET.as_ptr(), 0, &mut charset)
+                == sys::FcResultMatch
+                && sys::FcCharSetHasChar(charset, c as u32) != 0
+        };
+        if !covers {
+            return None;
+        }
+
+        let path = PathBuf::from(matched.filename()?);
+        let is_system_font = path.starts_with("/usr/") || path.starts_with("/etc/");
+        Some(PlatformFontInfo::new(
+            matched.name().unwrap_or("Unknown").to_string(),
+            path,
+            map_fontconfig_weight(matched.weight().unwrap_or(80)),
+            map_fontconfig_slant(matched.slant().unwrap_or(0)),
+            is_system_font,
+        ))
+    }
+
     /// Get font config path for Linux
     pub fn get_config_path() -> Option<PathBuf> {
         let paths = vec![
@@ -435,6 +1192,106 @@ mod linux {
 
         paths.into_iter().find(|p| p.exists())
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_config_elements() {
+            let contents = r#"<?xml version="1.0"?>
+<fontconfig>
+    <!-- <dir>/commented/out</dir> -->
+    <dir>/usr/share/fonts</dir>
+    <dir prefix="xdg">fonts</dir>
+    <cachedir>/var/cache/fontconfig</cachedir>
+    <include ignore_missing="yes">conf.d</include>
+</fontconfig>"#;
+
+            assert_eq!(
+                parse_config_elements(contents),
+                vec![
+                    ConfigElement {
+                        kind: ConfigElementKind::Dir,
+                        prefix: None,
+                        path: "/usr/share/fonts".to_string(),
+                    },
+                    ConfigElement {
+                        kind: ConfigElementKind::Dir,
+                        prefix: Some("xdg".to_string()),
+                        path: "fonts".to_string(),
+                    },
+                    ConfigElement {
+                        kind: ConfigElementKind::Include,
+                        prefix: None,
+                        path: "conf.d".to_string(),
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_resolve_config_path_relative_to_config_dir() {
+            let element = ConfigElement {
+                kind: ConfigElementKind::Include,
+                prefix: None,
+                path: "conf.d".to_string(),
+            };
+            assert_eq!(
+                resolve_config_path(
+                    &element,
+                    std::path::Path::new("/etc/fonts"),
+                    "XDG_CONFIG_HOME"
+                ),
+                Some(PathBuf::from("/etc/fonts/conf.d"))
+            );
+        }
+
+        #[test]
+        fn test_xdg_font_directories() {
+            let dirs = xdg_font_directories(
+                Some("/home/user/.data".into()),
+                Some("/opt/share:relative:/usr/share".into()),
+            );
+            assert_eq!(
+                dirs,
+                vec![
+                    PathBuf::from("/home/user/.data/fonts"),
+                    PathBuf::from("/opt/share/fonts"),
+                    PathBuf::from("/usr/share/fonts"),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_xdg_font_directories_defaults() {
+            let dirs = xdg_font_directories(None, Some("".into()));
+            assert!(dirs.contains(&PathBuf::from("/usr/local/share/fonts")));
+            assert!(dirs.contains(&PathBuf::from("/usr/share/fonts")));
+        }
+
+        #[test]
+        fn test_config_font_directories_reads_system_config() {
+            if std::path::Path::new("/etc/fonts/fonts.conf").exists()
+                && std::env::var_os("FONTCONFIG_FILE").is_none()
+            {
+                let dirs = config_font_directories();
+                assert!(dirs.contains(&PathBuf::from("/usr/share/fonts")));
+            }
+        }
+
+        #[test]
+        fn test_map_fontconfig_weight() {
+            assert_eq!(map_fontconfig_weight(0), FontWeight::Thin);
+            assert_eq!(map_fontconfig_weight(80), FontWeight::Regular);
+            assert_eq!(map_fontconfig_weight(200), FontWeight::Bold);
+            assert_eq!(map_fontconfig_weight(210), FontWeight::Black);
+            // Between Regular (80) and Medium (100)
+            assert_eq!(map_fontconfig_weight(90).value(), 450);
+            assert_eq!(map_fontconfig_weight(55).value(), 350);
+            assert_eq!(map_fontconfig_weight(500), FontWeight::MAX);
+        }
+    }
 }
 
 #[cfg(target_os = "windows")]
@@ -453,8 +1310,7 @@ mod windows {
     ///
     /// See: https://docs.microsoft.com/en-us/windows/win32/directwrite/direct-write-portal
     pub fn discover_fonts_detailed() -> Vec<PlatformFontInfo> {
-        eprintln!("WARNING: discover_system_fonts_detailed() not yet implemented for Windows");
-        eprintln!("         Falling back to basic path discovery");
+        warn_detailed_discovery_unimplemented("Windows");
 
         // Fall back to basic discovery
         let paths = discover_fonts();
@@ -550,6 +1406,47 @@ mod windows {
         defaults
     }
 
+    /// Get language-specific default families for Windows
+    pub fn get_language_defaults(category: FontCategory, language: &str) -> Vec<String> {
+        let families: &[&str] = match (language, category) {
+            ("ja", FontCategory::Serif) => &["Yu Mincho", "MS Mincho"],
+            ("ja", FontCategory::Monospace) => &["MS Gothic"],
+            ("ja", _) => &["Yu Gothic", "Meiryo", "MS Gothic"],
+            ("ko", FontCategory::Serif) => &["Batang"],
+            ("ko", FontCategory::Monospace) => &["GulimChe"],
+            ("ko", _) => &["Malgun Gothic", "Gulim"],
+            ("zh-TW" | "zh-HK", FontCategory::Serif) => &["PMingLiU", "MingLiU"],
+            ("zh-TW" | "zh-HK", FontCategory::Monospace) => &["MingLiU"],
+            ("zh-TW" | "zh-HK", _) => &["Microsoft JhengHei", "PMingLiU"],
+            (_, FontCategory::Serif) => &["SimSun", "NSimSun"],
+            (_, FontCategory::Monospace) => &["NSimSun"],
+            (_, _) => &["Microsoft YaHei", "SimHei"],
+        };
+        families.iter().map(|f| f.to_string()).collect()
+    }
+
+    /// Suggest the Windows font covering the script of `c`
+    ///
+    /// The system fonts DirectWrite's fallback would pick for common scripts.
+    pub fn fallback_font_for_char(c: char, style: FontStyle) -> Option<PlatformFontInfo> {
+        let families: &[&str] = match c as u32 {
+            0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF => &["Segoe UI"],
+            0x0900..=0x0DFF => &["Nirmala UI"],
+            0x0E00..=0x0EFF => &["Leelawadee UI"],
+            0x10A0..=0x10FF => &["Sylfaen"],
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => &["Malgun Gothic"],
+            0x3040..=0x30FF => &["Yu Gothic", "Meiryo"],
+            0x2E80..=0x2FDF | 0x3000..=0x303F | 0x3400..=0x9FFF | 0xF900..=0xFAFF => {
+                &["Microsoft YaHei", "SimSun"]
+            }
+            0x20000..=0x3FFFF => &["SimSun-ExtB"],
+            0x1F000..=0x1FAFF | 0x2600..=0x27BF => &["Segoe UI Emoji", "Segoe UI Symbol"],
+            0x2000..=0x2BFF => &["Segoe UI Symbol"],
+            _ => &["Segoe UI", "Arial"],
+        };
+        installed_fallback_font(discover_fonts_detailed(), families, style)
+    }
+
     /// Get font config path for Windows
     pub fn get_config_path() -> Option<PathBuf> {
         if let Some(windir) = std::env::var_os("WINDIR") {
@@ -576,8 +1473,7 @@ mod macos {
     ///
     /// See: https://developer.apple.com/documentation/coretext
     pub fn discover_fonts_detailed() -> Vec<PlatformFontInfo> {
-        eprintln!("WARNING: discover_system_fonts_detailed() not yet implemented for macOS");
-        eprintln!("         Falling back to basic path discovery");
+        warn_detailed_discovery_unimplemented("macOS");
 
         // Fall back to basic discovery
         let paths = discover_fonts();
@@ -682,6 +1578,44 @@ mod macos {
         defaults
     }
 
+    /// Get language-specific default families for macOS
+    pub fn get_language_defaults(category: FontCategory, language: &str) -> Vec<String> {
+        let families: &[&str] = match (language, category) {
+            ("ja", FontCategory::Serif) => &["Hiragino Mincho ProN"],
+            ("ja", _) => &["Hiragino Sans", "Hiragino Kaku Gothic ProN"],
+            ("ko", FontCategory::Serif) => &["AppleMyungjo"],
+            ("ko", _) => &["Apple SD Gothic Neo"],
+            ("zh-TW", FontCategory::Serif) => &["Songti TC"],
+            ("zh-TW", _) => &["PingFang TC"],
+            ("zh-HK", FontCategory::Serif) => &["Songti TC"],
+            ("zh-HK", _) => &["PingFang HK"],
+            (_, FontCategory::Serif) => &["Songti SC"],
+            (_, _) => &["PingFang SC"],
+        };
+        families.iter().map(|f| f.to_string()).collect()
+    }
+
+    /// Suggest the macOS font covering the script of `c`
+    ///
+    /// The system fonts CoreText's cascade list would pick for common scripts.
+    pub fn fallback_font_for_char(c: char, style: FontStyle) -> Option<PlatformFontInfo> {
+        let families: &[&str] = match c as u32 {
+            0x0590..=0x05FF | 0xFB1D..=0xFB4F => &["Arial Hebrew"],
+            0x0600..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => &["Geeza Pro"],
+            0x0900..=0x097F => &["Kohinoor Devanagari"],
+            0x0E00..=0x0E7F => &["Thonburi"],
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => &["Apple SD Gothic Neo"],
+            0x3040..=0x30FF => &["Hiragino Sans"],
+            0x2E80..=0x2FDF | 0x3000..=0x303F | 0x3400..=0x9FFF | 0xF900..=0xFAFF => {
+                &["PingFang SC", "Hiragino Sans"]
+            }
+            0x1F000..=0x1FAFF | 0x2600..=0x27BF => &["Apple Color Emoji"],
+            0x2000..=0x2BFF => &["Apple Symbols"],
+            _ => &["Helvetica", "Lucida Grande"],
+        };
+        installed_fallback_font(discover_fonts_detailed(), families, style)
+    }
+
     /// Get font config path for macOS
     pub fn get_config_path() -> Option<PathBuf> {
         Some(PathBuf::from("/Library/Fonts"))
@@ -715,12 +1649,51 @@ mod tests {
     }
 
     #[test]
-    fn test_get_font_config_path_returns_option() {
-        let path = get_font_config_path();
-        // Should return Some or None
-        match path {
-            Some(_) => assert!(true),
-            None => assert!(true),
+    fn test_normalize_cjk_language() {
+        assert_eq!(normalize_cjk_language("ja"), Some("ja"));
+        assert_eq!(normalize_cjk_language("ja-JP"), Some("ja"));
+        assert_eq!(normalize_cjk_language("ko_KR"), Some("ko"));
+        assert_eq!(normalize_cjk_language("zh"), Some("zh-CN"));
+        assert_eq!(normalize_cjk_language("zh-Hans-CN"), Some("zh-CN"));
+        assert_eq!(normalize_cjk_language("zh-Hant"), Some("zh-TW"));
+        assert_eq!(normalize_cjk_language("zh-TW"), Some("zh-TW"));
+        assert_eq!(normalize_cjk_language("zh-Hant-HK"), Some("zh-HK"));
+        assert_eq!(normalize_cjk_language("yue"), Some("zh-HK"));
+        assert_eq!(normalize_cjk_language("en-US"), None);
+        assert_eq!(normalize_cjk_language(""), None);
+    }
+
+    #[test]
+    fn test_get_default_font_families_for_language_non_cjk_matches_category() {
+        let families = get_default_font_families_for_language(FontCategory::Serif, "en");
+        let defaults = get_default_font_families();
+        assert_eq!(Some(&families), defaults.get(&FontCategory::Serif));
+    }
+
+    #[test]
+    fn test_mock_fallback_font_is_bundled() {
+        let font = platform_fallback_font_for_char(Platform::Mock, 'ب', FontStyle::Normal).unwrap();
+        assert_eq!(font.family_name, "DejaVu Sans");
+        assert!(font.path.exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_fallback_font_covers_char() {
+        // Only fonts fontconfig says contain the character are suggested
+        if let Some(font) = platform_fallback_font_for_char(Platform::Linux, 'A', FontStyle::Normal)
+        {
+            assert!(font.path.exists());
         }
+        assert_eq!(
+            platform_fallback_font_for_char(Platform::Linux, '\u{10FFFD}', FontStyle::Normal),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_font_config_path_returns_option() {
+        // Should return Some or None without panicking
+        let _path = get_font_config_path();
     }
 }
@@ -1,43 +1,13 @@
 //! Types for platform_integration component
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// Font weight values (100-900)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum FontWeight {
-    /// Thin weight (100)
-    Thin = 100,
-    /// Extra Light weight (200)
-    ExtraLight = 200,
-    /// Light weight (300)
-    Light = 300,
-    /// Regular weight (400)
-    Regular = 400,
-    /// Medium weight (500)
-    Medium = 500,
-    /// Semi Bold weight (600)
-    SemiBold = 600,
-    /// Bold weight (700)
-    Bold = 700,
-    /// Extra Bold weight (800)
-    ExtraBold = 800,
-    /// Black weight (900)
-    Black = 900,
-}
-
-/// Font style
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum FontStyle {
-    /// Normal (upright) style
-    Normal,
-    /// Italic style
-    Italic,
-    /// Oblique style with angle in degrees
-    Oblique(f32),
-}
+// Font weight and style are shared with the rest of the font system
+pub use font_types::types::{FontStyle, FontWeight};
 
 /// Font categories for classification
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontCategory {
     /// Serif fonts
     Serif,
@@ -53,6 +23,35 @@ pub enum FontCategory {
     Emoji,
 }
 
+impl FontCategory {
+    /// Get the CSS generic family name for this category
+    pub fn generic_family_name(&self) -> &'static str {
+        match self {
+            FontCategory::Serif => "serif",
+            FontCategory::SansSerif => "sans-serif",
+            FontCategory::Monospace => "monospace",
+            FontCategory::Cursive => "cursive",
+            FontCategory::Fantasy => "fantasy",
+            FontCategory::Emoji => "emoji",
+        }
+    }
+
+    /// Map a CSS generic family name (case-insensitive) to a category
+    ///
+    /// Returns None for concrete family names such as "Arial".
+    pub fn from_generic_family(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "serif" => Some(FontCategory::Serif),
+            "sans-serif" => Some(FontCategory::SansSerif),
+            "monospace" => Some(FontCategory::Monospace),
+            "cursive" => Some(FontCategory::Cursive),
+            "fantasy" => Some(FontCategory::Fantasy),
+            "emoji" => Some(FontCategory::Emoji),
+            _ => None,
+        }
+    }
+}
+
 /// Supported platforms
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Platform {
@@ -64,6 +63,26 @@ pub enum Platform {
     MacOS,
     /// Unknown platform
     Unknown,
+    /// Deterministic in-memory backend serving the bundled test fonts
+    ///
+    /// Selected with the `CORTEN_FONT_PLATFORM=mock` environment variable or
+    /// [`DiscoveryConfig::platform`]; see [`crate::active_platform`].
+    Mock,
+}
+
+impl Platform {
+    /// Parse a platform name as used by the `CORTEN_FONT_PLATFORM` variable
+    ///
+    /// Accepts "linux", "windows", "macos" and "mock" (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "linux" => Some(Platform::Linux),
+            "windows" => Some(Platform::Windows),
+            "macos" => Some(Platform::MacOS),
+            "mock" => Some(Platform::Mock),
+            _ => None,
+        }
+    }
 }
 
 /// Platform-specific font information
@@ -99,3 +118,92 @@ impl PlatformFontInfo {
         }
     }
 }
+
+/// Configuration for font discovery
+///
+/// Lets embedders add application-bundled font directories, exclude
+/// directories from discovery, and skip system fonts entirely (useful for
+/// deterministic test environments and sandboxed applications).
+///
+/// # Examples
+///
+/// ```
+/// use platform_integration::DiscoveryConfig;
+/// use std::path::PathBuf;
+///
+/// let config = DiscoveryConfig {
+///     extra_dirs: vec![PathBuf::from("/opt/app/fonts")],
+///     exclude_dirs: vec![PathBuf::from("/opt/app/fonts/legacy")],
+///     skip_system_fonts: true,
+///     platform: None,
+/// };
+/// assert!(config.is_excluded(&PathBuf::from("/opt/app/fonts/legacy/Old.ttf")));
+/// assert!(!config.is_excluded(&PathBuf::from("/opt/app/fonts/New.ttf")));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiscoveryConfig {
+    /// Additional directories to scan recursively for font files
+    pub extra_dirs: Vec<PathBuf>,
+    /// Directories whose fonts (including subdirectories) are never returned
+    pub exclude_dirs: Vec<PathBuf>,
+    /// Skip platform font discovery and only scan `extra_dirs`
+    pub skip_system_fonts: bool,
+    /// Platform backend to discover fonts and defaults from
+    ///
+    /// None uses [`crate::active_platform`]. Set to `Some(Platform::Mock)` for
+    /// hermetic tests regardless of the environment.
+    pub platform: Option<Platform>,
+}
+
+impl DiscoveryConfig {
+    /// Create a configuration that discovers system fonts only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a configuration that discovers only the mock platform's fonts
+    pub fn mock() -> Self {
+        Self {
+            platform: Some(Platform::Mock),
+            ..Self::default()
+        }
+    }
+
+    /// The platform this configuration discovers fonts from
+    pub fn resolved_platform(&self) -> Platform {
+        self.platform.unwrap_or_else(crate::active_platform)
+    }
+
+    /// Add the canonical form of each excluded directory that has one
+    ///
+    /// Relative or symlinked exclusions then match canonical font paths.
+    /// `FontRegistry::set_discovery_config` does this once for its config.
+    pub fn canonicalize_exclude_dirs(&mut self) {
+        let canonical: Vec<PathBuf> = self
+            .exclude_dirs
+            .iter()
+            .filter_map(|dir| dir.canonicalize().ok())
+            .collect();
+        for dir in canonical {
+            if !self.exclude_dirs.contains(&dir) {
+                self.exclude_dirs.push(dir);
+            }
+        }
+    }
+
+    /// Check whether a font path lies inside one of the excluded directories
+    ///
+    /// The path is compared both as given and canonicalized, so fonts
+    /// reached through a symlink can't escape an exclusion. Excluded
+    /// directories are compared as stored; see
+    /// [`DiscoveryConfig::canonicalize_exclude_dirs`].
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let canonical = path.canonicalize().ok();
+        self.exclude_dirs.iter().any(|dir| {
+            path.starts_with(dir)
+                || canonical
+                    .as_ref()
+                    .is_some_and(|canonical| canonical.starts_with(dir))
+        })
+    }
+}
@@ -5,9 +5,7 @@
 
 #[cfg(target_os = "linux")]
 mod linux_fontconfig_tests {
-    use platform_integration::{
-        discover_system_fonts_detailed, FontStyle, FontWeight, PlatformFontInfo,
-    };
+    use platform_integration::{discover_system_fonts_detailed, FontStyle, FontWeight};
 
     #[test]
     fn test_discover_system_fonts_detailed_returns_font_info() {
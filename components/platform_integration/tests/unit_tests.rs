@@ -1,9 +1,12 @@
 //! Unit tests for platform_integration
 
 use platform_integration::{
-    detect_platform, discover_system_fonts, get_default_font_families, get_font_config_path,
-    FontCategory, Platform,
+    active_platform, detect_platform, discover_fonts_with_config, discover_system_fonts,
+    get_default_font_families, get_default_font_families_for_language, get_font_config_path,
+    get_platform_default_font_families, get_platform_default_font_families_for_language,
+    DiscoveryConfig, FontCategory, Platform, MOCK_FONTS_DIR,
 };
+use std::path::{Path, PathBuf};
 
 #[test]
 fn test_detect_platform_returns_known_platform() {
@@ -135,6 +138,10 @@ fn test_platform_macos_on_macos() {
 #[test]
 #[cfg(target_os = "linux")]
 fn test_linux_discovers_fonts_in_common_locations() {
+    // The mock backend serves fixture fonts from the source tree
+    if active_platform() == Platform::Mock {
+        return;
+    }
     let fonts = discover_system_fonts();
 
     // On Linux with fonts installed, we should find fonts
@@ -174,3 +181,239 @@ fn test_default_families_returns_strings() {
         }
     }
 }
+
+#[test]
+fn test_language_defaults_differ_for_han_unification() {
+    // Japanese, Simplified Chinese, Traditional Chinese and Korean need
+    // different glyph shapes for the same Han codepoints
+    let defaults_for = |language| {
+        get_platform_default_font_families_for_language(
+            detect_platform(),
+            FontCategory::SansSerif,
+            language,
+        )
+    };
+    let ja = defaults_for("ja");
+    let zh_cn = defaults_for("zh-CN");
+    let zh_tw = defaults_for("zh-Hant");
+    let ko = defaults_for("ko");
+
+    let firsts = [&ja[0], &zh_cn[0], &zh_tw[0], &ko[0]];
+    for (i, a) in firsts.iter().enumerate() {
+        for b in &firsts[i + 1..] {
+            assert_ne!(a, b, "CJK locales should prefer different families");
+        }
+    }
+}
+
+#[test]
+fn test_language_defaults_include_category_defaults() {
+    let defaults = get_platform_default_font_families(detect_platform());
+    let sans = &defaults[&FontCategory::SansSerif];
+    let ja = get_platform_default_font_families_for_language(
+        detect_platform(),
+        FontCategory::SansSerif,
+        "ja-JP",
+    );
+
+    assert!(ja.len() > sans.len());
+    for family in sans {
+        assert!(ja.contains(family), "{} missing from ja defaults", family);
+    }
+}
+
+#[test]
+fn test_language_defaults_have_no_duplicates() {
+    let families = get_default_font_families_for_language(FontCategory::Monospace, "ko");
+    let mut lowered: Vec<String> = families.iter().map(|f| f.to_lowercase()).collect();
+    lowered.sort();
+    lowered.dedup();
+    assert_eq!(lowered.len(), families.len());
+}
+
+#[test]
+fn test_font_category_generic_family_round_trip() {
+    for category in [
+        FontCategory::Serif,
+        FontCategory::SansSerif,
+        FontCategory::Monospace,
+        FontCategory::Cursive,
+        FontCategory::Fantasy,
+        FontCategory::Emoji,
+    ] {
+        assert_eq!(
+            FontCategory::from_generic_family(category.generic_family_name()),
+            Some(category)
+        );
+    }
+    assert_eq!(
+        FontCategory::from_generic_family("Sans-Serif"),
+        Some(FontCategory::SansSerif)
+    );
+    assert_eq!(FontCategory::from_generic_family("Arial"), None);
+}
+
+/// Create an empty temporary directory unique to a test
+fn temp_font_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "platform_integration_{}_{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_discovery_config_default_matches_system_discovery() {
+    let config = DiscoveryConfig::default();
+    assert!(config.extra_dirs.is_empty());
+    assert!(config.exclude_dirs.is_empty());
+    assert!(!config.skip_system_fonts);
+
+    let fonts = discover_fonts_with_config(&config);
+    assert_eq!(
+        fonts.len(),
+        platform_integration::discover_system_fonts_detailed().len()
+    );
+}
+
+#[test]
+fn test_discovery_config_extra_dirs_without_system_fonts() {
+    // Given a bundled font directory with a nested font and a non-font file
+    let dir = temp_font_dir("extra");
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::write(dir.join("Bundled.ttf"), b"not really a font").unwrap();
+    std::fs::write(dir.join("nested/Other.otf"), b"not really a font").unwrap();
+    std::fs::write(dir.join("README.txt"), b"text").unwrap();
+
+    // When discovering with system fonts skipped
+    let config = DiscoveryConfig {
+        extra_dirs: vec![dir.clone()],
+        skip_system_fonts: true,
+        ..Default::default()
+    };
+    let fonts = discover_fonts_with_config(&config);
+
+    // Then only the bundled font files are returned, marked as non-system
+    let families: Vec<&str> = fonts.iter().map(|f| f.family_name.as_str()).collect();
+    assert_eq!(families, vec!["Bundled", "Other"]);
+    assert!(fonts.iter().all(|f| !f.is_system_font));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_discovery_config_exclude_dirs() {
+    // Given a bundled directory with an excluded subdirectory
+    let dir = temp_font_dir("exclude");
+    std::fs::create_dir_all(dir.join("legacy")).unwrap();
+    std::fs::write(dir.join("Keep.ttf"), b"font").unwrap();
+    std::fs::write(dir.join("legacy/Drop.ttf"), b"font").unwrap();
+
+    // When discovering with the subdirectory excluded
+    let config = DiscoveryConfig {
+        extra_dirs: vec![dir.clone()],
+        exclude_dirs: vec![dir.join("legacy")],
+        skip_system_fonts: true,
+        ..Default::default()
+    };
+    let fonts = discover_fonts_with_config(&config);
+
+    // Then fonts under the excluded directory are dropped
+    assert_eq!(fonts.len(), 1);
+    assert_eq!(fonts[0].family_name, "Keep");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_discovery_config_exclusions_follow_symlinks() {
+    // Given a font directory and a symlink to it
+    let dir = temp_font_dir("symlink");
+    std::fs::create_dir_all(dir.join("real")).unwrap();
+    std::fs::write(dir.join("real/Drop.ttf"), b"font").unwrap();
+    std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+
+    // When excluding the symlink, with canonicalized exclusions
+    let mut config = DiscoveryConfig {
+        extra_dirs: vec![dir.join("link")],
+        exclude_dirs: vec![dir.join("link")],
+        skip_system_fonts: true,
+        ..Default::default()
+    };
+    config.canonicalize_exclude_dirs();
+
+    // Then the font, discovered under its canonical path, is dropped
+    assert!(discover_fonts_with_config(&config).is_empty());
+
+    // And a font reached through the symlink can't escape an exclusion of
+    // the real directory
+    let config = DiscoveryConfig {
+        exclude_dirs: vec![dir.join("real").canonicalize().unwrap()],
+        ..Default::default()
+    };
+    assert!(config.is_excluded(&dir.join("link/Drop.ttf")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_discovery_config_exclude_system_dirs() {
+    let fonts = platform_integration::discover_system_fonts_detailed();
+    let Some(first) = fonts.first() else {
+        return;
+    };
+    let excluded = first.path.parent().unwrap().to_path_buf();
+
+    let config = DiscoveryConfig {
+        exclude_dirs: vec![excluded.clone()],
+        ..Default::default()
+    };
+    let filtered = discover_fonts_with_config(&config);
+    assert!(filtered.iter().all(|f| !f.path.starts_with(&excluded)));
+    assert!(filtered.len() < fonts.len());
+}
+
+#[test]
+fn test_platform_from_name() {
+    assert_eq!(Platform::from_name("mock"), Some(Platform::Mock));
+    assert_eq!(Platform::from_name(" MacOS "), Some(Platform::MacOS));
+    assert_eq!(Platform::from_name("beos"), None);
+}
+
+#[test]
+fn test_mock_discovery_serves_bundled_fonts() {
+    let fonts = discover_fonts_with_config(&DiscoveryConfig::mock());
+
+    let families: Vec<&str> = fonts.iter().map(|f| f.family_name.as_str()).collect();
+    assert_eq!(
+        families,
+        vec!["DejaVu Sans", "DejaVu Sans Mono", "DejaVu Serif"]
+    );
+    assert!(fonts.iter().all(|f| f.path.is_file()));
+    assert!(fonts.iter().all(|f| f
+        .path
+        .starts_with(Path::new(MOCK_FONTS_DIR).canonicalize().unwrap())));
+
+    // Repeated discovery is identical
+    assert_eq!(discover_fonts_with_config(&DiscoveryConfig::mock()), fonts);
+}
+
+#[test]
+fn test_mock_defaults_name_only_bundled_families() {
+    let defaults = get_platform_default_font_families(Platform::Mock);
+    assert_eq!(defaults[&FontCategory::SansSerif], vec!["DejaVu Sans"]);
+    assert_eq!(defaults[&FontCategory::Serif], vec!["DejaVu Serif"]);
+    assert_eq!(defaults[&FontCategory::Monospace], vec!["DejaVu Sans Mono"]);
+
+    // No CJK-specific families are prepended
+    let japanese = get_platform_default_font_families_for_language(
+        Platform::Mock,
+        FontCategory::SansSerif,
+        "ja",
+    );
+    assert_eq!(japanese, vec!["DejaVu Sans"]);
+}
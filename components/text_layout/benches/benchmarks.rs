@@ -1,11 +1,173 @@
-// Placeholder benchmark file
-// TODO: Add performance benchmarks for layout operations
+//! Benchmarks for text layout operations
+//!
+//! Measures line breaking and paragraph layout against the documented
+//! budgets (< 1ms and < 5ms for 1000 characters). Text is shaped with the
+//! DejaVu Sans fixture from `tests/fixtures/fonts` so glyph advances are
+//! realistic and independent of the host's installed fonts.
 
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use font_registry::FontRegistry;
+use font_types::ShapedText;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use text_layout::{JustificationMode, LayoutOptions, LineBreaker, ParagraphLayout};
+use text_shaper::{
+    FontVariantCaps, FormatCharacters, Language, MatchContext, Script, ShapingOptions, TextShaper,
+    TextTransform,
+};
 
-fn benchmark_placeholder(_c: &mut Criterion) {
-    // Benchmarks will be added in future iterations
+const SAMPLE: &str = "The quick brown fox jumps over the lazy dog. ";
+
+/// Load a vendored fixture font from the workspace `tests/fixtures/fonts`
+fn fixture_font(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../tests/fixtures/fonts")
+        .join(name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("missing fixture {}: {e}", path.display()))
+}
+
+/// Build `count` characters of word-separated sample text
+fn sample_text(count: usize) -> String {
+    SAMPLE.chars().cycle().take(count).collect()
+}
+
+/// Shape `text` with DejaVu Sans at 16px
+fn shape_fixture_text(text: &str) -> ShapedText {
+    let mut registry = FontRegistry::new();
+    let font_id = registry
+        .load_font_data(fixture_font("DejaVuSans.ttf"))
+        .expect("DejaVuSans.ttf should load");
+    let shaper = TextShaper::new(&registry);
+    let options = ShapingOptions {
+        script: Script::Latin,
+        language: Language {
+            tag: "en-US".to_string(),
+        },
+        direction: font_types::types::Direction::LeftToRight,
+        features: HashMap::new(),
+        kerning: true,
+        ligatures: true,
+        letter_spacing: 0.0,
+        word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
+    };
+    shaper
+        .shape_text(text, font_id, 16.0, &options)
+        .expect("fixture text should shape")
+}
+
+/// Benchmark UAX #14 break opportunity search
+fn bench_find_breaks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_breaks");
+    let breaker = LineBreaker::new();
+
+    for char_count in [100, 1000, 10000].iter() {
+        let text = sample_text(*char_count);
+        group.throughput(Throughput::Elements(*char_count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(char_count), &text, |b, text| {
+            b.iter(|| black_box(breaker.find_breaks(black_box(text))));
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark paragraph layout of pre-shaped text at increasing lengths
+fn bench_layout_paragraph(c: &mut Criterion) {
+    let mut group = c.benchmark_group("layout_paragraph");
+    let layout = ParagraphLayout::new();
+    let options = LayoutOptions::default();
+
+    for char_count in [100, 1000, 10000].iter() {
+        let text = sample_text(*char_count);
+        let shaped = shape_fixture_text(&text);
+        group.throughput(Throughput::Elements(*char_count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(char_count), &text, |b, text| {
+            b.iter(|| {
+                let result = layout.layout_paragraph(
+                    black_box(text),
+                    black_box(&shaped),
+                    black_box(&options),
+                );
+                black_box(result)
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark each justification mode on a 1000 character paragraph
+fn bench_justification_modes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("justification_modes");
+    let layout = ParagraphLayout::new();
+    let text = sample_text(1000);
+    let shaped = shape_fixture_text(&text);
+
+    let modes = [
+        ("left", JustificationMode::Left),
+        ("right", JustificationMode::Right),
+        ("center", JustificationMode::Center),
+        ("justify", JustificationMode::Justify),
+    ];
+
+    for (name, mode) in modes.iter() {
+        let options = LayoutOptions {
+            justification: *mode,
+            ..LayoutOptions::default()
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(name), &options, |b, options| {
+            b.iter(|| {
+                let result = layout.layout_paragraph(
+                    black_box(&text),
+                    black_box(&shaped),
+                    black_box(options),
+                );
+                black_box(result)
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark layout of the same paragraph at different container widths
+fn bench_layout_widths(c: &mut Criterion) {
+    let mut group = c.benchmark_group("layout_widths");
+    let layout = ParagraphLayout::new();
+    let text = sample_text(1000);
+    let shaped = shape_fixture_text(&text);
+
+    for width in [100.0f32, 300.0, 800.0].iter() {
+        let options = LayoutOptions {
+            max_width: *width,
+            ..LayoutOptions::default()
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(width),
+            &options,
+            |b, options| {
+                b.iter(|| {
+                    let result = layout.layout_paragraph(
+                        black_box(&text),
+                        black_box(&shaped),
+                        black_box(options),
+                    );
+                    black_box(result)
+                });
+            },
+        );
+    }
+    group.finish();
 }
 
-criterion_group!(benches, benchmark_placeholder);
+criterion_group!(
+    benches,
+    bench_find_breaks,
+    bench_layout_paragraph,
+    bench_justification_modes,
+    bench_layout_widths,
+);
 criterion_main!(benches);
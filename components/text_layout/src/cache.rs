@@ -0,0 +1,313 @@
+//! Paragraph layout cache
+//!
+//! Browsers lay out the same paragraphs frame after frame. The cache keeps
+//! the `LayoutResult` of recently laid out paragraphs, keyed by a hash of the
+//! text, of the shaping style and of the `LayoutOptions`, so unchanged
+//! paragraphs skip line breaking and justification.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use font_types::ShapedText;
+use lru::LruCache;
+use text_shaper::ShapingOptions;
+
+use crate::types::{LayoutOptions, LayoutResult};
+
+/// Default maximum number of cached paragraphs
+pub const DEFAULT_LAYOUT_CACHE_SIZE: usize = 256;
+
+/// Cache key for a laid out paragraph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct LayoutCacheKey {
+    /// Hash of the paragraph text
+    text: u64,
+    /// Hash of the shaping options and the shaped text's font and size
+    shaping: u64,
+    /// Hash of the layout options
+    options: u64,
+}
+
+impl LayoutCacheKey {
+    fn new(
+        text: &str,
+        shaped_text: &ShapedText,
+        shaping_options: &ShapingOptions,
+        options: &LayoutOptions,
+    ) -> Self {
+        Self {
+            text: hash_of(text),
+            // The shaping options don't name the font or size, so the shaped
+            // text's font and extent stand in for them
+            shaping: hash_of(&(
+                shaping_options,
+                shaped_text.glyphs.first().map(|glyph| glyph.font_id),
+                shaped_text.glyphs.len(),
+                shaped_text.width.to_bits(),
+                shaped_text.height.to_bits(),
+                shaped_text.baseline.to_bits(),
+            )),
+            options: hash_of(options),
+        }
+    }
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Layout cache statistics
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LayoutCacheStats {
+    /// Number of cache hits
+    pub hits: u64,
+    /// Number of cache misses
+    pub misses: u64,
+    /// Number of evictions
+    pub evictions: u64,
+    /// Current cache size
+    pub current_size: usize,
+    /// Maximum cache size
+    pub max_size: usize,
+    /// Cache hit rate
+    pub hit_rate: f64,
+}
+
+/// Least recently used cache of paragraph layouts
+///
+/// Entries are never stale by construction except when something outside
+/// the key changes, such as a font being replaced under the same ID; call
+/// [`LayoutCache::clear`] then.
+pub struct LayoutCache {
+    inner: Mutex<CacheInner>,
+}
+
+struct CacheInner {
+    cache: LruCache<LayoutCacheKey, Arc<LayoutResult>>,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl LayoutCache {
+    /// Create a cache holding up to `max_entries` paragraph layouts
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_entries` is zero.
+    #[must_use]
+    pub fn new(max_entries: usize) -> Self {
+        let capacity =
+            NonZeroUsize::new(max_entries).expect("layout cache needs at least one entry");
+        Self {
+            inner: Mutex::new(CacheInner {
+                cache: LruCache::new(capacity),
+                hits: 0,
+                misses: 0,
+                evictions: 0,
+            }),
+        }
+    }
+
+    /// Lock the cache; a panic while holding it cannot leave an entry half
+    /// written, so poisoning is ignored
+    fn lock(&self) -> MutexGuard<'_, CacheInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Look up the layout of a paragraph
+    pub(crate) fn get(
+        &self,
+        text: &str,
+        shaped_text: &ShapedText,
+        shaping_options: &ShapingOptions,
+        options: &LayoutOptions,
+    ) -> Option<Arc<LayoutResult>> {
+        let key = LayoutCacheKey::new(text, shaped_text, shaping_options, options);
+        let mut inner = self.lock();
+        let result = inner.cache.get(&key).cloned();
+        if result.is_some() {
+            inner.hits += 1;
+        } else {
+            inner.misses += 1;
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(cache = "layout", hit = result.is_some());
+        result
+    }
+
+    /// Store the layout of a paragraph
+    pub(crate) fn insert(
+        &self,
+        text: &str,
+        shaped_text: &ShapedText,
+        shaping_options: &ShapingOptions,
+        options: &LayoutOptions,
+        result: Arc<LayoutResult>,
+    ) {
+        let key = LayoutCacheKey::new(text, shaped_text, shaping_options, options);
+        let mut inner = self.lock();
+        if let Some((old_key, _)) = inner.cache.push(key, result) {
+            if old_key != key {
+                inner.evictions += 1;
+            }
+        }
+    }
+
+    /// Remove all cached layouts
+    pub fn clear(&self) {
+        self.lock().cache.clear();
+    }
+
+    /// Cache statistics
+    #[allow(clippy::cast_precision_loss)] // The hit rate only needs to be approximate
+    pub fn stats(&self) -> LayoutCacheStats {
+        let inner = self.lock();
+        let mut stats = LayoutCacheStats {
+            hits: inner.hits,
+            misses: inner.misses,
+            evictions: inner.evictions,
+            current_size: inner.cache.len(),
+            max_size: inner.cache.cap().get(),
+            hit_rate: 0.0,
+        };
+        if stats.hits + stats.misses > 0 {
+            stats.hit_rate = stats.hits as f64 / (stats.hits + stats.misses) as f64;
+        }
+        stats
+    }
+}
+
+impl Default for LayoutCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_LAYOUT_CACHE_SIZE)
+    }
+}
+
+impl std::fmt::Debug for LayoutCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LayoutCache")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::JustificationMode;
+    use font_types::Direction;
+    use std::collections::HashMap;
+    use text_shaper::{
+        FontVariantCaps, FormatCharacters, Language, MatchContext, Script, TextTransform,
+    };
+
+    fn shaping_options(letter_spacing: f32) -> ShapingOptions {
+        ShapingOptions {
+            script: Script::Latin,
+            language: Language {
+                tag: String::from("en"),
+            },
+            direction: Direction::LeftToRight,
+            features: HashMap::new(),
+            kerning: true,
+            ligatures: true,
+            letter_spacing,
+            word_spacing: 0.0,
+            variant_caps: FontVariantCaps::Normal,
+            feature_ranges: Vec::new(),
+            synthesize_small_caps: true,
+            match_context: MatchContext::Privileged,
+            text_transform: TextTransform::None,
+            format_characters: FormatCharacters::Hidden,
+        }
+    }
+
+    fn shaped_text(width: f32) -> ShapedText {
+        ShapedText {
+            glyphs: vec![],
+            width,
+            height: 20.0,
+            baseline: 15.0,
+        }
+    }
+
+    fn result() -> Arc<LayoutResult> {
+        Arc::new(LayoutResult {
+            lines: vec![],
+            total_height: 20.0,
+            total_width: 50.0,
+            overflow: false,
+            vertical: false,
+            glyph_lines: vec![],
+            line_bounds: vec![],
+        })
+    }
+
+    #[test]
+    fn test_layout_cache_keys() {
+        // Given: A cached paragraph
+        let cache = LayoutCache::new(8);
+        let shaping = shaping_options(0.0);
+        let options = LayoutOptions::default();
+        cache.insert("Hello", &shaped_text(50.0), &shaping, &options, result());
+
+        // When/Then: Only the same text, style and options hit
+        assert!(cache
+            .get("Hello", &shaped_text(50.0), &shaping, &options)
+            .is_some());
+        assert!(cache
+            .get("Hullo", &shaped_text(50.0), &shaping, &options)
+            .is_none());
+        assert!(cache
+            .get("Hello", &shaped_text(60.0), &shaping, &options)
+            .is_none());
+        let spaced = shaping_options(1.0);
+        assert!(cache
+            .get("Hello", &shaped_text(50.0), &spaced, &options)
+            .is_none());
+        let centered = LayoutOptions {
+            justification: JustificationMode::Center,
+            ..LayoutOptions::default()
+        };
+        assert!(cache
+            .get("Hello", &shaped_text(50.0), &shaping, &centered)
+            .is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 4);
+        assert_eq!(stats.current_size, 1);
+    }
+
+    #[test]
+    fn test_layout_cache_evicts_and_clears() {
+        // Given: A cache with room for two paragraphs
+        let cache = LayoutCache::new(2);
+        let shaping = shaping_options(0.0);
+        let options = LayoutOptions::default();
+
+        // When: Three paragraphs are cached
+        for text in ["one", "two", "three"] {
+            cache.insert(text, &shaped_text(10.0), &shaping, &options, result());
+        }
+
+        // Then: The least recently used one is evicted
+        assert!(cache
+            .get("one", &shaped_text(10.0), &shaping, &options)
+            .is_none());
+        let stats = cache.stats();
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.current_size, 2);
+        assert_eq!(stats.max_size, 2);
+
+        // And: Clearing removes every entry but keeps the counters
+        cache.clear();
+        assert_eq!(cache.stats().current_size, 0);
+        assert_eq!(cache.stats().evictions, 1);
+    }
+}
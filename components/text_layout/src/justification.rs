@@ -1,20 +1,24 @@
 //! Text justification algorithms
 
 use crate::types::{JustificationMode, LayoutLine};
-use font_types::{Point, Vector};
+use font_types::{FontId, Point, PositionedGlyph, Vector};
+use text_shaper::KashidaGlyph;
 
 /// Text justifier for different alignment modes
 pub struct Justifier;
 
 impl Justifier {
     /// Create a new justifier
+    #[must_use]
     pub fn new() -> Self {
         Self
     }
 
     /// Apply justification to a line
     ///
-    /// Modifies the line's x_offset and adjusts glyph positions for full justification.
+    /// Modifies the line's `x_offset` and adjusts glyph positions for full justification.
+    /// `Start` and `End` align left-to-right text; resolve them with
+    /// [`JustificationMode::resolve`] for other directions.
     ///
     /// # Arguments
     ///
@@ -35,6 +39,7 @@ impl Justifier {
     ///     x_offset: 0.0,
     ///     y_offset: 0.0,
     ///     text_range: (0, 10),
+    ///     glyph_indices: vec![],
     /// };
     ///
     /// let justifier = Justifier::new();
@@ -43,21 +48,131 @@ impl Justifier {
     /// ```
     pub fn justify_line(&self, line: &mut LayoutLine, target_width: f32, mode: JustificationMode) {
         match mode {
-            JustificationMode::Left => {
+            JustificationMode::Left | JustificationMode::Start => {
                 line.x_offset = 0.0;
             }
-            JustificationMode::Right => {
+            JustificationMode::Right | JustificationMode::End => {
                 line.x_offset = target_width - line.width;
             }
             JustificationMode::Center => {
                 line.x_offset = (target_width - line.width) / 2.0;
             }
             JustificationMode::Justify => {
-                self.distribute_space(line, target_width);
+                Self::distribute_space(line, target_width);
             }
         }
     }
 
+    /// Justify a line by spacing out its characters
+    ///
+    /// The extra width is split evenly between neighbouring clusters where
+    /// either one is `expandable`, e.g. belongs to a Han, kana or Hangul
+    /// run. Lines without such gaps are justified between words like
+    /// [`JustificationMode::Justify`].
+    #[allow(clippy::cast_precision_loss)] // A line has far fewer than 2^23 clusters
+    pub fn justify_line_inter_character(
+        &self,
+        line: &mut LayoutLine,
+        target_width: f32,
+        expandable: impl Fn(&PositionedGlyph) -> bool,
+    ) {
+        let extra_space = target_width - line.width;
+        let gaps: Vec<bool> = line
+            .glyphs
+            .windows(2)
+            .map(|pair| {
+                pair[0].cluster != pair[1].cluster && (expandable(&pair[0]) || expandable(&pair[1]))
+            })
+            .collect();
+        let gap_count = gaps.iter().filter(|&&gap| gap).count();
+        if extra_space <= 0.0 || gap_count == 0 {
+            Self::distribute_space(line, target_width);
+            return;
+        }
+
+        let space_per_gap = extra_space / gap_count as f32;
+        let mut shift = 0.0;
+        for (glyph, gap_before) in line
+            .glyphs
+            .iter_mut()
+            .zip(std::iter::once(false).chain(gaps))
+        {
+            if gap_before {
+                shift += space_per_gap;
+            }
+            glyph.position.x += shift;
+        }
+
+        line.x_offset = 0.0;
+        line.width = target_width;
+    }
+
+    /// Justify a line by elongating joined Arabic letters (kashida)
+    ///
+    /// `kashida_points` are sorted byte offsets of letters joined to the
+    /// letter before them, from [`text_shaper::kashida_points`] on the text
+    /// the line's clusters refer to. The extra width is split evenly over
+    /// the points found in right-to-left runs of `kashida`'s font and
+    /// filled with tatweel glyphs, overlapping where it isn't a multiple of
+    /// the tatweel advance. Lines without such points are justified between
+    /// words like [`JustificationMode::Justify`].
+    #[allow(clippy::cast_precision_loss)] // Kashida and tatweel counts stay small
+    pub fn justify_line_kashida(
+        &self,
+        line: &mut LayoutLine,
+        kashida_points: &[usize],
+        target_width: f32,
+        kashida: &KashidaGlyph,
+    ) {
+        let extra_space = target_width - line.width;
+        let insertions = kashida_insertions(line, kashida_points, kashida.font_id);
+        if extra_space <= 0.0 || kashida.advance <= 0.0 || insertions.is_empty() {
+            Self::distribute_space(line, target_width);
+            return;
+        }
+
+        let per_point = extra_space / insertions.len() as f32;
+        // A positive ratio, rounded up so the tatweels meet
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let tatweels = (per_point / kashida.advance).ceil() as usize;
+        let step = per_point / tatweels as f32;
+
+        let glyphs = std::mem::take(&mut line.glyphs);
+        let indices = std::mem::take(&mut line.glyph_indices);
+        let mut shift = 0.0;
+        for (index, mut glyph) in glyphs.iter().cloned().enumerate() {
+            glyph.position.x += shift;
+            let mut pen = glyph.position.x - glyph.offset.x + glyph.advance.x;
+            line.glyphs.push(glyph);
+            line.glyph_indices.extend(indices.get(index));
+            if insertions.binary_search(&index).is_err() {
+                continue;
+            }
+
+            // Tatweels extend the letter before the point, which follows
+            // visually in right-to-left text
+            let letter = index + 1;
+            for _ in 0..tatweels {
+                line.glyphs.push(PositionedGlyph {
+                    glyph_id: kashida.glyph_id,
+                    font_id: kashida.font_id,
+                    position: Point { x: pen, y: 0.0 },
+                    advance: Vector { x: step, y: 0.0 },
+                    offset: Vector { x: 0.0, y: 0.0 },
+                    scale: 1.0,
+                    cluster: glyphs[letter].cluster,
+                    unsafe_to_break: true,
+                });
+                line.glyph_indices.extend(indices.get(letter));
+                pen += step;
+                shift += step;
+            }
+        }
+
+        line.x_offset = 0.0;
+        line.width = target_width;
+    }
+
     /// Distribute space evenly across a line (full justification)
     ///
     /// For full justification, we distribute the extra space between words
@@ -67,7 +182,8 @@ impl Justifier {
     ///
     /// * `line` - The layout line to justify
     /// * `target_width` - The target width to justify to
-    fn distribute_space(&self, line: &mut LayoutLine, target_width: f32) {
+    #[allow(clippy::cast_precision_loss)] // A line has far fewer than 2^23 word gaps
+    fn distribute_space(line: &mut LayoutLine, target_width: f32) {
         // Calculate extra space needed
         let extra_space = target_width - line.width;
 
@@ -79,7 +195,7 @@ impl Justifier {
 
         // Count the number of spaces (gaps) between glyphs
         // We'll use advance vectors to identify potential break points
-        let gap_count = self.count_justification_gaps(line);
+        let gap_count = Self::count_justification_gaps(line);
 
         // If no gaps (single word), use left alignment
         if gap_count == 0 {
@@ -127,7 +243,7 @@ impl Justifier {
     /// # Returns
     ///
     /// Number of gaps between words
-    fn count_justification_gaps(&self, line: &LayoutLine) -> usize {
+    fn count_justification_gaps(line: &LayoutLine) -> usize {
         if line.glyphs.is_empty() {
             return 0;
         }
@@ -164,6 +280,28 @@ impl Justifier {
         lines: &mut [LayoutLine],
         target_width: f32,
         mode: JustificationMode,
+    ) {
+        self.justify_lines_by(lines, |_| target_width, mode);
+    }
+
+    /// Justify multiple lines, each to its own target width
+    ///
+    /// Like [`Justifier::justify_lines`], with `target_width` giving the
+    /// width of the line at each index, e.g. to leave room for indents.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "text_layout::justify_lines",
+            level = "debug",
+            skip_all,
+            fields(lines = lines.len())
+        )
+    )]
+    pub fn justify_lines_by(
+        &self,
+        lines: &mut [LayoutLine],
+        target_width: impl Fn(usize) -> f32,
+        mode: JustificationMode,
     ) {
         if lines.is_empty() {
             return;
@@ -176,17 +314,42 @@ impl Justifier {
             lines.len()
         };
 
-        for i in 0..lines_to_justify {
-            self.justify_line(&mut lines[i], target_width, mode);
+        for (index, line) in lines[..lines_to_justify].iter_mut().enumerate() {
+            self.justify_line(line, target_width(index), mode);
         }
 
         // Last line in justified mode uses left alignment
-        if mode == JustificationMode::Justify && !lines.is_empty() {
-            lines.last_mut().unwrap().x_offset = 0.0;
+        if mode == JustificationMode::Justify {
+            if let Some(last) = lines.last_mut() {
+                last.x_offset = 0.0;
+            }
         }
     }
 }
 
+/// Indices of the line glyphs after which tatweels go, in visual order
+///
+/// In right-to-left text the letter at a kashida point is visually left of
+/// the letter before it, so tatweels follow the last glyph of the point's
+/// cluster when the next glyph belongs to an earlier cluster. Both glyphs
+/// must come from the kashida font.
+fn kashida_insertions(line: &LayoutLine, kashida_points: &[usize], font_id: FontId) -> Vec<usize> {
+    line.glyphs
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| {
+            let (glyph, next) = (&pair[0], &pair[1]);
+            glyph.font_id == font_id
+                && next.font_id == font_id
+                && next.cluster < glyph.cluster
+                && kashida_points
+                    .binary_search(&(glyph.cluster as usize))
+                    .is_ok()
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
 impl Default for Justifier {
     fn default() -> Self {
         Self::new()
@@ -194,9 +357,10 @@ impl Default for Justifier {
 }
 
 #[cfg(test)]
+#[allow(clippy::float_cmp)]
 mod tests {
     use super::*;
-    use font_types::{GlyphId, PositionedGlyph};
+    use font_types::{GlyphId, Point, PositionedGlyph, Vector};
 
     fn create_test_glyph(x: f32, advance_x: f32) -> PositionedGlyph {
         PositionedGlyph {
@@ -208,6 +372,9 @@ mod tests {
                 y: 0.0,
             },
             offset: Vector { x: 0.0, y: 0.0 },
+            scale: 1.0,
+            cluster: 0,
+            unsafe_to_break: false,
         }
     }
 
@@ -224,12 +391,14 @@ mod tests {
             x_offset: 0.0,
             y_offset: 0.0,
             text_range: (0, 10),
+            glyph_indices: vec![],
         }
     }
 
     // ========== Justifier Creation Tests ==========
 
     #[test]
+    #[allow(clippy::default_constructed_unit_structs)]
     fn test_justifier_creation() {
         // Given: Creating a justifier
         // When: Using new() or default()
@@ -410,6 +579,7 @@ mod tests {
             x_offset: 0.0,
             y_offset: 0.0,
             text_range: (0, 0),
+            glyph_indices: vec![],
         };
 
         justifier.justify_line(&mut line, 100.0, JustificationMode::Justify);
@@ -432,6 +602,130 @@ mod tests {
         assert_eq!(line.x_offset, 0.0);
     }
 
+    // ========== Inter-Character Justification Tests ==========
+
+    #[test]
+    fn test_justify_inter_character_spaces_clusters() {
+        // Given: Three ideographs, the last with a mark in its cluster
+        let justifier = Justifier::new();
+        let glyph = |x, cluster| PositionedGlyph {
+            cluster,
+            ..create_test_glyph(x, 10.0)
+        };
+        let mut line = create_test_line(30.0);
+        line.glyphs = vec![
+            glyph(0.0, 0),
+            glyph(10.0, 3),
+            glyph(20.0, 6),
+            PositionedGlyph {
+                advance: Vector { x: 0.0, y: 0.0 },
+                ..glyph(30.0, 6)
+            },
+        ];
+
+        // When: Justifying 20px wider
+        justifier.justify_line_inter_character(&mut line, 50.0, |_| true);
+
+        // Then: Both cluster boundaries widen equally and the mark stays put
+        let xs: Vec<f32> = line.glyphs.iter().map(|g| g.position.x).collect();
+        assert_eq!(xs, [0.0, 20.0, 40.0, 50.0]);
+        assert_eq!(line.width, 50.0);
+    }
+
+    #[test]
+    fn test_justify_inter_character_skips_other_scripts() {
+        // Given: A line whose glyphs are all outside CJK runs
+        let justifier = Justifier::new();
+        let mut inter_character = create_test_line(30.0);
+        let mut inter_word = create_test_line(30.0);
+
+        // When: Justifying it by characters
+        justifier.justify_line_inter_character(&mut inter_character, 50.0, |_| false);
+        justifier.justify_line(&mut inter_word, 50.0, JustificationMode::Justify);
+
+        // Then: It is justified between words instead
+        let xs = |line: &LayoutLine| line.glyphs.iter().map(|g| g.position.x).collect::<Vec<_>>();
+        assert_eq!(xs(&inter_character), xs(&inter_word));
+    }
+
+    // ========== Kashida Justification Tests ==========
+
+    /// Right-to-left line of three joined letters at byte offsets 0, 2 and
+    /// 4, in visual order
+    fn create_arabic_line() -> LayoutLine {
+        let glyph = |x, cluster| PositionedGlyph {
+            cluster,
+            ..create_test_glyph(x, 10.0)
+        };
+        LayoutLine {
+            glyphs: vec![glyph(0.0, 4), glyph(10.0, 2), glyph(20.0, 0)],
+            width: 30.0,
+            height: 20.0,
+            baseline: 15.0,
+            x_offset: 0.0,
+            y_offset: 0.0,
+            text_range: (0, 6),
+            glyph_indices: vec![2, 1, 0],
+        }
+    }
+
+    fn tatweel() -> KashidaGlyph {
+        KashidaGlyph {
+            font_id: 0,
+            glyph_id: GlyphId { id: 99 },
+            advance: 8.0,
+        }
+    }
+
+    #[test]
+    fn test_justify_kashida_elongates_at_points() {
+        // Given: A line with a kashida point before its last letter
+        let justifier = Justifier::new();
+        let mut line = create_arabic_line();
+
+        // When: Justifying it 20px wider with an 8px tatweel
+        justifier.justify_line_kashida(&mut line, &[4], 50.0, &tatweel());
+
+        // Then: Three overlapping tatweels join the last two letters
+        let ids: Vec<u32> = line.glyphs.iter().map(|g| g.glyph_id.id).collect();
+        assert_eq!(ids, [1, 99, 99, 99, 1, 1]);
+        let xs: Vec<f32> = line.glyphs.iter().map(|g| g.position.x).collect();
+        let step = 20.0 / 3.0;
+        for (x, expected) in xs
+            .iter()
+            .zip([0.0, 10.0, 10.0 + step, 10.0 + 2.0 * step, 30.0, 40.0])
+        {
+            assert!((x - expected).abs() < 1e-4, "{xs:?}");
+        }
+        // Tatweels belong to the letter they extend
+        assert_eq!(line.glyphs[1].cluster, 2);
+        assert_eq!(line.glyph_indices, [2, 1, 1, 1, 1, 0]);
+        assert_eq!(line.width, 50.0);
+    }
+
+    #[test]
+    fn test_justify_kashida_needs_matching_points_and_font() {
+        // Given: A kashida point that isn't in the line, and a tatweel from
+        // another font
+        let justifier = Justifier::new();
+        let other_font = KashidaGlyph {
+            font_id: 3,
+            ..tatweel()
+        };
+
+        // When: Justifying with either
+        let mut no_points = create_arabic_line();
+        justifier.justify_line_kashida(&mut no_points, &[1], 50.0, &tatweel());
+        let mut wrong_font = create_arabic_line();
+        justifier.justify_line_kashida(&mut wrong_font, &[4], 50.0, &other_font);
+
+        // Then: No tatweels are inserted and the words are spread instead
+        for line in [no_points, wrong_font] {
+            assert_eq!(line.glyphs.len(), 3);
+            assert_eq!(line.width, 50.0);
+        }
+    }
+
     // ========== Multiple Lines Justification Tests ==========
 
     #[test]
@@ -520,7 +814,6 @@ mod tests {
         // Given: Empty line
         // When: Counting justification gaps
         // Then: Should return 0
-        let justifier = Justifier::new();
         let line = LayoutLine {
             glyphs: vec![],
             width: 0.0,
@@ -529,9 +822,10 @@ mod tests {
             x_offset: 0.0,
             y_offset: 0.0,
             text_range: (0, 0),
+            glyph_indices: vec![],
         };
 
-        let gap_count = justifier.count_justification_gaps(&line);
+        let gap_count = Justifier::count_justification_gaps(&line);
         assert_eq!(gap_count, 0);
     }
 
@@ -540,10 +834,9 @@ mod tests {
         // Given: Line with glyphs
         // When: Counting justification gaps
         // Then: Should return at least 1 for non-empty lines
-        let justifier = Justifier::new();
         let line = create_test_line(80.0);
 
-        let gap_count = justifier.count_justification_gaps(&line);
+        let gap_count = Justifier::count_justification_gaps(&line);
         assert!(gap_count >= 1);
     }
 }
@@ -1,6 +1,9 @@
 //! Line breaking algorithm (Unicode UAX #14)
 
+use std::ops::Range;
+
 use crate::types::LineBreak;
+use font_types::PositionedGlyph;
 use unicode_linebreak::{linebreaks, BreakOpportunity};
 
 /// Line breaker implementing Unicode UAX #14
@@ -8,6 +11,7 @@ pub struct LineBreaker;
 
 impl LineBreaker {
     /// Create a new line breaker
+    #[must_use]
     pub fn new() -> Self {
         Self
     }
@@ -35,6 +39,16 @@ impl LineBreaker {
     /// let breaks = breaker.find_breaks("Hello world");
     /// // Will find break opportunity after "Hello "
     /// ```
+    #[must_use]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "text_layout::find_breaks",
+            level = "debug",
+            skip_all,
+            fields(text_len = text.len())
+        )
+    )]
     pub fn find_breaks(&self, text: &str) -> Vec<LineBreak> {
         let mut breaks = Vec::new();
 
@@ -59,6 +73,7 @@ impl LineBreaker {
     /// # Returns
     ///
     /// Vector of line breaks within the specified range
+    #[must_use]
     pub fn find_breaks_in_range(&self, text: &str, start: usize, end: usize) -> Vec<LineBreak> {
         self.find_breaks(text)
             .into_iter()
@@ -77,6 +92,7 @@ impl LineBreaker {
     ///
     /// `Some(required)` if there's a break at this offset, where `required`
     /// indicates if it's a mandatory break. `None` if no break at this position.
+    #[must_use]
     pub fn is_break_at(&self, text: &str, offset: usize) -> Option<bool> {
         self.find_breaks(text)
             .into_iter()
@@ -85,6 +101,45 @@ impl LineBreaker {
     }
 }
 
+/// A run of consecutive glyphs shaped from the same cluster of text
+///
+/// Lines only break between clusters, so a ligature, an emoji ZWJ sequence
+/// or a base character with its combining marks always stays on one line.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GlyphCluster {
+    /// Indices of the cluster's glyphs
+    pub glyphs: Range<usize>,
+    /// Byte range of the cluster's text
+    pub text: Range<usize>,
+}
+
+/// Group `glyphs` into clusters
+///
+/// Each cluster's text runs from its `cluster` offset to the next larger
+/// cluster offset among the glyphs, or to the end of `text`.
+pub(crate) fn glyph_clusters(text: &str, glyphs: &[PositionedGlyph]) -> Vec<GlyphCluster> {
+    let mut starts: Vec<usize> = glyphs.iter().map(|glyph| glyph.cluster as usize).collect();
+    starts.sort_unstable();
+    starts.dedup();
+    let end_of = |start: usize| {
+        let next = starts.partition_point(|&offset| offset <= start);
+        starts.get(next).copied().unwrap_or(text.len()).max(start)
+    };
+
+    let mut clusters: Vec<GlyphCluster> = Vec::new();
+    for (index, glyph) in glyphs.iter().enumerate() {
+        let start = glyph.cluster as usize;
+        match clusters.last_mut() {
+            Some(last) if last.text.start == start => last.glyphs.end = index + 1,
+            _ => clusters.push(GlyphCluster {
+                glyphs: index..index + 1,
+                text: start..end_of(start),
+            }),
+        }
+    }
+    clusters
+}
+
 impl Default for LineBreaker {
     fn default() -> Self {
         Self::new()
@@ -98,6 +153,7 @@ mod tests {
     // ========== Basic Line Breaking Tests ==========
 
     #[test]
+    #[allow(clippy::default_constructed_unit_structs)]
     fn test_line_breaker_creation() {
         // Given: Creating a line breaker
         // When: Using new() or default()
@@ -333,6 +389,23 @@ mod tests {
         assert!(has_optional); // Space breaks are optional
     }
 
+    #[test]
+    fn test_soft_hyphen_and_zero_width_space_allow_breaks() {
+        // Given: Words joined by a soft hyphen and a zero-width space
+        let breaker = LineBreaker::new();
+        let text = "hy\u{ad}phen zero\u{200b}width";
+
+        // When: Finding line breaks
+        let breaks = breaker.find_breaks(text);
+
+        // Then: Each may break after the control character
+        for offset in [4, 16] {
+            let at = breaks.iter().find(|b| b.offset == offset).unwrap();
+            assert!(!at.required);
+        }
+        assert_eq!(breaker.is_break_at(text, 2), None);
+    }
+
     // ========== Edge Case Tests ==========
 
     #[test]
@@ -0,0 +1,392 @@
+//! Math layout helpers built on the OpenType MATH table
+//!
+//! A math layout engine (e.g. for `MathML`) needs two things from the font
+//! beyond ordinary text shaping: how much to shrink nested scripts, and how to
+//! build delimiters, radicals and arrows of arbitrary size. This module
+//! computes both from a parsed [`MathTable`]; positioning the rest of a
+//! formula uses the table's constants directly.
+
+use font_parser::types::GlyphId as FontGlyphId;
+use font_parser::{GlyphAssembly, GlyphPart, GlyphVariant, MathConstants, MathTable};
+use font_types::GlyphId;
+
+/// Scale of the first script level when the font doesn't give one (`TeX`'s
+/// default)
+const DEFAULT_SCRIPT_SCALE: f32 = 0.71;
+
+/// Scale of the second script level when the font doesn't give one
+const DEFAULT_SCRIPT_SCRIPT_SCALE: f32 = 0.5;
+
+/// Most glyphs a stretched assembly is built from, bounding the extender
+/// repeats for absurd target sizes
+pub const MAX_ASSEMBLY_GLYPHS: usize = 1024;
+
+/// Font size multiplier for `script_level` levels of script nesting
+///
+/// Level 0 is the base size; levels 1 and 2 use the font's
+/// `scriptPercentScaleDown` and `scriptScriptPercentScaleDown`, and each
+/// deeper level shrinks by a further 0.71 as in CSS `math-depth`.
+#[must_use]
+pub fn script_scale(constants: &MathConstants, script_level: u32) -> f32 {
+    let percent = |value: i16, default: f32| {
+        if value > 0 {
+            f32::from(value) / 100.0
+        } else {
+            default
+        }
+    };
+    match script_level {
+        0 => 1.0,
+        1 => percent(constants.script_percent_scale_down, DEFAULT_SCRIPT_SCALE),
+        level => {
+            let deeper = i32::try_from(level - 2).unwrap_or(i32::MAX);
+            percent(
+                constants.script_script_percent_scale_down,
+                DEFAULT_SCRIPT_SCRIPT_SCALE,
+            ) * DEFAULT_SCRIPT_SCALE.powi(deeper)
+        }
+    }
+}
+
+/// Direction a glyph is stretched in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StretchAxis {
+    /// Grow upwards, e.g. parentheses, braces and radicals
+    Vertical,
+    /// Grow rightwards, e.g. arrows and over/under braces
+    Horizontal,
+}
+
+/// One glyph of a stretched glyph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StretchedPart {
+    /// Glyph to draw
+    pub glyph: GlyphId,
+    /// Position along the stretch axis, in font units from the bottom (or
+    /// left) of the stretched glyph
+    pub offset: i32,
+}
+
+/// A glyph stretched to cover a target size
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StretchedGlyph {
+    /// Glyphs to draw: a single variant, or assembly parts bottom to top
+    /// (left to right)
+    pub parts: Vec<StretchedPart>,
+    /// Size along the stretch axis, in font units
+    pub size: i32,
+    /// Italic correction of the result, in font units
+    pub italics_correction: i16,
+}
+
+impl StretchedGlyph {
+    /// Whether the result is an assembly of several parts rather than a
+    /// single variant
+    #[must_use]
+    pub fn is_assembly(&self) -> bool {
+        self.parts.len() > 1
+    }
+}
+
+/// Stretch `glyph` to cover at least `target_size` font units along `axis`
+///
+/// Uses the smallest variant that is large enough, otherwise builds the
+/// glyph from its assembly parts, repeating extenders and spreading connector
+/// overlaps so the result is as close to `target_size` as the parts allow.
+/// Without an assembly the largest variant is used. Returns `None` if the
+/// font can't stretch `glyph` along `axis`.
+#[must_use]
+pub fn stretch_glyph(
+    math: &MathTable,
+    glyph: GlyphId,
+    target_size: i32,
+    axis: StretchAxis,
+) -> Option<StretchedGlyph> {
+    let glyph = FontGlyphId::try_from(glyph.id).ok()?;
+    let constructions = match axis {
+        StretchAxis::Vertical => &math.variants.vertical,
+        StretchAxis::Horizontal => &math.variants.horizontal,
+    };
+    let construction = constructions.get(&glyph)?;
+
+    let variant = |variant: &GlyphVariant| StretchedGlyph {
+        parts: vec![StretchedPart {
+            glyph: GlyphId {
+                id: u32::from(variant.glyph),
+            },
+            offset: 0,
+        }],
+        size: i32::from(variant.advance),
+        italics_correction: math
+            .glyph_info
+            .italics_correction(variant.glyph)
+            .unwrap_or(0),
+    };
+    if let Some(large_enough) = construction
+        .variants
+        .iter()
+        .find(|candidate| i32::from(candidate.advance) >= target_size)
+    {
+        return Some(variant(large_enough));
+    }
+    match &construction.assembly {
+        Some(assembly) if !assembly.parts.is_empty() => Some(assemble(
+            assembly,
+            i32::from(math.variants.min_connector_overlap),
+            target_size,
+        )),
+        _ => construction.variants.last().map(variant),
+    }
+}
+
+/// Build an assembly of at least `target_size`, or as large as
+/// [`MAX_ASSEMBLY_GLYPHS`] parts allow
+fn assemble(assembly: &GlyphAssembly, min_overlap: i32, target_size: i32) -> StretchedGlyph {
+    let (extenders, fixed): (Vec<_>, Vec<_>) =
+        assembly.parts.iter().partition(|part| part.is_extender);
+    let advance_sum = |parts: &[&GlyphPart]| -> i32 {
+        parts.iter().map(|part| i32::from(part.full_advance)).sum()
+    };
+    let fixed_size = advance_sum(&fixed);
+    let extender_size = advance_sum(&extenders);
+    let fixed_count = i32::try_from(fixed.len()).unwrap_or(i32::MAX);
+    let extender_count = i32::try_from(extenders.len()).unwrap_or(i32::MAX);
+
+    // Smallest number of extender repeats reaching the target with minimum
+    // overlaps: each repeat adds the extenders less one overlap apiece. An
+    // assembly made only of extenders needs them at least once.
+    let base_size = fixed_size - min_overlap * (fixed_count - 1);
+    let growth = extender_size - min_overlap * extender_count;
+    let min_repeats = i32::from(fixed.is_empty());
+    let max_repeats = i32::try_from(MAX_ASSEMBLY_GLYPHS.saturating_sub(fixed.len()))
+        .unwrap_or(i32::MAX)
+        / extender_count.max(1);
+    let repeats = if extender_count == 0 {
+        0
+    } else if growth <= 0 {
+        min_repeats
+    } else {
+        ((target_size - base_size).max(0) + growth - 1) / growth
+    }
+    .clamp(min_repeats, max_repeats.max(min_repeats));
+
+    let mut parts: Vec<&GlyphPart> = Vec::new();
+    for part in &assembly.parts {
+        let count = if part.is_extender { repeats } else { 1 };
+        parts.extend((0..count).map(|_| part));
+    }
+
+    // Spread the excess over the joints, but never overlap connectors by
+    // more than their length or less than the font's minimum
+    let joints = i32::try_from(parts.len()).unwrap_or(i32::MAX) - 1;
+    let natural_size: i32 = parts.iter().map(|part| i32::from(part.full_advance)).sum();
+    let overlap = if joints > 0 {
+        let max_overlap = parts
+            .windows(2)
+            .map(|pair| {
+                i32::from(
+                    pair[0]
+                        .end_connector_length
+                        .min(pair[1].start_connector_length),
+                )
+            })
+            .min()
+            .unwrap_or(0);
+        ((natural_size - target_size) / joints)
+            .min(max_overlap)
+            .max(min_overlap)
+    } else {
+        0
+    };
+
+    let mut offset = 0;
+    let mut stretched = Vec::with_capacity(parts.len());
+    for part in &parts {
+        stretched.push(StretchedPart {
+            glyph: GlyphId {
+                id: u32::from(part.glyph),
+            },
+            offset,
+        });
+        offset += i32::from(part.full_advance) - overlap;
+    }
+    StretchedGlyph {
+        parts: stretched,
+        size: natural_size - overlap * joints.max(0),
+        italics_correction: assembly.italics_correction,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use font_parser::{GlyphConstruction, MathVariants};
+
+    fn part(glyph: u16, start: u16, end: u16, full_advance: u16, is_extender: bool) -> GlyphPart {
+        GlyphPart {
+            glyph,
+            start_connector_length: start,
+            end_connector_length: end,
+            full_advance,
+            is_extender,
+        }
+    }
+
+    /// A parenthesis (glyph 20) with two variants and a bottom, extender,
+    /// top assembly; an arrow (glyph 40) with one variant and no assembly
+    fn math_table() -> MathTable {
+        let mut variants = MathVariants {
+            min_connector_overlap: 20,
+            ..Default::default()
+        };
+        variants.vertical.insert(
+            20,
+            GlyphConstruction {
+                variants: vec![
+                    GlyphVariant {
+                        glyph: 20,
+                        advance: 1000,
+                    },
+                    GlyphVariant {
+                        glyph: 21,
+                        advance: 1500,
+                    },
+                ],
+                assembly: Some(GlyphAssembly {
+                    italics_correction: 7,
+                    parts: vec![
+                        part(30, 0, 100, 500, false),
+                        part(31, 100, 100, 400, true),
+                        part(32, 100, 0, 500, false),
+                    ],
+                }),
+            },
+        );
+        variants.horizontal.insert(
+            40,
+            GlyphConstruction {
+                variants: vec![GlyphVariant {
+                    glyph: 41,
+                    advance: 800,
+                }],
+                assembly: None,
+            },
+        );
+        let mut table = MathTable {
+            variants,
+            ..Default::default()
+        };
+        table.glyph_info.italics_corrections.insert(21, 12);
+        table
+    }
+
+    #[test]
+    fn test_script_scale() {
+        // Given constants with and without script percentages
+        // When scaling nested scripts
+        // Then the font's percentages apply, with TeX defaults otherwise
+        let constants = MathConstants {
+            script_percent_scale_down: 70,
+            script_script_percent_scale_down: 50,
+            ..Default::default()
+        };
+        assert_eq!(script_scale(&constants, 0), 1.0);
+        assert!((script_scale(&constants, 1) - 0.7).abs() < 1e-6);
+        assert!((script_scale(&constants, 2) - 0.5).abs() < 1e-6);
+        assert!((script_scale(&constants, 3) - 0.355).abs() < 1e-6);
+
+        let defaults = MathConstants::default();
+        assert!((script_scale(&defaults, 1) - 0.71).abs() < 1e-6);
+        assert!((script_scale(&defaults, 2) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stretch_uses_smallest_large_enough_variant() {
+        // Given a delimiter with 1000 and 1500 unit variants
+        // When stretching it to 1200 units
+        // Then the 1500 unit variant is used, with its italic correction
+        let stretched = stretch_glyph(
+            &math_table(),
+            GlyphId { id: 20 },
+            1200,
+            StretchAxis::Vertical,
+        )
+        .unwrap();
+        assert!(!stretched.is_assembly());
+        assert_eq!(stretched.parts[0].glyph, GlyphId { id: 21 });
+        assert_eq!(stretched.size, 1500);
+        assert_eq!(stretched.italics_correction, 12);
+    }
+
+    #[test]
+    fn test_stretch_assembles_parts() {
+        // Given a delimiter whose variants stop at 1500 units
+        // When stretching it to 3000 units
+        // Then extenders repeat between the ends, overlapping evenly to
+        // just cover the target
+        let stretched = stretch_glyph(
+            &math_table(),
+            GlyphId { id: 20 },
+            3000,
+            StretchAxis::Vertical,
+        )
+        .unwrap();
+        let glyphs: Vec<u32> = stretched.parts.iter().map(|part| part.glyph.id).collect();
+        assert_eq!(glyphs, vec![30, 31, 31, 31, 31, 31, 31, 32]);
+        // 3400 units of parts over 7 joints overlapping by 57
+        assert_eq!(stretched.size, 3001);
+        assert_eq!(stretched.parts[1].offset, 443);
+        assert_eq!(stretched.parts[2].offset, 786);
+        assert_eq!(stretched.parts[7].offset, 3001 - 500);
+        assert_eq!(stretched.italics_correction, 7);
+    }
+
+    #[test]
+    fn test_stretch_small_assembly() {
+        // Given a target just above the largest variant
+        // When assembling
+        // Then a few extenders suffice, overlapping to land near the target
+        let stretched = stretch_glyph(
+            &math_table(),
+            GlyphId { id: 20 },
+            1501,
+            StretchAxis::Vertical,
+        )
+        .unwrap();
+        assert_eq!(stretched.parts.len(), 4);
+        // 1800 units of parts over 3 joints overlapping by 99
+        assert_eq!(stretched.size, 1503);
+        assert_eq!(stretched.parts[1].offset, 401);
+    }
+
+    #[test]
+    fn test_stretch_caps_assembly_size() {
+        // Given an absurd target size
+        // When assembling
+        // Then the assembly stops at the glyph cap
+        let stretched = stretch_glyph(
+            &math_table(),
+            GlyphId { id: 20 },
+            i32::MAX / 2,
+            StretchAxis::Vertical,
+        )
+        .unwrap();
+        assert_eq!(stretched.parts.len(), MAX_ASSEMBLY_GLYPHS);
+    }
+
+    #[test]
+    fn test_stretch_without_assembly_or_construction() {
+        // Given an arrow with a single variant and no assembly
+        // When stretching beyond it, or along an axis it doesn't stretch on
+        // Then the largest variant is used, or nothing
+        let table = math_table();
+        let stretched =
+            stretch_glyph(&table, GlyphId { id: 40 }, 5000, StretchAxis::Horizontal).unwrap();
+        assert_eq!(stretched.parts[0].glyph, GlyphId { id: 41 });
+        assert_eq!(stretched.size, 800);
+
+        assert!(stretch_glyph(&table, GlyphId { id: 40 }, 5000, StretchAxis::Vertical).is_none());
+        assert!(stretch_glyph(&table, GlyphId { id: 70_000 }, 10, StretchAxis::Vertical).is_none());
+    }
+}
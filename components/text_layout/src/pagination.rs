@@ -0,0 +1,300 @@
+//! Splitting a laid out paragraph across pages or columns
+//!
+//! Printing and multi-column layout place a paragraph in fragments of a
+//! fixed block size. [`LayoutResult::paginate`] distributes whole lines
+//! over such fragments, never cutting one, and honors the CSS `orphans` and
+//! `widows` properties: the minimum number of lines left at the bottom of a
+//! fragment before a break, and at the top of the fragment after it.
+
+use crate::types::{LayoutLine, LayoutResult};
+
+/// Slack when checking that lines fit, so rounding in accumulated line
+/// heights doesn't push the last line of a full page to the next one
+const FIT_TOLERANCE: f32 = 0.01;
+
+/// Options of [`LayoutResult::paginate_with_options`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PaginationOptions {
+    /// Fewest lines left at the bottom of a fragment before a break (CSS
+    /// `orphans`)
+    pub orphans: usize,
+    /// Fewest lines carried to the top of the fragment after a break (CSS
+    /// `widows`)
+    pub widows: usize,
+    /// Space left in the first fragment, when the paragraph starts part
+    /// way down a page; `None` for a full page
+    pub first_fragment_height: Option<f32>,
+}
+
+impl Default for PaginationOptions {
+    /// The CSS initial values: two orphans and two widows
+    fn default() -> Self {
+        Self {
+            orphans: 2,
+            widows: 2,
+            first_fragment_height: None,
+        }
+    }
+}
+
+/// Lines of a paragraph placed in one page or column
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutFragment {
+    /// Lines of the fragment, moved so the first starts at the fragment's
+    /// top (or left edge, for vertical columns)
+    pub lines: Vec<LayoutLine>,
+    /// Index of the fragment's first line in the paginated result
+    pub first_line: usize,
+    /// Block extent of the lines: their height, or their width for
+    /// vertical columns
+    pub height: f32,
+    /// Byte range of the fragment's text
+    pub text_range: (usize, usize),
+    /// Whether earlier lines of the paragraph are in a previous fragment
+    pub continues_previous: bool,
+    /// Whether later lines of the paragraph are in a following fragment
+    pub continues_next: bool,
+}
+
+impl LayoutResult {
+    /// Split the lines across fragments `page_height` tall, with the CSS
+    /// default of two orphans and two widows
+    ///
+    /// See [`paginate_with_options`](Self::paginate_with_options).
+    #[must_use]
+    pub fn paginate(&self, page_height: f32) -> Vec<LayoutFragment> {
+        self.paginate_with_options(page_height, &PaginationOptions::default())
+    }
+
+    /// Split the lines across fragments `page_height` tall
+    ///
+    /// Each fragment takes as many whole lines as fit, and fewer if that
+    /// would leave less than `widows` lines for the last fragment. Where
+    /// orphans and widows can't both be satisfied on a full page, the page
+    /// is filled. A short first fragment (`first_fragment_height`) that
+    /// can't hold `orphans` lines is left empty, so the paragraph starts
+    /// on the next page. A line taller than a page gets a fragment of its
+    /// own. Vertical layouts are split between columns along their width.
+    #[must_use]
+    pub fn paginate_with_options(
+        &self,
+        page_height: f32,
+        options: &PaginationOptions,
+    ) -> Vec<LayoutFragment> {
+        let count = self.lines.len();
+        let mut fragments = Vec::new();
+        let mut start = 0;
+        let mut available = options.first_fragment_height.unwrap_or(page_height);
+        let mut full_page = options.first_fragment_height.is_none();
+        while start < count || fragments.is_empty() {
+            let fitting = self.lines_fitting(start, available);
+            let mut end = start + fitting;
+            if end < count {
+                let before_widows = end;
+                end = end.min(count.saturating_sub(options.widows));
+                if end < start + options.orphans.max(1) {
+                    end = if full_page {
+                        // Violate widows rather than leave the page short
+                        before_widows.max(start + 1)
+                    } else {
+                        start
+                    };
+                }
+            }
+            fragments.push(self.fragment(start, end, count));
+            start = end;
+            available = page_height;
+            full_page = true;
+        }
+        fragments
+    }
+
+    /// Number of lines from `start` fitting in `available`
+    fn lines_fitting(&self, start: usize, available: f32) -> usize {
+        let Some(first) = self.lines.get(start) else {
+            return 0;
+        };
+        let origin = self.block_offset(first);
+        self.lines[start..]
+            .iter()
+            .take_while(|line| {
+                self.block_offset(line) + line.height - origin <= available + FIT_TOLERANCE
+            })
+            .count()
+    }
+
+    /// Offset of a line along the block axis
+    fn block_offset(&self, line: &LayoutLine) -> f32 {
+        if self.vertical {
+            line.x_offset
+        } else {
+            line.y_offset
+        }
+    }
+
+    fn fragment(&self, start: usize, end: usize, count: usize) -> LayoutFragment {
+        let lines = &self.lines[start..end];
+        let origin = lines.first().map_or(0.0, |line| self.block_offset(line));
+        let height = lines
+            .last()
+            .map_or(0.0, |line| self.block_offset(line) + line.height - origin);
+        let text_start = self.lines.get(start).map_or(0, |line| line.text_range.0);
+        let text_end = lines.last().map_or(text_start, |line| line.text_range.1);
+        let lines = lines
+            .iter()
+            .map(|line| {
+                let mut line = line.clone();
+                if self.vertical {
+                    line.x_offset -= origin;
+                } else {
+                    line.y_offset -= origin;
+                }
+                line
+            })
+            .collect();
+        LayoutFragment {
+            lines,
+            first_line: start,
+            height,
+            text_range: (text_start, text_end),
+            continues_previous: start > 0,
+            continues_next: end < count,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp, clippy::cast_precision_loss)]
+mod tests {
+    use super::*;
+
+    /// A paragraph of `count` lines, 10 tall and 5 bytes long each
+    fn paragraph(count: usize) -> LayoutResult {
+        let lines: Vec<LayoutLine> = (0..count)
+            .map(|index| LayoutLine {
+                glyphs: Vec::new(),
+                width: 50.0,
+                height: 10.0,
+                baseline: 8.0,
+                x_offset: 0.0,
+                y_offset: index as f32 * 10.0,
+                text_range: (index * 5, index * 5 + 5),
+                glyph_indices: Vec::new(),
+            })
+            .collect();
+        LayoutResult {
+            total_height: count as f32 * 10.0,
+            total_width: 50.0,
+            overflow: false,
+            vertical: false,
+            glyph_lines: Vec::new(),
+            line_bounds: Vec::new(),
+            lines,
+        }
+    }
+
+    fn line_counts(fragments: &[LayoutFragment]) -> Vec<usize> {
+        fragments
+            .iter()
+            .map(|fragment| fragment.lines.len())
+            .collect()
+    }
+
+    #[test]
+    fn test_paginate_fills_pages_with_whole_lines() {
+        // Given: Seven lines and pages three and a half lines tall
+        let result = paragraph(7);
+
+        // When: Paginating without widow or orphan control
+        let options = PaginationOptions {
+            orphans: 1,
+            widows: 1,
+            first_fragment_height: None,
+        };
+        let fragments = result.paginate_with_options(35.0, &options);
+
+        // Then: Pages take three lines each, restarting at the top
+        assert_eq!(line_counts(&fragments), [3, 3, 1]);
+        let second = &fragments[1];
+        assert_eq!(second.first_line, 3);
+        assert_eq!(second.lines[0].y_offset, 0.0);
+        assert_eq!(second.lines[2].y_offset, 20.0);
+        assert_eq!(second.height, 30.0);
+        assert_eq!(second.text_range, (15, 30));
+        assert!(second.continues_previous && second.continues_next);
+        assert!(!fragments[0].continues_previous);
+        assert!(!fragments[2].continues_next);
+    }
+
+    #[test]
+    fn test_paginate_moves_lines_to_avoid_widows() {
+        // Given: Four lines and pages three lines tall
+        let result = paragraph(4);
+
+        // When: Paginating with the default two widows
+        let fragments = result.paginate(30.0);
+
+        // Then: A line moves down so the last page isn't a lone line
+        assert_eq!(line_counts(&fragments), [2, 2]);
+
+        // And a paragraph that can't satisfy both fills the page
+        assert_eq!(line_counts(&paragraph(3).paginate(20.0)), [2, 1]);
+    }
+
+    #[test]
+    fn test_short_first_fragment_without_room_for_orphans_stays_empty() {
+        // Given: Three lines starting with room for one line on the page
+        let result = paragraph(3);
+        let options = PaginationOptions {
+            first_fragment_height: Some(15.0),
+            ..PaginationOptions::default()
+        };
+
+        // When: Paginating with the default two orphans
+        let fragments = result.paginate_with_options(30.0, &options);
+
+        // Then: The paragraph starts on the next page
+        assert_eq!(line_counts(&fragments), [0, 3]);
+        assert_eq!(fragments[0].text_range, (0, 0));
+        assert!(fragments[0].continues_next);
+        assert!(!fragments[1].continues_previous);
+    }
+
+    #[test]
+    fn test_paginate_keeps_oversized_lines_and_vertical_columns() {
+        // Given: Lines taller than the page, and a vertical layout
+        let tall = paragraph(2);
+        let mut vertical = paragraph(3);
+        vertical.vertical = true;
+        for (index, column) in vertical.lines.iter_mut().enumerate() {
+            column.x_offset = column.y_offset;
+            column.y_offset = 0.0;
+            assert_eq!(column.x_offset, index as f32 * 10.0);
+        }
+
+        // When: Paginating them
+        let tall_fragments = tall.paginate(5.0);
+        let columns = vertical.paginate_with_options(
+            20.0,
+            &PaginationOptions {
+                widows: 1,
+                ..PaginationOptions::default()
+            },
+        );
+
+        // Then: Each tall line gets its own fragment, and columns are split
+        // along the block axis
+        assert_eq!(line_counts(&tall_fragments), [1, 1]);
+        assert_eq!(line_counts(&columns), [2, 1]);
+        assert_eq!(columns[1].lines[0].x_offset, 0.0);
+    }
+
+    #[test]
+    fn test_paginate_empty_result_gives_one_empty_fragment() {
+        let fragments = paragraph(0).paginate(100.0);
+        assert_eq!(line_counts(&fragments), [0]);
+        assert!(!fragments[0].continues_next);
+    }
+}
@@ -1,9 +1,20 @@
 //! Paragraph layout engine
 
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::cache::{LayoutCache, LayoutCacheStats};
 use crate::justification::Justifier;
-use crate::line_breaker::LineBreaker;
-use crate::types::{LayoutError, LayoutLine, LayoutOptions, LayoutResult};
-use font_types::{PositionedGlyph, ShapedText};
+use crate::line_breaker::{glyph_clusters, GlyphCluster, LineBreaker};
+use crate::types::{
+    JustificationMode, JustificationStrategy, LayoutError, LayoutLine, LayoutOptions, LayoutResult,
+    TextDirection,
+};
+use font_types::{
+    Baseline, PositionedGlyph, RunMetrics, Script, ShapedParagraph, ShapedRun, ShapedText, Vector,
+};
+use text_shaper::{HyphenGlyph, ShapingOptions, SOFT_HYPHEN};
+use unicode_bidi::{BidiInfo, Level};
 
 /// Main paragraph layout engine
 ///
@@ -12,17 +23,117 @@ use font_types::{PositionedGlyph, ShapedText};
 pub struct ParagraphLayout {
     line_breaker: LineBreaker,
     justifier: Justifier,
+    /// Layouts of recent paragraphs, if caching is enabled
+    cache: Option<LayoutCache>,
 }
 
 impl ParagraphLayout {
     /// Create a new paragraph layout engine
+    #[must_use]
     pub fn new() -> Self {
         Self {
             line_breaker: LineBreaker::new(),
             justifier: Justifier::new(),
+            cache: None,
+        }
+    }
+
+    /// Create a paragraph layout engine caching up to `max_entries` layouts
+    /// for [`ParagraphLayout::layout_paragraph_cached`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_entries` is zero.
+    #[must_use]
+    pub fn with_cache(max_entries: usize) -> Self {
+        Self {
+            cache: Some(LayoutCache::new(max_entries)),
+            ..Self::new()
+        }
+    }
+
+    /// Layout a paragraph, reusing the cached result for the same text,
+    /// shaping options and layout options
+    ///
+    /// `shaping_options` are the options `shaped_text` was shaped with. The
+    /// cache can't see other changes that affect shaping, such as a font
+    /// being replaced; call [`ParagraphLayout::clear_cache`] after them.
+    /// Without a cache this is [`ParagraphLayout::layout_paragraph`]. Errors
+    /// are not cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`ParagraphLayout::layout_paragraph`].
+    pub fn layout_paragraph_cached(
+        &self,
+        text: &str,
+        shaped_text: &ShapedText,
+        shaping_options: &ShapingOptions,
+        options: &LayoutOptions,
+    ) -> Result<Arc<LayoutResult>, LayoutError> {
+        let Some(cache) = &self.cache else {
+            return self
+                .layout_paragraph(text, shaped_text, options)
+                .map(Arc::new);
+        };
+        if let Some(result) = cache.get(text, shaped_text, shaping_options, options) {
+            return Ok(result);
+        }
+        let result = Arc::new(self.layout_paragraph(text, shaped_text, options)?);
+        cache.insert(
+            text,
+            shaped_text,
+            shaping_options,
+            options,
+            Arc::clone(&result),
+        );
+        Ok(result)
+    }
+
+    /// Get layout cache statistics
+    ///
+    /// # Returns
+    ///
+    /// Cache statistics if caching is enabled, None otherwise
+    pub fn cache_stats(&self) -> Option<LayoutCacheStats> {
+        self.cache.as_ref().map(LayoutCache::stats)
+    }
+
+    /// Drop all cached layouts
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
         }
     }
 
+    /// Layout a paragraph shaped into runs, e.g. by font fallback
+    ///
+    /// The runs are laid out as one [`ShapedText`]; each glyph keeps the
+    /// font of its run, which [`LayoutLine::font_runs`] groups again per
+    /// line. The runs' scripts select the characters
+    /// [`JustificationStrategy::InterCharacter`] spaces out.
+    ///
+    /// Horizontal runs are aligned by the dominant baseline of the script
+    /// the paragraph starts with (see [`Script::dominant_baseline`]), placed
+    /// from each run's BASE table, and every line box grows to fit the
+    /// shifted runs on it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`ParagraphLayout::layout_paragraph`].
+    ///
+    /// [`LayoutLine::font_runs`]: crate::LayoutLine::font_runs
+    pub fn layout_shaped_paragraph(
+        &self,
+        text: &str,
+        paragraph: &ShapedParagraph,
+        options: &LayoutOptions,
+    ) -> Result<LayoutResult, LayoutError> {
+        let mut shaped_text = paragraph.to_shaped_text();
+        let runs = align_runs(paragraph, &mut shaped_text.glyphs);
+        self.layout_with_runs(text, &shaped_text, options, &runs)
+    }
+
     /// Layout a paragraph of shaped text
     ///
     /// Takes shaped text from the text shaper and lays it out into multiple
@@ -42,7 +153,8 @@ impl ParagraphLayout {
     ///
     /// Returns an error if:
     /// - The text is empty
-    /// - Layout options are invalid (e.g., negative width)
+    /// - Layout options are invalid (e.g., negative width, or margins and
+    ///   indent leaving no room for text)
     /// - Text cannot fit within constraints
     ///
     /// # Example
@@ -57,34 +169,85 @@ impl ParagraphLayout {
     /// # let shaped_text = ShapedText { glyphs: vec![], width: 0.0, height: 0.0, baseline: 0.0 };
     /// let result = layout.layout_paragraph("Hello world", &shaped_text, &options);
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "text_layout::layout_paragraph",
+            level = "debug",
+            skip_all,
+            fields(text_len = text.len(), glyphs = shaped_text.glyphs.len())
+        )
+    )]
     pub fn layout_paragraph(
         &self,
         text: &str,
         shaped_text: &ShapedText,
         options: &LayoutOptions,
+    ) -> Result<LayoutResult, LayoutError> {
+        self.layout_with_runs(text, shaped_text, options, &[])
+    }
+
+    /// Layout a paragraph shaped as `runs`
+    ///
+    /// `runs` is empty when the shaper didn't report them.
+    fn layout_with_runs(
+        &self,
+        text: &str,
+        shaped_text: &ShapedText,
+        options: &LayoutOptions,
+        runs: &[RunPlacement],
     ) -> Result<LayoutResult, LayoutError> {
         // Validate inputs
-        self.validate_inputs(text, options)?;
+        Self::validate_inputs(text, options)?;
 
         // Find line break opportunities
         let breaks = self.line_breaker.find_breaks(text);
 
-        // Break into lines based on max_width
-        let mut lines = self.break_into_lines(text, shaped_text, &breaks, options)?;
+        // Break into lines based on max_width; right-to-left text is broken
+        // in logical order and each line reordered for display
+        let rtl = options.direction == TextDirection::RightToLeft;
+        let mut lines = if rtl {
+            let (logical, source_indices) = logical_order(shaped_text);
+            let mut lines = Self::break_into_lines(text, &logical, &breaks, options);
+            let bidi = BidiInfo::new(text, Some(Level::rtl()));
+            for line in &mut lines {
+                for index in &mut line.glyph_indices {
+                    *index = source_indices[*index];
+                }
+                reorder_visually(&bidi, line);
+            }
+            lines
+        } else {
+            Self::break_into_lines(text, shaped_text, &breaks, options)
+        };
 
-        // Apply justification
-        self.justifier
-            .justify_lines(&mut lines, options.max_width, options.justification);
+        // Apply justification within each line's margins and indent
+        let justification = options.justification.resolve(options.direction);
+        self.justify(&mut lines, text, options, justification, runs);
+        // The last line of justified text is aligned to the start edge
+        if rtl && justification == JustificationMode::Justify {
+            let first_line = lines.len() == 1;
+            if let Some(last) = lines.last_mut() {
+                self.justifier.justify_line(
+                    last,
+                    options.line_width(first_line),
+                    JustificationMode::Right,
+                );
+            }
+        }
+        for (index, line) in lines.iter_mut().enumerate() {
+            line.x_offset += options.line_left(index == 0);
+        }
 
         // Calculate vertical positions
-        self.position_lines_vertically(&mut lines, options);
+        Self::position_lines_vertically(&mut lines, options, runs);
 
         // Calculate total dimensions
         let total_width = lines
             .iter()
             .map(|l| l.width + l.x_offset)
             .fold(0.0f32, f32::max);
-        let total_height = lines.last().map(|l| l.y_offset + l.height).unwrap_or(0.0);
+        let total_height = lines.last().map_or(0.0, |l| l.y_offset + l.height);
 
         // Check for overflow
         let overflow = if let Some(max_height) = options.max_height {
@@ -93,16 +256,79 @@ impl ParagraphLayout {
             false
         };
 
-        Ok(LayoutResult {
+        let mut result = LayoutResult {
+            glyph_lines: LayoutResult::glyph_lines(&lines),
+            line_bounds: Vec::new(),
             lines,
             total_height,
             total_width,
             overflow,
-        })
+            vertical: false,
+        };
+        result.line_bounds = result.line_boxes();
+        Ok(result)
+    }
+
+    /// Justify every line, stretching all but the last with the options'
+    /// justification strategy
+    fn justify(
+        &self,
+        lines: &mut [LayoutLine],
+        text: &str,
+        options: &LayoutOptions,
+        justification: JustificationMode,
+        runs: &[RunPlacement],
+    ) {
+        let strategy = options.justification_strategy;
+        if justification != JustificationMode::Justify
+            || strategy == JustificationStrategy::InterWord
+        {
+            self.justifier.justify_lines_by(
+                lines,
+                |index| options.line_width(index == 0),
+                justification,
+            );
+            return;
+        }
+
+        let kashida_points = match strategy {
+            JustificationStrategy::Kashida(_) => text_shaper::kashida_points(text),
+            _ => Vec::new(),
+        };
+        // Clusters take the script of their first character; characters
+        // common to several scripts (e.g. punctuation) take their run's
+        let cjk = |glyph: &PositionedGlyph| {
+            let cluster = glyph.cluster as usize;
+            text.get(cluster..)
+                .and_then(|rest| rest.chars().next())
+                .and_then(text_shaper::script_of)
+                .or_else(|| {
+                    runs.iter()
+                        .find(|run| run.text_range.contains(&cluster))
+                        .map(|run| run.script)
+                })
+                .is_some_and(Script::is_cjk)
+        };
+        let last = lines.len().saturating_sub(1);
+        for (index, line) in lines[..last].iter_mut().enumerate() {
+            let width = options.line_width(index == 0);
+            match strategy {
+                JustificationStrategy::Kashida(kashida) => {
+                    self.justifier
+                        .justify_line_kashida(line, &kashida_points, width, &kashida);
+                }
+                _ => self
+                    .justifier
+                    .justify_line_inter_character(line, width, cjk),
+            }
+        }
+        if let Some(last) = lines.last_mut() {
+            last.x_offset = 0.0;
+        }
     }
 
     /// Validate layout inputs
-    fn validate_inputs(&self, text: &str, options: &LayoutOptions) -> Result<(), LayoutError> {
+    fn validate_inputs(text: &str, options: &LayoutOptions) -> Result<(), LayoutError> {
         if text.is_empty() {
             return Err(LayoutError::InvalidText("Text is empty".to_string()));
         }
@@ -113,9 +339,15 @@ impl ParagraphLayout {
             ));
         }
 
-        if options.line_spacing <= 0.0 {
+        if !options.line_height.is_positive() {
             return Err(LayoutError::InvalidOptions(
-                "line_spacing must be positive".to_string(),
+                "line_height must be positive".to_string(),
+            ));
+        }
+
+        if options.line_width(true) <= 0.0 || options.line_width(false) <= 0.0 {
+            return Err(LayoutError::InvalidOptions(
+                "margins and indent leave no room for text".to_string(),
             ));
         }
 
@@ -123,18 +355,21 @@ impl ParagraphLayout {
     }
 
     /// Break shaped text into lines
+    ///
+    /// Lines break only between glyph clusters, at the last break
+    /// opportunity that keeps the line within `max_width`; whitespace may
+    /// hang past the edge, and a line with no opportunity breaks before the
+    /// cluster that takes it past 1.2 times `max_width`. Each line's `text_range` is the byte range
+    /// of its clusters.
     fn break_into_lines(
-        &self,
         text: &str,
         shaped_text: &ShapedText,
         breaks: &[crate::types::LineBreak],
         options: &LayoutOptions,
-    ) -> Result<Vec<LayoutLine>, LayoutError> {
-        let mut lines = Vec::new();
-
+    ) -> Vec<LayoutLine> {
         if shaped_text.glyphs.is_empty() {
             // Return single empty line
-            return Ok(vec![LayoutLine {
+            return vec![LayoutLine {
                 glyphs: vec![],
                 width: 0.0,
                 height: shaped_text.height,
@@ -142,104 +377,316 @@ impl ParagraphLayout {
                 x_offset: 0.0,
                 y_offset: 0.0,
                 text_range: (0, 0),
-            }]);
-        }
-
-        // Simple greedy line breaking algorithm
-        // In a real implementation, this would use proper Knuth-Plass algorithm
-        let mut current_line_glyphs: Vec<PositionedGlyph> = Vec::new();
-        let mut current_width = 0.0;
-        let mut line_start_char = 0;
-        let mut char_index = 0;
-
-        for (_glyph_index, glyph) in shaped_text.glyphs.iter().enumerate() {
-            let glyph_width = glyph.advance.x;
-
-            // Check if adding this glyph would exceed max width
-            if current_width + glyph_width > options.max_width && !current_line_glyphs.is_empty() {
-                // Find break opportunity before this glyph
-                let should_break = self.should_break_here(char_index, breaks, true);
-
-                if should_break || current_width + glyph_width > options.max_width * 1.2 {
-                    // Create line with current glyphs
-                    lines.push(LayoutLine {
-                        glyphs: current_line_glyphs.clone(),
-                        width: current_width,
-                        height: shaped_text.height,
-                        baseline: shaped_text.baseline,
-                        x_offset: 0.0,
-                        y_offset: 0.0,
-                        text_range: (line_start_char, char_index),
-                    });
-
-                    // Start new line
-                    current_line_glyphs.clear();
-                    current_width = 0.0;
-                    line_start_char = char_index;
-                }
-            }
-
-            // Add glyph to current line
-            current_line_glyphs.push(glyph.clone());
-            current_width += glyph_width;
-            char_index += 1;
-
-            // Check for mandatory break
-            if self.should_break_here(char_index, breaks, false) {
-                // Create line
-                lines.push(LayoutLine {
-                    glyphs: current_line_glyphs.clone(),
-                    width: current_width,
-                    height: shaped_text.height,
-                    baseline: shaped_text.baseline,
-                    x_offset: 0.0,
-                    y_offset: 0.0,
-                    text_range: (line_start_char, char_index),
-                });
-
-                // Start new line
-                current_line_glyphs.clear();
-                current_width = 0.0;
-                line_start_char = char_index;
-            }
+                glyph_indices: vec![],
+            }];
         }
 
-        // Add final line if not empty
-        if !current_line_glyphs.is_empty() {
-            lines.push(LayoutLine {
-                glyphs: current_line_glyphs,
-                width: current_width,
+        let clusters = glyph_clusters(text, &shaped_text.glyphs);
+        let widths: Vec<f32> = clusters
+            .iter()
+            .map(|cluster| {
+                shaped_text.glyphs[cluster.glyphs.clone()]
+                    .iter()
+                    .map(|glyph| glyph.advance.x)
+                    .sum()
+            })
+            .collect();
+        let hangs = |cluster: &GlyphCluster| {
+            text.get(cluster.text.clone())
+                .is_some_and(|text| !text.is_empty() && text.chars().all(char::is_whitespace))
+        };
+        let line = |clusters: &[GlyphCluster], width: f32| {
+            let glyphs = clusters.first().map_or(0, |c| c.glyphs.start)
+                ..clusters.last().map_or(0, |c| c.glyphs.end);
+            let start = clusters.iter().map(|c| c.text.start).min().unwrap_or(0);
+            let end = clusters.iter().map(|c| c.text.end).max().unwrap_or(start);
+            LayoutLine {
+                glyphs: shaped_text.glyphs[glyphs.clone()].to_vec(),
+                width,
                 height: shaped_text.height,
                 baseline: shaped_text.baseline,
                 x_offset: 0.0,
                 y_offset: 0.0,
-                text_range: (line_start_char, text.len()),
-            });
-        }
+                text_range: (start, end),
+                glyph_indices: glyphs.collect(),
+            }
+        };
 
-        Ok(lines)
-    }
+        // Greedy line breaking; `opportunity` is the last cluster boundary on
+        // the current line where it may end
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        let mut opportunity: Option<usize> = None;
+        let mut index = 0;
+        while index < clusters.len() {
+            let width: f32 = widths[line_start..index].iter().sum();
+            let max_width = options.line_width(lines.is_empty());
+            // Without a break opportunity the line may overflow by a fifth
+            // before it is broken mid-word
+            let limit = if opportunity.is_some() {
+                max_width
+            } else {
+                max_width * 1.2
+            };
+            if index > line_start && width + widths[index] > limit && !hangs(&clusters[index]) {
+                let end = opportunity.take().unwrap_or(index);
+                let mut broken = line(
+                    &clusters[line_start..end],
+                    widths[line_start..end].iter().sum(),
+                );
+                if let Some(hyphen) = break_hyphen(text, &clusters[end - 1], options) {
+                    show_hyphen(&mut broken, &hyphen);
+                }
+                lines.push(broken);
+                line_start = end;
+                continue;
+            }
 
-    /// Check if we should break at this character position
-    fn should_break_here(
-        &self,
-        char_index: usize,
-        breaks: &[crate::types::LineBreak],
-        require_optional: bool,
-    ) -> bool {
-        breaks
-            .iter()
-            .any(|b| b.offset == char_index && (b.required || (!require_optional && !b.required)))
+            index += 1;
+            if index == clusters.len() {
+                break;
+            }
+            let offset = clusters[index - 1].text.end;
+            match breaks.iter().find(|b| b.offset == offset) {
+                Some(b) if b.required => {
+                    lines.push(line(
+                        &clusters[line_start..index],
+                        width + widths[index - 1],
+                    ));
+                    line_start = index;
+                    opportunity = None;
+                }
+                Some(_) => {
+                    // A break after a soft hyphen must leave room for the
+                    // hyphen
+                    let hyphen = break_hyphen(text, &clusters[index - 1], options)
+                        .map_or(0.0, |hyphen| hyphen.advance);
+                    if width + widths[index - 1] + hyphen <= max_width {
+                        opportunity = Some(index);
+                    }
+                }
+                None => {}
+            }
+        }
+        lines.push(line(
+            &clusters[line_start..],
+            widths[line_start..].iter().sum(),
+        ));
+
+        lines
     }
 
-    /// Position lines vertically
-    fn position_lines_vertically(&self, lines: &mut [LayoutLine], options: &LayoutOptions) {
+    /// Size line boxes with half-leading and stack them vertically
+    ///
+    /// Lines arrive with the content height and ascent of their text; when
+    /// the text was shaped as `runs`, each run on a line is given its own
+    /// half-leading instead.
+    fn position_lines_vertically(
+        lines: &mut [LayoutLine],
+        options: &LayoutOptions,
+        runs: &[RunPlacement],
+    ) {
         let mut y_offset = 0.0;
 
         for line in lines {
+            let descent = line.height - line.baseline;
+            let on_line: Vec<(f32, f32)> = runs
+                .iter()
+                .filter(|run| {
+                    line.glyph_indices
+                        .iter()
+                        .any(|index| run.glyphs.contains(index))
+                })
+                .map(|run| (run.ascent, run.descent))
+                .collect();
+            let (height, baseline) = if on_line.is_empty() {
+                options.line_height.line_box([(line.baseline, descent)])
+            } else {
+                options.line_height.line_box(on_line)
+            };
+            line.height = height;
+            line.baseline = baseline;
             line.y_offset = y_offset;
-            y_offset += line.height * options.line_spacing;
+            y_offset += height;
+        }
+    }
+}
+
+/// A shaped run as laid out on lines
+struct RunPlacement {
+    /// Byte range of the paragraph text the run covers
+    text_range: Range<usize>,
+    /// Script the run was shaped as
+    script: Script,
+    /// Indices of the run's glyphs in the laid out `ShapedText`
+    glyphs: Range<usize>,
+    /// Ascent above the line's baseline
+    ascent: f32,
+    /// Descent below the line's baseline (positive)
+    descent: f32,
+}
+
+/// Shift the runs of `paragraph`, flattened into `glyphs`, so their
+/// dominant baselines line up with the first run's
+///
+/// Runs without metrics take the paragraph's height and baseline, and
+/// vertical runs, centered on their columns, are not shifted.
+fn align_runs(paragraph: &ShapedParagraph, glyphs: &mut [PositionedGlyph]) -> Vec<RunPlacement> {
+    let metrics = |run: &ShapedRun| {
+        if run.metrics == RunMetrics::default() {
+            RunMetrics {
+                ascent: paragraph.baseline,
+                descent: paragraph.height - paragraph.baseline,
+                baselines: Vec::new(),
+            }
+        } else {
+            run.metrics.clone()
         }
+    };
+    let first = paragraph.runs.iter().min_by_key(|run| run.text_range.start);
+    let dominant = first.map_or(Baseline::Alphabetic, |run| run.script.dominant_baseline());
+    let reference = first.map_or(0.0, |run| metrics(run).baseline(dominant));
+
+    let mut start = 0;
+    paragraph
+        .runs
+        .iter()
+        .map(|run| {
+            let run_metrics = metrics(run);
+            let glyph_range = start..start + run.glyphs.len();
+            start = glyph_range.end;
+            // How far the run's origin drops below the line's baseline
+            let shift = if run.direction.is_vertical() {
+                0.0
+            } else {
+                run_metrics.baseline(dominant) - reference
+            };
+            if shift != 0.0 {
+                // Horizontal glyph offsets point up
+                for glyph in &mut glyphs[glyph_range.clone()] {
+                    glyph.position.y -= shift;
+                    glyph.offset.y -= shift;
+                }
+            }
+            RunPlacement {
+                text_range: run.text_range.clone(),
+                script: run.script,
+                glyphs: glyph_range,
+                ascent: run_metrics.ascent - shift,
+                descent: run_metrics.descent + shift,
+            }
+        })
+        .collect()
+}
+
+/// The hyphen shown when a line breaks after `cluster`, if it is a soft
+/// hyphen
+fn break_hyphen(
+    text: &str,
+    cluster: &GlyphCluster,
+    options: &LayoutOptions,
+) -> Option<HyphenGlyph> {
+    options.hyphen.filter(|_| {
+        text.get(cluster.text.clone())
+            .is_some_and(|text| text.starts_with(SOFT_HYPHEN))
+    })
+}
+
+/// Replace the soft hyphen ending a line with a visible hyphen
+///
+/// The soft hyphen's glyph is the line's last in logical order; it keeps its
+/// cluster, so the hyphen maps back to the soft hyphen in the text.
+fn show_hyphen(line: &mut LayoutLine, hyphen: &HyphenGlyph) {
+    let Some(glyph) = line.glyphs.last_mut() else {
+        return;
+    };
+    glyph.position.x -= glyph.offset.x;
+    glyph.position.y -= glyph.offset.y;
+    glyph.offset = Vector { x: 0.0, y: 0.0 };
+    glyph.glyph_id = hyphen.glyph_id;
+    glyph.font_id = hyphen.font_id;
+    glyph.advance = Vector {
+        x: hyphen.advance,
+        y: 0.0,
+    };
+    glyph.scale = 1.0;
+    line.width += hyphen.advance;
+}
+
+/// Shaped text with its glyphs in logical (cluster) order, and the source
+/// index of each glyph
+///
+/// Right-to-left shaping returns glyphs in visual order, so the glyphs are
+/// reversed before sorting; glyphs within a cluster keep their relative
+/// order, which `reorder_visually` reverses again.
+fn logical_order(shaped_text: &ShapedText) -> (ShapedText, Vec<usize>) {
+    let mut source_indices: Vec<usize> = (0..shaped_text.glyphs.len()).rev().collect();
+    source_indices.sort_by_key(|&index| shaped_text.glyphs[index].cluster);
+    let glyphs = source_indices
+        .iter()
+        .map(|&index| shaped_text.glyphs[index].clone())
+        .collect();
+    let shaped_text = ShapedText {
+        glyphs,
+        ..*shaped_text
+    };
+    (shaped_text, source_indices)
+}
+
+/// Reorder a line's logically ordered glyphs for display (UAX #9 rule L2)
+/// and lay them out from the line's left edge
+fn reorder_visually(bidi: &BidiInfo<'_>, line: &mut LayoutLine) {
+    let Some(start) = line.glyphs.iter().map(|glyph| glyph.cluster as usize).min() else {
+        return;
+    };
+    let end = line
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.cluster as usize)
+        .max()
+        .map_or(start, |last| {
+            let last_char = bidi.text.get(last..).and_then(|rest| rest.chars().next());
+            last + last_char.map_or(0, char::len_utf8)
+        });
+
+    // Levels of the line's text, with trailing whitespace at the paragraph
+    // level (rule L1)
+    let levels = bidi
+        .paragraphs
+        .iter()
+        .find(|para| para.range.contains(&start))
+        .filter(|para| start < end && end <= para.range.end)
+        .map_or_else(
+            || bidi.levels.clone(),
+            |para| bidi.reordered_levels(para, start..end),
+        );
+    let glyph_levels: Vec<Level> = line
+        .glyphs
+        .iter()
+        .map(|glyph| {
+            levels
+                .get(glyph.cluster as usize)
+                .copied()
+                .unwrap_or_else(Level::rtl)
+        })
+        .collect();
+
+    let visual_order = BidiInfo::reorder_visual(&glyph_levels);
+    let logical = std::mem::take(&mut line.glyphs);
+    line.glyphs = visual_order
+        .iter()
+        .map(|&index| logical[index].clone())
+        .collect();
+    let logical_indices = std::mem::take(&mut line.glyph_indices);
+    line.glyph_indices = visual_order
+        .iter()
+        .map(|&index| logical_indices[index])
+        .collect();
+
+    let mut pen = 0.0;
+    for glyph in &mut line.glyphs {
+        glyph.position.x = pen + glyph.offset.x;
+        pen += glyph.advance.x;
     }
 }
 
@@ -250,8 +697,10 @@ impl Default for ParagraphLayout {
 }
 
 #[cfg(test)]
+#[allow(clippy::float_cmp, clippy::cast_precision_loss)]
 mod tests {
     use super::*;
+    use crate::types::LineHeight;
     use font_types::{GlyphId, Point, PositionedGlyph, Vector};
 
     fn create_test_glyph(x: f32, advance_x: f32) -> PositionedGlyph {
@@ -264,6 +713,9 @@ mod tests {
                 y: 0.0,
             },
             offset: Vector { x: 0.0, y: 0.0 },
+            scale: 1.0,
+            cluster: 0,
+            unsafe_to_break: false,
         }
     }
 
@@ -271,8 +723,11 @@ mod tests {
         let mut glyphs = Vec::new();
         let mut x = 0.0;
 
-        for _ in 0..num_glyphs {
-            glyphs.push(create_test_glyph(x, glyph_width));
+        for index in 0..num_glyphs {
+            glyphs.push(PositionedGlyph {
+                cluster: u32::try_from(index).unwrap(),
+                ..create_test_glyph(x, glyph_width)
+            });
             x += glyph_width;
         }
 
@@ -295,6 +750,201 @@ mod tests {
         let _layout2 = ParagraphLayout::default();
     }
 
+    #[test]
+    fn test_inter_character_justification_follows_run_scripts() {
+        // Given: Latin followed by Han that wraps onto a second line
+        let text = "ab\u{4e2d}\u{6587}\u{5b57}\u{6f22}";
+        let run = |script, range: std::ops::Range<usize>| {
+            let glyphs = text[range.clone()]
+                .char_indices()
+                .map(|(offset, _)| range.start + offset)
+                .map(|cluster| PositionedGlyph {
+                    cluster: u32::try_from(cluster).unwrap(),
+                    ..create_test_glyph(0.0, 10.0)
+                })
+                .collect();
+            font_types::ShapedRun {
+                font_id: 0,
+                direction: font_types::Direction::LeftToRight,
+                script,
+                text_range: range,
+                glyphs,
+                metrics: font_types::RunMetrics::default(),
+            }
+        };
+        let mut paragraph = ShapedParagraph {
+            runs: vec![run(Script::Latin, 0..2), run(Script::Han, 2..text.len())],
+            width: 60.0,
+            height: 20.0,
+            baseline: 15.0,
+        };
+        for (index, glyph) in paragraph
+            .runs
+            .iter_mut()
+            .flat_map(|run| &mut run.glyphs)
+            .enumerate()
+        {
+            glyph.position.x = index as f32 * 10.0;
+        }
+        let options = LayoutOptions {
+            max_width: 35.0,
+            justification: JustificationMode::Justify,
+            justification_strategy: JustificationStrategy::InterCharacter,
+            ..Default::default()
+        };
+
+        // When: Laying it out
+        let result = ParagraphLayout::new()
+            .layout_shaped_paragraph(text, &paragraph, &options)
+            .unwrap();
+
+        // Then: Only the gaps next to Han characters widen
+        let xs = |line: &LayoutLine| line.glyphs.iter().map(|g| g.position.x).collect::<Vec<_>>();
+        assert_eq!(xs(&result.lines[0]), [0.0, 10.0, 25.0]);
+        assert_eq!(result.lines[0].width, 35.0);
+        assert_eq!(xs(&result.lines[1]), [30.0, 40.0, 50.0]);
+    }
+
+    #[test]
+    fn test_inter_character_justification_leaves_latin_without_runs_to_words() {
+        // Given: Latin text shaped without script runs
+        let shaped_text = create_test_shaped_text(8, 10.0);
+        let layout = |justification_strategy| {
+            let options = LayoutOptions {
+                max_width: 75.0,
+                justification: JustificationMode::Justify,
+                justification_strategy,
+                ..Default::default()
+            };
+            ParagraphLayout::new()
+                .layout_paragraph("ab cd ef", &shaped_text, &options)
+                .unwrap()
+        };
+
+        // When: Justifying it between characters and between words
+        let by_character = layout(JustificationStrategy::InterCharacter);
+        let by_word = layout(JustificationStrategy::InterWord);
+
+        // Then: No cluster counts as CJK, so both justify between words
+        let xs = |line: &LayoutLine| line.glyphs.iter().map(|g| g.position.x).collect::<Vec<_>>();
+        assert_eq!(by_character.lines.len(), by_word.lines.len());
+        for (character_line, word_line) in by_character.lines.iter().zip(&by_word.lines) {
+            assert_eq!(xs(character_line), xs(word_line));
+        }
+        assert_eq!(by_character.lines[0].width, 75.0);
+    }
+
+    #[test]
+    fn test_layout_shaped_paragraph_keeps_run_fonts() {
+        // Given: A paragraph whose second run came from a fallback font
+        let run = |font_id, range: std::ops::Range<usize>| font_types::ShapedRun {
+            font_id,
+            direction: font_types::Direction::LeftToRight,
+            script: font_types::Script::Latin,
+            text_range: range.clone(),
+            glyphs: range
+                .map(|i| PositionedGlyph {
+                    font_id,
+                    cluster: u32::try_from(i).unwrap(),
+                    ..create_test_glyph(i as f32 * 10.0, 10.0)
+                })
+                .collect(),
+            metrics: font_types::RunMetrics::default(),
+        };
+        let paragraph = ShapedParagraph {
+            runs: vec![run(0, 0..2), run(7, 2..4)],
+            width: 40.0,
+            height: 20.0,
+            baseline: 15.0,
+        };
+
+        // When: Laying it out on one line
+        let result = ParagraphLayout::new()
+            .layout_shaped_paragraph("abcd", &paragraph, &LayoutOptions::default())
+            .unwrap();
+
+        // Then: The line still groups its glyphs by font
+        let fonts: Vec<_> = result.lines[0]
+            .font_runs()
+            .map(|(font_id, glyphs)| (font_id, glyphs.len()))
+            .collect();
+        assert_eq!(fonts, [(0, 2), (7, 2)]);
+    }
+
+    #[test]
+    fn test_layout_shaped_paragraph_aligns_run_baselines() {
+        // Given: A Latin font and a CJK font whose BASE table puts the
+        // alphabetic baseline 2px above its origin, on the ideographic one
+        let latin = RunMetrics {
+            ascent: 12.0,
+            descent: 4.0,
+            baselines: Vec::new(),
+        };
+        let cjk = RunMetrics {
+            ascent: 16.0,
+            descent: 0.0,
+            baselines: vec![(Baseline::Alphabetic, 2.0), (Baseline::Ideographic, 0.0)],
+        };
+        let layout = |text: &str, runs: Vec<(Script, Range<usize>, RunMetrics)>| {
+            let runs = runs
+                .into_iter()
+                .enumerate()
+                .map(|(index, (script, text_range, metrics))| ShapedRun {
+                    font_id: index,
+                    direction: font_types::Direction::LeftToRight,
+                    script,
+                    glyphs: vec![PositionedGlyph {
+                        cluster: u32::try_from(text_range.start).unwrap(),
+                        ..create_test_glyph(index as f32 * 10.0, 10.0)
+                    }],
+                    text_range,
+                    metrics,
+                })
+                .collect();
+            let paragraph = ShapedParagraph {
+                runs,
+                width: 20.0,
+                height: 16.0,
+                baseline: 16.0,
+            };
+            let options = LayoutOptions {
+                line_height: LineHeight::Normal,
+                ..Default::default()
+            };
+            ParagraphLayout::new()
+                .layout_shaped_paragraph(text, &paragraph, &options)
+                .unwrap()
+        };
+
+        // When: Latin text starts the paragraph
+        let result = layout(
+            "\u{e9}\u{4e2d}",
+            vec![
+                (Script::Latin, 0..2, latin.clone()),
+                (Script::Han, 2..5, cjk.clone()),
+            ],
+        );
+
+        // Then: The CJK run drops so the alphabetic baselines meet
+        let line = &result.lines[0];
+        let ys: Vec<f32> = line.glyphs.iter().map(|g| g.position.y).collect();
+        assert_eq!(ys, [0.0, -2.0]);
+        assert_eq!((line.height, line.baseline), (18.0, 14.0));
+
+        // When: Han text starts the paragraph
+        let result = layout(
+            "\u{4e2d}\u{e9}",
+            vec![(Script::Han, 0..3, cjk), (Script::Latin, 3..5, latin)],
+        );
+
+        // Then: The Latin run rises to sit its descent on the ideographic
+        // baseline
+        let line = &result.lines[0];
+        let ys: Vec<f32> = line.glyphs.iter().map(|g| g.position.y).collect();
+        assert_eq!(ys, [0.0, 4.0]);
+        assert_eq!((line.height, line.baseline), (16.0, 16.0));
+    }
+
     // ========== Input Validation Tests ==========
 
     #[test]
@@ -303,10 +953,10 @@ mod tests {
         // When: Attempting to layout
         // Then: Should return InvalidText error
         let layout = ParagraphLayout::new();
-        let _shaped_text = create_test_shaped_text(0, 10.0);
+        let shaped_text = create_test_shaped_text(0, 10.0);
         let options = LayoutOptions::default();
 
-        let result = layout.layout_paragraph("", &_shaped_text, &options);
+        let result = layout.layout_paragraph("", &shaped_text, &options);
 
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), LayoutError::InvalidText(_)));
@@ -319,8 +969,10 @@ mod tests {
         // Then: Should return InvalidOptions error
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(5, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = -100.0;
+        let options = LayoutOptions {
+            max_width: -100.0,
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello", &shaped_text, &options);
 
@@ -338,8 +990,10 @@ mod tests {
         // Then: Should return InvalidOptions error
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(5, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = 0.0;
+        let options = LayoutOptions {
+            max_width: 0.0,
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello", &shaped_text, &options);
 
@@ -351,14 +1005,16 @@ mod tests {
     }
 
     #[test]
-    fn test_layout_negative_line_spacing_error() {
-        // Given: Options with negative line_spacing
+    fn test_layout_negative_line_height_error() {
+        // Given: Options with negative line_height
         // When: Attempting to layout
         // Then: Should return InvalidOptions error
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(5, 10.0);
-        let mut options = LayoutOptions::default();
-        options.line_spacing = -1.0;
+        let options = LayoutOptions {
+            line_height: LineHeight::Multiplier(-1.0),
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello", &shaped_text, &options);
 
@@ -378,8 +1034,10 @@ mod tests {
         // Then: Should create single line
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(5, 10.0); // 50px total
-        let mut options = LayoutOptions::default();
-        options.max_width = 100.0; // Plenty of space
+        let options = LayoutOptions {
+            max_width: 100.0, // Plenty of space
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello", &shaped_text, &options);
 
@@ -396,8 +1054,10 @@ mod tests {
         // Then: Should create multiple lines
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(20, 10.0); // 200px total
-        let mut options = LayoutOptions::default();
-        options.max_width = 100.0; // Forces wrapping
+        let options = LayoutOptions {
+            max_width: 100.0, // Forces wrapping
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello world test example", &shaped_text, &options);
 
@@ -412,11 +1072,9 @@ mod tests {
         // When: Laying out
         // Then: Should create single empty line
         let layout = ParagraphLayout::new();
-        let shaped_text = create_test_shaped_text(0, 10.0);
         let options = LayoutOptions::default();
 
-        // Note: This will fail validation due to empty text
-        // Let's use non-empty text with empty glyphs
+        // Use non-empty text with empty glyphs (empty text fails validation)
         let shaped_text = ShapedText {
             glyphs: vec![],
             width: 0.0,
@@ -441,15 +1099,17 @@ mod tests {
         // Then: Should break appropriately
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(10, 10.0); // 100px total
-        let mut options = LayoutOptions::default();
-        options.max_width = 100.0; // Exact fit
+        let options = LayoutOptions {
+            max_width: 100.0, // Exact fit
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello test", &shaped_text, &options);
 
         assert!(result.is_ok());
         let result = result.unwrap();
         // Should fit in one or two lines depending on break opportunities
-        assert!(result.lines.len() >= 1);
+        assert!(!result.lines.is_empty());
     }
 
     #[test]
@@ -459,8 +1119,10 @@ mod tests {
         // Then: Total glyphs should equal original
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(15, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = 80.0;
+        let options = LayoutOptions {
+            max_width: 80.0,
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello world example", &shaped_text, &options);
 
@@ -480,9 +1142,11 @@ mod tests {
         // Then: Lines should be spaced correctly
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(20, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = 80.0;
-        options.line_spacing = 1.2; // Default
+        let options = LayoutOptions {
+            max_width: 80.0,
+            line_height: LineHeight::Multiplier(1.2), // Default
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello world test example", &shaped_text, &options);
 
@@ -505,9 +1169,11 @@ mod tests {
         // Then: Lines should use custom spacing
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(20, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = 80.0;
-        options.line_spacing = 2.0; // Double spacing
+        let options = LayoutOptions {
+            max_width: 80.0,
+            line_height: LineHeight::Multiplier(2.0), // Double spacing
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello world test example", &shaped_text, &options);
 
@@ -523,6 +1189,253 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_glyph_line_maps() {
+        // Given: Text broken over several lines
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(20, 10.0);
+        let options = LayoutOptions {
+            max_width: 80.0,
+            ..Default::default()
+        };
+
+        // When: Laying out
+        let result = layout
+            .layout_paragraph("Unbreakableparagraph", &shaped_text, &options)
+            .unwrap();
+
+        // Then: Each line lists its source glyphs, and each glyph its line
+        let indices: Vec<usize> = result
+            .lines
+            .iter()
+            .flat_map(|line| line.glyph_indices.iter().copied())
+            .collect();
+        assert_eq!(indices, (0..20).collect::<Vec<_>>());
+        assert_eq!(result.glyph_lines.len(), 20);
+        for (line_index, line) in result.lines.iter().enumerate() {
+            assert_eq!(line.glyph_indices.len(), line.glyphs.len());
+            for &glyph_index in &line.glyph_indices {
+                assert_eq!(result.line_of_glyph(glyph_index), Some(line_index));
+            }
+        }
+        assert_eq!(result.line_of_glyph(20), None);
+    }
+
+    /// Shaped text with one glyph per `(cluster, advance, font_id)`
+    fn create_cluster_shaped_text(glyphs: &[(u32, f32, usize)]) -> ShapedText {
+        let mut x = 0.0;
+        let glyphs: Vec<PositionedGlyph> = glyphs
+            .iter()
+            .map(|&(cluster, advance, font_id)| {
+                let glyph = PositionedGlyph {
+                    font_id,
+                    cluster,
+                    ..create_test_glyph(x, advance)
+                };
+                x += advance;
+                glyph
+            })
+            .collect();
+        ShapedText {
+            glyphs,
+            width: x,
+            height: 20.0,
+            baseline: 15.0,
+        }
+    }
+
+    #[test]
+    fn test_line_ranges_are_byte_offsets_around_emoji() {
+        // Given: A ZWJ family emoji shaped by a fallback font as one glyph
+        let text = "ab \u{1f469}\u{200d}\u{1f469}\u{200d}\u{1f467} cd";
+        let shaped_text = create_cluster_shaped_text(&[
+            (0, 10.0, 0),
+            (1, 10.0, 0),
+            (2, 10.0, 0),
+            (3, 20.0, 7),
+            (21, 10.0, 0),
+            (22, 10.0, 0),
+            (23, 10.0, 0),
+        ]);
+        let options = LayoutOptions {
+            max_width: 45.0,
+            ..Default::default()
+        };
+
+        // When: Laying it out over several lines
+        let result = ParagraphLayout::new()
+            .layout_paragraph(text, &shaped_text, &options)
+            .unwrap();
+
+        // Then: Lines break at word boundaries and cover the text in bytes
+        let ranges: Vec<_> = result.lines.iter().map(|line| line.text_range).collect();
+        assert_eq!(ranges, [(0, 3), (3, 22), (22, text.len())]);
+        assert_eq!(&text[3..22], "\u{1f469}\u{200d}\u{1f469}\u{200d}\u{1f467} ");
+        assert_eq!(result.lines[1].glyph_indices, [3, 4]);
+        assert_eq!(result.lines[1].glyphs[0].font_id, 7);
+    }
+
+    #[test]
+    fn test_lines_never_split_combining_mark_clusters() {
+        // Given: Unbreakable text whose accents are separate mark glyphs
+        let text = "e\u{301}e\u{301}e\u{301}";
+        let shaped_text = create_cluster_shaped_text(&[
+            (0, 10.0, 0),
+            (0, 0.0, 0),
+            (3, 10.0, 0),
+            (3, 0.0, 0),
+            (6, 10.0, 0),
+            (6, 0.0, 0),
+        ]);
+        let options = LayoutOptions {
+            max_width: 15.0,
+            ..Default::default()
+        };
+
+        // When: Laying it out narrower than two clusters
+        let result = ParagraphLayout::new()
+            .layout_paragraph(text, &shaped_text, &options)
+            .unwrap();
+
+        // Then: Each line holds a whole base and mark cluster
+        let ranges: Vec<_> = result.lines.iter().map(|line| line.text_range).collect();
+        assert_eq!(ranges, [(0, 3), (3, 6), (6, 9)]);
+        for line in &result.lines {
+            assert_eq!(line.glyphs.len(), 2);
+            assert_eq!(line.width, 10.0);
+        }
+    }
+
+    #[test]
+    fn test_soft_hyphen_shows_hyphen_only_at_line_break() {
+        // Given: A soft hyphen and a zero-width space inside words, both
+        // shaped as zero-width glyphs, and a 6px hyphen
+        let text = "ab\u{ad}cd ef\u{200b}gh";
+        let shaped_text = create_cluster_shaped_text(&[
+            (0, 10.0, 0),
+            (1, 10.0, 0),
+            (2, 0.0, 0),
+            (4, 10.0, 0),
+            (5, 10.0, 0),
+            (6, 10.0, 0),
+            (7, 10.0, 0),
+            (8, 10.0, 0),
+            (9, 0.0, 0),
+            (12, 10.0, 0),
+            (13, 10.0, 0),
+        ]);
+        let hyphen = HyphenGlyph {
+            font_id: 3,
+            glyph_id: GlyphId { id: 9 },
+            advance: 6.0,
+        };
+        let options = LayoutOptions::builder()
+            .max_width(30.0)
+            .hyphen(hyphen)
+            .build();
+
+        // When: Laying it out narrower than the words, and wide enough for
+        // one line
+        let layout = ParagraphLayout::new();
+        let narrow = layout
+            .layout_paragraph(text, &shaped_text, &options)
+            .unwrap();
+        let wide = layout
+            .layout_paragraph(
+                text,
+                &shaped_text,
+                &LayoutOptions {
+                    max_width: 200.0,
+                    ..options
+                },
+            )
+            .unwrap();
+
+        // Then: The broken soft hyphen becomes the hyphen, and the
+        // zero-width space breaks without showing anything
+        let ranges: Vec<_> = narrow.lines.iter().map(|line| line.text_range).collect();
+        assert_eq!(ranges, [(0, 4), (4, 7), (7, 12), (12, 14)]);
+        let first = &narrow.lines[0];
+        let shown = first.glyphs.last().unwrap();
+        assert_eq!(shown.glyph_id, GlyphId { id: 9 });
+        assert_eq!(shown.font_id, 3);
+        assert_eq!(shown.cluster, 2);
+        assert_eq!(shown.position.x, 20.0);
+        assert_eq!(shown.advance.x, 6.0);
+        assert_eq!(first.width, 26.0);
+        let zwsp = narrow.lines[2].glyphs.last().unwrap();
+        assert_eq!((zwsp.glyph_id, zwsp.advance.x), (GlyphId { id: 1 }, 0.0));
+        assert_eq!(narrow.lines[2].width, 20.0);
+
+        // And unbroken, the soft hyphen stays invisible
+        assert_eq!(wide.lines.len(), 1);
+        assert_eq!(wide.lines[0].glyphs[2].glyph_id, GlyphId { id: 1 });
+        assert_eq!(wide.lines[0].width, 90.0);
+    }
+
+    #[test]
+    fn test_soft_hyphen_break_needs_room_for_hyphen() {
+        // Given: A soft hyphen whose hyphen would overflow the line, after
+        // a space
+        let text = "x ab\u{ad}cd";
+        let shaped_text = create_cluster_shaped_text(&[
+            (0, 10.0, 0),
+            (1, 10.0, 0),
+            (2, 10.0, 0),
+            (3, 10.0, 0),
+            (4, 0.0, 0),
+            (6, 10.0, 0),
+            (7, 10.0, 0),
+        ]);
+        let options = LayoutOptions::builder()
+            .max_width(44.0)
+            .hyphen(HyphenGlyph {
+                font_id: 0,
+                glyph_id: GlyphId { id: 9 },
+                advance: 6.0,
+            })
+            .build();
+
+        // When: Laying it out
+        let result = ParagraphLayout::new()
+            .layout_paragraph(text, &shaped_text, &options)
+            .unwrap();
+
+        // Then: The line breaks at the space instead, without a hyphen
+        let ranges: Vec<_> = result.lines.iter().map(|line| line.text_range).collect();
+        assert_eq!(ranges, [(0, 2), (2, text.len())]);
+        assert!(result
+            .lines
+            .iter()
+            .flat_map(|line| &line.glyphs)
+            .all(|glyph| glyph.glyph_id != GlyphId { id: 9 }));
+    }
+
+    #[test]
+    fn test_fixed_line_height_adds_half_leading() {
+        // Given: 20px tall text (15px ascent) in 32px lines
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(20, 10.0);
+        let options = LayoutOptions {
+            max_width: 80.0,
+            line_height: LineHeight::Px(32.0),
+            ..Default::default()
+        };
+
+        // When: Laying out
+        let result = layout
+            .layout_paragraph("Unbreakableparagraph", &shaped_text, &options)
+            .unwrap();
+
+        // Then: Lines are 32px apart with the baseline 6px lower
+        assert_eq!(result.lines[1].y_offset, 32.0);
+        for line in &result.lines {
+            assert_eq!(line.height, 32.0);
+            assert_eq!(line.baseline, 21.0);
+        }
+        assert_eq!(result.total_height, result.lines.len() as f32 * 32.0);
+    }
+
     #[test]
     fn test_first_line_starts_at_zero() {
         // Given: Any paragraph layout
@@ -565,8 +1478,10 @@ mod tests {
         // Then: Should include all lines with spacing
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(20, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = 80.0;
+        let options = LayoutOptions {
+            max_width: 80.0,
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello world test example", &shaped_text, &options);
 
@@ -584,9 +1499,11 @@ mod tests {
         // Then: overflow should be false
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(20, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = 80.0;
-        options.max_height = None;
+        let options = LayoutOptions {
+            max_width: 80.0,
+            max_height: None,
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello world test example", &shaped_text, &options);
 
@@ -601,9 +1518,11 @@ mod tests {
         // Then: overflow should be true
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(50, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = 80.0;
-        options.max_height = Some(50.0); // Very restrictive
+        let options = LayoutOptions {
+            max_width: 80.0,
+            max_height: Some(50.0), // Very restrictive
+            ..Default::default()
+        };
 
         let result =
             layout.layout_paragraph("Hello world test example long text", &shaped_text, &options);
@@ -622,13 +1541,416 @@ mod tests {
         // Then: overflow should be false
         let layout = ParagraphLayout::new();
         let shaped_text = create_test_shaped_text(5, 10.0);
-        let mut options = LayoutOptions::default();
-        options.max_width = 200.0;
-        options.max_height = Some(1000.0); // Very generous
+        let options = LayoutOptions {
+            max_width: 200.0,
+            max_height: Some(1000.0), // Very generous
+            ..Default::default()
+        };
 
         let result = layout.layout_paragraph("Hello", &shaped_text, &options);
 
         assert!(result.is_ok());
         assert!(!result.unwrap().overflow);
     }
+
+    // ========== Layout Cache Tests ==========
+
+    fn shaping_options() -> ShapingOptions {
+        ShapingOptions {
+            script: text_shaper::Script::Latin,
+            language: text_shaper::Language {
+                tag: String::from("en"),
+            },
+            direction: font_types::Direction::LeftToRight,
+            features: std::collections::HashMap::new(),
+            kerning: true,
+            ligatures: true,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            variant_caps: text_shaper::FontVariantCaps::Normal,
+            feature_ranges: Vec::new(),
+            synthesize_small_caps: true,
+            match_context: text_shaper::MatchContext::Privileged,
+            text_transform: text_shaper::TextTransform::None,
+            format_characters: text_shaper::FormatCharacters::Hidden,
+        }
+    }
+
+    #[test]
+    fn test_layout_paragraph_cached_reuses_result() {
+        // Given: A layout engine with a cache
+        let layout = ParagraphLayout::with_cache(16);
+        let shaped_text = create_test_shaped_text(20, 10.0);
+        let options = LayoutOptions {
+            max_width: 100.0,
+            ..Default::default()
+        };
+
+        // When: Laying out the same paragraph twice
+        let first = layout
+            .layout_paragraph_cached(
+                "Hello world foo bar!",
+                &shaped_text,
+                &shaping_options(),
+                &options,
+            )
+            .unwrap();
+        let second = layout
+            .layout_paragraph_cached(
+                "Hello world foo bar!",
+                &shaped_text,
+                &shaping_options(),
+                &options,
+            )
+            .unwrap();
+
+        // Then: The second layout is the cached result
+        assert!(Arc::ptr_eq(&first, &second));
+        let stats = layout.cache_stats().unwrap();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.current_size, 1);
+
+        // And: A different width is laid out again
+        let narrow = LayoutOptions {
+            max_width: 50.0,
+            ..options.clone()
+        };
+        let third = layout
+            .layout_paragraph_cached(
+                "Hello world foo bar!",
+                &shaped_text,
+                &shaping_options(),
+                &narrow,
+            )
+            .unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(layout.cache_stats().unwrap().current_size, 2);
+    }
+
+    #[test]
+    fn test_layout_paragraph_cached_invalidation() {
+        // Given: A cached paragraph layout
+        let layout = ParagraphLayout::with_cache(16);
+        let shaped_text = create_test_shaped_text(5, 10.0);
+        let options = LayoutOptions::default();
+        let first = layout
+            .layout_paragraph_cached("Hello", &shaped_text, &shaping_options(), &options)
+            .unwrap();
+
+        // When: The cache is cleared
+        layout.clear_cache();
+
+        // Then: The paragraph is laid out again
+        let second = layout
+            .layout_paragraph_cached("Hello", &shaped_text, &shaping_options(), &options)
+            .unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(layout.cache_stats().unwrap().misses, 2);
+    }
+
+    #[test]
+    fn test_layout_paragraph_cached_without_cache() {
+        // Given: A layout engine without a cache
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(5, 10.0);
+
+        // When/Then: Layout still works and errors are reported
+        let result = layout.layout_paragraph_cached(
+            "Hello",
+            &shaped_text,
+            &shaping_options(),
+            &LayoutOptions::default(),
+        );
+        assert_eq!(result.unwrap().lines.len(), 1);
+        assert!(layout.cache_stats().is_none());
+        let result = layout.layout_paragraph_cached(
+            "",
+            &shaped_text,
+            &shaping_options(),
+            &LayoutOptions::default(),
+        );
+        assert!(matches!(result, Err(LayoutError::InvalidText(_))));
+    }
+
+    // ========== Indentation and Margin Tests ==========
+
+    #[test]
+    fn test_first_line_indent() {
+        // Given: A 30px first-line indent in a 100px wide paragraph
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(20, 10.0);
+        let options = LayoutOptions {
+            max_width: 100.0,
+            text_indent: 30.0,
+            ..Default::default()
+        };
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph("Unbreakableparagraph", &shaped_text, &options)
+            .unwrap();
+
+        // Then: Only the first line is indented and narrowed
+        assert_eq!(result.lines[0].x_offset, 30.0);
+        assert_eq!(result.lines[0].glyphs.len(), 8);
+        assert_eq!(result.lines[1].glyphs.len(), 12);
+        assert_eq!(result.lines[1].x_offset, 0.0);
+    }
+
+    #[test]
+    fn test_hanging_indent() {
+        // Given: A hanging indent inside start and end margins
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(20, 10.0);
+        let options = LayoutOptions {
+            max_width: 120.0,
+            text_indent: 20.0,
+            hanging_indent: true,
+            margin_start: 10.0,
+            margin_end: 10.0,
+            ..Default::default()
+        };
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph("Hello world foo bar!", &shaped_text, &options)
+            .unwrap();
+
+        // Then: The first line starts at the margin and the others are indented
+        assert_eq!(result.lines[0].x_offset, 10.0);
+        for line in &result.lines[1..] {
+            assert_eq!(line.x_offset, 30.0);
+        }
+    }
+
+    #[test]
+    fn test_indent_applies_to_start_edge_in_rtl() {
+        // Given: Right-aligned right-to-left text with a first-line indent
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(5, 10.0);
+        let options = LayoutOptions {
+            max_width: 100.0,
+            justification: JustificationMode::Right,
+            direction: TextDirection::RightToLeft,
+            text_indent: 20.0,
+            margin_start: 5.0,
+            margin_end: 15.0,
+            ..Default::default()
+        };
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph("Hello", &shaped_text, &options)
+            .unwrap();
+
+        // Then: The line ends before the start margin and indent on the right
+        let line = &result.lines[0];
+        assert_eq!(line.x_offset + line.width, 100.0 - 5.0 - 20.0);
+    }
+
+    #[test]
+    fn test_justified_lines_fill_indented_width() {
+        // Given: Justified text with margins and a first-line indent
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(20, 10.0);
+        let options = LayoutOptions {
+            max_width: 100.0,
+            justification: JustificationMode::Justify,
+            text_indent: 15.0,
+            margin_start: 5.0,
+            margin_end: 10.0,
+            ..Default::default()
+        };
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph("Hello world foo bar!", &shaped_text, &options)
+            .unwrap();
+
+        // Then: Justified lines end at the end margin
+        let first = &result.lines[0];
+        assert_eq!(first.x_offset, 20.0);
+        assert_eq!(first.x_offset + first.width, 90.0);
+        let last = result.lines.last().unwrap();
+        assert_eq!(last.x_offset, 5.0);
+    }
+
+    #[test]
+    fn test_margins_wider_than_paragraph_error() {
+        // Given: Margins and indent as wide as the paragraph
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(5, 10.0);
+        let options = LayoutOptions {
+            max_width: 100.0,
+            text_indent: 40.0,
+            margin_start: 30.0,
+            margin_end: 30.0,
+            ..Default::default()
+        };
+
+        // When: Attempting to layout
+        let result = layout.layout_paragraph("Hello", &shaped_text, &options);
+
+        // Then: Should return InvalidOptions error
+        assert!(matches!(result, Err(LayoutError::InvalidOptions(_))));
+    }
+
+    // ========== Right-to-Left Tests ==========
+
+    /// Shaped right-to-left text: one 10px glyph per character, in visual
+    /// order (descending clusters) as a right-to-left shaper returns them
+    fn create_rtl_shaped_text(text: &str) -> ShapedText {
+        let glyphs: Vec<PositionedGlyph> = text
+            .char_indices()
+            .rev()
+            .enumerate()
+            .map(|(index, (cluster, _))| {
+                let cluster = u32::try_from(cluster).unwrap();
+                PositionedGlyph {
+                    glyph_id: GlyphId { id: cluster },
+                    cluster,
+                    ..create_test_glyph(index as f32 * 10.0, 10.0)
+                }
+            })
+            .collect();
+        ShapedText {
+            width: glyphs.len() as f32 * 10.0,
+            glyphs,
+            height: 20.0,
+            baseline: 15.0,
+        }
+    }
+
+    fn clusters(line: &LayoutLine) -> Vec<u32> {
+        line.glyphs.iter().map(|glyph| glyph.cluster).collect()
+    }
+
+    fn rtl_options(max_width: f32) -> LayoutOptions {
+        LayoutOptions {
+            max_width,
+            direction: TextDirection::RightToLeft,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rtl_line_reorders_embedded_ltr() {
+        // Given: Right-to-left text with an embedded left-to-right word
+        let layout = ParagraphLayout::new();
+        let text = "אבabcגד";
+        let shaped_text = create_rtl_shaped_text(text);
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph(text, &shaped_text, &rtl_options(200.0))
+            .unwrap();
+
+        // Then: The Latin word reads left to right within the line
+        let line = &result.lines[0];
+        assert_eq!(clusters(line), vec![9, 7, 4, 5, 6, 2, 0]);
+        let positions: Vec<f32> = line.glyphs.iter().map(|glyph| glyph.position.x).collect();
+        assert_eq!(positions, vec![0.0, 10.0, 20.0, 30.0, 40.0, 50.0, 60.0]);
+
+        // And: The line is aligned to the right edge by default
+        assert_eq!(line.x_offset, 200.0 - 70.0);
+    }
+
+    #[test]
+    fn test_rtl_lines_follow_logical_order() {
+        // Given: Right-to-left text too long for one line
+        let layout = ParagraphLayout::new();
+        let text = "אבגדהוזחטי";
+        let shaped_text = create_rtl_shaped_text(text);
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph(text, &shaped_text, &rtl_options(60.0))
+            .unwrap();
+
+        // Then: The first line holds the start of the text, right to left
+        assert_eq!(result.lines.len(), 2);
+        assert_eq!(clusters(&result.lines[0]), vec![12, 10, 8, 6, 4, 2, 0]);
+        assert_eq!(clusters(&result.lines[1]), vec![18, 16, 14]);
+        assert_eq!(result.lines[1].x_offset, 30.0);
+
+        // And: Glyph indices refer to the shaped (visual) order
+        assert_eq!(result.lines[0].glyph_indices, vec![3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(result.lines[1].glyph_indices, vec![0, 1, 2]);
+        assert_eq!(result.line_of_glyph(0), Some(1));
+        assert_eq!(result.line_of_glyph(9), Some(0));
+    }
+
+    #[test]
+    fn test_rtl_justified_last_line_aligns_right() {
+        // Given: Justified right-to-left text over two lines
+        let layout = ParagraphLayout::new();
+        let text = "אבגדהוזחטי";
+        let shaped_text = create_rtl_shaped_text(text);
+        let options = LayoutOptions {
+            justification: JustificationMode::Justify,
+            margin_start: 10.0,
+            ..rtl_options(70.0)
+        };
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph(text, &shaped_text, &options)
+            .unwrap();
+
+        // Then: The last line ends at the start margin on the right
+        let last = result.lines.last().unwrap();
+        assert_eq!(last.x_offset + last.width, 60.0);
+    }
+
+    #[test]
+    fn test_rtl_kashida_justification_elongates_words() {
+        // Given: Arabic words justified with kashida over two lines
+        let layout = ParagraphLayout::new();
+        let text = "\u{628}\u{633}\u{645} \u{628}\u{633}\u{645} \u{628}\u{633}\u{645}";
+        let shaped_text = create_rtl_shaped_text(text);
+        let kashida = text_shaper::KashidaGlyph {
+            font_id: 0,
+            glyph_id: GlyphId { id: 999 },
+            advance: 4.0,
+        };
+        let options = LayoutOptions {
+            justification: JustificationMode::Justify,
+            justification_strategy: JustificationStrategy::Kashida(kashida),
+            ..rtl_options(90.0)
+        };
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph(text, &shaped_text, &options)
+            .unwrap();
+
+        // Then: Full lines are filled with tatweels, the last line isn't
+        let tatweels =
+            |line: &LayoutLine| line.glyphs.iter().filter(|g| g.glyph_id.id == 999).count();
+        let (last, full) = result.lines.split_last().unwrap();
+        assert!(!full.is_empty());
+        for line in full {
+            assert!(tatweels(line) > 0);
+            assert_eq!(line.width, 90.0);
+            assert_eq!(line.glyphs.len(), line.glyph_indices.len());
+        }
+        assert_eq!(tatweels(last), 0);
+    }
+
+    #[test]
+    fn test_ltr_text_keeps_shaped_order() {
+        // Given: Left-to-right text with start alignment
+        let layout = ParagraphLayout::new();
+        let shaped_text = create_test_shaped_text(5, 10.0);
+
+        // When: Laying out the paragraph
+        let result = layout
+            .layout_paragraph("Hello", &shaped_text, &LayoutOptions::default())
+            .unwrap();
+
+        // Then: The line starts at the left edge
+        assert_eq!(result.lines[0].x_offset, 0.0);
+        assert_eq!(result.lines[0].glyphs[4].position.x, 40.0);
+    }
 }
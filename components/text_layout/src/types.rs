@@ -1,6 +1,7 @@
 //! Core types for text layout
 
-use font_types::{Direction, PositionedGlyph};
+use font_types::{BoundingBox, Direction, FontId, PositionedGlyph};
+use text_shaper::{HyphenGlyph, KashidaGlyph};
 use thiserror::Error;
 
 /// Errors that can occur during layout operations
@@ -20,7 +21,8 @@ pub enum LayoutError {
 }
 
 /// Text direction for layout
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextDirection {
     /// Left-to-right horizontal text
     LeftToRight,
@@ -35,14 +37,15 @@ impl From<Direction> for TextDirection {
         match dir {
             Direction::LeftToRight => TextDirection::LeftToRight,
             Direction::RightToLeft => TextDirection::RightToLeft,
-            Direction::TopToBottom => TextDirection::TopToBottom,
-            Direction::BottomToTop => TextDirection::TopToBottom, // Map to same as TopToBottom
+            // BottomToTop maps to the same vertical flow as TopToBottom
+            Direction::TopToBottom | Direction::BottomToTop => TextDirection::TopToBottom,
         }
     }
 }
 
 /// Text justification mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JustificationMode {
     /// Align text to left edge
     Left,
@@ -52,21 +55,178 @@ pub enum JustificationMode {
     Center,
     /// Distribute space evenly across line (full justification)
     Justify,
+    /// Align text to the start edge (left for left-to-right text, right for
+    /// right-to-left text)
+    Start,
+    /// Align text to the end edge
+    End,
+}
+
+impl JustificationMode {
+    /// Physical alignment of text flowing in `direction`
+    ///
+    /// `Start` and `End` become `Left` or `Right`; other modes are unchanged.
+    #[must_use]
+    pub fn resolve(self, direction: TextDirection) -> Self {
+        let rtl = direction == TextDirection::RightToLeft;
+        match self {
+            JustificationMode::Start if rtl => JustificationMode::Right,
+            JustificationMode::Start => JustificationMode::Left,
+            JustificationMode::End if rtl => JustificationMode::Left,
+            JustificationMode::End => JustificationMode::Right,
+            mode => mode,
+        }
+    }
+}
+
+/// How justified lines are stretched to their width
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JustificationStrategy {
+    /// Widen the gaps between words
+    #[default]
+    InterWord,
+    /// Widen the gaps between characters of Han, kana and Hangul runs, which
+    /// have few or no word spaces
+    ///
+    /// [`ParagraphLayout::layout_shaped_paragraph`] takes the runs' scripts
+    /// from the shaper; a plain `ShapedText` has none, so every character
+    /// counts. Lines without such characters are justified between words.
+    ///
+    /// [`ParagraphLayout::layout_shaped_paragraph`]: crate::ParagraphLayout::layout_shaped_paragraph
+    InterCharacter,
+    /// Elongate joined Arabic letters with tatweel glyphs (kashida), at the
+    /// points from [`text_shaper::kashida_points`]
+    ///
+    /// The glyph comes from [`text_shaper::TextShaper::kashida_glyph`] and
+    /// is only inserted between letters shaped with its font. Lines without
+    /// such letters are justified between words.
+    Kashida(KashidaGlyph),
+}
+
+// Floats are hashed as their bit representation
+impl std::hash::Hash for JustificationStrategy {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        if let JustificationStrategy::Kashida(kashida) = self {
+            kashida.font_id.hash(state);
+            kashida.glyph_id.hash(state);
+            kashida.advance.to_bits().hash(state);
+        }
+    }
+}
+
+/// Height of a line box, following CSS `line-height`
+///
+/// The difference between the line height and a run's content height
+/// (ascent plus descent) is the leading, split evenly above and below the
+/// content (half-leading).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineHeight {
+    /// The content height, without leading
+    Normal,
+    /// A fixed height in pixels, shared by runs of every size
+    Px(f32),
+    /// A multiple of each run's content height
+    Multiplier(f32),
+}
+
+impl LineHeight {
+    /// Line height from a parsed CSS `font` shorthand
+    /// ([`font_types::CssFont::line_height`]): `normal`, or a fixed height
+    /// in pixels
+    #[must_use]
+    pub fn from_css(line_height: Option<f32>) -> Self {
+        line_height.map_or(LineHeight::Normal, LineHeight::Px)
+    }
+
+    /// Line height for a run with the given content height
+    #[must_use]
+    pub fn resolve(self, content_height: f32) -> f32 {
+        match self {
+            LineHeight::Normal => content_height,
+            LineHeight::Px(height) => height,
+            LineHeight::Multiplier(factor) => content_height * factor,
+        }
+    }
+
+    /// Height and baseline offset of a line box holding runs with the given
+    /// ascents and descents (both positive) from the line's baseline
+    ///
+    /// Each run is given half its leading above and below, and the line box
+    /// spans the highest top and lowest bottom, as browsers do for mixed
+    /// font sizes.
+    #[must_use]
+    pub fn line_box(self, runs: impl IntoIterator<Item = (f32, f32)>) -> (f32, f32) {
+        let (above, below) =
+            runs.into_iter()
+                .fold((0.0f32, 0.0f32), |(above, below), (ascent, descent)| {
+                    let content_height = ascent + descent;
+                    let half_leading = (self.resolve(content_height) - content_height) / 2.0;
+                    (
+                        above.max(ascent + half_leading),
+                        below.max(descent + half_leading),
+                    )
+                });
+        (above + below, above)
+    }
+
+    /// Whether the line height is usable for layout
+    pub(crate) fn is_positive(self) -> bool {
+        match self {
+            LineHeight::Normal => true,
+            LineHeight::Px(value) | LineHeight::Multiplier(value) => value > 0.0,
+        }
+    }
+}
+
+// Floats are hashed as their bit representation
+impl std::hash::Hash for LineHeight {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            LineHeight::Normal => {}
+            LineHeight::Px(value) | LineHeight::Multiplier(value) => value.to_bits().hash(state),
+        }
+    }
 }
 
 /// Options for paragraph layout
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LayoutOptions {
     /// Maximum width for text (in pixels)
     pub max_width: f32,
     /// Optional maximum height (None = no limit)
     pub max_height: Option<f32>,
-    /// Justification mode
+    /// Justification mode (default: `Start`)
     pub justification: JustificationMode,
-    /// Line spacing multiplier (default: 1.2)
-    pub line_spacing: f32,
+    /// Line height (default: 1.2 times the content height)
+    pub line_height: LineHeight,
     /// Text direction
     pub direction: TextDirection,
+    /// Indent of the first line from the start edge (in pixels, negative
+    /// to outdent)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub text_indent: f32,
+    /// Indent every line except the first instead (CSS `text-indent: hanging`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hanging_indent: bool,
+    /// Margin on the start edge (left for left-to-right text, right for
+    /// right-to-left text)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub margin_start: f32,
+    /// Margin on the end edge
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub margin_end: f32,
+    /// How `Justify` stretches lines (default: between words)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub justification_strategy: JustificationStrategy,
+    /// Hyphen shown where a line breaks at a soft hyphen (U+00AD), from
+    /// `TextShaper::hyphen_glyph`; without one, soft hyphens stay invisible
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hyphen: Option<HyphenGlyph>,
 }
 
 impl Default for LayoutOptions {
@@ -74,21 +234,175 @@ impl Default for LayoutOptions {
         Self {
             max_width: 500.0,
             max_height: None,
-            justification: JustificationMode::Left,
-            line_spacing: 1.2,
+            justification: JustificationMode::Start,
+            line_height: LineHeight::Multiplier(1.2),
             direction: TextDirection::LeftToRight,
+            text_indent: 0.0,
+            hanging_indent: false,
+            margin_start: 0.0,
+            margin_end: 0.0,
+            justification_strategy: JustificationStrategy::InterWord,
+            hyphen: None,
+        }
+    }
+}
+
+impl LayoutOptions {
+    /// Start building options from the defaults
+    #[must_use]
+    pub fn builder() -> LayoutOptionsBuilder {
+        LayoutOptionsBuilder::default()
+    }
+
+    /// Indent of a line from the start edge, inside the start margin
+    #[must_use]
+    pub fn line_indent(&self, first_line: bool) -> f32 {
+        if first_line == self.hanging_indent {
+            0.0
+        } else {
+            self.text_indent
         }
     }
+
+    /// Width available to a line after margins and indent
+    #[must_use]
+    pub fn line_width(&self, first_line: bool) -> f32 {
+        self.max_width - self.margin_start - self.margin_end - self.line_indent(first_line)
+    }
+
+    /// Horizontal position of the left edge of a line's box
+    ///
+    /// The indent and start margin are on the right for right-to-left text.
+    #[must_use]
+    pub fn line_left(&self, first_line: bool) -> f32 {
+        if self.direction == TextDirection::RightToLeft {
+            self.margin_end
+        } else {
+            self.margin_start + self.line_indent(first_line)
+        }
+    }
+}
+
+/// Builder for [`LayoutOptions`]
+///
+/// ```
+/// use text_layout::{JustificationMode, LayoutOptions, LineHeight};
+///
+/// let options = LayoutOptions::builder()
+///     .max_width(320.0)
+///     .justification(JustificationMode::Justify)
+///     .line_height(LineHeight::Multiplier(1.5))
+///     .build();
+/// assert_eq!(options.max_height, None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LayoutOptionsBuilder {
+    options: LayoutOptions,
+}
+
+impl LayoutOptionsBuilder {
+    /// Set the maximum line width in pixels
+    #[must_use]
+    pub fn max_width(mut self, width: f32) -> Self {
+        self.options.max_width = width;
+        self
+    }
+
+    /// Limit the paragraph height in pixels
+    #[must_use]
+    pub fn max_height(mut self, height: f32) -> Self {
+        self.options.max_height = Some(height);
+        self
+    }
+
+    /// Set the justification mode
+    #[must_use]
+    pub fn justification(mut self, justification: JustificationMode) -> Self {
+        self.options.justification = justification;
+        self
+    }
+
+    /// Set how justified lines are stretched
+    #[must_use]
+    pub fn justification_strategy(mut self, strategy: JustificationStrategy) -> Self {
+        self.options.justification_strategy = strategy;
+        self
+    }
+
+    /// Set the hyphen shown at soft hyphen line breaks
+    #[must_use]
+    pub fn hyphen(mut self, hyphen: HyphenGlyph) -> Self {
+        self.options.hyphen = Some(hyphen);
+        self
+    }
+
+    /// Set the line height
+    #[must_use]
+    pub fn line_height(mut self, line_height: LineHeight) -> Self {
+        self.options.line_height = line_height;
+        self
+    }
+
+    /// Set the text direction
+    #[must_use]
+    pub fn direction(mut self, direction: TextDirection) -> Self {
+        self.options.direction = direction;
+        self
+    }
+
+    /// Indent the first line by `indent` pixels, or with `hanging`, every
+    /// line except the first
+    #[must_use]
+    pub fn text_indent(mut self, indent: f32, hanging: bool) -> Self {
+        self.options.text_indent = indent;
+        self.options.hanging_indent = hanging;
+        self
+    }
+
+    /// Set the start and end margins in pixels
+    #[must_use]
+    pub fn margins(mut self, start: f32, end: f32) -> Self {
+        self.options.margin_start = start;
+        self.options.margin_end = end;
+        self
+    }
+
+    /// Finish the options
+    #[must_use]
+    pub fn build(self) -> LayoutOptions {
+        self.options
+    }
+}
+
+// Floats are hashed as their bit representation, so options that compare
+// equal (other than 0.0 and -0.0) hash equally
+impl std::hash::Hash for LayoutOptions {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.max_width.to_bits().hash(state);
+        self.max_height.map(f32::to_bits).hash(state);
+        self.justification.hash(state);
+        self.line_height.hash(state);
+        self.direction.hash(state);
+        self.text_indent.to_bits().hash(state);
+        self.hanging_indent.hash(state);
+        self.margin_start.to_bits().hash(state);
+        self.margin_end.to_bits().hash(state);
+        self.justification_strategy.hash(state);
+        self.hyphen
+            .map(|hyphen| (hyphen.font_id, hyphen.glyph_id, hyphen.advance.to_bits()))
+            .hash(state);
+    }
 }
 
 /// A single line of laid out text
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LayoutLine {
     /// Positioned glyphs in this line
     pub glyphs: Vec<PositionedGlyph>,
     /// Total width of the line (before justification)
     pub width: f32,
-    /// Height of the line
+    /// Height of the line box, including leading
     pub height: f32,
     /// Baseline offset from top of line
     pub baseline: f32,
@@ -96,12 +410,28 @@ pub struct LayoutLine {
     pub x_offset: f32,
     /// Vertical offset from top of paragraph
     pub y_offset: f32,
-    /// Text range (start and end character indices)
+    /// Byte range of the line's text; lines only break between glyph
+    /// clusters
     pub text_range: (usize, usize),
+    /// Index in the source `ShapedText` of each glyph in `glyphs`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub glyph_indices: Vec<usize>,
+}
+
+impl LayoutLine {
+    /// Consecutive glyphs of the line sharing a font, in display order
+    ///
+    /// Renderers can bind each font once per run rather than per glyph.
+    pub fn font_runs(&self) -> impl Iterator<Item = (FontId, &[PositionedGlyph])> {
+        self.glyphs
+            .chunk_by(|a, b| a.font_id == b.font_id)
+            .map(|run| (run[0].font_id, run))
+    }
 }
 
 /// Result of a layout operation
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LayoutResult {
     /// Lines of text
     pub lines: Vec<LayoutLine>,
@@ -109,12 +439,248 @@ pub struct LayoutResult {
     pub total_height: f32,
     /// Total width of laid out text
     pub total_width: f32,
-    /// Whether text overflowed max_height constraint
+    /// Whether text overflowed `max_height` constraint
     pub overflow: bool,
+    /// Whether the lines are vertical columns (from `VerticalLayout`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vertical: bool,
+    /// Line holding each glyph of the source `ShapedText`, by glyph index
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub glyph_lines: Vec<usize>,
+    /// Bounds of each line: its line box, grown by glyphs drawn outside it
+    /// once [`add_ink_bounds`](Self::add_ink_bounds) has run
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub line_bounds: Vec<Rect>,
+}
+
+/// An axis-aligned rectangle in paragraph coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    /// Left edge
+    pub x: f32,
+    /// Top edge
+    pub y: f32,
+    /// Width
+    pub width: f32,
+    /// Height
+    pub height: f32,
+}
+
+impl Rect {
+    /// Whether the rectangles overlap; rectangles sharing only an edge
+    /// don't
+    #[must_use]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    /// Smallest rectangle containing both
+    fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
+impl LayoutResult {
+    /// Line holding a glyph of the source `ShapedText`
+    #[must_use]
+    pub fn line_of_glyph(&self, glyph_index: usize) -> Option<usize> {
+        self.glyph_lines.get(glyph_index).copied()
+    }
+
+    /// Map each source glyph to the line holding it
+    pub(crate) fn glyph_lines(lines: &[LayoutLine]) -> Vec<usize> {
+        let mut glyph_lines = vec![0; lines.iter().map(|line| line.glyph_indices.len()).sum()];
+        for (line_index, line) in lines.iter().enumerate() {
+            for &glyph_index in &line.glyph_indices {
+                glyph_lines[glyph_index] = line_index;
+            }
+        }
+        glyph_lines
+    }
+
+    /// Line box of each line, for [`line_bounds`](Self::line_bounds)
+    pub(crate) fn line_boxes(&self) -> Vec<Rect> {
+        self.lines.iter().map(|line| self.line_box(line)).collect()
+    }
+
+    /// Rectangle a line takes, spanning its glyphs' advances and at least
+    /// its width along the line
+    fn line_box(&self, line: &LayoutLine) -> Rect {
+        let origin = line.glyphs.first().map_or(0.0, |glyph| self.pen(glyph));
+        let extent = line
+            .glyphs
+            .iter()
+            .fold((0.0, line.width), |(start, end), glyph| {
+                let pen = self.pen(glyph) - origin;
+                (
+                    f32::min(start, pen),
+                    f32::max(end, pen + self.advance(glyph)),
+                )
+            });
+        self.line_rect(line, extent)
+    }
+
+    /// Grow each line's bounds to cover the ink of its glyphs
+    ///
+    /// `extents` returns a glyph's ink bounds relative to its origin with y
+    /// up, at the shaped font size (e.g. from
+    /// `GlyphRenderer::glyph_extents`), or `None` for glyphs without ink.
+    /// Accents and descenders drawn past the line box then keep their line
+    /// in [`lines_in_rect`](Self::lines_in_rect). Vertical columns keep
+    /// their line boxes.
+    pub fn add_ink_bounds<F>(&mut self, mut extents: F)
+    where
+        F: FnMut(&PositionedGlyph) -> Option<BoundingBox>,
+    {
+        let mut bounds = Vec::with_capacity(self.lines.len());
+        for (index, line) in self.lines.iter().enumerate() {
+            let mut line_bounds = self.line_bound(index, line);
+            if !self.vertical {
+                let origin = line.glyphs.first().map_or(0.0, |glyph| self.pen(glyph));
+                for glyph in &line.glyphs {
+                    let Some(ink) = extents(glyph) else {
+                        continue;
+                    };
+                    // Glyph origin in paragraph coordinates, with y down
+                    let x = line.x_offset + glyph.position.x - origin;
+                    let y = line.y_offset + line.baseline - glyph.position.y;
+                    line_bounds = line_bounds.union(&Rect {
+                        x: x + ink.min_x * glyph.scale,
+                        y: y - ink.max_y * glyph.scale,
+                        width: (ink.max_x - ink.min_x) * glyph.scale,
+                        height: (ink.max_y - ink.min_y) * glyph.scale,
+                    });
+                }
+            }
+            bounds.push(line_bounds);
+        }
+        self.line_bounds = bounds;
+    }
+
+    /// Lines whose bounds intersect `rect`, with their indices, in order
+    ///
+    /// Renderers pass the viewport to skip lines scrolled out of view.
+    pub fn lines_in_rect(&self, rect: Rect) -> impl Iterator<Item = (usize, &LayoutLine)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(move |&(index, line)| self.line_bound(index, line).intersects(&rect))
+    }
+
+    /// Bounds of a line, or its line box if they aren't known
+    fn line_bound(&self, index: usize, line: &LayoutLine) -> Rect {
+        self.line_bounds
+            .get(index)
+            .copied()
+            .unwrap_or_else(|| self.line_box(line))
+    }
+
+    /// Rectangles highlighting the text in a byte range
+    ///
+    /// Each line (or column, for vertical layouts) contributes one rectangle
+    /// per visually contiguous run of selected glyphs, so a selection
+    /// crossing a bidi boundary is split within the line. Glyphs are
+    /// selected whole: a ligature is highlighted if any of its characters
+    /// is selected.
+    #[must_use]
+    pub fn selection_rects(&self, range: std::ops::Range<usize>) -> Vec<Rect> {
+        if range.is_empty() {
+            return Vec::new();
+        }
+
+        // A glyph covers the bytes up to the next cluster in the text
+        let mut clusters: Vec<usize> = self
+            .lines
+            .iter()
+            .flat_map(|line| line.glyphs.iter().map(|glyph| glyph.cluster as usize))
+            .collect();
+        clusters.sort_unstable();
+        clusters.dedup();
+        let selected = |glyph: &PositionedGlyph| {
+            let start = glyph.cluster as usize;
+            let end = clusters
+                .get(clusters.partition_point(|&cluster| cluster <= start))
+                .copied()
+                .unwrap_or(start + 1);
+            start < range.end && end > range.start
+        };
+
+        let mut rects = Vec::new();
+        for line in &self.lines {
+            // Glyph extents along the line, relative to its first glyph
+            let origin = line.glyphs.first().map_or(0.0, |glyph| self.pen(glyph));
+            let mut run: Option<(f32, f32)> = None;
+            for glyph in &line.glyphs {
+                if selected(glyph) {
+                    let start = self.pen(glyph) - origin;
+                    let end = start + self.advance(glyph);
+                    run = Some(run.map_or((start, end), |(run_start, run_end)| {
+                        (run_start.min(start), run_end.max(end))
+                    }));
+                } else if let Some(extent) = run.take() {
+                    rects.push(self.line_rect(line, extent));
+                }
+            }
+            if let Some(extent) = run {
+                rects.push(self.line_rect(line, extent));
+            }
+        }
+        rects
+    }
+
+    /// Pen position of a glyph along the line's flow
+    fn pen(&self, glyph: &PositionedGlyph) -> f32 {
+        if self.vertical {
+            glyph.position.y - glyph.offset.y
+        } else {
+            glyph.position.x - glyph.offset.x
+        }
+    }
+
+    /// Advance of a glyph along the line's flow
+    fn advance(&self, glyph: &PositionedGlyph) -> f32 {
+        if self.vertical {
+            glyph.advance.y
+        } else {
+            glyph.advance.x
+        }
+    }
+
+    /// Rectangle covering `start..end` along a line
+    fn line_rect(&self, line: &LayoutLine, (start, end): (f32, f32)) -> Rect {
+        if self.vertical {
+            // Columns are `height` wide and `width` tall
+            Rect {
+                x: line.x_offset,
+                y: line.y_offset + start,
+                width: line.height,
+                height: end - start,
+            }
+        } else {
+            Rect {
+                x: line.x_offset + start,
+                y: line.y_offset,
+                width: end - start,
+                height: line.height,
+            }
+        }
+    }
 }
 
 /// A line break opportunity
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineBreak {
     /// Byte offset in the text
     pub offset: usize,
@@ -123,9 +689,44 @@ pub struct LineBreak {
 }
 
 #[cfg(test)]
+#[allow(clippy::float_cmp, clippy::cast_precision_loss)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_layout_line_font_runs() {
+        // Given: A line whose glyphs come from two fonts
+        let glyph = |font_id| PositionedGlyph {
+            glyph_id: font_types::GlyphId { id: 1 },
+            font_id,
+            position: font_types::Point { x: 0.0, y: 0.0 },
+            advance: font_types::Vector { x: 5.0, y: 0.0 },
+            offset: font_types::Vector { x: 0.0, y: 0.0 },
+            scale: 1.0,
+            cluster: 0,
+            unsafe_to_break: false,
+        };
+        let line = LayoutLine {
+            glyphs: vec![glyph(1), glyph(1), glyph(2), glyph(1)],
+            width: 20.0,
+            height: 10.0,
+            baseline: 8.0,
+            x_offset: 0.0,
+            y_offset: 0.0,
+            text_range: (0, 4),
+            glyph_indices: vec![0, 1, 2, 3],
+        };
+
+        // When: Grouping them by font
+        let runs: Vec<(FontId, usize)> = line
+            .font_runs()
+            .map(|(font_id, glyphs)| (font_id, glyphs.len()))
+            .collect();
+
+        // Then: Each change of font starts a run
+        assert_eq!(runs, [(1, 2), (2, 1), (1, 1)]);
+    }
+
     // ========== LayoutError Tests ==========
 
     #[test]
@@ -159,6 +760,7 @@ mod tests {
     // ========== TextDirection Tests ==========
 
     #[test]
+    #[allow(clippy::no_effect_underscore_binding)]
     fn test_text_direction_variants() {
         // Given: Text direction options
         // When: Creating each variant
@@ -190,6 +792,7 @@ mod tests {
     // ========== JustificationMode Tests ==========
 
     #[test]
+    #[allow(clippy::no_effect_underscore_binding)]
     fn test_justification_mode_variants() {
         // Given: Justification options
         // When: Creating each variant
@@ -200,6 +803,36 @@ mod tests {
         let _justify = JustificationMode::Justify;
     }
 
+    #[test]
+    fn test_justification_mode_resolve() {
+        // Given: Logical justification modes
+        // When: Resolving them for a text direction
+        // Then: The start edge follows the direction
+        let ltr = TextDirection::LeftToRight;
+        let rtl = TextDirection::RightToLeft;
+        assert_eq!(
+            JustificationMode::Start.resolve(ltr),
+            JustificationMode::Left
+        );
+        assert_eq!(
+            JustificationMode::Start.resolve(rtl),
+            JustificationMode::Right
+        );
+        assert_eq!(
+            JustificationMode::End.resolve(ltr),
+            JustificationMode::Right
+        );
+        assert_eq!(JustificationMode::End.resolve(rtl), JustificationMode::Left);
+        assert_eq!(
+            JustificationMode::Left.resolve(rtl),
+            JustificationMode::Left
+        );
+        assert_eq!(
+            JustificationMode::Justify.resolve(rtl),
+            JustificationMode::Justify
+        );
+    }
+
     #[test]
     fn test_justification_mode_equality() {
         // Given: Justification modes
@@ -209,6 +842,44 @@ mod tests {
         assert_ne!(JustificationMode::Left, JustificationMode::Right);
     }
 
+    // ========== LineHeight Tests ==========
+
+    #[test]
+    fn test_line_height_resolve() {
+        // Given: Each line height mode
+        // When: Resolving for 20px of content
+        // Then: Should give the line box height
+        assert_eq!(LineHeight::Normal.resolve(20.0), 20.0);
+        assert_eq!(LineHeight::Px(30.0).resolve(20.0), 30.0);
+        assert_eq!(LineHeight::Multiplier(1.5).resolve(20.0), 30.0);
+    }
+
+    #[test]
+    fn test_line_box_half_leading() {
+        // Given: A run with 15px ascent and 5px descent
+        // When: The line is 30px tall
+        // Then: The 10px of leading is split above and below
+        assert_eq!(LineHeight::Px(30.0).line_box([(15.0, 5.0)]), (30.0, 20.0));
+        assert_eq!(LineHeight::Normal.line_box([(15.0, 5.0)]), (20.0, 15.0));
+
+        // And: A fixed height smaller than the content gives negative leading
+        assert_eq!(LineHeight::Px(10.0).line_box([(15.0, 5.0)]), (10.0, 10.0));
+    }
+
+    #[test]
+    fn test_line_box_mixed_sizes() {
+        // Given: A small run and a run twice its size on one line
+        let runs = [(15.0, 5.0), (30.0, 10.0)];
+
+        // When: Each run's line height scales with its size
+        // Then: The larger run sets the line box
+        assert_eq!(LineHeight::Multiplier(1.5).line_box(runs), (60.0, 40.0));
+
+        // When: Both runs share a fixed line height
+        // Then: Each run's half-leading positions it around the baseline
+        assert_eq!(LineHeight::Px(30.0).line_box(runs), (35.0, 25.0));
+    }
+
     // ========== LayoutOptions Tests ==========
 
     #[test]
@@ -220,9 +891,82 @@ mod tests {
 
         assert_eq!(options.max_width, 500.0);
         assert_eq!(options.max_height, None);
-        assert_eq!(options.justification, JustificationMode::Left);
-        assert_eq!(options.line_spacing, 1.2);
+        assert_eq!(options.justification, JustificationMode::Start);
+        assert_eq!(options.line_height, LineHeight::Multiplier(1.2));
         assert_eq!(options.direction, TextDirection::LeftToRight);
+        assert_eq!(options.text_indent, 0.0);
+        assert!(!options.hanging_indent);
+        assert_eq!(options.margin_start, 0.0);
+        assert_eq!(options.margin_end, 0.0);
+    }
+
+    #[test]
+    fn test_line_height_from_css_shorthand() {
+        // Given: Shorthands with and without a line height
+        let css = font_types::FontDescriptor::from_css("20px/1.5 serif").unwrap();
+        let normal = font_types::FontDescriptor::from_css("20px serif").unwrap();
+
+        // Then: Unitless heights are fixed at the font size, `normal` stays
+        assert_eq!(LineHeight::from_css(css.line_height), LineHeight::Px(30.0));
+        assert_eq!(LineHeight::from_css(normal.line_height), LineHeight::Normal);
+    }
+
+    #[test]
+    fn test_layout_options_builder() {
+        // Given: A builder setting a height limit, indent and margins
+        let options = LayoutOptions::builder()
+            .max_width(300.0)
+            .max_height(120.0)
+            .justification(JustificationMode::Center)
+            .direction(TextDirection::RightToLeft)
+            .text_indent(16.0, true)
+            .margins(4.0, 8.0)
+            .build();
+
+        // Then: The options match a struct literal with the same fields
+        assert_eq!(
+            options,
+            LayoutOptions {
+                max_width: 300.0,
+                max_height: Some(120.0),
+                justification: JustificationMode::Center,
+                direction: TextDirection::RightToLeft,
+                text_indent: 16.0,
+                hanging_indent: true,
+                margin_start: 4.0,
+                margin_end: 8.0,
+                ..Default::default()
+            }
+        );
+        assert_eq!(LayoutOptions::builder().build(), LayoutOptions::default());
+    }
+
+    #[test]
+    fn test_layout_options_line_box() {
+        // Given: Options with margins and a first-line indent
+        let mut options = LayoutOptions {
+            max_width: 200.0,
+            text_indent: 20.0,
+            margin_start: 10.0,
+            margin_end: 30.0,
+            ..Default::default()
+        };
+
+        // When/Then: The first line is indented from the start edge
+        assert_eq!(options.line_width(true), 140.0);
+        assert_eq!(options.line_width(false), 160.0);
+        assert_eq!(options.line_left(true), 30.0);
+        assert_eq!(options.line_left(false), 10.0);
+
+        // And: A hanging indent swaps which lines are indented
+        options.hanging_indent = true;
+        assert_eq!(options.line_indent(true), 0.0);
+        assert_eq!(options.line_indent(false), 20.0);
+
+        // And: Right-to-left lines start after the end margin on the left
+        options.direction = TextDirection::RightToLeft;
+        assert_eq!(options.line_left(true), 30.0);
+        assert_eq!(options.line_left(false), 30.0);
     }
 
     #[test]
@@ -234,14 +978,20 @@ mod tests {
             max_width: 300.0,
             max_height: Some(200.0),
             justification: JustificationMode::Justify,
-            line_spacing: 1.5,
+            line_height: LineHeight::Multiplier(1.5),
             direction: TextDirection::RightToLeft,
+            text_indent: 0.0,
+            hanging_indent: false,
+            margin_start: 0.0,
+            margin_end: 0.0,
+            justification_strategy: JustificationStrategy::InterWord,
+            hyphen: None,
         };
 
         assert_eq!(options.max_width, 300.0);
         assert_eq!(options.max_height, Some(200.0));
         assert_eq!(options.justification, JustificationMode::Justify);
-        assert_eq!(options.line_spacing, 1.5);
+        assert_eq!(options.line_height, LineHeight::Multiplier(1.5));
         assert_eq!(options.direction, TextDirection::RightToLeft);
     }
 
@@ -254,15 +1004,21 @@ mod tests {
             max_width: 400.0,
             max_height: Some(300.0),
             justification: JustificationMode::Center,
-            line_spacing: 1.8,
+            line_height: LineHeight::Multiplier(1.8),
             direction: TextDirection::TopToBottom,
+            text_indent: 0.0,
+            hanging_indent: false,
+            margin_start: 0.0,
+            margin_end: 0.0,
+            justification_strategy: JustificationStrategy::InterWord,
+            hyphen: None,
         };
 
         let cloned = options.clone();
         assert_eq!(options.max_width, cloned.max_width);
         assert_eq!(options.max_height, cloned.max_height);
         assert_eq!(options.justification, cloned.justification);
-        assert_eq!(options.line_spacing, cloned.line_spacing);
+        assert_eq!(options.line_height, cloned.line_height);
         assert_eq!(options.direction, cloned.direction);
     }
 
@@ -281,6 +1037,7 @@ mod tests {
             x_offset: 10.0,
             y_offset: 5.0,
             text_range: (0, 10),
+            glyph_indices: vec![],
         };
 
         assert_eq!(line.width, 100.0);
@@ -304,6 +1061,7 @@ mod tests {
             x_offset: 0.0,
             y_offset: 30.0,
             text_range: (10, 25),
+            glyph_indices: vec![],
         };
 
         let cloned = line.clone();
@@ -324,6 +1082,9 @@ mod tests {
             total_height: 100.0,
             total_width: 200.0,
             overflow: false,
+            vertical: false,
+            glyph_lines: vec![],
+            line_bounds: vec![],
         };
 
         assert_eq!(result.lines.len(), 0);
@@ -342,6 +1103,9 @@ mod tests {
             total_height: 500.0,
             total_width: 300.0,
             overflow: true,
+            vertical: false,
+            glyph_lines: vec![],
+            line_bounds: vec![],
         };
 
         assert!(result.overflow);
@@ -357,6 +1121,9 @@ mod tests {
             total_height: 250.0,
             total_width: 350.0,
             overflow: false,
+            vertical: false,
+            glyph_lines: vec![],
+            line_bounds: vec![],
         };
 
         let cloned = result.clone();
@@ -410,4 +1177,193 @@ mod tests {
         assert_eq!(b1, b2);
         assert_ne!(b1, b3);
     }
+
+    // ========== Selection Tests ==========
+
+    /// A line of 10px glyphs with the given clusters in visual order,
+    /// starting at pen position `x`
+    fn selection_line(clusters: &[u32], x: f32, x_offset: f32, y_offset: f32) -> LayoutLine {
+        let glyphs = clusters
+            .iter()
+            .enumerate()
+            .map(|(index, &cluster)| PositionedGlyph {
+                glyph_id: font_types::GlyphId { id: 1 },
+                font_id: 0,
+                position: font_types::Point {
+                    x: x + index as f32 * 10.0,
+                    y: 0.0,
+                },
+                advance: font_types::Vector { x: 10.0, y: 0.0 },
+                offset: font_types::Vector { x: 0.0, y: 0.0 },
+                scale: 1.0,
+                cluster,
+                unsafe_to_break: false,
+            })
+            .collect::<Vec<_>>();
+        LayoutLine {
+            width: glyphs.len() as f32 * 10.0,
+            glyphs,
+            height: 20.0,
+            baseline: 15.0,
+            x_offset,
+            y_offset,
+            text_range: (0, 0),
+            glyph_indices: vec![],
+        }
+    }
+
+    fn selection_result(lines: Vec<LayoutLine>, vertical: bool) -> LayoutResult {
+        LayoutResult {
+            lines,
+            total_height: 0.0,
+            total_width: 0.0,
+            overflow: false,
+            vertical,
+            glyph_lines: vec![],
+            line_bounds: vec![],
+        }
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_selection_rects_across_lines() {
+        // Given: Two lines, the second continuing the first's pen position
+        let result = selection_result(
+            vec![
+                selection_line(&[0, 1, 2, 3, 4], 0.0, 5.0, 0.0),
+                selection_line(&[5, 6, 7, 8, 9], 50.0, 0.0, 24.0),
+            ],
+            false,
+        );
+
+        // When: Selecting bytes 3..7
+        let rects = result.selection_rects(3..7);
+
+        // Then: Each line gets one rectangle relative to its own start
+        assert_eq!(
+            rects,
+            vec![rect(35.0, 0.0, 20.0, 20.0), rect(0.0, 24.0, 20.0, 20.0)]
+        );
+        assert!(result.selection_rects(4..4).is_empty());
+    }
+
+    #[test]
+    fn test_selection_rects_split_at_bidi_boundary() {
+        // Given: A line with a right-to-left run for bytes 3..6
+        let result = selection_result(
+            vec![selection_line(&[0, 1, 2, 5, 4, 3, 6, 7], 0.0, 0.0, 0.0)],
+            false,
+        );
+
+        // When: Selecting bytes 2..4, crossing into the right-to-left run
+        let rects = result.selection_rects(2..4);
+
+        // Then: The selection is split into two visual rectangles
+        assert_eq!(
+            rects,
+            vec![rect(20.0, 0.0, 10.0, 20.0), rect(50.0, 0.0, 10.0, 20.0)]
+        );
+
+        // And: The whole run is one rectangle
+        assert_eq!(
+            result.selection_rects(3..6),
+            vec![rect(30.0, 0.0, 30.0, 20.0)]
+        );
+    }
+
+    #[test]
+    fn test_selection_rects_select_whole_ligature() {
+        // Given: A ligature glyph covering bytes 2..5
+        let result = selection_result(vec![selection_line(&[0, 1, 2, 5], 0.0, 0.0, 0.0)], false);
+
+        // When/Then: Selecting part of the ligature highlights all of it
+        assert_eq!(
+            result.selection_rects(3..4),
+            vec![rect(20.0, 0.0, 10.0, 20.0)]
+        );
+    }
+
+    #[test]
+    fn test_selection_rects_vertical_columns() {
+        // Given: Two 20px wide columns of 20px tall glyphs
+        let column = |clusters: &[u32], x_offset: f32| {
+            let mut line = selection_line(clusters, 0.0, x_offset, 0.0);
+            for (index, glyph) in line.glyphs.iter_mut().enumerate() {
+                glyph.position = font_types::Point {
+                    x: 0.0,
+                    y: index as f32 * 20.0,
+                };
+                glyph.advance = font_types::Vector { x: 0.0, y: 20.0 };
+            }
+            line
+        };
+        let result = selection_result(
+            vec![column(&[0, 1, 2], 0.0), column(&[3, 4, 5], 20.0)],
+            true,
+        );
+
+        // When: Selecting bytes 1..4
+        let rects = result.selection_rects(1..4);
+
+        // Then: Each column gets a vertical rectangle
+        assert_eq!(
+            rects,
+            vec![rect(0.0, 20.0, 20.0, 40.0), rect(20.0, 0.0, 20.0, 20.0)]
+        );
+    }
+
+    // ========== Culling Tests ==========
+
+    #[test]
+    fn test_lines_in_rect_culls_by_line_box_and_ink() {
+        // Given: Three 20px lines, the last with an accent reaching 10px
+        // above its line box
+        let mut lines = vec![
+            selection_line(&[0, 1, 2], 0.0, 0.0, 0.0),
+            selection_line(&[3, 4, 5], 30.0, 0.0, 20.0),
+            selection_line(&[6, 7, 8], 60.0, 0.0, 40.0),
+        ];
+        lines[2].glyphs[0].glyph_id = font_types::GlyphId { id: 2 };
+        let mut result = selection_result(lines, false);
+        let extents = |glyph: &PositionedGlyph| {
+            let max_y = if glyph.glyph_id.id == 2 { 25.0 } else { 12.0 };
+            Some(BoundingBox {
+                min_x: 1.0,
+                min_y: -2.0,
+                max_x: 9.0,
+                max_y,
+            })
+        };
+        let viewport = rect(0.0, 25.0, 100.0, 10.0);
+        let visible = |result: &LayoutResult, viewport| -> Vec<usize> {
+            result
+                .lines_in_rect(viewport)
+                .map(|(index, _)| index)
+                .collect()
+        };
+
+        // When: Culling with line boxes only
+        // Then: Only the line the viewport crosses is kept
+        assert_eq!(visible(&result, viewport), [1]);
+        result.line_bounds = result.line_boxes();
+        assert_eq!(result.line_bounds[1], rect(0.0, 20.0, 30.0, 20.0));
+        assert_eq!(visible(&result, viewport), [1]);
+
+        // When: Adding the glyphs' ink
+        result.add_ink_bounds(extents);
+
+        // Then: The accent keeps the last line in view
+        assert_eq!(result.line_bounds[2], rect(0.0, 30.0, 30.0, 30.0));
+        assert_eq!(result.line_bounds[0], rect(0.0, 0.0, 30.0, 20.0));
+        assert_eq!(visible(&result, viewport), [1, 2]);
+        assert!(visible(&result, rect(0.0, 60.0, 100.0, 10.0)).is_empty());
+    }
 }
@@ -1,5 +1,6 @@
 //! Vertical text layout for CJK languages
 
+use crate::line_breaker::{glyph_clusters, GlyphCluster};
 use crate::types::{LayoutError, LayoutLine, LayoutOptions, LayoutResult, TextDirection};
 use font_types::{Point, PositionedGlyph, ShapedText, Vector};
 
@@ -11,6 +12,7 @@ pub struct VerticalLayout;
 
 impl VerticalLayout {
     /// Create a new vertical layout engine
+    #[must_use]
     pub fn new() -> Self {
         Self
     }
@@ -32,6 +34,13 @@ impl VerticalLayout {
     ///
     /// Result containing the laid out lines or an error.
     ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The text is empty
+    /// - Layout options are invalid (e.g., negative width)
+    /// - The direction is not `TopToBottom`
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -44,6 +53,15 @@ impl VerticalLayout {
     /// # let shaped_text = ShapedText { glyphs: vec![], width: 0.0, height: 0.0, baseline: 0.0 };
     /// let result = layout.layout_vertical("縦書き", &shaped_text, &options);
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "text_layout::layout_vertical",
+            level = "debug",
+            skip_all,
+            fields(text_len = text.len(), glyphs = shaped_text.glyphs.len())
+        )
+    )]
     pub fn layout_vertical(
         &self,
         text: &str,
@@ -51,22 +69,22 @@ impl VerticalLayout {
         options: &LayoutOptions,
     ) -> Result<LayoutResult, LayoutError> {
         // Validate inputs
-        self.validate_inputs(text, options)?;
+        Self::validate_inputs(text, options)?;
 
         // For vertical layout, max_width becomes max column height
         let max_column_height = options.max_width;
 
         // Break into vertical columns
-        let mut columns = self.break_into_columns(shaped_text, max_column_height)?;
+        let mut columns = Self::break_into_columns(text, shaped_text, max_column_height);
 
         // Position columns horizontally (right-to-left)
-        self.position_columns(&mut columns, shaped_text);
+        Self::position_columns(&mut columns, shaped_text);
 
         // Rotate glyphs for vertical orientation
-        self.apply_vertical_orientation(&mut columns);
+        Self::apply_vertical_orientation(&mut columns, shaped_text);
 
         // Calculate total dimensions
-        let total_width = columns.last().map(|c| c.x_offset + c.height).unwrap_or(0.0);
+        let total_width = columns.last().map_or(0.0, |c| c.x_offset + c.height);
         let total_height = columns
             .iter()
             .map(|c| c.width) // In vertical layout, width is the vertical extent
@@ -80,16 +98,21 @@ impl VerticalLayout {
             false
         };
 
-        Ok(LayoutResult {
+        let mut result = LayoutResult {
+            glyph_lines: LayoutResult::glyph_lines(&columns),
+            line_bounds: Vec::new(),
             lines: columns,
             total_height,
             total_width,
             overflow,
-        })
+            vertical: true,
+        };
+        result.line_bounds = result.line_boxes();
+        Ok(result)
     }
 
     /// Validate layout inputs
-    fn validate_inputs(&self, text: &str, options: &LayoutOptions) -> Result<(), LayoutError> {
+    fn validate_inputs(text: &str, options: &LayoutOptions) -> Result<(), LayoutError> {
         if text.is_empty() {
             return Err(LayoutError::InvalidText("Text is empty".to_string()));
         }
@@ -110,15 +133,18 @@ impl VerticalLayout {
     }
 
     /// Break shaped text into vertical columns
+    ///
+    /// Columns break only between glyph clusters; each column's
+    /// `text_range` is the byte range of its clusters.
     fn break_into_columns(
-        &self,
+        text: &str,
         shaped_text: &ShapedText,
         max_column_height: f32,
-    ) -> Result<Vec<LayoutLine>, LayoutError> {
+    ) -> Vec<LayoutLine> {
         let mut columns = Vec::new();
 
         if shaped_text.glyphs.is_empty() {
-            return Ok(vec![LayoutLine {
+            return vec![LayoutLine {
                 glyphs: vec![],
                 width: shaped_text.height,
                 height: shaped_text.width,
@@ -126,66 +152,66 @@ impl VerticalLayout {
                 x_offset: 0.0,
                 y_offset: 0.0,
                 text_range: (0, 0),
-            }]);
+                glyph_indices: vec![],
+            }];
         }
 
-        // Simple column breaking: stack glyphs vertically until max height
-        let mut current_column_glyphs: Vec<PositionedGlyph> = Vec::new();
+        let vertical = is_vertically_shaped(shaped_text);
+        let thickness = column_thickness(shaped_text);
+        let column = |clusters: &[GlyphCluster], column_height: f32| {
+            let glyphs = clusters.first().map_or(0, |c| c.glyphs.start)
+                ..clusters.last().map_or(0, |c| c.glyphs.end);
+            let start = clusters.iter().map(|c| c.text.start).min().unwrap_or(0);
+            let end = clusters.iter().map(|c| c.text.end).max().unwrap_or(start);
+            LayoutLine {
+                glyphs: shaped_text.glyphs[glyphs.clone()].to_vec(),
+                width: column_height,
+                height: thickness,
+                // Glyphs are centered on the column's central baseline
+                baseline: if vertical {
+                    shaped_text.baseline
+                } else {
+                    thickness / 2.0
+                },
+                x_offset: 0.0,
+                y_offset: 0.0,
+                text_range: (start, end),
+                glyph_indices: glyphs.collect(),
+            }
+        };
+
+        // Simple column breaking: stack clusters vertically until max height
+        let clusters = glyph_clusters(text, &shaped_text.glyphs);
+        let mut column_start = 0;
         let mut current_height = 0.0;
-        let mut char_index = 0;
-        let mut column_start_char = 0;
-
-        for glyph in &shaped_text.glyphs {
-            // In vertical layout, we stack by glyph height (which comes from advance.y)
-            // For simplicity, use a fixed glyph height based on font metrics
-            let glyph_height = shaped_text.height; // Approximate
-
-            // Check if adding this glyph would exceed max column height
-            if current_height + glyph_height > max_column_height
-                && !current_column_glyphs.is_empty()
-            {
-                // Create column with current glyphs
-                columns.push(LayoutLine {
-                    glyphs: current_column_glyphs.clone(),
-                    width: current_height,
-                    height: shaped_text.width, // Column width
-                    baseline: shaped_text.baseline,
-                    x_offset: 0.0,
-                    y_offset: 0.0,
-                    text_range: (column_start_char, char_index),
-                });
+
+        for (index, cluster) in clusters.iter().enumerate() {
+            let cluster_height: f32 = shaped_text.glyphs[cluster.glyphs.clone()]
+                .iter()
+                .map(|glyph| column_advance(glyph, vertical, shaped_text.height))
+                .sum();
+
+            // Check if adding this cluster would exceed max column height
+            if current_height + cluster_height > max_column_height && index > column_start {
+                columns.push(column(&clusters[column_start..index], current_height));
 
                 // Start new column
-                current_column_glyphs.clear();
                 current_height = 0.0;
-                column_start_char = char_index;
+                column_start = index;
             }
 
-            // Add glyph to current column
-            current_column_glyphs.push(glyph.clone());
-            current_height += glyph_height;
-            char_index += 1;
+            current_height += cluster_height;
         }
 
-        // Add final column if not empty
-        if !current_column_glyphs.is_empty() {
-            columns.push(LayoutLine {
-                glyphs: current_column_glyphs,
-                width: current_height,
-                height: shaped_text.width,
-                baseline: shaped_text.baseline,
-                x_offset: 0.0,
-                y_offset: 0.0,
-                text_range: (column_start_char, char_index),
-            });
-        }
+        // Add final column
+        columns.push(column(&clusters[column_start..], current_height));
 
-        Ok(columns)
+        columns
     }
 
     /// Position columns horizontally (right-to-left)
-    fn position_columns(&self, columns: &mut [LayoutLine], shaped_text: &ShapedText) {
-        let column_width = shaped_text.width;
+    fn position_columns(columns: &mut [LayoutLine], shaped_text: &ShapedText) {
+        let column_width = column_thickness(shaped_text);
         let mut x_offset = 0.0;
 
         // In vertical layout, columns progress right-to-left
@@ -198,32 +224,89 @@ impl VerticalLayout {
 
     /// Apply vertical orientation to glyphs
     ///
-    /// In vertical text, some glyphs need to be rotated 90 degrees.
-    /// This is a simplified implementation that adjusts positioning.
-    fn apply_vertical_orientation(&self, columns: &mut [LayoutLine]) {
+    /// Vertically shaped glyphs keep their advances and offsets from their
+    /// vertical origin. Horizontally shaped glyphs (from fonts without
+    /// vertical metrics) get the synthesized metrics the shaper uses for
+    /// such fonts: each spacing glyph stands upright in a cell as tall as
+    /// the line height, centered on the column's baseline with its own
+    /// baseline at the ascent, and marks keep their place relative to it.
+    fn apply_vertical_orientation(columns: &mut [LayoutLine], shaped_text: &ShapedText) {
+        let vertical = is_vertically_shaped(shaped_text);
+        let cell_height = shaped_text.height;
+        let ascent = shaped_text.baseline;
+
         for column in columns {
+            // Restart each column at 0
             let mut y_pos = 0.0;
+            // Pen position, advance and top of the current cell's glyph
+            let mut cell = (0.0, 0.0, 0.0);
 
             for glyph in &mut column.glyphs {
-                // Stack glyphs vertically
+                let advance = column_advance(glyph, vertical, cell_height);
+                if vertical {
+                    glyph.position = Point {
+                        x: glyph.offset.x,
+                        y: y_pos + glyph.offset.y,
+                    };
+                    y_pos += advance;
+                    continue;
+                }
+
+                // Horizontal positions have y up from the baseline
+                let pen_x = glyph.position.x - glyph.offset.x;
+                if glyph.advance.x != 0.0 {
+                    cell = (pen_x, glyph.advance.x, y_pos);
+                }
+                let (cell_x, cell_advance, cell_top) = cell;
                 glyph.position = Point {
-                    x: glyph.position.x,
-                    y: y_pos,
+                    x: glyph.position.x - cell_x - cell_advance / 2.0,
+                    y: cell_top + ascent - glyph.position.y,
                 };
-
-                // Advance vertically instead of horizontally
-                y_pos += glyph.advance.x; // Use horizontal advance as vertical spacing
-
-                // Swap advance to vertical
-                glyph.advance = Vector {
-                    x: 0.0,
-                    y: glyph.advance.x,
+                glyph.offset = Vector {
+                    x: glyph.position.x,
+                    y: glyph.position.y - y_pos,
                 };
+                glyph.advance = Vector { x: 0.0, y: advance };
+                y_pos += advance;
             }
         }
     }
 }
 
+/// Whether the text was shaped with a vertical direction
+fn is_vertically_shaped(shaped_text: &ShapedText) -> bool {
+    shaped_text
+        .glyphs
+        .iter()
+        .any(|glyph| glyph.advance.x == 0.0 && glyph.advance.y > 0.0)
+}
+
+/// Thickness of the text's columns
+///
+/// The shaper makes vertically shaped text as wide as its line height;
+/// horizontally shaped text stands upright in columns of its line height.
+fn column_thickness(shaped_text: &ShapedText) -> f32 {
+    if is_vertically_shaped(shaped_text) {
+        shaped_text.width
+    } else {
+        shaped_text.height
+    }
+}
+
+/// Advance of a glyph down its column
+///
+/// Horizontally shaped spacing glyphs take up a cell of `cell_height`;
+/// marks, with no horizontal advance, don't advance.
+fn column_advance(glyph: &PositionedGlyph, vertical: bool, cell_height: f32) -> f32 {
+    if vertical {
+        glyph.advance.y
+    } else if glyph.advance.x == 0.0 {
+        0.0
+    } else {
+        cell_height
+    }
+}
+
 impl Default for VerticalLayout {
     fn default() -> Self {
         Self::new()
@@ -231,8 +314,10 @@ impl Default for VerticalLayout {
 }
 
 #[cfg(test)]
+#[allow(clippy::float_cmp, clippy::cast_precision_loss)]
 mod tests {
     use super::*;
+    use crate::types::{JustificationStrategy, LineHeight};
     use font_types::{GlyphId, Point, PositionedGlyph, Vector};
 
     fn create_test_glyph(x: f32, y: f32, advance_x: f32) -> PositionedGlyph {
@@ -245,6 +330,9 @@ mod tests {
                 y: 0.0,
             },
             offset: Vector { x: 0.0, y: 0.0 },
+            scale: 1.0,
+            cluster: 0,
+            unsafe_to_break: false,
         }
     }
 
@@ -252,8 +340,11 @@ mod tests {
         let mut glyphs = Vec::new();
         let mut x = 0.0;
 
-        for _ in 0..num_glyphs {
-            glyphs.push(create_test_glyph(x, 0.0, glyph_width));
+        for index in 0..num_glyphs {
+            glyphs.push(PositionedGlyph {
+                cluster: u32::try_from(index).unwrap(),
+                ..create_test_glyph(x, 0.0, glyph_width)
+            });
             x += glyph_width;
         }
 
@@ -270,14 +361,21 @@ mod tests {
             max_width: max_column_height,
             max_height: None,
             justification: crate::types::JustificationMode::Left,
-            line_spacing: 1.0,
+            line_height: LineHeight::Multiplier(1.0),
             direction: TextDirection::TopToBottom,
+            text_indent: 0.0,
+            hanging_indent: false,
+            margin_start: 0.0,
+            margin_end: 0.0,
+            justification_strategy: JustificationStrategy::InterWord,
+            hyphen: None,
         }
     }
 
     // ========== VerticalLayout Creation Tests ==========
 
     #[test]
+    #[allow(clippy::default_constructed_unit_structs)]
     fn test_vertical_layout_creation() {
         // Given: Creating a vertical layout engine
         // When: Using new() or default()
@@ -358,6 +456,7 @@ mod tests {
         let result = result.unwrap();
         assert_eq!(result.lines.len(), 1);
         assert!(!result.overflow);
+        assert!(result.vertical);
     }
 
     #[test]
@@ -374,6 +473,11 @@ mod tests {
         assert!(result.is_ok());
         let result = result.unwrap();
         assert!(result.lines.len() > 1);
+        assert_eq!(
+            result.lines[1].glyph_indices[0],
+            result.lines[0].glyphs.len()
+        );
+        assert_eq!(result.line_of_glyph(9), Some(result.lines.len() - 1));
     }
 
     #[test]
@@ -478,13 +582,115 @@ mod tests {
 
         if !result.lines.is_empty() && !result.lines[0].glyphs.is_empty() {
             let glyph = &result.lines[0].glyphs[0];
-            // Advance should be vertical (y component non-zero)
-            assert!(glyph.advance.y > 0.0 || glyph.advance.y == 0.0); // Allow both
-                                                                      // Horizontal advance should be zero
+            // Vertical advance must not be negative
+            assert!(glyph.advance.y >= 0.0);
+            // Horizontal advance should be zero
             assert_eq!(glyph.advance.x, 0.0);
         }
     }
 
+    #[test]
+    fn test_vertically_shaped_glyphs_keep_their_advances() {
+        // Given: Glyphs shaped top-to-bottom with 12px vertical advances
+        // When: Laying them out in 30px columns
+        // Then: Columns break by the real advances and offsets are kept
+        let layout = VerticalLayout::new();
+        let text = "縦書き。";
+        let glyphs = text
+            .char_indices()
+            .enumerate()
+            .map(|(i, (cluster, _))| PositionedGlyph {
+                position: Point {
+                    x: -5.0,
+                    y: i as f32 * 12.0 + 1.0,
+                },
+                advance: Vector { x: 0.0, y: 12.0 },
+                offset: Vector { x: -5.0, y: 1.0 },
+                cluster: u32::try_from(cluster).unwrap(),
+                ..create_test_glyph(0.0, 0.0, 0.0)
+            })
+            .collect();
+        let shaped_text = ShapedText {
+            glyphs,
+            width: 20.0,
+            height: 48.0,
+            baseline: 10.0,
+        };
+
+        let result = layout
+            .layout_vertical(text, &shaped_text, &create_vertical_options(30.0))
+            .unwrap();
+
+        assert_eq!(result.lines.len(), 2);
+        assert_eq!(result.lines[1].text_range, (6, text.len()));
+        let second = &result.lines[1].glyphs;
+        assert_eq!(second[0].position, Point { x: -5.0, y: 1.0 });
+        assert_eq!(second[1].position, Point { x: -5.0, y: 13.0 });
+        assert_eq!(second[1].advance, Vector { x: 0.0, y: 12.0 });
+        assert_eq!(result.lines[1].x_offset, 20.0);
+    }
+
+    #[test]
+    fn test_horizontally_shaped_glyphs_stand_upright_in_cells() {
+        // Given: Text shaped horizontally (a font without vertical metrics):
+        // 10px glyphs and a mark on the first, ascent 16 and line height 20
+        let layout = VerticalLayout::new();
+        let text = "a\u{301}bc";
+        let glyphs = vec![
+            create_test_glyph(0.0, 0.0, 10.0),
+            PositionedGlyph {
+                position: Point { x: 8.0, y: 2.0 },
+                offset: Vector { x: -2.0, y: 2.0 },
+                ..create_test_glyph(0.0, 0.0, 0.0)
+            },
+            PositionedGlyph {
+                cluster: 3,
+                ..create_test_glyph(10.0, 0.0, 10.0)
+            },
+            PositionedGlyph {
+                cluster: 4,
+                ..create_test_glyph(20.0, 0.0, 10.0)
+            },
+        ];
+        let shaped_text = ShapedText {
+            glyphs,
+            width: 30.0,
+            height: 20.0,
+            baseline: 16.0,
+        };
+
+        // When: Laying it out in 45px columns
+        let result = layout
+            .layout_vertical(text, &shaped_text, &create_vertical_options(45.0))
+            .unwrap();
+
+        // Then: Each spacing glyph takes a cell as tall as the line, centered
+        // on the column's baseline with its own baseline at the ascent
+        assert_eq!(result.lines.len(), 2);
+        let first = &result.lines[0];
+        assert_eq!(
+            (first.width, first.height, first.baseline),
+            (40.0, 20.0, 10.0)
+        );
+        let positions: Vec<Point> = first.glyphs.iter().map(|g| g.position).collect();
+        assert_eq!(
+            positions,
+            [
+                Point { x: -5.0, y: 16.0 },
+                Point { x: 3.0, y: 14.0 },
+                Point { x: -5.0, y: 36.0 }
+            ]
+        );
+        assert_eq!(first.glyphs[1].advance, Vector { x: 0.0, y: 0.0 });
+        assert_eq!(first.glyphs[2].advance, Vector { x: 0.0, y: 20.0 });
+        assert_eq!(first.glyphs[2].offset, Vector { x: -5.0, y: 16.0 });
+        assert_eq!(result.lines[1].x_offset, 20.0);
+        assert_eq!(
+            result.lines[1].glyphs[0].position,
+            Point { x: -5.0, y: 16.0 }
+        );
+    }
+
     // ========== Column Positioning Tests ==========
 
     #[test]
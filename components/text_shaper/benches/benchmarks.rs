@@ -1,27 +1,57 @@
 //! Benchmarks for text shaping operations
 //!
 //! Tests the performance of text shaping with various text lengths,
-//! scripts, and complexity levels.
-//!
-//! Note: These benchmarks currently measure overhead with stub implementations.
-//! They will measure actual performance once text shaping is fully implemented.
+//! scripts, and complexity levels. Shaping runs against the DejaVu fonts
+//! vendored in `tests/fixtures/fonts` so results do not depend on the
+//! fonts installed on the host.
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
-use text_shaper::{TextShaper, ShapingOptions, Language, Script};
 use font_registry::FontRegistry;
+use font_types::EvictionPolicy;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use text_shaper::shaper::ShapingCacheConfig;
+use text_shaper::{
+    FontVariantCaps, FormatCharacters, Language, MatchContext, Script, ShapingOptions, TextShaper,
+    TextTransform,
+};
+
+/// Load a vendored fixture font from the workspace `tests/fixtures/fonts`
+fn fixture_font(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../tests/fixtures/fonts")
+        .join(name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("missing fixture {}: {e}", path.display()))
+}
+
+/// Create a registry holding DejaVu Sans and return it with the font's ID
+fn fixture_registry() -> (FontRegistry, font_types::types::FontId) {
+    let mut registry = FontRegistry::new();
+    let font_id = registry
+        .load_font_data(fixture_font("DejaVuSans.ttf"))
+        .expect("DejaVuSans.ttf should load");
+    (registry, font_id)
+}
 
 /// Create default shaping options for benchmarking
 fn default_shaping_options() -> ShapingOptions {
     ShapingOptions {
         script: Script::Latin,
-        language: Language { tag: "en-US".to_string() },
+        language: Language {
+            tag: "en-US".to_string(),
+        },
         direction: font_types::types::Direction::LeftToRight,
         features: HashMap::new(),
         kerning: true,
         ligatures: false,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     }
 }
 
@@ -50,14 +80,14 @@ fn bench_shape_ascii(c: &mut Criterion) {
     for (name, text) in texts.iter() {
         group.throughput(Throughput::Bytes(text.len() as u64));
         group.bench_with_input(BenchmarkId::from_parameter(name), text, |b, &text| {
-            let registry = FontRegistry::new();
+            let (registry, font_id) = fixture_registry();
             let shaper = TextShaper::new(&registry);
             let options = default_shaping_options();
-            let font_id: font_types::types::FontId = 0; // Stub font ID (type alias)
             let size = 16.0;
 
             b.iter(|| {
-                // Note: Will return error with empty registry, but measures overhead
+                // Measure a full shape rather than a shaping cache hit
+                shaper.clear_cache();
                 let _ = shaper.shape_text(
                     black_box(text),
                     black_box(font_id),
@@ -70,25 +100,26 @@ fn bench_shape_ascii(c: &mut Criterion) {
     group.finish();
 }
 
-/// Benchmark shaping with different character counts
-fn bench_shape_scalability(c: &mut Criterion) {
-    let mut group = c.benchmark_group("shape_scalability");
+/// Benchmark the simple shaping path against the full shaping engine
+fn bench_simple_shaping(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simple_shaping");
+    let text = "The quick brown fox jumps over the lazy dog";
+    group.throughput(Throughput::Bytes(text.len() as u64));
 
-    for char_count in [10, 50, 100, 500, 1000].iter() {
-        group.throughput(Throughput::Elements(*char_count as u64));
-        group.bench_with_input(BenchmarkId::from_parameter(char_count), char_count, |b, &count| {
-            let text: String = "a".repeat(count);
-            let registry = FontRegistry::new();
+    for enabled in [true, false] {
+        let name = if enabled { "simple" } else { "full" };
+        group.bench_function(name, |b| {
+            let (registry, font_id) = fixture_registry();
             let shaper = TextShaper::new(&registry);
+            shaper.shared_cache().unwrap().set_simple_shaping(enabled);
             let options = default_shaping_options();
-            let font_id: font_types::types::FontId = 0;
-            let size = 16.0;
 
             b.iter(|| {
+                shaper.clear_cache();
                 let _ = shaper.shape_text(
-                    black_box(&text),
+                    black_box(text),
                     black_box(font_id),
-                    black_box(size),
+                    black_box(16.0),
                     black_box(&options),
                 );
             });
@@ -97,6 +128,42 @@ fn bench_shape_scalability(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark shaping with different character counts
+fn bench_shape_scalability(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shape_scalability");
+
+    for char_count in [10, 50, 100, 500, 1000].iter() {
+        group.throughput(Throughput::Elements(*char_count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(char_count),
+            char_count,
+            |b, &count| {
+                let text: String = "The quick brown fox jumps over the lazy dog. "
+                    .chars()
+                    .cycle()
+                    .take(count)
+                    .collect();
+                let (registry, font_id) = fixture_registry();
+                let shaper = TextShaper::new(&registry);
+                let options = default_shaping_options();
+                let size = 16.0;
+
+                b.iter(|| {
+                    // Measure a full shape rather than a shaping cache hit
+                    shaper.clear_cache();
+                    let _ = shaper.shape_text(
+                        black_box(&text),
+                        black_box(font_id),
+                        black_box(size),
+                        black_box(&options),
+                    );
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
 /// Benchmark shaping options creation
 fn bench_create_options(c: &mut Criterion) {
     c.bench_function("create_shaping_options", |b| {
@@ -115,10 +182,9 @@ fn bench_shape_repeated(c: &mut Criterion) {
         group.throughput(Throughput::Elements(*count as u64));
         group.bench_with_input(BenchmarkId::from_parameter(count), count, |b, &count| {
             let text = "The quick brown fox";
-            let registry = FontRegistry::new();
+            let (registry, font_id) = fixture_registry();
             let shaper = TextShaper::new(&registry);
             let options = default_shaping_options();
-            let font_id: font_types::types::FontId = 0;
             let size = 16.0;
 
             b.iter(|| {
@@ -136,12 +202,89 @@ fn bench_shape_repeated(c: &mut Criterion) {
     group.finish();
 }
 
+/// Words shaped while browsing: UI labels on every page, article words
+/// skewed towards a common vocabulary, and names and numbers seen once
+fn browsing_trace() -> Vec<String> {
+    let ui = [
+        "Back", "Forward", "Reload", "Home", "Search", "Menu", "Sign in",
+    ];
+    let mut lfsr = 0xACE1u16;
+    let mut next = move || {
+        let bit = (lfsr ^ (lfsr >> 2) ^ (lfsr >> 3) ^ (lfsr >> 5)) & 1;
+        lfsr = (lfsr >> 1) | (bit << 15);
+        lfsr
+    };
+
+    let mut trace = Vec::new();
+    for page in 0..40 {
+        trace.extend(ui.iter().map(|label| label.to_string()));
+        for _ in 0..200 {
+            let uniform = f32::from(next()) / f32::from(u16::MAX);
+            trace.push(format!("word{}", (uniform * uniform * 400.0) as u32));
+        }
+        trace.extend((0..60).map(|unique| format!("name{page}-{unique}")));
+    }
+    trace
+}
+
+/// Compare shaping cache eviction policies on a browsing trace; each
+/// policy's hit rate is printed once
+fn bench_eviction_policies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shaping_eviction_policy_trace");
+    let (registry, font_id) = fixture_registry();
+    let options = default_shaping_options();
+    let trace = browsing_trace();
+    let shaper = |eviction_policy| {
+        TextShaper::with_config(
+            &registry,
+            ShapingCacheConfig {
+                max_entries: 128,
+                enable_statistics: true,
+                eviction_policy,
+                simple_shaping: true,
+                verify_keys: false,
+            },
+        )
+    };
+    let replay = |shaper: &TextShaper| {
+        for word in &trace {
+            let _ = shaper.shape_text(word, font_id, 16.0, &options);
+        }
+    };
+
+    for policy in [
+        EvictionPolicy::Lru,
+        EvictionPolicy::SegmentedLru,
+        EvictionPolicy::CostAware,
+    ] {
+        let warm = shaper(policy);
+        replay(&warm);
+        let stats = warm.cache_stats().unwrap();
+        println!("{policy:?}: hit rate {:.1}%", stats.hit_rate * 100.0);
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{policy:?}")),
+            &policy,
+            |b, &policy| {
+                b.iter(|| {
+                    let shaper = shaper(policy);
+                    replay(&shaper);
+                    black_box(shaper.cache_stats())
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_create_shaper,
     bench_create_options,
     bench_shape_ascii,
+    bench_simple_shaping,
     bench_shape_scalability,
     bench_shape_repeated,
+    bench_eviction_policies,
 );
 criterion_main!(benches);
@@ -0,0 +1,106 @@
+//! HarfBuzz shaping engine
+
+use std::str::FromStr;
+
+use font_types::types::Direction;
+use harfbuzz_rs::{Face, Feature, Font, Tag, UnicodeBuffer};
+
+use super::{feature_range_tags, feature_tags, script_tag, to_pixels, RawGlyph, ShapingBackend};
+use crate::types::{ShapingError, ShapingOptions};
+
+/// Shaping with the HarfBuzz C library
+pub(crate) struct HarfBuzzBackend;
+
+impl ShapingBackend for HarfBuzzBackend {
+    fn shape(
+        &self,
+        font_data: &[u8],
+        size: f32,
+        text: &str,
+        options: &ShapingOptions,
+    ) -> Result<Vec<RawGlyph>, ShapingError> {
+        shape(font_data, size, text, options)
+    }
+}
+
+fn shape(
+    font_data: &[u8],
+    size: f32,
+    text: &str,
+    options: &ShapingOptions,
+) -> Result<Vec<RawGlyph>, ShapingError> {
+    let hb_face = Face::from_bytes(font_data, 0);
+    let upem = hb_face.upem();
+    let mut hb_font = Font::new(hb_face);
+
+    // Shape in font units and scale afterwards, so results don't depend on
+    // HarfBuzz's fixed-point rounding
+    hb_font.set_scale(upem as i32, upem as i32);
+
+    let [a, b, c, d] = script_tag(options.script);
+    let mut buffer = UnicodeBuffer::new()
+        .add_str(text)
+        .set_direction(direction_to_hb_direction(options.direction))
+        .set_script(Tag::new(a as char, b as char, c as char, d as char));
+
+    // Unspecified languages leave the engine's default language system
+    if !options.language.is_unspecified() {
+        if let Ok(lang) = harfbuzz_rs::Language::from_str(&options.language.tag) {
+            buffer = buffer.set_language(lang);
+        }
+    }
+
+    let tag = |[a, b, c, d]: [u8; 4]| Tag::new(a as char, b as char, c as char, d as char);
+    let features: Vec<Feature> = feature_tags(options)
+        .map(|(feature, value)| Feature::new(tag(feature), value, ..))
+        .chain(
+            feature_range_tags(options)
+                .map(|(feature, value, range)| Feature::new(tag(feature), value, range)),
+        )
+        .collect();
+
+    let output = harfbuzz_rs::shape(&hb_font, buffer, &features);
+
+    Ok(output
+        .get_glyph_infos()
+        .iter()
+        .zip(output.get_glyph_positions())
+        .map(|(info, pos)| RawGlyph {
+            glyph_id: info.codepoint,
+            cluster: info.cluster,
+            unsafe_to_break: info.glyph_flags().unsafe_to_break(),
+            x_advance: to_pixels(pos.x_advance, size, upem),
+            y_advance: to_pixels(pos.y_advance, size, upem),
+            x_offset: to_pixels(pos.x_offset, size, upem),
+            y_offset: to_pixels(pos.y_offset, size, upem),
+        })
+        .collect())
+}
+
+/// Convert direction to harfbuzz direction
+fn direction_to_hb_direction(direction: Direction) -> harfbuzz_rs::Direction {
+    match direction {
+        Direction::LeftToRight => harfbuzz_rs::Direction::Ltr,
+        Direction::RightToLeft => harfbuzz_rs::Direction::Rtl,
+        Direction::TopToBottom => harfbuzz_rs::Direction::Ttb,
+        Direction::BottomToTop => harfbuzz_rs::Direction::Btt,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direction_conversion() {
+        // Test that direction conversion works
+        assert_eq!(
+            direction_to_hb_direction(Direction::LeftToRight),
+            harfbuzz_rs::Direction::Ltr
+        );
+        assert_eq!(
+            direction_to_hb_direction(Direction::RightToLeft),
+            harfbuzz_rs::Direction::Rtl
+        );
+    }
+}
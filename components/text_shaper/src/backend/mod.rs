@@ -0,0 +1,299 @@
+//! Shaping engines
+//!
+//! HarfBuzz (`harfbuzz` feature, default) is used when available; otherwise
+//! the pure-Rust rustybuzz engine (`rustybuzz` feature) shapes the text, which
+//! keeps the crate free of C dependencies for targets such as wasm32. Both
+//! engines shape in font units and scale the result the same way, so their
+//! output is interchangeable. Plain Latin runs can skip both through the
+//! [`simple`] fast path, which produces the same output for the runs it
+//! accepts.
+
+#[cfg(feature = "harfbuzz")]
+mod harfbuzz;
+// With both features enabled, rustybuzz is only built for comparison tests
+#[cfg(all(feature = "rustybuzz", any(not(feature = "harfbuzz"), test)))]
+mod rustybuzz;
+pub(crate) mod simple;
+
+#[cfg(not(any(feature = "harfbuzz", feature = "rustybuzz")))]
+compile_error!("text_shaper requires the `harfbuzz` or `rustybuzz` feature");
+
+use std::ops::Range;
+
+use crate::types::{Script, ShapingError, ShapingOptions};
+use font_types::types::Direction;
+
+/// One shaped glyph as produced by a shaping engine, in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RawGlyph {
+    /// Glyph ID in the font
+    pub glyph_id: u32,
+    /// Byte offset of the cluster this glyph belongs to
+    pub cluster: u32,
+    /// Breaking the text at the start of this glyph's cluster changes shaping
+    pub unsafe_to_break: bool,
+    /// Horizontal advance
+    pub x_advance: f32,
+    /// Vertical advance
+    pub y_advance: f32,
+    /// Horizontal offset from the pen position
+    pub x_offset: f32,
+    /// Vertical offset from the pen position
+    pub y_offset: f32,
+}
+
+/// A text shaping engine
+pub(crate) trait ShapingBackend {
+    /// Shape `text` with the font in `font_data` at `size` pixels per em
+    fn shape(
+        &self,
+        font_data: &[u8],
+        size: f32,
+        text: &str,
+        options: &ShapingOptions,
+    ) -> Result<Vec<RawGlyph>, ShapingError>;
+}
+
+// Engine selected by cargo features; HarfBuzz takes precedence
+#[cfg(feature = "harfbuzz")]
+use self::harfbuzz::HarfBuzzBackend as DefaultBackend;
+#[cfg(all(feature = "rustybuzz", not(feature = "harfbuzz")))]
+use self::rustybuzz::RustybuzzBackend as DefaultBackend;
+
+/// Shape `text` with the default engine
+pub(crate) fn shape(
+    font_data: &[u8],
+    size: f32,
+    text: &str,
+    options: &ShapingOptions,
+) -> Result<Vec<RawGlyph>, ShapingError> {
+    DefaultBackend.shape(font_data, size, text, options)
+}
+
+/// Convert a position in font units to pixels at `size` pixels per em
+fn to_pixels(units: i32, size: f32, units_per_em: u32) -> f32 {
+    units as f32 * size / units_per_em as f32
+}
+
+/// ISO 15924 tag of a script
+pub(crate) fn script_tag(script: Script) -> [u8; 4] {
+    match script {
+        Script::Latin => *b"latn",
+        Script::Arabic => *b"arab",
+        Script::Hebrew => *b"hebr",
+        Script::Cyrillic => *b"cyrl",
+        Script::Greek => *b"grek",
+        Script::Han => *b"hani",
+        Script::Hangul => *b"hang",
+        Script::Hiragana => *b"hira",
+        Script::Katakana => *b"kana",
+        Script::Common => *b"zyyy",
+    }
+}
+
+/// Vertical alternates and rotated forms, on by default in vertical text
+const VERTICAL_FEATURES: [[u8; 4]; 2] = [*b"vert", *b"vrt2"];
+
+/// Options shaping vertical text as upright horizontal text
+///
+/// For fonts without vertical metrics, whose glyphs are then placed in
+/// cells of synthesized metrics. The vertical features stay on unless the
+/// options turn them off, and kerning, which engines only apply to
+/// horizontal text, is off.
+pub(crate) fn upright_options(options: &ShapingOptions) -> ShapingOptions {
+    let mut upright = options.clone();
+    upright.direction = if options.direction == Direction::BottomToTop {
+        Direction::RightToLeft
+    } else {
+        Direction::LeftToRight
+    };
+    for tag in VERTICAL_FEATURES {
+        upright
+            .features
+            .entry(String::from_utf8_lossy(&tag).into_owned())
+            .or_insert(1);
+    }
+    upright.features.entry("kern".to_string()).or_insert(0);
+    upright
+}
+
+/// OpenType features from the options whose tags are four bytes long
+///
+/// Vertical text starts with [`VERTICAL_FEATURES`] enabled; the options
+/// follow, so they can turn them off.
+fn feature_tags(options: &ShapingOptions) -> impl Iterator<Item = ([u8; 4], u32)> + '_ {
+    let vertical = options.direction.is_vertical();
+    VERTICAL_FEATURES
+        .into_iter()
+        .filter(move |_| vertical)
+        .map(|tag| (tag, 1))
+        .chain(options.features.iter().filter_map(|(tag, value)| {
+            let tag: [u8; 4] = tag.as_bytes().try_into().ok()?;
+            Some((tag, *value))
+        }))
+}
+
+/// Feature ranges from the options whose tags are four bytes long
+///
+/// Engines apply features in order, so these follow the global features to
+/// override them inside their ranges.
+fn feature_range_tags(
+    options: &ShapingOptions,
+) -> impl Iterator<Item = ([u8; 4], u32, Range<usize>)> + '_ {
+    options.feature_ranges.iter().filter_map(|feature| {
+        let tag: [u8; 4] = feature.tag.as_bytes().try_into().ok()?;
+        Some((tag, feature.value, feature.range.clone()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use font_types::types::Direction;
+
+    #[test]
+    fn test_script_tag() {
+        assert_eq!(&script_tag(Script::Latin), b"latn");
+        assert_eq!(&script_tag(Script::Arabic), b"arab");
+        assert_eq!(&script_tag(Script::Common), b"zyyy");
+    }
+
+    #[test]
+    fn test_vertical_text_enables_vertical_features() {
+        let mut options = ShapingOptions::builder()
+            .direction(Direction::TopToBottom)
+            .feature("vrt2", 0)
+            .build();
+        let features: Vec<_> = feature_tags(&options).collect();
+        assert_eq!(
+            features,
+            [(*b"vert", 1), (*b"vrt2", 1), (*b"vrt2", 0)],
+            "explicit features come last so they win"
+        );
+
+        options.direction = Direction::LeftToRight;
+        assert_eq!(feature_tags(&options).collect::<Vec<_>>(), [(*b"vrt2", 0)]);
+    }
+}
+
+/// Both engines must produce the same glyphs, clusters and positions
+///
+/// Run with `cargo test -p text_shaper --features rustybuzz`.
+#[cfg(all(test, feature = "harfbuzz", feature = "rustybuzz"))]
+mod comparison_tests {
+    use super::harfbuzz::HarfBuzzBackend;
+    use super::rustybuzz::RustybuzzBackend;
+    use super::*;
+    use crate::types::Language;
+    use font_types::types::Direction;
+
+    /// Largest allowed position difference in pixels
+    const TOLERANCE: f32 = 1e-3;
+
+    fn font_data() -> Vec<u8> {
+        std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+        ))
+        .unwrap()
+    }
+
+    fn options(script: Script, language: &str, direction: Direction) -> ShapingOptions {
+        ShapingOptions {
+            script,
+            language: Language {
+                tag: language.to_string(),
+            },
+            direction,
+            ..ShapingOptions::default()
+        }
+    }
+
+    fn assert_same_output(font_data: &[u8], size: f32, text: &str, options: &ShapingOptions) {
+        let hb = HarfBuzzBackend
+            .shape(font_data, size, text, options)
+            .unwrap();
+        let rb = RustybuzzBackend
+            .shape(font_data, size, text, options)
+            .unwrap();
+
+        assert_eq!(hb.len(), rb.len(), "glyph count differs for {:?}", text);
+        for (i, (h, r)) in hb.iter().zip(&rb).enumerate() {
+            assert_eq!(h.glyph_id, r.glyph_id, "glyph {} of {:?}", i, text);
+            assert_eq!(h.cluster, r.cluster, "cluster {} of {:?}", i, text);
+            assert_eq!(
+                h.unsafe_to_break, r.unsafe_to_break,
+                "unsafe_to_break {} of {:?}",
+                i, text
+            );
+            for (name, a, b) in [
+                ("x_advance", h.x_advance, r.x_advance),
+                ("y_advance", h.y_advance, r.y_advance),
+                ("x_offset", h.x_offset, r.x_offset),
+                ("y_offset", h.y_offset, r.y_offset),
+            ] {
+                assert!(
+                    (a - b).abs() <= TOLERANCE,
+                    "{} of glyph {} in {:?}: harfbuzz {} vs rustybuzz {}",
+                    name,
+                    i,
+                    text,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_latin_matches() {
+        let data = font_data();
+        let opts = options(Script::Latin, "en", Direction::LeftToRight);
+        for size in [12.0, 16.0, 23.5] {
+            assert_same_output(&data, size, "Hello, World!", &opts);
+            assert_same_output(&data, size, "AVATAR WAVE Ty", &opts);
+        }
+    }
+
+    #[test]
+    fn test_combining_marks_match() {
+        let data = font_data();
+        let opts = options(Script::Latin, "en", Direction::LeftToRight);
+        assert_same_output(&data, 16.0, "e\u{301}a\u{308}o\u{323}\u{302}", &opts);
+    }
+
+    #[test]
+    fn test_rtl_matches() {
+        let data = font_data();
+        let arabic = options(Script::Arabic, "ar", Direction::RightToLeft);
+        assert_same_output(&data, 16.0, "مرحبا بالعالم", &arabic);
+        let hebrew = options(Script::Hebrew, "he", Direction::RightToLeft);
+        assert_same_output(&data, 16.0, "שלום עולם", &hebrew);
+    }
+
+    #[test]
+    fn test_vertical_matches() {
+        let data = font_data();
+        let opts = options(Script::Latin, "en", Direction::TopToBottom);
+        assert_same_output(&data, 16.0, "Hello", &opts);
+        let opts = options(Script::Han, "ja", Direction::TopToBottom);
+        assert_same_output(&data, 16.0, "縦書き、テスト。", &opts);
+    }
+
+    #[test]
+    fn test_features_match() {
+        let data = font_data();
+        let mut opts = options(Script::Latin, "en", Direction::LeftToRight);
+        opts.features.insert("kern".to_string(), 0);
+        opts.features.insert("liga".to_string(), 0);
+        assert_same_output(&data, 16.0, "AVATAR office", &opts);
+
+        opts.features.clear();
+        opts.feature_ranges.push(crate::types::FeatureRange {
+            tag: "kern".to_string(),
+            value: 0,
+            range: 2..6,
+        });
+        assert_same_output(&data, 16.0, "AVATAR office", &opts);
+    }
+}
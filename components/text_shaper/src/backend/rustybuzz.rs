@@ -0,0 +1,84 @@
+//! rustybuzz (pure Rust) shaping engine
+
+use std::str::FromStr;
+
+use font_types::types::Direction;
+use rustybuzz::{Face, Feature, Tag, UnicodeBuffer};
+
+use super::{feature_range_tags, feature_tags, script_tag, to_pixels, RawGlyph, ShapingBackend};
+use crate::types::{ShapingError, ShapingOptions};
+
+/// Shaping with the pure-Rust rustybuzz engine
+pub(crate) struct RustybuzzBackend;
+
+impl ShapingBackend for RustybuzzBackend {
+    fn shape(
+        &self,
+        font_data: &[u8],
+        size: f32,
+        text: &str,
+        options: &ShapingOptions,
+    ) -> Result<Vec<RawGlyph>, ShapingError> {
+        shape(font_data, size, text, options)
+    }
+}
+
+fn shape(
+    font_data: &[u8],
+    size: f32,
+    text: &str,
+    options: &ShapingOptions,
+) -> Result<Vec<RawGlyph>, ShapingError> {
+    let face = Face::from_slice(font_data, 0).ok_or(ShapingError::FontNotFound)?;
+
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_direction(direction_to_rb_direction(options.direction));
+    if let Some(script) =
+        rustybuzz::Script::from_iso15924_tag(Tag::from_bytes(&script_tag(options.script)))
+    {
+        buffer.set_script(script);
+    }
+    // Unspecified languages leave the engine's default language system
+    if !options.language.is_unspecified() {
+        if let Ok(lang) = rustybuzz::Language::from_str(&options.language.tag) {
+            buffer.set_language(lang);
+        }
+    }
+
+    let features: Vec<Feature> = feature_tags(options)
+        .map(|(tag, value)| Feature::new(Tag::from_bytes(&tag), value, ..))
+        .chain(
+            feature_range_tags(options)
+                .map(|(tag, value, range)| Feature::new(Tag::from_bytes(&tag), value, range)),
+        )
+        .collect();
+
+    let output = rustybuzz::shape(&face, &features, buffer);
+
+    let upem = face.units_per_em() as u32;
+    Ok(output
+        .glyph_infos()
+        .iter()
+        .zip(output.glyph_positions())
+        .map(|(info, pos)| RawGlyph {
+            glyph_id: info.glyph_id,
+            cluster: info.cluster,
+            unsafe_to_break: info.unsafe_to_break(),
+            x_advance: to_pixels(pos.x_advance, size, upem),
+            y_advance: to_pixels(pos.y_advance, size, upem),
+            x_offset: to_pixels(pos.x_offset, size, upem),
+            y_offset: to_pixels(pos.y_offset, size, upem),
+        })
+        .collect())
+}
+
+/// Convert direction to rustybuzz direction
+fn direction_to_rb_direction(direction: Direction) -> rustybuzz::Direction {
+    match direction {
+        Direction::LeftToRight => rustybuzz::Direction::LeftToRight,
+        Direction::RightToLeft => rustybuzz::Direction::RightToLeft,
+        Direction::TopToBottom => rustybuzz::Direction::TopToBottom,
+        Direction::BottomToTop => rustybuzz::Direction::BottomToTop,
+    }
+}
@@ -0,0 +1,1021 @@
+//! Simple shaping fast path for plain Latin text
+//!
+//! Printable ASCII in a left-to-right Latin run maps one character to one
+//! glyph, so shaping it only takes cmap lookups, hmtx advances and GPOS pair
+//! kerning, as long as no other layout lookup can act on its glyphs.
+//! [`SimpleFace`] reads what that needs from a font once, including which
+//! characters each feature's lookups may touch, and [`SimpleFace::shape`]
+//! declines runs that the full engines could shape differently.
+
+use font_parser::{LanguageSystem, LayoutTable, OpenTypeFont, Tag};
+use font_types::types::Direction;
+use rustc_hash::FxHashMap;
+
+use super::{to_pixels, RawGlyph};
+use crate::types::{Script, ShapingOptions};
+
+/// First character the fast path handles (space)
+const FIRST_CHAR: u8 = 0x20;
+
+/// Number of printable ASCII characters, from space to tilde
+const CHAR_COUNT: usize = 95;
+
+/// Set of printable ASCII characters, one bit per character
+type CharMask = u128;
+
+/// Mask holding every character, for lookups that cannot be analyzed
+const ALL_CHARS: CharMask = (1 << CHAR_COUNT) - 1;
+
+/// Features the engines enable by default in horizontal left-to-right text
+const DEFAULT_FEATURES: [[u8; 4]; 18] = [
+    *b"rvrn", *b"ltra", *b"ltrm", *b"ccmp", *b"locl", *b"rlig", *b"calt", *b"clig", *b"liga",
+    *b"rclt", *b"curs", *b"dist", *b"kern", *b"mark", *b"mkmk", *b"abvm", *b"blwm", *b"rand",
+];
+
+/// Kerning feature, the only one the fast path applies itself
+const KERN: [u8; 4] = *b"kern";
+
+/// Tables whose presence makes the engines shape in ways not modeled here
+/// (AAT substitution, kerning and tracking)
+const UNSUPPORTED_TABLES: [&str; 4] = ["morx", "mort", "kerx", "trak"];
+
+/// GPOS lookup type of pair adjustments
+const PAIR_ADJUSTMENT: u16 = 2;
+
+/// GSUB lookup type of ligature substitutions
+const LIGATURE_SUBSTITUTION: u16 = 4;
+
+/// Lookup flags that skip base glyphs or ligatures when matching pairs
+const IGNORE_BASES_OR_LIGATURES: u16 = 0x0002 | 0x0004;
+
+/// GDEF glyph class of combining marks
+const MARK_CLASS: u16 = 3;
+
+/// A font prepared for the simple shaping path
+#[derive(Debug)]
+pub(crate) struct SimpleFace {
+    /// Units per em
+    units_per_em: u32,
+    /// Glyph of each character, 0 if the font lacks it
+    glyphs: [u16; CHAR_COUNT],
+    /// Advance of each character's glyph in font units
+    advances: [u16; CHAR_COUNT],
+    /// Characters whose glyph GDEF classifies as a mark
+    marks: CharMask,
+    /// Layout of Latin runs
+    latin: ScriptPlan,
+    /// Layout of Common runs
+    common: ScriptPlan,
+}
+
+/// What the layout tables do to one script's runs
+#[derive(Debug, Default)]
+struct ScriptPlan {
+    /// What triggers each feature's lookups, for the lookups the fast path
+    /// does not apply itself
+    features: FxHashMap<[u8; 4], Triggers>,
+    /// What triggers the language systems' required features
+    required: Triggers,
+    /// Kerning lookups, in the order the engines apply them
+    kerning: Vec<PairLookup>,
+}
+
+/// Character sequences that may make lookups act on plain text
+#[derive(Debug, Clone, Default)]
+struct Triggers {
+    /// Characters that trigger a lookup wherever they appear
+    chars: CharMask,
+    /// Sequences of character sets that trigger a lookup where consecutive
+    /// characters of a run fall in them, e.g. `f` followed by `i`
+    sequences: Vec<Vec<CharMask>>,
+}
+
+impl Triggers {
+    /// Triggers of lookups that cannot be analyzed
+    fn all() -> Self {
+        Triggers {
+            chars: ALL_CHARS,
+            sequences: Vec::new(),
+        }
+    }
+
+    /// Add a sequence; sequences with a position no character fills never
+    /// trigger
+    fn add(&mut self, sequence: Vec<CharMask>) {
+        match sequence.as_slice() {
+            [] => {}
+            _ if sequence.contains(&0) => {}
+            [chars] => self.chars |= chars,
+            _ => self.sequences.push(sequence),
+        }
+    }
+
+    fn extend(&mut self, other: Triggers) {
+        self.chars |= other.chars;
+        self.sequences.extend(other.sequences);
+    }
+
+    /// Whether the run `chars`, made of the characters in `used`, contains
+    /// a trigger
+    fn matches(&self, used: CharMask, chars: &[u8]) -> bool {
+        used & self.chars != 0
+            || self.sequences.iter().any(|sequence| {
+                sequence.iter().all(|&set| used & set != 0)
+                    && chars.windows(sequence.len()).any(|window| {
+                        window
+                            .iter()
+                            .zip(sequence)
+                            .all(|(&index, &set)| set & 1 << index != 0)
+                    })
+            })
+    }
+}
+
+/// Adjustments of one pair adjustment lookup, keyed by character pair
+#[derive(Debug, Default)]
+struct PairLookup {
+    pairs: FxHashMap<(u8, u8), PairAdjustment>,
+}
+
+/// Adjustment of a matched glyph pair
+#[derive(Debug, Clone, Copy)]
+struct PairAdjustment {
+    first: ValueRecord,
+    second: ValueRecord,
+    /// The subtable has values for the second glyph, so the next pair
+    /// starts after it
+    has_second: bool,
+}
+
+/// The horizontal part of a GPOS value record, in font units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ValueRecord {
+    x_placement: i16,
+    y_placement: i16,
+    x_advance: i16,
+}
+
+impl ValueRecord {
+    fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// One shaped glyph in font units
+#[derive(Debug, Clone, Copy)]
+struct UnitGlyph {
+    glyph_id: u16,
+    x_advance: i32,
+    x_offset: i32,
+    y_offset: i32,
+    unsafe_to_break: bool,
+}
+
+impl SimpleFace {
+    /// Prepare `font_data` for the simple path
+    ///
+    /// Returns `None` for fonts the fast path does not support, e.g. ones
+    /// with AAT layout or conditional feature variations.
+    pub(crate) fn new(font_data: &[u8]) -> Option<Self> {
+        let font = OpenTypeFont::parse(font_data.to_vec()).ok()?;
+        let table = |tag: &str| font.get_table(Tag::new(tag).expect("valid tag"));
+        if UNSUPPORTED_TABLES.iter().any(|tag| table(tag).is_some()) {
+            return None;
+        }
+
+        let units_per_em = u16_at(table("head")?, 18)? as u32;
+        let cmap = font.get_cmap()?;
+        let mut glyphs = [0; CHAR_COUNT];
+        let mut advances = [0; CHAR_COUNT];
+        for (index, (glyph, advance)) in glyphs.iter_mut().zip(&mut advances).enumerate() {
+            let c = char::from(FIRST_CHAR + index as u8);
+            if let Some(glyph_id) = cmap.get_glyph(c) {
+                *glyph = glyph_id;
+                *advance = font.get_advance(glyph_id)?;
+            }
+        }
+
+        let gsub = LayoutData::new(table("GSUB"), false)?;
+        let gpos = LayoutData::new(table("GPOS"), true)?;
+        let legacy_kerning = table("kern").is_some();
+
+        let mut face = SimpleFace {
+            units_per_em,
+            glyphs,
+            advances,
+            marks: 0,
+            latin: ScriptPlan::default(),
+            common: ScriptPlan::default(),
+        };
+        if let Some(class_def) = table("GDEF").and_then(|gdef| offset16(gdef, 0, 4)) {
+            let gdef = table("GDEF")?;
+            face.marks = face.chars_where(|glyph| class_of(gdef, class_def, glyph) == MARK_CLASS);
+        }
+        // Engines fall back from the script's own tag to the default script
+        // (and Latin, for fonts that only list it)
+        face.latin = face.plan(&gsub, &gpos, &["latn", "DFLT", "dflt"], legacy_kerning);
+        face.common = face.plan(&gsub, &gpos, &["DFLT", "dflt", "latn"], legacy_kerning);
+        Some(face)
+    }
+
+    /// Shape `text` at `size` pixels per em, if the fast path can
+    ///
+    /// Returns `None` unless the run is left-to-right Latin or Common text of
+    /// printable ASCII the font maps, without marks, and no lookup of an
+    /// enabled feature other than kerning could act on its glyphs.
+    pub(crate) fn shape(
+        &self,
+        size: f32,
+        text: &str,
+        options: &ShapingOptions,
+    ) -> Option<Vec<RawGlyph>> {
+        if options.direction != Direction::LeftToRight {
+            return None;
+        }
+        let plan = match options.script {
+            Script::Latin => &self.latin,
+            Script::Common => &self.common,
+            _ => return None,
+        };
+
+        let mut chars = Vec::with_capacity(text.len());
+        let mut used: CharMask = 0;
+        for byte in text.bytes() {
+            let index = byte
+                .checked_sub(FIRST_CHAR)
+                .filter(|&i| (i as usize) < CHAR_COUNT)?;
+            if self.glyphs[index as usize] == 0 {
+                return None;
+            }
+            used |= 1 << index;
+            chars.push(index);
+        }
+        if used & self.marks != 0 {
+            return None;
+        }
+
+        let (features, kerning) = enabled_features(options)?;
+        let triggered = plan.required.matches(used, &chars)
+            || features
+                .iter()
+                .filter_map(|tag| plan.features.get(tag))
+                .any(|triggers| triggers.matches(used, &chars));
+        if triggered {
+            return None;
+        }
+
+        let mut glyphs: Vec<UnitGlyph> = chars
+            .iter()
+            .map(|&index| UnitGlyph {
+                glyph_id: self.glyphs[index as usize],
+                x_advance: self.advances[index as usize] as i32,
+                x_offset: 0,
+                y_offset: 0,
+                unsafe_to_break: false,
+            })
+            .collect();
+        if kerning {
+            for lookup in &plan.kerning {
+                lookup.apply(&chars, &mut glyphs);
+            }
+        }
+
+        Some(
+            glyphs
+                .into_iter()
+                .enumerate()
+                .map(|(cluster, glyph)| RawGlyph {
+                    glyph_id: glyph.glyph_id as u32,
+                    cluster: cluster as u32,
+                    unsafe_to_break: glyph.unsafe_to_break,
+                    x_advance: to_pixels(glyph.x_advance, size, self.units_per_em),
+                    y_advance: 0.0,
+                    x_offset: to_pixels(glyph.x_offset, size, self.units_per_em),
+                    y_offset: to_pixels(glyph.y_offset, size, self.units_per_em),
+                })
+                .collect(),
+        )
+    }
+
+    /// Characters whose glyph satisfies `predicate`
+    fn chars_where(&self, mut predicate: impl FnMut(u16) -> bool) -> CharMask {
+        self.glyphs
+            .iter()
+            .enumerate()
+            .filter(|&(_, &glyph)| glyph != 0 && predicate(glyph))
+            .fold(0, |mask, (index, _)| mask | 1 << index)
+    }
+
+    /// Analyze the lookups GSUB and GPOS apply to a script
+    fn plan(
+        &self,
+        gsub: &LayoutData<'_>,
+        gpos: &LayoutData<'_>,
+        script_tags: &[&str],
+        legacy_kerning: bool,
+    ) -> ScriptPlan {
+        let mut plan = ScriptPlan::default();
+        let mut gpos_kerning = false;
+        for layout in [gsub, gpos] {
+            let Some((_, table)) = &layout.table else {
+                continue;
+            };
+            let Some(script) = script_tags.iter().find_map(|tag| {
+                let tag = Tag::new(tag).expect("valid tag");
+                table.scripts.iter().find(|script| script.tag == tag)
+            }) else {
+                continue;
+            };
+            let language_systems: Vec<&LanguageSystem> = script
+                .default_language
+                .iter()
+                .chain(script.languages.iter().map(|(_, system)| system))
+                .collect();
+
+            // Kerning is applied here only if every language system kerns
+            // with the same lookups, whichever one the engine picks
+            let kern_lookups = |system: &LanguageSystem| -> Vec<u16> {
+                let mut lookups: Vec<u16> = system
+                    .feature_indices
+                    .iter()
+                    .filter_map(|&index| table.features.get(index as usize))
+                    .filter(|feature| feature.tag.to_bytes().to_be_bytes() == KERN)
+                    .flat_map(|feature| feature.lookup_indices.iter().copied())
+                    .collect();
+                lookups.sort_unstable();
+                lookups.dedup();
+                lookups
+            };
+            let mut applied_kerning = Vec::new();
+            if layout.is_gpos {
+                let lookups: Vec<Vec<u16>> =
+                    language_systems.iter().map(|s| kern_lookups(s)).collect();
+                gpos_kerning = lookups.iter().any(|lookups| !lookups.is_empty());
+                if lookups.windows(2).all(|pair| pair[0] == pair[1]) {
+                    if let Some(kerning) = lookups.first().and_then(|lookups| {
+                        lookups
+                            .iter()
+                            .map(|&index| self.pair_lookup(layout, index))
+                            .collect::<Option<Vec<_>>>()
+                    }) {
+                        plan.kerning = kerning;
+                        applied_kerning = lookups.into_iter().next().unwrap_or_default();
+                    }
+                }
+            }
+
+            // Language systems share most lookups; analyze each once
+            let mut required = Vec::new();
+            let mut feature_lookups: FxHashMap<[u8; 4], Vec<u16>> = FxHashMap::default();
+            for system in &language_systems {
+                for &index in system.required_feature.iter() {
+                    if let Some(feature) = table.features.get(index as usize) {
+                        required.extend_from_slice(&feature.lookup_indices);
+                    }
+                }
+                for &index in &system.feature_indices {
+                    let Some(feature) = table.features.get(index as usize) else {
+                        continue;
+                    };
+                    let tag = feature.tag.to_bytes().to_be_bytes();
+                    feature_lookups.entry(tag).or_default().extend(
+                        feature.lookup_indices.iter().filter(|lookup| {
+                            !(layout.is_gpos && tag == KERN && applied_kerning.contains(lookup))
+                        }),
+                    );
+                }
+            }
+            let mut triggers: FxHashMap<u16, Triggers> = FxHashMap::default();
+            let mut lookup_triggers = |lookups: &mut Vec<u16>| {
+                lookups.sort_unstable();
+                lookups.dedup();
+                let mut merged = Triggers::default();
+                for &lookup in lookups.iter() {
+                    merged.extend(
+                        triggers
+                            .entry(lookup)
+                            .or_insert_with(|| self.lookup_triggers(layout, lookup))
+                            .clone(),
+                    );
+                }
+                merged
+            };
+            plan.required.extend(lookup_triggers(&mut required));
+            for (tag, mut lookups) in feature_lookups {
+                let merged = lookup_triggers(&mut lookups);
+                plan.features.entry(tag).or_default().extend(merged);
+            }
+        }
+
+        // Without GPOS kerning the engines kern with the legacy `kern` table
+        if legacy_kerning && !gpos_kerning {
+            plan.features.insert(KERN, Triggers::all());
+        }
+        plan
+    }
+
+    /// What may make a lookup act on plain text
+    ///
+    /// Lookups that cannot be read count as acting on everything.
+    fn lookup_triggers(&self, layout: &LayoutData<'_>, index: u16) -> Triggers {
+        let flags = layout
+            .lookup_offset(index)
+            .and_then(|lookup| u16_at(layout.data(), lookup + 2));
+        let (Some(flags), Some(subtables)) = (flags, layout.subtables(index)) else {
+            return Triggers::all();
+        };
+        let mut triggers = Triggers::default();
+        for (lookup_type, subtable) in subtables {
+            match self.subtable_triggers(layout, lookup_type, subtable) {
+                Some(subtable_triggers) => triggers.extend(subtable_triggers),
+                None => return Triggers::all(),
+            }
+        }
+        // Skipping base glyphs or ligatures lets sequences match characters
+        // that are not adjacent
+        if flags & IGNORE_BASES_OR_LIGATURES != 0 {
+            let chars = triggers
+                .sequences
+                .iter()
+                .flatten()
+                .fold(triggers.chars, |chars, set| chars | set);
+            triggers = Triggers {
+                chars,
+                sequences: Vec::new(),
+            };
+        }
+        triggers
+    }
+
+    /// What may make a lookup subtable act on plain text
+    ///
+    /// Subtables start matching at a glyph in their (first input) coverage.
+    /// Ligatures also need their other components, and context rules the
+    /// rest of their sequence, so e.g. a rule for `i` before a combining
+    /// mark never matches plain text.
+    fn subtable_triggers(
+        &self,
+        layout: &LayoutData<'_>,
+        lookup_type: u16,
+        subtable: usize,
+    ) -> Option<Triggers> {
+        let data = layout.data();
+        let covered = |coverage: usize| {
+            self.chars_where(|glyph| coverage_index(data, coverage, glyph).is_some())
+        };
+        let glyph_chars = |glyph: u16| self.chars_where(|candidate| candidate == glyph);
+        let coverage = offset16(data, subtable, 2);
+        let mut triggers = Triggers::default();
+
+        if !layout.is_gpos && lookup_type == LIGATURE_SUBSTITUTION {
+            let coverage = coverage?;
+            let set_count = u16_at(data, subtable + 4)?;
+            for (index, &glyph) in self.glyphs.iter().enumerate() {
+                let Some(set) = coverage_index(data, coverage, glyph).filter(|&i| i < set_count)
+                else {
+                    continue;
+                };
+                let Some(set) = offset16(data, subtable, 6 + set as usize * 2) else {
+                    continue;
+                };
+                for ligature in 0..u16_at(data, set)? as usize {
+                    let ligature = offset16(data, set, 2 + ligature * 2)?;
+                    let components = u16_at(data, ligature + 2)?.saturating_sub(1) as usize;
+                    let mut sequence = vec![1 << index];
+                    for component in 0..components {
+                        sequence.push(glyph_chars(u16_at(data, ligature + 4 + component * 2)?));
+                    }
+                    triggers.add(sequence);
+                }
+            }
+            return Some(triggers);
+        }
+
+        let (context, chained) = if layout.is_gpos { (7, 8) } else { (5, 6) };
+        if lookup_type != context && lookup_type != chained {
+            triggers.add(vec![covered(coverage?)]);
+            return Some(triggers);
+        }
+        let chained = lookup_type == chained;
+
+        let format = u16_at(data, subtable)?;
+        if format == 3 {
+            // One coverage per glyph of the backtrack, input and lookahead
+            let read_coverages = |position: usize, count: usize| -> Option<Vec<CharMask>> {
+                (0..count)
+                    .map(|i| Some(covered(offset16(data, subtable, position + i * 2)?)))
+                    .collect()
+            };
+            let sequence = if chained {
+                let backtrack_count = u16_at(data, subtable + 2)? as usize;
+                let mut backtrack = read_coverages(4, backtrack_count)?;
+                let input_field = 4 + backtrack_count * 2;
+                let input_count = u16_at(data, subtable + input_field)? as usize;
+                let input = read_coverages(input_field + 2, input_count)?;
+                let lookahead_field = input_field + 2 + input_count * 2;
+                let lookahead_count = u16_at(data, subtable + lookahead_field)? as usize;
+                let lookahead = read_coverages(lookahead_field + 2, lookahead_count)?;
+                // Backtrack coverages are stored nearest glyph first
+                backtrack.reverse();
+                backtrack
+                    .into_iter()
+                    .chain(input)
+                    .chain(lookahead)
+                    .collect()
+            } else {
+                read_coverages(6, u16_at(data, subtable + 2)? as usize)?
+            };
+            triggers.add(sequence);
+            return Some(triggers);
+        }
+
+        // Formats 1 and 2 hold rule sets per first glyph or per input class
+        let (class_defs, rule_sets) = match (format, chained) {
+            (1, _) => (None, 4),
+            (2, false) => (Some([4, 4, 4]), 6),
+            (2, true) => (Some([4, 6, 8]), 10),
+            _ => return None,
+        };
+        let class_defs = class_defs
+            .map(|fields| fields.map(|field| offset16(data, subtable, field).unwrap_or(0)));
+        let chars_of = |sequence: usize, value: u16| match class_defs {
+            Some(class_defs) => {
+                self.chars_where(|glyph| class_of(data, class_defs[sequence], glyph) == value)
+            }
+            None => glyph_chars(value),
+        };
+
+        let covered = covered(coverage?);
+        let set_count = u16_at(data, subtable + rule_sets)?;
+        for set in 0..set_count {
+            let first = match class_defs {
+                Some([_, input, _]) => {
+                    covered & self.chars_where(|glyph| class_of(data, input, glyph) == set)
+                }
+                None => self.chars_where(|glyph| {
+                    coverage_index(data, coverage.unwrap_or(0), glyph) == Some(set)
+                }),
+            };
+            if first == 0 {
+                continue;
+            }
+            let Some(rule_set) = offset16(data, subtable, rule_sets + 2 + set as usize * 2) else {
+                continue;
+            };
+            for rule in 0..u16_at(data, rule_set)? as usize {
+                let rule = offset16(data, rule_set, 2 + rule * 2)?;
+                let [backtrack, input, lookahead] = rule_sequences(data, rule, chained)?;
+                let sequence = backtrack
+                    .iter()
+                    .rev()
+                    .map(|&value| chars_of(0, value))
+                    .chain([first])
+                    .chain(input.iter().map(|&value| chars_of(1, value)))
+                    .chain(lookahead.iter().map(|&value| chars_of(2, value)))
+                    .collect();
+                triggers.add(sequence);
+            }
+        }
+        Some(triggers)
+    }
+
+    /// Read a kerning lookup, if it only holds pair adjustments the fast
+    /// path can apply
+    fn pair_lookup(&self, layout: &LayoutData<'_>, index: u16) -> Option<PairLookup> {
+        let data = layout.data();
+        let lookup = layout.lookup_offset(index)?;
+        if u16_at(data, lookup + 2)? & IGNORE_BASES_OR_LIGATURES != 0 {
+            return None;
+        }
+
+        let mut lookup_pairs = PairLookup::default();
+        for (lookup_type, subtable) in layout.subtables(index)? {
+            if lookup_type != PAIR_ADJUSTMENT {
+                return None;
+            }
+            let coverage = offset16(data, subtable, 2)?;
+            let formats = [u16_at(data, subtable + 4)?, u16_at(data, subtable + 6)?];
+            let sizes = formats.map(|format| format.count_ones() as usize * 2);
+            let has_second = formats[1] != 0;
+            let adjustment = |record: usize| -> Option<PairAdjustment> {
+                Some(PairAdjustment {
+                    first: value_record(data, record, formats[0])?,
+                    second: value_record(data, record + sizes[0], formats[1])?,
+                    has_second,
+                })
+            };
+
+            for (first, &first_glyph) in self.glyphs.iter().enumerate() {
+                let Some(coverage_index) = coverage_index(data, coverage, first_glyph) else {
+                    continue;
+                };
+                for (second, &second_glyph) in self.glyphs.iter().enumerate() {
+                    let key = (first as u8, second as u8);
+                    if second_glyph == 0 || lookup_pairs.pairs.contains_key(&key) {
+                        continue;
+                    }
+                    let record = match u16_at(data, subtable)? {
+                        1 => {
+                            let pair_set =
+                                offset16(data, subtable, 10 + coverage_index as usize * 2)?;
+                            let count = u16_at(data, pair_set)? as usize;
+                            let record_size = 2 + sizes[0] + sizes[1];
+                            (0..count)
+                                .map(|i| pair_set + 2 + i * record_size)
+                                .find(|&record| u16_at(data, record) == Some(second_glyph))
+                                .map(|record| record + 2)
+                        }
+                        2 => {
+                            let class1 = class_of(data, offset16(data, subtable, 8)?, first_glyph);
+                            let class2 =
+                                class_of(data, offset16(data, subtable, 10)?, second_glyph);
+                            let class1_count = u16_at(data, subtable + 12)?;
+                            let class2_count = u16_at(data, subtable + 14)?;
+                            (class1 < class1_count && class2 < class2_count).then(|| {
+                                let index =
+                                    class1 as usize * class2_count as usize + class2 as usize;
+                                subtable + 16 + index * (sizes[0] + sizes[1])
+                            })
+                        }
+                        _ => return None,
+                    };
+                    if let Some(record) = record {
+                        lookup_pairs.pairs.insert(key, adjustment(record)?);
+                    }
+                }
+            }
+        }
+        Some(lookup_pairs)
+    }
+}
+
+impl PairLookup {
+    /// Adjust the glyphs of `chars` as the engines apply a pair adjustment
+    /// lookup
+    ///
+    /// A matched pair is unsafe to break inside when it moves a glyph; when
+    /// the subtable also positions the second glyph, matching resumes after
+    /// it and the glyph following the pair becomes unsafe to break too.
+    fn apply(&self, chars: &[u8], glyphs: &mut [UnitGlyph]) {
+        let mut index = 0;
+        while index + 1 < chars.len() {
+            let Some(adjustment) = self.pairs.get(&(chars[index], chars[index + 1])) else {
+                index += 1;
+                continue;
+            };
+            for (glyph, value) in glyphs[index..]
+                .iter_mut()
+                .zip([adjustment.first, adjustment.second])
+            {
+                glyph.x_advance += value.x_advance as i32;
+                glyph.x_offset += value.x_placement as i32;
+                glyph.y_offset += value.y_placement as i32;
+            }
+            if !(adjustment.first.is_zero() && adjustment.second.is_zero()) {
+                glyphs[index + 1].unsafe_to_break = true;
+            }
+            if adjustment.has_second {
+                glyphs[index + 1].unsafe_to_break = true;
+                if let Some(next) = glyphs.get_mut(index + 2) {
+                    next.unsafe_to_break = true;
+                }
+                index += 2;
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+/// Features enabled for a run, and whether kerning is among them
+///
+/// Returns `None` when kerning is only toggled for part of the run.
+fn enabled_features(options: &ShapingOptions) -> Option<(Vec<[u8; 4]>, bool)> {
+    let mut features: Vec<[u8; 4]> = DEFAULT_FEATURES.to_vec();
+    for (tag, &value) in &options.features {
+        let Ok(tag) = <[u8; 4]>::try_from(tag.as_bytes()) else {
+            continue;
+        };
+        features.retain(|&enabled| enabled != tag);
+        if value != 0 {
+            features.push(tag);
+        }
+    }
+    // Features enabled for a range may act anywhere in the run as far as
+    // this path is concerned
+    for range in &options.feature_ranges {
+        let Ok(tag) = <[u8; 4]>::try_from(range.tag.as_bytes()) else {
+            continue;
+        };
+        if tag == KERN {
+            return None;
+        }
+        if range.value != 0 && !features.contains(&tag) {
+            features.push(tag);
+        }
+    }
+    let kerning = features.contains(&KERN);
+    Some((features, kerning))
+}
+
+/// A GSUB or GPOS table with its script and feature lists
+struct LayoutData<'a> {
+    table: Option<(&'a [u8], LayoutTable)>,
+    is_gpos: bool,
+}
+
+impl<'a> LayoutData<'a> {
+    /// Wrap a layout table, if present
+    ///
+    /// Returns `None` for tables that cannot be read or that substitute
+    /// features by variation coordinates.
+    fn new(data: Option<&'a [u8]>, is_gpos: bool) -> Option<Self> {
+        let Some(data) = data else {
+            return Some(LayoutData {
+                table: None,
+                is_gpos,
+            });
+        };
+        if u16_at(data, 2)? >= 1 && u32_at(data, 10).is_some_and(|offset| offset != 0) {
+            return None;
+        }
+        Some(LayoutData {
+            table: Some((data, LayoutTable::parse(data).ok()?)),
+            is_gpos,
+        })
+    }
+
+    fn data(&self) -> &'a [u8] {
+        self.table.as_ref().map_or(&[], |(data, _)| data)
+    }
+
+    fn lookup_offset(&self, index: u16) -> Option<usize> {
+        let lookup_list = offset16(self.data(), 0, 8)?;
+        if index >= u16_at(self.data(), lookup_list)? {
+            return None;
+        }
+        offset16(self.data(), lookup_list, 2 + index as usize * 2)
+    }
+
+    /// Subtables of a lookup with their lookup types, extensions resolved
+    fn subtables(&self, index: u16) -> Option<Vec<(u16, usize)>> {
+        let data = self.data();
+        let lookup = self.lookup_offset(index)?;
+        let lookup_type = u16_at(data, lookup)?;
+        let extension = if self.is_gpos { 9 } else { 7 };
+        (0..u16_at(data, lookup + 4)? as usize)
+            .map(|i| {
+                let subtable = offset16(data, lookup, 6 + i * 2)?;
+                if lookup_type != extension {
+                    return Some((lookup_type, subtable));
+                }
+                let extension_type = u16_at(data, subtable + 2)?;
+                let offset = u32_at(data, subtable + 4)? as usize;
+                (extension_type != extension).then_some((extension_type, subtable + offset))
+            })
+            .collect()
+    }
+}
+
+/// Backtrack (nearest glyph first), remaining input and lookahead glyphs or
+/// classes of a context rule
+fn rule_sequences(data: &[u8], rule: usize, chained: bool) -> Option<[Vec<u16>; 3]> {
+    let read = |position: usize, count: usize| -> Option<Vec<u16>> {
+        (0..count).map(|i| u16_at(data, position + i * 2)).collect()
+    };
+    if !chained {
+        let count = u16_at(data, rule)?.saturating_sub(1) as usize;
+        return Some([Vec::new(), read(rule + 4, count)?, Vec::new()]);
+    }
+    let mut sequences: [Vec<u16>; 3] = Default::default();
+    let mut position = rule;
+    for (index, sequence) in sequences.iter_mut().enumerate() {
+        let mut count = u16_at(data, position)? as usize;
+        if index == 1 {
+            // The first input glyph is the one the rule set belongs to
+            count = count.saturating_sub(1);
+        }
+        *sequence = read(position + 2, count)?;
+        position += 2 + u16_at(data, position)? as usize * 2;
+    }
+    Some(sequences)
+}
+
+/// Coverage index of `glyph`, if the coverage table at `offset` covers it
+fn coverage_index(data: &[u8], offset: usize, glyph: u16) -> Option<u16> {
+    let count = u16_at(data, offset + 2)? as usize;
+    match u16_at(data, offset)? {
+        1 => (0..count)
+            .find(|&i| u16_at(data, offset + 4 + i * 2) == Some(glyph))
+            .map(|i| i as u16),
+        2 => (0..count).find_map(|i| {
+            let record = offset + 4 + i * 6;
+            let start = u16_at(data, record)?;
+            let end = u16_at(data, record + 2)?;
+            let start_index = u16_at(data, record + 4)?;
+            (start..=end)
+                .contains(&glyph)
+                .then(|| start_index.wrapping_add(glyph - start))
+        }),
+        _ => None,
+    }
+}
+
+/// Class of `glyph` in the class definition table at `offset`; 0 if absent
+fn class_of(data: &[u8], offset: usize, glyph: u16) -> u16 {
+    let class = || -> Option<u16> {
+        match u16_at(data, offset)? {
+            1 => {
+                let start = u16_at(data, offset + 2)?;
+                let count = u16_at(data, offset + 4)?;
+                let index = glyph.checked_sub(start).filter(|&i| i < count)?;
+                u16_at(data, offset + 6 + index as usize * 2)
+            }
+            2 => {
+                let count = u16_at(data, offset + 2)? as usize;
+                (0..count).find_map(|i| {
+                    let record = offset + 4 + i * 6;
+                    let start = u16_at(data, record)?;
+                    let end = u16_at(data, record + 2)?;
+                    (start..=end)
+                        .contains(&glyph)
+                        .then(|| u16_at(data, record + 4))
+                        .flatten()
+                })
+            }
+            _ => None,
+        }
+    };
+    class().unwrap_or(0)
+}
+
+/// Read the placement and advance fields of a value record
+fn value_record(data: &[u8], offset: usize, format: u16) -> Option<ValueRecord> {
+    let mut record = ValueRecord::default();
+    let mut position = offset;
+    let mut field = |bit: u16| -> Option<i16> {
+        if format & bit == 0 {
+            return Some(0);
+        }
+        let value = u16_at(data, position)? as i16;
+        position += 2;
+        Some(value)
+    };
+    record.x_placement = field(0x0001)?;
+    record.y_placement = field(0x0002)?;
+    record.x_advance = field(0x0004)?;
+    Some(record)
+}
+
+/// Offset16 at `base + field`, relative to `base`; `None` if null
+fn offset16(data: &[u8], base: usize, field: usize) -> Option<usize> {
+    let offset = u16_at(data, base + field)?;
+    (offset != 0).then_some(base + offset as usize)
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// The fast path must match the shaping engine on every run it accepts
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FeatureRange;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = format!(
+            "{}/../../tests/fixtures/fonts/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        std::fs::read(path).expect("font fixture")
+    }
+
+    fn latin_options() -> ShapingOptions {
+        ShapingOptions::builder()
+            .script(Script::Latin)
+            .language("en")
+            .build()
+    }
+
+    /// Shape with both paths, returning whether the fast path took the run
+    fn assert_matches_engine(
+        font_data: &[u8],
+        size: f32,
+        text: &str,
+        options: &ShapingOptions,
+    ) -> bool {
+        let face = SimpleFace::new(font_data).expect("supported font");
+        let Some(simple) = face.shape(size, text, options) else {
+            return false;
+        };
+        let engine = super::super::shape(font_data, size, text, options).unwrap();
+        assert_eq!(simple.len(), engine.len(), "glyph count of {:?}", text);
+        for (i, (s, e)) in simple.iter().zip(&engine).enumerate() {
+            assert_eq!(s.glyph_id, e.glyph_id, "glyph {} of {:?}", i, text);
+            assert_eq!(s.cluster, e.cluster, "cluster {} of {:?}", i, text);
+            assert_eq!(
+                s.unsafe_to_break, e.unsafe_to_break,
+                "unsafe_to_break {} of {:?}",
+                i, text
+            );
+            for (name, a, b) in [
+                ("x_advance", s.x_advance, e.x_advance),
+                ("y_advance", s.y_advance, e.y_advance),
+                ("x_offset", s.x_offset, e.x_offset),
+                ("y_offset", s.y_offset, e.y_offset),
+            ] {
+                assert!(
+                    (a - b).abs() <= 1e-4,
+                    "{} of glyph {} in {:?}: simple {} vs engine {}",
+                    name,
+                    i,
+                    text,
+                    a,
+                    b
+                );
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn test_simple_path_matches_engine_on_kerned_text() {
+        let options = latin_options();
+        for font in ["DejaVuSans.ttf", "DejaVuSerif.ttf"] {
+            let data = fixture(font);
+            for size in [12.0, 16.0, 23.5] {
+                for text in [
+                    "Hello, World!",
+                    "AVATAR WAVE Ty",
+                    "To Vietnam, P.J. Yates wrote: \"LT Av\".",
+                    "0123456789 +-*/ = ~[]{}",
+                    // `f` only blocks the fast path before a ligature partner
+                    "The quick brown fox jumps over the lazy dog",
+                ] {
+                    assert!(
+                        assert_matches_engine(&data, size, text, &options),
+                        "{} should take the fast path in {}",
+                        text,
+                        font
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simple_path_matches_engine_across_options() {
+        let data = fixture("DejaVuSans.ttf");
+        let mut common = latin_options();
+        common.script = Script::Common;
+        let no_kerning = ShapingOptions::builder()
+            .script(Script::Latin)
+            .feature("kern", 0)
+            .build();
+        let no_ligatures = ShapingOptions::builder()
+            .script(Script::Latin)
+            .feature("liga", 0)
+            .build();
+        for options in [&common, &no_kerning, &no_ligatures] {
+            assert!(assert_matches_engine(&data, 16.0, "AVATAR WAVE", options));
+        }
+        assert!(assert_matches_engine(&data, 16.0, "office", &no_ligatures));
+    }
+
+    #[test]
+    fn test_simple_path_declines_runs_it_cannot_shape() {
+        let data = fixture("DejaVuSans.ttf");
+        let face = SimpleFace::new(&data).unwrap();
+        let options = latin_options();
+
+        // Ligatures, combining marks and non-ASCII text need the engine
+        assert!(face.shape(16.0, "office", &options).is_none());
+        assert!(face.shape(16.0, "cafe\u{301}", &options).is_none());
+        assert!(face.shape(16.0, "naïve", &options).is_none());
+
+        // As do other directions and scripts
+        let mut rtl = options.clone();
+        rtl.direction = Direction::RightToLeft;
+        assert!(face.shape(16.0, "Hello", &rtl).is_none());
+        let mut arabic = options.clone();
+        arabic.script = Script::Arabic;
+        assert!(face.shape(16.0, "Hello", &arabic).is_none());
+
+        // Kerning toggled for part of the run
+        let mut partial = options.clone();
+        partial.feature_ranges.push(FeatureRange {
+            tag: "kern".to_string(),
+            value: 0,
+            range: 0..2,
+        });
+        assert!(face.shape(16.0, "AVATAR", &partial).is_none());
+    }
+}
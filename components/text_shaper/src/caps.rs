@@ -0,0 +1,342 @@
+//! `font-variant-caps` support
+//!
+//! Capital variants map to OpenType features when the font's GSUB provides
+//! them for the script. Missing small caps are synthesized: the affected
+//! letters are uppercased and shaped as separate runs at a reduced size,
+//! which each glyph records as its scale. With
+//! `ShapingOptions::synthesize_small_caps` off, they are shaped unchanged.
+
+use font_parser::{OpenTypeFont, Tag};
+
+use crate::backend::{self, RawGlyph};
+use crate::transform::TransformedText;
+use crate::types::{FeatureRange, FontVariantCaps, ShapingError, ShapingOptions, TextTransform};
+
+/// Small caps size relative to the font size when the font has no
+/// x-height/cap-height metrics
+pub(crate) const DEFAULT_SMALL_CAPS_SCALE: f32 = 0.7;
+
+/// How a variant is rendered with a particular font
+#[derive(Debug, Default, PartialEq)]
+struct CapsPlan {
+    /// OpenType features to enable
+    features: Vec<&'static str>,
+    /// Render lowercase letters as synthesized small caps
+    synthesize_lowercase: bool,
+    /// Render uppercase letters as synthesized small caps
+    synthesize_uppercase: bool,
+}
+
+impl CapsPlan {
+    fn new(variant: FontVariantCaps, supports: impl Fn(&str) -> bool) -> Self {
+        let mut plan = CapsPlan::default();
+        match variant {
+            FontVariantCaps::Normal => {}
+            FontVariantCaps::SmallCaps => plan.small_caps(&supports),
+            FontVariantCaps::AllSmallCaps => {
+                plan.small_caps(&supports);
+                plan.capitals_to_small_caps(&supports);
+            }
+            FontVariantCaps::PetiteCaps => {
+                if supports("pcap") {
+                    plan.features.push("pcap");
+                } else {
+                    plan.small_caps(&supports);
+                }
+            }
+            FontVariantCaps::AllPetiteCaps => {
+                if supports("pcap") && supports("c2pc") {
+                    plan.features.extend(["pcap", "c2pc"]);
+                } else {
+                    plan.small_caps(&supports);
+                    plan.capitals_to_small_caps(&supports);
+                }
+            }
+            FontVariantCaps::Unicase => {
+                if supports("unic") {
+                    plan.features.push("unic");
+                } else {
+                    plan.synthesize_uppercase = true;
+                }
+            }
+            FontVariantCaps::TitlingCaps => {
+                if supports("titl") {
+                    plan.features.push("titl");
+                }
+            }
+        }
+        plan
+    }
+
+    fn small_caps(&mut self, supports: impl Fn(&str) -> bool) {
+        if supports("smcp") {
+            self.features.push("smcp");
+        } else {
+            self.synthesize_lowercase = true;
+        }
+    }
+
+    fn capitals_to_small_caps(&mut self, supports: impl Fn(&str) -> bool) {
+        if supports("c2sc") {
+            self.features.push("c2sc");
+        } else {
+            self.synthesize_uppercase = true;
+        }
+    }
+
+    /// Whether `c` is drawn as a synthesized small capital
+    fn synthesizes(&self, c: char) -> bool {
+        (self.synthesize_lowercase && c.is_lowercase() && has_uppercase(c))
+            || (self.synthesize_uppercase && c.is_uppercase())
+    }
+}
+
+/// Shape `text` honoring `options.variant_caps`
+///
+/// Returns each glyph with its size relative to `size`; synthesized small
+/// caps use `small_caps_scale`.
+pub(crate) fn shape(
+    font_data: &[u8],
+    size: f32,
+    text: &str,
+    options: &ShapingOptions,
+    small_caps_scale: f32,
+) -> Result<Vec<(RawGlyph, f32)>, ShapingError> {
+    let gsub = OpenTypeFont::parse(font_data.to_vec())
+        .ok()
+        .and_then(|font| font.get_gsub());
+    let script = Tag::from_bytes(u32::from_be_bytes(backend::script_tag(options.script)));
+    let mut plan = CapsPlan::new(options.variant_caps, |feature| {
+        gsub.as_ref().is_some_and(|gsub| {
+            gsub.has_script_feature(script, Tag::new(feature).expect("valid tag"))
+        })
+    });
+    if !options.synthesize_small_caps {
+        plan.synthesize_lowercase = false;
+        plan.synthesize_uppercase = false;
+    }
+
+    // Features set explicitly take precedence over the variant
+    let mut options = options.clone();
+    for feature in &plan.features {
+        options.features.entry(feature.to_string()).or_insert(1);
+    }
+
+    if !plan.synthesize_lowercase && !plan.synthesize_uppercase {
+        return Ok(backend::shape(font_data, size, text, &options)?
+            .into_iter()
+            .map(|glyph| (glyph, 1.0))
+            .collect());
+    }
+
+    let mut glyphs = Vec::new();
+    for (start, run, synthesized) in caps_runs(text, &plan) {
+        let mut run_options = options.clone();
+        if synthesized {
+            let upper = TransformedText::new(run, TextTransform::Uppercase, &options.language);
+            run_options.feature_ranges = run_feature_ranges(&options, start, run.len(), |offset| {
+                upper.transformed_offset(offset)
+            });
+            for mut glyph in backend::shape(
+                font_data,
+                size * small_caps_scale,
+                upper.text(),
+                &run_options,
+            )? {
+                glyph.cluster = (start + upper.source_offset(glyph.cluster as usize)) as u32;
+                glyphs.push((glyph, small_caps_scale));
+            }
+        } else {
+            run_options.feature_ranges =
+                run_feature_ranges(&options, start, run.len(), |offset| offset);
+            for mut glyph in backend::shape(font_data, size, run, &run_options)? {
+                glyph.cluster += start as u32;
+                glyphs.push((glyph, 1.0));
+            }
+        }
+    }
+    Ok(glyphs)
+}
+
+/// Split `text` into `(byte offset, run, synthesized)` runs
+fn caps_runs<'t>(text: &'t str, plan: &CapsPlan) -> Vec<(usize, &'t str, bool)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut current = None;
+    for (index, c) in text.char_indices() {
+        let synthesized = plan.synthesizes(c);
+        // Marks and other caseless characters stay with the preceding run
+        let caseless = !c.is_alphabetic();
+        match current {
+            Some(previous) if previous == synthesized || caseless => {}
+            Some(previous) => {
+                runs.push((start, &text[start..index], previous));
+                start = index;
+                current = Some(synthesized);
+            }
+            None => current = Some(synthesized),
+        }
+    }
+    if let Some(synthesized) = current {
+        runs.push((start, &text[start..], synthesized));
+    }
+    runs
+}
+
+/// Feature ranges clipped to the run at `start` and moved into its text
+///
+/// `to_run` maps a byte offset within the run to the shaped run text, which
+/// differs from the source for uppercased runs.
+pub(crate) fn run_feature_ranges(
+    options: &ShapingOptions,
+    start: usize,
+    len: usize,
+    to_run: impl Fn(usize) -> usize,
+) -> Vec<FeatureRange> {
+    options
+        .feature_ranges
+        .iter()
+        .filter(|feature| feature.range.start < start + len && feature.range.end > start)
+        .map(|feature| FeatureRange {
+            range: to_run(feature.range.start.max(start) - start)
+                ..to_run(feature.range.end.min(start + len) - start),
+            ..feature.clone()
+        })
+        .collect()
+}
+
+fn has_uppercase(c: char) -> bool {
+    let mut upper = c.to_uppercase();
+    upper.next() != Some(c) || upper.next().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FormatCharacters, MatchContext};
+
+    #[test]
+    fn test_plan_prefers_font_features() {
+        let all = |_: &str| true;
+        let plan = CapsPlan::new(FontVariantCaps::AllSmallCaps, all);
+        assert_eq!(plan.features, vec!["smcp", "c2sc"]);
+        assert!(!plan.synthesize_lowercase && !plan.synthesize_uppercase);
+    }
+
+    #[test]
+    fn test_plan_falls_back_to_synthesis() {
+        let none = |_: &str| false;
+        let plan = CapsPlan::new(FontVariantCaps::AllPetiteCaps, none);
+        assert!(plan.features.is_empty());
+        assert!(plan.synthesize_lowercase && plan.synthesize_uppercase);
+
+        let plan = CapsPlan::new(FontVariantCaps::Unicase, none);
+        assert!(!plan.synthesize_lowercase && plan.synthesize_uppercase);
+
+        assert_eq!(
+            CapsPlan::new(FontVariantCaps::TitlingCaps, none),
+            CapsPlan::default()
+        );
+    }
+
+    #[test]
+    fn test_plan_mixes_features_and_synthesis() {
+        let smcp_only = |feature: &str| feature == "smcp";
+        let plan = CapsPlan::new(FontVariantCaps::AllSmallCaps, smcp_only);
+        assert_eq!(plan.features, vec!["smcp"]);
+        assert!(!plan.synthesize_lowercase && plan.synthesize_uppercase);
+    }
+
+    #[test]
+    fn test_caps_runs_split_on_case() {
+        let plan = CapsPlan {
+            synthesize_lowercase: true,
+            ..CapsPlan::default()
+        };
+        assert_eq!(
+            caps_runs("Hello, World", &plan),
+            vec![
+                (0, "H", false),
+                (1, "ello, ", true),
+                (7, "W", false),
+                (8, "orld", true),
+            ]
+        );
+    }
+
+    fn options_with_ranges(ranges: &[(&str, std::ops::Range<usize>)]) -> ShapingOptions {
+        ShapingOptions {
+            script: crate::types::Script::Latin,
+            language: crate::types::Language {
+                tag: "en".to_string(),
+            },
+            direction: font_types::types::Direction::LeftToRight,
+            features: Default::default(),
+            kerning: true,
+            ligatures: true,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            variant_caps: FontVariantCaps::SmallCaps,
+            feature_ranges: ranges
+                .iter()
+                .map(|(tag, range)| FeatureRange {
+                    tag: tag.to_string(),
+                    value: 1,
+                    range: range.clone(),
+                })
+                .collect(),
+            synthesize_small_caps: true,
+            match_context: MatchContext::Privileged,
+            text_transform: TextTransform::None,
+            format_characters: FormatCharacters::Hidden,
+        }
+    }
+
+    #[test]
+    fn test_run_feature_ranges_are_clipped_and_moved() {
+        let options = options_with_ranges(&[("tnum", 2..6), ("zero", 8..10)]);
+
+        let ranges = run_feature_ranges(&options, 4, 4, |offset| offset);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].tag, "tnum");
+        assert_eq!(ranges[0].range, 0..2);
+    }
+
+    #[test]
+    fn test_run_feature_ranges_follow_uppercasing() {
+        // "aßc" uppercases to "ASSC": "ß" (bytes 1..3) becomes "SS" (1..3)
+        // and "c" (3..4) stays at 3..4
+        let options = options_with_ranges(&[("smcp", 1..3), ("case", 3..4)]);
+        let upper = TransformedText::new("aßc", TextTransform::Uppercase, &options.language);
+
+        let ranges = run_feature_ranges(&options, 0, 4, |offset| upper.transformed_offset(offset));
+        assert_eq!(ranges[0].range, 1..3);
+        assert_eq!(ranges[1].range, 3..4);
+    }
+
+    fn uppercase(run: &str, tag: &str) -> (String, Vec<usize>) {
+        let language = crate::types::Language {
+            tag: tag.to_string(),
+        };
+        let upper = TransformedText::new(run, TextTransform::Uppercase, &language);
+        let origins = (0..=upper.text().len())
+            .map(|offset| upper.source_offset(offset))
+            .collect();
+        (upper.text().to_string(), origins)
+    }
+
+    #[test]
+    fn test_uppercase_maps_bytes_to_source() {
+        let (upper, origins) = uppercase("aßc", "en");
+        assert_eq!(upper, "ASSC");
+        assert_eq!(origins, vec![0, 1, 1, 3, 4]);
+    }
+
+    #[test]
+    fn test_uppercase_turkic_dotted_i() {
+        let (upper, origins) = uppercase("iı", "tr");
+        assert_eq!(upper, "İI");
+        assert_eq!(origins, vec![0, 0, 1, 3]);
+        assert_eq!(uppercase("iı", "en").0, "II");
+    }
+}
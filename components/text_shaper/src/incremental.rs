@@ -0,0 +1,237 @@
+//! Incremental reshaping after text edits
+//!
+//! An edit only changes the shaping of the text around it. The dirty region
+//! is widened to whitespace on both sides where the previous result was safe
+//! to break (no ligature, kerning pair or contextual substitution spans the
+//! boundary), reshaped on its own, and spliced between the previous glyphs
+//! before and after it. Glyph positions are then laid out again from the
+//! advances, exactly as a full shaping pass would.
+
+use std::ops::Range;
+
+use font_types::types::{Direction, Point, PositionedGlyph, ShapedText};
+
+use crate::types::{ShapingError, TextEdit};
+
+/// Length of the text before `edit` turned it into `text`
+///
+/// # Errors
+///
+/// Returns `ShapingError::InvalidText` if the edit doesn't fit `text` or
+/// doesn't fall on character boundaries.
+pub(crate) fn previous_len(edit: &TextEdit, text: &str) -> Result<usize, ShapingError> {
+    let inserted_end = edit.range.start + edit.inserted_len;
+    if edit.range.start > edit.range.end
+        || !text.is_char_boundary(edit.range.start)
+        || inserted_end > text.len()
+        || !text.is_char_boundary(inserted_end)
+    {
+        return Err(ShapingError::InvalidText(format!(
+            "edit {:?} (+{} bytes) doesn't fit the text",
+            edit.range, edit.inserted_len
+        )));
+    }
+    Ok(text.len() - edit.inserted_len + edit.range.len())
+}
+
+/// Byte range of the previous text to reshape, or `None` when the whole text
+/// has to be shaped again
+///
+/// The region starts at whitespace before the edit and ends at whitespace
+/// after it, at the nearest such points where `previous` is safe to break.
+pub(crate) fn dirty_region(
+    previous: &[PositionedGlyph],
+    edit: &TextEdit,
+    text: &str,
+    previous_len: usize,
+) -> Option<Range<usize>> {
+    if previous
+        .iter()
+        .any(|glyph| glyph.cluster as usize >= previous_len)
+    {
+        return None;
+    }
+    let safe_to_break = |offset: usize| {
+        let mut cluster = previous
+            .iter()
+            .filter(|glyph| glyph.cluster as usize == offset)
+            .peekable();
+        cluster.peek().is_some() && cluster.all(|glyph| !glyph.unsafe_to_break)
+    };
+
+    let start = text[..edit.range.start]
+        .char_indices()
+        .rev()
+        .filter(|(_, c)| c.is_whitespace())
+        .map(|(offset, _)| offset)
+        .find(|&offset| safe_to_break(offset))
+        .unwrap_or(0);
+
+    // Whitespace right after the edit is skipped: the inserted text may
+    // interact with it in ways the previous result can't show
+    let inserted_end = edit.range.start + edit.inserted_len;
+    let end = text[inserted_end..]
+        .char_indices()
+        .filter(|&(offset, c)| offset > 0 && c.is_whitespace())
+        .map(|(offset, _)| edit.range.end + offset)
+        .find(|&offset| safe_to_break(offset))
+        .unwrap_or(previous_len);
+
+    (start > 0 || end < previous_len).then_some(start..end)
+}
+
+/// Replace the glyphs of `region` in `previous` with `reshaped`
+///
+/// `reshaped` holds the glyphs of the region's new text with clusters
+/// relative to the edited text. Clusters after the region move by the
+/// edit's change in length, and positions are laid out again.
+pub(crate) fn splice(
+    previous: &ShapedText,
+    region: Range<usize>,
+    edit: &TextEdit,
+    reshaped: Vec<PositionedGlyph>,
+    direction: Direction,
+) -> ShapedText {
+    let before = previous
+        .glyphs
+        .iter()
+        .filter(|glyph| (glyph.cluster as usize) < region.start)
+        .cloned();
+    let after = previous
+        .glyphs
+        .iter()
+        .filter(|glyph| glyph.cluster as usize >= region.end)
+        .map(|glyph| PositionedGlyph {
+            cluster: (glyph.cluster as usize + edit.inserted_len - edit.range.len()) as u32,
+            ..glyph.clone()
+        });
+
+    let backward = matches!(direction, Direction::RightToLeft | Direction::BottomToTop);
+    let mut glyphs: Vec<PositionedGlyph> = if backward {
+        after.chain(reshaped).chain(before).collect()
+    } else {
+        before.chain(reshaped).chain(after).collect()
+    };
+
+    let mut pen = Point { x: 0.0, y: 0.0 };
+    for glyph in &mut glyphs {
+        glyph.position = Point {
+            x: pen.x + glyph.offset.x,
+            y: pen.y + glyph.offset.y,
+        };
+        pen.x += glyph.advance.x;
+        pen.y += glyph.advance.y;
+    }
+
+    // Vertical text grows along y and keeps its column width
+    let (width, height) = if direction.is_vertical() {
+        (previous.width, pen.y)
+    } else {
+        (pen.x, previous.height)
+    };
+    ShapedText {
+        glyphs,
+        width,
+        height,
+        baseline: previous.baseline,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use font_types::types::{GlyphId, Vector};
+
+    /// One glyph per byte of `text`, unsafe to break where `unsafe_at` has
+    /// the glyph's offset
+    fn glyphs(text: &str, unsafe_at: &[usize]) -> Vec<PositionedGlyph> {
+        (0..text.len())
+            .map(|cluster| PositionedGlyph {
+                glyph_id: GlyphId {
+                    id: text.as_bytes()[cluster] as u32,
+                },
+                font_id: 0,
+                position: Point {
+                    x: cluster as f32 * 10.0,
+                    y: 0.0,
+                },
+                advance: Vector { x: 10.0, y: 0.0 },
+                offset: Vector { x: 0.0, y: 0.0 },
+                scale: 1.0,
+                cluster: cluster as u32,
+                unsafe_to_break: unsafe_at.contains(&cluster),
+            })
+            .collect()
+    }
+
+    fn edit(range: Range<usize>, inserted_len: usize) -> TextEdit {
+        TextEdit {
+            range,
+            inserted_len,
+        }
+    }
+
+    #[test]
+    fn test_previous_len() {
+        assert_eq!(previous_len(&edit(2..4, 1), "abXef"), Ok(6));
+        assert_eq!(previous_len(&edit(5..5, 0), "abcde"), Ok(5));
+        assert!(previous_len(&edit(Range { start: 4, end: 2 }, 0), "abcde").is_err());
+        assert!(previous_len(&edit(3..3, 4), "abcde").is_err());
+        assert!(previous_len(&edit(1..1, 1), "aé").is_err());
+    }
+
+    #[test]
+    fn test_dirty_region_spans_edited_words() {
+        // "one two three four" -> "one twoo three four"
+        let previous = glyphs("one two three four", &[]);
+        let region = dirty_region(&previous, &edit(7..7, 1), "one twoo three four", 18);
+
+        assert_eq!(region, Some(3..13));
+    }
+
+    #[test]
+    fn test_dirty_region_skips_unsafe_boundaries() {
+        // The space before "two" kerns with the preceding "e"
+        let previous = glyphs("one two three four", &[3]);
+        let region = dirty_region(&previous, &edit(5..6, 1), "one tXo three four", 18);
+
+        assert_eq!(region, Some(0..7));
+    }
+
+    #[test]
+    fn test_dirty_region_whole_text() {
+        // Without whitespace, the edited word is the whole text
+        let previous = glyphs("onetwo", &[]);
+        assert_eq!(dirty_region(&previous, &edit(3..3, 1), "oneXtwo", 6), None);
+
+        // The previous result belongs to a longer text
+        let previous = glyphs("one two", &[]);
+        assert_eq!(dirty_region(&previous, &edit(0..1, 1), "xne tw", 6), None);
+    }
+
+    #[test]
+    fn test_splice_moves_following_glyphs() {
+        // "ab cd ef" -> "ab xyz ef", reshaping " cd" (2..5) as " xyz"
+        let previous = ShapedText {
+            glyphs: glyphs("ab cd ef", &[]),
+            width: 80.0,
+            height: 12.0,
+            baseline: 9.0,
+        };
+        let reshaped: Vec<_> = glyphs("ab xyz", &[]).split_off(2);
+
+        let shaped = splice(
+            &previous,
+            2..5,
+            &edit(3..5, 3),
+            reshaped,
+            Direction::LeftToRight,
+        );
+
+        let clusters: Vec<u32> = shaped.glyphs.iter().map(|glyph| glyph.cluster).collect();
+        assert_eq!(clusters, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(shaped.glyphs[8].position.x, 80.0);
+        assert_eq!(shaped.width, 90.0);
+        assert_eq!(shaped.baseline, 9.0);
+    }
+}
@@ -0,0 +1,133 @@
+//! Kashida (tatweel) elongation points for Arabic justification
+//!
+//! Arabic text is justified by lengthening the connection between two
+//! joined letters with tatweel (U+0640) glyphs rather than by widening the
+//! spaces. A connection exists where a dual-joining letter is followed by a
+//! letter that joins on its right; combining marks in between are
+//! transparent. Calligraphers elongate once per group of joined letters,
+//! usually at the last connection, which is where the points are placed.
+
+/// How a character takes part in cursive joining (Unicode
+/// `Joining_Type`), for the Arabic block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Joining {
+    /// Joins on both sides
+    Dual,
+    /// Joins only to the preceding letter
+    Right,
+    /// Skipped when joining (combining marks)
+    Transparent,
+    /// Breaks the connection
+    None,
+}
+
+fn joining(c: char) -> Joining {
+    match c {
+        '\u{610}'..='\u{61A}'
+        | '\u{64B}'..='\u{65F}'
+        | '\u{670}'
+        | '\u{6D6}'..='\u{6DC}'
+        | '\u{6DF}'..='\u{6E4}'
+        | '\u{6E7}'..='\u{6E8}'
+        | '\u{6EA}'..='\u{6ED}' => Joining::Transparent,
+        '\u{622}'..='\u{625}'
+        | '\u{627}'
+        | '\u{629}'
+        | '\u{62F}'..='\u{632}'
+        | '\u{648}'
+        | '\u{671}'..='\u{673}'
+        | '\u{675}'..='\u{677}'
+        | '\u{688}'..='\u{699}'
+        | '\u{6C0}'
+        | '\u{6C3}'..='\u{6CB}'
+        | '\u{6CD}'
+        | '\u{6CF}'
+        | '\u{6D2}'..='\u{6D3}'
+        | '\u{6D5}'
+        | '\u{6EE}'..='\u{6EF}' => Joining::Right,
+        '\u{620}'
+        | '\u{626}'
+        | '\u{628}'
+        | '\u{62A}'..='\u{62E}'
+        | '\u{633}'..='\u{63F}'
+        | '\u{640}'..='\u{647}'
+        | '\u{649}'..='\u{64A}'
+        | '\u{66E}'..='\u{66F}'
+        | '\u{678}'..='\u{687}'
+        | '\u{69A}'..='\u{6BF}'
+        | '\u{6C1}'..='\u{6C2}'
+        | '\u{6CC}'
+        | '\u{6CE}'
+        | '\u{6D0}'..='\u{6D1}'
+        | '\u{6FA}'..='\u{6FC}'
+        | '\u{6FF}' => Joining::Dual,
+        _ => Joining::None,
+    }
+}
+
+/// Byte offsets in `text` where a kashida can be inserted, in text order
+///
+/// Each offset is the start of a letter joined to the letter before it;
+/// tatweel glyphs go between the two. There is one point per group of
+/// joined letters, at its last connection.
+pub fn kashida_points(text: &str) -> Vec<usize> {
+    let mut points = Vec::new();
+    // Offset of the last connection in the current group of joined letters
+    let mut pending = None;
+    let mut joins_next = false;
+
+    for (offset, c) in text.char_indices() {
+        match joining(c) {
+            Joining::Transparent => continue,
+            Joining::Dual => {
+                if joins_next {
+                    pending = Some(offset);
+                }
+                joins_next = true;
+            }
+            Joining::Right => {
+                if joins_next {
+                    pending = Some(offset);
+                }
+                joins_next = false;
+                points.extend(pending.take());
+            }
+            Joining::None => {
+                joins_next = false;
+                points.extend(pending.take());
+            }
+        }
+    }
+    points.extend(pending);
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kashida_at_last_connection_of_each_word() {
+        // "بسم الله": beh-seen-meem, then alef (no connection), lam-lam-heh
+        let text = "\u{628}\u{633}\u{645} \u{627}\u{644}\u{644}\u{647}";
+        let meem = "\u{628}\u{633}".len();
+        let heh = text.len() - "\u{647}".len();
+        assert_eq!(kashida_points(text), [meem, heh]);
+    }
+
+    #[test]
+    fn test_right_joining_letters_end_groups() {
+        // beh-alef join, but alef doesn't join the following beh; marks
+        // between letters are skipped
+        let text = "\u{628}\u{64E}\u{627}\u{628}\u{628}";
+        let alef = "\u{628}\u{64E}".len();
+        let last_beh = text.len() - "\u{628}".len();
+        assert_eq!(kashida_points(text), [alef, last_beh]);
+    }
+
+    #[test]
+    fn test_no_kashida_outside_arabic() {
+        assert!(kashida_points("Hello world").is_empty());
+        assert!(kashida_points("\u{627}\u{62F}").is_empty());
+    }
+}
@@ -0,0 +1,535 @@
+//! BCP 47 language tags and language-sensitive shaping defaults
+//!
+//! Shaping engines pick localized glyph forms (`locl`) from the language of
+//! the text. When `ShapingOptions::language` is left unspecified, the
+//! language is inferred from the script and the shaper's locale. Some
+//! languages also enable default features, and Turkic languages avoid `fi`
+//! ligatures that would drop the dot distinguishing `i` from `ı` in fonts
+//! without Turkish localized forms.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use font_parser::{OpenTypeFont, Tag};
+
+use crate::backend;
+use crate::types::{FeatureRange, Language, Script, ShapingError, ShapingOptions};
+
+/// Primary language subtag of undetermined language
+const UNDETERMINED: &str = "und";
+
+/// OpenType language system tags of common languages
+const OPENTYPE_TAGS: &[(&str, &[u8; 4])] = &[
+    ("ar", b"ARA "),
+    ("az", b"AZE "),
+    ("ba", b"BSH "),
+    ("be", b"BEL "),
+    ("bg", b"BGR "),
+    ("ca", b"CAT "),
+    ("crh", b"CRT "),
+    ("cs", b"CSY "),
+    ("da", b"DAN "),
+    ("de", b"DEU "),
+    ("el", b"ELL "),
+    ("en", b"ENG "),
+    ("es", b"ESP "),
+    ("fa", b"FAR "),
+    ("fi", b"FIN "),
+    ("fr", b"FRA "),
+    ("he", b"IWR "),
+    ("hr", b"HRV "),
+    ("hu", b"HUN "),
+    ("it", b"ITA "),
+    ("ja", b"JAN "),
+    ("kk", b"KAZ "),
+    ("ko", b"KOR "),
+    ("mk", b"MKD "),
+    ("mo", b"MOL "),
+    ("nb", b"NOR "),
+    ("nl", b"NLD "),
+    ("pl", b"PLK "),
+    ("pt", b"PTG "),
+    ("ro", b"ROM "),
+    ("ru", b"RUS "),
+    ("sk", b"SKY "),
+    ("sr", b"SRB "),
+    ("sv", b"SVE "),
+    ("tr", b"TRK "),
+    ("tt", b"TAT "),
+    ("uk", b"UKR "),
+    ("ur", b"URD "),
+    ("vi", b"VIT "),
+    ("yi", b"JII "),
+];
+
+/// Scripts of languages not written (only) in Latin
+const LANGUAGE_SCRIPTS: &[(&str, &[Script])] = &[
+    ("ar", &[Script::Arabic]),
+    ("az", &[Script::Latin, Script::Cyrillic]),
+    ("ba", &[Script::Cyrillic]),
+    ("be", &[Script::Cyrillic]),
+    ("bg", &[Script::Cyrillic]),
+    ("el", &[Script::Greek]),
+    ("fa", &[Script::Arabic]),
+    ("he", &[Script::Hebrew]),
+    ("ja", &[Script::Han, Script::Hiragana, Script::Katakana]),
+    ("kk", &[Script::Cyrillic]),
+    ("ko", &[Script::Hangul, Script::Han]),
+    ("mk", &[Script::Cyrillic]),
+    ("ru", &[Script::Cyrillic]),
+    ("sr", &[Script::Cyrillic, Script::Latin]),
+    ("tt", &[Script::Cyrillic]),
+    ("uk", &[Script::Cyrillic]),
+    ("ur", &[Script::Arabic]),
+    ("yi", &[Script::Hebrew]),
+    ("zh", &[Script::Han]),
+];
+
+/// Languages whose orthography relies on the font's localized forms
+const LOCALIZED_FORMS: &[&str] = &[
+    "az", "ba", "bg", "ca", "crh", "mk", "mo", "nl", "pl", "ro", "sr", "tr", "tt",
+];
+
+/// Languages with dotted and dotless i
+const TURKIC: &[&str] = &["az", "ba", "crh", "tr", "tt"];
+
+impl Language {
+    /// Parse and normalize a BCP 47 language tag
+    ///
+    /// Accepts `_` as a separator and normalizes case: the language
+    /// lowercase, the script titlecase (`Latn`) and the region uppercase.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapingError::InvalidLanguage` if the tag is not well-formed.
+    pub fn parse(tag: &str) -> Result<Self, ShapingError> {
+        let invalid = || ShapingError::InvalidLanguage(tag.to_string());
+        let lowercase = tag.replace('_', "-").to_ascii_lowercase();
+        let mut subtags = lowercase.split('-').peekable();
+        let mut normalized = Vec::new();
+
+        // Private use tags have no language
+        if subtags.peek() != Some(&"x") {
+            let language = subtags.next().ok_or_else(invalid)?;
+            if !(is_alpha(language, 2..=3) || is_alpha(language, 5..=8)) {
+                return Err(invalid());
+            }
+            normalized.push(language.to_string());
+            // Extended language subtags
+            if language.len() <= 3 {
+                for _ in 0..3 {
+                    match subtags.next_if(|subtag| is_alpha(subtag, 3..=3)) {
+                        Some(extlang) => normalized.push(extlang.to_string()),
+                        None => break,
+                    }
+                }
+            }
+            if let Some(script) = subtags.next_if(|subtag| is_alpha(subtag, 4..=4)) {
+                normalized.push(script[..1].to_ascii_uppercase() + &script[1..]);
+            }
+            if let Some(region) = subtags.next_if(|subtag| {
+                is_alpha(subtag, 2..=2)
+                    || (subtag.len() == 3 && subtag.bytes().all(|b| b.is_ascii_digit()))
+            }) {
+                normalized.push(region.to_ascii_uppercase());
+            }
+            while let Some(variant) = subtags.next_if(|subtag| is_variant(subtag)) {
+                normalized.push(variant.to_string());
+            }
+            while let Some(singleton) = subtags.next_if(|subtag| {
+                subtag.len() == 1 && *subtag != "x" && is_alphanumeric(subtag, 1..=1)
+            }) {
+                normalized.push(singleton.to_string());
+                let mut extension_len = 0;
+                while let Some(subtag) = subtags.next_if(|subtag| is_alphanumeric(subtag, 2..=8)) {
+                    normalized.push(subtag.to_string());
+                    extension_len += 1;
+                }
+                if extension_len == 0 {
+                    return Err(invalid());
+                }
+            }
+        }
+
+        if let Some(private_use) = subtags.next() {
+            if private_use != "x" {
+                return Err(invalid());
+            }
+            normalized.push(private_use.to_string());
+            let mut private_use_len = 0;
+            for subtag in subtags {
+                if !is_alphanumeric(subtag, 1..=8) {
+                    return Err(invalid());
+                }
+                normalized.push(subtag.to_string());
+                private_use_len += 1;
+            }
+            if private_use_len == 0 {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Language {
+            tag: normalized.join("-"),
+        })
+    }
+
+    /// Infer the language of text in `script`
+    ///
+    /// Uses `locale` when its language is written in `script` (for
+    /// `Script::Common`, any locale), otherwise the script's most common
+    /// language. Languages not known to use another script are assumed to
+    /// be written in Latin.
+    pub fn infer(script: Script, locale: Option<&Language>) -> Language {
+        if let Some(locale) = locale.filter(|locale| !locale.is_unspecified()) {
+            if script == Script::Common || locale.writes(script) {
+                return locale.clone();
+            }
+        }
+        let tag = match script {
+            Script::Latin => "en",
+            Script::Arabic => "ar",
+            Script::Hebrew => "he",
+            Script::Cyrillic => "ru",
+            Script::Greek => "el",
+            Script::Han => "zh",
+            Script::Hangul => "ko",
+            Script::Hiragana | Script::Katakana => "ja",
+            Script::Common => UNDETERMINED,
+        };
+        Language {
+            tag: tag.to_string(),
+        }
+    }
+
+    /// Whether no language is given (an empty tag or `und`)
+    pub fn is_unspecified(&self) -> bool {
+        self.tag.is_empty() || self.primary_language().eq_ignore_ascii_case(UNDETERMINED)
+    }
+
+    /// Primary language subtag (e.g. "tr" for "tr-TR")
+    pub fn primary_language(&self) -> &str {
+        self.tag.split(['-', '_']).next().unwrap_or_default()
+    }
+
+    /// Script subtag (e.g. "Latn" for "sr-Latn-RS")
+    pub fn script_subtag(&self) -> Option<&str> {
+        self.language_subtags()
+            .find(|subtag| subtag.len() == 4 && subtag.bytes().all(|b| b.is_ascii_alphabetic()))
+    }
+
+    /// Region subtag (e.g. "TR" for "tr-TR")
+    pub fn region(&self) -> Option<&str> {
+        self.language_subtags().find(|subtag| {
+            (subtag.len() == 2 && subtag.bytes().all(|b| b.is_ascii_alphabetic()))
+                || (subtag.len() == 3 && subtag.bytes().all(|b| b.is_ascii_digit()))
+        })
+    }
+
+    /// OpenType language system tag (e.g. "TRK " for Turkish), if known
+    pub fn opentype_tag(&self) -> Option<&'static str> {
+        let primary = self.primary_language().to_ascii_lowercase();
+        if primary == "zh" {
+            let traditional = self
+                .script_subtag()
+                .is_some_and(|s| s.eq_ignore_ascii_case("hant"))
+                || self
+                    .region()
+                    .is_some_and(|r| ["HK", "MO", "TW"].iter().any(|t| r.eq_ignore_ascii_case(t)));
+            return Some(if traditional { "ZHT " } else { "ZHS " });
+        }
+        OPENTYPE_TAGS
+            .iter()
+            .find(|(language, _)| *language == primary)
+            .map(|(_, tag)| std::str::from_utf8(*tag).expect("ASCII tag"))
+    }
+
+    /// OpenType features enabled by default for this language
+    ///
+    /// Explicit `ShapingOptions::features` take precedence.
+    pub fn default_features(&self) -> &'static [(&'static str, u32)] {
+        if LOCALIZED_FORMS.contains(&self.primary_language()) {
+            &[("locl", 1)]
+        } else {
+            &[]
+        }
+    }
+
+    /// Whether the language distinguishes dotted and dotless i, so that
+    /// `i` uppercases to `İ`
+    pub fn is_turkic(&self) -> bool {
+        TURKIC.contains(&self.primary_language())
+    }
+
+    /// Whether the language is written in `script`
+    fn writes(&self, script: Script) -> bool {
+        if let Some(subtag) = self.script_subtag() {
+            return script_matches(subtag, script);
+        }
+        let primary = self.primary_language().to_ascii_lowercase();
+        match LANGUAGE_SCRIPTS
+            .iter()
+            .find(|(language, _)| *language == primary)
+        {
+            Some((_, scripts)) => scripts.contains(&script),
+            None => script == Script::Latin,
+        }
+    }
+
+    /// Subtags after the primary language, up to variants and extensions
+    fn language_subtags(&self) -> impl Iterator<Item = &str> {
+        self.tag
+            .split(['-', '_'])
+            .skip(1)
+            .take_while(|subtag| subtag.len() > 1)
+    }
+}
+
+impl FromStr for Language {
+    type Err = ShapingError;
+
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        Language::parse(tag)
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.tag)
+    }
+}
+
+/// Resolve `options.language`: normalize a given tag, or infer one from the
+/// script and `locale` when unspecified
+pub(crate) fn resolve<'o>(
+    options: &'o ShapingOptions,
+    locale: Option<&Language>,
+) -> Result<Cow<'o, ShapingOptions>, ShapingError> {
+    let language = if options.language.is_unspecified() {
+        Language::infer(options.script, locale)
+    } else {
+        Language::parse(&options.language.tag)?
+    };
+    if language == options.language {
+        return Ok(Cow::Borrowed(options));
+    }
+    let mut options = options.clone();
+    options.language = language;
+    Ok(Cow::Owned(options))
+}
+
+/// Add the language's default features to `options`
+///
+/// For Turkic languages, `liga` is disabled over `fi` and `ffi` unless the
+/// font localizes forms for the language, since the ligature merges the dot
+/// of `i` into the `f`.
+pub(crate) fn apply_defaults<'o>(
+    options: Cow<'o, ShapingOptions>,
+    text: &str,
+    font_data: &[u8],
+) -> Cow<'o, ShapingOptions> {
+    let language = &options.language;
+    let defaults = language.default_features();
+    let ligature_breaks =
+        if language.is_turkic() && !has_localized_forms(options.as_ref(), font_data) {
+            dotted_i_ligatures(text)
+        } else {
+            Vec::new()
+        };
+    if defaults
+        .iter()
+        .all(|(feature, _)| options.features.contains_key(*feature))
+        && ligature_breaks.is_empty()
+    {
+        return options;
+    }
+
+    let mut options = options.into_owned();
+    for (feature, value) in defaults {
+        options
+            .features
+            .entry(feature.to_string())
+            .or_insert(*value);
+    }
+    // Ranges given by the caller come last so they still take precedence
+    let ranges = std::mem::take(&mut options.feature_ranges);
+    options.feature_ranges = ligature_breaks
+        .into_iter()
+        .map(|range| FeatureRange {
+            tag: "liga".to_string(),
+            value: 0,
+            range,
+        })
+        .chain(ranges)
+        .collect();
+    Cow::Owned(options)
+}
+
+/// Whether the font's GSUB enables `locl` for the options' language
+fn has_localized_forms(options: &ShapingOptions, font_data: &[u8]) -> bool {
+    let Some(language) = options.language.opentype_tag() else {
+        return false;
+    };
+    let script = Tag::from_bytes(u32::from_be_bytes(backend::script_tag(options.script)));
+    OpenTypeFont::parse(font_data.to_vec())
+        .ok()
+        .and_then(|font| font.get_gsub())
+        .is_some_and(|gsub| {
+            gsub.has_language_feature(
+                script,
+                Tag::new(language).expect("valid tag"),
+                Tag::new("locl").expect("valid tag"),
+            )
+        })
+}
+
+/// Byte ranges of `fi` sequences (including `ffi`) in `text`
+fn dotted_i_ligatures(text: &str) -> Vec<std::ops::Range<usize>> {
+    text.match_indices("fi")
+        .map(|(start, _)| {
+            let start = if text[..start].ends_with('f') {
+                start - 1
+            } else {
+                start
+            };
+            start
+                ..start
+                    + if text[start..].starts_with("ffi") {
+                        3
+                    } else {
+                        2
+                    }
+        })
+        .collect()
+}
+
+/// Whether an ISO 15924 script subtag denotes `script`
+fn script_matches(subtag: &str, script: Script) -> bool {
+    let subtag = subtag.to_ascii_lowercase();
+    let code = std::str::from_utf8(&backend::script_tag(script))
+        .expect("ASCII tag")
+        .to_string();
+    code == subtag
+        || match script {
+            Script::Han => matches!(subtag.as_str(), "hans" | "hant" | "jpan" | "kore"),
+            Script::Hiragana | Script::Katakana => matches!(subtag.as_str(), "jpan" | "hrkt"),
+            Script::Hangul => subtag == "kore",
+            _ => false,
+        }
+}
+
+fn is_alpha(subtag: &str, len: std::ops::RangeInclusive<usize>) -> bool {
+    len.contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+fn is_alphanumeric(subtag: &str, len: std::ops::RangeInclusive<usize>) -> bool {
+    len.contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+/// 5-8 alphanumerics, or a digit followed by 3 alphanumerics
+fn is_variant(subtag: &str) -> bool {
+    is_alphanumeric(subtag, 5..=8)
+        || (is_alphanumeric(subtag, 4..=4) && subtag.as_bytes()[0].is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(tag: &str) -> Language {
+        Language {
+            tag: tag.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_normalizes_case() {
+        assert_eq!(Language::parse("EN_us").unwrap().tag, "en-US");
+        assert_eq!(Language::parse("sr-latn-rs").unwrap().tag, "sr-Latn-RS");
+        assert_eq!(Language::parse("es-419").unwrap().tag, "es-419");
+        assert_eq!(
+            Language::parse("de-CH-1996-u-co-phonebk-x-Private")
+                .unwrap()
+                .tag,
+            "de-CH-1996-u-co-phonebk-x-private"
+        );
+        assert_eq!(Language::parse("zh-yue-HK").unwrap().tag, "zh-yue-HK");
+        assert_eq!(Language::parse("x-klingon").unwrap().tag, "x-klingon");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_tags() {
+        for tag in [
+            "",
+            "e",
+            "en-toolongsubtag",
+            "en--US",
+            "en-US-",
+            "en-u",
+            "en-x",
+            "en US",
+        ] {
+            assert_eq!(
+                Language::parse(tag),
+                Err(ShapingError::InvalidLanguage(tag.to_string())),
+                "{tag:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_subtags() {
+        let tag = Language::parse("sr-Latn-RS-u-ca-gregory").unwrap();
+        assert_eq!(tag.primary_language(), "sr");
+        assert_eq!(tag.script_subtag(), Some("Latn"));
+        assert_eq!(tag.region(), Some("RS"));
+
+        let tag = language("tr");
+        assert_eq!(tag.script_subtag(), None);
+        assert_eq!(tag.region(), None);
+        assert!(language("").is_unspecified());
+        assert!(language("und-Latn").is_unspecified());
+        assert!(!tag.is_unspecified());
+    }
+
+    #[test]
+    fn test_opentype_tag() {
+        assert_eq!(language("tr-TR").opentype_tag(), Some("TRK "));
+        assert_eq!(language("zh-Hant").opentype_tag(), Some("ZHT "));
+        assert_eq!(language("zh-TW").opentype_tag(), Some("ZHT "));
+        assert_eq!(language("zh-CN").opentype_tag(), Some("ZHS "));
+        assert_eq!(language("tlh").opentype_tag(), None);
+    }
+
+    #[test]
+    fn test_infer_prefers_locale_written_in_script() {
+        let turkish = language("tr-TR");
+        let serbian = language("sr-Latn");
+        let japanese = language("ja");
+
+        assert_eq!(Language::infer(Script::Latin, Some(&turkish)), turkish);
+        assert_eq!(Language::infer(Script::Cyrillic, Some(&turkish)).tag, "ru");
+        assert_eq!(Language::infer(Script::Latin, Some(&serbian)), serbian);
+        assert_eq!(Language::infer(Script::Cyrillic, Some(&serbian)).tag, "ru");
+        assert_eq!(Language::infer(Script::Han, Some(&japanese)), japanese);
+        assert_eq!(Language::infer(Script::Common, Some(&japanese)), japanese);
+        assert_eq!(Language::infer(Script::Arabic, None).tag, "ar");
+        assert!(Language::infer(Script::Common, None).is_unspecified());
+    }
+
+    #[test]
+    fn test_default_features() {
+        assert_eq!(language("tr").default_features(), &[("locl", 1)]);
+        assert!(language("en").default_features().is_empty());
+        assert!(language("az-Latn").is_turkic());
+        assert!(!language("en").is_turkic());
+    }
+
+    #[test]
+    fn test_dotted_i_ligatures() {
+        assert_eq!(dotted_i_ligatures("fit ofis"), vec![0..2, 5..7]);
+        assert_eq!(dotted_i_ligatures("affine"), vec![1..4]);
+        assert!(dotted_i_ligatures("fıstık").is_empty());
+    }
+}
@@ -3,20 +3,27 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+mod backend;
+mod caps;
+mod incremental;
+mod kashida;
+mod language;
+pub mod pipeline;
 pub mod shaper;
+mod transform;
 pub mod types;
 
 // Re-export main types for convenience
-pub use shaper::TextShaper;
-pub use types::{Language, Script, ShapingError, ShapingOptions};
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_placeholder() {
-        // Tests will be added during TDD
-        assert!(true);
-    }
-}
+pub use kashida::kashida_points;
+pub use pipeline::{
+    script_of, BidiStage, FallbackStage, ItemizeStage, PipelineOutput, PipelineStage, ShapeStage,
+    ShapingPipeline, TextAnalysis, TextAnnotation, TextItem,
+};
+pub use shaper::{ShapingCache, ShapingCacheConfig, ShapingCacheStats, TextShaper};
+pub use transform::TransformedText;
+pub use types::{
+    FeatureRange, FontVariantCaps, FormatCharacters, HyphenGlyph, KashidaGlyph, Language,
+    MatchContext, MissingGlyph, Script, ShapingError, ShapingOptions, ShapingOptionsBuilder,
+    TextEdit, TextTransform, FORMAT_PLACEHOLDER, SHAPING_OPTIONS_KEY_VERSION, SOFT_HYPHEN,
+    ZERO_WIDTH_SPACE,
+};
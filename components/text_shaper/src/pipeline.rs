@@ -0,0 +1,681 @@
+//! Shaping as a pipeline of pluggable stages
+//!
+//! A [`ShapingPipeline`] runs a paragraph through [`PipelineStage`]s that
+//! each refine a [`TextAnalysis`]. The default stages itemize the text into
+//! runs of one script, resolve their bidi levels, shape each run with the
+//! matched font, and reshape the clusters that font lacks with fallback
+//! fonts. Embedders add their own stages between these, e.g. to annotate
+//! misspelled words or to send emoji to a font of their choice, and the
+//! pipeline assembles the shaped items into a [`ShapedParagraph`].
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use font_types::types::{Direction, FontDescriptor, FontId, Script, ShapedParagraph, ShapedText};
+use unicode_bidi::{BidiInfo, Level};
+
+use crate::caps;
+use crate::shaper::{self, PendingRun, TextShaper};
+use crate::types::{MissingGlyph, ShapingError, ShapingOptions};
+
+/// One step of a [`ShapingPipeline`]
+///
+/// Stages run in pipeline order, each on the analysis the previous stages
+/// left, and are shared by every paragraph the pipeline shapes.
+pub trait PipelineStage: Send + Sync {
+    /// Name identifying the stage within a pipeline
+    fn name(&self) -> &str;
+
+    /// Refine the analysis of a paragraph
+    ///
+    /// # Errors
+    ///
+    /// Returns an error to abandon shaping the paragraph.
+    fn run(
+        &self,
+        shaper: &TextShaper<'_>,
+        analysis: &mut TextAnalysis<'_>,
+    ) -> Result<(), ShapingError>;
+}
+
+/// Range of a paragraph shaped as one run
+#[derive(Debug, Clone)]
+pub struct TextItem {
+    /// Byte range of the item in the paragraph text
+    pub range: Range<usize>,
+    /// Script of the item's characters
+    pub script: Script,
+    /// Direction the item is shaped in
+    pub direction: Direction,
+    /// Bidi embedding level; odd levels are right-to-left
+    pub bidi_level: u8,
+    /// Font to shape with, or `None` for the font matching the descriptor
+    pub font_id: Option<FontId>,
+    /// Shaped glyphs, with clusters as byte offsets into the paragraph
+    /// text, or `None` until a stage shapes the item
+    pub shaped: Option<ShapedText>,
+}
+
+/// Label a stage attached to a range of the text, e.g. a spelling error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextAnnotation {
+    /// Byte range of the annotated text
+    pub range: Range<usize>,
+    /// What the annotation means, chosen by the stage
+    pub label: String,
+}
+
+/// State of a paragraph passed from stage to stage
+#[derive(Debug, Clone)]
+pub struct TextAnalysis<'t> {
+    /// Paragraph text
+    pub text: &'t str,
+    /// Font descriptor the paragraph is shaped with
+    pub descriptor: FontDescriptor,
+    /// Options the paragraph is shaped with
+    pub options: ShapingOptions,
+    /// Font matching the descriptor
+    pub matched_font: FontId,
+    /// Fonts tried for clusters the matched font doesn't cover, in order
+    pub fallback_fonts: Vec<FontId>,
+    /// Items covering the text, in text order
+    pub items: Vec<TextItem>,
+    /// Annotations added by stages
+    pub annotations: Vec<TextAnnotation>,
+}
+
+impl TextAnalysis<'_> {
+    /// Split the item containing byte `offset` in two at `offset`
+    ///
+    /// Both halves keep the item's script, direction and font; a shaped
+    /// item loses its glyphs, for the shape stage to shape each half.
+    /// Returns whether an item was split, which it isn't when `offset` is
+    /// already an item boundary or not a character boundary.
+    pub fn split_at(&mut self, offset: usize) -> bool {
+        if !self.text.is_char_boundary(offset) {
+            return false;
+        }
+        let Some(index) = self
+            .items
+            .iter()
+            .position(|item| item.range.start < offset && offset < item.range.end)
+        else {
+            return false;
+        };
+        let item = &mut self.items[index];
+        item.shaped = None;
+        let mut tail = item.clone();
+        item.range.end = offset;
+        tail.range.start = offset;
+        self.items.insert(index + 1, tail);
+        true
+    }
+
+    /// Options to shape `item` with
+    fn item_options(&self, item: &TextItem) -> ShapingOptions {
+        ShapingOptions {
+            script: item.script,
+            direction: item.direction,
+            feature_ranges: caps::run_feature_ranges(
+                &self.options,
+                item.range.start,
+                item.range.len(),
+                |offset| offset,
+            ),
+            ..self.options.clone()
+        }
+    }
+}
+
+/// Result of running a paragraph through a [`ShapingPipeline`]
+#[derive(Debug, Clone, Default)]
+pub struct PipelineOutput {
+    /// Shaped runs, one per item, in visual order
+    pub paragraph: ShapedParagraph,
+    /// Clusters shaped as `.notdef`, in text order
+    pub missing: Vec<MissingGlyph>,
+    /// Annotations added by stages
+    pub annotations: Vec<TextAnnotation>,
+}
+
+/// Stage splitting the text into items of one script
+///
+/// Characters common to several scripts (spaces, digits, punctuation) and
+/// combining marks join the item around them; text without letters of a
+/// known script keeps `ShapingOptions::script`. It replaces any items of
+/// earlier stages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ItemizeStage;
+
+impl ItemizeStage {
+    /// Name of the stage
+    pub const NAME: &'static str = "itemize";
+}
+
+impl PipelineStage for ItemizeStage {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn run(
+        &self,
+        _shaper: &TextShaper<'_>,
+        analysis: &mut TextAnalysis<'_>,
+    ) -> Result<(), ShapingError> {
+        let base = base_item(analysis.text, &analysis.options);
+        let mut items: Vec<TextItem> = Vec::new();
+        for (offset, c) in analysis.text.char_indices() {
+            let Some(script) = script_of(c) else {
+                continue;
+            };
+            match items.last_mut() {
+                Some(last) if last.script == script => {}
+                Some(last) => {
+                    last.range.end = offset;
+                    items.push(TextItem {
+                        range: offset..analysis.text.len(),
+                        script,
+                        ..base.clone()
+                    });
+                }
+                // Leading common characters join the first item
+                None => items.push(TextItem {
+                    script,
+                    ..base.clone()
+                }),
+            }
+        }
+        if items.is_empty() {
+            items.push(base);
+        }
+        analysis.items = items;
+        Ok(())
+    }
+}
+
+/// Stage splitting horizontal items at bidi level changes
+///
+/// Levels are resolved with the Unicode Bidirectional Algorithm, taking
+/// `ShapingOptions::direction` as the paragraph direction. Items at odd
+/// levels are shaped right-to-left. Vertical text is left as it is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BidiStage;
+
+impl BidiStage {
+    /// Name of the stage
+    pub const NAME: &'static str = "bidi";
+}
+
+impl PipelineStage for BidiStage {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn run(
+        &self,
+        _shaper: &TextShaper<'_>,
+        analysis: &mut TextAnalysis<'_>,
+    ) -> Result<(), ShapingError> {
+        if analysis.options.direction.is_vertical() {
+            return Ok(());
+        }
+        let bidi = BidiInfo::new(analysis.text, Some(base_level(analysis.options.direction)));
+        let mut items = Vec::with_capacity(analysis.items.len());
+        for item in analysis.items.drain(..) {
+            let mut start = item.range.start;
+            while start < item.range.end {
+                let level = bidi.levels[start];
+                let end = (start..item.range.end)
+                    .find(|&offset| bidi.levels[offset] != level)
+                    .unwrap_or(item.range.end);
+                let direction = if level.is_rtl() {
+                    Direction::RightToLeft
+                } else {
+                    Direction::LeftToRight
+                };
+                let unchanged = start == item.range.start
+                    && end == item.range.end
+                    && direction == item.direction;
+                items.push(TextItem {
+                    range: start..end,
+                    direction,
+                    bidi_level: level.number(),
+                    // Keep glyphs only when the item is shaped as before
+                    shaped: item.shaped.clone().filter(|_| unchanged),
+                    ..item.clone()
+                });
+                start = end;
+            }
+        }
+        analysis.items = items;
+        Ok(())
+    }
+}
+
+/// Stage shaping each item not shaped yet
+///
+/// Items are shaped with their own font, or else the matched font, in
+/// their script and direction.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShapeStage;
+
+impl ShapeStage {
+    /// Name of the stage
+    pub const NAME: &'static str = "shape";
+}
+
+impl PipelineStage for ShapeStage {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn run(
+        &self,
+        shaper: &TextShaper<'_>,
+        analysis: &mut TextAnalysis<'_>,
+    ) -> Result<(), ShapingError> {
+        for index in 0..analysis.items.len() {
+            let item = &analysis.items[index];
+            if item.shaped.is_some() {
+                continue;
+            }
+            let font_id = item.font_id.unwrap_or(analysis.matched_font);
+            let shaped = shape_item(shaper, analysis, item, font_id)?;
+            let item = &mut analysis.items[index];
+            item.font_id = Some(font_id);
+            item.shaped = Some(shaped);
+        }
+        Ok(())
+    }
+}
+
+/// Stage reshaping clusters shaped as `.notdef` with fallback fonts
+///
+/// Like [`TextShaper::shape_paragraph`], each such cluster goes to the
+/// first font covering its first character: a font matching another family
+/// of the descriptor, then any loaded font in ID order. Items are split
+/// where their font changes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FallbackStage;
+
+impl FallbackStage {
+    /// Name of the stage
+    pub const NAME: &'static str = "fallback";
+}
+
+impl PipelineStage for FallbackStage {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn run(
+        &self,
+        shaper: &TextShaper<'_>,
+        analysis: &mut TextAnalysis<'_>,
+    ) -> Result<(), ShapingError> {
+        let fallbacks = shaper.fallback_fonts(
+            &analysis.descriptor,
+            analysis.matched_font,
+            analysis.options.match_context,
+        );
+        let registry = shaper.registry();
+        let mut items = Vec::with_capacity(analysis.items.len());
+        for item in std::mem::take(&mut analysis.items) {
+            let (Some(font_id), Some(shaped)) = (item.font_id, &item.shaped) else {
+                items.push(item);
+                continue;
+            };
+            if shaped.glyphs.iter().all(|glyph| glyph.glyph_id.id != 0) {
+                items.push(item);
+                continue;
+            }
+
+            // Segments are found in the item's own text
+            let mut relative = shaped.clone();
+            for glyph in &mut relative.glyphs {
+                glyph.cluster -= item.range.start as u32;
+            }
+            let segments = shaper::font_segments(
+                &relative,
+                &analysis.text[item.range.clone()],
+                font_id,
+                |c| {
+                    fallbacks
+                        .iter()
+                        .copied()
+                        .find(|&fallback| registry.font_covers(fallback, c))
+                },
+            );
+            if let [(only, _)] = segments[..] {
+                if only == font_id {
+                    items.push(item);
+                    continue;
+                }
+            }
+            for (font, range) in segments {
+                let mut segment = TextItem {
+                    range: item.range.start + range.start..item.range.start + range.end,
+                    font_id: Some(font),
+                    shaped: None,
+                    ..item.clone()
+                };
+                segment.shaped = Some(shape_item(shaper, analysis, &segment, font)?);
+                items.push(segment);
+            }
+        }
+        analysis.items = items;
+        analysis.fallback_fonts = fallbacks;
+        Ok(())
+    }
+}
+
+/// Ordered stages shaping a paragraph
+///
+/// [`ShapingPipeline::new`] has the default stages, named after their
+/// `NAME` constants: [`ItemizeStage`], [`BidiStage`], [`ShapeStage`] and
+/// [`FallbackStage`]. Stages are shared, so cloning a pipeline is cheap.
+#[derive(Clone)]
+pub struct ShapingPipeline {
+    stages: Vec<Arc<dyn PipelineStage>>,
+}
+
+impl Default for ShapingPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ShapingPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShapingPipeline")
+            .field("stages", &self.stage_names())
+            .finish()
+    }
+}
+
+impl ShapingPipeline {
+    /// Create a pipeline with the default stages
+    pub fn new() -> Self {
+        let mut pipeline = Self::empty();
+        pipeline.push(ItemizeStage);
+        pipeline.push(BidiStage);
+        pipeline.push(ShapeStage);
+        pipeline.push(FallbackStage);
+        pipeline
+    }
+
+    /// Create a pipeline without stages
+    ///
+    /// A paragraph then stays one item in `ShapingOptions::script` and
+    /// direction, which some stage must shape.
+    pub fn empty() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Names of the stages, in the order they run
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    /// Add a stage after all others
+    pub fn push(&mut self, stage: impl PipelineStage + 'static) {
+        self.stages.push(Arc::new(stage));
+    }
+
+    /// Add a stage just before the stage named `name`
+    ///
+    /// Returns whether the pipeline has such a stage; if not, it is
+    /// unchanged.
+    pub fn insert_before(&mut self, name: &str, stage: impl PipelineStage + 'static) -> bool {
+        let Some(index) = self.position(name) else {
+            return false;
+        };
+        self.stages.insert(index, Arc::new(stage));
+        true
+    }
+
+    /// Add a stage just after the stage named `name`
+    ///
+    /// Returns whether the pipeline has such a stage; if not, it is
+    /// unchanged.
+    pub fn insert_after(&mut self, name: &str, stage: impl PipelineStage + 'static) -> bool {
+        let Some(index) = self.position(name) else {
+            return false;
+        };
+        self.stages.insert(index + 1, Arc::new(stage));
+        true
+    }
+
+    /// Replace the stage named `name`
+    ///
+    /// Returns whether the pipeline has such a stage; if not, it is
+    /// unchanged.
+    pub fn replace(&mut self, name: &str, stage: impl PipelineStage + 'static) -> bool {
+        let Some(index) = self.position(name) else {
+            return false;
+        };
+        self.stages[index] = Arc::new(stage);
+        true
+    }
+
+    /// Remove the stage named `name`, returning whether there was one
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(index) = self.position(name) else {
+            return false;
+        };
+        self.stages.remove(index);
+        true
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|stage| stage.name() == name)
+    }
+
+    /// Shape a paragraph by running it through the stages
+    ///
+    /// The paragraph starts as one item covering the text. After the last
+    /// stage, the items are placed one after another in visual order (by
+    /// bidi level for horizontal text), each as a run with its script,
+    /// direction, font and metrics.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShapingError::FontNotFound`] when no font matches
+    /// `descriptor`, [`ShapingError::InvalidText`] when an item is left
+    /// unshaped, or the first error of a stage.
+    pub fn run(
+        &self,
+        shaper: &TextShaper<'_>,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+    ) -> Result<PipelineOutput, ShapingError> {
+        if text.is_empty() {
+            return Ok(PipelineOutput::default());
+        }
+
+        let matched_font = shaper
+            .registry()
+            .match_font_in_context(descriptor, options.match_context)
+            .ok_or(ShapingError::FontNotFound)?;
+        let mut options = options.clone();
+        options.synthesize_small_caps &= descriptor.synthesis.small_caps;
+        let mut analysis = TextAnalysis {
+            text,
+            descriptor: descriptor.clone(),
+            items: vec![base_item(text, &options)],
+            options,
+            matched_font,
+            fallback_fonts: Vec::new(),
+            annotations: Vec::new(),
+        };
+        for stage in &self.stages {
+            stage.run(shaper, &mut analysis)?;
+        }
+
+        let vertical = analysis.options.direction.is_vertical();
+        let mut runs = Vec::with_capacity(analysis.items.len());
+        for item in &analysis.items {
+            let (Some(font_id), Some(shaped)) = (item.font_id, &item.shaped) else {
+                return Err(ShapingError::InvalidText(format!(
+                    "no stage shaped bytes {:?}",
+                    item.range
+                )));
+            };
+            runs.push(PendingRun {
+                text_range: item.range.clone(),
+                font_id,
+                direction: item.direction,
+                script: item.script,
+                shaped: shaped.clone(),
+            });
+        }
+        let order: Vec<usize> = if vertical {
+            let mut order: Vec<usize> = (0..runs.len()).collect();
+            if analysis.options.direction == Direction::BottomToTop {
+                order.reverse();
+            }
+            order
+        } else {
+            let levels: Vec<Level> = analysis
+                .items
+                .iter()
+                .map(|item| Level::new(item.bidi_level).unwrap_or_else(|_| Level::ltr()))
+                .collect();
+            BidiInfo::reorder_visual(&levels)
+        };
+        let mut runs: Vec<Option<PendingRun>> = runs.into_iter().map(Some).collect();
+        let runs = order.into_iter().filter_map(|index| runs[index].take());
+
+        let mut paragraph = shaper::assemble_runs(runs.collect(), vertical);
+        shaper.set_run_metrics(&mut paragraph, descriptor.size, &analysis.options);
+        let attempted: Vec<FontId> = std::iter::once(matched_font)
+            .chain(analysis.fallback_fonts.iter().copied())
+            .collect();
+        let missing = shaper::missing_glyphs(text, paragraph.glyphs(), &attempted);
+        Ok(PipelineOutput {
+            paragraph,
+            missing,
+            annotations: analysis.annotations,
+        })
+    }
+}
+
+/// Item covering all of `text`, in the script and direction of `options`
+fn base_item(text: &str, options: &ShapingOptions) -> TextItem {
+    TextItem {
+        range: 0..text.len(),
+        script: options.script,
+        direction: options.direction,
+        bidi_level: base_level(options.direction).number(),
+        font_id: None,
+        shaped: None,
+    }
+}
+
+fn base_level(direction: Direction) -> Level {
+    if direction == Direction::RightToLeft {
+        Level::rtl()
+    } else {
+        Level::ltr()
+    }
+}
+
+/// Shape `item` with `font_id`, clusters being byte offsets into the text
+fn shape_item(
+    shaper: &TextShaper<'_>,
+    analysis: &TextAnalysis<'_>,
+    item: &TextItem,
+    font_id: FontId,
+) -> Result<ShapedText, ShapingError> {
+    let options = analysis.item_options(item);
+    let mut shaped = shaper.shape_text(
+        &analysis.text[item.range.clone()],
+        font_id,
+        analysis.descriptor.size,
+        &options,
+    )?;
+    for glyph in &mut shaped.glyphs {
+        glyph.cluster += item.range.start as u32;
+    }
+    Ok(shaped)
+}
+
+/// Script of a letter, or `None` for characters common to several scripts
+/// (spaces, digits, punctuation, symbols) and combining marks
+pub fn script_of(c: char) -> Option<Script> {
+    let script = match c {
+        'A'..='Z'
+        | 'a'..='z'
+        | '\u{AA}'
+        | '\u{BA}'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{24F}'
+        | '\u{1E00}'..='\u{1EFF}'
+        | '\u{2C60}'..='\u{2C7F}'
+        | '\u{A720}'..='\u{A7FF}'
+        | '\u{FF21}'..='\u{FF3A}'
+        | '\u{FF41}'..='\u{FF5A}' => Script::Latin,
+        '\u{370}'..='\u{373}'
+        | '\u{376}'..='\u{377}'
+        | '\u{37B}'..='\u{37D}'
+        | '\u{37F}'
+        | '\u{386}'
+        | '\u{388}'..='\u{3FF}'
+        | '\u{1F00}'..='\u{1FFF}' => Script::Greek,
+        '\u{400}'..='\u{482}' | '\u{48A}'..='\u{52F}' | '\u{A640}'..='\u{A66E}' => Script::Cyrillic,
+        '\u{5D0}'..='\u{5F4}' | '\u{FB1D}'..='\u{FB4F}' => Script::Hebrew,
+        '\u{620}'..='\u{63F}'
+        | '\u{641}'..='\u{64A}'
+        | '\u{660}'..='\u{669}'
+        | '\u{66E}'..='\u{6D5}'
+        | '\u{6EE}'..='\u{6FF}'
+        | '\u{750}'..='\u{77F}'
+        | '\u{8A0}'..='\u{8C9}'
+        | '\u{FB50}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFC}' => Script::Arabic,
+        '\u{1100}'..='\u{11FF}' | '\u{3131}'..='\u{318E}' | '\u{AC00}'..='\u{D7A3}' => {
+            Script::Hangul
+        }
+        '\u{3041}'..='\u{3096}' | '\u{309D}'..='\u{309F}' => Script::Hiragana,
+        '\u{30A1}'..='\u{30FA}'
+        | '\u{30FD}'..='\u{30FF}'
+        | '\u{31F0}'..='\u{31FF}'
+        | '\u{FF66}'..='\u{FF6F}'
+        | '\u{FF71}'..='\u{FF9D}' => Script::Katakana,
+        '\u{2E80}'..='\u{2FD5}'
+        | '\u{3005}'
+        | '\u{3007}'
+        | '\u{3021}'..='\u{3029}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{3134F}' => Script::Han,
+        _ => return None,
+    };
+    Some(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_of_letters_and_common_characters() {
+        assert_eq!(script_of('a'), Some(Script::Latin));
+        assert_eq!(script_of('é'), Some(Script::Latin));
+        assert_eq!(script_of('Ж'), Some(Script::Cyrillic));
+        assert_eq!(script_of('λ'), Some(Script::Greek));
+        assert_eq!(script_of('א'), Some(Script::Hebrew));
+        assert_eq!(script_of('ب'), Some(Script::Arabic));
+        assert_eq!(script_of('한'), Some(Script::Hangul));
+        assert_eq!(script_of('ひ'), Some(Script::Hiragana));
+        assert_eq!(script_of('カ'), Some(Script::Katakana));
+        assert_eq!(script_of('字'), Some(Script::Han));
+        for common in [' ', '1', ',', '×', '\u{301}', 'ー', '😀'] {
+            assert_eq!(script_of(common), None, "{common:?}");
+        }
+    }
+}
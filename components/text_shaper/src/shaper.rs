@@ -1,18 +1,32 @@
-//! Text shaper implementation using Harfbuzz
+//! Text shaper implementation using HarfBuzz or rustybuzz
 
-use std::cell::RefCell;
+use std::borrow::Cow;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
-use std::str::FromStr;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
-use crate::types::{Script, ShapingError, ShapingOptions};
-use font_registry::{FontDescriptor as RegistryFontDescriptor, FontRegistry};
+use crate::backend;
+use crate::backend::simple::SimpleFace;
+use crate::caps;
+use crate::incremental;
+use crate::language;
+use crate::transform::{self, TransformedText};
+use crate::types::{
+    FontVariantCaps, FormatCharacters, HyphenGlyph, KashidaGlyph, Language, MissingGlyph,
+    ShapingError, ShapingOptions, TextEdit, TextTransform,
+};
+use font_parser::{OpenTypeFont, Tag};
+use font_registry::FontRegistry;
+use font_types::cache::{Displaced, EvictionPolicy, PolicyCache};
+use font_types::memory::{MemoryPressureLevel, MemoryReport, MemoryReporter, MemoryTrimmer};
 use font_types::types::{
-    Direction, FontDescriptor, FontId, GlyphId, Point, PositionedGlyph, ShapedText, Vector,
+    Baseline, Direction, FontDescriptor, FontId, GlyphId, MatchContext, PackedShapedText, Point,
+    PositionedGlyph, RunMetrics, Script, ShapedParagraph, ShapedRun, ShapedText, Vector,
 };
-use harfbuzz_rs::{Face, Font, Tag, UnicodeBuffer};
-use lru::LruCache;
+use rustc_hash::FxHashMap;
 
 /// Default shaping cache size
 const DEFAULT_SHAPING_CACHE_SIZE: usize = 1000;
@@ -24,6 +38,14 @@ pub struct ShapingCacheConfig {
     pub max_entries: usize,
     /// Enable statistics tracking
     pub enable_statistics: bool,
+    /// Which result a full cache evicts
+    pub eviction_policy: EvictionPolicy,
+    /// Shape plain Latin runs without the full shaping engine (see
+    /// [`ShapingCache::set_simple_shaping`])
+    pub simple_shaping: bool,
+    /// Keep each cached text to rule out hash collisions (see
+    /// [`ShapingCache::set_verify_keys`])
+    pub verify_keys: bool,
 }
 
 impl Default for ShapingCacheConfig {
@@ -31,39 +53,55 @@ impl Default for ShapingCacheConfig {
         Self {
             max_entries: DEFAULT_SHAPING_CACHE_SIZE,
             enable_statistics: true,
+            eviction_policy: EvictionPolicy::Lru,
+            simple_shaping: true,
+            verify_keys: false,
         }
     }
 }
 
 /// Cache key for shaped text
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// The text is keyed by a 128-bit hash of its bytes and its length, so
+/// lookups don't copy it, and the options by a 128-bit hash of their
+/// [`ShapingOptions::cache_key`] encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct ShapingCacheKey {
-    /// Text to shape
-    text: String,
+    /// Hash of the text to shape
+    text_hash: u128,
+    /// Length of the text in bytes
+    text_len: usize,
     /// Font ID
     font_id: FontId,
-    /// Size in fixed point (size * 10 for precision)
-    size_fixed: u32,
-    /// Hash of shaping options
-    options_hash: u64,
+    /// Bits of the size, so sizes that differ at all get their own entry
+    size_bits: u32,
+    /// Hash of the shaping options' cache key
+    options_hash: u128,
 }
 
 impl ShapingCacheKey {
     fn new(text: &str, font_id: FontId, size: f32, options: &ShapingOptions) -> Self {
-        // Hash the options for cache key
-        let mut hasher = DefaultHasher::new();
-        options.hash(&mut hasher);
-        let options_hash = hasher.finish();
-
         Self {
-            text: text.to_string(),
+            text_hash: hash128(text.as_bytes()),
+            text_len: text.len(),
             font_id,
-            size_fixed: (size * 10.0) as u32,
-            options_hash,
+            size_bits: size.to_bits(),
+            options_hash: hash128(&options.cache_key()),
         }
     }
 }
 
+/// 128-bit hash of `bytes`, from two differently seeded 64-bit hashes
+fn hash128(bytes: &[u8]) -> u128 {
+    let half = |seed: u64| {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(seed);
+        hasher.write(bytes);
+        hasher.finish()
+    };
+    u128::from(half(0)) << 64 | u128::from(half(1))
+}
+
 /// Shaping cache statistics
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ShapingCacheStats {
@@ -81,10 +119,47 @@ pub struct ShapingCacheStats {
     pub hit_rate: f64,
 }
 
-/// Shaping cache
-struct ShapingCache {
-    cache: LruCache<ShapingCacheKey, ShapedText>,
+/// Entries per shard above which a cache is split into several shards
+const SHARD_ENTRIES: usize = 64;
+
+/// Maximum number of cache shards
+const MAX_SHARDS: usize = 16;
+
+/// Shaping cache, shareable between shapers on different threads
+///
+/// Entries are spread over independently locked shards by key hash, so
+/// layout workers rarely contend. Each shard evicts by the cache's
+/// [`EvictionPolicy`] when full; small caches use a single shard and so
+/// behave as one cache with that policy.
+pub struct ShapingCache {
+    shards: Vec<Mutex<CacheShard>>,
+    /// Access counter giving entries a global recency order for trimming
+    clock: AtomicU64,
+    /// Whether eligible runs take the simple shaping path
+    simple_shaping: AtomicBool,
+    /// Whether entries keep their text to check hits against
+    verify_keys: AtomicBool,
+    /// Fonts prepared for the simple shaping path; `None` for fonts it
+    /// does not support. Clearing the cache keeps them, as they describe
+    /// the font rather than any shaped text
+    simple_faces: Mutex<FxHashMap<FontId, Option<Arc<SimpleFace>>>>,
+}
+
+/// Cached shaping result
+struct CacheEntry {
+    shaped: Arc<PackedShapedText>,
+    /// Clock value of the entry's last use
+    last_used: u64,
+    /// Text the result was shaped from, kept when keys are verified
+    text: Option<Box<str>>,
+}
+
+/// One independently locked part of the shaping cache
+struct CacheShard {
+    cache: PolicyCache<ShapingCacheKey, CacheEntry>,
     stats: CacheStatistics,
+    /// Heap and inline bytes of the cached entries
+    memory_bytes: usize,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -94,60 +169,260 @@ struct CacheStatistics {
     evictions: u64,
 }
 
+impl CacheShard {
+    /// Bytes attributed to one cached entry
+    fn entry_bytes(entry: &CacheEntry) -> usize {
+        std::mem::size_of::<ShapingCacheKey>()
+            + std::mem::size_of::<CacheEntry>()
+            + entry.text.as_ref().map_or(0, |text| text.len())
+            + std::mem::size_of::<PackedShapedText>()
+            + entry.shaped.heap_bytes()
+    }
+
+    /// Evict the entry the policy picks
+    fn pop(&mut self) {
+        if let Some((_, entry)) = self.cache.pop() {
+            self.memory_bytes -= Self::entry_bytes(&entry);
+            self.stats.evictions += 1;
+        }
+    }
+}
+
 impl ShapingCache {
-    fn new(max_size: usize) -> Self {
+    /// Create an LRU cache holding up to `max_entries` shaping results
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_entries` is zero.
+    pub fn new(max_entries: usize) -> Self {
+        Self::with_policy(max_entries, EvictionPolicy::Lru)
+    }
+
+    /// Create a cache holding up to `max_entries` shaping results, evicting
+    /// by `policy`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_entries` is zero.
+    pub fn with_policy(max_entries: usize, policy: EvictionPolicy) -> Self {
+        assert!(max_entries > 0, "shaping cache needs at least one entry");
+        let shard_count = (max_entries / SHARD_ENTRIES).clamp(1, MAX_SHARDS);
+        let shards = (0..shard_count)
+            .map(|index| {
+                // Spread the remainder so the capacities add up to max_entries
+                let capacity =
+                    max_entries / shard_count + usize::from(index < max_entries % shard_count);
+                Mutex::new(CacheShard {
+                    cache: PolicyCache::new(NonZeroUsize::new(capacity).unwrap(), policy),
+                    stats: CacheStatistics::default(),
+                    memory_bytes: 0,
+                })
+            })
+            .collect();
         Self {
-            cache: LruCache::new(NonZeroUsize::new(max_size).unwrap()),
-            stats: CacheStatistics::default(),
+            shards,
+            clock: AtomicU64::new(0),
+            simple_shaping: AtomicBool::new(true),
+            verify_keys: AtomicBool::new(false),
+            simple_faces: Mutex::new(FxHashMap::default()),
         }
     }
 
-    fn get(&mut self, key: &ShapingCacheKey) -> Option<&ShapedText> {
-        if let Some(shaped) = self.cache.get(key) {
-            self.stats.hits += 1;
-            Some(shaped)
-        } else {
-            self.stats.misses += 1;
-            None
-        }
+    /// Enable or disable the simple shaping path (on by default)
+    ///
+    /// Left-to-right Latin runs of printable ASCII are then shaped from the
+    /// font's cmap, advances and pair kerning directly, skipping the full
+    /// shaping engine. Runs whose glyphs another enabled OpenType feature
+    /// could act on (ligatures, contextual alternates, marks, ...) still go
+    /// through the engine, so the output is the same either way.
+    pub fn set_simple_shaping(&self, enabled: bool) {
+        self.simple_shaping.store(enabled, Ordering::Relaxed);
     }
 
-    fn insert(&mut self, key: ShapingCacheKey, value: ShapedText) {
-        if self.cache.push(key, value).is_some() {
-            self.stats.evictions += 1;
+    /// Whether the simple shaping path is enabled
+    pub fn simple_shaping(&self) -> bool {
+        self.simple_shaping.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable key verification (off by default)
+    ///
+    /// Results are keyed by a 128-bit hash of their text, so lookups never
+    /// copy it. With verification on, results cached from then on also keep
+    /// their text, and a hit whose text differs counts as a miss.
+    pub fn set_verify_keys(&self, enabled: bool) {
+        self.verify_keys.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether cached results are checked against their text
+    pub fn verify_keys(&self) -> bool {
+        self.verify_keys.load(Ordering::Relaxed)
+    }
+
+    /// The font prepared for the simple shaping path, if enabled and supported
+    fn simple_face(&self, font_id: FontId, font_data: &[u8]) -> Option<Arc<SimpleFace>> {
+        if !self.simple_shaping() {
+            return None;
         }
+        let mut faces = self
+            .simple_faces
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        faces
+            .entry(font_id)
+            .or_insert_with(|| SimpleFace::new(font_data).map(Arc::new))
+            .clone()
     }
 
-    fn clear(&mut self) {
-        self.cache.clear();
+    /// Lock the shard holding `key`
+    fn shard(&self, key: &ShapingCacheKey) -> MutexGuard<'_, CacheShard> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let index = hasher.finish() as usize % self.shards.len();
+        lock(&self.shards[index])
+    }
+
+    /// Next clock value; taken with the shard locked so each shard's
+    /// recency order matches the clock
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
     }
 
-    fn get_stats(&self) -> ShapingCacheStats {
-        let hit_rate = if self.stats.hits + self.stats.misses > 0 {
-            self.stats.hits as f64 / (self.stats.hits + self.stats.misses) as f64
+    fn get(&self, key: &ShapingCacheKey, text: &str) -> Option<Arc<PackedShapedText>> {
+        let mut shard = self.shard(key);
+        let now = self.tick();
+        let shaped = shard
+            .cache
+            .get_mut(key)
+            .filter(|entry| entry.text.as_deref().is_none_or(|cached| cached == text))
+            .map(|entry| {
+                entry.last_used = now;
+                Arc::clone(&entry.shaped)
+            });
+        if shaped.is_some() {
+            shard.stats.hits += 1;
         } else {
-            0.0
+            shard.stats.misses += 1;
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(cache = "shaping", hit = shaped.is_some());
+        shaped
+    }
+
+    fn insert(&self, key: ShapingCacheKey, text: &str, shaped: Arc<PackedShapedText>) {
+        let text = self.verify_keys().then(|| text.into());
+        let mut shard = self.shard(&key);
+        let entry = CacheEntry {
+            shaped,
+            last_used: self.tick(),
+            text,
         };
+        let bytes = CacheShard::entry_bytes(&entry);
+        shard.memory_bytes += bytes;
+        if let Some(displaced) = shard.cache.push(key, entry, bytes) {
+            if matches!(displaced, Displaced::Evicted(..)) {
+                shard.stats.evictions += 1;
+            }
+            shard.memory_bytes -= CacheShard::entry_bytes(&displaced.into_value());
+        }
+    }
+
+    /// Evict a share of cached results across all shards, returning the
+    /// bytes freed
+    ///
+    /// Victims are taken in each shard's policy order; between shards, the
+    /// one whose next victim was used longest ago goes first, so an LRU
+    /// cache trims its globally least recently used results.
+    pub fn trim_memory(&self, level: MemoryPressureLevel) -> usize {
+        let mut shards: Vec<_> = self.shards.iter().map(lock).collect();
+        let before: usize = shards.iter().map(|shard| shard.memory_bytes).sum();
+        let len: usize = shards.iter().map(|shard| shard.cache.len()).sum();
+        for _ in level.retained(len)..len {
+            let oldest = shards
+                .iter()
+                .enumerate()
+                .filter_map(|(index, shard)| {
+                    let (_, entry) = shard.cache.peek_next()?;
+                    Some((entry.last_used, index))
+                })
+                .min();
+            let Some((_, index)) = oldest else {
+                break;
+            };
+            shards[index].pop();
+        }
+        before - shards.iter().map(|shard| shard.memory_bytes).sum::<usize>()
+    }
+
+    /// Eviction policy of the cache
+    pub fn policy(&self) -> EvictionPolicy {
+        lock(&self.shards[0]).cache.policy()
+    }
 
-        ShapingCacheStats {
-            hits: self.stats.hits,
-            misses: self.stats.misses,
-            evictions: self.stats.evictions,
-            current_size: self.cache.len(),
-            max_size: self.cache.cap().get(),
-            hit_rate,
+    /// Remove all cached results
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            let mut shard = lock(shard);
+            shard.cache.clear();
+            shard.memory_bytes = 0;
         }
     }
+
+    /// Statistics summed over all shards
+    pub fn stats(&self) -> ShapingCacheStats {
+        let mut stats = ShapingCacheStats::default();
+        for shard in &self.shards {
+            let shard = lock(shard);
+            stats.hits += shard.stats.hits;
+            stats.misses += shard.stats.misses;
+            stats.evictions += shard.stats.evictions;
+            stats.current_size += shard.cache.len();
+            stats.max_size += shard.cache.cap().get();
+        }
+        if stats.hits + stats.misses > 0 {
+            stats.hit_rate = stats.hits as f64 / (stats.hits + stats.misses) as f64;
+        }
+        stats
+    }
+
+    /// Shaped text held by the cache
+    pub fn memory_report(&self) -> MemoryReport {
+        self.shards
+            .iter()
+            .fold(MemoryReport::default(), |report, shard| {
+                let shard = lock(shard);
+                MemoryReport {
+                    bytes: report.bytes + shard.memory_bytes,
+                    entries: report.entries + shard.cache.len(),
+                }
+            })
+    }
+}
+
+impl std::fmt::Debug for ShapingCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShapingCache")
+            .field("shards", &self.shards.len())
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// Lock a shard; a panic while holding it cannot leave an entry half
+/// written, so poisoning is ignored
+fn lock(shard: &Mutex<CacheShard>) -> MutexGuard<'_, CacheShard> {
+    shard.lock().unwrap_or_else(PoisonError::into_inner)
 }
 
 /// Text shaping engine
 pub struct TextShaper<'a> {
     /// Reference to font registry
     registry: &'a FontRegistry,
-    /// Shaping cache with interior mutability
-    cache: Option<RefCell<ShapingCache>>,
+    /// Shaping cache, possibly shared with other shapers
+    cache: Option<Arc<ShapingCache>>,
     /// Cache configuration
     config: ShapingCacheConfig,
+    /// Locale used to infer unspecified languages
+    locale: Option<Language>,
 }
 
 impl<'a> TextShaper<'a> {
@@ -176,7 +451,10 @@ impl<'a> TextShaper<'a> {
     /// New TextShaper instance
     pub fn with_config(registry: &'a FontRegistry, config: ShapingCacheConfig) -> Self {
         let cache = if config.enable_statistics {
-            Some(RefCell::new(ShapingCache::new(config.max_entries)))
+            let cache = ShapingCache::with_policy(config.max_entries, config.eviction_policy);
+            cache.set_simple_shaping(config.simple_shaping);
+            cache.set_verify_keys(config.verify_keys);
+            Some(Arc::new(cache))
         } else {
             None
         };
@@ -185,6 +463,52 @@ impl<'a> TextShaper<'a> {
             registry,
             cache,
             config,
+            locale: None,
+        }
+    }
+
+    /// Font registry the shaper takes fonts from
+    pub fn registry(&self) -> &'a FontRegistry {
+        self.registry
+    }
+
+    /// Set the locale used when `ShapingOptions::language` is unspecified
+    ///
+    /// Text in a script the locale's language is written in is shaped in
+    /// that language; other text uses the script's most common language
+    /// (see [`Language::infer`]).
+    pub fn set_locale(&mut self, locale: Option<Language>) {
+        self.locale = locale;
+    }
+
+    /// Locale used when `ShapingOptions::language` is unspecified
+    pub fn locale(&self) -> Option<&Language> {
+        self.locale.as_ref()
+    }
+
+    /// Create new text shaper using an existing, possibly shared cache
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - Font registry containing loaded fonts
+    /// * `cache` - Shaping cache, typically from [`TextShaper::shared_cache`]
+    ///
+    /// # Returns
+    ///
+    /// New TextShaper instance
+    pub fn with_shared_cache(registry: &'a FontRegistry, cache: Arc<ShapingCache>) -> Self {
+        let config = ShapingCacheConfig {
+            max_entries: cache.stats().max_size,
+            enable_statistics: true,
+            eviction_policy: cache.policy(),
+            simple_shaping: cache.simple_shaping(),
+            verify_keys: cache.verify_keys(),
+        };
+        Self {
+            registry,
+            cache: Some(cache),
+            config,
+            locale: None,
         }
     }
 
@@ -194,13 +518,27 @@ impl<'a> TextShaper<'a> {
     ///
     /// Cache statistics if caching is enabled, None otherwise
     pub fn cache_stats(&self) -> Option<ShapingCacheStats> {
-        self.cache.as_ref().map(|c| c.borrow().get_stats())
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Cache configuration, read back from the cache for shapers created
+    /// with [`TextShaper::with_shared_cache`]
+    pub fn cache_config(&self) -> &ShapingCacheConfig {
+        &self.config
+    }
+
+    /// Cache shared with other shapers, if caching is enabled
+    ///
+    /// Pass it to [`TextShaper::with_shared_cache`] to reuse shaping results
+    /// across shapers, e.g. one per layout worker thread.
+    pub fn shared_cache(&self) -> Option<Arc<ShapingCache>> {
+        self.cache.clone()
     }
 
     /// Clear the shaping cache
     pub fn clear_cache(&self) {
         if let Some(cache) = &self.cache {
-            cache.borrow_mut().clear();
+            cache.clear();
         }
     }
 
@@ -215,7 +553,8 @@ impl<'a> TextShaper<'a> {
     ///
     /// # Returns
     ///
-    /// Result containing shaped text or error
+    /// Result containing shaped text or error; `ShapingError::InvalidLanguage`
+    /// if `options.language` is not a well-formed BCP 47 tag
     pub fn shape_text(
         &self,
         text: &str,
@@ -223,24 +562,159 @@ impl<'a> TextShaper<'a> {
         size: f32,
         options: &ShapingOptions,
     ) -> Result<ShapedText, ShapingError> {
+        self.shape_text_packed(text, font_id, size, options)
+            .map(|packed| packed.unpack())
+    }
+
+    /// Shape text with specific font, returning the compact cached result
+    ///
+    /// Like [`TextShaper::shape_text`], but a cache hit only takes another
+    /// reference to the cached glyphs instead of copying them, which suits
+    /// long paragraphs that are laid out repeatedly.
+    ///
+    /// # Returns
+    ///
+    /// Result containing shaped text or error
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "text_shaper::shape_text",
+            level = "debug",
+            skip_all,
+            fields(font_id, size, text_len = text.len())
+        )
+    )]
+    pub fn shape_text_packed(
+        &self,
+        text: &str,
+        font_id: FontId,
+        size: f32,
+        options: &ShapingOptions,
+    ) -> Result<Arc<PackedShapedText>, ShapingError> {
         // Validate input
         if text.is_empty() {
-            return Ok(ShapedText {
+            return Ok(Arc::new(PackedShapedText::pack(&ShapedText {
                 glyphs: Vec::new(),
                 width: 0.0,
                 height: 0.0,
                 baseline: 0.0,
-            });
+            })));
         }
 
+        // Resolve the language first so equivalent tags share cache entries
+        let options = language::resolve(options, self.locale.as_ref())?;
+
         // Check cache first
-        if let Some(cache) = &self.cache {
-            let cache_key = ShapingCacheKey::new(text, font_id, size, options);
-            if let Some(shaped) = cache.borrow_mut().get(&cache_key) {
-                return Ok(shaped.clone());
+        let cache_key = self
+            .cache
+            .as_ref()
+            .map(|_| ShapingCacheKey::new(text, font_id, size, &options));
+        if let (Some(cache), Some(cache_key)) = (&self.cache, &cache_key) {
+            if let Some(shaped) = cache.get(cache_key, text) {
+                return Ok(shaped);
             }
         }
 
+        let shaped_text = Arc::new(PackedShapedText::pack(
+            &self.shape_uncached(text, font_id, size, options)?,
+        ));
+
+        // Store in cache
+        if let (Some(cache), Some(cache_key)) = (&self.cache, cache_key) {
+            cache.insert(cache_key, text, Arc::clone(&shaped_text));
+        }
+
+        Ok(shaped_text)
+    }
+
+    /// Reshape text after an edit, reusing the glyphs of unchanged words
+    ///
+    /// `previous` is the result of shaping the text before `edit` with the
+    /// same font, size and options, and `text` the edited text. Only the
+    /// words around the edit are shaped again, up to whitespace where
+    /// `previous` is safe to break; the result equals shaping `text` from
+    /// scratch and is cached like it.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous` - Shaped text before the edit
+    /// * `edit` - Byte range replaced in the previous text
+    /// * `text` - Text after the edit
+    /// * `font_id` - Font identifier
+    /// * `size` - Font size in pixels
+    /// * `options` - Shaping options for the edited text
+    ///
+    /// # Returns
+    ///
+    /// Result containing shaped text or error; `ShapingError::InvalidText`
+    /// if the edit doesn't fit `text`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "text_shaper::reshape_edit",
+            level = "debug",
+            skip_all,
+            fields(font_id, size, text_len = text.len())
+        )
+    )]
+    pub fn reshape_edit(
+        &self,
+        previous: &ShapedText,
+        edit: &TextEdit,
+        text: &str,
+        font_id: FontId,
+        size: f32,
+        options: &ShapingOptions,
+    ) -> Result<ShapedText, ShapingError> {
+        let previous_len = incremental::previous_len(edit, text)?;
+        let Some(region) = incremental::dirty_region(&previous.glyphs, edit, text, previous_len)
+        else {
+            return self.shape_text(text, font_id, size, options);
+        };
+        let options = language::resolve(options, self.locale.as_ref())?;
+
+        // The region's text after the edit
+        let start = region.start;
+        let end = region.end + edit.inserted_len - edit.range.len();
+        let mut region_options = options.as_ref().clone();
+        region_options.feature_ranges =
+            caps::run_feature_ranges(&options, start, end - start, |offset| offset);
+        let mut reshaped = self
+            .shape_uncached(&text[start..end], font_id, size, Cow::Owned(region_options))?
+            .glyphs;
+        for glyph in &mut reshaped {
+            glyph.cluster += start as u32;
+        }
+
+        let shaped = incremental::splice(previous, region, edit, reshaped, options.direction);
+        if let Some(cache) = &self.cache {
+            let cache_key = ShapingCacheKey::new(text, font_id, size, &options);
+            cache.insert(cache_key, text, Arc::new(PackedShapedText::pack(&shaped)));
+        }
+        Ok(shaped)
+    }
+
+    /// Shape text with resolved options, bypassing the cache
+    fn shape_uncached(
+        &self,
+        text: &str,
+        font_id: FontId,
+        size: f32,
+        options: Cow<'_, ShapingOptions>,
+    ) -> Result<ShapedText, ShapingError> {
+        if options.text_transform != TextTransform::None {
+            let mut options = options.into_owned();
+            let transformed = TransformedText::new(text, options.text_transform, &options.language);
+            options.text_transform = TextTransform::None;
+            return self.shape_transformed(text, &transformed, font_id, size, options);
+        }
+        if options.format_characters == FormatCharacters::Visible
+            && text.chars().any(transform::is_format_character)
+        {
+            let transformed = TransformedText::with_format_placeholders(text);
+            return self.shape_transformed(text, &transformed, font_id, size, options.into_owned());
+        }
+
         // Get font face from registry
         let font_face = self
             .registry
@@ -253,117 +727,238 @@ impl<'a> TextShaper<'a> {
         // Handle Option<&[u8]> from lazy loading
         let font_data = font_data.ok_or(ShapingError::FontNotFound)?;
 
-        // Create Harfbuzz font from font data
-        let hb_face = Face::from_bytes(font_data, 0);
-        let mut hb_font = Font::new(hb_face);
+        let options = language::apply_defaults(options, text, font_data);
 
-        // Set font size (Harfbuzz uses 26.6 fixed point format)
-        let font_units_per_em = font_face.metrics.units_per_em as i32;
-        let scale = (size * 64.0) as i32; // Convert to 26.6 fixed point
-        hb_font.set_scale(scale, scale);
-        hb_font.set_ppem(size as u32, size as u32);
+        // Line metrics honor the face's overrides; glyphs are shaped at the
+        // size-adjusted size
+        let metrics = font_face.metric_overrides.apply(&font_face.metrics, size);
+        let size = size * font_face.metric_overrides.size_adjust;
 
-        // Create buffer with text
-        let mut buffer = UnicodeBuffer::new();
-        buffer = buffer.add_str(text);
-
-        // Set buffer properties
-        buffer = buffer.set_direction(direction_to_hb_direction(options.direction));
-        buffer = buffer.set_script(script_to_tag(options.script));
-
-        if let Ok(lang) = harfbuzz_rs::Language::from_str(&options.language.tag) {
-            buffer = buffer.set_language(lang);
-        }
-
-        // Apply OpenType features
-        let features: Vec<harfbuzz_rs::Feature> = options
-            .features
-            .iter()
-            .filter_map(|(tag, value)| {
-                if tag.len() == 4 {
-                    let tag_bytes = tag.as_bytes();
-                    Some(harfbuzz_rs::Feature::new(
-                        Tag::new(
-                            tag_bytes[0] as char,
-                            tag_bytes[1] as char,
-                            tag_bytes[2] as char,
-                            tag_bytes[3] as char,
-                        ),
-                        *value,
-                        ..
-                    ))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        // Shape the text
-        let output = harfbuzz_rs::shape(&hb_font, buffer, &features);
-
-        // Extract glyph info and positions
-        let positions = output.get_glyph_positions();
-        let infos = output.get_glyph_infos();
+        // Engines give glyphs of fonts without vertical metrics an em
+        // advance and an origin at their own top, so such fonts are shaped
+        // upright and laid out in synthesized cells below
+        let vertical = options.direction.is_vertical();
+        let upright = vertical && lacks_vertical_metrics(font_data);
+        let engine_options = if upright {
+            Cow::Owned(backend::upright_options(&options))
+        } else {
+            Cow::Borrowed(options.as_ref())
+        };
+        let raw_glyphs = if options.variant_caps == FontVariantCaps::Normal {
+            let simple = self
+                .cache
+                .as_ref()
+                .and_then(|cache| cache.simple_face(font_id, font_data))
+                .and_then(|face| face.shape(size, text, &engine_options));
+            match simple {
+                Some(raw_glyphs) => raw_glyphs,
+                None => backend::shape(font_data, size, text, &engine_options)?,
+            }
+            .into_iter()
+            .map(|raw| (raw, 1.0))
+            .collect()
+        } else {
+            let metrics = &font_face.metrics;
+            let small_caps_scale = if metrics.x_height > 0.0 && metrics.cap_height > 0.0 {
+                metrics.x_height / metrics.cap_height
+            } else {
+                caps::DEFAULT_SMALL_CAPS_SCALE
+            };
+            caps::shape(font_data, size, text, &engine_options, small_caps_scale)?
+        };
 
-        // Convert to our format
-        let mut glyphs = Vec::with_capacity(infos.len());
+        // Convert to our format. Engines give positions with y up; vertical
+        // text instead advances down the column, with offsets from each
+        // glyph's vertical origin on the central baseline
+        let mut glyphs = Vec::with_capacity(raw_glyphs.len());
         let mut cursor_x = 0.0;
         let mut cursor_y = 0.0;
-        let mut total_width = 0.0;
 
-        for (info, pos) in infos.iter().zip(positions.iter()) {
-            // Convert from 26.6 fixed point to float
-            let x_advance = pos.x_advance as f32 / 64.0;
-            let y_advance = pos.y_advance as f32 / 64.0;
-            let x_offset = pos.x_offset as f32 / 64.0;
-            let y_offset = pos.y_offset as f32 / 64.0;
+        // Upright glyphs follow the synthesized metrics of
+        // `font_parser::OpenTypeFont::get_vertical_glyph_metrics`: each
+        // spacing glyph starts a cell as tall as a line, centered across the
+        // column with the baseline at the ascent, and marks keep their
+        // place relative to it
+        let cell_height = metrics.ascent - metrics.descent;
+        let mut upright_x = 0.0;
+        let mut cell = UprightCell::default();
 
-            // Apply letter spacing
-            let adjusted_x_advance = x_advance + options.letter_spacing;
+        for (raw, scale) in &raw_glyphs {
+            let pen_x = upright_x;
+            upright_x += raw.x_advance;
+            let invisible = is_format_cluster(text, raw.cluster);
+            if invisible && raw.glyph_id == 0 {
+                // Dropped rather than drawn as tofu, or sent to fallback
+                continue;
+            }
+            let (advance, offset) = if invisible {
+                // Never drawn, and not spaced out by letter spacing
+                (Vector { x: 0.0, y: 0.0 }, Vector { x: 0.0, y: 0.0 })
+            } else if upright {
+                if raw.x_advance != 0.0 {
+                    cell = UprightCell {
+                        x: pen_x,
+                        advance: raw.x_advance,
+                        top: cursor_y,
+                    };
+                }
+                let height = if raw.x_advance == 0.0 {
+                    0.0
+                } else {
+                    cell_height
+                };
+                let advance = Vector {
+                    x: 0.0,
+                    y: height + options.letter_spacing,
+                };
+                let offset = Vector {
+                    x: pen_x + raw.x_offset - cell.x - cell.advance / 2.0,
+                    y: cell.top + metrics.ascent - raw.y_offset - cursor_y,
+                };
+                (advance, offset)
+            } else if vertical {
+                // Apply letter spacing along the column
+                let advance = Vector {
+                    x: 0.0,
+                    y: -raw.y_advance + options.letter_spacing,
+                };
+                let offset = Vector {
+                    x: raw.x_offset,
+                    y: -raw.y_offset,
+                };
+                (advance, offset)
+            } else {
+                // Apply letter spacing
+                let advance = Vector {
+                    x: raw.x_advance + options.letter_spacing,
+                    y: raw.y_advance,
+                };
+                let offset = Vector {
+                    x: raw.x_offset,
+                    y: raw.y_offset,
+                };
+                (advance, offset)
+            };
 
             glyphs.push(PositionedGlyph {
-                glyph_id: GlyphId {
-                    id: info.codepoint,
-                },
+                glyph_id: GlyphId { id: raw.glyph_id },
                 font_id,
                 position: Point {
-                    x: cursor_x + x_offset,
-                    y: cursor_y + y_offset,
-                },
-                advance: Vector {
-                    x: adjusted_x_advance,
-                    y: y_advance,
-                },
-                offset: Vector {
-                    x: x_offset,
-                    y: y_offset,
+                    x: cursor_x + offset.x,
+                    y: cursor_y + offset.y,
                 },
+                advance,
+                offset,
+                scale: *scale * font_face.metric_overrides.size_adjust,
+                cluster: raw.cluster,
+                unsafe_to_break: raw.unsafe_to_break,
             });
 
-            cursor_x += adjusted_x_advance;
-            cursor_y += y_advance;
-            total_width = cursor_x;
+            cursor_x += advance.x;
+            cursor_y += advance.y;
         }
 
         // Calculate height and baseline from font metrics
-        let scale_factor = size / font_units_per_em as f32;
-        let height = (font_face.metrics.ascent - font_face.metrics.descent) * scale_factor;
-        let baseline = font_face.metrics.ascent * scale_factor;
+        let line_height = metrics.ascent - metrics.descent;
+        if vertical {
+            // Columns are as wide as lines are tall, around a central baseline
+            return Ok(ShapedText {
+                glyphs,
+                width: line_height,
+                height: cursor_y,
+                baseline: line_height / 2.0,
+            });
+        }
+        let baseline = metrics.ascent;
 
-        let shaped_text = ShapedText {
+        Ok(ShapedText {
             glyphs,
-            width: total_width,
-            height,
+            width: cursor_x,
+            height: line_height,
             baseline,
-        };
+        })
+    }
 
-        // Store in cache
-        if let Some(cache) = &self.cache {
-            let cache_key = ShapingCacheKey::new(text, font_id, size, options);
-            cache.borrow_mut().insert(cache_key, shaped_text.clone());
+    /// Shape the `transformed` form of `text`, with clusters as byte
+    /// offsets into `text`
+    fn shape_transformed(
+        &self,
+        text: &str,
+        transformed: &TransformedText,
+        font_id: FontId,
+        size: f32,
+        mut options: ShapingOptions,
+    ) -> Result<ShapedText, ShapingError> {
+        options.feature_ranges = caps::run_feature_ranges(&options, 0, text.len(), |offset| {
+            transformed.transformed_offset(offset)
+        });
+
+        let mut shaped =
+            self.shape_uncached(transformed.text(), font_id, size, Cow::Owned(options))?;
+        for glyph in &mut shaped.glyphs {
+            glyph.cluster = transformed.source_offset(glyph.cluster as usize) as u32;
         }
+        Ok(shaped)
+    }
 
-        Ok(shaped_text)
+    /// Tatweel glyph of a font at `size`, for kashida justification
+    ///
+    /// Returns `None` when the font has no tatweel. Use it with the points
+    /// from [`kashida_points`](crate::kashida_points) to elongate Arabic
+    /// text shaped with the same font.
+    ///
+    /// # Returns
+    ///
+    /// Result containing the tatweel glyph, if any, or error
+    pub fn kashida_glyph(
+        &self,
+        font_id: FontId,
+        size: f32,
+        options: &ShapingOptions,
+    ) -> Result<Option<KashidaGlyph>, ShapingError> {
+        let shaped = self.shape_text_packed("\u{640}", font_id, size, options)?;
+        let mut glyphs = shaped.iter();
+        Ok(match (glyphs.next(), glyphs.next()) {
+            (Some(glyph), None) if glyph.glyph_id.id != 0 && glyph.advance.x > 0.0 => {
+                Some(KashidaGlyph {
+                    font_id,
+                    glyph_id: glyph.glyph_id,
+                    advance: glyph.advance.x,
+                })
+            }
+            _ => None,
+        })
+    }
+
+    /// Hyphen glyph of a font at `size`, drawn where a line breaks at a
+    /// soft hyphen
+    ///
+    /// Uses U+2010 HYPHEN, or U+002D HYPHEN-MINUS when the font lacks it.
+    /// Returns `None` when the font has neither.
+    ///
+    /// # Returns
+    ///
+    /// Result containing the hyphen glyph, if any, or error
+    pub fn hyphen_glyph(
+        &self,
+        font_id: FontId,
+        size: f32,
+        options: &ShapingOptions,
+    ) -> Result<Option<HyphenGlyph>, ShapingError> {
+        for hyphen in ["\u{2010}", "-"] {
+            let shaped = self.shape_text_packed(hyphen, font_id, size, options)?;
+            let mut glyphs = shaped.iter();
+            if let (Some(glyph), None) = (glyphs.next(), glyphs.next()) {
+                if glyph.glyph_id.id != 0 {
+                    return Ok(Some(HyphenGlyph {
+                        font_id,
+                        glyph_id: glyph.glyph_id,
+                        advance: glyph.advance.x,
+                    }));
+                }
+            }
+        }
+        Ok(None)
     }
 
     /// Shape text with font fallback
@@ -377,65 +972,452 @@ impl<'a> TextShaper<'a> {
     /// # Returns
     ///
     /// Result containing shaped text or error
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "text_shaper::shape_text_with_fallback",
+            level = "debug",
+            skip_all,
+            fields(text_len = text.len())
+        )
+    )]
     pub fn shape_text_with_fallback(
         &self,
         text: &str,
         descriptor: &FontDescriptor,
         options: &ShapingOptions,
     ) -> Result<ShapedText, ShapingError> {
+        self.shape_text_with_fallback_report(text, descriptor, options)
+            .map(|(shaped, _)| shaped)
+    }
+
+    /// Shape text with font fallback, reporting clusters no font covers
+    ///
+    /// Like [`shape_text_with_fallback`](Self::shape_text_with_fallback),
+    /// but also returns one [`MissingGlyph`] per cluster that was shaped as
+    /// `.notdef`, in text order, so callers can detect "tofu".
+    ///
+    /// # Returns
+    ///
+    /// Result containing the shaped text and its missing clusters, or error
+    pub fn shape_text_with_fallback_report(
+        &self,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+    ) -> Result<(ShapedText, Vec<MissingGlyph>), ShapingError> {
+        let (paragraph, missing) =
+            self.shape_paragraph_with_fallback_report(text, descriptor, options)?;
+        Ok((paragraph.to_shaped_text(), missing))
+    }
+
+    /// Shape text with font fallback into runs of one font each
+    ///
+    /// Clusters the matched font shapes as `.notdef` go to the first fallback
+    /// covering their first character: a font matching another family of
+    /// the descriptor, then any loaded font in ID order. Consecutive clusters
+    /// using the same font are shaped together as one [`ShapedRun`]. Runs
+    /// are in visual order, with glyphs positioned within the paragraph and
+    /// clusters as byte offsets into `text`.
+    ///
+    /// # Returns
+    ///
+    /// Result containing the shaped runs or error
+    pub fn shape_paragraph(
+        &self,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+    ) -> Result<ShapedParagraph, ShapingError> {
+        self.shape_paragraph_with_fallback_report(text, descriptor, options)
+            .map(|(paragraph, _)| paragraph)
+    }
+
+    /// Shape text into runs like [`shape_paragraph`](Self::shape_paragraph),
+    /// reporting clusters no font covers
+    ///
+    /// # Returns
+    ///
+    /// Result containing the shaped runs and the missing clusters, or error
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "text_shaper::shape_paragraph",
+            level = "debug",
+            skip_all,
+            fields(text_len = text.len())
+        )
+    )]
+    pub fn shape_paragraph_with_fallback_report(
+        &self,
+        text: &str,
+        descriptor: &FontDescriptor,
+        options: &ShapingOptions,
+    ) -> Result<(ShapedParagraph, Vec<MissingGlyph>), ShapingError> {
         // Validate input
         if text.is_empty() {
-            return Ok(ShapedText {
-                glyphs: Vec::new(),
-                width: 0.0,
-                height: 0.0,
-                baseline: 0.0,
-            });
+            return Ok((ShapedParagraph::default(), Vec::new()));
         }
 
-        // Convert font_types::FontDescriptor to font_registry::FontDescriptor
-        let registry_descriptor = RegistryFontDescriptor {
-            family: descriptor.family.clone(),
-            weight: descriptor.weight,
-            style: descriptor.style,
-            stretch: descriptor.stretch,
-            size: descriptor.size,
-        };
-
         // Try to match font using descriptor
         let font_id = self
             .registry
-            .match_font(&registry_descriptor)
+            .match_font_in_context(descriptor, options.match_context)
             .ok_or(ShapingError::FontNotFound)?;
+        let descriptor_options;
+        let options = if options.synthesize_small_caps && !descriptor.synthesis.small_caps {
+            descriptor_options = ShapingOptions {
+                synthesize_small_caps: false,
+                ..options.clone()
+            };
+            &descriptor_options
+        } else {
+            options
+        };
+        let primary = self.shape_text(text, font_id, descriptor.size, options)?;
 
-        // Use shape_text with the matched font
-        self.shape_text(text, font_id, descriptor.size, options)
+        let fallbacks = self.fallback_fonts(descriptor, font_id, options.match_context);
+        let segments = font_segments(&primary, text, font_id, |c| {
+            fallbacks
+                .iter()
+                .copied()
+                .find(|&fallback| self.registry.font_covers(fallback, c))
+        });
+        let mut runs = if let [(only, _)] = segments[..] {
+            if only == font_id {
+                vec![PendingRun::new(0..text.len(), font_id, primary, options)]
+            } else {
+                let shaped = self.shape_text(text, only, descriptor.size, options)?;
+                vec![PendingRun::new(0..text.len(), only, shaped, options)]
+            }
+        } else {
+            segments
+                .into_iter()
+                .map(|(font, range)| {
+                    let mut shaped =
+                        self.shape_text(&text[range.clone()], font, descriptor.size, options)?;
+                    for glyph in &mut shaped.glyphs {
+                        glyph.cluster += range.start as u32;
+                    }
+                    Ok(PendingRun::new(range, font, shaped, options))
+                })
+                .collect::<Result<Vec<_>, ShapingError>>()?
+        };
+        if matches!(
+            options.direction,
+            Direction::RightToLeft | Direction::BottomToTop
+        ) {
+            runs.reverse();
+        }
+
+        let mut paragraph = assemble_runs(runs, options.direction.is_vertical());
+        self.set_run_metrics(&mut paragraph, descriptor.size, options);
+        let attempted: Vec<FontId> = std::iter::once(font_id).chain(fallbacks).collect();
+        let missing = missing_glyphs(text, paragraph.glyphs(), &attempted);
+        Ok((paragraph, missing))
+    }
+
+    /// Fill in the metrics of each run of `paragraph`, shaped at `size`
+    /// with `options` and the run's script
+    pub(crate) fn set_run_metrics(
+        &self,
+        paragraph: &mut ShapedParagraph,
+        size: f32,
+        options: &ShapingOptions,
+    ) {
+        let mut metrics: Vec<(FontId, Script, RunMetrics)> = Vec::new();
+        for run in &mut paragraph.runs {
+            let known = metrics
+                .iter()
+                .find(|(font, script, _)| *font == run.font_id && *script == run.script);
+            run.metrics = match known {
+                Some((_, _, known)) => known.clone(),
+                None => {
+                    let run_options = ShapingOptions {
+                        script: run.script,
+                        ..options.clone()
+                    };
+                    let font_metrics = self.run_metrics(run.font_id, size, &run_options);
+                    metrics.push((run.font_id, run.script, font_metrics.clone()));
+                    font_metrics
+                }
+            };
+        }
+    }
+
+    /// Ascent, descent and BASE baselines of a font at `size`, for runs
+    /// shaped with `options`
+    ///
+    /// BASE baselines are only read for horizontal text.
+    fn run_metrics(&self, font_id: FontId, size: f32, options: &ShapingOptions) -> RunMetrics {
+        let Some(font_face) = self.registry.get_font_face(font_id) else {
+            return RunMetrics::default();
+        };
+        let metrics = font_face.metric_overrides.apply(&font_face.metrics, size);
+        let scale_factor = size * font_face.metric_overrides.size_adjust
+            / f32::from(font_face.metrics.units_per_em);
+        let base = font_face
+            .data()
+            .filter(|_| !options.direction.is_vertical())
+            .and_then(|data| OpenTypeFont::parse(data.to_vec()).ok())
+            .and_then(|font| font.get_base())
+            .and_then(|base| base.horizontal);
+        let script = base_script_tag(options.script);
+        let baselines = base
+            .and_then(|axis| axis.script(script).cloned())
+            .map(|record| {
+                [
+                    Baseline::Alphabetic,
+                    Baseline::Hanging,
+                    Baseline::Ideographic,
+                    Baseline::IdeographicTop,
+                ]
+                .into_iter()
+                .filter_map(|baseline| {
+                    let tag = Tag::new(baseline.tag()).expect("valid tag");
+                    let coordinate = record.coordinate(tag)?;
+                    Some((baseline, f32::from(coordinate) * scale_factor))
+                })
+                .collect()
+            })
+            .unwrap_or_default();
+
+        RunMetrics {
+            ascent: metrics.ascent,
+            descent: -metrics.descent,
+            baselines,
+        }
+    }
+
+    /// Fonts visible in `context` to try for characters the matched font
+    /// doesn't cover
+    pub(crate) fn fallback_fonts(
+        &self,
+        descriptor: &FontDescriptor,
+        matched: FontId,
+        context: MatchContext,
+    ) -> Vec<FontId> {
+        let from_families = descriptor.family.iter().filter_map(|family| {
+            self.registry.match_font_in_context(
+                &FontDescriptor {
+                    family: vec![family.clone()],
+                    ..descriptor.clone()
+                },
+                context,
+            )
+        });
+        let loaded = self
+            .registry
+            .font_faces()
+            .into_iter()
+            .map(|face| face.id)
+            .filter(|&id| self.registry.is_font_visible(id, context));
+
+        let mut fonts: Vec<FontId> = Vec::new();
+        for font_id in from_families.chain(loaded) {
+            if font_id != matched && !fonts.contains(&font_id) {
+                fonts.push(font_id);
+            }
+        }
+        fonts
     }
 }
 
-/// Convert Script to harfbuzz Tag
-fn script_to_tag(script: Script) -> Tag {
+/// OpenType script tag BASE records are looked up by
+///
+/// OpenType files both kana scripts under `kana`, and the `DFLT` record
+/// covers common characters.
+fn base_script_tag(script: Script) -> Tag {
     match script {
-        Script::Latin => Tag::new('l', 'a', 't', 'n'),
-        Script::Arabic => Tag::new('a', 'r', 'a', 'b'),
-        Script::Hebrew => Tag::new('h', 'e', 'b', 'r'),
-        Script::Cyrillic => Tag::new('c', 'y', 'r', 'l'),
-        Script::Greek => Tag::new('g', 'r', 'e', 'k'),
-        Script::Han => Tag::new('h', 'a', 'n', 'i'),
-        Script::Hangul => Tag::new('h', 'a', 'n', 'g'),
-        Script::Hiragana => Tag::new('h', 'i', 'r', 'a'),
-        Script::Katakana => Tag::new('k', 'a', 'n', 'a'),
-        Script::Common => Tag::new('z', 'y', 'y', 'y'),
+        Script::Hiragana | Script::Katakana => Tag::new("kana"),
+        Script::Common => Tag::new("DFLT"),
+        _ => Tag::new(std::str::from_utf8(&backend::script_tag(script)).expect("ASCII tag")),
+    }
+    .expect("valid tag")
+}
+
+/// Split text into byte ranges of one font, in text order
+///
+/// Clusters of `shaped` without `.notdef` keep `font_id`; the others use
+/// the font `fallback` picks for their first character, or `font_id` when
+/// it finds none.
+pub(crate) fn font_segments(
+    shaped: &ShapedText,
+    text: &str,
+    font_id: FontId,
+    mut fallback: impl FnMut(char) -> Option<FontId>,
+) -> Vec<(FontId, Range<usize>)> {
+    let mut clusters: Vec<(usize, bool)> = shaped
+        .glyphs
+        .iter()
+        .map(|glyph| (glyph.cluster as usize, glyph.glyph_id.id == 0))
+        .collect();
+    clusters.sort_unstable();
+    // Keep one entry per cluster, marked missing if any of its glyphs is
+    clusters.dedup_by(|next, first| {
+        let same = next.0 == first.0;
+        if same {
+            first.1 |= next.1;
+        }
+        same
+    });
+    if clusters.first().is_none_or(|&(start, _)| start != 0) {
+        clusters.insert(0, (0, false));
+    }
+
+    let mut segments: Vec<(FontId, Range<usize>)> = Vec::new();
+    for (i, &(start, missing)) in clusters.iter().enumerate() {
+        let end = clusters.get(i + 1).map_or(text.len(), |&(next, _)| next);
+        let font = if missing {
+            text.get(start..)
+                .and_then(|rest| rest.chars().next())
+                .and_then(&mut fallback)
+                .unwrap_or(font_id)
+        } else {
+            font_id
+        };
+        match segments.last_mut() {
+            Some((last, range)) if *last == font => range.end = end,
+            _ => segments.push((font, start..end)),
+        }
+    }
+    segments
+}
+
+/// A shaped run waiting to be placed in a paragraph
+pub(crate) struct PendingRun {
+    pub(crate) text_range: Range<usize>,
+    pub(crate) font_id: FontId,
+    pub(crate) direction: Direction,
+    pub(crate) script: Script,
+    pub(crate) shaped: ShapedText,
+}
+
+impl PendingRun {
+    fn new(
+        text_range: Range<usize>,
+        font_id: FontId,
+        shaped: ShapedText,
+        options: &ShapingOptions,
+    ) -> Self {
+        Self {
+            text_range,
+            font_id,
+            direction: options.direction,
+            script: options.script,
+            shaped,
+        }
+    }
+}
+
+/// Lay out shaped runs, given in visual order, one after another
+pub(crate) fn assemble_runs(runs: Vec<PendingRun>, vertical: bool) -> ShapedParagraph {
+    let mut paragraph = ShapedParagraph::default();
+    let mut pen = 0.0;
+    for run in runs {
+        let shaped = run.shaped;
+        let mut glyphs = shaped.glyphs;
+        for glyph in &mut glyphs {
+            if vertical {
+                glyph.position.y += pen;
+            } else {
+                glyph.position.x += pen;
+            }
+        }
+        if vertical {
+            pen += shaped.height;
+            paragraph.width = paragraph.width.max(shaped.width);
+        } else {
+            pen += shaped.width;
+            paragraph.height = paragraph.height.max(shaped.height);
+        }
+        paragraph.baseline = paragraph.baseline.max(shaped.baseline);
+        paragraph.runs.push(ShapedRun {
+            font_id: run.font_id,
+            direction: run.direction,
+            script: run.script,
+            text_range: run.text_range,
+            glyphs,
+            metrics: RunMetrics::default(),
+        });
+    }
+    if vertical {
+        paragraph.height = pen;
+    } else {
+        paragraph.width = pen;
+    }
+    paragraph
+}
+
+/// Cell of a spacing glyph shaped upright in vertical text
+#[derive(Default)]
+struct UprightCell {
+    /// Horizontal pen position the glyph was shaped at
+    x: f32,
+    /// Horizontal advance of the glyph
+    advance: f32,
+    /// Top of the cell down the column
+    top: f32,
+}
+
+/// Whether the font has no vhea/vmtx, so its vertical metrics are
+/// synthesized
+fn lacks_vertical_metrics(font_data: &[u8]) -> bool {
+    OpenTypeFont::parse(font_data.to_vec()).is_ok_and(|font| !font.has_vertical_metrics())
+}
+
+/// Whether the cluster at byte `cluster` of `text` starts with a format
+/// character, such as a soft hyphen (drawn as a hyphen by layout when a
+/// line breaks at it), a zero-width space or a bidi control
+fn is_format_cluster(text: &str, cluster: u32) -> bool {
+    text.get(cluster as usize..)
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(transform::is_format_character)
+}
+
+/// Clusters of `glyphs` holding `.notdef`, once each in text order
+pub(crate) fn missing_glyphs<'g>(
+    text: &str,
+    glyphs: impl IntoIterator<Item = &'g PositionedGlyph>,
+    attempted: &[FontId],
+) -> Vec<MissingGlyph> {
+    let mut clusters: Vec<u32> = glyphs
+        .into_iter()
+        .filter(|glyph| glyph.glyph_id.id == 0)
+        .map(|glyph| glyph.cluster)
+        .collect();
+    clusters.sort_unstable();
+    clusters.dedup();
+    clusters
+        .into_iter()
+        .filter_map(|cluster| {
+            let character = text.get(cluster as usize..)?.chars().next()?;
+            Some(MissingGlyph {
+                cluster,
+                character,
+                attempted_fonts: attempted.to_vec(),
+            })
+        })
+        .collect()
+}
+
+impl MemoryReporter for TextShaper<'_> {
+    /// Shaped text held by the shaping cache (font data belongs to the registry)
+    fn memory_report(&self) -> MemoryReport {
+        self.cache
+            .as_ref()
+            .map(|cache| cache.memory_report())
+            .unwrap_or_default()
     }
 }
 
-/// Convert direction to harfbuzz direction
-fn direction_to_hb_direction(direction: Direction) -> harfbuzz_rs::Direction {
-    match direction {
-        Direction::LeftToRight => harfbuzz_rs::Direction::Ltr,
-        Direction::RightToLeft => harfbuzz_rs::Direction::Rtl,
-        Direction::TopToBottom => harfbuzz_rs::Direction::Ttb,
-        Direction::BottomToTop => harfbuzz_rs::Direction::Btt,
+impl MemoryTrimmer for TextShaper<'_> {
+    /// Evict the least recently used share of cached shaping results,
+    /// including results other shapers sharing the cache put there
+    fn trim_memory(&mut self, level: MemoryPressureLevel) -> usize {
+        self.cache
+            .as_ref()
+            .map_or(0, |cache| cache.trim_memory(level))
     }
 }
 
@@ -443,29 +1425,138 @@ fn direction_to_hb_direction(direction: Direction) -> harfbuzz_rs::Direction {
 mod tests {
     use super::*;
 
+    fn shaped(width: f32) -> Arc<PackedShapedText> {
+        Arc::new(PackedShapedText::pack(&ShapedText {
+            glyphs: Vec::new(),
+            width,
+            height: 0.0,
+            baseline: 0.0,
+        }))
+    }
+
     #[test]
-    fn test_script_to_tag() {
-        // Test that script conversion works
-        assert_eq!(
-            script_to_tag(Script::Latin),
-            Tag::new('l', 'a', 't', 'n')
-        );
-        assert_eq!(
-            script_to_tag(Script::Arabic),
-            Tag::new('a', 'r', 'a', 'b')
-        );
+    fn test_key_is_case_sensitive_and_copy_free() {
+        let options = ShapingOptions::default();
+        let key = ShapingCacheKey::new("Menu", 0, 16.0, &options);
+        assert_eq!(key, ShapingCacheKey::new("Menu", 0, 16.0, &options));
+        assert_ne!(key, ShapingCacheKey::new("menu", 0, 16.0, &options));
+        assert_eq!(key.text_len, 4);
     }
 
     #[test]
-    fn test_direction_conversion() {
-        // Test that direction conversion works
+    fn test_differing_options_never_share_a_key() {
+        // Given: Options each differing from the defaults in one way,
+        // including features and ranges that would run together unprefixed
+        let base = ShapingOptions::default();
+        let with = |change: fn(&mut ShapingOptions)| {
+            let mut options = base.clone();
+            change(&mut options);
+            options
+        };
+        fn range(tag: &str, range: Range<usize>) -> crate::types::FeatureRange {
+            crate::types::FeatureRange {
+                tag: tag.to_string(),
+                value: 1,
+                range,
+            }
+        }
+        let variants = vec![
+            base.clone(),
+            with(|o| o.script = Script::Latin),
+            with(|o| o.language.tag = "en".to_string()),
+            with(|o| o.direction = Direction::RightToLeft),
+            with(|o| o.features = [("ab".to_string(), 1)].into()),
+            with(|o| o.features = [("a".to_string(), 1), ("b".to_string(), 1)].into()),
+            with(|o| o.features = [("ab".to_string(), 2)].into()),
+            with(|o| o.kerning = false),
+            with(|o| o.ligatures = false),
+            with(|o| o.letter_spacing = 0.5),
+            with(|o| o.letter_spacing = 1.0),
+            with(|o| o.word_spacing = 0.5),
+            with(|o| o.variant_caps = FontVariantCaps::SmallCaps),
+            with(|o| o.feature_ranges = vec![range("tnum", 0..2)]),
+            with(|o| o.feature_ranges = vec![range("tnum", 0..3)]),
+            with(|o| o.feature_ranges = vec![range("tnu", 0..2), range("m", 0..2)]),
+            with(|o| o.synthesize_small_caps = false),
+            with(|o| o.text_transform = TextTransform::Uppercase),
+            with(|o| o.format_characters = FormatCharacters::Visible),
+        ];
+
+        // When: Encoding them as cache keys
+        let keys: Vec<_> = variants.iter().map(ShapingOptions::cache_key).collect();
+
+        // Then: Every key is versioned and no two collide
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(key[0], crate::types::SHAPING_OPTIONS_KEY_VERSION);
+            for (other, other_key) in keys.iter().enumerate().skip(index + 1) {
+                assert_ne!(key, other_key, "options {index} and {other}");
+                assert_ne!(
+                    ShapingCacheKey::new("a", 0, 16.0, &variants[index]),
+                    ShapingCacheKey::new("a", 0, 16.0, &variants[other])
+                );
+            }
+        }
+
+        // And: Equal options share a key whatever the feature order, the
+        // sign of zero spacing or the match context
+        let mut features = with(|o| o.features = [("kern".to_string(), 0)].into());
+        features.features.insert("liga".to_string(), 0);
+        let mut reordered = with(|o| o.features = [("liga".to_string(), 0)].into());
+        reordered.features.insert("kern".to_string(), 0);
+        assert_eq!(features.cache_key(), reordered.cache_key());
         assert_eq!(
-            direction_to_hb_direction(Direction::LeftToRight),
-            harfbuzz_rs::Direction::Ltr
+            base.cache_key(),
+            with(|o| o.letter_spacing = -0.0).cache_key()
         );
         assert_eq!(
-            direction_to_hb_direction(Direction::RightToLeft),
-            harfbuzz_rs::Direction::Rtl
+            base.cache_key(),
+            with(|o| o.match_context = MatchContext::Untrusted).cache_key()
         );
+
+        // And: Sizes that differ at all get their own entries
+        assert_ne!(
+            ShapingCacheKey::new("a", 0, 16.04, &base),
+            ShapingCacheKey::new("a", 0, 16.06, &base)
+        );
+    }
+
+    #[test]
+    fn test_verified_keys_reject_colliding_text() {
+        // Given: A result cached under a key, with verification on
+        let cache = ShapingCache::new(4);
+        cache.set_verify_keys(true);
+        let key = ShapingCacheKey::new("abc", 0, 16.0, &ShapingOptions::default());
+        cache.insert(key, "abc", shaped(1.0));
+
+        // When: Another text of the same length collides with the key
+        let collision = cache.get(&key, "xyz");
+
+        // Then: It misses, while the cached text still hits
+        assert!(collision.is_none());
+        assert_eq!(cache.get(&key, "abc").unwrap().width, 1.0);
+
+        // And: Without verification the hash alone decides
+        cache.set_verify_keys(false);
+        cache.insert(key, "abc", shaped(2.0));
+        assert_eq!(cache.get(&key, "xyz").unwrap().width, 2.0);
+    }
+
+    #[test]
+    fn test_replacing_a_result_is_not_an_eviction() {
+        // Given: A cached result
+        let cache = ShapingCache::new(4);
+        let key = ShapingCacheKey::new("abc", 0, 16.0, &ShapingOptions::default());
+        cache.insert(key, "abc", shaped(1.0));
+        let bytes = cache.memory_report().bytes;
+
+        // When: The same key is cached again
+        cache.insert(key, "abc", shaped(2.0));
+
+        // Then: The result is replaced without counting an eviction
+        let stats = cache.stats();
+        assert_eq!(stats.evictions, 0);
+        assert_eq!(stats.current_size, 1);
+        assert_eq!(cache.memory_report().bytes, bytes);
+        assert_eq!(cache.get(&key, "abc").unwrap().width, 2.0);
     }
 }
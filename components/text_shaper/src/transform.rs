@@ -0,0 +1,288 @@
+//! `text-transform` support
+//!
+//! The text is transformed right before shaping, after line breaks were
+//! found in the source text, and the shaped clusters are mapped back to byte
+//! offsets of the source so that hit testing and line breaking keep
+//! working on it. Casing may change the length of the text (`ß` uppercases
+//! to `SS`), so each transformed byte records the source character it came
+//! from. Format characters shown as placeholders are mapped the same way.
+
+use crate::types::{Language, TextTransform, FORMAT_PLACEHOLDER};
+
+/// Digraphs whose titlecase form differs from their uppercase form
+const TITLECASE_DIGRAPHS: &[(char, char)] = &[
+    ('\u{1c4}', '\u{1c5}'),
+    ('\u{1c5}', '\u{1c5}'),
+    ('\u{1c6}', '\u{1c5}'),
+    ('\u{1c7}', '\u{1c8}'),
+    ('\u{1c8}', '\u{1c8}'),
+    ('\u{1c9}', '\u{1c8}'),
+    ('\u{1ca}', '\u{1cb}'),
+    ('\u{1cb}', '\u{1cb}'),
+    ('\u{1cc}', '\u{1cb}'),
+    ('\u{1f1}', '\u{1f2}'),
+    ('\u{1f2}', '\u{1f2}'),
+    ('\u{1f3}', '\u{1f2}'),
+];
+
+/// Text after a [`TextTransform`], with a map back to the source text
+///
+/// ```
+/// use text_shaper::{Language, TextTransform, TransformedText};
+///
+/// let language = Language { tag: "de".to_string() };
+/// let transformed = TransformedText::new("straße", TextTransform::Uppercase, &language);
+/// assert_eq!(transformed.text(), "STRASSE");
+/// // Both S of "SS" came from "ß" at byte 4
+/// assert_eq!(transformed.source_offset(5), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransformedText {
+    text: String,
+    /// Source byte offset of each byte of `text`, then the source length
+    origins: Vec<usize>,
+}
+
+impl TransformedText {
+    /// Transform `source` with the casing rules of `language`
+    pub fn new(source: &str, transform: TextTransform, language: &Language) -> Self {
+        let turkic = language.is_turkic();
+        let mut text = String::with_capacity(source.len());
+        let mut origins = Vec::with_capacity(source.len() + 1);
+        let mut in_word = false;
+        let mut previous = None;
+        let mut chars = source.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            let before = text.len();
+            match transform {
+                TextTransform::None => text.push(c),
+                TextTransform::Uppercase => push_uppercase(&mut text, c, turkic),
+                TextTransform::Lowercase => {
+                    let next = chars.peek().map(|&(_, next)| next);
+                    push_lowercase(&mut text, c, turkic, previous, next);
+                }
+                TextTransform::Capitalize if !in_word && c.is_alphabetic() => {
+                    push_titlecase(&mut text, c, turkic);
+                }
+                TextTransform::Capitalize => text.push(c),
+            }
+            origins.resize(origins.len() + (text.len() - before), index);
+
+            if c.is_alphanumeric() {
+                in_word = true;
+            } else if separates_words(c) {
+                in_word = false;
+            }
+            previous = Some(c);
+        }
+        // Clusters may point at the end of the text
+        origins.push(source.len());
+        Self { text, origins }
+    }
+
+    /// Replace each format character of `source` with
+    /// [`FORMAT_PLACEHOLDER`]
+    pub(crate) fn with_format_placeholders(source: &str) -> Self {
+        let mut text = String::with_capacity(source.len());
+        let mut origins = Vec::with_capacity(source.len() + 1);
+        for (index, c) in source.char_indices() {
+            text.push(if is_format_character(c) {
+                FORMAT_PLACEHOLDER
+            } else {
+                c
+            });
+            origins.resize(text.len(), index);
+        }
+        origins.push(source.len());
+        Self { text, origins }
+    }
+
+    /// The transformed text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte offset in the source of the character that produced the byte
+    /// at `offset` of the transformed text
+    ///
+    /// Offsets at or past the end map to the end of the source.
+    pub fn source_offset(&self, offset: usize) -> usize {
+        let last = self.origins.len() - 1;
+        self.origins[offset.min(last)]
+    }
+
+    /// Byte offset in the transformed text of the first byte produced by
+    /// the source character at `source_offset`
+    pub fn transformed_offset(&self, source_offset: usize) -> usize {
+        self.origins
+            .partition_point(|&origin| origin < source_offset)
+    }
+}
+
+/// Whether `c` is an invisible format character (see
+/// [`FormatCharacters`](crate::FormatCharacters))
+pub(crate) fn is_format_character(c: char) -> bool {
+    matches!(
+        c,
+        '\u{ad}'
+            | '\u{61c}'
+            | '\u{180e}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{2069}'
+            | '\u{feff}'
+    )
+}
+
+fn push_uppercase(text: &mut String, c: char, turkic: bool) {
+    if turkic && c == 'i' {
+        text.push('İ');
+    } else {
+        text.extend(c.to_uppercase());
+    }
+}
+
+/// Lowercase `c` between `previous` and `next`, for the final sigma rule
+fn push_lowercase(
+    text: &mut String,
+    c: char,
+    turkic: bool,
+    previous: Option<char>,
+    next: Option<char>,
+) {
+    match c {
+        'I' if turkic => text.push('ı'),
+        'İ' if turkic => text.push('i'),
+        'Σ' if previous.is_some_and(char::is_alphabetic)
+            && !next.is_some_and(char::is_alphabetic) =>
+        {
+            text.push('ς');
+        }
+        _ => text.extend(c.to_lowercase()),
+    }
+}
+
+/// Titlecase the first letter of a word: its uppercase form, with any
+/// further letters it expands to in lowercase (`ß` becomes `Ss`)
+fn push_titlecase(text: &mut String, c: char, turkic: bool) {
+    if let Some(&(_, title)) = TITLECASE_DIGRAPHS.iter().find(|(from, _)| *from == c) {
+        text.push(title);
+        return;
+    }
+    let start = text.len();
+    push_uppercase(text, c, turkic);
+    if let Some(first) = text[start..].chars().next() {
+        let rest = text[start + first.len_utf8()..].to_lowercase();
+        text.truncate(start + first.len_utf8());
+        text.push_str(&rest);
+    }
+}
+
+/// Whether `c` ends a word for capitalization; apostrophes don't, so
+/// "don't" capitalizes as "Don't"
+fn separates_words(c: char) -> bool {
+    c.is_whitespace()
+        || (c.is_ascii_punctuation() && c != '\'')
+        || (('\u{2000}'..='\u{206f}').contains(&c) && c != '\u{2019}')
+        || ('\u{3000}'..='\u{303f}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(text: &str, transform: TextTransform, tag: &str) -> String {
+        let language = Language {
+            tag: tag.to_string(),
+        };
+        TransformedText::new(text, transform, &language).text
+    }
+
+    #[test]
+    fn test_uppercase_and_lowercase() {
+        assert_eq!(
+            transform("Hello, World", TextTransform::Uppercase, "en"),
+            "HELLO, WORLD"
+        );
+        assert_eq!(
+            transform("Hello, World", TextTransform::Lowercase, "en"),
+            "hello, world"
+        );
+        assert_eq!(
+            transform("straße", TextTransform::Uppercase, "de"),
+            "STRASSE"
+        );
+        assert_eq!(transform("Hello", TextTransform::None, "en"), "Hello");
+    }
+
+    #[test]
+    fn test_turkic_dotted_and_dotless_i() {
+        assert_eq!(
+            transform("istanbul", TextTransform::Uppercase, "tr"),
+            "İSTANBUL"
+        );
+        assert_eq!(
+            transform("DİYARBAKIR", TextTransform::Lowercase, "tr"),
+            "diyarbakır"
+        );
+        assert_eq!(transform("izmir", TextTransform::Capitalize, "az"), "İzmir");
+        assert_eq!(
+            transform("istanbul", TextTransform::Uppercase, "en"),
+            "ISTANBUL"
+        );
+        assert_eq!(
+            transform("DIYARBAKIR", TextTransform::Lowercase, "en"),
+            "diyarbakir"
+        );
+    }
+
+    #[test]
+    fn test_lowercase_final_sigma() {
+        assert_eq!(
+            transform("ΟΔΟΣ ΣΟΦΙΑΣ", TextTransform::Lowercase, "el"),
+            "οδο\u{3c2} σοφια\u{3c2}"
+        );
+    }
+
+    #[test]
+    fn test_capitalize_words() {
+        assert_eq!(
+            transform("don't stop-me (now) ßig", TextTransform::Capitalize, "en"),
+            "Don't Stop-Me (Now) Ssig"
+        );
+        assert_eq!(
+            transform("\u{1c6}ungla", TextTransform::Capitalize, "hr"),
+            "\u{1c5}ungla"
+        );
+        assert_eq!(
+            transform("e\u{301}te\u{301}", TextTransform::Capitalize, "fr"),
+            "E\u{301}te\u{301}"
+        );
+    }
+
+    #[test]
+    fn test_format_placeholders_map_back_to_source() {
+        let transformed = TransformedText::with_format_placeholders("a\u{200d}b\u{2067}c");
+        assert_eq!(transformed.text(), "a\u{2b1a}b\u{2b1a}c");
+        assert_eq!(transformed.source_offset(1), 1);
+        assert_eq!(transformed.source_offset(4), 4);
+        assert_eq!(transformed.source_offset(5), 5);
+        assert_eq!(transformed.source_offset(8), 8);
+        assert!(!is_format_character('\u{600}'));
+    }
+
+    #[test]
+    fn test_offsets_map_back_to_source() {
+        let language = Language {
+            tag: "de".to_string(),
+        };
+        let transformed = TransformedText::new("aßc", TextTransform::Uppercase, &language);
+        assert_eq!(transformed.text(), "ASSC");
+        let sources: Vec<usize> = (0..=4).map(|i| transformed.source_offset(i)).collect();
+        assert_eq!(sources, [0, 1, 1, 3, 4]);
+        assert_eq!(transformed.transformed_offset(1), 1);
+        assert_eq!(transformed.transformed_offset(3), 3);
+        assert_eq!(transformed.transformed_offset(4), 4);
+    }
+}
@@ -1,40 +1,64 @@
 //! Common types for text_shaper
 
+use font_types::types::{FontId, GlyphId};
 use std::collections::HashMap;
 use thiserror::Error;
 
-/// Unicode script identifier
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Script {
-    /// Latin script
-    Latin,
-    /// Arabic script
-    Arabic,
-    /// Hebrew script
-    Hebrew,
-    /// Cyrillic script
-    Cyrillic,
-    /// Greek script
-    Greek,
-    /// Han (Chinese) script
-    Han,
-    /// Hangul (Korean) script
-    Hangul,
-    /// Hiragana (Japanese) script
-    Hiragana,
-    /// Katakana (Japanese) script
-    Katakana,
-    /// Common script (shared characters)
-    Common,
-}
+pub use font_types::types::{MatchContext, Script};
 
 /// Language identifier with BCP 47 tag
+///
+/// An empty tag or `und` leaves the language unspecified, to be inferred
+/// from the script and the shaper's locale. Use [`Language::parse`] to
+/// validate and normalize a tag.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Language {
     /// BCP 47 language tag (e.g., "en-US", "fr-FR")
     pub tag: String,
 }
 
+/// Capital letter glyph variants (CSS `font-variant-caps`)
+///
+/// Uses the font's OpenType features when available. Small caps are
+/// otherwise synthesized by shaping uppercase glyphs at a reduced size;
+/// petite caps fall back to small caps and titling caps to normal glyphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FontVariantCaps {
+    /// Normal glyphs
+    #[default]
+    Normal,
+    /// Lowercase letters as small capitals (`smcp`)
+    SmallCaps,
+    /// All letters as small capitals (`smcp` and `c2sc`)
+    AllSmallCaps,
+    /// Lowercase letters as petite capitals (`pcap`)
+    PetiteCaps,
+    /// All letters as petite capitals (`pcap` and `c2pc`)
+    AllPetiteCaps,
+    /// Uppercase letters as small capitals, lowercase unchanged (`unic`)
+    Unicase,
+    /// Capitals designed for titles (`titl`)
+    TitlingCaps,
+}
+
+/// Case transform applied to the text before shaping (CSS `text-transform`)
+///
+/// Line breaks are found in the untransformed text; the shaped glyphs'
+/// clusters are byte offsets into it. Casing follows the language, so
+/// Turkic languages map `i` to `İ` and `I` to `ı`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextTransform {
+    /// Text shaped as written
+    #[default]
+    None,
+    /// All characters uppercased
+    Uppercase,
+    /// All characters lowercased
+    Lowercase,
+    /// First letter of each word titlecased
+    Capitalize,
+}
+
 /// Text shaping errors
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum ShapingError {
@@ -49,6 +73,104 @@ pub enum ShapingError {
     /// Unsupported script
     #[error("Unsupported script: {0}")]
     UnsupportedScript(String),
+
+    /// Malformed BCP 47 language tag
+    #[error("Invalid language tag: {0}")]
+    InvalidLanguage(String),
+}
+
+/// Cluster that no font in a fallback chain covers
+///
+/// The cluster is shaped as `.notdef` (glyph 0), which renders as a box
+/// ("tofu"). Embedders can log these or fetch a font for the character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingGlyph {
+    /// Byte offset of the cluster in the text
+    pub cluster: u32,
+    /// First character of the cluster
+    pub character: char,
+    /// Fonts tried for the cluster, in fallback order
+    pub attempted_fonts: Vec<FontId>,
+}
+
+/// Tatweel glyph of a font, inserted to justify Arabic text with kashida
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KashidaGlyph {
+    /// Font the glyph belongs to; kashida only joins letters of this font
+    pub font_id: FontId,
+    /// Glyph of U+0640 ARABIC TATWEEL
+    pub glyph_id: GlyphId,
+    /// Horizontal advance of one tatweel in pixels
+    pub advance: f32,
+}
+
+/// How format characters are shaped
+///
+/// Format characters have no appearance of their own: bidi controls (LRM,
+/// RLM, the embeddings, overrides and isolates such as FSI and PDI), ZWJ
+/// and ZWNJ, soft hyphens, zero-width spaces, word joiners and byte order
+/// marks. They still take part in shaping (joining, ligatures) and layout
+/// (bidi, line breaks) either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FormatCharacters {
+    /// Shaped with no advance; glyphs a font lacks for them are dropped
+    /// rather than drawn as `.notdef`
+    #[default]
+    Hidden,
+    /// Each shown as [`FORMAT_PLACEHOLDER`], for editors showing
+    /// invisible characters
+    Visible,
+}
+
+/// U+2B1A DOTTED SQUARE, shown in place of each format character with
+/// [`FormatCharacters::Visible`]
+pub const FORMAT_PLACEHOLDER: char = '\u{2b1a}';
+
+/// Hyphen glyph of a font, drawn at the end of a line broken at a soft
+/// hyphen
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HyphenGlyph {
+    /// Font the glyph belongs to
+    pub font_id: FontId,
+    /// Glyph of U+2010 HYPHEN, or of U+002D HYPHEN-MINUS
+    pub glyph_id: GlyphId,
+    /// Horizontal advance in pixels
+    pub advance: f32,
+}
+
+/// U+00AD SOFT HYPHEN: shaped as a zero-width glyph, and drawn as a hyphen
+/// only where a line breaks at it
+pub const SOFT_HYPHEN: char = '\u{ad}';
+
+/// U+200B ZERO WIDTH SPACE: shaped as a zero-width glyph, marking a break
+/// opportunity that is never drawn
+pub const ZERO_WIDTH_SPACE: char = '\u{200b}';
+
+/// OpenType feature applied to part of the text
+///
+/// Corresponds to `font-feature-settings` on a sub-span, e.g. tabular
+/// numbers inside a table cell run.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FeatureRange {
+    /// Feature tag (e.g. "tnum")
+    pub tag: String,
+    /// Feature value (0 disables, 1 enables, higher values select alternates)
+    pub value: u32,
+    /// Byte range of the shaped text the feature applies to
+    pub range: std::ops::Range<usize>,
+}
+
+/// Replacement of a byte range of text, as made by an editor
+///
+/// Deletions insert nothing, insertions replace an empty range.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextEdit {
+    /// Byte range of the previous text that was replaced
+    pub range: std::ops::Range<usize>,
+    /// Length in bytes of the text inserted at `range.start`
+    pub inserted_len: usize,
 }
 
 /// Options for text shaping
@@ -77,29 +199,258 @@ pub struct ShapingOptions {
 
     /// Additional word spacing (in pixels)
     pub word_spacing: f32,
+
+    /// Capital letter variants
+    pub variant_caps: FontVariantCaps,
+
+    /// OpenType features scoped to byte ranges of the text, overriding
+    /// `features` where they overlap
+    pub feature_ranges: Vec<FeatureRange>,
+
+    /// Synthesize small caps the font has no glyphs for by scaling
+    /// capitals (CSS `font-synthesis-small-caps`); when off, those letters
+    /// are shaped unchanged
+    pub synthesize_small_caps: bool,
+
+    /// Who the text is shaped for; untrusted callers only fall back to the
+    /// fonts the registry's visibility policy allows
+    pub match_context: MatchContext,
+
+    /// Case transform applied before shaping
+    pub text_transform: TextTransform,
+
+    /// Whether format characters are hidden or shown as placeholders
+    pub format_characters: FormatCharacters,
+}
+
+/// Version of the [`ShapingOptions::cache_key`] encoding, its first byte
+///
+/// Bumped whenever the encoding changes, so keys persisted by an older
+/// version never match.
+pub const SHAPING_OPTIONS_KEY_VERSION: u8 = 1;
+
+/// Spacing units per pixel in cache keys (26.6 fixed point)
+const SPACING_KEY_SCALE: f32 = 64.0;
+
+impl ShapingOptions {
+    /// Encoding of every option that affects shaping results, for cache
+    /// keys
+    ///
+    /// Options that differ in shaping give different keys: features are
+    /// sorted by tag, strings and lists are length-prefixed, and spacings
+    /// are rounded to 1/64 px so that equal spacings (including `0.0` and
+    /// `-0.0`) share a key. `match_context` is left out, as it only affects
+    /// font selection. The first byte is [`SHAPING_OPTIONS_KEY_VERSION`].
+    pub fn cache_key(&self) -> Vec<u8> {
+        // Destructured so that a new option can't be left out of the key
+        let ShapingOptions {
+            script,
+            language,
+            direction,
+            features,
+            kerning,
+            ligatures,
+            letter_spacing,
+            word_spacing,
+            variant_caps,
+            feature_ranges,
+            synthesize_small_caps,
+            match_context: _,
+            text_transform,
+            format_characters,
+        } = self;
+
+        let mut key = vec![SHAPING_OPTIONS_KEY_VERSION];
+        let put_str = |key: &mut Vec<u8>, value: &str| {
+            key.extend_from_slice(&(value.len() as u64).to_le_bytes());
+            key.extend_from_slice(value.as_bytes());
+        };
+        let put_spacing = |key: &mut Vec<u8>, spacing: f32| {
+            let fixed = (spacing * SPACING_KEY_SCALE).round() as i32;
+            key.extend_from_slice(&fixed.to_le_bytes());
+        };
+
+        key.push(*script as u8);
+        put_str(&mut key, &language.tag);
+        key.push(*direction as u8);
+        let mut features: Vec<_> = features.iter().collect();
+        features.sort_unstable();
+        key.extend_from_slice(&(features.len() as u64).to_le_bytes());
+        for (tag, value) in features {
+            put_str(&mut key, tag);
+            key.extend_from_slice(&value.to_le_bytes());
+        }
+        key.push(u8::from(*kerning));
+        key.push(u8::from(*ligatures));
+        put_spacing(&mut key, *letter_spacing);
+        put_spacing(&mut key, *word_spacing);
+        key.push(*variant_caps as u8);
+        key.extend_from_slice(&(feature_ranges.len() as u64).to_le_bytes());
+        for feature in feature_ranges {
+            put_str(&mut key, &feature.tag);
+            key.extend_from_slice(&feature.value.to_le_bytes());
+            key.extend_from_slice(&(feature.range.start as u64).to_le_bytes());
+            key.extend_from_slice(&(feature.range.end as u64).to_le_bytes());
+        }
+        key.push(u8::from(*synthesize_small_caps));
+        key.push(*text_transform as u8);
+        key.push(*format_characters as u8);
+        key
+    }
 }
 
-// Custom Hash implementation for ShapingOptions
-// HashMap doesn't implement Hash, so we convert to sorted vector for hashing
 impl std::hash::Hash for ShapingOptions {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.script.hash(state);
-        self.language.hash(state);
-        // Direction needs to derive Hash in font_types, or we hash its discriminant
-        std::mem::discriminant(&self.direction).hash(state);
-
-        // Hash features as sorted vector
-        let mut features: Vec<_> = self.features.iter().collect();
-        features.sort_by_key(|(k, _)| *k);
-        for (key, value) in features {
-            key.hash(state);
-            value.hash(state);
+        self.cache_key().hash(state);
+    }
+}
+
+impl Default for ShapingOptions {
+    /// Common script, unspecified language, left-to-right, with kerning and
+    /// ligatures on
+    fn default() -> Self {
+        Self {
+            script: Script::Common,
+            language: Language {
+                tag: "und".to_string(),
+            },
+            direction: font_types::types::Direction::LeftToRight,
+            features: HashMap::new(),
+            kerning: true,
+            ligatures: true,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            variant_caps: FontVariantCaps::Normal,
+            feature_ranges: Vec::new(),
+            synthesize_small_caps: true,
+            match_context: MatchContext::Privileged,
+            text_transform: TextTransform::None,
+            format_characters: FormatCharacters::Hidden,
         }
+    }
+}
+
+impl ShapingOptions {
+    /// Start building options from the defaults
+    pub fn builder() -> ShapingOptionsBuilder {
+        ShapingOptionsBuilder::default()
+    }
+}
+
+/// Builder for [`ShapingOptions`]
+///
+/// ```
+/// use text_shaper::{Script, ShapingOptions};
+///
+/// let options = ShapingOptions::builder()
+///     .script(Script::Latin)
+///     .language("en-US")
+///     .feature("tnum", 1)
+///     .letter_spacing(0.5)
+///     .build();
+/// assert_eq!(options.features["tnum"], 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShapingOptionsBuilder {
+    options: ShapingOptions,
+}
+
+impl ShapingOptionsBuilder {
+    /// Set the script
+    pub fn script(mut self, script: Script) -> Self {
+        self.options.script = script;
+        self
+    }
+
+    /// Set the BCP 47 language tag (see [`Language::parse`] to validate it)
+    pub fn language(mut self, tag: impl Into<String>) -> Self {
+        self.options.language = Language { tag: tag.into() };
+        self
+    }
+
+    /// Set the text direction
+    pub fn direction(mut self, direction: font_types::types::Direction) -> Self {
+        self.options.direction = direction;
+        self
+    }
+
+    /// Set an OpenType feature for the whole text (0 disables it)
+    pub fn feature(mut self, tag: impl Into<String>, value: u32) -> Self {
+        self.options.features.insert(tag.into(), value);
+        self
+    }
+
+    /// Set an OpenType feature for a byte range of the text
+    pub fn feature_range(
+        mut self,
+        tag: impl Into<String>,
+        value: u32,
+        range: std::ops::Range<usize>,
+    ) -> Self {
+        self.options.feature_ranges.push(FeatureRange {
+            tag: tag.into(),
+            value,
+            range,
+        });
+        self
+    }
+
+    /// Enable or disable kerning
+    pub fn kerning(mut self, kerning: bool) -> Self {
+        self.options.kerning = kerning;
+        self
+    }
+
+    /// Enable or disable ligatures
+    pub fn ligatures(mut self, ligatures: bool) -> Self {
+        self.options.ligatures = ligatures;
+        self
+    }
+
+    /// Set the additional letter spacing in pixels
+    pub fn letter_spacing(mut self, spacing: f32) -> Self {
+        self.options.letter_spacing = spacing;
+        self
+    }
+
+    /// Set the additional word spacing in pixels
+    pub fn word_spacing(mut self, spacing: f32) -> Self {
+        self.options.word_spacing = spacing;
+        self
+    }
+
+    /// Set the capital letter variants
+    pub fn variant_caps(mut self, caps: FontVariantCaps) -> Self {
+        self.options.variant_caps = caps;
+        self
+    }
+
+    /// Allow or forbid synthesizing small caps the font lacks
+    pub fn synthesize_small_caps(mut self, synthesize: bool) -> Self {
+        self.options.synthesize_small_caps = synthesize;
+        self
+    }
+
+    /// Set who the text is shaped for
+    pub fn match_context(mut self, context: MatchContext) -> Self {
+        self.options.match_context = context;
+        self
+    }
+
+    /// Set the case transform applied before shaping
+    pub fn text_transform(mut self, transform: TextTransform) -> Self {
+        self.options.text_transform = transform;
+        self
+    }
+
+    /// Set whether format characters are hidden or shown as placeholders
+    pub fn format_characters(mut self, format_characters: FormatCharacters) -> Self {
+        self.options.format_characters = format_characters;
+        self
+    }
 
-        self.kerning.hash(state);
-        self.ligatures.hash(state);
-        // Hash floats as their bit representation
-        self.letter_spacing.to_bits().hash(state);
-        self.word_spacing.to_bits().hash(state);
+    /// Finish the options
+    pub fn build(self) -> ShapingOptions {
+        self.options
     }
 }
@@ -1,6 +1,6 @@
 //! Contract compliance tests - verify public API matches contract exactly
 
-use text_shaper::{TextShaper, ShapingOptions, Script, Language, ShapingError};
+use text_shaper::{TextShaper, ShapingOptions, Script, Language, ShapingError, FontVariantCaps};
 use font_registry::FontRegistry;
 use font_types::types::{FontDescriptor, FontWeight, FontStyle, FontStretch, Direction};
 use std::collections::HashMap;
@@ -30,6 +30,8 @@ fn test_shaping_options_exports() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
     };
 }
 
@@ -108,6 +110,8 @@ fn test_shape_text_method_signature() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
     };
 
     // Verify method signature matches contract
@@ -144,6 +148,8 @@ fn test_shape_text_with_fallback_method_signature() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
     };
 
     // Verify method signature matches contract
@@ -172,6 +178,8 @@ fn test_shaping_options_all_fields() {
         ligatures: false,
         letter_spacing: 2.0,
         word_spacing: 3.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
     };
 
     // Verify all fields are accessible
@@ -1,9 +1,14 @@
 //! Contract compliance tests - verify public API matches contract exactly
 
 use font_registry::FontRegistry;
-use font_types::types::{Direction, FontDescriptor, FontStretch, FontStyle, FontWeight};
+use font_types::types::{
+    Direction, FontDescriptor, FontStretch, FontStyle, FontSynthesis, FontWeight,
+};
 use std::collections::HashMap;
-use text_shaper::{Language, Script, ShapingError, ShapingOptions, TextShaper};
+use text_shaper::{
+    FontVariantCaps, FormatCharacters, Language, MatchContext, Script, ShapingError,
+    ShapingOptions, TextShaper, TextTransform,
+};
 
 #[test]
 fn test_contract_textshaper_exports() {
@@ -30,6 +35,12 @@ fn test_contract_shaping_options_exports() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 }
 
@@ -108,6 +119,12 @@ fn test_contract_shape_text_signature() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     // Verify method signature matches contract
@@ -131,6 +148,7 @@ fn test_contract_shape_text_with_fallback_signature() {
         style: FontStyle::Normal,
         stretch: FontStretch::Normal,
         size: 16.0,
+        synthesis: FontSynthesis::ALL,
     };
 
     let options = ShapingOptions {
@@ -144,6 +162,12 @@ fn test_contract_shape_text_with_fallback_signature() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     // Verify method signature matches contract
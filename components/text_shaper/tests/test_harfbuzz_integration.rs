@@ -3,15 +3,39 @@
 //! These tests verify that real text shaping is performed using Harfbuzz,
 //! not just placeholder implementations.
 
-use font_registry::FontRegistry;
-use font_types::types::{Direction, FontDescriptor, FontStretch, FontStyle, FontWeight};
+use font_registry::{DiscoveryConfig, FontRegistry};
+use font_types::memory::{MemoryPressureLevel, MemoryReporter, MemoryTrimmer};
+use font_types::types::{
+    Direction, FontDescriptor, FontId, FontMetricsOverrides, FontStretch, FontStyle, FontSynthesis,
+    FontWeight, ShapedParagraph,
+};
+use font_types::EvictionPolicy;
 use std::collections::HashMap;
-use text_shaper::{Language, Script, ShapingOptions, TextShaper};
+use std::sync::Arc;
+use text_shaper::shaper::ShapingCacheConfig;
+use text_shaper::{
+    FeatureRange, FontVariantCaps, FormatCharacters, ItemizeStage, Language, MatchContext,
+    PipelineStage, Script, ShapeStage, ShapingCache, ShapingError, ShapingOptions, ShapingPipeline,
+    TextAnalysis, TextAnnotation, TextEdit, TextShaper, TextTransform,
+};
+
+/// DejaVu Sans from the workspace test fixtures
+const DEJAVU_SANS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../tests/fixtures/fonts/DejaVuSans.ttf"
+);
+
+/// DejaVu Serif, which unlike DejaVu Sans has no Hebrew
+const DEJAVU_SERIF: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../tests/fixtures/fonts/DejaVuSerif.ttf"
+);
 
 #[test]
 fn test_shape_text_returns_glyphs() {
-    // Given: A font registry with system fonts loaded
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let loaded = registry.load_system_fonts().unwrap_or(0);
 
     // Skip test if no fonts available
@@ -38,6 +62,12 @@ fn test_shape_text_returns_glyphs() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -60,8 +90,9 @@ fn test_shape_text_returns_glyphs() {
 
 #[test]
 fn test_shape_text_glyph_positioning() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let loaded = registry.load_system_fonts().unwrap_or(0);
 
     if loaded == 0 {
@@ -87,6 +118,12 @@ fn test_shape_text_glyph_positioning() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -107,8 +144,9 @@ fn test_shape_text_glyph_positioning() {
 
 #[test]
 fn test_shape_text_with_ligatures() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let loaded = registry.load_system_fonts().unwrap_or(0);
 
     if loaded == 0 {
@@ -137,6 +175,12 @@ fn test_shape_text_with_ligatures() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -152,8 +196,9 @@ fn test_shape_text_with_ligatures() {
 
 #[test]
 fn test_shape_text_with_kerning() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let loaded = registry.load_system_fonts().unwrap_or(0);
 
     if loaded == 0 {
@@ -182,6 +227,12 @@ fn test_shape_text_with_kerning() {
         ligatures: false,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -196,8 +247,9 @@ fn test_shape_text_with_kerning() {
 
 #[test]
 fn test_shape_text_with_fallback_descriptor() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let loaded = registry.load_system_fonts().unwrap_or(0);
 
     if loaded == 0 {
@@ -219,6 +271,7 @@ fn test_shape_text_with_fallback_descriptor() {
         style: FontStyle::Normal,
         stretch: FontStretch::Normal,
         size: 16.0,
+        synthesis: FontSynthesis::ALL,
     };
 
     let options = ShapingOptions {
@@ -232,6 +285,12 @@ fn test_shape_text_with_fallback_descriptor() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text_with_fallback(text, &descriptor, &options);
@@ -246,8 +305,9 @@ fn test_shape_text_with_fallback_descriptor() {
 
 #[test]
 fn test_shape_text_multiple_scripts() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let loaded = registry.load_system_fonts().unwrap_or(0);
 
     if loaded == 0 {
@@ -276,6 +336,12 @@ fn test_shape_text_multiple_scripts() {
             ligatures: true,
             letter_spacing: 0.0,
             word_spacing: 0.0,
+            variant_caps: FontVariantCaps::Normal,
+            feature_ranges: Vec::new(),
+            synthesize_small_caps: true,
+            match_context: MatchContext::Privileged,
+            text_transform: TextTransform::None,
+            format_characters: FormatCharacters::Hidden,
         };
 
         let result = shaper.shape_text(text, 0, 16.0, &options);
@@ -291,8 +357,9 @@ fn test_shape_text_multiple_scripts() {
 
 #[test]
 fn test_shape_text_cluster_indices() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let loaded = registry.load_system_fonts().unwrap_or(0);
 
     if loaded == 0 {
@@ -318,6 +385,12 @@ fn test_shape_text_cluster_indices() {
         ligatures: false,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -329,11 +402,1213 @@ fn test_shape_text_cluster_indices() {
         // Verify glyphs are positioned from left to right
         let mut prev_x = 0.0;
         for glyph in &shaped.glyphs {
-            assert!(
-                glyph.position.x >= prev_x,
-                "Expected glyphs in LTR order"
-            );
+            assert!(glyph.position.x >= prev_x, "Expected glyphs in LTR order");
             prev_x = glyph.position.x;
         }
     }
 }
+
+#[test]
+fn test_shape_text_synthesizes_small_caps() {
+    // Given: DejaVu Sans, which has no smcp feature
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+
+    let mut options = ShapingOptions {
+        script: Script::Latin,
+        language: Language {
+            tag: String::from("en"),
+        },
+        direction: Direction::LeftToRight,
+        features: HashMap::new(),
+        kerning: true,
+        ligatures: true,
+        letter_spacing: 0.0,
+        word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
+    };
+    let upper = shaper.shape_text("AB", font_id, 20.0, &options).unwrap();
+
+    // When: Shaping lowercase text as small caps
+    options.variant_caps = FontVariantCaps::SmallCaps;
+    let small_caps = shaper.shape_text("Ab", font_id, 20.0, &options).unwrap();
+
+    // Then: 'b' becomes a scaled-down capital B and 'A' is unchanged
+    assert_eq!(small_caps.glyphs.len(), 2);
+    assert_eq!(small_caps.glyphs[0].glyph_id, upper.glyphs[0].glyph_id);
+    assert_eq!(small_caps.glyphs[0].scale, 1.0);
+    assert_eq!(small_caps.glyphs[1].glyph_id, upper.glyphs[1].glyph_id);
+    let scale = small_caps.glyphs[1].scale;
+    assert!(scale > 0.5 && scale < 1.0, "unexpected scale {}", scale);
+    assert!((small_caps.glyphs[1].advance.x - upper.glyphs[1].advance.x * scale).abs() < 0.1);
+}
+
+#[test]
+fn test_shape_text_applies_feature_ranges() {
+    // Given: DejaVu Sans, which kerns "AV"
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+
+    let mut options = ShapingOptions {
+        script: Script::Latin,
+        language: Language {
+            tag: String::from("en"),
+        },
+        direction: Direction::LeftToRight,
+        features: HashMap::new(),
+        kerning: true,
+        ligatures: true,
+        letter_spacing: 0.0,
+        word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
+    };
+    let kerned = shaper.shape_text("AVAV", font_id, 20.0, &options).unwrap();
+
+    // When: Disabling kerning for the second pair only
+    options.feature_ranges.push(FeatureRange {
+        tag: String::from("kern"),
+        value: 0,
+        range: 2..4,
+    });
+    let partial = shaper.shape_text("AVAV", font_id, 20.0, &options).unwrap();
+
+    // Then: The first pair stays kerned while the second uses plain advances
+    assert_eq!(partial.glyphs[0].advance.x, kerned.glyphs[0].advance.x);
+    assert!(partial.glyphs[2].advance.x > partial.glyphs[0].advance.x);
+    assert!(partial.width > kerned.width);
+
+    // Feature ranges take part in the cache key
+    let stats = shaper.cache_stats().unwrap();
+    assert_eq!(stats.hits, 0);
+}
+
+#[test]
+fn test_shape_text_top_to_bottom() {
+    // Given: DejaVu Sans, which has no vhea/vmtx so its vertical metrics
+    // are synthesized
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+
+    let options = ShapingOptions {
+        script: Script::Latin,
+        language: Language {
+            tag: String::from("en"),
+        },
+        direction: Direction::TopToBottom,
+        features: HashMap::new(),
+        kerning: true,
+        ligatures: true,
+        letter_spacing: 0.0,
+        word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
+    };
+
+    // When: Shaping a column of text
+    let shaped = shaper.shape_text("AB", font_id, 20.0, &options).unwrap();
+
+    // Then: Glyphs advance down the column by the line height, centered
+    // on its baseline and upright with their baseline at the ascent
+    let metrics = &registry.get_font_face(font_id).unwrap().metrics;
+    let scale = 20.0 / f32::from(metrics.units_per_em);
+    let horizontal = shaper
+        .shape_text(
+            "AB",
+            font_id,
+            20.0,
+            &ShapingOptions {
+                direction: Direction::LeftToRight,
+                ..options.clone()
+            },
+        )
+        .unwrap();
+    assert_eq!(shaped.glyphs.len(), 2);
+    for (glyph, upright) in shaped.glyphs.iter().zip(&horizontal.glyphs) {
+        assert_eq!(glyph.advance.x, 0.0);
+        assert!((glyph.advance.y - shaped.width).abs() < 1e-3);
+        assert!((glyph.offset.x + upright.advance.x / 2.0).abs() < 1e-3);
+        assert!((glyph.offset.y - metrics.ascent * scale).abs() < 1e-3);
+    }
+    assert!((shaped.width - (metrics.ascent - metrics.descent) * scale).abs() < 1e-3);
+    assert!(shaped.glyphs[1].position.y > shaped.glyphs[0].position.y);
+    let total: f32 = shaped.glyphs.iter().map(|g| g.advance.y).sum();
+    assert!((shaped.height - total).abs() < 1e-3);
+    assert!((shaped.baseline - shaped.width / 2.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_memory_report_follows_shaping_cache() {
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+    assert_eq!(shaper.memory_report().bytes, 0);
+
+    let options = ShapingOptions {
+        script: Script::Latin,
+        language: Language {
+            tag: String::from("en"),
+        },
+        direction: Direction::LeftToRight,
+        features: HashMap::new(),
+        kerning: true,
+        ligatures: true,
+        letter_spacing: 0.0,
+        word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
+    };
+    shaper.shape_text("Hello", font_id, 16.0, &options).unwrap();
+    let one = shaper.memory_report();
+    assert_eq!(one.entries, 1);
+    assert!(one.bytes > 5 * std::mem::size_of::<font_types::PositionedGlyph>());
+
+    // A cache hit adds nothing; a new text adds an entry
+    shaper.shape_text("Hello", font_id, 16.0, &options).unwrap();
+    assert_eq!(shaper.memory_report(), one);
+    shaper
+        .shape_text("World!", font_id, 16.0, &options)
+        .unwrap();
+    assert!(shaper.memory_report().bytes > one.bytes);
+
+    shaper.clear_cache();
+    assert_eq!(shaper.memory_report().bytes, 0);
+}
+
+#[test]
+fn test_trim_memory_evicts_shaping_results() {
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let mut shaper = TextShaper::new(&registry);
+    let options = ShapingOptions {
+        script: Script::Latin,
+        language: Language {
+            tag: String::from("en"),
+        },
+        direction: Direction::LeftToRight,
+        features: HashMap::new(),
+        kerning: true,
+        ligatures: true,
+        letter_spacing: 0.0,
+        word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
+    };
+    for text in ["one", "two", "three", "four"] {
+        shaper.shape_text(text, font_id, 16.0, &options).unwrap();
+    }
+
+    let before = shaper.memory_report().bytes;
+    let freed = shaper.trim_memory(MemoryPressureLevel::Moderate);
+    assert_eq!(shaper.memory_report().entries, 2);
+    assert_eq!(shaper.memory_report().bytes, before - freed);
+
+    // The most recent results survive
+    shaper.shape_text("four", font_id, 16.0, &options).unwrap();
+    assert_eq!(shaper.cache_stats().unwrap().hits, 1);
+
+    shaper.trim_memory(MemoryPressureLevel::Critical);
+    assert_eq!(shaper.memory_report().bytes, 0);
+}
+
+fn options_in(language: &str) -> ShapingOptions {
+    ShapingOptions {
+        script: Script::Latin,
+        language: Language {
+            tag: String::from(language),
+        },
+        direction: Direction::LeftToRight,
+        features: HashMap::new(),
+        kerning: true,
+        ligatures: true,
+        letter_spacing: 0.0,
+        word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
+    }
+}
+
+#[test]
+fn test_segmented_lru_cache_keeps_reused_text_through_scans() {
+    // Given: A small segmented LRU cache holding two texts shaped twice
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let config = ShapingCacheConfig {
+        max_entries: 8,
+        enable_statistics: true,
+        eviction_policy: EvictionPolicy::SegmentedLru,
+        simple_shaping: true,
+        verify_keys: false,
+    };
+    let shaper = TextShaper::with_config(&registry, config);
+    let options = options_in("en");
+    for text in ["Menu", "Search", "Menu", "Search"] {
+        shaper.shape_text(text, font_id, 16.0, &options).unwrap();
+    }
+
+    // When: A page of one-off words passes through the cache
+    for index in 0..32 {
+        let word = format!("word{index}");
+        shaper.shape_text(&word, font_id, 16.0, &options).unwrap();
+    }
+
+    // Then: The reused texts are still cached
+    let before = shaper.cache_stats().unwrap();
+    shaper.shape_text("Menu", font_id, 16.0, &options).unwrap();
+    shaper
+        .shape_text("Search", font_id, 16.0, &options)
+        .unwrap();
+    let after = shaper.cache_stats().unwrap();
+    assert_eq!(after.hits, before.hits + 2);
+    assert_eq!(
+        shaper.shared_cache().unwrap().policy(),
+        EvictionPolicy::SegmentedLru
+    );
+}
+
+#[test]
+fn test_simple_shaping_matches_full_shaping() {
+    // Given: Shapers with and without the simple shaping path
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let simple = TextShaper::new(&registry);
+    let full = TextShaper::with_config(
+        &registry,
+        ShapingCacheConfig {
+            simple_shaping: false,
+            ..ShapingCacheConfig::default()
+        },
+    );
+    assert!(simple.shared_cache().unwrap().simple_shaping());
+    assert!(!full.shared_cache().unwrap().simple_shaping());
+
+    // When: Shaping kerned ASCII, ligatures and non-ASCII text with both
+    let mut options = options_in("en");
+    options.letter_spacing = 0.5;
+    for text in [
+        "AVATAR WAVE, To Tyler.",
+        "The office affair",
+        "na\u{ef}ve caf\u{e9}",
+    ] {
+        let a = simple.shape_text(text, font_id, 16.0, &options).unwrap();
+        let b = full.shape_text(text, font_id, 16.0, &options).unwrap();
+
+        // Then: The results are identical
+        assert_eq!(a.width, b.width, "{:?}", text);
+        assert_eq!(a.glyphs.len(), b.glyphs.len(), "{:?}", text);
+        for (x, y) in a.glyphs.iter().zip(&b.glyphs) {
+            assert_eq!(x.glyph_id, y.glyph_id, "{:?}", text);
+            assert_eq!(x.cluster, y.cluster, "{:?}", text);
+            assert_eq!(x.unsafe_to_break, y.unsafe_to_break, "{:?}", text);
+            assert_eq!(x.position, y.position, "{:?}", text);
+            assert_eq!(x.advance, y.advance, "{:?}", text);
+        }
+    }
+}
+
+#[test]
+fn test_shape_text_rejects_malformed_language() {
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+
+    let result = shaper.shape_text("Hello", font_id, 16.0, &options_in("en--US"));
+
+    assert!(matches!(
+        result,
+        Err(ShapingError::InvalidLanguage(tag)) if tag == "en--US"
+    ));
+}
+
+#[test]
+fn test_shape_text_infers_language_from_locale() {
+    // Given: A shaper with a Turkish locale
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let mut shaper = TextShaper::new(&registry);
+    shaper.set_locale(Some(Language::parse("tr_TR").unwrap()));
+    assert_eq!(shaper.locale().unwrap().tag, "tr-TR");
+
+    // When: Shaping Latin text with an unspecified language and with "tr-tr"
+    let inferred = shaper
+        .shape_text("fit", font_id, 16.0, &options_in(""))
+        .unwrap();
+    let explicit = shaper
+        .shape_text("fit", font_id, 16.0, &options_in("tr-tr"))
+        .unwrap();
+
+    // Then: Both resolve to the same normalized language and share a cache entry
+    assert_eq!(inferred.glyphs.len(), explicit.glyphs.len());
+    assert_eq!(shaper.cache_stats().unwrap().hits, 1);
+}
+
+#[test]
+fn test_turkish_keeps_dotted_i_out_of_fi_ligature() {
+    // Given: DejaVu Sans, whose "fi" ligature has no Turkish localized form
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+
+    // When: Shaping "fi" in English and in Turkish
+    let english = shaper
+        .shape_text("fi", font_id, 16.0, &options_in("en"))
+        .unwrap();
+    let turkish = shaper
+        .shape_text("fi", font_id, 16.0, &options_in("tr"))
+        .unwrap();
+
+    // Then: Only English forms the ligature
+    assert_eq!(english.glyphs.len(), 1);
+    assert_eq!(turkish.glyphs.len(), 2);
+}
+
+#[test]
+fn test_text_transform_shapes_cased_text_with_source_clusters() {
+    // Given: DejaVu Sans
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+    let glyph_ids = |shaped: &font_types::ShapedText| -> Vec<u32> {
+        shaped
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.glyph_id.id)
+            .collect()
+    };
+
+    // When: Uppercasing "straße", whose "ß" becomes "SS"
+    let options = ShapingOptions {
+        text_transform: TextTransform::Uppercase,
+        ..options_in("de")
+    };
+    let transformed = shaper
+        .shape_text("stra\u{df}e", font_id, 16.0, &options)
+        .unwrap();
+    let upper = shaper
+        .shape_text("STRASSE", font_id, 16.0, &options_in("de"))
+        .unwrap();
+
+    // Then: It shapes like the uppercase text, with clusters in the source
+    assert_eq!(glyph_ids(&transformed), glyph_ids(&upper));
+    let clusters: Vec<u32> = transformed.glyphs.iter().map(|g| g.cluster).collect();
+    assert_eq!(clusters, [0, 1, 2, 3, 4, 4, 6]);
+
+    // And: Turkish uppercases "i" to the dotted capital
+    let options = ShapingOptions {
+        text_transform: TextTransform::Uppercase,
+        ..options_in("tr")
+    };
+    let turkish = shaper.shape_text("i", font_id, 16.0, &options).unwrap();
+    let dotted = shaper
+        .shape_text("\u{130}", font_id, 16.0, &options_in("tr"))
+        .unwrap();
+    assert_eq!(glyph_ids(&turkish), glyph_ids(&dotted));
+}
+
+#[test]
+fn test_shared_cache_across_threads() {
+    // Given: One shaping cache shared by shapers on several worker threads
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let cache = Arc::new(ShapingCache::new(1000));
+    assert_eq!(cache.stats().max_size, 1000);
+
+    // When: Each worker shapes the same paragraph
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            let shaper = TextShaper::with_shared_cache(&registry, Arc::clone(&cache));
+            scope.spawn(move || {
+                for word in ["shared", "between", "workers"] {
+                    shaper
+                        .shape_text(word, font_id, 16.0, &options_in("en"))
+                        .unwrap();
+                }
+            });
+        }
+    });
+
+    // Then: Every word is cached once and all lookups are counted
+    let stats = cache.stats();
+    assert_eq!(stats.current_size, 3);
+    assert_eq!(stats.hits + stats.misses, 12);
+
+    let shaper = TextShaper::with_shared_cache(&registry, Arc::clone(&cache));
+    shaper
+        .shape_text("workers", font_id, 16.0, &options_in("en"))
+        .unwrap();
+    assert_eq!(shaper.cache_stats().unwrap().hits, stats.hits + 1);
+    assert!(Arc::ptr_eq(&shaper.shared_cache().unwrap(), &cache));
+    assert_eq!(shaper.cache_config().max_entries, 1000);
+}
+
+#[test]
+fn test_trim_memory_evicts_least_recent_across_shards() {
+    // Given: A sharded cache holding eight results
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let mut shaper = TextShaper::new(&registry);
+    let words = ["a", "b", "c", "d", "e", "f", "g", "h"];
+    for word in words {
+        shaper
+            .shape_text(word, font_id, 16.0, &options_in("en"))
+            .unwrap();
+    }
+
+    // When: Trimming half of them
+    shaper.trim_memory(MemoryPressureLevel::Moderate);
+
+    // Then: The four most recently used results remain, wherever they are stored
+    assert_eq!(shaper.memory_report().entries, 4);
+    for word in &words[4..] {
+        shaper
+            .shape_text(word, font_id, 16.0, &options_in("en"))
+            .unwrap();
+    }
+    assert_eq!(shaper.cache_stats().unwrap().hits, 4);
+}
+
+#[test]
+fn test_shape_text_packed_shares_cached_glyphs() {
+    // Given: A long paragraph shaped once
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+    let text = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+    let first = shaper
+        .shape_text_packed(&text, font_id, 16.0, &options_in("en"))
+        .unwrap();
+
+    // When: Shaping it again, packed and unpacked
+    let second = shaper
+        .shape_text_packed(&text, font_id, 16.0, &options_in("en"))
+        .unwrap();
+    let unpacked = shaper
+        .shape_text(&text, font_id, 16.0, &options_in("en"))
+        .unwrap();
+
+    // Then: The packed hit shares the cached entry, and both forms agree
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(unpacked.glyphs.len(), first.len());
+    for (glyph, packed) in unpacked.glyphs.iter().zip(first.iter()) {
+        assert_eq!(glyph.glyph_id, packed.glyph_id);
+        assert_eq!(glyph.position, packed.position);
+        assert_eq!(glyph.advance, packed.advance);
+    }
+    assert_eq!(unpacked.width, first.width);
+
+    // The cache holds fewer bytes than the expanded glyphs
+    let glyph_bytes = unpacked.glyphs.len() * std::mem::size_of::<font_types::PositionedGlyph>();
+    assert!(shaper.memory_report().bytes < glyph_bytes / 2);
+}
+
+#[test]
+fn test_cache_keys_distinguish_case_and_verify_text() {
+    // Given: A shaper whose cache keeps texts to verify hits
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let config = ShapingCacheConfig {
+        verify_keys: true,
+        ..ShapingCacheConfig::default()
+    };
+    let shaper = TextShaper::with_config(&registry, config);
+    let options = options_in("en");
+
+    // When: Shaping texts differing only in case, then one again
+    let lower = shaper.shape_text("menu", font_id, 16.0, &options).unwrap();
+    let upper = shaper.shape_text("MENU", font_id, 16.0, &options).unwrap();
+    let again = shaper.shape_text("menu", font_id, 16.0, &options).unwrap();
+
+    // Then: Each case has its own entry, and the repeat is a verified hit
+    assert_ne!(lower.glyphs[0].glyph_id, upper.glyphs[0].glyph_id);
+    assert_eq!(again.glyphs[0].glyph_id, lower.glyphs[0].glyph_id);
+    let stats = shaper.cache_stats().unwrap();
+    assert_eq!((stats.hits, stats.misses, stats.current_size), (1, 2, 2));
+    assert!(shaper.shared_cache().unwrap().verify_keys());
+}
+
+#[test]
+fn test_metric_overrides_scale_glyphs_and_line_metrics() {
+    // Given: The same font loaded twice, one face with web font overrides
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    registry.set_deduplication(false);
+    let plain_id = registry.load_font_data(data.clone()).unwrap();
+    let adjusted_id = registry.load_font_data(data).unwrap();
+    let overrides = FontMetricsOverrides {
+        size_adjust: 0.5,
+        ascent: Some(0.8),
+        descent: Some(0.2),
+        line_gap: None,
+    };
+    registry
+        .set_metric_overrides(adjusted_id, overrides)
+        .unwrap();
+    let shaper = TextShaper::new(&registry);
+    let options = options_in("en");
+
+    // When: Shaping the same text with both faces
+    let plain = shaper
+        .shape_text("Hello", plain_id, 20.0, &options)
+        .unwrap();
+    let adjusted = shaper
+        .shape_text("Hello", adjusted_id, 20.0, &options)
+        .unwrap();
+
+    // Then: Glyphs are half as large, and lines use the overridden metrics
+    assert!((adjusted.width - plain.width / 2.0).abs() < 0.01);
+    assert!(adjusted.glyphs.iter().all(|glyph| glyph.scale == 0.5));
+    assert!((adjusted.baseline - 8.0).abs() < 1e-4);
+    assert!((adjusted.height - 10.0).abs() < 1e-4);
+}
+
+/// Reshape `old` after replacing `range` with `inserted`, and check the
+/// result against shaping the edited text from scratch
+fn assert_reshape_matches(
+    old: &str,
+    range: std::ops::Range<usize>,
+    inserted: &str,
+    options: &ShapingOptions,
+) {
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+    let fresh = TextShaper::new(&registry);
+
+    let text = format!("{}{}{}", &old[..range.start], inserted, &old[range.end..]);
+    let edit = TextEdit {
+        range,
+        inserted_len: inserted.len(),
+    };
+    let previous = shaper.shape_text(old, font_id, 16.0, options).unwrap();
+    let incremental = shaper
+        .reshape_edit(&previous, &edit, &text, font_id, 16.0, options)
+        .unwrap();
+    let full = fresh.shape_text(&text, font_id, 16.0, options).unwrap();
+
+    assert_eq!(incremental.glyphs.len(), full.glyphs.len(), "{text:?}");
+    for (i, (a, b)) in incremental.glyphs.iter().zip(&full.glyphs).enumerate() {
+        assert_eq!(a.glyph_id, b.glyph_id, "glyph {i} of {text:?}");
+        assert_eq!(a.cluster, b.cluster, "cluster {i} of {text:?}");
+        assert_eq!(a.position, b.position, "position {i} of {text:?}");
+        assert_eq!(a.advance, b.advance, "advance {i} of {text:?}");
+        assert_eq!(a.unsafe_to_break, b.unsafe_to_break, "flag {i} of {text:?}");
+    }
+    assert_eq!(incremental.width, full.width);
+    assert_eq!(incremental.height, full.height);
+
+    // The result is cached for the edited text
+    let cached = shaper.shape_text(&text, font_id, 16.0, options).unwrap();
+    assert_eq!(cached.width, full.width);
+}
+
+#[test]
+fn test_reshape_edit_matches_full_shaping() {
+    // Given: Text with ligatures ("fi", "ffi") and kerning pairs ("AV")
+    let old = "Fine fish AVAV waved at the office";
+    let options = options_in("en");
+
+    // When/Then: Insertions, deletions and replacements anywhere in the text
+    // shape the same as the edited text
+    assert_reshape_matches(old, 5..5, "unfit ", &options);
+    assert_reshape_matches(old, 10..15, "", &options);
+    assert_reshape_matches(old, 28..34, "affine", &options);
+    assert_reshape_matches(old, 34..34, " WAVE", &options);
+    assert_reshape_matches(old, 0..0, "A", &options);
+    assert_reshape_matches(old, 6..7, "ı", &options);
+    assert_reshape_matches(old, 12..12, "V", &options);
+    assert_reshape_matches(old, 4..5, "", &options);
+}
+
+#[test]
+fn test_reshape_edit_with_spacing_and_feature_ranges() {
+    let mut options = options_in("en");
+    options.letter_spacing = 1.5;
+    options.feature_ranges.push(FeatureRange {
+        tag: String::from("kern"),
+        value: 0,
+        range: 20..30,
+    });
+
+    assert_reshape_matches("AVAV one two AVAV three AVAV", 9..12, "four", &options);
+    assert_reshape_matches("AVAV one two AVAV three AVAV", 25..25, "A", &options);
+}
+
+#[test]
+fn test_reshape_edit_right_to_left() {
+    let mut options = options_in("ar");
+    options.script = Script::Arabic;
+    options.direction = Direction::RightToLeft;
+    let old = "مرحبا بالعالم الجميل جدا";
+
+    // Insert a letter inside the second word and delete the last word
+    let second_word = old.find("بالعالم").unwrap();
+    assert_reshape_matches(old, second_word + 2..second_word + 2, "ا", &options);
+    let last_space = old.rfind(' ').unwrap();
+    assert_reshape_matches(old, last_space..old.len(), "", &options);
+}
+
+#[test]
+fn test_reshape_edit_rejects_mismatched_edit() {
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+    let previous = shaper
+        .shape_text("one two", font_id, 16.0, &options_in("en"))
+        .unwrap();
+
+    let edit = TextEdit {
+        range: 4..7,
+        inserted_len: 10,
+    };
+    let result = shaper.reshape_edit(
+        &previous,
+        &edit,
+        "one six",
+        font_id,
+        16.0,
+        &options_in("en"),
+    );
+
+    assert!(matches!(result, Err(ShapingError::InvalidText(_))));
+}
+
+#[test]
+fn test_shape_text_with_fallback_reports_missing_glyphs() {
+    // Given: Text mixing Latin, which DejaVu Sans covers, and Han, which it doesn't
+    let data = std::fs::read(DEJAVU_SANS).expect("fixture font");
+    let mut registry = FontRegistry::new();
+    let font_id = registry.load_font_data(data).unwrap();
+    let shaper = TextShaper::new(&registry);
+    let descriptor = FontDescriptor {
+        family: vec![String::from("DejaVu Sans")],
+        ..FontDescriptor::default()
+    };
+
+    // When: Shaping with a fallback report
+    let (shaped, missing) = shaper
+        .shape_text_with_fallback_report("a中b字", &descriptor, &options_in("en"))
+        .unwrap();
+
+    // Then: Each uncovered cluster is reported once with the fonts tried
+    assert_eq!(shaped.glyphs.len(), 4);
+    let reported: Vec<(u32, char)> = missing.iter().map(|m| (m.cluster, m.character)).collect();
+    assert_eq!(reported, vec![(1, '中'), (5, '字')]);
+    assert!(missing.iter().all(|m| m.attempted_fonts == vec![font_id]));
+
+    let (_, covered) = shaper
+        .shape_text_with_fallback_report("ab", &descriptor, &options_in("en"))
+        .unwrap();
+    assert!(covered.is_empty());
+}
+
+#[test]
+fn test_shape_paragraph_splits_runs_at_fallback_fonts() {
+    // Given: DejaVu Serif for the text and DejaVu Sans loaded as a fallback
+    let mut registry = FontRegistry::new();
+    let serif = registry
+        .load_font_data(std::fs::read(DEJAVU_SERIF).expect("fixture font"))
+        .unwrap();
+    let sans = registry
+        .load_font_data(std::fs::read(DEJAVU_SANS).expect("fixture font"))
+        .unwrap();
+    let shaper = TextShaper::new(&registry);
+    let descriptor = FontDescriptor {
+        family: vec![String::from("DejaVu Serif")],
+        ..FontDescriptor::default()
+    };
+
+    // When: Shaping text with Hebrew the serif face doesn't cover
+    let (paragraph, missing) = shaper
+        .shape_paragraph_with_fallback_report("ab\u{5d0}\u{5d1}cd", &descriptor, &options_in("en"))
+        .unwrap();
+
+    // Then: The Hebrew gets a run of its own in the fallback font
+    assert!(missing.is_empty());
+    let runs: Vec<_> = paragraph
+        .runs
+        .iter()
+        .map(|run| (run.font_id, run.text_range.clone()))
+        .collect();
+    assert_eq!(runs, vec![(serif, 0..2), (sans, 2..6), (serif, 6..8)]);
+    for run in &paragraph.runs {
+        assert!(run.glyphs.iter().all(|glyph| glyph.font_id == run.font_id));
+        assert!(run.glyphs.iter().all(|glyph| glyph.glyph_id.id != 0));
+        assert!(run
+            .glyphs
+            .iter()
+            .all(|glyph| run.text_range.contains(&(glyph.cluster as usize))));
+    }
+
+    // Runs follow each other and the flat result keeps per-glyph fonts
+    let last = &paragraph.runs[2].glyphs[0];
+    let expected = paragraph.runs[0].advance() + paragraph.runs[1].advance();
+    assert!((last.position.x - last.offset.x - expected).abs() < 1e-3);
+    let flat = shaper
+        .shape_text_with_fallback("ab\u{5d0}\u{5d1}cd", &descriptor, &options_in("en"))
+        .unwrap();
+    assert_eq!(flat.glyphs.len(), paragraph.glyph_count());
+    assert!((flat.width - paragraph.width).abs() < 1e-3);
+}
+
+#[test]
+fn test_shape_paragraph_reports_run_metrics() {
+    // Given: DejaVu Sans, which has no BASE table
+    let mut registry = FontRegistry::new();
+    registry
+        .load_font_data(std::fs::read(DEJAVU_SANS).expect("fixture font"))
+        .unwrap();
+    let shaper = TextShaper::new(&registry);
+    let descriptor = FontDescriptor {
+        family: vec![String::from("DejaVu Sans")],
+        ..FontDescriptor::default()
+    };
+
+    // When: Shaping a paragraph
+    let paragraph = shaper
+        .shape_paragraph("Hello", &descriptor, &options_in("en"))
+        .unwrap();
+
+    // Then: The run carries the font's extents and no BASE baselines
+    let metrics = &paragraph.runs[0].metrics;
+    assert!((metrics.ascent - paragraph.baseline).abs() < 1e-3);
+    assert!((metrics.ascent + metrics.descent - paragraph.height).abs() < 1e-3);
+    assert!(metrics.baselines.is_empty());
+}
+
+#[test]
+fn test_kashida_glyph_is_the_fonts_tatweel() {
+    // Given: DejaVu Sans, which covers Arabic, and DejaVu Serif, which doesn't
+    let mut registry = FontRegistry::new();
+    let sans = registry
+        .load_font_data(std::fs::read(DEJAVU_SANS).expect("fixture font"))
+        .unwrap();
+    let serif = registry
+        .load_font_data(std::fs::read(DEJAVU_SERIF).expect("fixture font"))
+        .unwrap();
+    let shaper = TextShaper::new(&registry);
+    let options = ShapingOptions::builder()
+        .script(Script::Arabic)
+        .language("ar")
+        .direction(Direction::RightToLeft)
+        .build();
+
+    // When: Looking up the tatweel of each font
+    let kashida = shaper.kashida_glyph(sans, 20.0, &options).unwrap().unwrap();
+
+    // Then: It is the glyph shaping U+0640 gives, and missing without Arabic
+    let tatweel = shaper.shape_text("\u{640}", sans, 20.0, &options).unwrap();
+    assert_eq!(kashida.font_id, sans);
+    assert_eq!(kashida.glyph_id, tatweel.glyphs[0].glyph_id);
+    assert_eq!(kashida.advance, tatweel.width);
+    assert!(shaper
+        .kashida_glyph(serif, 20.0, &options)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_soft_hyphen_and_zero_width_space_are_invisible() {
+    // Given: DejaVu Sans and letter spacing
+    let mut registry = FontRegistry::new();
+    let font_id = registry
+        .load_font_data(std::fs::read(DEJAVU_SANS).expect("fixture font"))
+        .unwrap();
+    let shaper = TextShaper::new(&registry);
+    let options = ShapingOptions {
+        letter_spacing: 2.0,
+        ..options_in("en")
+    };
+
+    // When: Shaping words joined by a soft hyphen and a zero-width space
+    let plain = shaper.shape_text("abcd", font_id, 16.0, &options).unwrap();
+    let text = "ab\u{ad}c\u{200b}d";
+    let shaped = shaper.shape_text(text, font_id, 16.0, &options).unwrap();
+
+    // Then: Both take no space, not even the letter spacing
+    assert_eq!(shaped.glyphs.len(), 6);
+    for cluster in [2, 5] {
+        let glyph = shaped
+            .glyphs
+            .iter()
+            .find(|glyph| glyph.cluster == cluster)
+            .unwrap();
+        assert_eq!(glyph.advance.x, 0.0);
+    }
+    assert!((shaped.width - plain.width).abs() < 0.01);
+}
+
+#[test]
+fn test_hyphen_glyph_is_the_fonts_hyphen() {
+    // Given: DejaVu Sans
+    let mut registry = FontRegistry::new();
+    let font_id = registry
+        .load_font_data(std::fs::read(DEJAVU_SANS).expect("fixture font"))
+        .unwrap();
+    let shaper = TextShaper::new(&registry);
+    let options = options_in("en");
+
+    // When: Looking up the hyphen shown at soft hyphen breaks
+    let hyphen = shaper
+        .hyphen_glyph(font_id, 20.0, &options)
+        .unwrap()
+        .unwrap();
+
+    // Then: It is the visible hyphen U+2010 shapes to
+    let shaped = shaper
+        .shape_text("\u{2010}", font_id, 20.0, &options)
+        .unwrap();
+    assert_eq!(hyphen.font_id, font_id);
+    assert_eq!(hyphen.glyph_id, shaped.glyphs[0].glyph_id);
+    assert_eq!(hyphen.advance, shaped.width);
+    assert!(hyphen.advance > 0.0);
+}
+
+#[test]
+fn test_format_characters_are_hidden_by_default() {
+    // Given: DejaVu Sans
+    let mut registry = FontRegistry::new();
+    let font_id = registry
+        .load_font_data(std::fs::read(DEJAVU_SANS).expect("fixture font"))
+        .unwrap();
+    let shaper = TextShaper::new(&registry);
+    let options = options_in("en");
+
+    // When: Shaping text with bidi controls, a ZWJ and a byte order mark
+    let plain = shaper.shape_text("abcd", font_id, 16.0, &options).unwrap();
+    let text = "a\u{200e}b\u{2066}c\u{2069}\u{200d}\u{feff}d";
+    let shaped = shaper.shape_text(text, font_id, 16.0, &options).unwrap();
+
+    // Then: They take no space and none is drawn as .notdef
+    assert!(shaped.glyphs.iter().all(|glyph| glyph.glyph_id.id != 0));
+    for glyph in &shaped.glyphs {
+        if !text[glyph.cluster as usize..].starts_with(|c: char| c.is_ascii_lowercase()) {
+            assert_eq!(glyph.advance.x, 0.0);
+        }
+    }
+    assert!((shaped.width - plain.width).abs() < 0.01);
+}
+
+#[test]
+fn test_visible_format_characters_show_placeholders() {
+    // Given: DejaVu Sans, showing format characters
+    let mut registry = FontRegistry::new();
+    let font_id = registry
+        .load_font_data(std::fs::read(DEJAVU_SANS).expect("fixture font"))
+        .unwrap();
+    let shaper = TextShaper::new(&registry);
+    let options = ShapingOptions {
+        format_characters: FormatCharacters::Visible,
+        ..options_in("en")
+    };
+
+    // When: Shaping text with a right-to-left mark and an isolate
+    let text = "a\u{200f}b\u{2068}c\u{2069}";
+    let shaped = shaper.shape_text(text, font_id, 16.0, &options).unwrap();
+
+    // Then: Each is a dotted square, with clusters in the source text
+    let placeholder = shaper
+        .shape_text("\u{2b1a}", font_id, 16.0, &options_in("en"))
+        .unwrap();
+    let clusters: Vec<u32> = shaped.glyphs.iter().map(|glyph| glyph.cluster).collect();
+    assert_eq!(clusters, [0, 1, 4, 5, 8, 9]);
+    for index in [1, 3, 5] {
+        let glyph = &shaped.glyphs[index];
+        assert_eq!(glyph.glyph_id, placeholder.glyphs[0].glyph_id);
+        assert_eq!(glyph.advance.x, placeholder.width);
+    }
+
+    // And the case transform composes with the placeholders
+    let upper = ShapingOptions {
+        text_transform: TextTransform::Uppercase,
+        ..options.clone()
+    };
+    let shaped = shaper
+        .shape_text("\u{df}\u{200b}x", font_id, 16.0, &upper)
+        .unwrap();
+    let clusters: Vec<u32> = shaped.glyphs.iter().map(|glyph| glyph.cluster).collect();
+    assert_eq!(clusters, [0, 0, 2, 5]);
+    assert_eq!(shaped.glyphs[2].glyph_id, placeholder.glyphs[0].glyph_id);
+}
+
+/// Registry with DejaVu Serif and DejaVu Sans, returning their IDs
+fn serif_and_sans() -> (FontRegistry, FontId, FontId) {
+    let mut registry = FontRegistry::new();
+    let serif = registry
+        .load_font_data(std::fs::read(DEJAVU_SERIF).expect("fixture font"))
+        .unwrap();
+    let sans = registry
+        .load_font_data(std::fs::read(DEJAVU_SANS).expect("fixture font"))
+        .unwrap();
+    (registry, serif, sans)
+}
+
+fn family(name: &str) -> FontDescriptor {
+    FontDescriptor {
+        family: vec![String::from(name)],
+        ..FontDescriptor::default()
+    }
+}
+
+#[test]
+fn test_default_pipeline_itemizes_resolves_bidi_and_falls_back() {
+    // Given: DejaVu Serif for the text and DejaVu Sans loaded as a fallback
+    let (registry, serif, sans) = serif_and_sans();
+    let shaper = TextShaper::new(&registry);
+    let pipeline = ShapingPipeline::new();
+    assert_eq!(
+        pipeline.stage_names(),
+        ["itemize", "bidi", "shape", "fallback"]
+    );
+
+    // When: Shaping Latin around Hebrew the serif face doesn't cover
+    let text = "ab \u{5d0}\u{5d1}cd";
+    let output = pipeline
+        .run(&shaper, text, &family("DejaVu Serif"), &options_in("en"))
+        .unwrap();
+
+    // Then: The Hebrew is a right-to-left run in the fallback font
+    let runs: Vec<_> = output
+        .paragraph
+        .runs
+        .iter()
+        .map(|run| {
+            (
+                run.text_range.clone(),
+                run.font_id,
+                run.script,
+                run.direction,
+            )
+        })
+        .collect();
+    assert_eq!(
+        runs,
+        vec![
+            (0..3, serif, Script::Latin, Direction::LeftToRight),
+            (3..7, sans, Script::Hebrew, Direction::RightToLeft),
+            (7..9, serif, Script::Latin, Direction::LeftToRight),
+        ]
+    );
+    assert!(output.missing.is_empty());
+    let hebrew: Vec<u32> = output.paragraph.runs[1]
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.cluster)
+        .collect();
+    assert_eq!(hebrew, [5, 3]);
+
+    // And single-script text shapes as shape_paragraph does
+    let descriptor = family("DejaVu Serif");
+    let expected = shaper
+        .shape_paragraph("Hello, world", &descriptor, &options_in("en"))
+        .unwrap();
+    let output = pipeline
+        .run(&shaper, "Hello, world", &descriptor, &options_in("en"))
+        .unwrap();
+    assert_eq!(output.paragraph.runs.len(), 1);
+    let glyphs = |paragraph: &ShapedParagraph| -> Vec<(u32, u32, f32)> {
+        paragraph
+            .glyphs()
+            .map(|glyph| (glyph.glyph_id.id, glyph.cluster, glyph.position.x))
+            .collect()
+    };
+    assert_eq!(glyphs(&output.paragraph), glyphs(&expected));
+    assert_eq!(output.paragraph.runs[0].metrics, expected.runs[0].metrics);
+}
+
+#[test]
+fn test_pipeline_orders_runs_of_right_to_left_paragraphs() {
+    // Given: A right-to-left paragraph with a Latin word
+    let (registry, _, sans) = serif_and_sans();
+    let shaper = TextShaper::new(&registry);
+    let options = ShapingOptions {
+        direction: Direction::RightToLeft,
+        script: Script::Hebrew,
+        ..options_in("he")
+    };
+
+    // When: Running the default pipeline
+    let output = ShapingPipeline::new()
+        .run(
+            &shaper,
+            "\u{5d0}\u{5d1} cd",
+            &family("DejaVu Sans"),
+            &options,
+        )
+        .unwrap();
+
+    // Then: The Latin word is shaped left-to-right, left of the Hebrew
+    let runs: Vec<_> = output
+        .paragraph
+        .runs
+        .iter()
+        .map(|run| (run.text_range.clone(), run.font_id, run.direction))
+        .collect();
+    assert_eq!(
+        runs,
+        vec![
+            (5..7, sans, Direction::LeftToRight),
+            (0..5, sans, Direction::RightToLeft),
+        ]
+    );
+    let latin = &output.paragraph.runs[0];
+    assert_eq!(latin.glyphs[0].cluster, 5);
+    assert!((output.paragraph.runs[1].glyphs[0].position.x - latin.advance()).abs() < 1e-3);
+}
+
+/// Stage annotating every occurrence of a misspelled word
+struct SpellCheck;
+
+impl PipelineStage for SpellCheck {
+    fn name(&self) -> &str {
+        "spellcheck"
+    }
+
+    fn run(&self, _: &TextShaper<'_>, analysis: &mut TextAnalysis<'_>) -> Result<(), ShapingError> {
+        for (start, word) in analysis.text.match_indices("teh") {
+            analysis.annotations.push(TextAnnotation {
+                range: start..start + word.len(),
+                label: String::from("misspelled"),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Stage shaping the characters `chars` accepts with `font_id`, as an
+/// embedder would with its emoji font
+struct CharacterFont {
+    chars: fn(char) -> bool,
+    font_id: FontId,
+}
+
+impl PipelineStage for CharacterFont {
+    fn name(&self) -> &str {
+        "character-font"
+    }
+
+    fn run(&self, _: &TextShaper<'_>, analysis: &mut TextAnalysis<'_>) -> Result<(), ShapingError> {
+        let text = analysis.text;
+        let mut previous = None;
+        for (offset, c) in text.char_indices() {
+            let accepted = (self.chars)(c);
+            if previous.is_some_and(|previous| previous != accepted) {
+                analysis.split_at(offset);
+            }
+            previous = Some(accepted);
+        }
+        for item in &mut analysis.items {
+            if text[item.range.clone()].chars().all(self.chars) {
+                item.font_id = Some(self.font_id);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pipeline_runs_custom_stages() {
+    // Given: A pipeline that spell-checks after itemizing and shapes digits
+    // with DejaVu Serif
+    let (registry, serif, sans) = serif_and_sans();
+    let shaper = TextShaper::new(&registry);
+    let mut pipeline = ShapingPipeline::new();
+    assert!(pipeline.insert_after(ItemizeStage::NAME, SpellCheck));
+    assert!(pipeline.insert_before(
+        ShapeStage::NAME,
+        CharacterFont {
+            chars: |c| c.is_ascii_digit(),
+            font_id: serif,
+        }
+    ));
+    assert!(!pipeline.insert_before("emoji", SpellCheck));
+    assert_eq!(
+        pipeline.stage_names(),
+        [
+            "itemize",
+            "spellcheck",
+            "bidi",
+            "character-font",
+            "shape",
+            "fallback"
+        ]
+    );
+
+    // When: Shaping text with a typo and digits
+    let output = pipeline
+        .run(
+            &shaper,
+            "teh 42 cats",
+            &family("DejaVu Sans"),
+            &options_in("en"),
+        )
+        .unwrap();
+
+    // Then: The typo is annotated and the digits have a run in the serif face
+    assert_eq!(
+        output.annotations,
+        vec![TextAnnotation {
+            range: 0..3,
+            label: String::from("misspelled"),
+        }]
+    );
+    let runs: Vec<_> = output
+        .paragraph
+        .runs
+        .iter()
+        .map(|run| (run.text_range.clone(), run.font_id))
+        .collect();
+    assert_eq!(runs, vec![(0..4, sans), (4..6, serif), (6..11, sans)]);
+
+    // And a pipeline whose items are never shaped fails
+    assert!(pipeline.remove(ShapeStage::NAME));
+    assert!(!pipeline.remove(ShapeStage::NAME));
+    assert!(matches!(
+        pipeline.run(&shaper, "teh", &family("DejaVu Sans"), &options_in("en")),
+        Err(ShapingError::InvalidText(_))
+    ));
+}
@@ -1,9 +1,14 @@
 //! Tests for TextShaper functionality
 
-use font_registry::FontRegistry;
-use font_types::types::{Direction, FontDescriptor, FontStretch, FontStyle, FontWeight};
+use font_registry::{DiscoveryConfig, FontRegistry};
+use font_types::types::{
+    Direction, FontDescriptor, FontStretch, FontStyle, FontSynthesis, FontWeight,
+};
 use std::collections::HashMap;
-use text_shaper::{Language, Script, ShapingError, ShapingOptions, TextShaper};
+use text_shaper::{
+    FontVariantCaps, FormatCharacters, Language, MatchContext, Script, ShapingError,
+    ShapingOptions, TextShaper, TextTransform,
+};
 
 #[test]
 fn test_text_shaper_new() {
@@ -20,8 +25,9 @@ fn test_text_shaper_new() {
 
 #[test]
 fn test_shape_text_basic_latin() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let _ = registry.load_system_fonts();
 
     let shaper = TextShaper::new(&registry);
@@ -42,6 +48,12 @@ fn test_shape_text_basic_latin() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -72,6 +84,12 @@ fn test_shape_text_with_invalid_font_id() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -82,8 +100,9 @@ fn test_shape_text_with_invalid_font_id() {
 
 #[test]
 fn test_shape_text_with_empty_string() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let _ = registry.load_system_fonts();
 
     let shaper = TextShaper::new(&registry);
@@ -104,6 +123,12 @@ fn test_shape_text_with_empty_string() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -114,8 +139,9 @@ fn test_shape_text_with_empty_string() {
 
 #[test]
 fn test_shape_text_with_fallback() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let _ = registry.load_system_fonts();
 
     let shaper = TextShaper::new(&registry);
@@ -128,6 +154,7 @@ fn test_shape_text_with_fallback() {
         style: FontStyle::Normal,
         stretch: FontStretch::Normal,
         size: 16.0,
+        synthesis: FontSynthesis::ALL,
     };
 
     let options = ShapingOptions {
@@ -141,6 +168,12 @@ fn test_shape_text_with_fallback() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text_with_fallback(text, &descriptor, &options);
@@ -151,8 +184,9 @@ fn test_shape_text_with_fallback() {
 
 #[test]
 fn test_shape_text_with_features() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let _ = registry.load_system_fonts();
 
     let shaper = TextShaper::new(&registry);
@@ -177,6 +211,12 @@ fn test_shape_text_with_features() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -187,8 +227,9 @@ fn test_shape_text_with_features() {
 
 #[test]
 fn test_shape_text_rtl() {
-    // Given: A font registry with system fonts
+    // Given: A font registry with the mock platform's bundled fonts
     let mut registry = FontRegistry::new();
+    registry.set_discovery_config(DiscoveryConfig::mock());
     let _ = registry.load_system_fonts();
 
     let shaper = TextShaper::new(&registry);
@@ -209,6 +250,12 @@ fn test_shape_text_rtl() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
+        synthesize_small_caps: true,
+        match_context: MatchContext::Privileged,
+        text_transform: TextTransform::None,
+        format_characters: FormatCharacters::Hidden,
     };
 
     let result = shaper.shape_text(text, font_id, size, &options);
@@ -1,6 +1,6 @@
 //! Unit tests for text_shaper types
 
-use text_shaper::{Script, Language, ShapingError, ShapingOptions};
+use text_shaper::{Script, Language, ShapingError, ShapingOptions, FontVariantCaps};
 use std::collections::HashMap;
 
 #[test]
@@ -68,6 +68,8 @@ fn test_shaping_options_default_construction() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
     };
 
     // Then: All fields should be accessible
@@ -98,6 +100,8 @@ fn test_shaping_options_with_features() {
         ligatures: true,
         letter_spacing: 0.0,
         word_spacing: 0.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
     };
 
     // Then: Features should be accessible
@@ -120,6 +124,8 @@ fn test_shaping_options_with_spacing() {
         ligatures: false,
         letter_spacing: 2.5,
         word_spacing: 5.0,
+        variant_caps: FontVariantCaps::Normal,
+        feature_ranges: Vec::new(),
     };
 
     // Then: Spacing values should be preserved
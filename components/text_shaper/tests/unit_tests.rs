@@ -4,7 +4,10 @@
 mod test_harfbuzz_integration;
 mod test_types {
     use std::collections::HashMap;
-    use text_shaper::{Language, Script, ShapingError, ShapingOptions};
+    use text_shaper::{
+        FeatureRange, FontVariantCaps, FormatCharacters, Language, MatchContext, Script,
+        ShapingError, ShapingOptions, TextTransform,
+    };
 
     #[test]
     fn test_script_variants_exist() {
@@ -71,6 +74,12 @@ mod test_types {
             ligatures: true,
             letter_spacing: 0.0,
             word_spacing: 0.0,
+            variant_caps: FontVariantCaps::Normal,
+            feature_ranges: Vec::new(),
+            synthesize_small_caps: true,
+            match_context: MatchContext::Privileged,
+            text_transform: TextTransform::None,
+            format_characters: FormatCharacters::Hidden,
         };
 
         // Then: All fields should be accessible
@@ -101,6 +110,12 @@ mod test_types {
             ligatures: true,
             letter_spacing: 0.0,
             word_spacing: 0.0,
+            variant_caps: FontVariantCaps::Normal,
+            feature_ranges: Vec::new(),
+            synthesize_small_caps: true,
+            match_context: MatchContext::Privileged,
+            text_transform: TextTransform::None,
+            format_characters: FormatCharacters::Hidden,
         };
 
         // Then: Features should be accessible
@@ -123,6 +138,12 @@ mod test_types {
             ligatures: false,
             letter_spacing: 2.5,
             word_spacing: 5.0,
+            variant_caps: FontVariantCaps::Normal,
+            feature_ranges: Vec::new(),
+            synthesize_small_caps: true,
+            match_context: MatchContext::Privileged,
+            text_transform: TextTransform::None,
+            format_characters: FormatCharacters::Hidden,
         };
 
         // Then: Spacing values should be preserved
@@ -130,4 +151,37 @@ mod test_types {
         assert_eq!(options.word_spacing, 5.0);
         assert!(!options.ligatures);
     }
+
+    #[test]
+    fn test_shaping_options_builder() {
+        // Given: A builder setting a few options
+        let options = ShapingOptions::builder()
+            .script(Script::Latin)
+            .language("en")
+            .feature("liga", 0)
+            .feature_range("tnum", 1, 2..5)
+            .word_spacing(3.0)
+            .build();
+
+        // Then: They are set and the rest are the defaults
+        assert_eq!(options.script, Script::Latin);
+        assert_eq!(options.language.tag, "en");
+        assert_eq!(options.features.get("liga"), Some(&0));
+        assert_eq!(
+            options.feature_ranges,
+            [FeatureRange {
+                tag: "tnum".to_string(),
+                value: 1,
+                range: 2..5,
+            }]
+        );
+        assert_eq!(options.word_spacing, 3.0);
+        assert_eq!(options.letter_spacing, 0.0);
+        assert!(options.kerning && options.ligatures);
+        assert_eq!(options.variant_caps, FontVariantCaps::Normal);
+
+        let defaults = ShapingOptions::default();
+        assert_eq!(defaults.script, Script::Common);
+        assert_eq!(defaults.language.tag, "und");
+    }
 }